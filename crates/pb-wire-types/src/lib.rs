@@ -1,7 +1,9 @@
 //! Wire types for sending between BE<->FE.
 
 /// Media destination for completed downloads.
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[derive(
+    Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash, Default,
+)]
 pub enum Destination {
     #[default]
     Movies,
@@ -9,6 +11,9 @@ pub enum Destination {
 }
 
 impl Destination {
+    /// All destinations.
+    pub const ALL: [Destination; 2] = [Destination::Movies, Destination::Shows];
+
     pub fn label(&self) -> &'static str {
         match self {
             Self::Movies => "Movies",
@@ -58,26 +63,69 @@ pub enum TransmissionStatus {
     Downloading,
     QueuedSeed,
     Seeding,
+    /// A status code this version doesn't know, e.g. from a newer
+    /// Transmission.
+    Unknown(i64),
 }
 
 impl TransmissionStatus {
-    pub fn label(&self) -> &'static str {
+    /// The status for one of Transmission's `status` codes.
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            0 => Self::Stopped,
+            1 => Self::QueuedVerify,
+            2 => Self::Verifying,
+            3 => Self::QueuedDownload,
+            4 => Self::Downloading,
+            5 => Self::QueuedSeed,
+            6 => Self::Seeding,
+            code => Self::Unknown(code),
+        }
+    }
+
+    /// The status closest to one of qBittorrent's torrent `state`s.  Paused,
+    /// errored and missing-files torrents are stopped, and stalled ones
+    /// still count as downloading or seeding.
+    pub fn from_qbittorrent(state: &str) -> Self {
+        match state {
+            "pausedDL" | "pausedUP" | "stoppedDL" | "stoppedUP" | "error" | "missingFiles" => {
+                Self::Stopped
+            }
+            "checkingDL" | "checkingUP" | "checkingResumeData" => Self::Verifying,
+            "queuedDL" => Self::QueuedDownload,
+            "downloading" | "stalledDL" | "forcedDL" | "metaDL" | "forcedMetaDL" | "allocating" => {
+                Self::Downloading
+            }
+            "queuedUP" => Self::QueuedSeed,
+            "uploading" | "stalledUP" | "forcedUP" => Self::Seeding,
+            _ => Self::Unknown(-1),
+        }
+    }
+
+    /// Whether the torrent is being (or is queued to be) verified.
+    pub fn is_verifying(&self) -> bool {
+        matches!(self, Self::QueuedVerify | Self::Verifying)
+    }
+
+    pub fn label(&self) -> String {
         match self {
-            Self::Stopped => "Stopped",
-            Self::QueuedVerify => "Queued (Verify)",
-            Self::Verifying => "Verifying",
-            Self::QueuedDownload => "Queued",
-            Self::Downloading => "Downloading",
-            Self::QueuedSeed => "Queued (Seed)",
-            Self::Seeding => "Seeding",
+            Self::Stopped => "Stopped".into(),
+            Self::QueuedVerify => "Queued (Verify)".into(),
+            Self::Verifying => "Verifying".into(),
+            Self::QueuedDownload => "Queued".into(),
+            Self::Downloading => "Downloading".into(),
+            Self::QueuedSeed => "Queued (Seed)".into(),
+            Self::Seeding => "Seeding".into(),
+            Self::Unknown(code) => format!("Unknown ({code})"),
         }
     }
 }
 
-/// State of the copy operation for a download entry.
+/// State of the copy operation for a download entry.  Why a copy failed is
+/// kept beside it, in [`DownloadEntry::failure`].
 #[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
 pub enum CopyState {
-    /// Not yet copied (waiting for download to complete or dest to be configured).
+    /// Not yet copied (waiting for download to complete).
     #[default]
     NotCopied,
     /// Copy is currently in progress.
@@ -86,6 +134,9 @@ pub enum CopyState {
     Copied,
     /// Copy failed (will be retried on next cycle).
     Failed,
+    /// Downloaded, but no directory is configured for the destination, so
+    /// there is nowhere to copy to.
+    Unconfigured,
 }
 
 impl CopyState {
@@ -93,28 +144,85 @@ impl CopyState {
     pub fn indicator(&self) -> &'static str {
         match self {
             Self::NotCopied => "",
-            Self::Copying => "\u{23F3}", // hourglass
-            Self::Copied => "\u{2705}",  // green check
-            Self::Failed => "\u{274C}",  // red cross
+            Self::Copying => "\u{23F3}",      // hourglass
+            Self::Copied => "\u{2705}",       // green check
+            Self::Failed => "\u{274C}",       // red cross
+            Self::Unconfigured => "\u{2699}", // gear
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NotCopied => "Not copied",
+            Self::Copying => "Copying",
+            Self::Copied => "Copied",
+            Self::Failed => "Failed",
+            Self::Unconfigured => "No destination directory",
+        }
+    }
+
+    /// Whether the copy task should (still) try to copy the download.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::NotCopied | Self::Failed | Self::Unconfigured)
+    }
+
+    /// The state of a download copied to several destinations, from the
+    /// state of each copy: copied only once every copy is, otherwise
+    /// whichever state most needs attention.
+    pub fn combine(states: impl IntoIterator<Item = CopyState>) -> CopyState {
+        let states = states.into_iter().collect::<Vec<_>>();
+        let any = |state| states.contains(&state);
+        if any(Self::Copying) {
+            Self::Copying
+        } else if any(Self::Failed) {
+            Self::Failed
+        } else if any(Self::Unconfigured) {
+            Self::Unconfigured
+        } else if !states.is_empty() && states.iter().all(|s| *s == Self::Copied) {
+            Self::Copied
+        } else {
+            Self::NotCopied
         }
     }
 }
 
 /// A torrent as reported by the Transmission RPC daemon.
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct TransmissionTorrent {
     pub id: i64,
     pub name: String,
-    pub hash_string: String,
+    pub hash_string: InfoHash,
     pub status: TransmissionStatus,
     /// 0.0 to 1.0
     pub percent_done: f64,
+    /// Verification progress while the torrent is being checked, 0.0 to 1.0
+    #[serde(default)]
+    pub recheck_progress: f64,
     /// Bytes per second
     pub rate_download: i64,
     /// Bytes per second
     pub rate_upload: i64,
     /// Seconds remaining, -1 if unknown, -2 if not applicable
     pub eta: i64,
+    /// Uploaded / downloaded, -1 if not available, -2 if infinite
+    #[serde(default)]
+    pub upload_ratio: f64,
+    /// Total bytes uploaded
+    #[serde(default)]
+    pub uploaded_ever: i64,
+    /// Total seconds spent seeding
+    #[serde(default)]
+    pub seconds_seeding: i64,
+    /// When the torrent was added, in seconds since the epoch
+    #[serde(default)]
+    pub added_date: i64,
+    /// When the download completed, in seconds since the epoch, 0 if it
+    /// hasn't
+    #[serde(default)]
+    pub done_date: i64,
+    /// Whether the torrent reached its seed ratio or idle limit
+    #[serde(default)]
+    pub is_finished: bool,
     /// Total size in bytes when download is complete
     pub size_when_done: i64,
     /// Number of peers connected
@@ -123,6 +231,10 @@ pub struct TransmissionTorrent {
     pub peers_sending_to_us: i64,
     /// Number of peers we are sending data to
     pub peers_getting_from_us: i64,
+    /// Downloading with no peers sending to us for longer than the
+    /// configured stall timeout.
+    #[serde(default)]
+    pub stalled: bool,
     /// Error code (0 = OK)
     pub error: i64,
     /// Human-readable error string
@@ -131,165 +243,3898 @@ pub struct TransmissionTorrent {
     pub download_dir: Option<String>,
     /// The destination this torrent is assigned to (from our ledger), if any.
     pub destination: Option<Destination>,
-    /// Copy state for this torrent's files.
+    /// How the destination was assigned, when there is one.
+    #[serde(default)]
+    pub origin: Option<DownloadOrigin>,
+    /// Copy state for this torrent's files, taking every destination it is
+    /// copied to together.
     #[serde(default)]
     pub copy_state: CopyState,
+    /// The copy to each destination, when there is more than one.
+    #[serde(default)]
+    pub targets: Vec<CopyTarget>,
+    /// The ledger's note about the copy, if any.
+    #[serde(default)]
+    pub copy_note: Option<String>,
+    /// The user's own note on the download.
+    #[serde(default)]
+    pub user_note: Option<String>,
+    /// Why the last copy failed, while it is [`CopyState::Failed`].
+    #[serde(default)]
+    pub copy_failure: Option<String>,
+    /// How fast the copy went, when it is [`CopyState::Copied`] and that
+    /// was recorded.
+    #[serde(default)]
+    pub copy_throughput: Option<CopyThroughput>,
+    /// When quiet hours end, if they are holding back this torrent's copy.
+    #[serde(default)]
+    pub copy_deferred_until: Option<TimeOfDay>,
+    /// Finished and [`CopyState::NotCopied`], with nothing holding back its
+    /// copy, so it is only waiting for the copy task's next cycle.
+    #[serde(default)]
+    pub copy_pending: bool,
+    /// The torrent was copied and has seeded up to its destination's
+    /// [`SeedLimits`].
+    #[serde(default)]
+    pub seed_goal_met: bool,
+    /// Why the torrent can't be copied until Settings change, e.g. "Shows
+    /// directory not configured".
+    #[serde(default)]
+    pub copy_blocked_reason: Option<String>,
+    /// Recent samples while downloading, oldest first and at most
+    /// [`RATE_HISTORY_SAMPLES`] of them.
+    #[serde(default)]
+    pub history: Vec<RateSample>,
+    /// Since when no peer has sent any data, by samples kept across
+    /// restarts.  Only tracked while a download in the ledger is
+    /// incomplete.
+    #[serde(default)]
+    pub unavailable_since: Option<i64>,
+    /// No peer has sent any data for longer than
+    /// [`TransmissionConfig::dead_after_hours`].
+    #[serde(default)]
+    pub likely_dead: bool,
+    /// In the ledger, and the client has reported an error for it on every
+    /// poll for a while, e.g. a tracker that no longer knows the torrent.
+    #[serde(default)]
+    pub needs_replacement: bool,
+    /// What to search for to find another copy, while it is likely dead or
+    /// needs replacing.
+    #[serde(default)]
+    pub search_query: Option<String>,
 }
 
-/// An entry in the persistent watchlist.
+impl TransmissionTorrent {
+    /// A stopped torrent with nothing known about it but its hash, to fill
+    /// in the fields a caller doesn't set.
+    pub fn new(hash_string: InfoHash) -> Self {
+        Self {
+            id: 0,
+            name: String::new(),
+            hash_string,
+            status: TransmissionStatus::Stopped,
+            percent_done: 0.0,
+            recheck_progress: 0.0,
+            rate_download: 0,
+            rate_upload: 0,
+            eta: -1,
+            upload_ratio: 0.0,
+            uploaded_ever: 0,
+            seconds_seeding: 0,
+            added_date: 0,
+            done_date: 0,
+            is_finished: false,
+            size_when_done: 0,
+            peers_connected: 0,
+            peers_sending_to_us: 0,
+            peers_getting_from_us: 0,
+            stalled: false,
+            error: 0,
+            error_string: String::new(),
+            download_dir: None,
+            destination: None,
+            origin: None,
+            copy_state: CopyState::NotCopied,
+            targets: vec![],
+            copy_note: None,
+            user_note: None,
+            copy_failure: None,
+            copy_throughput: None,
+            copy_deferred_until: None,
+            copy_pending: false,
+            seed_goal_met: false,
+            copy_blocked_reason: None,
+            history: vec![],
+            unavailable_since: None,
+            likely_dead: false,
+            needs_replacement: false,
+            search_query: None,
+        }
+    }
+}
+
+/// How many polls of a downloading torrent's rate and peers are kept.
+pub const RATE_HISTORY_SAMPLES: usize = 60;
+
+/// A downloading torrent's rate and peers at one poll.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RateSample {
+    /// Unix timestamp of the poll.
+    pub at: i64,
+    pub rate_download: i64,
+    pub peers_connected: i64,
+    pub peers_sending_to_us: i64,
+}
+
+/// The lowest, mean and highest of some values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleRange {
+    pub min: i64,
+    pub avg: i64,
+    pub max: i64,
+}
+
+impl SampleRange {
+    /// The range of `values`, or `None` if there are none.
+    pub fn of(values: impl IntoIterator<Item = i64>) -> Option<Self> {
+        let mut count = 0;
+        let mut sum = 0i128;
+        let mut range: Option<Self> = None;
+        for value in values {
+            count += 1;
+            sum += value as i128;
+            range = Some(match range {
+                Some(range) => Self {
+                    min: range.min.min(value),
+                    max: range.max.max(value),
+                    ..range
+                },
+                None => Self {
+                    min: value,
+                    avg: value,
+                    max: value,
+                },
+            });
+        }
+        range.map(|range| Self {
+            avg: (sum / count) as i64,
+            ..range
+        })
+    }
+}
+
+/// What `repair_torrent` did to an errored torrent.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct RepairSummary {
+    /// New data location, if the torrent was pointed at a directory where
+    /// its files actually exist.
+    pub relocated_to: Option<String>,
+    /// Whether a verify was started.
+    pub verified: bool,
+    /// Whether the torrent was reannounced to its trackers.
+    pub reannounced: bool,
+}
+
+impl RepairSummary {
+    /// Short human-readable description of the steps taken.
+    pub fn describe(&self) -> String {
+        let mut steps = vec![];
+        if let Some(dir) = &self.relocated_to {
+            steps.push(format!("moved to {dir}"));
+        }
+        if self.verified {
+            steps.push("verifying".to_string());
+        }
+        if self.reannounced {
+            steps.push("reannounced".to_string());
+        }
+        if steps.is_empty() {
+            "nothing to do".to_string()
+        } else {
+            steps.join(", ")
+        }
+    }
+}
+
+/// Aggregate activity across all torrents, polled by the backend.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ActivitySummary {
+    /// Torrents with status Downloading.
+    pub downloading: usize,
+    /// Ledger entries currently being copied.
+    pub copying: usize,
+}
+
+impl ActivitySummary {
+    /// Number of things in flight.
+    pub fn active(&self) -> usize {
+        self.downloading + self.copying
+    }
+
+    /// Short description, e.g. "3 downloading \u{2014} 1 copying".
+    pub fn describe(&self) -> String {
+        match (self.downloading, self.copying) {
+            (0, 0) => "Idle".to_string(),
+            (d, 0) => format!("{d} downloading"),
+            (0, c) => format!("{c} copying"),
+            (d, c) => format!("{d} downloading \u{2014} {c} copying"),
+        }
+    }
+}
+
+/// Health of a single backend service.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ServiceHealth {
+    pub ok: bool,
+    /// Version or other detail when healthy, the error otherwise.
+    pub detail: String,
+}
+
+/// Health of a copy destination directory.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct WatchlistEntry {
-    /// Unique ID (monotonic counter).
-    pub id: u64,
-    /// The search title (e.g. "The Matrix 5", "Saturday Night Live").
-    pub title: String,
-    /// Movies or Shows.
+pub struct DestinationHealth {
     pub destination: Destination,
-    /// Unix timestamp when this entry was added.
-    pub added: i64,
+    /// Configured directory, `None` if the destination isn't set up.
+    pub path: Option<String>,
+    pub reachable: bool,
+    pub writable: bool,
+    /// Free space on the destination's volume, if it could be read.
+    pub free_bytes: Option<u64>,
+    /// Whether the share the destination should be on is mounted; `None`
+    /// unless the destination is flagged as a mount.
+    #[serde(default)]
+    pub mounted: Option<bool>,
+    /// When the health task first found the directory unreachable, in
+    /// seconds since the epoch; `None` while it is reachable.
+    #[serde(default)]
+    pub unreachable_since: Option<i64>,
 }
 
-/// An entry in the persistent downloads ledger.
+impl DestinationHealth {
+    /// An unconfigured destination is not a problem, only one that is
+    /// configured but unusable.
+    pub fn is_ok(&self) -> bool {
+        self.path.is_none() || (self.reachable && self.writable && self.mounted != Some(false))
+    }
+}
+
+/// How full a volume is getting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsageLevel {
+    Ok,
+    /// Over 85% used.
+    NearlyFull,
+    /// Over 95% used.
+    Full,
+}
+
+/// How full a configured destination's volume is, and what was copied
+/// there.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct DownloadEntry {
-    pub info_hash: String,
-    pub name: String,
+pub struct DestinationUsage {
     pub destination: Destination,
-    /// State of the copy operation.
+    /// The configured directory.
+    pub path: String,
+    /// Size of the destination's volume, if it could be read.
+    pub total_bytes: Option<u64>,
+    /// Free space on the destination's volume, if it could be read.
+    pub free_bytes: Option<u64>,
+    /// Ledger entries copied there.
+    pub copied: usize,
+    /// Combined size of the copies that recorded theirs.
+    pub copied_bytes: u64,
+    /// Ledger entries still to be copied there.
+    pub pending: usize,
+}
+
+impl DestinationUsage {
+    /// Share of the volume in use, if its size is known.
+    pub fn used_fraction(&self) -> Option<f64> {
+        let (total, free) = (self.total_bytes?, self.free_bytes?);
+        (total > 0).then(|| sanitize_fraction(1.0 - free as f64 / total as f64))
+    }
+
+    /// How full the volume is; `Ok` when that isn't known.
+    pub fn level(&self) -> UsageLevel {
+        match self.used_fraction() {
+            Some(used) if used > 0.95 => UsageLevel::Full,
+            Some(used) if used > 0.85 => UsageLevel::NearlyFull,
+            _ => UsageLevel::Ok,
+        }
+    }
+}
+
+/// What a `reconcile_now` pass concluded about the libraries.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ReconcileSummary {
+    /// Untracked torrents found at a destination and added as copied.
+    pub auto_added: usize,
+    /// Tracked torrents found at their destination and marked copied.
+    pub marked_copied: usize,
+    /// Copied torrents found incomplete and marked for copying again.
+    pub downgraded: usize,
+    /// What kept the pass from finishing, if anything.
+    pub errors: Vec<String>,
+}
+
+impl ReconcileSummary {
+    /// E.g. "Re-scanned libraries: 2 added, 1 marked copied".
+    pub fn describe(&self) -> String {
+        let mut parts = vec![];
+        if self.auto_added > 0 {
+            parts.push(format!("{} added", self.auto_added));
+        }
+        if self.marked_copied > 0 {
+            parts.push(format!("{} marked copied", self.marked_copied));
+        }
+        if self.downgraded > 0 {
+            parts.push(format!("{} to copy again", self.downgraded));
+        }
+        let mut text = if parts.is_empty() {
+            "Re-scanned libraries: nothing changed".to_string()
+        } else {
+            format!("Re-scanned libraries: {}", parts.join(", "))
+        };
+        for error in &self.errors {
+            text.push_str(&format!(". {error}"));
+        }
+        text
+    }
+}
+
+/// Number of ledger entries in each copy state.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct LedgerCounts {
+    pub not_copied: usize,
+    pub copying: usize,
+    pub copied: usize,
+    pub failed: usize,
+    /// Downloaded, but with no directory configured to copy to.
     #[serde(default)]
-    pub copy_state: CopyState,
+    pub unconfigured: usize,
 }
 
-/// Configuration for connecting to a Transmission RPC daemon.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct TransmissionConfig {
-    pub host: String,
-    pub port: u16,
-    pub username: Option<String>,
-    pub password: Option<String>,
-    /// Destination directory for completed movie downloads.
+/// Summary of every backend subsystem, from `get_health`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct HealthReport {
+    pub transmission: ServiceHealth,
+    pub destinations: Vec<DestinationHealth>,
+    pub search: ServiceHealth,
+    pub ledger: LedgerCounts,
+    /// Whether the copy task is copying something right now.
+    pub copy_in_flight: bool,
     #[serde(default)]
-    pub movies_dir: Option<String>,
-    /// Destination directory for completed TV show downloads.
+    pub copy_engine: CopyEngineStatus,
+    /// The download client last connected to, if it answered.
     #[serde(default)]
-    pub shows_dir: Option<String>,
+    pub daemon: Option<DaemonInfo>,
 }
 
-impl Default for TransmissionConfig {
-    fn default() -> Self {
-        Self {
-            host: "localhost".into(),
-            port: 9091,
-            username: None,
-            password: None,
-            movies_dir: None,
-            shows_dir: None,
+/// Copy cycles that panicked in a row after which the copy engine counts as
+/// unhealthy.
+pub const UNHEALTHY_AFTER_CRASHES: u32 = 3;
+
+/// How the copy task's cycles have been going.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct CopyEngineStatus {
+    /// Cycles that panicked since the last one that finished.
+    pub crashes: u32,
+    /// What the last panic said, `None` once a cycle finishes.
+    pub last_panic: Option<String>,
+}
+
+impl CopyEngineStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.crashes < UNHEALTHY_AFTER_CRASHES
+    }
+}
+
+impl HealthReport {
+    /// Human-readable problems, empty when everything is healthy.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if !self.transmission.ok {
+            problems.push(format!("Transmission: {}", self.transmission.detail));
+        }
+        for dest in self.destinations.iter().filter(|d| !d.is_ok()) {
+            let path = dest.path.as_deref().unwrap_or_default();
+            if !dest.reachable {
+                problems.push(format!(
+                    "{} directory unreachable: {path}",
+                    dest.destination
+                ));
+            } else if dest.mounted == Some(false) {
+                problems.push(format!(
+                    "{} directory not mounted: {path}",
+                    dest.destination
+                ));
+            } else {
+                problems.push(format!(
+                    "{} directory not writable: {path}",
+                    dest.destination
+                ));
+            }
         }
+        if !self.search.ok {
+            problems.push(format!("Search: {}", self.search.detail));
+        }
+        if !self.copy_engine.is_healthy() {
+            let reason = self.copy_engine.last_panic.as_deref().unwrap_or_default();
+            problems.push(format!(
+                "Copy engine unhealthy: crashed {} times in a row ({reason})",
+                self.copy_engine.crashes
+            ));
+        }
+        if self.ledger.failed > 0 {
+            problems.push(format!("{} failed copies", self.ledger.failed));
+        }
+        if self.ledger.unconfigured > 0 {
+            problems.push(format!(
+                "{} downloads with no destination directory to copy to",
+                self.ledger.unconfigured
+            ));
+        }
+        problems
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.problems().is_empty()
     }
 }
 
-impl TransmissionConfig {
-    /// Get the destination directory for a given destination kind.
-    pub fn dir_for(&self, dest: Destination) -> Option<&str> {
-        match dest {
-            Destination::Movies => self.movies_dir.as_deref(),
-            Destination::Shows => self.shows_dir.as_deref(),
+/// Failed polls in a row after which Transmission counts as down.
+pub const DOWN_AFTER_FAILURES: u32 = 3;
+
+/// How polling Transmission has been going, from `get_connection_status`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ConnectionStatus {
+    /// Unix timestamp of the last poll, `None` before the first.
+    pub checked_at: Option<i64>,
+    /// Polls that failed since the last one that succeeded.
+    pub failures: u32,
+    /// Whether any poll has succeeded since the app started.
+    pub ever_connected: bool,
+    /// Why the last poll failed, `None` if it succeeded.
+    pub last_error: Option<String>,
+}
+
+/// Summary of a [`ConnectionStatus`] for the navbar dot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionLevel {
+    /// Not polled yet.
+    Unknown,
+    /// The last poll succeeded.
+    Connected,
+    /// The last poll failed, but an earlier one succeeded.
+    Flaky,
+    /// Polls keep failing.
+    Down,
+}
+
+impl ConnectionStatus {
+    /// Note how a poll at `now` went.
+    pub fn record(&mut self, result: Result<(), String>, now: i64) {
+        self.checked_at = Some(now);
+        match result {
+            Ok(()) => {
+                self.failures = 0;
+                self.ever_connected = true;
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.failures += 1;
+                self.last_error = Some(e);
+            }
+        }
+    }
+
+    pub fn level(&self) -> ConnectionLevel {
+        if self.checked_at.is_none() {
+            ConnectionLevel::Unknown
+        } else if self.failures == 0 {
+            ConnectionLevel::Connected
+        } else if self.ever_connected && self.failures < DOWN_AFTER_FAILURES {
+            ConnectionLevel::Flaky
+        } else {
+            ConnectionLevel::Down
         }
     }
 }
 
-/// Info about a torrent file.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct Torrent {
-    pub added: String,
-    pub category: String,
-    pub descr: Option<String>,
-    pub download_count: Option<String>,
-    pub id: String,
-    pub info_hash: String,
-    pub leechers: String,
-    pub name: String,
-    pub num_files: Option<String>,
-    pub seeders: String,
-    pub size: String,
-    pub status: String,
-    pub username: String,
-    pub magnet: Option<String>,
+/// Severity of a backend log line, most severe first.
+#[derive(
+    Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
 }
 
-impl Torrent {
-    pub fn added_i64(&self) -> i64 {
-        self.added.parse().unwrap_or_default()
+impl LogLevel {
+    /// All levels, most severe first.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
     }
 
-    pub fn seeders_i64(&self) -> i64 {
-        self.seeders.parse().unwrap_or_default()
+    /// Parse a level as written in the log file, e.g. "WARN".
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.label() == label)
     }
+}
 
-    pub fn leechers_i64(&self) -> i64 {
-        self.leechers.parse().unwrap_or_default()
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
     }
+}
+
+/// Which build is running and where it keeps its files, for bug reports.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AppInfo {
+    pub version: String,
+    /// Commit the app was built from, if it was built from a git checkout.
+    pub git_hash: Option<String>,
+    pub tauri_version: String,
+    /// Version of the system webview, if it could be found.
+    pub webview_version: Option<String>,
+    pub config_path: String,
+    pub ledger_path: String,
+    pub log_path: String,
+    /// The download client last connected to, if it answered.
+    #[serde(default)]
+    pub daemon: Option<DaemonInfo>,
+}
 
-    pub fn size_bytes(&self) -> usize {
-        self.size.parse().unwrap_or_default()
+impl AppInfo {
+    /// Plain text to paste into a bug report.
+    pub fn describe(&self) -> String {
+        let unknown = "unknown";
+        format!(
+            "Privateer {} ({})\nTauri {}\nWebview {}\nClient: {}\nConfig: {}\nLedger: {}\nLog: {}",
+            self.version,
+            self.git_hash.as_deref().unwrap_or(unknown),
+            self.tauri_version,
+            self.webview_version.as_deref().unwrap_or(unknown),
+            self.daemon
+                .as_ref()
+                .map_or_else(|| unknown.to_string(), DaemonInfo::describe),
+            self.config_path,
+            self.ledger_path,
+            self.log_path,
+        )
     }
 }
 
+/// A release newer than the running build.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct TorrentInfo {
-    pub added: i64,
-    pub category: u32,
-    pub descr: Option<String>,
-    pub download_count: Option<String>,
-    pub id: u32,
-    pub info_hash: String,
-    pub leechers: u32,
-    pub name: String,
-    pub num_files: Option<u32>,
-    pub seeders: u32,
-    pub size: u64,
-    pub status: String,
-    pub username: String,
-    pub magnet: Option<String>,
+pub struct UpdateInfo {
+    /// The release's tag, e.g. "v0.3.0".
+    pub version: String,
+    /// The release's page.
+    pub url: String,
 }
 
-/// Categorises errors so the frontend can branch on the kind.
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
-pub enum ErrorKind {
-    /// Privateer search/info API errors (network, parsing, etc.).
-    PirateSearch,
-    /// Could not connect to the Transmission RPC daemon.
-    TransmissionConnection,
-    /// Transmission RPC returned a non-OK response.
-    TransmissionRpc,
-    /// Configuration file I/O or serialisation errors.
-    Config,
-    /// A URL could not be parsed.
-    InvalidUrl,
-    /// Serialisation / deserialisation errors on the invoke bridge.
-    Serialization,
-    /// Filesystem copy operation failed.
-    Copy,
+/// One line of the backend log file.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct LogLine {
+    /// Level of the record this line belongs to.  Continuation lines of a
+    /// multi-line record share the record's level.
+    pub level: LogLevel,
+    pub text: String,
 }
 
-/// Application error sent across the Tauri invoke bridge.
+/// A short message for the frontend to pop up, sent with the `toast` event.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct AppError {
-    pub kind: ErrorKind,
+pub struct Toast {
+    pub level: LogLevel,
     pub message: String,
 }
 
-impl std::fmt::Display for AppError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
+/// A search API request's place in the backend's queue, sent with the
+/// `search-queue` event whenever it changes.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct SearchQueuePlace {
+    /// The search query, or the torrent id of an info request.
+    pub request: String,
+    /// 1 for the next in line, 0 once it is the request's turn.
+    pub place: usize,
+}
+
+/// Copies made to one destination, part of [`CopyMetrics`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DestinationMetrics {
+    pub destination: Destination,
+    pub bytes_copied: u64,
+    pub files_copied: u64,
+    pub copies_completed: u64,
+    pub copies_failed: u64,
+    /// Time spent in successful copies, zero for metrics from before it
+    /// was counted per destination.
+    #[serde(default)]
+    pub copy_seconds: f64,
+}
+
+/// Cumulative copy statistics, from `get_copy_metrics`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CopyMetrics {
+    pub bytes_copied: u64,
+    pub files_copied: u64,
+    pub copies_completed: u64,
+    pub copies_failed: u64,
+    /// Total time spent in successful copies.
+    pub copy_seconds: f64,
+    pub destinations: Vec<DestinationMetrics>,
+    /// Unix timestamp of the first copy counted, `None` until there is one.
+    pub since: Option<i64>,
+}
+
+impl CopyMetrics {
+    /// The counters for `destination`, added if there are none yet.
+    pub fn destination_mut(&mut self, destination: Destination) -> &mut DestinationMetrics {
+        let idx = match self
+            .destinations
+            .iter()
+            .position(|d| d.destination == destination)
+        {
+            Some(idx) => idx,
+            None => {
+                self.destinations.push(DestinationMetrics {
+                    destination,
+                    bytes_copied: 0,
+                    files_copied: 0,
+                    copies_completed: 0,
+                    copies_failed: 0,
+                    copy_seconds: 0.0,
+                });
+                self.destinations.len() - 1
+            }
+        };
+        &mut self.destinations[idx]
     }
 }
 
-impl AppError {
-    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
-        Self {
+/// An entry in the persistent watchlist.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct WatchlistEntry {
+    /// Unique ID (monotonic counter).
+    pub id: u64,
+    /// The search title (e.g. "The Matrix 5", "Saturday Night Live").
+    pub title: String,
+    /// Movies or Shows.
+    pub destination: Destination,
+    /// Unix timestamp when this entry was added.
+    pub added: i64,
+}
+
+/// A fraction reported by Transmission, like `percentDone`, clamped to
+/// `0.0..=1.0`.  Transmission sometimes reports a little over 1.0, or NaN
+/// while verifying, which count as done and not started.
+pub fn sanitize_fraction(fraction: f64) -> f64 {
+    if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0)
+    }
+}
+
+/// A transfer rate reported by Transmission, with negative rates as none.
+pub fn sanitize_rate(bytes_per_second: i64) -> i64 {
+    bytes_per_second.max(0)
+}
+
+/// Coarse duration, e.g. "45s", "12m", "3h 5m" or "2d 3h".
+pub fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// How long something took, to the second under an hour, e.g. "42s",
+/// "9m 12s" or "1h 5m".
+pub fn format_elapsed(seconds: u64) -> String {
+    if (60..3600).contains(&seconds) {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format_duration(seconds)
+    }
+}
+
+/// Time left from Transmission's ETA, which is negative when unknown or not
+/// applicable.
+pub fn format_eta(seconds: i64) -> String {
+    match u64::try_from(seconds) {
+        Ok(seconds) => format_duration(seconds),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// Which multiples of a byte sizes are shown in.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum SizeUnits {
+    /// Powers of 1000: kB, MB, GB...
+    #[default]
+    Si,
+    /// Powers of 1024: KiB, MiB, GiB...
+    Binary,
+}
+
+impl SizeUnits {
+    pub const ALL: [SizeUnits; 2] = [SizeUnits::Si, SizeUnits::Binary];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SizeUnits::Si => "SI",
+            SizeUnits::Binary => "Binary",
+        }
+    }
+
+    fn base(&self) -> u64 {
+        match self {
+            SizeUnits::Si => 1000,
+            SizeUnits::Binary => 1024,
+        }
+    }
+
+    fn names(&self) -> [&'static str; 6] {
+        match self {
+            SizeUnits::Si => ["kB", "MB", "GB", "TB", "PB", "EB"],
+            SizeUnits::Binary => ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+        }
+    }
+}
+
+/// Size with one decimal place, e.g. "512 B", "1.5 GB" or "1.4 GiB".
+pub fn format_size(bytes: u64, units: SizeUnits) -> String {
+    let base = units.base();
+    let names = units.names();
+    if bytes < base {
+        return format!("{bytes} B");
+    }
+    let base = base as f64;
+    let mut size = bytes as f64 / base;
+    let mut unit = 0;
+    // Move up a unit before rounding would show e.g. "1000.0 kB"
+    while size >= 999.95 && unit < names.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+    format!("{size:.1} {}", names[unit])
+}
+
+/// Transfer rate, e.g. "1.2 MB/s".  Negative rates count as none.
+pub fn format_rate(bytes_per_second: i64, units: SizeUnits) -> String {
+    format!("{}/s", format_size(bytes_per_second.max(0) as u64, units))
+}
+
+/// How long before `now` the unix timestamp `then` was, e.g. "3h 5m ago",
+/// or "in 2m" for times in the future.  Spans of a month or more are only
+/// counted in months or years, e.g. "8 years ago".
+pub fn format_relative(now: i64, then: i64) -> String {
+    let seconds = now.saturating_sub(then);
+    let days = seconds.unsigned_abs() / 86_400;
+    let span = match (days / 365, days / 30) {
+        (0, 0) => format_duration(seconds.unsigned_abs()),
+        (0, 1) => "1 month".to_string(),
+        // 360 to 364 days are still less than a year
+        (0, months) => format!("{} months", months.min(11)),
+        (1, _) => "1 year".to_string(),
+        (years, _) => format!("{years} years"),
+    };
+    if seconds.unsigned_abs() < 60 {
+        "just now".to_string()
+    } else if seconds < 0 {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
+}
+
+/// A torrent's BitTorrent info hash, kept as the 40 lowercase hex digits
+/// Transmission reports so that hashes compare equal however they were
+/// written.  Parses hex in either case, or 32 base32 characters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InfoHash(String);
+
+impl InfoHash {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for InfoHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for InfoHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hash = s.trim();
+        match hash.len() {
+            40 if hash.bytes().all(|b| b.is_ascii_hexdigit()) => Some(hash.to_ascii_lowercase()),
+            32 => base32_to_hex(hash),
+            _ => None,
+        }
+        .map(Self)
+        .ok_or_else(|| format!("'{s}' is not an info hash"))
+    }
+}
+
+impl TryFrom<String> for InfoHash {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<InfoHash> for String {
+    fn from(hash: InfoHash) -> Self {
+        hash.0
+    }
+}
+
+/// How a torrent came to be in the ledger.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum DownloadOrigin {
+    /// Assigned a destination by the user, or added from a subscription.
+    #[default]
+    User,
+    /// Found already at a destination and added by reconciliation.
+    AutoDetected,
+}
+
+impl DownloadOrigin {
+    /// Shown in the Downloads row's tooltip.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::User => "Assigned by you",
+            Self::AutoDetected => "Found in the library by Privateer",
+        }
+    }
+}
+
+/// An entry in the persistent downloads ledger.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DownloadEntry {
+    pub info_hash: InfoHash,
+    pub name: String,
+    pub destination: Destination,
+    /// State of the copy operation.
+    #[serde(default)]
+    pub copy_state: CopyState,
+    /// Where the download was copied to, which may not be its own name in
+    /// the destination directory.
+    #[serde(default)]
+    pub copied_path: Option<String>,
+    /// Files renamed as they were copied.
+    #[serde(default)]
+    pub renamed_files: Vec<RenamedFile>,
+    /// Something to know about the copy, e.g. files that kept their names.
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Why the last copy failed, while it is [`CopyState::Failed`].
+    #[serde(default)]
+    pub failure: Option<String>,
+    /// Unix timestamp when the copy finished.
+    #[serde(default)]
+    pub copied_at: Option<i64>,
+    /// Bytes copied, for copies made since this was recorded.
+    #[serde(default)]
+    pub copied_bytes: Option<u64>,
+    /// How long the copy took, not counting time it was paused, for copies
+    /// made since this was recorded.
+    #[serde(default)]
+    pub copy_seconds: Option<f64>,
+    /// Copy even during quiet hours.  Cleared when the copy starts.
+    #[serde(default)]
+    pub copy_now: bool,
+    /// Names the torrent had before it was renamed in Transmission, which
+    /// its copy may still be found under.
+    #[serde(default)]
+    pub previous_names: Vec<String>,
+    /// The magnet link the torrent was added with, to add it again if
+    /// Transmission loses it.
+    #[serde(default)]
+    pub magnet: Option<String>,
+    /// Id of the server profile the torrent was added to.  Entries from
+    /// before profiles belong to the first one, which kept id 0.
+    #[serde(default)]
+    pub profile: u32,
+    /// The torrent was stopped after seeding up to its destination's
+    /// [`SeedLimits`], and is left alone from then on.
+    #[serde(default)]
+    pub seeding_completed: bool,
+    /// The name the torrent is copied under when its own can't be used as
+    /// a file name, e.g. because it has a `/` in it.
+    #[serde(default)]
+    pub safe_name: Option<String>,
+    /// Entries from before origins were kept were all assigned by the user.
+    #[serde(default)]
+    pub origin: DownloadOrigin,
+    /// The user's own note on the download, e.g. who it is for.  Unlike
+    /// [`note`](Self::note) only the user changes it.
+    #[serde(default)]
+    pub user_note: Option<String>,
+    /// More destinations the download is copied to, each on its own, after
+    /// [`destination`](Self::destination).  Entries from before there could
+    /// be more than one have none.
+    #[serde(default)]
+    pub extra_targets: Vec<CopyTarget>,
+}
+
+/// A destination a download is copied to, besides the ledger entry's own.
+///
+/// The copy goes to the destination's configured directory, or to a
+/// directory of the user's own, e.g. a local scratch drive next to the NAS.
+/// Either way it is laid out as the destination lays out its library.
+///
+/// Only what each copy needs to be told apart is kept; the renamed files
+/// and timings on the [`DownloadEntry`] are those of the copy to its own
+/// destination.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct CopyTarget {
+    pub destination: Destination,
+    /// The user's own directory to copy to instead of the destination's.
+    /// Targets from before there could be one have none.
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub copy_state: CopyState,
+    /// Why the last copy failed, while it is [`CopyState::Failed`].
+    #[serde(default)]
+    pub failure: Option<String>,
+    /// Unix timestamp when the copy finished.
+    #[serde(default)]
+    pub copied_at: Option<i64>,
+    /// Where the download was copied to.
+    #[serde(default)]
+    pub copied_path: Option<String>,
+}
+
+impl CopyTarget {
+    /// A destination the download hasn't been copied to yet.
+    pub fn new(destination: Destination) -> Self {
+        Self {
+            destination,
+            dir: None,
+            copy_state: CopyState::NotCopied,
+            failure: None,
+            copied_at: None,
+            copied_path: None,
+        }
+    }
+
+    /// A copy to `dir`, laid out as for `destination`, not made yet.
+    pub fn in_dir(destination: Destination, dir: String) -> Self {
+        Self {
+            dir: Some(dir),
+            ..Self::new(destination)
+        }
+    }
+
+    /// Whether both copy to the same place: the same destination, in the
+    /// same directory of the user's own if there is one.
+    pub fn same_place(&self, other: &CopyTarget) -> bool {
+        self.destination == other.destination && self.dir == other.dir
+    }
+
+    /// The directory the download is copied to, if there is one.
+    pub fn copy_dir<'a>(&'a self, config: &'a TransmissionConfig) -> Option<&'a str> {
+        match self.dir.as_deref() {
+            Some(dir) => Some(dir),
+            None => config.dir_for(self.destination),
+        }
+        .filter(|dir| !dir.is_empty())
+    }
+
+    /// The destination's name, and the directory if it is the user's own.
+    pub fn label(&self) -> String {
+        match &self.dir {
+            Some(dir) => format!("{} in {dir}", self.destination.label()),
+            None => self.destination.label().to_string(),
+        }
+    }
+}
+
+/// A directory of the user's own to copy a download to, laid out as for
+/// `destination`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct TargetDir {
+    pub destination: Destination,
+    pub dir: String,
+}
+
+impl DownloadEntry {
+    /// An entry the user added for `destination`, not copied yet, to fill
+    /// in the fields a caller doesn't set.
+    pub fn new(info_hash: InfoHash, name: String, destination: Destination) -> Self {
+        Self {
+            info_hash,
+            name,
+            destination,
+            copy_state: CopyState::NotCopied,
+            copied_path: None,
+            renamed_files: vec![],
+            note: None,
+            failure: None,
+            copied_at: None,
+            copied_bytes: None,
+            copy_seconds: None,
+            copy_now: false,
+            previous_names: vec![],
+            magnet: None,
+            profile: 0,
+            seeding_completed: false,
+            safe_name: None,
+            origin: DownloadOrigin::User,
+            user_note: None,
+            extra_targets: vec![],
+        }
+    }
+
+    /// Every destination the download is copied to, its own first.
+    pub fn targets(&self) -> Vec<CopyTarget> {
+        let own = CopyTarget {
+            destination: self.destination,
+            dir: None,
+            copy_state: self.copy_state,
+            failure: self.failure.clone(),
+            copied_at: self.copied_at,
+            copied_path: self.copied_path.clone(),
+        };
+        std::iter::once(own)
+            .chain(self.extra_targets.iter().cloned())
+            .collect()
+    }
+
+    /// Change the copy to the target at `index` of
+    /// [`targets`](Self::targets).  The entry's own copy always goes to its
+    /// destination's directory.
+    pub fn update_target(&mut self, index: usize, f: impl FnOnce(&mut CopyTarget)) {
+        if let Some(extra) = index.checked_sub(1) {
+            f(&mut self.extra_targets[extra]);
+            return;
+        }
+        let mut own = self.targets().swap_remove(0);
+        f(&mut own);
+        self.destination = own.destination;
+        self.copy_state = own.copy_state;
+        self.failure = own.failure;
+        self.copied_at = own.copied_at;
+        self.copied_path = own.copied_path;
+    }
+
+    /// Change the copy to every target.
+    pub fn update_targets(&mut self, mut f: impl FnMut(&mut CopyTarget)) {
+        for index in 0..=self.extra_targets.len() {
+            self.update_target(index, &mut f);
+        }
+    }
+
+    /// The state of the download's copies taken together; see
+    /// [`CopyState::combine`].
+    pub fn overall_copy_state(&self) -> CopyState {
+        CopyState::combine(
+            std::iter::once(self.copy_state).chain(self.extra_targets.iter().map(|t| t.copy_state)),
+        )
+    }
+
+    /// Follow a rename of the torrent, remembering the old name since a
+    /// copy made before the rename is still under it.
+    pub fn rename(&mut self, name: &str) {
+        let previous = std::mem::replace(&mut self.name, name.to_string());
+        self.previous_names.retain(|n| n != name);
+        if previous != name && !self.previous_names.contains(&previous) {
+            self.previous_names.push(previous);
+        }
+    }
+
+    /// The magnet link the torrent was added with, or one made from its
+    /// info hash.
+    pub fn magnet_uri(&self) -> String {
+        self.magnet
+            .clone()
+            .unwrap_or_else(|| magnet_uri(self.info_hash.as_str(), &self.name))
+    }
+
+    /// How fast the copy went, if it is copied and that was recorded.
+    pub fn copy_throughput(&self) -> Option<CopyThroughput> {
+        if self.copy_state != CopyState::Copied {
+            return None;
+        }
+        Some(CopyThroughput {
+            bytes: self.copied_bytes?,
+            seconds: self.copy_seconds?,
+        })
+    }
+}
+
+/// The size and duration of a finished copy.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct CopyThroughput {
+    pub bytes: u64,
+    pub seconds: f64,
+}
+
+impl CopyThroughput {
+    /// Average bytes per second, none for a copy that took no time.
+    pub fn rate(&self) -> i64 {
+        if self.seconds > 0.0 {
+            (self.bytes as f64 / self.seconds) as i64
+        } else {
+            0
+        }
+    }
+
+    /// E.g. "9m 12s (62.0 MB/s)".
+    pub fn describe(&self, units: SizeUnits) -> String {
+        format!(
+            "{} ({})",
+            format_elapsed(self.seconds.max(0.0).round() as u64),
+            format_rate(self.rate(), units)
+        )
+    }
+}
+
+/// Which ledger entries `get_downloads_ledger` returns.  Every filter is
+/// optional; the default returns the whole ledger.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LedgerQuery {
+    /// Only entries in one of these states; empty for any state.
+    pub copy_states: Vec<CopyState>,
+    pub destination: Option<Destination>,
+    /// Only entries whose name contains this, ignoring case.
+    pub name: Option<String>,
+    /// Only entries copied at or after this Unix timestamp.
+    pub copied_after: Option<i64>,
+    /// Only entries copied before this Unix timestamp.
+    pub copied_before: Option<i64>,
+    /// Matching entries to skip.
+    pub offset: usize,
+    /// At most this many entries; `None` for all of them.
+    pub limit: Option<usize>,
+}
+
+/// A page of the ledger entries matching a [`LedgerQuery`].
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct LedgerPage {
+    pub entries: Vec<DownloadEntry>,
+    /// How many entries match, on every page.
+    pub total: usize,
+}
+
+/// How many days a removed ledger entry can be restored for.
+pub const REMOVED_KEEP_DAYS: i64 = 30;
+
+/// A ledger entry that was removed, kept for [`REMOVED_KEEP_DAYS`] in case
+/// it is wanted back.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct RemovedDownload {
+    pub entry: DownloadEntry,
+    /// Unix timestamp when it was removed.
+    pub removed_at: i64,
+}
+
+impl RemovedDownload {
+    /// Whether it has been kept for long enough at `now`.
+    pub fn is_expired(&self, now: i64) -> bool {
+        now - self.removed_at >= REMOVED_KEEP_DAYS * 86_400
+    }
+}
+
+/// A path a download's files may be at, and what is there.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct LocatedPath {
+    pub path: String,
+    pub exists: bool,
+    /// Total size of the files there, if they could be read.
+    pub bytes: Option<u64>,
+}
+
+/// Where a download's files could be at one destination, or at a directory
+/// of the user's own.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DestinationLocation {
+    pub destination: Destination,
+    /// The ledger's copy to this destination, if it has one.
+    pub target: Option<CopyTarget>,
+    /// Where the download would be copied to now, if there is a directory to
+    /// copy it to.
+    pub planned: Option<LocatedPath>,
+    /// Where its files were found: where the ledger says they were copied
+    /// to, or else under any of the download's names.
+    pub found: Option<LocatedPath>,
+}
+
+/// Where a download's files currently live, as the copy engine resolves
+/// them.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DownloadLocation {
+    pub info_hash: InfoHash,
+    pub name: String,
+    /// Whether the download client has the torrent.
+    pub in_client: bool,
+    /// Why the download client couldn't be asked.
+    pub client_error: Option<String>,
+    /// The client's download directory for the torrent.
+    pub download_dir: Option<String>,
+    /// Where the download is read from for copying, after path mappings.
+    pub source: Option<LocatedPath>,
+    /// Why the source couldn't be checked, e.g. the SFTP host is down.
+    pub source_error: Option<String>,
+    pub destinations: Vec<DestinationLocation>,
+    /// The ledger's entry, if the download is in the ledger.
+    pub entry: Option<DownloadEntry>,
+}
+
+/// How re-adding one ledger entry to Transmission went.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ReaddResult {
+    pub info_hash: InfoHash,
+    pub name: String,
+    /// Why Transmission didn't take it, if it didn't.
+    pub error: Option<String>,
+}
+
+/// File format of an export of the downloads list.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// A copied file that was renamed.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct RenamedFile {
+    /// Where the file would be under its own name.
+    pub original: String,
+    /// Where it is.
+    pub renamed: String,
+}
+
+/// A torrent to record in the downloads ledger, as sent by the batch
+/// `add_downloads` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct NewDownload {
+    pub info_hash: InfoHash,
+    pub name: String,
+    pub destination: Destination,
+    /// More destinations to copy it to as well, each to its configured
+    /// directory.  Repeats, and [`destination`](Self::destination) itself,
+    /// are ignored.
+    #[serde(default)]
+    pub extra_destinations: Vec<Destination>,
+    /// Directories of the user's own to copy it to as well.  Repeats and
+    /// blank directories are ignored.
+    #[serde(default)]
+    pub extra_dirs: Vec<TargetDir>,
+}
+
+impl NewDownload {
+    /// The copies asked for besides the one to its destination, to the
+    /// extra destinations first.
+    pub fn extra_targets(&self) -> Vec<CopyTarget> {
+        let destinations = self.extra_destinations.iter().copied().map(CopyTarget::new);
+        let dirs = self
+            .extra_dirs
+            .iter()
+            .map(|extra| CopyTarget::in_dir(extra.destination, extra.dir.clone()));
+        destinations.chain(dirs).collect()
+    }
+}
+
+/// What `add_downloads` did with one [`NewDownload`].
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum AddOutcome {
+    /// The torrent wasn't in the ledger.
+    Added,
+    /// The torrent was in the ledger and was reset to copy to its
+    /// destination.
+    Updated,
+    /// The torrent was already waiting to copy to its destination.
+    Unchanged,
+}
+
+/// The [`AddOutcome`] of one torrent passed to `add_downloads`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AddDownloadResult {
+    pub info_hash: InfoHash,
+    pub outcome: AddOutcome,
+}
+
+/// What `preview_auto_assign` proposes for the torrents in the download
+/// client that aren't in the ledger.  Nothing is recorded until the
+/// assignments are passed to `add_downloads`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AutoAssignPreview {
+    /// The torrents a destination was found for, by name.
+    pub assignments: Vec<NewDownload>,
+    /// The names of the torrents left unassigned, because no rule
+    /// recognised them or the rules disagreed.
+    pub ambiguous: Vec<String>,
+}
+
+/// A `magnet:` link opened from outside the app, e.g. clicked in a browser.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct MagnetLink {
+    pub info_hash: InfoHash,
+    /// The link's display name, or the info hash if it has none.
+    pub name: String,
+    /// The link as opened.
+    pub uri: String,
+}
+
+// Command arguments.
+//
+// Tauri looks up each command parameter by its camelCase name, so these are
+// serialized in camelCase.  The snake_case aliases let either spelling
+// through when the JSON is read back.
+
+/// Arguments of the `add_download` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AddDownloadArgs {
+    #[serde(alias = "info_hash")]
+    pub info_hash: InfoHash,
+    pub name: String,
+    pub destination: Destination,
+    /// More destinations to copy it to as well, each to its configured
+    /// directory.  Repeats, and [`destination`](Self::destination) itself,
+    /// are ignored.
+    #[serde(default)]
+    pub extra_destinations: Vec<Destination>,
+    /// Directories of the user's own to copy it to as well.  Repeats and
+    /// blank directories are ignored.
+    #[serde(default)]
+    pub extra_dirs: Vec<TargetDir>,
+}
+
+/// Arguments of commands acting on one torrent, like `repair_torrent` and
+/// `dismiss_magnet`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoHashArgs {
+    #[serde(alias = "info_hash")]
+    pub info_hash: InfoHash,
+}
+
+/// Arguments of commands acting on several torrents, like `pause_torrents`
+/// and `readd_missing_torrents`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HashesArgs {
+    pub hashes: Vec<InfoHash>,
+}
+
+/// Arguments of the `rename_torrent` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameTorrentArgs {
+    #[serde(alias = "info_hash")]
+    pub info_hash: InfoHash,
+    #[serde(alias = "old_path")]
+    pub old_path: String,
+    #[serde(alias = "new_name")]
+    pub new_name: String,
+}
+
+/// Arguments of the `transfer_ledger_entry` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferLedgerArgs {
+    /// The broken download whose entry moves.
+    #[serde(alias = "old_hash")]
+    pub old_hash: InfoHash,
+    /// The download replacing it.
+    #[serde(alias = "new_hash")]
+    pub new_hash: InfoHash,
+}
+
+/// Arguments of the `preview_auto_assign` command.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoAssignArgs {
+    /// Only torrents whose name contains this, ignoring case.
+    #[serde(default, alias = "name_filter")]
+    pub name_filter: Option<String>,
+}
+
+/// Arguments of the `update_download_note` command.  A blank note removes
+/// it.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNoteArgs {
+    #[serde(alias = "info_hash")]
+    pub info_hash: InfoHash,
+    pub note: Option<String>,
+}
+
+/// Arguments of the `remove_torrents` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveTorrentsArgs {
+    pub hashes: Vec<InfoHash>,
+    #[serde(alias = "delete_data")]
+    pub delete_data: bool,
+    /// See [`ConfirmArgs`].
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Arguments of destructive commands that take nothing else, like
+/// `reset_copy_metrics`.
+///
+/// Destructive commands refuse to run unless `confirm` is set, and a
+/// missing `confirm` is not set.  The frontend only sets it once the user
+/// has had a few seconds to undo.  Commands that can be undone afterwards,
+/// like `remove_download`, don't take it.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ConfirmArgs {
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Arguments of the `remove_from_watchlist` command.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct RemoveWatchlistArgs {
+    pub id: u64,
+    /// See [`ConfirmArgs`].
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Decode `%XX` escapes and `+` as a space, as in a URL's query string.
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let escaped = rest
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &rest[2..];
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Escape everything but unreserved characters as `%XX`.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// A magnet link with only an info hash and display name.  Transmission
+/// finds the metadata and peers through DHT and peer exchange.
+pub fn magnet_uri(info_hash: &str, name: &str) -> String {
+    let name = percent_encode(name);
+    format!("magnet:?xt=urn:btih:{info_hash}&dn={name}")
+}
+
+/// Turn a 32 character base32 info hash into 40 hex digits.
+fn base32_to_hex(hash: &str) -> Option<String> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut hex = String::with_capacity(40);
+    let (mut buffer, mut bits) = (0u64, 0);
+    for c in hash.to_ascii_uppercase().bytes() {
+        let value = ALPHABET.iter().position(|&a| a == c)? as u64;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        while bits >= 4 {
+            bits -= 4;
+            hex.push(char::from_digit(((buffer >> bits) & 0xf) as u32, 16)?);
+        }
+    }
+    Some(hex)
+}
+
+/// Parse a `magnet:` link with a BitTorrent info hash (`xt=urn:btih:...`),
+/// in hex or base32.  Returns `None` for anything else.
+pub fn parse_magnet(uri: &str) -> Option<MagnetLink> {
+    let uri = uri.trim();
+    let (scheme, query) = uri.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case("magnet") {
+        return None;
+    }
+    let query = query.strip_prefix('?').unwrap_or(query);
+
+    let mut info_hash = None;
+    let mut name = None;
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        match key {
+            "xt" if info_hash.is_none() => {
+                let value = percent_decode(value);
+                let hash = match value.get(..9) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case("urn:btih:") => &value[9..],
+                    _ => continue,
+                };
+                info_hash = hash.parse::<InfoHash>().ok();
+            }
+            "dn" => name = Some(percent_decode(value).trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let info_hash = info_hash?;
+    Some(MagnetLink {
+        name: name
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| info_hash.to_string()),
+        info_hash,
+        uri: uri.to_string(),
+    })
+}
+
+/// An RSS feed whose new items are added to Transmission when their title
+/// matches.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct FeedSubscription {
+    pub url: String,
+    /// Regex an item's title must match, ignoring case.
+    pub include: String,
+    /// Regex for titles to skip even though `include` matches.
+    #[serde(default)]
+    pub exclude: Option<String>,
+    /// Where matching downloads are copied.
+    pub destination: Destination,
+}
+
+/// An item in a subscription's feed, as previewed in Settings.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct FeedPreviewItem {
+    pub title: String,
+    /// Whether the subscription would add this item.
+    pub matches: bool,
+}
+
+/// Per-type toggles for desktop notifications.  Everything is on by default.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// A torrent finished downloading.
+    pub download_complete: bool,
+    /// A completed download was copied to its destination.
+    pub copy_complete: bool,
+    /// Copying a completed download failed.
+    pub copy_failed: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            download_complete: true,
+            copy_complete: true,
+            copy_failed: true,
+        }
+    }
+}
+
+/// Kind of media server to ask for library scans.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum MediaServerKind {
+    #[default]
+    Plex,
+    Jellyfin,
+}
+
+impl MediaServerKind {
+    pub const ALL: [MediaServerKind; 2] = [MediaServerKind::Plex, MediaServerKind::Jellyfin];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Plex => "Plex",
+            Self::Jellyfin => "Jellyfin",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+impl std::fmt::Display for MediaServerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// A media server to ask for a library scan after each copy, so new files
+/// show up without waiting for its scheduled scan.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MediaServerConfig {
+    pub kind: MediaServerKind,
+    /// e.g. `http://192.168.1.10:32400`
+    pub base_url: String,
+    /// Plex token or Jellyfin API key.
+    pub token: String,
+    /// Plex library section holding the movies directory.  Jellyfin finds
+    /// the library from the path, so it doesn't need sections.
+    pub movies_section: Option<u32>,
+    /// Plex library section holding the shows directory.
+    pub shows_section: Option<u32>,
+}
+
+impl MediaServerConfig {
+    /// The Plex library section for a destination.
+    pub fn section_for(&self, dest: Destination) -> Option<u32> {
+        match dest {
+            Destination::Movies => self.movies_section,
+            Destination::Shows => self.shows_section,
+        }
+    }
+}
+
+/// Which events are posted to the webhook.  Everything is on by default.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebhookEvents {
+    /// A torrent finished downloading.
+    pub download_complete: bool,
+    /// Copying a completed download started.
+    pub copy_started: bool,
+    /// A completed download was copied to its destination.
+    pub copy_completed: bool,
+    /// Copying a completed download failed.
+    pub copy_failed: bool,
+}
+
+impl Default for WebhookEvents {
+    fn default() -> Self {
+        Self {
+            download_complete: true,
+            copy_started: true,
+            copy_completed: true,
+            copy_failed: true,
+        }
+    }
+}
+
+/// A URL that download and copy events are posted to as JSON.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub events: WebhookEvents,
+    /// When set, each request is signed with HMAC-SHA256 of the body keyed
+    /// by this secret, in the `X-Privateer-Signature` header.
+    pub secret: Option<String>,
+}
+
+/// An SSH login on the host Transmission runs on, for reading completed
+/// downloads over SFTP.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SftpConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    /// Private key to log in with.  Takes precedence over `password`.
+    pub key_path: Option<String>,
+    /// Password to log in with, or the passphrase of `key_path`.
+    pub password: Option<String>,
+}
+
+impl Default for SftpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 22,
+            user: String::new(),
+            key_path: None,
+            password: None,
+        }
+    }
+}
+
+/// Where the copy task reads completed downloads from.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub enum CopyBackend {
+    /// Transmission's download dir is on this machine, or mounted on it.
+    #[default]
+    Local,
+    /// Transmission's download dir is read over SSH.
+    Sftp(SftpConfig),
+}
+
+/// Sent to the Settings form instead of the saved password, and sent back
+/// when the password wasn't touched, so the password itself never has to be
+/// shown.
+pub const SAVED_PASSWORD: &str = "\u{0}saved";
+
+/// The BitTorrent client a server profile connects to.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum ClientKind {
+    #[default]
+    Transmission,
+    /// Through its Web API.
+    QBittorrent,
+}
+
+impl ClientKind {
+    pub const ALL: [ClientKind; 2] = [ClientKind::Transmission, ClientKind::QBittorrent];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Transmission => "Transmission",
+            Self::QBittorrent => "qBittorrent",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+
+    /// The port the client listens on out of the box.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::Transmission => 9091,
+            Self::QBittorrent => 8080,
+        }
+    }
+}
+
+impl std::fmt::Display for ClientKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Something Privateer does that older Transmission daemons can't.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum ClientFeature {
+    /// `torrent-rename-path`
+    RenamePath,
+    /// `free-space`
+    FreeSpace,
+    /// Torrent labels
+    Labels,
+}
+
+impl ClientFeature {
+    pub const ALL: [ClientFeature; 3] = [
+        ClientFeature::RenamePath,
+        ClientFeature::FreeSpace,
+        ClientFeature::Labels,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::RenamePath => "Renaming",
+            Self::FreeSpace => "Checking free space",
+            Self::Labels => "Labels",
+        }
+    }
+
+    /// The `rpc-version` of the first Transmission with the feature.
+    pub fn rpc_version(&self) -> i64 {
+        match self {
+            Self::RenamePath | Self::FreeSpace => 15,
+            Self::Labels => 16,
+        }
+    }
+
+    /// The first Transmission release with the feature.
+    pub fn transmission_version(&self) -> &'static str {
+        match self {
+            Self::RenamePath | Self::FreeSpace => "2.80",
+            Self::Labels => "3.00",
+        }
+    }
+
+    /// Why the feature is missing, e.g. "Labels need Transmission 3.00 or
+    /// newer".
+    pub fn unavailable_reason(&self) -> String {
+        let verb = match self {
+            Self::Labels => "need",
+            _ => "needs",
+        };
+        format!(
+            "{} {verb} Transmission {} or newer",
+            self.label(),
+            self.transmission_version()
+        )
+    }
+}
+
+/// What the connected download client can do.  Everything is assumed
+/// until the client is known.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ClientCapabilities {
+    pub rename_path: bool,
+    pub free_space: bool,
+    pub labels: bool,
+}
+
+impl Default for ClientCapabilities {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl ClientCapabilities {
+    pub const ALL: Self = Self {
+        rename_path: true,
+        free_space: true,
+        labels: true,
+    };
+
+    /// What a Transmission daemon speaking `rpc_version` can do.
+    pub fn for_rpc_version(rpc_version: i64) -> Self {
+        let has = |feature: ClientFeature| rpc_version >= feature.rpc_version();
+        Self {
+            rename_path: has(ClientFeature::RenamePath),
+            free_space: has(ClientFeature::FreeSpace),
+            labels: has(ClientFeature::Labels),
+        }
+    }
+
+    pub fn has(&self, feature: ClientFeature) -> bool {
+        match feature {
+            ClientFeature::RenamePath => self.rename_path,
+            ClientFeature::FreeSpace => self.free_space,
+            ClientFeature::Labels => self.labels,
+        }
+    }
+
+    pub fn missing(&self) -> Vec<ClientFeature> {
+        ClientFeature::ALL
+            .into_iter()
+            .filter(|feature| !self.has(*feature))
+            .collect()
+    }
+}
+
+/// The download client Privateer last connected to.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct DaemonInfo {
+    pub client: ClientKind,
+    /// As the client reports it, e.g. "4.0.5 (a6fe2a64aa)".
+    pub version: String,
+    /// Transmission's RPC version, `None` for other clients.
+    pub rpc_version: Option<i64>,
+    pub capabilities: ClientCapabilities,
+}
+
+impl DaemonInfo {
+    /// A Transmission daemon, with what its `rpc_version` allows.
+    pub fn transmission(version: String, rpc_version: i64) -> Self {
+        Self {
+            client: ClientKind::Transmission,
+            version,
+            rpc_version: Some(rpc_version),
+            capabilities: ClientCapabilities::for_rpc_version(rpc_version),
+        }
+    }
+
+    /// e.g. "Transmission 4.0.5 (a6fe2a64aa), RPC version 17".
+    pub fn describe(&self) -> String {
+        match self.rpc_version {
+            Some(rpc_version) => {
+                format!(
+                    "{} {}, RPC version {rpc_version}",
+                    self.client, self.version
+                )
+            }
+            None => format!("{} {}", self.client, self.version),
+        }
+    }
+
+    /// Why each missing feature is missing.
+    pub fn limitations(&self) -> Vec<String> {
+        self.capabilities
+            .missing()
+            .iter()
+            .map(ClientFeature::unavailable_reason)
+            .collect()
+    }
+}
+
+/// A named Transmission daemon, or other download client, to connect to.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ServerProfile {
+    /// Stays the same through renames, so ledger entries can refer to it.
+    pub id: u32,
+    pub name: String,
+    /// Profiles saved before other clients were supported are Transmission.
+    #[serde(default)]
+    pub client: ClientKind,
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for ServerProfile {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: "Default".into(),
+            client: ClientKind::Transmission,
+            host: "localhost".into(),
+            port: ClientKind::Transmission.default_port(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+impl ServerProfile {
+    /// The first problem with connecting to this daemon.
+    pub fn validate(&self) -> Result<(), SettingsProblem> {
+        if self.name.trim().is_empty() {
+            return Err(SettingsProblem::ProfileName);
+        }
+        if self.port == 0 {
+            return Err(SettingsProblem::Port);
+        }
+        check_host(&self.host)?;
+        check_credentials(
+            self.username.as_deref().unwrap_or_default(),
+            self.password.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+/// The server profiles by name, for the navbar switcher.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ProfileChoices {
+    pub names: Vec<String>,
+    pub active: usize,
+}
+
+/// Configuration for connecting to a Transmission RPC daemon.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct TransmissionConfig {
+    /// The daemons to choose from.  Never empty once loaded.
+    #[serde(default = "TransmissionConfig::default_profiles")]
+    pub profiles: Vec<ServerProfile>,
+    /// Index into `profiles` of the daemon in use.
+    #[serde(default)]
+    pub active_profile: usize,
+    /// Destination directory for completed movie downloads.
+    #[serde(default)]
+    pub movies_dir: Option<String>,
+    /// Destination directory for completed TV show downloads.
+    #[serde(default)]
+    pub shows_dir: Option<String>,
+    /// The movies directory is on a mounted share, so nothing is copied
+    /// while the share is missing.
+    #[serde(default)]
+    pub movies_expect_mount: bool,
+    /// The shows directory is on a mounted share.
+    #[serde(default)]
+    pub shows_expect_mount: bool,
+    /// Minutes a download may go without any peer sending data before it is
+    /// flagged as stalled.
+    #[serde(default = "TransmissionConfig::default_stall_minutes")]
+    pub stall_minutes: u32,
+    /// Hours an incomplete download may go without any peer sending data
+    /// before it is flagged as likely dead; 0 never flags one.
+    #[serde(default = "TransmissionConfig::default_dead_after_hours")]
+    pub dead_after_hours: u32,
+    /// Which desktop notifications to show.
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    /// Hide the window to the tray when it is closed, instead of quitting.
+    #[serde(default = "TransmissionConfig::default_close_to_tray")]
+    pub close_to_tray: bool,
+    /// Seconds to wait for a Transmission RPC call before giving up.
+    #[serde(default = "TransmissionConfig::default_rpc_timeout_secs")]
+    pub rpc_timeout_secs: u64,
+    /// Media server to ask for a library scan after each copy.
+    #[serde(default)]
+    pub media_server: Option<MediaServerConfig>,
+    /// Webhook to post download and copy events to.
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    /// Command run after each copy, e.g. a script that fixes permissions.
+    /// The copy's details are passed in `PRIVATEER_*` environment variables.
+    #[serde(default)]
+    pub post_copy_command: Option<String>,
+    /// Where Transmission's paths are mounted locally, as
+    /// `(remote prefix, local prefix)` pairs, for a daemon on another host.
+    #[serde(default)]
+    pub path_mappings: Vec<(String, String)>,
+    /// Where completed downloads are read from.
+    #[serde(default)]
+    pub copy_backend: CopyBackend,
+    /// Copy local downloads with an external `rsync`, which resumes a copy
+    /// interrupted part way through.
+    #[serde(default)]
+    pub copy_with_rsync: bool,
+    /// Copy movies into a `Title (Year)` folder parsed from the release
+    /// name, which media servers match more reliably.
+    #[serde(default)]
+    pub clean_movie_names: bool,
+    /// Rename episode files copied to Shows by `episode_template`.
+    #[serde(default)]
+    pub rename_episodes: bool,
+    /// New name for episode files, from `{show}`, `{season}`, `{episode}`
+    /// and `{quality}`; numbers take a width like `{season:02}`.
+    #[serde(default = "TransmissionConfig::default_episode_template")]
+    pub episode_template: String,
+    /// Add magnet links opened from outside the app straight to this
+    /// destination, instead of asking for one.
+    #[serde(default)]
+    pub magnet_destination: Option<Destination>,
+    /// RSS feeds whose new items are added automatically.
+    #[serde(default)]
+    pub subscriptions: Vec<FeedSubscription>,
+    /// Minutes between polls of the subscribed feeds.
+    #[serde(default = "TransmissionConfig::default_feed_poll_minutes")]
+    pub feed_poll_minutes: u32,
+    /// Local start and end times between which automatic copies wait.  A
+    /// window that ends before it starts runs past midnight.
+    #[serde(default)]
+    pub quiet_hours: Option<(TimeOfDay, TimeOfDay)>,
+    /// Minutes a copy may go without writing anything before it is given
+    /// up as stalled; 0 waits forever.
+    #[serde(default = "TransmissionConfig::default_copy_stall_minutes")]
+    pub copy_stall_minutes: u32,
+    /// Look for a newer release when Settings opens.
+    #[serde(default)]
+    pub check_for_updates: bool,
+    /// No new copies start, from Downloads' "Pause copying" until it is
+    /// resumed.  Not part of the settings form.
+    #[serde(default)]
+    pub copying_paused: bool,
+    /// When copied movies stop seeding.
+    #[serde(default)]
+    pub movies_seed_limits: SeedLimits,
+    /// When copied shows stop seeding.
+    #[serde(default)]
+    pub shows_seed_limits: SeedLimits,
+    /// Locale dates are shown in, e.g. "en-GB", or `None` for the system's.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Units sizes are shown in, in the app and in exports.
+    #[serde(default)]
+    pub size_units: SizeUnits,
+    /// Leave torrents without a ledger entry alone: reconciliation doesn't
+    /// add them and Downloads doesn't look for them at the destinations,
+    /// e.g. when other tools manage some of the client's torrents.
+    #[serde(default)]
+    pub only_assigned: bool,
+}
+
+impl Default for TransmissionConfig {
+    fn default() -> Self {
+        Self {
+            profiles: Self::default_profiles(),
+            active_profile: 0,
+            movies_dir: None,
+            shows_dir: None,
+            movies_expect_mount: false,
+            shows_expect_mount: false,
+            stall_minutes: Self::default_stall_minutes(),
+            dead_after_hours: Self::default_dead_after_hours(),
+            notifications: NotificationConfig::default(),
+            close_to_tray: Self::default_close_to_tray(),
+            rpc_timeout_secs: Self::default_rpc_timeout_secs(),
+            media_server: None,
+            webhook: None,
+            post_copy_command: None,
+            path_mappings: Vec::new(),
+            copy_backend: CopyBackend::Local,
+            copy_with_rsync: false,
+            clean_movie_names: false,
+            rename_episodes: false,
+            episode_template: Self::default_episode_template(),
+            magnet_destination: None,
+            subscriptions: Vec::new(),
+            feed_poll_minutes: Self::default_feed_poll_minutes(),
+            quiet_hours: None,
+            copy_stall_minutes: Self::default_copy_stall_minutes(),
+            check_for_updates: false,
+            copying_paused: false,
+            movies_seed_limits: SeedLimits::default(),
+            shows_seed_limits: SeedLimits::default(),
+            locale: None,
+            size_units: SizeUnits::default(),
+            only_assigned: false,
+        }
+    }
+}
+
+impl TransmissionConfig {
+    /// The first problem that keeps these settings from being saved.
+    pub fn validate(&self) -> Result<(), SettingsProblem> {
+        if self.active_profile >= self.profiles.len() {
+            return Err(SettingsProblem::NoSuchProfile);
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            profile.validate()?;
+            let name = profile.name.trim();
+            if self.profiles[..i]
+                .iter()
+                .any(|p| p.name.trim().eq_ignore_ascii_case(name))
+            {
+                return Err(SettingsProblem::DuplicateProfileName);
+            }
+        }
+        self.movies_seed_limits.validate()?;
+        self.shows_seed_limits.validate()?;
+        if let Some(locale) = &self.locale {
+            check_locale(locale)?;
+        }
+        let dir = |dest| self.dir_for(dest).unwrap_or_default();
+        match check_destination_dirs(dir(Destination::Movies), dir(Destination::Shows)) {
+            Err(problem) if problem.is_error() => Err(problem),
+            _ => Ok(()),
+        }
+    }
+
+    /// The profile of the daemon in use.
+    pub fn active(&self) -> ServerProfile {
+        self.profiles
+            .get(self.active_profile)
+            .or(self.profiles.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The profile names, for switching between them.
+    pub fn profile_choices(&self) -> ProfileChoices {
+        ProfileChoices {
+            names: self.profiles.iter().map(|p| p.name.clone()).collect(),
+            active: self.active_profile,
+        }
+    }
+
+    /// This config with every password replaced by [`SAVED_PASSWORD`].
+    pub fn masked(mut self) -> Self {
+        for profile in &mut self.profiles {
+            if profile.password.is_some() {
+                profile.password = Some(SAVED_PASSWORD.into());
+            }
+        }
+        self
+    }
+
+    /// Put the saved passwords back where the form left them unchanged.
+    pub fn keep_saved_password(&mut self, saved: &TransmissionConfig) {
+        for profile in &mut self.profiles {
+            if profile.password.as_deref() == Some(SAVED_PASSWORD) {
+                profile.password = saved
+                    .profiles
+                    .iter()
+                    .find(|p| p.id == profile.id)
+                    .and_then(|p| p.password.clone());
+            }
+        }
+    }
+
+    fn default_profiles() -> Vec<ServerProfile> {
+        vec![ServerProfile::default()]
+    }
+
+    fn default_stall_minutes() -> u32 {
+        10
+    }
+
+    fn default_dead_after_hours() -> u32 {
+        24
+    }
+
+    fn default_copy_stall_minutes() -> u32 {
+        10
+    }
+
+    fn default_close_to_tray() -> bool {
+        true
+    }
+
+    fn default_rpc_timeout_secs() -> u64 {
+        10
+    }
+
+    fn default_feed_poll_minutes() -> u32 {
+        15
+    }
+
+    pub fn default_episode_template() -> String {
+        "{show} - S{season:02}E{episode:02}".into()
+    }
+
+    /// Timeout for Transmission RPC calls.
+    pub fn rpc_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.rpc_timeout_secs.max(1))
+    }
+
+    /// Get the destination directory for a given destination kind.
+    pub fn dir_for(&self, dest: Destination) -> Option<&str> {
+        match dest {
+            Destination::Movies => self.movies_dir.as_deref(),
+            Destination::Shows => self.shows_dir.as_deref(),
+        }
+    }
+
+    /// Why nothing can be copied to `dest`, if it has no directory.
+    pub fn copy_blocked_reason(&self, dest: Destination) -> Option<String> {
+        match self.dir_for(dest) {
+            Some(dir) if !dir.is_empty() => None,
+            _ => Some(format!("{} directory not configured", dest.label())),
+        }
+    }
+
+    /// Whether the destination's directory should be on a mounted share.
+    pub fn expects_mount(&self, dest: Destination) -> bool {
+        match dest {
+            Destination::Movies => self.movies_expect_mount,
+            Destination::Shows => self.shows_expect_mount,
+        }
+    }
+
+    /// When torrents copied to the destination stop seeding.
+    pub fn seed_limits(&self, dest: Destination) -> SeedLimits {
+        match dest {
+            Destination::Movies => self.movies_seed_limits,
+            Destination::Shows => self.shows_seed_limits,
+        }
+    }
+
+    /// When quiet hours end, if `now` is within them.
+    pub fn quiet_until(&self, now: TimeOfDay) -> Option<TimeOfDay> {
+        let (start, end) = self.quiet_hours?;
+        let (from, to, now) = (start.minutes(), end.minutes(), now.minutes());
+        let quiet = if from <= to {
+            (from..to).contains(&now)
+        } else {
+            now >= from || now < to
+        };
+        quiet.then_some(end)
+    }
+}
+
+/// When a torrent copied to a destination has seeded enough to be stopped.
+/// Torrents not in the ledger are never stopped.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct SeedLimits {
+    /// Upload ratio to stop at.
+    #[serde(default)]
+    pub seed_ratio_limit: Option<f64>,
+    /// Minutes of seeding to stop after.
+    #[serde(default)]
+    pub seed_time_limit: Option<u32>,
+}
+
+impl SeedLimits {
+    /// Whether a torrent with Transmission's `upload_ratio` that has been
+    /// seeding for `seconds_seeding` has reached either limit.
+    pub fn is_met(&self, upload_ratio: f64, seconds_seeding: i64) -> bool {
+        // Transmission reports -2 for an infinite ratio
+        let ratio_met = self
+            .seed_ratio_limit
+            .is_some_and(|limit| upload_ratio == -2.0 || upload_ratio >= limit);
+        let time_met = self
+            .seed_time_limit
+            .is_some_and(|minutes| seconds_seeding >= minutes as i64 * 60);
+        ratio_met || time_met
+    }
+
+    pub fn validate(&self) -> Result<(), SettingsProblem> {
+        match self.seed_ratio_limit {
+            Some(ratio) if !(ratio.is_finite() && ratio >= 0.0) => Err(SettingsProblem::SeedRatio),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Something wrong with a value in Settings, worded the same by the form
+/// and by the backend refusing to save it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsProblem {
+    Port,
+    HostEmpty,
+    HostSpaces,
+    HostScheme,
+    Credentials,
+    /// Only a warning: the directory may well exist relative to wherever
+    /// the app happens to run, but probably not.
+    RelativeDir,
+    DownloadDirEmpty,
+    IncompleteDirEmpty,
+    SpeedLimit,
+    SeedRatio,
+    ProfileName,
+    DuplicateProfileName,
+    NoSuchProfile,
+    SameDestinationDirs,
+    /// Only a warning: a copy found under both is taken to be in the
+    /// deeper directory.
+    NestedDestinationDirs,
+    Locale,
+}
+
+impl SettingsProblem {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Port => "Port must be a number from 1 to 65535.",
+            Self::HostEmpty => "Host can't be empty.",
+            Self::HostSpaces => "Host can't contain spaces.",
+            Self::HostScheme => "Host is just the name or address, without http://.",
+            Self::Credentials => "Set both the username and the password, or neither.",
+            Self::RelativeDir => "This path is relative; use a full path like /Volumes/Media.",
+            Self::DownloadDirEmpty => "Download directory can't be empty.",
+            Self::IncompleteDirEmpty => {
+                "Choose a directory for incomplete downloads, or stop using one."
+            }
+            Self::SpeedLimit => "Speed limits are whole numbers of KB/s.",
+            Self::SeedRatio => "Seed ratio must be a number of 0 or more.",
+            Self::ProfileName => "Every server profile needs a name.",
+            Self::DuplicateProfileName => "Give each server profile a different name.",
+            Self::NoSuchProfile => "That server profile doesn't exist.",
+            Self::SameDestinationDirs => "Movies and Shows need different directories.",
+            Self::NestedDestinationDirs => {
+                "One destination is inside the other. A download found in both is \
+                 taken to belong to the deeper one."
+            }
+            Self::Locale => "Locale is a language tag like en-US, or empty for the system's.",
+        }
+    }
+
+    /// Whether the settings can't be saved with this problem.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Self::RelativeDir | Self::NestedDestinationDirs)
+    }
+}
+
+impl std::fmt::Display for SettingsProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for SettingsProblem {}
+
+/// Parse the port field.
+pub fn parse_port(port: &str) -> Result<u16, SettingsProblem> {
+    match port.trim().parse() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(SettingsProblem::Port),
+    }
+}
+
+/// Check a locale looks like a BCP 47 language tag, e.g. "en", "en-GB" or
+/// "zh-Hant-TW", which is all the browser needs to not throw on it.
+pub fn check_locale(locale: &str) -> Result<(), SettingsProblem> {
+    let mut subtags = locale.trim().split('-');
+    let language = subtags.next().unwrap_or_default();
+    let is_language = matches!(language.len(), 2..=3 | 5..=8)
+        && language.chars().all(|c| c.is_ascii_alphabetic());
+    let is_subtag = |subtag: &str| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+    if is_language && subtags.all(is_subtag) {
+        Ok(())
+    } else {
+        Err(SettingsProblem::Locale)
+    }
+}
+
+pub fn check_host(host: &str) -> Result<(), SettingsProblem> {
+    let host = host.trim();
+    if host.is_empty() {
+        Err(SettingsProblem::HostEmpty)
+    } else if host.contains(char::is_whitespace) {
+        Err(SettingsProblem::HostSpaces)
+    } else if host.contains("://") {
+        Err(SettingsProblem::HostScheme)
+    } else {
+        Ok(())
+    }
+}
+
+/// Transmission's authentication needs both or neither.
+pub fn check_credentials(username: &str, password: &str) -> Result<(), SettingsProblem> {
+    if username.is_empty() == password.is_empty() {
+        Ok(())
+    } else {
+        Err(SettingsProblem::Credentials)
+    }
+}
+
+/// Warn about a relative destination directory.  An empty one is fine:
+/// that destination just isn't copied to.
+pub fn check_dir(dir: &str) -> Result<(), SettingsProblem> {
+    let dir = dir.trim();
+    let bytes = dir.as_bytes();
+    let is_absolute = dir.starts_with(['/', '\\'])
+        || (bytes.len() > 2
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'/' | b'\\'));
+    if dir.is_empty() || is_absolute {
+        Ok(())
+    } else {
+        Err(SettingsProblem::RelativeDir)
+    }
+}
+
+/// Check that the destination directories don't overlap, comparing the
+/// paths as written.  The same directory for both is an error, one inside
+/// the other only a warning.
+pub fn check_destination_dirs(movies: &str, shows: &str) -> Result<(), SettingsProblem> {
+    let parts = |dir: &str| -> Vec<String> {
+        normalize_dir(dir)
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(str::to_string)
+            .collect()
+    };
+    if movies.trim().is_empty() || shows.trim().is_empty() {
+        return Ok(());
+    }
+    let (movies, shows) = (parts(movies), parts(shows));
+    if movies == shows {
+        Err(SettingsProblem::SameDestinationDirs)
+    } else if movies.starts_with(&shows) || shows.starts_with(&movies) {
+        Err(SettingsProblem::NestedDestinationDirs)
+    } else {
+        Ok(())
+    }
+}
+
+/// Transmission's own preferences that can be edited from Settings, from
+/// `get_session_config`.  A field is `None` when the daemon doesn't have
+/// it, e.g. an older version, and is shown read-only.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct SessionConfig {
+    /// Where new torrents are downloaded, on the daemon's machine.
+    pub download_dir: Option<String>,
+    pub incomplete_dir_enabled: Option<bool>,
+    /// Where torrents are kept until they finish.
+    pub incomplete_dir: Option<String>,
+    pub speed_limit_down_enabled: Option<bool>,
+    /// In KB/s.
+    pub speed_limit_down: Option<u32>,
+    pub speed_limit_up_enabled: Option<bool>,
+    /// In KB/s.
+    pub speed_limit_up: Option<u32>,
+    /// Whether torrents stop seeding at `seed_ratio_limit`.
+    pub seed_ratio_limited: Option<bool>,
+    pub seed_ratio_limit: Option<f64>,
+}
+
+impl SessionConfig {
+    /// The first problem that keeps these preferences from being saved.
+    pub fn validate(&self) -> Result<(), SettingsProblem> {
+        let is_blank = |dir: &Option<String>| dir.as_deref().is_some_and(|d| d.trim().is_empty());
+        if is_blank(&self.download_dir) {
+            return Err(SettingsProblem::DownloadDirEmpty);
+        }
+        if self.incomplete_dir_enabled == Some(true) && is_blank(&self.incomplete_dir) {
+            return Err(SettingsProblem::IncompleteDirEmpty);
+        }
+        if self
+            .seed_ratio_limit
+            .is_some_and(|ratio| !ratio.is_finite() || ratio < 0.0)
+        {
+            return Err(SettingsProblem::SeedRatio);
+        }
+        Ok(())
+    }
+}
+
+/// Parse a speed limit field, in KB/s.
+pub fn parse_speed_limit(limit: &str) -> Result<u32, SettingsProblem> {
+    limit
+        .trim()
+        .parse()
+        .map_err(|_| SettingsProblem::SpeedLimit)
+}
+
+/// Parse the seed ratio field.
+pub fn parse_seed_ratio(ratio: &str) -> Result<f64, SettingsProblem> {
+    match ratio.trim().parse::<f64>() {
+        Ok(ratio) if ratio.is_finite() && ratio >= 0.0 => Ok(ratio),
+        _ => Err(SettingsProblem::SeedRatio),
+    }
+}
+
+/// A directory path without trailing separators, so that joining a name to
+/// it puts exactly one separator in between.  A root like `/` or `C:\` is
+/// kept as it is.
+pub fn normalize_dir(dir: &str) -> String {
+    let dir = dir.trim();
+    let trimmed = dir.trim_end_matches(['/', '\\']);
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        // A root, which needs its separator
+        dir.get(..trimmed.len() + 1).unwrap_or(dir).to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// A local time of day, kept as `HH:MM`.
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self { hour, minute })
+    }
+
+    /// Parse `HH:MM`, e.g. from an `<input type="time">`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (hour, minute) = s.trim().split_once(':')?;
+        Self::new(hour.parse().ok()?, minute.parse().ok()?)
+    }
+
+    /// Minutes since midnight.
+    pub fn minutes(&self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+}
+
+impl std::fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s).ok_or_else(|| format!("'{s}' is not a time of day (HH:MM)"))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+/// Info about a torrent file.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Torrent {
+    pub added: String,
+    pub category: String,
+    pub descr: Option<String>,
+    pub download_count: Option<String>,
+    pub id: String,
+    pub info_hash: String,
+    pub leechers: String,
+    pub name: String,
+    pub num_files: Option<String>,
+    pub seeders: String,
+    pub size: String,
+    pub status: String,
+    pub username: String,
+    pub magnet: Option<String>,
+    /// How healthy the swarm looks, from 0 to [`HEALTH_DOTS`]; `None` when
+    /// the seeder count is unknown.  See [`health_score`].
+    #[serde(default)]
+    pub health: Option<u8>,
+}
+
+/// Number of dots in a search result's health indicator.
+pub const HEALTH_DOTS: u8 = 5;
+
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// How likely a search result is to download well, from 0 to
+/// [`HEALTH_DOTS`].
+///
+/// Starts from the seeder count in rough orders of magnitude, then adds a
+/// dot when seeders outnumber leechers and takes one away when leechers
+/// swamp them.  Counts for uploads less than a day old haven't settled, so
+/// those score at most 3 however many seeders they claim.
+pub fn health_score(seeders: i64, leechers: i64, age_seconds: i64) -> u8 {
+    if seeders <= 0 {
+        return 0;
+    }
+    let mut score: i64 = match seeders {
+        1..=4 => 1,
+        5..=19 => 2,
+        20..=99 => 3,
+        _ => 4,
+    };
+    let ratio = seeders as f64 / leechers.max(1) as f64;
+    if ratio >= 1.0 {
+        score += 1;
+    } else if ratio < 0.25 {
+        score -= 1;
+    }
+    if age_seconds < DAY_SECONDS {
+        score = score.min(3);
+    }
+    score.clamp(1, HEALTH_DOTS as i64) as u8
+}
+
+/// The vertical resolution a release name gives, e.g. 1080 for
+/// `Show.S01E01.1080p.WEB`, or `None` if it gives none.  `4K` and `UHD`
+/// count as 2160.
+pub fn release_resolution(name: &str) -> Option<u32> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .find_map(|word| {
+            let word = word.to_ascii_lowercase();
+            if word == "4k" || word == "uhd" {
+                return Some(2160);
+            }
+            let digits = word.strip_suffix('p').or_else(|| word.strip_suffix('i'))?;
+            let is_number =
+                (3..=4).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit());
+            is_number.then(|| digits.parse().ok()).flatten()
+        })
+}
+
+/// Parse a number from the search API, which sends them as strings and
+/// sometimes sends "", "None" or a size in scientific notation.
+fn parse_api_number(s: &str) -> Option<i64> {
+    let s = s.trim();
+    s.parse().ok().or_else(|| {
+        let n: f64 = s.parse().ok()?;
+        (n.is_finite() && n >= 0.0 && n.fract() == 0.0).then_some(n as i64)
+    })
+}
+
+impl Torrent {
+    /// When the torrent was uploaded, as a Unix timestamp.
+    pub fn added_i64(&self) -> Option<i64> {
+        parse_api_number(&self.added).filter(|added| *added > 0)
+    }
+
+    pub fn seeders_i64(&self) -> Option<i64> {
+        parse_api_number(&self.seeders).filter(|n| *n >= 0)
+    }
+
+    pub fn leechers_i64(&self) -> Option<i64> {
+        parse_api_number(&self.leechers).filter(|n| *n >= 0)
+    }
+
+    pub fn size_bytes(&self) -> Option<usize> {
+        parse_api_number(&self.size).and_then(|size| usize::try_from(size).ok())
+    }
+
+    /// [`health_score`] as of `now`.  Unknown leechers count as none and an
+    /// unknown upload date as old.
+    pub fn health_at(&self, now: i64) -> Option<u8> {
+        let seeders = self.seeders_i64()?;
+        let leechers = self.leechers_i64().unwrap_or(0);
+        let age = self.added_i64().map_or(i64::MAX, |added| now - added);
+        Some(health_score(seeders, leechers, age))
+    }
+
+    /// What the health score is made of, e.g. "42 seeders, 3.1:1 ratio,
+    /// 2 days old".
+    pub fn health_breakdown(&self, now: i64) -> String {
+        let Some(seeders) = self.seeders_i64() else {
+            return "Unknown seeders".to_string();
+        };
+        let plural = if seeders == 1 { "" } else { "s" };
+        let mut parts = vec![format!("{seeders} seeder{plural}")];
+        match self.leechers_i64() {
+            Some(0) => parts.push("no leechers".to_string()),
+            Some(leechers) => {
+                parts.push(format!("{:.1}:1 ratio", seeders as f64 / leechers as f64))
+            }
+            None => {}
+        }
+        if let Some(added) = self.added_i64() {
+            let age = (now - added).max(0);
+            parts.push(match age / DAY_SECONDS {
+                0 => "less than a day old".to_string(),
+                1 => "1 day old".to_string(),
+                days => format!("{days} days old"),
+            });
+        }
+        parts.join(", ")
+    }
+
+    /// Names of the numeric fields that couldn't be parsed.
+    pub fn unparsed_fields(&self) -> Vec<&'static str> {
+        [
+            ("added", self.added_i64().is_none()),
+            ("seeders", self.seeders_i64().is_none()),
+            ("leechers", self.leechers_i64().is_none()),
+            ("size", self.size_bytes().is_none()),
+        ]
+        .into_iter()
+        .filter_map(|(field, unparsed)| unparsed.then_some(field))
+        .collect()
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct TorrentInfo {
+    pub added: i64,
+    pub category: u32,
+    pub descr: Option<String>,
+    pub download_count: Option<String>,
+    pub id: u32,
+    pub info_hash: String,
+    pub leechers: u32,
+    pub name: String,
+    pub num_files: Option<u32>,
+    pub seeders: u32,
+    pub size: u64,
+    pub status: String,
+    pub username: String,
+    pub magnet: Option<String>,
+}
+
+impl TorrentInfo {
+    /// Whether this is the search API's answer for a listing that doesn't
+    /// exist (any more): no id, and an info hash of zeros.
+    pub fn is_gone(&self) -> bool {
+        self.id == 0 || self.info_hash.chars().all(|c| c == '0')
+    }
+}
+
+/// Categorises errors so the frontend can branch on the kind.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Privateer search/info API errors (network, parsing, etc.).
+    PirateSearch,
+    /// Too many search/info requests are queued; try again shortly.
+    RateLimited,
+    /// A search listing doesn't exist, e.g. it was taken down.
+    ListingNotFound,
+    /// Could not connect to the Transmission RPC daemon.
+    TransmissionConnection,
+    /// Transmission rejected the configured username/password.
+    TransmissionAuth,
+    /// Transmission RPC returned a non-OK response.
+    TransmissionRpc,
+    /// Configuration file I/O or serialisation errors.
+    Config,
+    /// A URL could not be parsed.
+    InvalidUrl,
+    /// Serialisation / deserialisation errors on the invoke bridge.
+    Serialization,
+    /// Filesystem copy operation failed.
+    Copy,
+    /// The downloads ledger could not be written.
+    Ledger,
+    /// The destination disk is full.
+    DiskSpace,
+    /// The destination directory isn't mounted or reachable right now.
+    DestinationUnavailable,
+    /// A request took too long to answer.
+    Timeout,
+    /// A media server, webhook or feed rejected the configured credentials.
+    Auth,
+    /// The backend log file could not be read.
+    Log,
+    /// The media server could not be reached or rejected a request.
+    MediaServer,
+    /// The webhook could not be reached or rejected a request.
+    Webhook,
+    /// The post-copy command could not run or failed.
+    Hook,
+    /// An RSS feed could not be fetched or read.
+    Feed,
+    /// The downloads list could not be exported.
+    Export,
+    /// Checking for a newer release failed.
+    Update,
+}
+
+impl ErrorKind {
+    /// Whether errors of this kind usually go away on their own, so trying
+    /// again is worth offering.
+    pub fn is_transient(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::PirateSearch
+                | ErrorKind::RateLimited
+                | ErrorKind::TransmissionConnection
+                | ErrorKind::Ledger
+                | ErrorKind::DestinationUnavailable
+                | ErrorKind::Timeout
+        )
+    }
+}
+
+/// Application error sent across the Tauri invoke bridge.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Whether running the same command again might succeed.  Missing from
+    /// errors serialized by older builds.
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl AppError {
+    /// An error that is retryable if its kind is transient.
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
             kind,
             message: message.into(),
+            retryable: kind.is_transient(),
+        }
+    }
+
+    /// Override whether the command is worth running again.
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "c9e15763f722f23e98a29decdfae341b98d53056";
+
+    #[test]
+    fn magnet_links() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:{}&dn=Cosmos+Laundromat%20(2015)&tr=udp%3A%2F%2Ftracker",
+            HASH.to_uppercase()
+        );
+        assert_eq!(
+            parse_magnet(&uri),
+            Some(MagnetLink {
+                info_hash: HASH.parse().unwrap(),
+                name: "Cosmos Laundromat (2015)".into(),
+                uri: uri.clone(),
+            })
+        );
+
+        let base32 = parse_magnet("magnet:?xt=urn:btih:ZHQVOY7XELZD5GFCTXWN7LRUDOMNKMCW").unwrap();
+        assert_eq!(base32.info_hash.as_str(), HASH);
+        assert_eq!(base32.name, HASH);
+    }
+
+    #[test]
+    fn transmission_status_codes() {
+        let known = [
+            (0, TransmissionStatus::Stopped, "Stopped"),
+            (1, TransmissionStatus::QueuedVerify, "Queued (Verify)"),
+            (2, TransmissionStatus::Verifying, "Verifying"),
+            (3, TransmissionStatus::QueuedDownload, "Queued"),
+            (4, TransmissionStatus::Downloading, "Downloading"),
+            (5, TransmissionStatus::QueuedSeed, "Queued (Seed)"),
+            (6, TransmissionStatus::Seeding, "Seeding"),
+        ];
+        for (code, status, label) in known {
+            assert_eq!(status, TransmissionStatus::from_code(code));
+            assert_eq!(label, status.label());
+        }
+
+        let qbittorrent = [
+            ("pausedUP", TransmissionStatus::Stopped),
+            ("stoppedDL", TransmissionStatus::Stopped),
+            ("missingFiles", TransmissionStatus::Stopped),
+            ("checkingResumeData", TransmissionStatus::Verifying),
+            ("queuedDL", TransmissionStatus::QueuedDownload),
+            ("stalledDL", TransmissionStatus::Downloading),
+            ("metaDL", TransmissionStatus::Downloading),
+            ("queuedUP", TransmissionStatus::QueuedSeed),
+            ("stalledUP", TransmissionStatus::Seeding),
+            ("forcedUP", TransmissionStatus::Seeding),
+        ];
+        for (state, status) in qbittorrent {
+            assert_eq!(
+                status,
+                TransmissionStatus::from_qbittorrent(state),
+                "{state}"
+            );
+        }
+        assert_eq!(
+            TransmissionStatus::Unknown(-1),
+            TransmissionStatus::from_qbittorrent("moving")
+        );
+
+        let unknown = TransmissionStatus::from_code(7);
+        assert_eq!(TransmissionStatus::Unknown(7), unknown);
+        assert_eq!("Unknown (7)", unknown.label());
+        let json = serde_json::to_string(&unknown).unwrap();
+        assert_eq!(unknown, serde_json::from_str(&json).unwrap());
+        assert_eq!(
+            TransmissionStatus::Seeding,
+            serde_json::from_str(r#""Seeding""#).unwrap()
+        );
+    }
+
+    #[test]
+    fn search_numbers_may_be_unknown() {
+        let torrent = |added: &str, seeders: &str, size: &str| Torrent {
+            added: added.into(),
+            category: "201".into(),
+            descr: None,
+            download_count: None,
+            id: "1".into(),
+            info_hash: HASH.into(),
+            leechers: "None".into(),
+            name: "Cosmos Laundromat".into(),
+            num_files: None,
+            seeders: seeders.into(),
+            size: size.into(),
+            status: "vip".into(),
+            username: "blender".into(),
+            magnet: None,
+            health: None,
+        };
+
+        let good = torrent("1700000000", " 12 ", "4700000000");
+        assert_eq!(Some(1_700_000_000), good.added_i64());
+        assert_eq!(Some(12), good.seeders_i64());
+        assert_eq!(None, good.leechers_i64());
+        assert_eq!(Some(4_700_000_000), good.size_bytes());
+        assert_eq!(vec!["leechers"], good.unparsed_fields());
+
+        let scientific = torrent("0", "", "4.7E9");
+        assert_eq!(Some(4_700_000_000), scientific.size_bytes());
+        assert_eq!(None, scientific.added_i64());
+        assert_eq!(None, scientific.seeders_i64());
+
+        let weird = torrent("None", "-1", "1.5e-3");
+        assert_eq!(
+            vec!["added", "seeders", "leechers", "size"],
+            weird.unparsed_fields()
+        );
+        assert_eq!(None, torrent("", "", "NaN").size_bytes());
+    }
+
+    #[test]
+    fn health_weighs_seeders_ratio_and_age() {
+        let old = 30 * DAY_SECONDS;
+        assert_eq!(0, health_score(0, 50, old));
+        assert_eq!(2, health_score(4, 0, old));
+        assert_eq!(4, health_score(42, 10, old));
+        assert_eq!(5, health_score(500, 100, old));
+        // Leechers swamping the seeders cost a dot
+        assert_eq!(3, health_score(500, 5000, old));
+        assert_eq!(1, health_score(1, 10, old));
+        // A fresh upload's counts are capped until they settle
+        assert_eq!(3, health_score(500, 100, DAY_SECONDS / 2));
+
+        let now = 1_700_000_000;
+        let torrent = Torrent {
+            added: (now - 2 * DAY_SECONDS - 60).to_string(),
+            category: "201".into(),
+            descr: None,
+            download_count: None,
+            id: "1".into(),
+            info_hash: HASH.into(),
+            leechers: "13".into(),
+            name: "Cosmos Laundromat".into(),
+            num_files: None,
+            seeders: "42".into(),
+            size: "4700000000".into(),
+            status: "vip".into(),
+            username: "blender".into(),
+            magnet: None,
+            health: None,
+        };
+        assert_eq!(Some(4), torrent.health_at(now));
+        assert_eq!(
+            "42 seeders, 3.2:1 ratio, 2 days old",
+            torrent.health_breakdown(now)
+        );
+
+        let unknown = Torrent {
+            seeders: "None".into(),
+            leechers: "None".into(),
+            added: "".into(),
+            ..torrent
+        };
+        assert_eq!(None, unknown.health_at(now));
+        assert_eq!("Unknown seeders", unknown.health_breakdown(now));
+    }
+
+    #[test]
+    fn release_resolutions() {
+        assert_eq!(
+            Some(1080),
+            release_resolution("The.Bear.S02E05.1080p.WEB.h264-ETHEL")
+        );
+        assert_eq!(
+            Some(720),
+            release_resolution("Some Movie (2024) [720p] [YTS.MX]")
+        );
+        assert_eq!(Some(2160), release_resolution("Dune.Part.Two.2024.4K.HDR"));
+        assert_eq!(Some(576), release_resolution("Old.Show.S01E01.576i.DVB"));
+        assert_eq!(None, release_resolution("Blade.Runner.2049.BluRay.x264"));
+        assert_eq!(None, release_resolution("Some.Show.S01E01.HDTV"));
+    }
+
+    #[test]
+    fn gone_listings() {
+        let info = TorrentInfo {
+            added: 1700000000,
+            category: 208,
+            descr: None,
+            download_count: None,
+            id: 71234567,
+            info_hash: "a".repeat(40),
+            leechers: 3,
+            name: "The.Bear.S02E05.1080p.WEB.h264-ETHEL".to_string(),
+            num_files: Some(1),
+            seeders: 40,
+            size: 1_400_000_000,
+            status: "vip".to_string(),
+            username: "ethel".to_string(),
+            magnet: None,
+        };
+        assert!(!info.is_gone());
+        let gone = TorrentInfo {
+            id: 0,
+            info_hash: "0".repeat(40),
+            name: "Torrent does not exist.".to_string(),
+            ..info.clone()
+        };
+        assert!(gone.is_gone());
+        assert!(TorrentInfo { id: 0, ..info }.is_gone());
+    }
+
+    #[test]
+    fn repeated_copy_crashes_are_a_problem() {
+        let ok = ServiceHealth {
+            ok: true,
+            detail: String::new(),
+        };
+        let mut report = HealthReport {
+            transmission: ok.clone(),
+            search: ok,
+            ..HealthReport::default()
+        };
+        report.copy_engine = CopyEngineStatus {
+            crashes: UNHEALTHY_AFTER_CRASHES - 1,
+            last_panic: Some("index out of bounds".into()),
+        };
+        assert!(report.is_healthy());
+        report.copy_engine.crashes += 1;
+        assert_eq!(
+            vec!["Copy engine unhealthy: crashed 3 times in a row (index out of bounds)"],
+            report.problems()
+        );
+
+        // Reports from before the copy engine was tracked
+        let json = r#"{"transmission":{"ok":true,"detail":""},"destinations":[],
+            "search":{"ok":true,"detail":""},
+            "ledger":{"not_copied":0,"copying":0,"copied":0,"failed":0},
+            "copy_in_flight":false}"#;
+        let report: HealthReport = serde_json::from_str(json).unwrap();
+        assert_eq!(CopyEngineStatus::default(), report.copy_engine);
+    }
+
+    #[test]
+    fn info_hashes_are_normalized() {
+        let upper: InfoHash = HASH.to_uppercase().parse().unwrap();
+        let base32: InfoHash = "ZHQVOY7XELZD5GFCTXWN7LRUDOMNKMCW".parse().unwrap();
+        assert_eq!(HASH, upper.as_str());
+        assert_eq!(upper, base32);
+        assert!("abc123".parse::<InfoHash>().is_err());
+        assert!("g".repeat(40).parse::<InfoHash>().is_err());
+
+        // Ledgers saved before hashes were normalized keep whatever case
+        // Transmission or the search API used.
+        let saved = format!(
+            r#"{{"info_hash": "{}", "name": "Cosmos", "destination": "Movies"}}"#,
+            HASH.to_uppercase()
+        );
+        let entry: DownloadEntry = serde_json::from_str(&saved).unwrap();
+        assert_eq!(upper, entry.info_hash);
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(HASH, json["info_hash"]);
+
+        let invalid = r#"{"info_hash": "abc", "name": "Cosmos", "destination": "Movies"}"#;
+        assert!(serde_json::from_str::<DownloadEntry>(invalid).is_err());
+    }
+
+    #[test]
+    fn connection_level_follows_polls() {
+        let mut status = ConnectionStatus::default();
+        assert_eq!(ConnectionLevel::Unknown, status.level());
+        status.record(Err("refused".into()), 1);
+        assert_eq!(ConnectionLevel::Down, status.level());
+        status.record(Ok(()), 2);
+        assert_eq!(ConnectionLevel::Connected, status.level());
+        assert_eq!(None, status.last_error);
+        for now in 3..3 + DOWN_AFTER_FAILURES as i64 {
+            assert_ne!(ConnectionLevel::Down, status.level());
+            status.record(Err("timed out".into()), now);
+            assert_eq!(Some("timed out"), status.last_error.as_deref());
+        }
+        assert_eq!(ConnectionLevel::Down, status.level());
+    }
+
+    #[test]
+    fn settings_fields() {
+        assert_eq!(Ok(9091), parse_port(" 9091 "));
+        for port in ["", "0", "65536", "90 91", "-1"] {
+            assert_eq!(Err(SettingsProblem::Port), parse_port(port), "{port}");
+        }
+        assert_eq!(Ok(()), check_host("seedbox.local"));
+        assert_eq!(Err(SettingsProblem::HostEmpty), check_host(" "));
+        assert_eq!(Err(SettingsProblem::HostSpaces), check_host("seed box"));
+        assert_eq!(
+            Err(SettingsProblem::HostScheme),
+            check_host("http://seedbox")
+        );
+        assert_eq!(Ok(()), check_credentials("", ""));
+        assert_eq!(
+            Err(SettingsProblem::Credentials),
+            check_credentials("me", "")
+        );
+        assert_eq!(Ok(()), check_dir("/Volumes/Media"));
+        assert_eq!(Ok(()), check_dir(r"D:\Media"));
+        assert_eq!(Err(SettingsProblem::RelativeDir), check_dir("Media/Movies"));
+        assert_eq!(Err(SettingsProblem::RelativeDir), check_dir("~/Movies"));
+
+        let mut config = TransmissionConfig::default();
+        assert_eq!(Ok(()), config.validate());
+        config.profiles[0].username = Some("me".into());
+        assert_eq!(Err(SettingsProblem::Credentials), config.validate());
+    }
+
+    #[test]
+    fn destination_dirs_must_not_overlap() {
+        assert_eq!(
+            Ok(()),
+            check_destination_dirs("/Media/Movies", "/Media/Shows")
+        );
+        assert_eq!(Ok(()), check_destination_dirs("/Media/Movies", ""));
+        assert_eq!(
+            Ok(()),
+            check_destination_dirs("/Media/Film", "/Media/Filmstrips")
+        );
+        assert_eq!(
+            Err(SettingsProblem::SameDestinationDirs),
+            check_destination_dirs("/Media/", "//Media")
+        );
+        assert_eq!(
+            Err(SettingsProblem::NestedDestinationDirs),
+            check_destination_dirs("/Media", "/Media/Shows")
+        );
+        assert_eq!(
+            Err(SettingsProblem::NestedDestinationDirs),
+            check_destination_dirs(r"D:\Media\Movies\Shows", r"D:\Media\Movies")
+        );
+
+        // Only the same directory keeps the config from being saved
+        let mut config = TransmissionConfig {
+            movies_dir: Some("/Media".into()),
+            shows_dir: Some("/Media/Shows".into()),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), config.validate());
+        config.shows_dir = Some("/Media/".into());
+        assert_eq!(Err(SettingsProblem::SameDestinationDirs), config.validate());
+    }
+
+    #[test]
+    fn profiles_are_checked_together() {
+        let seedbox = ServerProfile {
+            id: 1,
+            name: "Seedbox".into(),
+            host: "seedbox.local".into(),
+            ..Default::default()
+        };
+        let mut config = TransmissionConfig {
+            profiles: vec![ServerProfile::default(), seedbox.clone()],
+            active_profile: 1,
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), config.validate());
+        assert_eq!(seedbox, config.active());
+
+        config.profiles[1].name = " default".into();
+        assert_eq!(
+            Err(SettingsProblem::DuplicateProfileName),
+            config.validate()
+        );
+        config.profiles[1].name = "".into();
+        assert_eq!(Err(SettingsProblem::ProfileName), config.validate());
+
+        config.profiles.pop();
+        assert_eq!(Err(SettingsProblem::NoSuchProfile), config.validate());
+        assert_eq!(ServerProfile::default(), config.active());
+    }
+
+    #[test]
+    fn saved_password_is_kept_unless_changed() {
+        let profile = |id, password: &str| ServerProfile {
+            id,
+            name: format!("Server {id}"),
+            username: Some("me".into()),
+            password: Some(password.into()),
+            ..Default::default()
+        };
+        let saved = TransmissionConfig {
+            profiles: vec![profile(0, "hunter2"), profile(1, "swordfish")],
+            ..Default::default()
+        };
+        let mut unchanged = saved.clone().masked();
+        for profile in &unchanged.profiles {
+            assert_eq!(Some(SAVED_PASSWORD), profile.password.as_deref());
+        }
+        // Matched by id, not position
+        unchanged.profiles.reverse();
+        unchanged.keep_saved_password(&saved);
+        unchanged.profiles.reverse();
+        assert_eq!(saved, unchanged);
+
+        let mut cleared = saved.clone();
+        cleared.profiles[0].username = None;
+        cleared.profiles[0].password = None;
+        cleared.keep_saved_password(&saved);
+        assert_eq!(None, cleared.profiles[0].password);
+        let added = profile(2, SAVED_PASSWORD);
+        let mut config = TransmissionConfig {
+            profiles: vec![added],
+            ..Default::default()
+        };
+        config.keep_saved_password(&saved);
+        assert_eq!(None, config.profiles[0].password);
+        assert_eq!(
+            None,
+            TransmissionConfig::default().masked().profiles[0].password
+        );
+    }
+
+    #[test]
+    fn session_fields() {
+        assert_eq!(Ok(512), parse_speed_limit(" 512 "));
+        assert_eq!(Err(SettingsProblem::SpeedLimit), parse_speed_limit("1.5"));
+        assert_eq!(Err(SettingsProblem::SpeedLimit), parse_speed_limit("-1"));
+        assert_eq!(Ok(1.5), parse_seed_ratio("1.5"));
+        assert_eq!(Err(SettingsProblem::SeedRatio), parse_seed_ratio("-2"));
+        assert_eq!(Err(SettingsProblem::SeedRatio), parse_seed_ratio("NaN"));
+
+        assert_eq!(Ok(()), SessionConfig::default().validate());
+        let session = SessionConfig {
+            download_dir: Some(" ".into()),
+            ..Default::default()
+        };
+        assert_eq!(Err(SettingsProblem::DownloadDirEmpty), session.validate());
+        let session = SessionConfig {
+            incomplete_dir_enabled: Some(true),
+            incomplete_dir: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(Err(SettingsProblem::IncompleteDirEmpty), session.validate());
+    }
+
+    #[test]
+    fn directories_lose_trailing_separators() {
+        assert_eq!("/Media/Shows", normalize_dir("/Media/Shows/"));
+        assert_eq!("/Volumes/Media", normalize_dir(" /Volumes/Media// "));
+        assert_eq!(r"D:\Media\Movies", normalize_dir(r"D:\Media\Movies\"));
+        assert_eq!("/", normalize_dir("//"));
+        assert_eq!(r"C:\", normalize_dir(r"C:\"));
+        assert_eq!("", normalize_dir(""));
+    }
+
+    #[test]
+    fn quiet_hours() {
+        let time = |s| TimeOfDay::parse(s).unwrap();
+        let mut config = TransmissionConfig {
+            quiet_hours: Some((time("21:00"), time("23:00"))),
+            ..Default::default()
+        };
+        assert_eq!(config.quiet_until(time("20:59")), None);
+        assert_eq!(config.quiet_until(time("21:00")), Some(time("23:00")));
+        assert_eq!(config.quiet_until(time("23:00")), None);
+
+        // Past midnight
+        config.quiet_hours = Some((time("22:30"), time("06:00")));
+        assert_eq!(config.quiet_until(time("23:15")), Some(time("06:00")));
+        assert_eq!(config.quiet_until(time("05:59")), Some(time("06:00")));
+        assert_eq!(config.quiet_until(time("12:00")), None);
+
+        let json = serde_json::to_string(&config.quiet_hours).unwrap();
+        assert_eq!(json, r#"["22:30","06:00"]"#);
+        assert!(serde_json::from_str::<TimeOfDay>(r#""24:00""#).is_err());
+        assert_eq!(TimeOfDay::parse("7:05").unwrap().to_string(), "07:05");
+    }
+
+    #[test]
+    fn copies_are_blocked_without_a_directory() {
+        let config = TransmissionConfig {
+            movies_dir: Some("/mnt/movies".to_string()),
+            shows_dir: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(None, config.copy_blocked_reason(Destination::Movies));
+        assert_eq!(
+            Some("Shows directory not configured".to_string()),
+            config.copy_blocked_reason(Destination::Shows)
+        );
+        let unset = TransmissionConfig::default();
+        assert!(unset.copy_blocked_reason(Destination::Movies).is_some());
+    }
+
+    #[test]
+    fn seed_limits_per_destination() {
+        let config = TransmissionConfig {
+            movies_seed_limits: SeedLimits {
+                seed_ratio_limit: Some(1.5),
+                seed_time_limit: None,
+            },
+            shows_seed_limits: SeedLimits {
+                seed_ratio_limit: Some(3.0),
+                seed_time_limit: Some(60),
+            },
+            ..Default::default()
+        };
+        let movies = config.seed_limits(Destination::Movies);
+        let shows = config.seed_limits(Destination::Shows);
+        assert!(movies.is_met(1.5, 0));
+        assert!(!shows.is_met(1.5, 0));
+        assert!(shows.is_met(0.2, 3600));
+        assert!(!shows.is_met(0.2, 3599));
+        // Infinite, and not available
+        assert!(movies.is_met(-2.0, 0));
+        assert!(!movies.is_met(-1.0, 0));
+        assert!(!SeedLimits::default().is_met(10.0, i64::MAX));
+
+        assert_eq!(Ok(()), config.validate());
+        let config = TransmissionConfig {
+            shows_seed_limits: SeedLimits {
+                seed_ratio_limit: Some(-1.0),
+                seed_time_limit: None,
+            },
+            ..Default::default()
+        };
+        assert_eq!(Err(SettingsProblem::SeedRatio), config.validate());
+    }
+
+    #[test]
+    fn copy_settings_default_when_missing() {
+        // Saved before any of the copy settings existed
+        let config: TransmissionConfig =
+            serde_json::from_str(r#"{ "movies_dir": "/Media/Movies" }"#).unwrap();
+        assert_eq!(
+            TransmissionConfig {
+                movies_dir: Some("/Media/Movies".into()),
+                ..Default::default()
+            },
+            config
+        );
+
+        let config = TransmissionConfig {
+            copy_backend: CopyBackend::Sftp(SftpConfig::default()),
+            copy_with_rsync: true,
+            clean_movie_names: true,
+            rename_episodes: true,
+            episode_template: "{show} {season}x{episode:02}".into(),
+            quiet_hours: Some((TimeOfDay::default(), TimeOfDay::default())),
+            ..Default::default()
+        };
+        let saved = serde_json::to_string(&config).unwrap();
+        assert_eq!(config, serde_json::from_str(&saved).unwrap());
+    }
+
+    #[test]
+    fn app_info_for_bug_reports() {
+        let info = AppInfo {
+            version: "0.2.0".into(),
+            git_hash: Some("abc1234".into()),
+            tauri_version: "2.5.0".into(),
+            config_path: "/data/transmission_config.json".into(),
+            ..Default::default()
+        };
+        let text = info.describe();
+        assert!(text.starts_with("Privateer 0.2.0 (abc1234)\nTauri 2.5.0\nWebview unknown\n"));
+        assert!(text.contains("Client: unknown\n"));
+        assert!(text.contains("Config: /data/transmission_config.json"));
+
+        let info = AppInfo {
+            daemon: Some(DaemonInfo::transmission("4.0.5 (a6fe2a64aa)".into(), 17)),
+            ..info
+        };
+        assert!(
+            info.describe()
+                .contains("Client: Transmission 4.0.5 (a6fe2a64aa), RPC version 17\n")
+        );
+    }
+
+    #[test]
+    fn capabilities_follow_the_rpc_version() {
+        let old = ClientCapabilities::for_rpc_version(14);
+        assert_eq!(ClientFeature::ALL.to_vec(), old.missing());
+        let v2_94 = ClientCapabilities::for_rpc_version(15);
+        assert_eq!(
+            ClientCapabilities {
+                rename_path: true,
+                free_space: true,
+                labels: false,
+            },
+            v2_94
+        );
+        assert_eq!(
+            ClientCapabilities::ALL,
+            ClientCapabilities::for_rpc_version(16)
+        );
+        assert_eq!(
+            ClientCapabilities::ALL,
+            ClientCapabilities::for_rpc_version(17)
+        );
+
+        let daemon = DaemonInfo::transmission("2.94 (d8e60ee44f)".into(), 15);
+        assert_eq!(
+            vec!["Labels need Transmission 3.00 or newer".to_string()],
+            daemon.limitations()
+        );
+    }
+
+    #[test]
+    fn hash_only_magnet_links() {
+        let name = "Cosmos Laundromat (2015) & Friends";
+        let link = parse_magnet(&magnet_uri(HASH, name)).unwrap();
+        assert_eq!(HASH, link.info_hash.as_str());
+        assert_eq!(name, link.name);
+
+        let mut entry =
+            DownloadEntry::new(HASH.parse().unwrap(), name.to_string(), Destination::Movies);
+        assert_eq!(magnet_uri(HASH, name), entry.magnet_uri());
+        entry.magnet = Some(format!("magnet:?xt=urn:btih:{HASH}&tr=udp%3A%2F%2Ftracker"));
+        assert_eq!(entry.magnet, Some(entry.magnet_uri()));
+    }
+
+    #[test]
+    fn ledger_entries_keep_why_copies_failed() {
+        // Saved before failures were kept
+        let saved = r#"{
+            "info_hash": "c9e15763f722f23e98a29decdfae341b98d53056",
+            "name": "Some Show",
+            "destination": "Shows",
+            "copy_state": "Failed"
+        }"#;
+        let mut entry: DownloadEntry = serde_json::from_str(saved).unwrap();
+        assert_eq!(CopyState::Failed, entry.copy_state);
+        assert_eq!(None, entry.failure);
+        assert_eq!(DownloadOrigin::User, entry.origin);
+        assert_eq!(None, entry.copy_throughput());
+
+        entry.copy_state = CopyState::Unconfigured;
+        entry.failure = Some("disk full".into());
+        let resaved = serde_json::to_string(&entry).unwrap();
+        assert_eq!(entry, serde_json::from_str(&resaved).unwrap());
+        assert!(entry.copy_state.is_pending());
+        assert!(!CopyState::Copying.is_pending());
+    }
+
+    #[test]
+    fn ledger_entries_from_before_extra_targets_have_one_target() {
+        let saved = r#"{
+            "info_hash": "c9e15763f722f23e98a29decdfae341b98d53056",
+            "name": "Some Show",
+            "destination": "Shows",
+            "copy_state": "Copied",
+            "copied_path": "/Media/Shows/Some Show"
+        }"#;
+        let mut entry: DownloadEntry = serde_json::from_str(saved).unwrap();
+        assert!(entry.extra_targets.is_empty());
+        let targets = entry.targets();
+        assert_eq!(1, targets.len());
+        assert_eq!(Destination::Shows, targets[0].destination);
+        assert_eq!(CopyState::Copied, targets[0].copy_state);
+        assert_eq!(entry.copied_path, targets[0].copied_path);
+        assert_eq!(CopyState::Copied, entry.overall_copy_state());
+
+        entry
+            .extra_targets
+            .push(CopyTarget::new(Destination::Movies));
+        assert_eq!(CopyState::NotCopied, entry.overall_copy_state());
+        entry.update_target(1, |target| target.copy_state = CopyState::Failed);
+        assert_eq!(CopyState::Failed, entry.overall_copy_state());
+        entry.update_targets(|target| target.failure = Some("disk full".into()));
+        assert_eq!(Some("disk full"), entry.failure.as_deref());
+        assert_eq!(entry.failure, entry.extra_targets[0].failure);
+        assert_eq!(CopyState::Copied, entry.copy_state);
+        let resaved = serde_json::to_string(&entry).unwrap();
+        assert_eq!(entry, serde_json::from_str(&resaved).unwrap());
+    }
+
+    #[test]
+    fn targets_copy_to_their_own_dir_or_their_destination() {
+        // Saved before targets could have directories of their own
+        let saved = r#"{"destination": "Shows", "copy_state": "Copied"}"#;
+        let shows: CopyTarget = serde_json::from_str(saved).unwrap();
+        assert_eq!(None, shows.dir);
+        let scratch = CopyTarget::in_dir(Destination::Shows, "/Volumes/Scratch".into());
+        assert!(shows.same_place(&CopyTarget::new(Destination::Shows)));
+        assert!(!shows.same_place(&scratch));
+
+        let config = TransmissionConfig {
+            shows_dir: Some("/Media/Shows".into()),
+            ..Default::default()
+        };
+        assert_eq!(Some("/Media/Shows"), shows.copy_dir(&config));
+        assert_eq!(Some("/Volumes/Scratch"), scratch.copy_dir(&config));
+        assert_eq!(None, shows.copy_dir(&TransmissionConfig::default()));
+        assert_eq!("Shows in /Volumes/Scratch", scratch.label());
+
+        let download = NewDownload {
+            info_hash: HASH.parse().unwrap(),
+            name: "Some Show".to_string(),
+            destination: Destination::Shows,
+            extra_destinations: vec![Destination::Movies],
+            extra_dirs: vec![TargetDir {
+                destination: Destination::Shows,
+                dir: "/Volumes/Scratch".into(),
+            }],
+        };
+        assert_eq!(
+            vec![CopyTarget::new(Destination::Movies), scratch],
+            download.extra_targets()
+        );
+    }
+
+    #[test]
+    fn copy_states_combine_to_what_needs_attention() {
+        use CopyState::*;
+        assert_eq!(NotCopied, CopyState::combine([]));
+        assert_eq!(Copied, CopyState::combine([Copied, Copied]));
+        assert_eq!(NotCopied, CopyState::combine([Copied, NotCopied]));
+        assert_eq!(Unconfigured, CopyState::combine([Copied, Unconfigured]));
+        assert_eq!(Failed, CopyState::combine([Unconfigured, Failed, Copied]));
+        assert_eq!(Copying, CopyState::combine([Failed, Copying]));
+    }
+
+    #[test]
+    fn malformed_magnet_links() {
+        for uri in [
+            "",
+            "https://example.com/?xt=urn:btih:c9e15763f722f23e98a29decdfae341b98d53056",
+            "magnet:?dn=No+Hash",
+            "magnet:?xt=urn:btih:c9e157",
+            "magnet:?xt=urn:btih:ZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZ1",
+            "magnet:?xt=urn:sha1:c9e15763f722f23e98a29decdfae341b98d53056",
+        ] {
+            assert_eq!(parse_magnet(uri), None, "{uri}");
+        }
+    }
+
+    #[test]
+    fn errors_from_older_builds_are_not_retryable() {
+        let old = r#"{"kind":"TransmissionConnection","message":"refused"}"#;
+        let error: AppError = serde_json::from_str(old).unwrap();
+        assert_eq!(ErrorKind::TransmissionConnection, error.kind);
+        assert!(!error.retryable);
+
+        let error = AppError::new(ErrorKind::Timeout, "timed out after 10s");
+        assert!(error.retryable);
+        let resaved = serde_json::to_string(&error).unwrap();
+        assert_eq!(error, serde_json::from_str(&resaved).unwrap());
+        assert!(!AppError::new(ErrorKind::Auth, "401").retryable);
+    }
+
+    #[test]
+    fn durations_and_etas() {
+        assert_eq!("0s", format_duration(0));
+        assert_eq!("45s", format_duration(45));
+        assert_eq!("12m", format_duration(12 * 60 + 30));
+        assert_eq!("3h 5m", format_duration(3 * 3600 + 5 * 60));
+        assert_eq!("2d 3h", format_duration(2 * 86400 + 3 * 3600 + 59));
+        assert_eq!("213503982334d 14h", format_duration(u64::MAX / 1000));
+
+        assert_eq!("42s", format_elapsed(42));
+        assert_eq!("9m 12s", format_elapsed(9 * 60 + 12));
+        assert_eq!("1h 5m", format_elapsed(3600 + 5 * 60 + 12));
+
+        assert_eq!("0s", format_eta(0));
+        assert_eq!("1h 0m", format_eta(3600));
+        assert_eq!("unknown", format_eta(-1));
+        assert_eq!("unknown", format_eta(-2));
+        assert_eq!("unknown", format_eta(i64::MIN));
+    }
+
+    #[test]
+    fn sizes_and_rates() {
+        let si = |bytes| format_size(bytes, SizeUnits::Si);
+        assert_eq!("0 B", si(0));
+        assert_eq!("999 B", si(999));
+        assert_eq!("1.0 kB", si(1000));
+        assert_eq!("1.5 MB", si(1_500_000));
+        assert_eq!("1.0 MB", si(999_999));
+        assert_eq!("4.7 GB", si(4_700_000_000));
+        assert_eq!("18.4 EB", si(u64::MAX));
+
+        assert_eq!("0 B/s", format_rate(0, SizeUnits::Si));
+        assert_eq!("0 B/s", format_rate(-5, SizeUnits::Si));
+        assert_eq!("2.5 MB/s", format_rate(2_500_000, SizeUnits::Si));
+        assert_eq!("9.2 EB/s", format_rate(i64::MAX, SizeUnits::Si));
+
+        let copy = CopyThroughput {
+            bytes: 34_224_000_000,
+            seconds: 552.0,
+        };
+        assert_eq!("9m 12s (62.0 MB/s)", copy.describe(SizeUnits::Si));
+        let instant = CopyThroughput {
+            bytes: 5,
+            seconds: 0.0,
+        };
+        assert_eq!(0, instant.rate());
+    }
+
+    #[test]
+    fn sample_ranges() {
+        assert_eq!(SampleRange::of([]), None);
+        assert_eq!(
+            SampleRange::of([5]),
+            Some(SampleRange {
+                min: 5,
+                avg: 5,
+                max: 5
+            })
+        );
+        assert_eq!(
+            SampleRange::of([0, 3_000_000, 1_000_000, 0]),
+            Some(SampleRange {
+                min: 0,
+                avg: 1_000_000,
+                max: 3_000_000
+            })
+        );
+        // Large rates don't overflow the sum
+        assert_eq!(
+            SampleRange::of([i64::MAX, i64::MAX]).map(|range| range.avg),
+            Some(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn locales_are_language_tags() {
+        for locale in ["en", "en-GB", "zh-Hant-TW", "es-419", " de-DE "] {
+            assert_eq!(check_locale(locale), Ok(()), "{locale}");
+        }
+        for locale in ["", "e", "en_GB", "en-", "en GB", "12-GB", "languages-GB"] {
+            assert_eq!(
+                check_locale(locale),
+                Err(SettingsProblem::Locale),
+                "{locale}"
+            );
+        }
+    }
+
+    #[test]
+    fn binary_sizes_change_unit_at_powers_of_1024() {
+        let binary = |bytes| format_size(bytes, SizeUnits::Binary);
+        assert_eq!("1000 B", binary(1000));
+        assert_eq!("1023 B", binary(1023));
+        assert_eq!("1.0 KiB", binary(1024));
+        assert_eq!("1.5 KiB", binary(1536));
+        // Just under 1 MiB rounds up into the next unit, not "1024.0 KiB"
+        assert_eq!("1.0 MiB", binary(1024 * 1024 - 1));
+        assert_eq!("1.0 MiB", binary(1024 * 1024));
+        assert_eq!("4.4 GiB", binary(4_700_000_000));
+        assert_eq!("16.0 EiB", binary(u64::MAX));
+
+        // The same sizes either side of each boundary in SI
+        assert_eq!("1.0 kB", format_size(1000, SizeUnits::Si));
+        assert_eq!("1.0 kB", format_size(1023, SizeUnits::Si));
+        assert_eq!("1.0 MB", format_size(1024 * 1024 - 1, SizeUnits::Si));
+
+        assert_eq!("2.0 MiB/s", format_rate(2 * 1024 * 1024, SizeUnits::Binary));
+    }
+
+    #[test]
+    fn relative_times() {
+        let now = 1_700_000_000;
+        assert_eq!("just now", format_relative(now, now));
+        assert_eq!("just now", format_relative(now, now - 59));
+        assert_eq!("just now", format_relative(now, now + 30));
+        assert_eq!("5m ago", format_relative(now, now - 300));
+        assert_eq!("1d 0h ago", format_relative(now, now - 86400));
+        assert_eq!("in 2h 0m", format_relative(now, now + 7200));
+        assert_eq!("29d 23h ago", format_relative(now, now - 30 * 86400 + 3600));
+        assert_eq!("1 month ago", format_relative(now, now - 30 * 86400));
+        assert_eq!("11 months ago", format_relative(now, now - 364 * 86400));
+        assert_eq!("1 year ago", format_relative(now, now - 400 * 86400));
+        assert_eq!("8 years ago", format_relative(now, 1_440_000_000));
+        assert!(format_relative(i64::MAX, i64::MIN).ends_with(" ago"));
+        assert!(format_relative(i64::MIN, i64::MAX).starts_with("in "));
+    }
+
+    /// The JSON object keys `value` serializes to.
+    fn keys(value: impl serde::Serialize) -> Vec<String> {
+        match serde_json::to_value(value).unwrap() {
+            serde_json::Value::Object(map) => map.keys().cloned().collect(),
+            other => panic!("expected an object, got {other}"),
         }
     }
+
+    #[test]
+    fn command_args_are_camel_case() {
+        let info_hash: InfoHash = HASH.parse().unwrap();
+        let add = AddDownloadArgs {
+            info_hash: info_hash.clone(),
+            name: "Some Show".to_string(),
+            destination: Destination::Shows,
+            extra_destinations: vec![],
+            extra_dirs: vec![],
+        };
+        let mut add_keys = keys(&add);
+        add_keys.sort();
+        assert_eq!(
+            vec![
+                "destination",
+                "extraDestinations",
+                "extraDirs",
+                "infoHash",
+                "name"
+            ],
+            add_keys
+        );
+        let json = serde_json::to_string(&add).unwrap();
+        assert_eq!(add, serde_json::from_str(&json).unwrap());
+        let snake = format!(
+            r#"{{"info_hash":"{}","name":"Some Show","destination":"Shows"}}"#,
+            HASH.to_uppercase()
+        );
+        assert_eq!(add, serde_json::from_str(&snake).unwrap());
+
+        let one = InfoHashArgs {
+            info_hash: info_hash.clone(),
+        };
+        assert_eq!(vec!["infoHash"], keys(&one));
+        let json = serde_json::to_string(&one).unwrap();
+        assert_eq!(one, serde_json::from_str(&json).unwrap());
+
+        let many = HashesArgs {
+            hashes: vec![info_hash.clone()],
+        };
+        assert_eq!(vec!["hashes"], keys(&many));
+        let json = serde_json::to_string(&many).unwrap();
+        assert_eq!(many, serde_json::from_str(&json).unwrap());
+
+        let note = UpdateNoteArgs {
+            info_hash: info_hash.clone(),
+            note: Some("for mum".to_string()),
+        };
+        let mut note_keys = keys(&note);
+        note_keys.sort();
+        assert_eq!(vec!["infoHash", "note"], note_keys);
+        let json = serde_json::to_string(&note).unwrap();
+        assert_eq!(note, serde_json::from_str(&json).unwrap());
+
+        let rename = RenameTorrentArgs {
+            info_hash,
+            old_path: "Some Show".to_string(),
+            new_name: "Some Show S01".to_string(),
+        };
+        let mut rename_keys = keys(&rename);
+        rename_keys.sort();
+        assert_eq!(vec!["infoHash", "newName", "oldPath"], rename_keys);
+        let snake = format!(
+            r#"{{"info_hash":"{HASH}","old_path":"Some Show","new_name":"Some Show S01"}}"#
+        );
+        assert_eq!(rename, serde_json::from_str(&snake).unwrap());
+
+        let transfer = TransferLedgerArgs {
+            old_hash: HASH.parse().unwrap(),
+            new_hash: "b".repeat(40).parse().unwrap(),
+        };
+        let mut transfer_keys = keys(&transfer);
+        transfer_keys.sort();
+        assert_eq!(vec!["newHash", "oldHash"], transfer_keys);
+        let json = serde_json::to_string(&transfer).unwrap();
+        assert_eq!(transfer, serde_json::from_str(&json).unwrap());
+
+        let auto_assign = AutoAssignArgs {
+            name_filter: Some("bear".to_string()),
+        };
+        assert_eq!(vec!["nameFilter"], keys(&auto_assign));
+        let snake = r#"{"name_filter":"bear"}"#;
+        assert_eq!(auto_assign, serde_json::from_str(snake).unwrap());
+        let empty: AutoAssignArgs = serde_json::from_str("{}").unwrap();
+        assert_eq!(None, empty.name_filter);
+
+        let remove = RemoveTorrentsArgs {
+            hashes: vec![HASH.parse().unwrap()],
+            delete_data: true,
+            confirm: true,
+        };
+        let mut remove_keys = keys(&remove);
+        remove_keys.sort();
+        assert_eq!(vec!["confirm", "deleteData", "hashes"], remove_keys);
+        // Without `confirm` a removal is refused, not run
+        let unconfirmed = format!(r#"{{"hashes":["{HASH}"],"delete_data":true}}"#);
+        let unconfirmed: RemoveTorrentsArgs = serde_json::from_str(&unconfirmed).unwrap();
+        assert!(!unconfirmed.confirm);
+        assert_eq!(ConfirmArgs::default(), serde_json::from_str("{}").unwrap());
+    }
+
+    #[test]
+    fn transmission_numbers_are_sanitized() {
+        assert_eq!(0.0, sanitize_fraction(f64::NAN));
+        assert_eq!(0.0, sanitize_fraction(-0.25));
+        assert_eq!(0.0, sanitize_fraction(f64::NEG_INFINITY));
+        assert_eq!(0.5, sanitize_fraction(0.5));
+        assert_eq!(1.0, sanitize_fraction(1.0000001));
+        assert_eq!(1.0, sanitize_fraction(f64::INFINITY));
+
+        assert_eq!(0, sanitize_rate(-1));
+        assert_eq!(0, sanitize_rate(i64::MIN));
+        assert_eq!(0, sanitize_rate(0));
+        assert_eq!(2_500_000, sanitize_rate(2_500_000));
+    }
+
+    #[test]
+    fn destination_usage_levels() {
+        let usage = |total_bytes, free_bytes| DestinationUsage {
+            destination: Destination::Movies,
+            path: "/media/movies".to_string(),
+            total_bytes,
+            free_bytes,
+            copied: 0,
+            copied_bytes: 0,
+            pending: 0,
+        };
+        assert_eq!(None, usage(None, Some(10)).used_fraction());
+        assert_eq!(None, usage(Some(0), Some(0)).used_fraction());
+        assert_eq!(Some(0.25), usage(Some(100), Some(75)).used_fraction());
+        // Free space can read above the total on some network shares
+        assert_eq!(Some(0.0), usage(Some(100), Some(120)).used_fraction());
+
+        assert_eq!(UsageLevel::Ok, usage(None, None).level());
+        assert_eq!(UsageLevel::Ok, usage(Some(100), Some(15)).level());
+        assert_eq!(UsageLevel::NearlyFull, usage(Some(100), Some(14)).level());
+        assert_eq!(UsageLevel::NearlyFull, usage(Some(100), Some(5)).level());
+        assert_eq!(UsageLevel::Full, usage(Some(100), Some(4)).level());
+        assert_eq!(UsageLevel::Full, usage(Some(100), Some(0)).level());
+    }
 }
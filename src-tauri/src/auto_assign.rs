@@ -0,0 +1,182 @@
+//! Assigning destinations to the torrents the download client already has,
+//! e.g. after pointing Privateer at a client with years of history.
+//!
+//! Each torrent that isn't in the ledger is run through the [`RULES`], each
+//! of which may name a destination.  A torrent is proposed for the
+//! destination its rules agree on; one no rule recognises, or whose rules
+//! disagree, is left unassigned.  Nothing is recorded here: the user
+//! confirms the proposals, which are then recorded in one `add_downloads`.
+
+use privateer_wire_types::{AutoAssignPreview, Destination, DownloadEntry, NewDownload};
+
+use crate::download_client::ClientTorrent;
+use crate::release_name::{Episode, Movie};
+
+/// A rule: the destination it gives a torrent, if it recognises it.
+type Rule = fn(&ClientTorrent) -> Option<Destination>;
+
+/// The rules every torrent is run through.  Rules by tracker or label go
+/// here too.
+const RULES: &[Rule] = &[by_release_name];
+
+/// Shows for an episode tag like `S02E05`, Movies for a year without one.
+fn by_release_name(t: &ClientTorrent) -> Option<Destination> {
+    if Episode::parse(&t.name).is_some() {
+        Some(Destination::Shows)
+    } else if Movie::parse(&t.name).is_some() {
+        Some(Destination::Movies)
+    } else {
+        None
+    }
+}
+
+/// The destination every one of `rules` that recognises `t` gives it.
+fn destination(rules: &[Rule], t: &ClientTorrent) -> Option<Destination> {
+    let mut destinations = rules.iter().filter_map(|rule| rule(t));
+    let first = destinations.next()?;
+    destinations.all(|d| d == first).then_some(first)
+}
+
+/// [`propose`] by `rules`.
+fn propose_with(
+    rules: &[Rule],
+    torrents: &[ClientTorrent],
+    ledger: &[DownloadEntry],
+    name_filter: Option<&str>,
+) -> AutoAssignPreview {
+    let name_filter = name_filter
+        .map(|filter| filter.trim().to_lowercase())
+        .filter(|filter| !filter.is_empty());
+    let mut unassigned = torrents
+        .iter()
+        .filter_map(|t| Some((t, t.hash.as_ref()?)))
+        .filter(|(_, hash)| !ledger.iter().any(|e| &e.info_hash == *hash))
+        .filter(|(t, _)| {
+            name_filter
+                .as_deref()
+                .is_none_or(|filter| t.name.to_lowercase().contains(filter))
+        })
+        .collect::<Vec<_>>();
+    unassigned.sort_by_key(|(t, _)| t.name.to_lowercase());
+
+    let mut preview = AutoAssignPreview::default();
+    for (t, hash) in unassigned {
+        match destination(rules, t) {
+            Some(destination) => preview.assignments.push(NewDownload {
+                info_hash: hash.clone(),
+                name: t.name.clone(),
+                destination,
+                extra_destinations: vec![],
+                extra_dirs: vec![],
+            }),
+            None => preview.ambiguous.push(t.name.clone()),
+        }
+    }
+    preview
+}
+
+/// Propose a destination for each of `torrents` that isn't in `ledger`,
+/// only those whose name contains `name_filter` (ignoring case) if given.
+pub fn propose(
+    torrents: &[ClientTorrent],
+    ledger: &[DownloadEntry],
+    name_filter: Option<&str>,
+) -> AutoAssignPreview {
+    propose_with(RULES, torrents, ledger, name_filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use privateer_wire_types::InfoHash;
+
+    use super::*;
+    use crate::ledger;
+
+    fn hash(c: char) -> InfoHash {
+        c.to_string().repeat(40).parse().unwrap()
+    }
+
+    fn torrent(c: char, name: &str) -> ClientTorrent {
+        ClientTorrent {
+            hash: Some(hash(c)),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn destinations(preview: &AutoAssignPreview) -> Vec<(&str, Destination)> {
+        preview
+            .assignments
+            .iter()
+            .map(|a| (a.name.as_str(), a.destination))
+            .collect()
+    }
+
+    #[test]
+    fn release_names_pick_the_destination() {
+        let torrents = [
+            torrent('a', "The.Bear.S02E05.1080p.WEB.h264-ETHEL"),
+            torrent('b', "Dune.Part.Two.2024.2160p.WEB-DL"),
+            torrent('c', "Doctor.Who.2005.S01E01.720p"),
+            torrent('d', "Some.Concert.Bootleg.FLAC"),
+        ];
+        let preview = propose(&torrents, &[], None);
+        assert_eq!(
+            vec![
+                ("Doctor.Who.2005.S01E01.720p", Destination::Shows),
+                ("Dune.Part.Two.2024.2160p.WEB-DL", Destination::Movies),
+                ("The.Bear.S02E05.1080p.WEB.h264-ETHEL", Destination::Shows),
+            ],
+            destinations(&preview)
+        );
+        assert_eq!(vec!["Some.Concert.Bootleg.FLAC"], preview.ambiguous);
+        assert_eq!(hash('c'), preview.assignments[0].info_hash);
+    }
+
+    #[test]
+    fn only_unassigned_torrents_matching_the_filter_are_proposed() {
+        let torrents = [
+            torrent('a', "The.Bear.S02E05.1080p"),
+            torrent('b', "The.Bear.S02E06.1080p"),
+            torrent('c', "Dune.2021.1080p"),
+            ClientTorrent {
+                hash: None,
+                ..torrent('d', "The.Bear.S02E07.1080p")
+            },
+        ];
+        let mut ledger = vec![];
+        let name = "The.Bear.S02E06.1080p".to_string();
+        ledger::record_download(
+            &mut ledger,
+            hash('b'),
+            name,
+            Destination::Shows,
+            &[],
+            None,
+            0,
+        );
+        let preview = propose(&torrents, &ledger, Some("  BEAR "));
+        assert_eq!(
+            vec![("The.Bear.S02E05.1080p", Destination::Shows)],
+            destinations(&preview)
+        );
+        assert!(preview.ambiguous.is_empty());
+
+        let preview = propose(&torrents, &ledger, Some(""));
+        assert_eq!(2, preview.assignments.len());
+    }
+
+    #[test]
+    fn rules_that_disagree_leave_it_unassigned() {
+        let movies: Rule = |_| Some(Destination::Movies);
+        let unsure: Rule = |_| None;
+        let torrents = [torrent('a', "The.Bear.S02E05.1080p")];
+
+        let preview = propose_with(&[by_release_name, unsure], &torrents, &[], None);
+        assert_eq!(1, preview.assignments.len());
+
+        let preview = propose_with(&[by_release_name, movies], &torrents, &[], None);
+        assert!(preview.assignments.is_empty());
+        assert_eq!(vec!["The.Bear.S02E05.1080p"], preview.ambiguous);
+    }
+}
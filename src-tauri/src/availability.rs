@@ -0,0 +1,280 @@
+//! How many peers each incomplete download has had over time, to warn about
+//! torrents that are dying.
+//!
+//! The torrents task samples the downloads in the ledger that are still
+//! downloading: every [`SAMPLE_SECS`], and whenever peers start or stop
+//! sending data.  A download that has gone [`dead_after_hours`] without any
+//! peer sending data is flagged as likely dead, with a search for another
+//! copy.
+//!
+//! Unlike the rate history the samples are kept in `availability.json`, so
+//! a torrent that has been dead for days isn't forgotten on restart.  At
+//! most [`MAX_SAMPLES`] are kept for each torrent, and a torrent's samples
+//! are dropped once it finishes, is paused, leaves the ledger or leaves the
+//! download client.
+//!
+//! [`dead_after_hours`]: privateer_wire_types::TransmissionConfig::dead_after_hours
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use privateer_wire_types::{InfoHash, TransmissionStatus, TransmissionTorrent};
+use snafu::ResultExt;
+
+use crate::error::*;
+use crate::release_name;
+
+/// How often a download's peers are sampled while nothing changes.
+pub const SAMPLE_SECS: i64 = 30 * 60;
+
+/// Samples kept for each download, a little over four days' worth.
+pub const MAX_SAMPLES: usize = 200;
+
+/// A gap between samples longer than this, e.g. while the app was closed
+/// or the download was queued, means nothing is known about the time in
+/// between.
+const MAX_GAP_SECS: i64 = 2 * SAMPLE_SECS;
+
+/// A download's peers at one time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilitySample {
+    pub at: i64,
+    pub peers_connected: i64,
+    pub peers_sending_to_us: i64,
+}
+
+impl AvailabilitySample {
+    fn is_unavailable(&self) -> bool {
+        self.peers_sending_to_us == 0
+    }
+}
+
+/// Whether `t`'s peers are tracked: it is in the ledger and not finished.
+fn is_tracked(t: &TransmissionTorrent) -> bool {
+    t.destination.is_some() && t.percent_done < 1.0 && t.status != TransmissionStatus::Stopped
+}
+
+/// The samples of each tracked download, by info hash, kept on disk.
+pub struct Availability {
+    path: PathBuf,
+    samples: HashMap<InfoHash, VecDeque<AvailabilitySample>>,
+}
+
+impl Availability {
+    pub fn load(path: PathBuf) -> Self {
+        let samples = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(samples) => Some(samples),
+                Err(e) => {
+                    log::warn!("ignoring unreadable '{}': {e}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, samples }
+    }
+
+    /// Sample the downloading torrents that are due at `now`, and forget
+    /// the torrents no longer tracked.  Returns whether anything changed.
+    pub fn record(&mut self, torrents: &[TransmissionTorrent], now: i64) -> bool {
+        let before = self.samples.len();
+        self.samples.retain(|hash, _| {
+            torrents
+                .iter()
+                .any(|t| &t.hash_string == hash && is_tracked(t))
+        });
+        let mut changed = self.samples.len() != before;
+        let downloading = torrents
+            .iter()
+            .filter(|t| is_tracked(t) && t.status == TransmissionStatus::Downloading);
+        for t in downloading {
+            let sample = AvailabilitySample {
+                at: now,
+                peers_connected: t.peers_connected,
+                peers_sending_to_us: t.peers_sending_to_us,
+            };
+            let samples = self.samples.entry(t.hash_string.clone()).or_default();
+            let is_due = samples.back().is_none_or(|last| {
+                now - last.at >= SAMPLE_SECS || last.is_unavailable() != sample.is_unavailable()
+            });
+            if !is_due {
+                continue;
+            }
+            if samples.len() == MAX_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+            changed = true;
+        }
+        changed
+    }
+
+    /// When the samples of `hash` last started showing no peer sending data,
+    /// if they show none now.
+    fn unavailable_since(&self, hash: &InfoHash) -> Option<i64> {
+        let samples = self.samples.get(hash)?;
+        let mut since = None;
+        let mut next_at = None;
+        for sample in samples.iter().rev() {
+            let is_gap = next_at.is_some_and(|next_at| next_at - sample.at > MAX_GAP_SECS);
+            if !sample.is_unavailable() || is_gap {
+                break;
+            }
+            since = Some(sample.at);
+            next_at = Some(sample.at);
+        }
+        since
+    }
+
+    /// Fill in how long each torrent has been unavailable, and flag those
+    /// downloading without a peer sending data for `dead_after_hours` or
+    /// more.  Zero hours flags none.
+    pub fn attach(&self, torrents: &mut [TransmissionTorrent], now: i64, dead_after_hours: u32) {
+        for t in torrents {
+            t.unavailable_since = self.unavailable_since(&t.hash_string);
+            t.likely_dead = dead_after_hours > 0
+                && t.status == TransmissionStatus::Downloading
+                && t.peers_sending_to_us == 0
+                && t.unavailable_since
+                    .is_some_and(|since| now - since >= i64::from(dead_after_hours) * 3600);
+            t.search_query = t.likely_dead.then(|| release_name::search_query(&t.name));
+        }
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context(CreateDirSnafu {
+                path: parent.to_path_buf(),
+            })?;
+        }
+        let json = serde_json::to_string(&self.samples).context(SerializeSnafu)?;
+        std::fs::write(&self.path, json).context(WriteFileSnafu {
+            path: self.path.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use privateer_wire_types::Destination;
+
+    use super::*;
+
+    const HOUR: i64 = 3600;
+
+    fn torrent(peers_sending_to_us: i64) -> TransmissionTorrent {
+        TransmissionTorrent {
+            id: 1,
+            name: "The.Bear.S02E05.1080p.WEB.h264-ETHEL".to_string(),
+            status: TransmissionStatus::Downloading,
+            percent_done: 0.5,
+            peers_connected: 3,
+            peers_sending_to_us,
+            destination: Some(Destination::Shows),
+            ..TransmissionTorrent::new("a".repeat(40).parse().unwrap())
+        }
+    }
+
+    fn availability() -> Availability {
+        Availability {
+            path: PathBuf::new(),
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Poll every ten minutes from `start` until `end`.
+    fn poll(availability: &mut Availability, t: &TransmissionTorrent, start: i64, end: i64) {
+        for now in (start..end).step_by(600) {
+            availability.record(std::slice::from_ref(t), now);
+        }
+    }
+
+    #[test]
+    fn downloads_without_peers_for_too_long_are_likely_dead() {
+        let mut availability = availability();
+        poll(&mut availability, &torrent(2), 0, 2 * HOUR);
+        poll(&mut availability, &torrent(0), 2 * HOUR, 26 * HOUR);
+
+        let mut torrents = [torrent(0)];
+        availability.attach(&mut torrents, 25 * HOUR, 24);
+        assert_eq!(Some(2 * HOUR), torrents[0].unavailable_since);
+        assert!(!torrents[0].likely_dead);
+
+        availability.attach(&mut torrents, 26 * HOUR, 24);
+        assert!(torrents[0].likely_dead);
+        assert_eq!(Some("The Bear S02E05"), torrents[0].search_query.as_deref());
+
+        availability.attach(&mut torrents, 26 * HOUR, 0);
+        assert!(!torrents[0].likely_dead, "0 hours never flags");
+
+        // A peer turning up clears it straight away
+        availability.record(&[torrent(1)], 26 * HOUR);
+        let mut torrents = [torrent(1)];
+        availability.attach(&mut torrents, 26 * HOUR, 24);
+        assert_eq!(None, torrents[0].unavailable_since);
+        assert!(!torrents[0].likely_dead);
+    }
+
+    #[test]
+    fn samples_are_spaced_and_capped() {
+        let mut availability = availability();
+        let t = torrent(0);
+        assert!(availability.record(std::slice::from_ref(&t), 0));
+        assert!(!availability.record(std::slice::from_ref(&t), SAMPLE_SECS - 1));
+        assert!(availability.record(std::slice::from_ref(&t), SAMPLE_SECS));
+
+        poll(&mut availability, &t, 0, 1000 * HOUR);
+        assert_eq!(MAX_SAMPLES, availability.samples[&t.hash_string].len());
+    }
+
+    #[test]
+    fn gaps_end_the_unavailable_stretch() {
+        let mut availability = availability();
+        poll(&mut availability, &torrent(0), 0, 10 * HOUR);
+        // The app was closed overnight
+        poll(&mut availability, &torrent(0), 20 * HOUR, 22 * HOUR);
+
+        let mut torrents = [torrent(0)];
+        availability.attach(&mut torrents, 22 * HOUR, 24);
+        assert_eq!(Some(20 * HOUR), torrents[0].unavailable_since);
+    }
+
+    #[test]
+    fn finished_paused_and_removed_downloads_are_forgotten() {
+        let mut availability = availability();
+        let mut finished = torrent(0);
+        let mut paused = torrent(0);
+        paused.hash_string = "b".repeat(40).parse().unwrap();
+        let mut removed = torrent(0);
+        removed.hash_string = "c".repeat(40).parse().unwrap();
+        let mut unassigned = torrent(0);
+        unassigned.hash_string = "d".repeat(40).parse().unwrap();
+        unassigned.destination = None;
+        availability.record(&[finished.clone(), paused.clone(), removed, unassigned], 0);
+        assert_eq!(3, availability.samples.len());
+
+        finished.percent_done = 1.0;
+        paused.status = TransmissionStatus::Stopped;
+        assert!(availability.record(&[finished, paused], HOUR));
+        assert!(availability.samples.is_empty());
+    }
+
+    #[test]
+    fn samples_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!(
+            "privateer-availability-{}.json",
+            std::process::id()
+        ));
+        let mut availability = Availability::load(path.clone());
+        poll(&mut availability, &torrent(0), 0, 30 * HOUR);
+        availability.save().unwrap();
+
+        let loaded = Availability::load(path.clone());
+        let mut torrents = [torrent(0)];
+        loaded.attach(&mut torrents, 30 * HOUR, 24);
+        assert!(torrents[0].likely_dead);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,3663 @@
+//! The copy engine: reconciles the ledger with Transmission and copies
+//! completed downloads to their destination directories.
+//!
+//! Everything outside the engine comes in through a trait so the logic can
+//! be tested without Tauri or a running daemon:
+//!
+//! - [`LedgerStore`] loads and saves the downloads ledger,
+//! - [`TorrentSource`] lists the torrents Transmission knows about,
+//! - [`Filesystem`] does the file operations,
+//! - [`SourceFs`] reads completed downloads, from the local [`Filesystem`] or
+//!   over SFTP,
+//! - [`CopyEvents`] hears about downloads and copies (desktop notifications,
+//!   copy metrics).
+//!
+//! The background task in `lib.rs`, run by the desktop app or headless, only
+//! schedules cycles and supplies the real implementations.
+
+use std::future::Future;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use privateer_wire_types::{
+    format_duration, format_rate, format_size, CopyBackend, CopyState, CopyTarget, Destination,
+    DestinationLocation, DownloadEntry, DownloadLocation, DownloadOrigin, InfoHash, LocatedPath,
+    ReconcileSummary, RenamedFile, SettingsProblem, SizeUnits, TimeOfDay, TransmissionConfig,
+    TransmissionStatus,
+};
+use snafu::ResultExt;
+use tokio::sync::watch;
+
+use crate::copy_schedule::CycleOutcome;
+use crate::error::*;
+use crate::file_name;
+use crate::mount;
+use crate::path_mapping;
+use crate::path_probe::{self, PathProbe};
+use crate::release_name;
+use crate::rsync;
+use crate::sftp::SftpSource;
+
+/// Persistent storage for the downloads ledger.
+///
+/// A ledger that can't be loaded is an error, never an empty ledger, so
+/// the copier can't save over one it failed to read.  The commands change
+/// the ledger while copies run, so the copier never saves a ledger it
+/// loaded earlier: it changes one entry at a time with
+/// [`update`](Self::update).
+pub trait LedgerStore {
+    async fn load(&self) -> Result<Vec<DownloadEntry>, ConfigError>;
+    /// Reload the ledger, make `change` to it and save it, holding it all
+    /// the while.  Nothing is saved unless `change` returns true.
+    async fn update(
+        &self,
+        change: impl FnOnce(&mut Vec<DownloadEntry>) -> bool,
+    ) -> Result<(), ConfigError>;
+}
+
+/// A torrent as the copy engine sees it.
+#[derive(Clone, Debug, Default)]
+pub struct SourceTorrent {
+    pub info_hash: Option<InfoHash>,
+    pub name: Option<String>,
+    /// 0.0 to 1.0
+    pub percent_done: f32,
+    pub status: TransmissionStatus,
+    pub download_dir: Option<String>,
+    /// When the download completed, in seconds since the epoch.
+    pub done_date: Option<i64>,
+    /// Transmission's upload ratio, -1 if not available and -2 if infinite.
+    pub upload_ratio: f64,
+    pub seconds_seeding: i64,
+}
+
+/// A file of a torrent, as Transmission lists it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TorrentFile {
+    /// Path from the torrent's download directory, starting with the
+    /// torrent's name unless it is a single file.
+    pub name: String,
+    /// Size in bytes once complete.
+    pub length: u64,
+    /// Whether the file is selected for download.
+    pub wanted: bool,
+}
+
+/// Lists the torrents in Transmission.
+pub trait TorrentSource {
+    async fn torrents(
+        &mut self,
+        config: &TransmissionConfig,
+    ) -> Result<Vec<SourceTorrent>, ClientError>;
+
+    /// The files of the torrent with the given info hash.
+    async fn files(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &InfoHash,
+    ) -> Result<Vec<TorrentFile>, ClientError>;
+
+    /// Stop the torrents with the given info hashes.
+    async fn stop(
+        &mut self,
+        config: &TransmissionConfig,
+        hashes: &[InfoHash],
+    ) -> Result<(), ClientError>;
+}
+
+/// The file operations the copy engine needs.
+pub trait Filesystem {
+    /// Whether something is at `path`, counting a path that doesn't answer
+    /// in time as missing.
+    async fn exists(&self, path: &Path) -> bool;
+    /// Whether something is at `path`, `None` if that can't be told in time.
+    async fn try_exists(&self, path: &Path) -> Option<bool> {
+        Some(self.exists(path).await)
+    }
+    async fn is_dir(&self, path: &Path) -> bool;
+    async fn create_dir_all(&self, path: &Path) -> std::io::Result<()>;
+    /// Paths of the entries in a directory.
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>>;
+    /// Size of a file in bytes.
+    async fn len(&self, path: &Path) -> std::io::Result<u64>;
+    /// Copy one file, returning the number of bytes copied.  Bytes are
+    /// counted on `heartbeat` as they are written.
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64>;
+    /// Remove a file, or a directory and everything in it.
+    async fn remove(&self, path: &Path) -> std::io::Result<()>;
+    /// Rename a file on the same volume.
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()>;
+}
+
+/// Where completed downloads are read from.
+pub trait SourceFs {
+    async fn exists(&self, path: &Path) -> bool;
+    async fn is_dir(&self, path: &Path) -> bool;
+    /// Paths of the entries in a directory.
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>>;
+    /// Size of a file in bytes.
+    async fn len(&self, path: &Path) -> std::io::Result<u64>;
+    /// Copy one file to the local `dst`, returning the number of bytes copied.
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64>;
+}
+
+/// Counts the bytes a copy in progress has written, so the watchdog can
+/// tell a slow copy from a hung one.
+#[derive(Clone, Default)]
+pub struct Heartbeat(Arc<AtomicU64>);
+
+impl Heartbeat {
+    /// Count `bytes` more as written.
+    pub fn beat(&self, bytes: u64) {
+        self.0.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn bytes(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Downloads read straight from a [`Filesystem`].
+struct LocalSource<'a, F>(&'a F);
+
+impl<F: Filesystem> SourceFs for LocalSource<'_, F> {
+    async fn exists(&self, path: &Path) -> bool {
+        self.0.exists(path).await
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        self.0.is_dir(path).await
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        self.0.read_dir(path).await
+    }
+
+    async fn len(&self, path: &Path) -> std::io::Result<u64> {
+        self.0.len(path).await
+    }
+
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64> {
+        self.0.copy_file(src, dst, heartbeat).await
+    }
+}
+
+/// The source picked by the configured [`CopyBackend`].
+enum Source<'a, F> {
+    Local(LocalSource<'a, F>),
+    Sftp(&'a SftpSource),
+}
+
+impl<F: Filesystem> SourceFs for Source<'_, F> {
+    async fn exists(&self, path: &Path) -> bool {
+        match self {
+            Self::Local(local) => local.exists(path).await,
+            Self::Sftp(sftp) => sftp.exists(path).await,
+        }
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        match self {
+            Self::Local(local) => local.is_dir(path).await,
+            Self::Sftp(sftp) => sftp.is_dir(path).await,
+        }
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        match self {
+            Self::Local(local) => local.read_dir(path).await,
+            Self::Sftp(sftp) => sftp.read_dir(path).await,
+        }
+    }
+
+    async fn len(&self, path: &Path) -> std::io::Result<u64> {
+        match self {
+            Self::Local(local) => local.len(path).await,
+            Self::Sftp(sftp) => sftp.len(path).await,
+        }
+    }
+
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64> {
+        match self {
+            Self::Local(local) => local.copy_file(src, dst, heartbeat).await,
+            Self::Sftp(sftp) => sftp.copy_file(src, dst, heartbeat).await,
+        }
+    }
+}
+
+/// Where downloads are read from, given the SFTP connection if
+/// [`CopyBackend::Sftp`] is configured.
+fn source_for<'a, F>(
+    fs: &'a F,
+    remote: &'a Option<Result<SftpSource, String>>,
+) -> Result<Source<'a, F>, String> {
+    match remote {
+        None => Ok(Source::Local(LocalSource(fs))),
+        Some(Ok(sftp)) => Ok(Source::Sftp(sftp)),
+        Some(Err(message)) => Err(message.clone()),
+    }
+}
+
+/// What a successful copy moved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CopyStats {
+    pub bytes: u64,
+    pub files: u64,
+    pub elapsed: Duration,
+}
+
+/// Things the copy engine reports as it goes.
+pub trait CopyEvents {
+    /// Called for every torrent on every cycle.
+    fn observe_download(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        percent: f32,
+    );
+    /// A copy is about to start from `previous`.
+    fn copy_started(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        previous: CopyState,
+    );
+    /// The torrent was copied to `path` at `dest`, which has a number in
+    /// its name if the copy [collided](CopyEvents::copy_collided).
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+        stats: &CopyStats,
+    );
+    /// The copy failed, or the torrent can't be copied at all, in which
+    /// case no [`copy_started`](CopyEvents::copy_started) came first.
+    fn copy_failed(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        reason: &str,
+    );
+    /// Different files already have the torrent's name at `dest`, so it is
+    /// about to be copied to `path` instead.  Does nothing by default.
+    fn copy_collided(
+        &mut self,
+        _config: &TransmissionConfig,
+        _hash: &str,
+        _name: &str,
+        _dest: Destination,
+        _path: &Path,
+    ) {
+    }
+    /// A cycle panicked, the `crashes`th in a row; the copy task carries on
+    /// after a cooldown.  Does nothing by default.
+    fn cycle_panicked(&mut self, _message: &str, _crashes: u32) {}
+}
+
+/// Both observers hear every event, `A` first.
+impl<A: CopyEvents, B: CopyEvents> CopyEvents for (A, B) {
+    fn observe_download(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        percent: f32,
+    ) {
+        self.0.observe_download(config, hash, name, percent);
+        self.1.observe_download(config, hash, name, percent);
+    }
+
+    fn copy_started(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        previous: CopyState,
+    ) {
+        self.0.copy_started(config, hash, name, dest, previous);
+        self.1.copy_started(config, hash, name, dest, previous);
+    }
+
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+        stats: &CopyStats,
+    ) {
+        self.0.copy_complete(config, hash, name, dest, path, stats);
+        self.1.copy_complete(config, hash, name, dest, path, stats);
+    }
+
+    fn copy_failed(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        reason: &str,
+    ) {
+        self.0.copy_failed(config, hash, name, dest, reason);
+        self.1.copy_failed(config, hash, name, dest, reason);
+    }
+
+    fn copy_collided(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+    ) {
+        self.0.copy_collided(config, hash, name, dest, path);
+        self.1.copy_collided(config, hash, name, dest, path);
+    }
+
+    fn cycle_panicked(&mut self, message: &str, crashes: u32) {
+        self.0.cycle_panicked(message, crashes);
+        self.1.cycle_panicked(message, crashes);
+    }
+}
+
+/// Files at least this big are copied by [`copy_chunked`] on the blocking
+/// pool rather than with `tokio::fs::copy`.
+const LARGE_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How often the watchdog checks a copy in progress for progress.
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Buffer size for [`copy_chunked`] and SFTP downloads.
+pub const COPY_BUFFER_BYTES: usize = 8 * 1024 * 1024;
+
+/// The real filesystem.  Nothing blocks the tokio runtime, so large copies
+/// to slow NAS drives don't stall the app: small files go through
+/// `tokio::fs`, large ones through [`copy_chunked`] on the blocking pool.
+///
+/// Paths are checked through a [`PathProbe`], so an unreachable share
+/// can't hold a check up for long.  Clones share what the probe remembers.
+#[derive(Clone, Default)]
+pub struct TokioFs {
+    probe: PathProbe,
+}
+
+#[cfg(test)]
+impl TokioFs {
+    /// The real filesystem checked through `probe`, for tests.
+    pub fn new(probe: PathProbe) -> Self {
+        Self { probe }
+    }
+}
+
+/// Sets its flag when dropped, so a blocking copy stops once the future
+/// waiting for it is dropped.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Copy a file through a large buffer, checking `cancelled` between chunks
+/// and counting each on `heartbeat`.
+///
+/// Network shares reward few large writes; small ones leave most of the
+/// link idle.  Like `std::fs::copy`, the permissions are carried over.
+fn copy_chunked(
+    src: &Path,
+    dst: &Path,
+    cancelled: &AtomicBool,
+    heartbeat: &Heartbeat,
+) -> std::io::Result<u64> {
+    let mut reader = std::fs::File::open(src)?;
+    let mut writer = std::fs::File::create(dst)?;
+    let mut buffer = vec![0; COPY_BUFFER_BYTES];
+    let mut copied = 0;
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "copy cancelled",
+            ));
+        }
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buffer[..read])?;
+        heartbeat.beat(read as u64);
+        copied += read as u64;
+    }
+    writer.flush()?;
+    writer.set_permissions(reader.metadata()?.permissions())?;
+    Ok(copied)
+}
+
+impl Filesystem for TokioFs {
+    async fn exists(&self, path: &Path) -> bool {
+        self.probe.exists(path).await
+    }
+
+    async fn try_exists(&self, path: &Path) -> Option<bool> {
+        self.probe.try_exists(path).await
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        let metadata = tokio::time::timeout(path_probe::TIMEOUT, tokio::fs::metadata(path)).await;
+        metadata.is_ok_and(|metadata| metadata.is_ok_and(|metadata| metadata.is_dir()))
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        self.probe.forget(path);
+        tokio::fs::create_dir_all(path).await
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut read_dir = tokio::fs::read_dir(path).await?;
+        let mut paths = vec![];
+        while let Some(entry) = read_dir.next_entry().await? {
+            paths.push(entry.path());
+        }
+        Ok(paths)
+    }
+
+    async fn len(&self, path: &Path) -> std::io::Result<u64> {
+        Ok(tokio::fs::metadata(path).await?.len())
+    }
+
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64> {
+        self.probe.forget(dst);
+        let len = tokio::fs::metadata(src).await?.len();
+        if len < LARGE_FILE_BYTES {
+            let copied = tokio::fs::copy(src, dst).await?;
+            heartbeat.beat(copied);
+            return Ok(copied);
+        }
+
+        let cancel = CancelOnDrop(Arc::new(AtomicBool::new(false)));
+        let cancelled = cancel.0.clone();
+        let heartbeat = heartbeat.clone();
+        let (src, dst) = (src.to_path_buf(), dst.to_path_buf());
+        tokio::task::spawn_blocking(move || copy_chunked(&src, &dst, &cancelled, &heartbeat))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    async fn remove(&self, path: &Path) -> std::io::Result<()> {
+        self.probe.forget(path);
+        if self.is_dir(path).await {
+            tokio::fs::remove_dir_all(path).await
+        } else {
+            tokio::fs::remove_file(path).await
+        }
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        self.probe.forget(from);
+        self.probe.forget(to);
+        tokio::fs::rename(from, to).await
+    }
+}
+
+/// The name a torrent is copied under in the `dest` directory, or `None` if
+/// its name can't be made a file name.
+///
+/// The name is [sanitized](file_name::sanitize) first.  With
+/// `clean_movie_names` a movie then gets a `Title (Year)` folder, and with
+/// `rename_episodes` a single episode file is named by the template.  A name
+/// that can't be parsed is kept as it is.
+pub fn copy_name(config: &TransmissionConfig, dest: Destination, name: &str) -> Option<String> {
+    file_name::sanitize(name).map(|safe_name| library_name(config, dest, safe_name))
+}
+
+/// [`copy_name`] of a name that is already safe.
+fn library_name(config: &TransmissionConfig, dest: Destination, safe_name: String) -> String {
+    match dest {
+        Destination::Movies if config.clean_movie_names => {
+            release_name::movie_folder(&safe_name).unwrap_or(safe_name)
+        }
+        Destination::Shows if config.rename_episodes => release_name::rename_episodes(
+            &config.episode_template,
+            std::slice::from_ref(&safe_name),
+        )
+        .renamed
+        .pop()
+        .map_or(safe_name, |(_, new_name)| new_name),
+        _ => safe_name,
+    }
+}
+
+/// Whether a torrent `percent_done` complete with `status` is ready to be
+/// copied.  A torrent being verified may report 100% while its data is
+/// still being checked, so it waits until verification finishes.
+pub fn is_ready(percent_done: f32, status: TransmissionStatus) -> bool {
+    percent_done >= 1.0 && !status.is_verifying()
+}
+
+/// Where a torrent named `name` is copied to at `dest`, or `None` if `dest`
+/// has no directory or the name can't be made a file name.
+pub fn copy_path(config: &TransmissionConfig, dest: Destination, name: &str) -> Option<PathBuf> {
+    let dir = config.dir_for(dest).filter(|dir| !dir.is_empty())?;
+    Some(Path::new(dir).join(copy_name(config, dest, name)?))
+}
+
+/// Where Transmission's `download_dir` is read from: over SFTP
+/// Transmission's own path, otherwise wherever it is mounted locally.
+pub fn source_dir(config: &TransmissionConfig, download_dir: &str) -> PathBuf {
+    match &config.copy_backend {
+        CopyBackend::Local => path_mapping::to_local(&config.path_mappings, download_dir),
+        CopyBackend::Sftp(_) => PathBuf::from(download_dir),
+    }
+}
+
+/// Where a torrent's files already are at `dest`, under any of its `names`
+/// or the names it would be copied under.  A name is only looked up as it is
+/// when that can't reach outside `dest`.  Paths that don't answer in time
+/// count as missing.
+async fn existing_copy<'a>(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    dest: Destination,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Option<PathBuf> {
+    let dir = config.dir_for(dest).filter(|dir| !dir.is_empty())?;
+    try_existing_copy(fs, config, dest, Path::new(dir), names)
+        .await
+        .flatten()
+}
+
+/// Like [`existing_copy`], but in `dir`, laid out as for `dest`, and `None`
+/// if a path doesn't answer in time.
+async fn try_existing_copy<'a>(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    dest: Destination,
+    dir: &Path,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Option<Option<PathBuf>> {
+    let paths = names
+        .into_iter()
+        .flat_map(|name| {
+            [
+                file_name::is_plain(name).then(|| name.to_string()),
+                file_name::sanitize(name),
+                copy_name(config, dest, name),
+            ]
+        })
+        .flatten()
+        .map(|name| dir.join(name))
+        .collect::<Vec<_>>();
+    for path in paths {
+        if fs.try_exists(&path).await? {
+            return Some(Some(path));
+        }
+    }
+    Some(None)
+}
+
+/// `name` followed by the names a ledger entry's torrent had before it was
+/// renamed and the safe name it was last copied under.
+fn names<'a>(entry: &'a DownloadEntry, name: &'a str) -> impl Iterator<Item = &'a str> {
+    std::iter::once(name)
+        .chain(entry.previous_names.iter().map(String::as_str))
+        .chain(entry.safe_name.as_deref())
+}
+
+/// Check whether a ledger entry's files already exist at its destination,
+/// where they were copied to if that's known.
+pub async fn check_already_copied(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    entry: &DownloadEntry,
+    name: &str,
+) -> bool {
+    match &entry.copied_path {
+        Some(path) => fs.exists(Path::new(path)).await,
+        None => existing_copy(fs, config, entry.destination, names(entry, name))
+            .await
+            .is_some(),
+    }
+}
+
+/// Where the target copying to the same place as `target` is among
+/// `entry`'s targets.
+fn target_index(entry: &DownloadEntry, target: &CopyTarget) -> Option<usize> {
+    entry.targets().iter().position(|t| t.same_place(target))
+}
+
+/// Count a reconciliation change that couldn't be saved in `summary`.
+fn reconcile_saved(saved: Result<(), ConfigError>, summary: &mut ReconcileSummary) {
+    if let Err(e) = saved {
+        log::error!("Copy task: failed to save ledger after reconciliation: {e}");
+        summary.errors.push(e.to_string());
+    }
+}
+
+/// Follow the rename of an entry's torrent in Transmission, warning when
+/// its copy can't be found under any of its names.
+async fn sync_name(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    entry: &mut DownloadEntry,
+    name: &str,
+) {
+    log::info!(
+        torrent = name;
+        "Reconcile: '{}' was renamed to '{name}'",
+        entry.name
+    );
+    entry.rename(name);
+    if entry.copy_state == CopyState::Copied && !check_already_copied(fs, config, entry, name).await
+    {
+        log::warn!(
+            torrent = name;
+            "Reconcile: no copy of '{name}' found at {} under any of its names",
+            entry.destination
+        );
+    }
+}
+
+/// Detect whether a torrent already exists at either destination directory.
+///
+/// Returns the destination and the path the torrent's files were found at,
+/// or `None` if the torrent doesn't exist at either location.  Found at
+/// both, which only happens when one directory is inside the other, the
+/// deeper directory wins since it is the more specific; directories of the
+/// same depth (the same directory) go to Movies.
+pub async fn detect_destination(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    name: &str,
+) -> Option<(Destination, PathBuf)> {
+    let depth = |dest| {
+        config
+            .dir_for(dest)
+            .map_or(0, |dir| Path::new(dir).components().count())
+    };
+    let mut found = vec![];
+    for dest in Destination::ALL {
+        if let Some(path) = existing_copy(fs, config, dest, [name]).await {
+            found.push((dest, path));
+        }
+    }
+    found
+        .into_iter()
+        .min_by_key(|(dest, _)| std::cmp::Reverse(depth(*dest)))
+}
+
+/// `dir` with symlinks followed as far as it exists, and the rest as
+/// written.
+fn resolve_dir(dir: &Path) -> PathBuf {
+    for existing in dir.ancestors() {
+        if let Ok(resolved) = std::fs::canonicalize(existing) {
+            let rest = dir.strip_prefix(existing).unwrap_or(Path::new(""));
+            return resolved.join(rest);
+        }
+    }
+    dir.to_path_buf()
+}
+
+/// Check that the destination directories don't overlap once symlinks are
+/// followed, which comparing the paths as written can't tell.
+pub fn check_destination_dirs(config: &TransmissionConfig) -> Result<(), SettingsProblem> {
+    let resolve = |dest| {
+        let dir = config.dir_for(dest).filter(|dir| !dir.is_empty())?;
+        Some(resolve_dir(Path::new(dir)))
+    };
+    let (Some(movies), Some(shows)) = (resolve(Destination::Movies), resolve(Destination::Shows))
+    else {
+        return Ok(());
+    };
+    if movies == shows {
+        Err(SettingsProblem::SameDestinationDirs)
+    } else if movies.starts_with(&shows) || shows.starts_with(&movies) {
+        Err(SettingsProblem::NestedDestinationDirs)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether anything is at `path`, and the total size of the files there.
+async fn look_at(source: &impl SourceFs, path: &Path) -> LocatedPath {
+    let exists = source.exists(path).await;
+    let bytes = if exists {
+        let sizes = file_sizes(source, path).await.ok();
+        sizes.map(|sizes| sizes.iter().sum())
+    } else {
+        None
+    };
+    LocatedPath {
+        path: path.display().to_string(),
+        exists,
+        bytes,
+    }
+}
+
+/// Where a download's files are now, as the copy engine would look for
+/// them: where Transmission downloaded `torrent`, if it still has it, and
+/// at each destination.  `remote` is the SFTP connection when downloads are
+/// read over SFTP.
+pub async fn locate(
+    fs: &impl Filesystem,
+    remote: &Option<Result<SftpSource, String>>,
+    config: &TransmissionConfig,
+    info_hash: &InfoHash,
+    torrent: Option<&SourceTorrent>,
+    entry: Option<&DownloadEntry>,
+) -> DownloadLocation {
+    let name = torrent
+        .and_then(|t| t.name.as_deref())
+        .or(entry.map(|entry| entry.name.as_str()))
+        .unwrap_or(info_hash.as_str());
+    let download_dir = torrent.and_then(|t| t.download_dir.clone());
+
+    let (mut source, mut source_error) = (None, None);
+    if let Some(dir) = &download_dir {
+        let path = source_dir(config, dir).join(name);
+        match source_for(fs, remote) {
+            Ok(from) => source = Some(look_at(&from, &path).await),
+            Err(message) => source_error = Some(message),
+        }
+    }
+
+    let local = LocalSource(fs);
+    let mut destinations = vec![];
+    // Each destination's own directory, then the user's own directories
+    let own_dirs = Destination::ALL.map(CopyTarget::new);
+    let their_dirs = entry.map_or(vec![], |entry| {
+        let extra = entry.extra_targets.iter();
+        extra.filter(|t| t.dir.is_some()).cloned().collect()
+    });
+    for place in own_dirs.into_iter().chain(their_dirs) {
+        let dest = place.destination;
+        let target = entry.and_then(|entry| {
+            let mut targets = entry.targets().into_iter();
+            targets.find(|target| target.same_place(&place))
+        });
+        let dir = place.copy_dir(config).map(PathBuf::from);
+        let planned = match dir.as_ref().zip(copy_name(config, dest, name)) {
+            Some((dir, copy_name)) => Some(look_at(&local, &dir.join(copy_name)).await),
+            None => None,
+        };
+        let recorded = target.as_ref().and_then(|t| t.copied_path.as_deref());
+        let found = match (recorded, &dir) {
+            (Some(path), _) if fs.exists(Path::new(path)).await => Some(PathBuf::from(path)),
+            (_, None) => None,
+            (_, Some(dir)) => {
+                let names: Vec<&str> = match entry {
+                    Some(entry) => names(entry, name).collect(),
+                    None => vec![name],
+                };
+                try_existing_copy(fs, config, dest, dir, names)
+                    .await
+                    .flatten()
+            }
+        };
+        let found = match found {
+            Some(path) => Some(look_at(&local, &path).await),
+            None => None,
+        };
+        destinations.push(DestinationLocation {
+            destination: dest,
+            target,
+            planned,
+            found,
+        });
+    }
+
+    DownloadLocation {
+        info_hash: info_hash.clone(),
+        name: name.to_string(),
+        in_client: torrent.is_some(),
+        client_error: None,
+        download_dir,
+        source,
+        source_error,
+        destinations,
+        entry: entry.cloned(),
+    }
+}
+
+/// Sizes of the files under `path`, or of `path` itself if it is a file,
+/// smallest first.
+async fn file_sizes(source: &impl SourceFs, path: &Path) -> std::io::Result<Vec<u64>> {
+    let mut sizes = vec![];
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        if source.is_dir(&path).await {
+            pending.extend(source.read_dir(&path).await?);
+        } else {
+            sizes.push(source.len(&path).await?);
+        }
+    }
+    sizes.sort_unstable();
+    Ok(sizes)
+}
+
+/// Whether an existing copy holds as many files as the download, of the
+/// same sizes, or `None` if either can't be read.  Names aren't compared
+/// since the copy's files may have been renamed.
+async fn same_files(
+    source: &impl SourceFs,
+    src: &Path,
+    fs: &impl Filesystem,
+    copy: &Path,
+) -> Option<bool> {
+    let downloaded = file_sizes(source, src).await.ok()?;
+    let copied = file_sizes(&LocalSource(fs), copy).await.ok()?;
+    Some(downloaded == copied)
+}
+
+/// The first wanted file that isn't in `dir` under its final name at its
+/// full length yet, e.g. because Transmission is still writing it as
+/// `.part`.
+async fn unfinished_file<'a>(
+    source: &impl SourceFs,
+    dir: &Path,
+    files: &'a [TorrentFile],
+) -> Option<&'a str> {
+    for file in files.iter().filter(|file| file.wanted) {
+        match source.len(&dir.join(&file.name)).await {
+            Ok(len) if len == file.length => {}
+            _ => return Some(&file.name),
+        }
+    }
+    None
+}
+
+/// `path` if nothing is there, otherwise the first free `Name (2)`,
+/// `Name (3)`, ...  Files keep their extension after the number.  `None` if
+/// a name doesn't answer in time.
+async fn free_path(fs: &impl Filesystem, path: &Path, is_file: bool) -> Option<PathBuf> {
+    if !fs.try_exists(path).await? {
+        return Some(path.to_path_buf());
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, extension) = match path.extension() {
+        Some(extension) if is_file => (
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+            format!(".{}", extension.to_string_lossy()),
+        ),
+        _ => (name, String::new()),
+    };
+    for n in 2.. {
+        let free = path.with_file_name(format!("{stem} ({n}){extension}"));
+        if !fs.try_exists(&free).await? {
+            return Some(free);
+        }
+    }
+    Some(path.to_path_buf())
+}
+
+/// Holds a copy in progress between files while copying is paused.
+struct Suspend {
+    pause: Option<watch::Receiver<bool>>,
+    stop: Option<watch::Receiver<bool>>,
+    /// How long the copy has been held, which doesn't count as copying.
+    waited: Duration,
+}
+
+impl Suspend {
+    /// Wait until copying is resumed, or the task is stopped, which lets
+    /// the copy finish.
+    async fn wait(&mut self) {
+        let Suspend {
+            pause,
+            stop,
+            waited,
+        } = self;
+        let Some(pause) = pause.as_mut().filter(|pause| *pause.borrow()) else {
+            return;
+        };
+        log::info!("Copy task: copying paused, suspending the copy in progress");
+        let since = Instant::now();
+        let resumed = pause.wait_for(|paused| !*paused);
+        match stop {
+            Some(stop) => {
+                tokio::select! {
+                    _ = resumed => {}
+                    _ = stop.wait_for(|stop| *stop) => {}
+                }
+            }
+            None => {
+                let _ = resumed.await;
+            }
+        }
+        *waited += since.elapsed();
+        log::info!("Copy task: resuming the copy in progress");
+    }
+}
+
+/// Recursively copy `src` from `source` to `dst` in `fs`, adding what was
+/// copied to `stats` and counting it on `heartbeat`.  Before each file it
+/// waits while `suspend` says so.
+async fn copy_recursive(
+    source: &impl SourceFs,
+    fs: &impl Filesystem,
+    src: &Path,
+    dst: &Path,
+    stats: &mut CopyStats,
+    suspend: &mut Suspend,
+    heartbeat: &Heartbeat,
+) -> Result<(), CopyError> {
+    if source.is_dir(src).await {
+        fs.create_dir_all(dst).await.context(CopyCreateDirSnafu {
+            path: dst.to_path_buf(),
+        })?;
+        let children = source.read_dir(src).await.context(CopyReadDirSnafu {
+            path: src.to_path_buf(),
+        })?;
+        for child_src in children {
+            let Some(file_name) = child_src.file_name() else {
+                continue;
+            };
+            let child_dst = dst.join(file_name);
+            Box::pin(copy_recursive(
+                source, fs, &child_src, &child_dst, stats, suspend, heartbeat,
+            ))
+            .await?;
+        }
+    } else {
+        // Single file
+        suspend.wait().await;
+        if let Some(parent) = dst.parent() {
+            fs.create_dir_all(parent)
+                .await
+                .context(CopyCreateDirSnafu {
+                    path: parent.to_path_buf(),
+                })?;
+        }
+        stats.bytes += source
+            .copy_file(src, dst, heartbeat)
+            .await
+            .context(CopyFileSnafu {
+                src: src.to_path_buf(),
+                dst: dst.to_path_buf(),
+            })?;
+        stats.files += 1;
+    }
+    Ok(())
+}
+
+/// What renaming the episode files of a copy did.
+#[derive(Default)]
+struct EpisodeRenaming {
+    renamed: Vec<RenamedFile>,
+    /// Files that kept their names.
+    unparsed: Vec<String>,
+}
+
+impl EpisodeRenaming {
+    /// Rename the episode files of torrent `name`, copied to `copied` in
+    /// `dir`, by `template`.  A single file was already copied under its
+    /// new name.
+    async fn rename(
+        &mut self,
+        fs: &impl Filesystem,
+        template: &str,
+        dir: &Path,
+        name: &str,
+        copied: &Path,
+    ) -> Result<(), CopyError> {
+        if fs.is_dir(copied).await {
+            return self.rename_dir(fs, template, copied).await;
+        }
+        let renames = release_name::rename_episodes(template, &[name.to_string()]);
+        self.unparsed.extend(renames.unparsed);
+        let original = dir.join(name);
+        if copied != original {
+            self.renamed.push(RenamedFile {
+                original: original.display().to_string(),
+                renamed: copied.display().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn rename_dir(
+        &mut self,
+        fs: &impl Filesystem,
+        template: &str,
+        dir: &Path,
+    ) -> Result<(), CopyError> {
+        let children = fs.read_dir(dir).await.context(CopyReadDirSnafu {
+            path: dir.to_path_buf(),
+        })?;
+        let mut names = vec![];
+        for child in children {
+            if fs.is_dir(&child).await {
+                Box::pin(self.rename_dir(fs, template, &child)).await?;
+            } else if let Some(name) = child.file_name().and_then(|name| name.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+        let renames = release_name::rename_episodes(template, &names);
+        for (name, new_name) in renames.renamed {
+            let (from, to) = (dir.join(&name), dir.join(&new_name));
+            if fs.exists(&to).await {
+                self.unparsed.push(name);
+                continue;
+            }
+            fs.rename(&from, &to).await.context(CopyRenameFileSnafu {
+                from: from.clone(),
+                to: to.clone(),
+            })?;
+            self.renamed.push(RenamedFile {
+                original: from.display().to_string(),
+                renamed: to.display().to_string(),
+            });
+        }
+        self.unparsed.extend(renames.unparsed);
+        Ok(())
+    }
+
+    fn note(&self) -> Option<String> {
+        (!self.unparsed.is_empty()).then(|| {
+            format!(
+                "Kept the names of files whose episode couldn't be parsed: {}",
+                self.unparsed.join(", ")
+            )
+        })
+    }
+}
+
+/// Reconciles the ledger and copies completed downloads.
+///
+/// State transitions are persisted to the ledger as they happen so the
+/// frontend can show real-time progress:
+///
+///   NotCopied/Failed  →  Copying  →  Copied | Failed
+pub struct Copier<L, S, F> {
+    ledger: L,
+    source: S,
+    fs: F,
+    /// Set to stop starting new copies; a copy in progress still finishes.
+    stop: Option<watch::Receiver<bool>>,
+    /// Set to pause copying; a copy in progress suspends between files.
+    pause: Option<watch::Receiver<bool>>,
+    /// The local time, for quiet hours.
+    clock: fn() -> TimeOfDay,
+}
+
+/// The local time of day.
+pub fn local_time() -> TimeOfDay {
+    use chrono::Timelike;
+
+    let now = chrono::Local::now();
+    TimeOfDay {
+        hour: now.hour() as u8,
+        minute: now.minute() as u8,
+    }
+}
+
+/// How long ago a download finished, given Transmission's `doneDate`.
+fn since_done(done: i64) -> String {
+    format_duration(crate::unix_now().saturating_sub(done).max(0) as u64)
+}
+
+/// Size and speed of a finished copy, e.g. "1.5 GB at 80.2 MB/s".
+fn copy_summary(stats: &CopyStats, units: SizeUnits) -> String {
+    let seconds = stats.elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 {
+        format_rate((stats.bytes as f64 / seconds) as i64, units)
+    } else {
+        format_rate(0, units)
+    };
+    format!("{} at {rate}", format_size(stats.bytes, units))
+}
+
+impl<L: LedgerStore, S: TorrentSource, F: Filesystem> Copier<L, S, F> {
+    pub fn new(ledger: L, source: S, fs: F) -> Self {
+        Self {
+            ledger,
+            source,
+            fs,
+            stop: None,
+            pause: None,
+            clock: local_time,
+        }
+    }
+
+    /// Read the time for quiet hours from `clock` instead of the local time.
+    pub fn with_clock(mut self, clock: fn() -> TimeOfDay) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Stop between copies once `stop` is set, leaving the remaining
+    /// entries pending for the next start.
+    pub fn with_stop(mut self, stop: watch::Receiver<bool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// Start no copies while `pause` is set, and suspend the one in
+    /// progress before its next file.  An rsync copy runs to the end.
+    pub fn with_pause(mut self, pause: watch::Receiver<bool>) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    fn stopping(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| *stop.borrow())
+    }
+
+    /// Whether copying is paused, by `config` or since it was loaded.
+    fn paused(&self, config: &TransmissionConfig) -> bool {
+        config.copying_paused || self.pause.as_ref().is_some_and(|pause| *pause.borrow())
+    }
+
+    /// Run `copy`, giving up on it once `heartbeat` has stood still for the
+    /// configured number of minutes.  Dropping the copy cancels it; a file
+    /// handle wedged in the kernel is left to its blocking thread.
+    async fn watchdog(
+        &self,
+        config: &TransmissionConfig,
+        heartbeat: &Heartbeat,
+        copy: impl Future<Output = Result<(), CopyError>>,
+    ) -> Result<(), CopyError> {
+        let minutes = config.copy_stall_minutes;
+        if minutes == 0 {
+            return copy.await;
+        }
+        let timeout = Duration::from_secs(minutes as u64 * 60);
+        tokio::select! {
+            copied = copy => copied,
+            () = self.stalled(heartbeat, timeout) => {
+                log::warn!("Copy task: no progress for {minutes} minutes, giving up on the copy");
+                Err(CopyError::CopyStalled { minutes })
+            }
+        }
+    }
+
+    /// Resolves once `heartbeat` has stood still for `timeout`, not counting
+    /// time spent paused.
+    async fn stalled(&self, heartbeat: &Heartbeat, timeout: Duration) {
+        let mut bytes = heartbeat.bytes();
+        let mut since = tokio::time::Instant::now();
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL.min(timeout)).await;
+            let paused = self.pause.as_ref().is_some_and(|pause| *pause.borrow());
+            if paused || heartbeat.bytes() != bytes {
+                bytes = heartbeat.bytes();
+                since = tokio::time::Instant::now();
+            } else if since.elapsed() >= timeout {
+                return;
+            }
+        }
+    }
+
+    /// Where downloads are read from, connecting to the SFTP host on first
+    /// use in a cycle.  An unreachable host stays unreachable for the rest
+    /// of the cycle.
+    async fn source<'a>(
+        &'a self,
+        config: &TransmissionConfig,
+        remote: &'a mut Option<Result<SftpSource, String>>,
+    ) -> Result<Source<'a, F>, String> {
+        if let (CopyBackend::Sftp(sftp), None) = (&config.copy_backend, &*remote) {
+            let connected = SftpSource::connect(sftp).await.map_err(|e| {
+                log::warn!("Copy task: {e}");
+                e.to_string()
+            });
+            *remote = Some(connected);
+        }
+        source_for(&self.fs, remote)
+    }
+
+    /// Mark copies left `Copying` when the app last quit or crashed as
+    /// `Failed`, so they are retried instead of blocking later entries.
+    /// Call before the first cycle.
+    pub async fn fail_interrupted(&self) {
+        let updated = self.ledger.update(|ledger| {
+            let mut interrupted = 0;
+            for entry in ledger.iter_mut() {
+                let name = entry.name.clone();
+                entry.update_targets(|target| {
+                    if target.copy_state != CopyState::Copying {
+                        return;
+                    }
+                    log::warn!(
+                        torrent = name.as_str();
+                        "Copy task: copy of '{name}' to {} was interrupted, marking Failed",
+                        target.destination
+                    );
+                    target.copy_state = CopyState::Failed;
+                    target.failure = Some("Copy interrupted when the app quit".to_string());
+                    interrupted += 1;
+                });
+            }
+            interrupted > 0
+        });
+        if let Err(e) = updated.await {
+            log::error!("Copy task: can't check for interrupted copies: {e}");
+        }
+    }
+
+    /// Make `change` to the entry with `info_hash` in the ledger as saved,
+    /// leaving every other entry as the commands left it.  An entry removed
+    /// since is left removed.
+    async fn change_entry(
+        &self,
+        info_hash: &InfoHash,
+        change: impl FnOnce(&mut DownloadEntry),
+    ) -> Result<(), ConfigError> {
+        self.ledger
+            .update(|ledger| {
+                let entry = ledger.iter_mut().find(|e| &e.info_hash == info_hash);
+                entry.map(change).is_some()
+            })
+            .await
+    }
+
+    /// Make `change` to the target of the entry with `info_hash` copying to
+    /// the same place as `target`, in the ledger as saved.  A target dropped
+    /// since is left dropped.
+    async fn change_target(
+        &self,
+        info_hash: &InfoHash,
+        target: &CopyTarget,
+        change: impl FnOnce(&mut CopyTarget),
+    ) -> Result<(), ConfigError> {
+        self.change_entry(info_hash, |entry| {
+            if let Some(index) = target_index(entry, target) {
+                entry.update_target(index, change);
+            }
+        })
+        .await
+    }
+
+    /// One reconciliation and copy pass over the ledger.
+    pub async fn run_cycle(
+        &mut self,
+        config: &TransmissionConfig,
+        events: &mut impl CopyEvents,
+    ) -> CycleOutcome {
+        let mut ledger = match self.ledger.load().await {
+            Ok(ledger) => ledger,
+            Err(e) => {
+                log::error!("Copy task: {e}");
+                return CycleOutcome::Idle;
+            }
+        };
+
+        // We need the torrent list for both reconciliation and copying.
+        let torrents = match self.source.torrents(config).await {
+            Ok(torrents) => torrents,
+            Err(e) => {
+                log::warn!("Copy task: torrent_get failed: {e}");
+                return CycleOutcome::Idle;
+            }
+        };
+
+        let mut summary = ReconcileSummary::default();
+        self.reconcile(config, &mut ledger, &torrents, events, &mut summary)
+            .await;
+        self.stop_seeding(config, &mut ledger, &torrents).await;
+
+        self.copy_pending(config, &ledger, &torrents, events).await
+    }
+
+    /// Reconcile the ledger with the destinations right away, without
+    /// copying anything, and say what changed.
+    pub async fn reconcile_now(
+        &mut self,
+        config: &TransmissionConfig,
+        events: &mut impl CopyEvents,
+    ) -> ReconcileSummary {
+        let mut summary = ReconcileSummary::default();
+        let mut ledger = match self.ledger.load().await {
+            Ok(ledger) => ledger,
+            Err(e) => {
+                summary.errors.push(e.to_string());
+                return summary;
+            }
+        };
+        let torrents = match self.source.torrents(config).await {
+            Ok(torrents) => torrents,
+            Err(e) => {
+                summary.errors.push(format!("Could not list torrents: {e}"));
+                return summary;
+            }
+        };
+        self.reconcile(config, &mut ledger, &torrents, events, &mut summary)
+            .await;
+        summary
+    }
+
+    /// Scan Transmission torrents and update the ledger, counting what
+    /// changed in `summary`.  Each change is made both to `ledger` and to
+    /// the ledger as saved.
+    ///
+    /// 1. Untracked torrents whose files exist at a destination dir
+    ///    → auto-add to ledger as Copied.
+    /// 2. Stale states (NotCopied/Failed but files exist at dest)
+    ///    → update to Copied.
+    async fn reconcile(
+        &self,
+        config: &TransmissionConfig,
+        ledger: &mut Vec<DownloadEntry>,
+        torrents: &[SourceTorrent],
+        events: &mut impl CopyEvents,
+        summary: &mut ReconcileSummary,
+    ) {
+        for tt in torrents {
+            let hash = match &tt.info_hash {
+                Some(h) => h,
+                None => continue,
+            };
+            let name = match tt.name.as_deref() {
+                Some(n) => n,
+                None => continue,
+            };
+
+            events.observe_download(config, hash.as_str(), name, tt.percent_done);
+
+            let existing = ledger.iter_mut().find(|e| &e.info_hash == hash);
+
+            match existing {
+                Some(entry) => {
+                    if entry.name != name {
+                        sync_name(&self.fs, config, entry, name).await;
+                        let saved = self.change_entry(hash, |e| e.rename(name)).await;
+                        reconcile_saved(saved, summary);
+                    }
+                    // Fix stale states: ledger says it isn't copied yet but
+                    // files already exist at the destination.  Files found
+                    // only by name are compared with the download before
+                    // counting as copied, in `copy_pending`.
+                    let mut found = vec![];
+                    for target in entry.targets() {
+                        let is_there = match target.copied_path.as_deref() {
+                            Some(path) if target.copy_state.is_pending() => {
+                                self.fs.exists(Path::new(path)).await
+                            }
+                            _ => false,
+                        };
+                        found.push(is_there);
+                    }
+                    let mut found = found.into_iter();
+                    let mut copied = vec![];
+                    entry.update_targets(|target| {
+                        if found.next() == Some(true) {
+                            log::info!(
+                                torrent = name;
+                                "Reconcile: '{name}' already at {}, marking Copied",
+                                target.destination
+                            );
+                            target.copy_state = CopyState::Copied;
+                            target.failure = None;
+                            summary.marked_copied += 1;
+                            copied.push(target.clone());
+                        }
+                    });
+                    for target in copied {
+                        let saved = self
+                            .change_target(hash, &target, |target| {
+                                target.copy_state = CopyState::Copied;
+                                target.failure = None;
+                            })
+                            .await;
+                        reconcile_saved(saved, summary);
+                    }
+                }
+                // Other tools may be managing torrents the user didn't assign
+                None if config.only_assigned => {}
+                None => {
+                    // Not in ledger — check whether files exist at either
+                    // destination. If so, auto-add as Copied.
+                    if let Some((dest, path)) = detect_destination(&self.fs, config, name).await {
+                        log::info!(
+                            torrent = name;
+                            "Reconcile: auto-adding '{name}' to ledger as {dest} (Copied)"
+                        );
+                        let added = DownloadEntry {
+                            copy_state: CopyState::Copied,
+                            copied_path: Some(path.display().to_string()),
+                            profile: config.active().id,
+                            origin: DownloadOrigin::AutoDetected,
+                            ..DownloadEntry::new(hash.clone(), name.to_string(), dest)
+                        };
+                        ledger.push(added.clone());
+                        summary.auto_added += 1;
+                        // Unless the user added it meanwhile
+                        let saved = self
+                            .ledger
+                            .update(|saved| {
+                                let is_new = !saved.iter().any(|e| &e.info_hash == hash);
+                                if is_new {
+                                    saved.push(added);
+                                }
+                                is_new
+                            })
+                            .await;
+                        reconcile_saved(saved, summary);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stop copied torrents that have seeded up to their destination's
+    /// [`SeedLimits`](privateer_wire_types::SeedLimits), marking them so
+    /// they are left alone if started again.  Torrents not in the ledger are
+    /// never stopped.
+    async fn stop_seeding(
+        &mut self,
+        config: &TransmissionConfig,
+        ledger: &mut [DownloadEntry],
+        torrents: &[SourceTorrent],
+    ) {
+        let goal_met = |entry: &DownloadEntry| {
+            let limits = config.seed_limits(entry.destination);
+            torrents.iter().any(|t| {
+                t.info_hash.as_ref() == Some(&entry.info_hash)
+                    && matches!(
+                        t.status,
+                        TransmissionStatus::Seeding | TransmissionStatus::QueuedSeed
+                    )
+                    && limits.is_met(t.upload_ratio, t.seconds_seeding)
+            })
+        };
+        let done: Vec<usize> = ledger
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.overall_copy_state() == CopyState::Copied && !e.seeding_completed)
+            .filter(|(_, e)| goal_met(e))
+            .map(|(i, _)| i)
+            .collect();
+        if done.is_empty() {
+            return;
+        }
+
+        let hashes: Vec<InfoHash> = done.iter().map(|&i| ledger[i].info_hash.clone()).collect();
+        if let Err(e) = self.source.stop(config, &hashes).await {
+            log::warn!("Copy task: failed to stop torrents that finished seeding: {e}");
+            return;
+        }
+        for i in done {
+            let name = ledger[i].name.as_str();
+            log::info!(
+                torrent = name;
+                "Copy task: '{name}' reached its {} seeding goal, stopped it",
+                ledger[i].destination
+            );
+            let stopped = self
+                .change_entry(&ledger[i].info_hash, |entry| entry.seeding_completed = true)
+                .await;
+            if let Err(e) = stopped {
+                log::error!("Copy task: failed to save ledger after stopping '{name}': {e}");
+            }
+            ledger[i].seeding_completed = true;
+        }
+    }
+
+    /// Copy every entry that is not yet copied and whose torrent is complete,
+    /// to each of its destinations in turn.  Entries are read again from
+    /// the ledger as saved before they are copied, since the commands
+    /// change them during earlier copies.
+    async fn copy_pending(
+        &mut self,
+        config: &TransmissionConfig,
+        ledger: &[DownloadEntry],
+        torrents: &[SourceTorrent],
+        events: &mut impl CopyEvents,
+    ) -> CycleOutcome {
+        // Find the entries and targets eligible for copying (not yet copied,
+        // not currently copying)
+        let pending: Vec<(InfoHash, String, CopyTarget)> = ledger
+            .iter()
+            .flat_map(|e| {
+                e.targets()
+                    .into_iter()
+                    .filter(|target| target.copy_state.is_pending())
+                    .map(|target| (e.info_hash.clone(), e.name.clone(), target))
+            })
+            .collect();
+
+        let mut outcome = CycleOutcome::Idle;
+        let quiet_until = config.quiet_until((self.clock)());
+        // Connected on the first copy that needs it, once per cycle
+        let mut remote: Option<Result<SftpSource, String>> = None;
+
+        for (info_hash, entry_name, target) in pending {
+            if self.stopping() {
+                log::info!("Copy task: stopping, remaining copies are left for the next start");
+                break;
+            }
+
+            // Find the matching torrent in Transmission
+            let trans_torrent = torrents
+                .iter()
+                .find(|t| t.info_hash.as_ref() == Some(&info_hash));
+
+            let trans_torrent = match trans_torrent {
+                Some(t) => t,
+                None => continue,
+            };
+
+            if !is_ready(trans_torrent.percent_done, trans_torrent.status) {
+                if trans_torrent.status.is_verifying() {
+                    log::debug!(
+                        torrent = entry_name.as_str();
+                        "Copy task: '{entry_name}' is verifying, skipping"
+                    );
+                }
+                continue;
+            }
+
+            // Retried, renamed, reassigned or removed during an earlier copy
+            let entry = match self.ledger.load().await {
+                Ok(ledger) => ledger.into_iter().find(|e| e.info_hash == info_hash),
+                Err(e) => {
+                    log::error!("Copy task: {e}");
+                    break;
+                }
+            };
+            let Some(entry) = entry else {
+                continue;
+            };
+            let copy_target = entry.targets().into_iter().find(|t| t.same_place(&target));
+            let Some(copy_target) = copy_target.filter(|t| t.copy_state.is_pending()) else {
+                continue;
+            };
+            let destination = copy_target.destination;
+
+            let torrent_name = trans_torrent
+                .name
+                .clone()
+                .unwrap_or_else(|| entry_name.clone());
+            let done_date = trans_torrent.done_date;
+            let download_dir = match trans_torrent.download_dir.as_deref() {
+                Some(d) => d.to_string(),
+                None => {
+                    log::warn!(
+                        torrent = entry_name.as_str();
+                        "Copy task: no download_dir for torrent '{entry_name}'"
+                    );
+                    continue;
+                }
+            };
+
+            let dest_dir = match copy_target.copy_dir(config) {
+                Some(d) => d.to_string(),
+                None => {
+                    log::debug!(
+                        torrent = entry_name.as_str();
+                        "Copy task: no destination dir configured for {destination} (torrent '{entry_name}')",
+                    );
+                    // Tell waiting for a directory apart from waiting for
+                    // the download
+                    if copy_target.copy_state != CopyState::Unconfigured {
+                        let _ = self
+                            .change_target(&info_hash, &copy_target, |t| {
+                                t.copy_state = CopyState::Unconfigured;
+                                t.failure = None;
+                            })
+                            .await;
+                    }
+                    continue;
+                }
+            };
+
+            // A name of nothing but separators and dots has no file name
+            // inside the library.  It fails once rather than every cycle.
+            let Some(safe_name) = file_name::sanitize(&torrent_name) else {
+                let reason = format!("'{torrent_name}' can't be used as a file name");
+                if copy_target.failure.as_deref() != Some(reason.as_str()) {
+                    log::error!(
+                        torrent = torrent_name.as_str();
+                        "Copy task: {reason}"
+                    );
+                    events.copy_failed(
+                        config,
+                        info_hash.as_str(),
+                        &torrent_name,
+                        destination,
+                        &reason,
+                    );
+                    let _ = self
+                        .change_target(&info_hash, &copy_target, |t| {
+                            t.copy_state = CopyState::Failed;
+                            t.failure = Some(reason);
+                        })
+                        .await;
+                }
+                continue;
+            };
+            let entry_safe_name = (safe_name != torrent_name).then(|| safe_name.clone());
+
+            let src_dir = source_dir(config, &download_dir);
+            let src_path = src_dir.join(&torrent_name);
+            let copy_name = library_name(config, destination, safe_name.clone());
+            let mut dst_path = PathBuf::from(&dest_dir).join(&copy_name);
+
+            // Already at destination — mark Copied without re-copying, unless
+            // it's a different download with the same name, or that can't
+            // be told
+            let names = names(&entry, &torrent_name);
+            let existing =
+                try_existing_copy(&self.fs, config, destination, Path::new(&dest_dir), names);
+            let Some(existing) = existing.await else {
+                // Whatever is there might be written over: try again next
+                // cycle
+                log::warn!(
+                    torrent = torrent_name.as_str();
+                    "Copy task: couldn't check {destination} for '{torrent_name}' in time, deferred"
+                );
+                continue;
+            };
+            if let Some(existing) = existing {
+                let same = match self.source(config, &mut remote).await {
+                    Ok(source) => same_files(&source, &src_path, &self.fs, &existing).await,
+                    Err(_) => None,
+                };
+                match same {
+                    None => {
+                        // Marking it copied might leave the library without
+                        // it, and copying might write over it: try again
+                        // next cycle
+                        log::warn!(
+                            torrent = torrent_name.as_str();
+                            "Copy task: couldn't compare '{}' with the download, leaving it pending",
+                            existing.display()
+                        );
+                        continue;
+                    }
+                    Some(false) => {
+                        let is_file = !self.fs.is_dir(&existing).await;
+                        let Some(free) = free_path(&self.fs, &dst_path, is_file).await else {
+                            log::warn!(
+                                torrent = torrent_name.as_str();
+                                "Copy task: couldn't find a free name beside '{}' in time, deferred",
+                                existing.display()
+                            );
+                            continue;
+                        };
+                        dst_path = free;
+                        log::warn!(
+                            torrent = torrent_name.as_str();
+                            "Copy task: '{}' holds different files, copying to '{}'",
+                            existing.display(),
+                            dst_path.display()
+                        );
+                        events.copy_collided(
+                            config,
+                            info_hash.as_str(),
+                            &torrent_name,
+                            destination,
+                            &dst_path,
+                        );
+                    }
+                    Some(true) => {
+                        log::info!(
+                            torrent = torrent_name.as_str();
+                            "Copy task: '{}' already exists at destination, marking copied",
+                            torrent_name
+                        );
+                        let _ = self
+                            .change_entry(&info_hash, |entry| {
+                                let Some(index) = target_index(entry, &copy_target) else {
+                                    return;
+                                };
+                                entry.safe_name = entry_safe_name;
+                                entry.update_target(index, |t| {
+                                    t.copy_state = CopyState::Copied;
+                                    t.failure = None;
+                                    t.copied_path = Some(existing.display().to_string());
+                                });
+                            })
+                            .await;
+                        outcome = CycleOutcome::Worked;
+                        continue;
+                    }
+                }
+            }
+
+            // "Copy now" overrides quiet hours
+            if let Some(until) = quiet_until.filter(|_| !entry.copy_now) {
+                log::debug!(
+                    torrent = torrent_name.as_str();
+                    "Copy task: quiet hours, '{torrent_name}' deferred until {until}"
+                );
+                continue;
+            }
+
+            // Pausing holds back even "Copy now"
+            if self.paused(config) {
+                log::debug!(
+                    torrent = torrent_name.as_str();
+                    "Copy task: copying paused, '{torrent_name}' deferred"
+                );
+                continue;
+            }
+
+            // 100% done can be reported a moment before the last pieces are
+            // written under their final names
+            let files = self.source.files(config, &info_hash).await;
+
+            // An unreachable host fails the copy, to be retried next cycle
+            let source = self.source(config, &mut remote).await;
+            // Copying into the placeholder left where a share should be
+            // mounted would fill the system disk.  Only the destinations'
+            // own directories are flagged as mounts.
+            let source = match source {
+                Ok(source) if copy_target.dir.is_none() => {
+                    mount::check_mounted_in_time(config, destination, Path::new(&dest_dir))
+                        .await
+                        .map(|()| source)
+                        .map_err(|e| e.to_string())
+                }
+                source => source,
+            };
+
+            // A renamed single-file movie goes inside its new folder
+            let mut copy_to = dst_path.clone();
+            if let Ok(source) = &source {
+                if !source.exists(&src_path).await {
+                    log::warn!(
+                        torrent = torrent_name.as_str();
+                        "Copy task: source '{}' does not exist, skipping",
+                        src_path.display()
+                    );
+                    continue;
+                }
+                let unfinished = match &files {
+                    Ok(files) => unfinished_file(source, &src_dir, files).await,
+                    Err(e) => {
+                        log::warn!(
+                            torrent = torrent_name.as_str();
+                            "Copy task: couldn't list the files of '{torrent_name}', deferred: {e}"
+                        );
+                        continue;
+                    }
+                };
+                if let Some(file) = unfinished {
+                    log::debug!(
+                        torrent = torrent_name.as_str();
+                        "Copy task: '{file}' isn't complete on disk yet, '{torrent_name}' deferred"
+                    );
+                    continue;
+                }
+                if destination == Destination::Movies
+                    && copy_name != safe_name
+                    && !source.is_dir(&src_path).await
+                {
+                    copy_to = dst_path.join(&safe_name);
+                }
+            }
+
+            // Transition: → Copying  (persist immediately so the UI updates)
+            events.copy_started(
+                config,
+                info_hash.as_str(),
+                &torrent_name,
+                destination,
+                copy_target.copy_state,
+            );
+            let copying = self
+                .change_entry(&info_hash, |entry| {
+                    let Some(index) = target_index(entry, &copy_target) else {
+                        return;
+                    };
+                    entry.safe_name = entry_safe_name.clone();
+                    entry.update_target(index, |t| {
+                        t.copy_state = CopyState::Copying;
+                        t.failure = None;
+                        t.copied_at = None;
+                    });
+                    // "Copy now" holds until the entry's last destination starts
+                    if !entry.targets().iter().any(|t| t.copy_state.is_pending()) {
+                        entry.copy_now = false;
+                    }
+                    // The renames, note and throughput are kept for the
+                    // entry's own destination
+                    if index == 0 {
+                        entry.renamed_files.clear();
+                        entry.note = None;
+                        entry.copied_bytes = None;
+                        entry.copy_seconds = None;
+                    }
+                })
+                .await;
+            if let Err(e) = copying {
+                log::error!(
+                    torrent = torrent_name.as_str();
+                    "Copy task: failed to save ledger (Copying): {e}"
+                );
+            }
+
+            log::info!(
+                torrent = torrent_name.as_str();
+                "Copy task: copying '{}' -> '{}'",
+                src_path.display(),
+                copy_to.display()
+            );
+
+            // Timed around the whole entry, less the time it was paused
+            let mut stats = CopyStats::default();
+            let started = Instant::now();
+            let heartbeat = Heartbeat::default();
+            let mut suspend = Suspend {
+                pause: self.pause.clone(),
+                stop: self.stop.clone(),
+                waited: Duration::ZERO,
+            };
+            let copied = match &source {
+                Ok(source) => {
+                    let copy = async {
+                        if config.copy_with_rsync && matches!(source, Source::Local(_)) {
+                            return rsync::copy(
+                                &src_path,
+                                &copy_to,
+                                &torrent_name,
+                                &mut stats,
+                                &heartbeat,
+                                config.size_units,
+                            )
+                            .await;
+                        }
+                        copy_recursive(
+                            source,
+                            &self.fs,
+                            &src_path,
+                            &copy_to,
+                            &mut stats,
+                            &mut suspend,
+                            &heartbeat,
+                        )
+                        .await
+                    };
+                    self.watchdog(config, &heartbeat, copy)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                Err(message) => Err(message.clone()),
+            };
+            let mut renaming = EpisodeRenaming::default();
+            let copied = match copied {
+                Ok(()) if destination == Destination::Shows && config.rename_episodes => renaming
+                    .rename(
+                        &self.fs,
+                        &config.episode_template,
+                        Path::new(&dest_dir),
+                        &safe_name,
+                        &copy_to,
+                    )
+                    .await
+                    .map_err(|e| e.to_string()),
+                copied => copied,
+            };
+            stats.elapsed = started.elapsed().saturating_sub(suspend.waited);
+            let saved = match copied {
+                Ok(()) => {
+                    let after = done_date
+                        .map(|done| format!(", {} after it finished", since_done(done)))
+                        .unwrap_or_default();
+                    log::info!(
+                        torrent = torrent_name.as_str();
+                        "Copy task: successfully copied '{}' ({}){after}",
+                        torrent_name,
+                        copy_summary(&stats, config.size_units)
+                    );
+                    outcome = CycleOutcome::Worked;
+                    events.copy_complete(
+                        config,
+                        info_hash.as_str(),
+                        &torrent_name,
+                        destination,
+                        &dst_path,
+                        &stats,
+                    );
+                    self.change_entry(&info_hash, |entry| {
+                        let Some(index) = target_index(entry, &copy_target) else {
+                            return;
+                        };
+                        entry.update_target(index, |t| {
+                            t.copy_state = CopyState::Copied;
+                            t.copied_path = Some(dst_path.display().to_string());
+                            t.copied_at = Some(crate::unix_now());
+                        });
+                        if index == 0 {
+                            entry.note = renaming.note();
+                            entry.renamed_files = renaming.renamed;
+                            entry.copied_bytes = Some(stats.bytes);
+                            entry.copy_seconds = Some(stats.elapsed.as_secs_f64());
+                        }
+                    })
+                    .await
+                }
+                Err(e) => {
+                    log::error!(
+                        torrent = torrent_name.as_str();
+                        "Copy task: failed to copy '{}': {e}",
+                        torrent_name
+                    );
+                    events.copy_failed(config, info_hash.as_str(), &torrent_name, destination, &e);
+                    // Clean up partial copy on failure
+                    if self.fs.exists(&dst_path).await {
+                        let _ = self.fs.remove(&dst_path).await;
+                    }
+                    self.change_target(&info_hash, &copy_target, |t| {
+                        t.copy_state = CopyState::Failed;
+                        t.failure = Some(e);
+                    })
+                    .await
+                }
+            };
+
+            // Persist Copied/Failed state
+            if let Err(e) = saved {
+                log::error!(
+                    torrent = torrent_name.as_str();
+                    "Copy task: failed to save ledger: {e}"
+                );
+            }
+        }
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use privateer_wire_types::SeedLimits;
+    use std::cell::{Cell, RefCell};
+
+    /// A ledger kept in memory.
+    struct MemoryLedger(RefCell<Vec<DownloadEntry>>);
+
+    impl LedgerStore for &MemoryLedger {
+        async fn load(&self) -> Result<Vec<DownloadEntry>, ConfigError> {
+            Ok(self.0.borrow().clone())
+        }
+
+        async fn update(
+            &self,
+            change: impl FnOnce(&mut Vec<DownloadEntry>) -> bool,
+        ) -> Result<(), ConfigError> {
+            change(&mut self.0.borrow_mut());
+            Ok(())
+        }
+    }
+
+    impl MemoryLedger {
+        fn state(&self) -> CopyState {
+            self.0.borrow()[0].copy_state
+        }
+    }
+
+    /// A fixed torrent list.
+    struct FixedSource(Vec<SourceTorrent>);
+
+    impl TorrentSource for FixedSource {
+        async fn torrents(
+            &mut self,
+            _config: &TransmissionConfig,
+        ) -> Result<Vec<SourceTorrent>, ClientError> {
+            Ok(self.0.clone())
+        }
+
+        /// Stopped torrents show up as such in the next list.
+        async fn stop(
+            &mut self,
+            _config: &TransmissionConfig,
+            hashes: &[InfoHash],
+        ) -> Result<(), ClientError> {
+            for torrent in &mut self.0 {
+                if torrent
+                    .info_hash
+                    .as_ref()
+                    .is_some_and(|h| hashes.contains(h))
+                {
+                    torrent.status = TransmissionStatus::Stopped;
+                }
+            }
+            Ok(())
+        }
+
+        /// Whatever is on disk under the torrent's name, all complete.
+        async fn files(
+            &mut self,
+            _config: &TransmissionConfig,
+            hash: &InfoHash,
+        ) -> Result<Vec<TorrentFile>, ClientError> {
+            let Some(torrent) = self.0.iter().find(|t| t.info_hash.as_ref() == Some(hash)) else {
+                return Ok(vec![]);
+            };
+            let dir = PathBuf::from(torrent.download_dir.clone().unwrap_or_default());
+            let mut names = vec![PathBuf::from(torrent.name.clone().unwrap_or_default())];
+            let mut files = vec![];
+            while let Some(name) = names.pop() {
+                let path = dir.join(&name);
+                if path.is_dir() {
+                    for entry in std::fs::read_dir(&path).unwrap() {
+                        names.push(name.join(entry.unwrap().file_name()));
+                    }
+                } else if let Ok(metadata) = std::fs::metadata(&path) {
+                    files.push(TorrentFile {
+                        name: name.display().to_string(),
+                        length: metadata.len(),
+                        wanted: true,
+                    });
+                }
+            }
+            Ok(files)
+        }
+    }
+
+    /// A [`FixedSource`] listing the given files for every torrent.
+    struct ListedFiles(FixedSource, Vec<TorrentFile>);
+
+    impl TorrentSource for ListedFiles {
+        async fn torrents(
+            &mut self,
+            config: &TransmissionConfig,
+        ) -> Result<Vec<SourceTorrent>, ClientError> {
+            self.0.torrents(config).await
+        }
+
+        async fn stop(
+            &mut self,
+            config: &TransmissionConfig,
+            hashes: &[InfoHash],
+        ) -> Result<(), ClientError> {
+            self.0.stop(config, hashes).await
+        }
+
+        async fn files(
+            &mut self,
+            _config: &TransmissionConfig,
+            _hash: &InfoHash,
+        ) -> Result<Vec<TorrentFile>, ClientError> {
+            Ok(self.1.clone())
+        }
+    }
+
+    /// The real filesystem, except that copying a file with the given
+    /// name fails.
+    struct FailingFs(&'static str);
+
+    impl Filesystem for FailingFs {
+        async fn exists(&self, path: &Path) -> bool {
+            TokioFs::default().exists(path).await
+        }
+
+        async fn is_dir(&self, path: &Path) -> bool {
+            TokioFs::default().is_dir(path).await
+        }
+
+        async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().create_dir_all(path).await
+        }
+
+        async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+            // Sorted so the failing file is reached after the others
+            let mut paths = TokioFs::default().read_dir(path).await?;
+            paths.sort();
+            Ok(paths)
+        }
+
+        async fn len(&self, path: &Path) -> std::io::Result<u64> {
+            TokioFs::default().len(path).await
+        }
+
+        async fn copy_file(
+            &self,
+            src: &Path,
+            dst: &Path,
+            heartbeat: &Heartbeat,
+        ) -> std::io::Result<u64> {
+            if src.file_name().is_some_and(|name| name == self.0) {
+                return Err(std::io::Error::other("disk full"));
+            }
+            TokioFs::default().copy_file(src, dst, heartbeat).await
+        }
+
+        async fn remove(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().remove(path).await
+        }
+
+        async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            TokioFs::default().rename(from, to).await
+        }
+    }
+
+    /// Writes the named file's first bytes and then never finishes, like a
+    /// dropped network share.
+    struct StallingFs(&'static str);
+
+    impl Filesystem for StallingFs {
+        async fn exists(&self, path: &Path) -> bool {
+            TokioFs::default().exists(path).await
+        }
+
+        async fn is_dir(&self, path: &Path) -> bool {
+            TokioFs::default().is_dir(path).await
+        }
+
+        async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().create_dir_all(path).await
+        }
+
+        async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+            TokioFs::default().read_dir(path).await
+        }
+
+        async fn len(&self, path: &Path) -> std::io::Result<u64> {
+            TokioFs::default().len(path).await
+        }
+
+        async fn copy_file(
+            &self,
+            src: &Path,
+            dst: &Path,
+            heartbeat: &Heartbeat,
+        ) -> std::io::Result<u64> {
+            if src.file_name().is_some_and(|name| name == self.0) {
+                tokio::fs::write(dst, b"mo").await?;
+                heartbeat.beat(2);
+                std::future::pending::<()>().await;
+            }
+            TokioFs::default().copy_file(src, dst, heartbeat).await
+        }
+
+        async fn remove(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().remove(path).await
+        }
+
+        async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            TokioFs::default().rename(from, to).await
+        }
+    }
+
+    /// Changes to the ledger, as the user makes them from the app.
+    type Commands = fn(&mut Vec<DownloadEntry>);
+
+    /// The real filesystem, except that `commands` change the ledger while
+    /// the first file is copied.
+    struct BusyLedgerFs<'a> {
+        ledger: &'a MemoryLedger,
+        commands: Cell<Option<Commands>>,
+    }
+
+    impl Filesystem for BusyLedgerFs<'_> {
+        async fn exists(&self, path: &Path) -> bool {
+            TokioFs::default().exists(path).await
+        }
+
+        async fn is_dir(&self, path: &Path) -> bool {
+            TokioFs::default().is_dir(path).await
+        }
+
+        async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().create_dir_all(path).await
+        }
+
+        async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+            TokioFs::default().read_dir(path).await
+        }
+
+        async fn len(&self, path: &Path) -> std::io::Result<u64> {
+            TokioFs::default().len(path).await
+        }
+
+        async fn copy_file(
+            &self,
+            src: &Path,
+            dst: &Path,
+            heartbeat: &Heartbeat,
+        ) -> std::io::Result<u64> {
+            if let Some(commands) = self.commands.take() {
+                commands(&mut self.ledger.0.borrow_mut());
+            }
+            TokioFs::default().copy_file(src, dst, heartbeat).await
+        }
+
+        async fn remove(&self, path: &Path) -> std::io::Result<()> {
+            TokioFs::default().remove(path).await
+        }
+
+        async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            TokioFs::default().rename(from, to).await
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordedEvents(Vec<String>);
+
+    impl CopyEvents for RecordedEvents {
+        fn observe_download(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: f32) {}
+
+        fn copy_started(
+            &mut self,
+            _: &TransmissionConfig,
+            _: &str,
+            _: &str,
+            _: Destination,
+            _: CopyState,
+        ) {
+            self.0.push("started".to_string());
+        }
+
+        fn copy_complete(
+            &mut self,
+            _: &TransmissionConfig,
+            _: &str,
+            name: &str,
+            _: Destination,
+            _: &Path,
+            stats: &CopyStats,
+        ) {
+            self.0.push(format!(
+                "complete {name} ({} files, {} bytes)",
+                stats.files, stats.bytes
+            ));
+        }
+
+        fn copy_failed(
+            &mut self,
+            _: &TransmissionConfig,
+            _: &str,
+            name: &str,
+            _: Destination,
+            _: &str,
+        ) {
+            self.0.push(format!("failed {name}"));
+        }
+
+        fn copy_collided(
+            &mut self,
+            _: &TransmissionConfig,
+            _: &str,
+            name: &str,
+            _: Destination,
+            path: &Path,
+        ) {
+            let folder = path.file_name().unwrap_or_default().to_string_lossy();
+            self.0.push(format!("collided {name} -> {folder}"));
+        }
+    }
+
+    const HASH: &str = "c9e15763f722f23e98a29decdfae341b98d53056";
+    const NAME: &str = "Some.Movie.2024";
+
+    /// Fresh `downloads` and `movies` directories for one test.
+    struct Dirs {
+        root: PathBuf,
+        downloads: PathBuf,
+        movies: PathBuf,
+    }
+
+    impl Dirs {
+        fn new(test: &str) -> Self {
+            let root = std::env::temp_dir()
+                .join(format!("privateer-copier-{}-{test}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            let downloads = root.join("downloads");
+            let movies = root.join("movies");
+            std::fs::create_dir_all(&downloads).unwrap();
+            std::fs::create_dir_all(&movies).unwrap();
+            Self {
+                root,
+                downloads,
+                movies,
+            }
+        }
+
+        fn config(&self) -> TransmissionConfig {
+            TransmissionConfig {
+                movies_dir: Some(self.movies.display().to_string()),
+                ..Default::default()
+            }
+        }
+
+        fn torrent(&self) -> SourceTorrent {
+            SourceTorrent {
+                info_hash: Some(HASH.parse().unwrap()),
+                name: Some(NAME.to_string()),
+                percent_done: 1.0,
+                status: TransmissionStatus::Seeding,
+                download_dir: Some(self.downloads.display().to_string()),
+                done_date: None,
+                upload_ratio: 0.0,
+                seconds_seeding: 0,
+            }
+        }
+
+        /// A completed download with a nested file tree.
+        fn download_tree(&self) {
+            let src = self.downloads.join(NAME);
+            std::fs::create_dir_all(src.join("Subs")).unwrap();
+            std::fs::write(src.join("a.nfo"), "info").unwrap();
+            std::fs::write(src.join("b.mkv"), "movie").unwrap();
+            std::fs::write(src.join("Subs").join("en.srt"), "subs").unwrap();
+        }
+    }
+
+    impl Drop for Dirs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn ledger() -> MemoryLedger {
+        MemoryLedger(RefCell::new(vec![DownloadEntry::new(
+            HASH.parse().unwrap(),
+            NAME.to_string(),
+            Destination::Movies,
+        )]))
+    }
+
+    #[tokio::test]
+    async fn copies_a_complete_download() {
+        let dirs = Dirs::new("complete");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Worked);
+        assert_eq!(ledger.state(), CopyState::Copied);
+        let dst = dirs.movies.join(NAME);
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+        assert_eq!(
+            std::fs::read_to_string(dst.join("Subs").join("en.srt")).unwrap(),
+            "subs"
+        );
+        assert_eq!(
+            events.0,
+            [
+                "started".to_string(),
+                format!("complete {NAME} (3 files, 13 bytes)")
+            ]
+        );
+        let throughput = ledger.0.borrow()[0].copy_throughput().unwrap();
+        assert_eq!(13, throughput.bytes);
+    }
+
+    #[tokio::test]
+    async fn ledger_changes_made_during_a_copy_are_kept() {
+        const OTHER: &str = "0123456789abcdef0123456789abcdef01234567";
+        let dirs = Dirs::new("busy-ledger");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut failed = DownloadEntry::new(
+            OTHER.parse().unwrap(),
+            "Other.Movie.2023".to_string(),
+            Destination::Movies,
+        );
+        failed.copy_state = CopyState::Failed;
+        failed.failure = Some("disk full".to_string());
+        ledger.0.borrow_mut().push(failed);
+        let fs = BusyLedgerFs {
+            ledger: &ledger,
+            commands: Cell::new(Some(|ledger| {
+                // retry_copies
+                ledger[1].copy_state = CopyState::NotCopied;
+                ledger[1].failure = None;
+                // add_downloads, of the download being copied and a new one
+                ledger[0]
+                    .extra_targets
+                    .push(CopyTarget::new(Destination::Shows));
+                ledger.push(DownloadEntry::new(
+                    "fedcba9876543210fedcba9876543210fedcba98".parse().unwrap(),
+                    "New.Movie.2025".to_string(),
+                    Destination::Movies,
+                ));
+                // copy_now and set_user_note
+                ledger[1].copy_now = true;
+                ledger[1].user_note = Some("for mum".to_string());
+            })),
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![dirs.torrent()]), fs);
+
+        let outcome = copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(outcome, CycleOutcome::Worked);
+        let ledger = ledger.0.borrow();
+        assert_eq!(3, ledger.len());
+        let targets = ledger[0].targets();
+        assert_eq!(CopyState::Copied, targets[0].copy_state);
+        assert_eq!(Destination::Shows, targets[1].destination);
+        assert_eq!(CopyState::NotCopied, targets[1].copy_state);
+        assert_eq!(CopyState::NotCopied, ledger[1].copy_state);
+        assert_eq!(None, ledger[1].failure);
+        assert!(ledger[1].copy_now);
+        assert_eq!(Some("for mum"), ledger[1].user_note.as_deref());
+        assert_eq!("New.Movie.2025", ledger[2].name);
+    }
+
+    #[tokio::test]
+    async fn extra_destinations_get_copies_of_their_own() {
+        let dirs = Dirs::new("extra");
+        dirs.download_tree();
+        let shows = dirs.root.join("shows");
+        std::fs::create_dir_all(&shows).unwrap();
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0]
+            .extra_targets
+            .push(CopyTarget::new(Destination::Shows));
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+
+        // Without a directory only the extra destination waits
+        copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        assert_eq!(entry.extra_targets[0].copy_state, CopyState::Unconfigured);
+        assert_eq!(entry.overall_copy_state(), CopyState::Unconfigured);
+
+        let config = TransmissionConfig {
+            shows_dir: Some(shows.display().to_string()),
+            ..dirs.config()
+        };
+        copier
+            .run_cycle(&config, &mut RecordedEvents::default())
+            .await;
+
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.overall_copy_state(), CopyState::Copied);
+        let dst = shows.join(NAME);
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+        assert_eq!(
+            entry.extra_targets[0].copied_path,
+            Some(dst.display().to_string())
+        );
+        assert!(entry.extra_targets[0].copied_at.is_some());
+        assert!(dirs.movies.join(NAME).join("b.mkv").exists());
+    }
+
+    #[tokio::test]
+    async fn user_dirs_get_copies_of_their_own() {
+        let dirs = Dirs::new("user-dir");
+        dirs.download_tree();
+        let scratch = dirs.root.join("scratch");
+        std::fs::create_dir_all(&scratch).unwrap();
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0]
+            .extra_targets
+            .push(CopyTarget::in_dir(
+                Destination::Movies,
+                scratch.display().to_string(),
+            ));
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+
+        copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.overall_copy_state(), CopyState::Copied);
+        let dst = scratch.join(NAME);
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+        assert_eq!(
+            entry.extra_targets[0].copied_path,
+            Some(dst.display().to_string())
+        );
+        assert!(dirs.movies.join(NAME).join("b.mkv").exists());
+    }
+
+    #[tokio::test]
+    async fn clean_movie_names_copy_into_a_title_year_folder() {
+        let dirs = Dirs::new("clean");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let config = TransmissionConfig {
+            clean_movie_names: true,
+            ..dirs.config()
+        };
+
+        copier
+            .run_cycle(&config, &mut RecordedEvents::default())
+            .await;
+
+        let dst = dirs.movies.join("Some Movie (2024)");
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+        assert!(!dirs.movies.join(NAME).exists());
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        assert_eq!(entry.copied_path, Some(dst.display().to_string()));
+        let fs = TokioFs::default();
+        assert!(check_already_copied(&fs, &config, &entry, NAME).await);
+        // Turning the setting off doesn't lose track of the copy
+        assert!(check_already_copied(&fs, &dirs.config(), &entry, NAME).await);
+    }
+
+    #[tokio::test]
+    async fn clean_movie_names_put_a_single_file_in_its_folder() {
+        let dirs = Dirs::new("clean-file");
+        std::fs::write(dirs.downloads.join(NAME), "movie").unwrap();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let config = TransmissionConfig {
+            clean_movie_names: true,
+            ..dirs.config()
+        };
+
+        copier
+            .run_cycle(&config, &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(ledger.state(), CopyState::Copied);
+        let dst = dirs.movies.join("Some Movie (2024)").join(NAME);
+        assert_eq!(std::fs::read_to_string(dst).unwrap(), "movie");
+    }
+
+    #[tokio::test]
+    async fn unsafe_names_are_copied_under_a_safe_name() {
+        let dirs = Dirs::new("unsafe");
+        let name = "Some: Movie? 2024";
+        std::fs::create_dir_all(dirs.downloads.join(name)).unwrap();
+        std::fs::write(dirs.downloads.join(name).join("b.mkv"), "movie").unwrap();
+        let ledger = ledger();
+        let torrent = SourceTorrent {
+            name: Some(name.to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+
+        copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        let dst = dirs.movies.join("Some - Movie 2024");
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        assert_eq!(entry.safe_name.as_deref(), Some("Some - Movie 2024"));
+        assert!(check_already_copied(&TokioFs::default(), &dirs.config(), &entry, name).await);
+    }
+
+    #[tokio::test]
+    async fn names_without_a_file_name_fail_once() {
+        let dirs = Dirs::new("no-file-name");
+        let ledger = ledger();
+        let torrent = SourceTorrent {
+            name: Some("../..".to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(ledger.state(), CopyState::Failed);
+        assert_eq!(
+            ledger.0.borrow()[0].failure.as_deref(),
+            Some("'../..' can't be used as a file name")
+        );
+        assert_eq!(events.0, ["failed ../.."]);
+        assert_eq!(std::fs::read_dir(&dirs.movies).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn episode_files_are_renamed_by_the_template() {
+        let dirs = Dirs::new("episodes");
+        let name = "The.Bear.S02E05.1080p.WEB";
+        let src = dirs.downloads.join(name);
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join(format!("{name}.mkv")), "episode").unwrap();
+        std::fs::write(src.join(format!("{name}.en.srt")), "subs").unwrap();
+        std::fs::write(src.join("Extras.mkv"), "extras").unwrap();
+        let ledger = MemoryLedger(RefCell::new(vec![DownloadEntry::new(
+            HASH.parse().unwrap(),
+            name.to_string(),
+            Destination::Shows,
+        )]));
+        let torrent = SourceTorrent {
+            name: Some(name.to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+        let config = TransmissionConfig {
+            shows_dir: Some(dirs.movies.display().to_string()),
+            rename_episodes: true,
+            ..Default::default()
+        };
+
+        copier
+            .run_cycle(&config, &mut RecordedEvents::default())
+            .await;
+
+        let dst = dirs.movies.join(name);
+        assert!(dst.join("The Bear - S02E05.mkv").exists());
+        assert!(dst.join("The Bear - S02E05.en.srt").exists());
+        assert!(dst.join("Extras.mkv").exists());
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        assert_eq!(
+            entry.renamed_files[0],
+            RenamedFile {
+                original: dst.join(format!("{name}.mkv")).display().to_string(),
+                renamed: dst.join("The Bear - S02E05.mkv").display().to_string(),
+            }
+        );
+        assert_eq!(entry.renamed_files.len(), 2);
+        assert_eq!(
+            entry.note.as_deref(),
+            Some("Kept the names of files whose episode couldn't be parsed: Extras.mkv")
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_download_dir_is_read_through_its_mapping() {
+        let dirs = Dirs::new("mapped");
+        dirs.download_tree();
+        let ledger = ledger();
+        let torrent = SourceTorrent {
+            download_dir: Some("/home/seed/downloads".to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+        let config = TransmissionConfig {
+            path_mappings: vec![(
+                "/home/seed/downloads/".to_string(),
+                dirs.downloads.display().to_string(),
+            )],
+            ..dirs.config()
+        };
+
+        copier
+            .run_cycle(&config, &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(ledger.state(), CopyState::Copied);
+        assert!(dirs.movies.join(NAME).join("b.mkv").exists());
+    }
+
+    #[tokio::test]
+    async fn failure_mid_tree_marks_failed_and_removes_partial_copy() {
+        let dirs = Dirs::new("failure");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            FailingFs("b.mkv"),
+        );
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::Failed);
+        let failure = ledger.0.borrow()[0].failure.clone().unwrap();
+        assert!(failure.contains("b.mkv"), "{failure}");
+        assert!(!dirs.movies.join(NAME).exists());
+        assert_eq!(events.0, ["started".to_string(), format!("failed {NAME}")]);
+    }
+
+    #[tokio::test]
+    async fn unmounted_destination_fails_without_copying() {
+        let dirs = Dirs::new("unmounted");
+        dirs.download_tree();
+        let share = dirs.root.join("share").join("Movies");
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let config = TransmissionConfig {
+            movies_dir: Some(share.display().to_string()),
+            movies_expect_mount: true,
+            ..Default::default()
+        };
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&config, &mut events).await;
+
+        assert_eq!(ledger.state(), CopyState::Failed);
+        assert_eq!(events.0, ["started".to_string(), format!("failed {NAME}")]);
+        assert!(!share.exists());
+    }
+
+    #[tokio::test]
+    async fn unreachable_sftp_host_fails_the_copy() {
+        let dirs = Dirs::new("sftp-down");
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let config = TransmissionConfig {
+            copy_backend: CopyBackend::Sftp(privateer_wire_types::SftpConfig {
+                host: "127.0.0.1".into(),
+                port: 1,
+                user: "seed".into(),
+                ..Default::default()
+            }),
+            ..dirs.config()
+        };
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier.run_cycle(&config, &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::Failed);
+        assert_eq!(events.0, ["started".to_string(), format!("failed {NAME}")]);
+        assert!(!dirs.movies.join(NAME).exists());
+    }
+
+    #[tokio::test]
+    async fn already_at_destination_is_marked_copied_without_copying() {
+        let dirs = Dirs::new("exists");
+        dirs.download_tree();
+        // The same files, renamed
+        let dst = dirs.movies.join(NAME);
+        std::fs::create_dir_all(&dst).unwrap();
+        std::fs::write(dst.join("Some Movie.nfo"), "INFO").unwrap();
+        std::fs::write(dst.join("Some Movie.mkv"), "MOVIE").unwrap();
+        std::fs::write(dst.join("Some Movie.en.srt"), "SUBS").unwrap();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(ledger.state(), CopyState::Copied);
+        assert_eq!(
+            ledger.0.borrow()[0].copied_path,
+            Some(dst.display().to_string())
+        );
+        assert!(!dst.join("b.mkv").exists());
+        assert!(events.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn same_name_with_different_files_is_copied_alongside() {
+        let dirs = Dirs::new("collision");
+        dirs.download_tree();
+        let dst = dirs.movies.join(NAME);
+        std::fs::create_dir_all(&dst).unwrap();
+        std::fs::write(dst.join("b.mkv"), "another movie").unwrap();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&dirs.config(), &mut events).await;
+
+        let copy = dirs.movies.join(format!("{NAME} (2)"));
+        assert_eq!(ledger.state(), CopyState::Copied);
+        assert_eq!(
+            ledger.0.borrow()[0].copied_path,
+            Some(copy.display().to_string())
+        );
+        assert_eq!(
+            std::fs::read_to_string(copy.join("b.mkv")).unwrap(),
+            "movie"
+        );
+        assert!(copy.join("Subs").join("en.srt").exists());
+        assert_eq!(
+            std::fs::read_to_string(dst.join("b.mkv")).unwrap(),
+            "another movie"
+        );
+        assert!(!dst.join("a.nfo").exists());
+        assert_eq!(
+            events.0[..2],
+            [
+                format!("collided {NAME} -> {NAME} (2)"),
+                "started".to_string()
+            ]
+        );
+    }
+
+    /// A share that stops answering for the paths under it.
+    struct HangsUnder(PathBuf);
+
+    impl path_probe::ExistsCheck for HangsUnder {
+        fn exists(&self, path: &Path) -> bool {
+            if path.starts_with(&self.0) {
+                std::thread::sleep(Duration::from_millis(300));
+            }
+            path.exists()
+        }
+    }
+
+    #[tokio::test]
+    async fn destinations_that_dont_answer_defer_the_copy() {
+        let dirs = Dirs::new("unanswered");
+        dirs.download_tree();
+        let ledger = ledger();
+        let hanging = |under: PathBuf| {
+            let probe = PathProbe::new(
+                HangsUnder(under),
+                Duration::from_millis(20),
+                path_probe::CACHE_TTL,
+            );
+            TokioFs::new(probe)
+        };
+        let mut events = RecordedEvents::default();
+
+        // Whether there is a copy already can't be told
+        let fs = hanging(dirs.movies.clone());
+        let mut copier = Copier::new(&ledger, FixedSource(vec![dirs.torrent()]), fs);
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(CopyState::NotCopied, ledger.state());
+
+        // A different download is there, but whether the next name is free
+        // can't be told
+        let other = dirs.movies.join(NAME);
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("b.mkv"), "another movie").unwrap();
+        let fs = hanging(dirs.movies.join(format!("{NAME} (2)")));
+        let mut copier = Copier::new(&ledger, FixedSource(vec![dirs.torrent()]), fs);
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(CopyState::NotCopied, ledger.state());
+
+        assert!(events.0.is_empty());
+        assert_eq!(1, std::fs::read_dir(&dirs.movies).unwrap().count());
+        assert_eq!(1, std::fs::read_dir(&other).unwrap().count());
+    }
+
+    #[tokio::test]
+    async fn copies_that_cant_be_compared_stay_pending() {
+        let dirs = Dirs::new("uncomparable");
+        let dst = dirs.movies.join(NAME);
+        std::fs::create_dir_all(&dst).unwrap();
+        std::fs::write(dst.join("b.mkv"), "another movie").unwrap();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        // The download can't be listed
+        let config = TransmissionConfig {
+            copy_backend: CopyBackend::Sftp(privateer_wire_types::SftpConfig {
+                host: "127.0.0.1".into(),
+                port: 1,
+                user: "seed".into(),
+                ..Default::default()
+            }),
+            ..dirs.config()
+        };
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&config, &mut events).await;
+
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+        assert_eq!(ledger.0.borrow()[0].copied_path, None);
+        assert!(events.0.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dst.join("b.mkv")).unwrap(),
+            "another movie"
+        );
+        assert!(!dirs.movies.join(format!("{NAME} (2)")).exists());
+    }
+
+    #[tokio::test]
+    async fn free_path_numbers_taken_names() {
+        let dirs = Dirs::new("free-path");
+        let fs = TokioFs::default();
+        let folder = dirs.movies.join("Movie (2024)");
+        assert_eq!(Some(folder.clone()), free_path(&fs, &folder, false).await);
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::create_dir_all(dirs.movies.join("Movie (2024) (2)")).unwrap();
+        assert_eq!(
+            Some(dirs.movies.join("Movie (2024) (3)")),
+            free_path(&fs, &folder, false).await
+        );
+
+        let file = dirs.movies.join("Show.S01E01.mkv");
+        std::fs::write(&file, "episode").unwrap();
+        assert_eq!(
+            Some(dirs.movies.join("Show.S01E01 (2).mkv")),
+            free_path(&fs, &file, true).await
+        );
+    }
+
+    #[tokio::test]
+    async fn nested_destinations_prefer_the_deeper_one() {
+        let dirs = Dirs::new("nested-detect");
+        let shows = dirs.movies.join("Shows");
+        std::fs::create_dir_all(shows.join("Shows")).unwrap();
+        let mut config = TransmissionConfig {
+            shows_dir: Some(shows.display().to_string()),
+            ..dirs.config()
+        };
+        // "Shows" is both the shows directory, under Movies, and a download
+        // copied into it
+        assert_eq!(
+            Some((Destination::Shows, shows.join("Shows"))),
+            detect_destination(&TokioFs::default(), &config, "Shows").await
+        );
+
+        config.shows_dir = config.movies_dir.clone();
+        std::fs::create_dir_all(dirs.movies.join(NAME)).unwrap();
+        assert_eq!(
+            Some((Destination::Movies, dirs.movies.join(NAME))),
+            detect_destination(&TokioFs::default(), &config, NAME).await
+        );
+    }
+
+    #[test]
+    fn only_finished_verified_torrents_are_ready() {
+        assert!(is_ready(1.0, TransmissionStatus::Seeding));
+        assert!(is_ready(1.0, TransmissionStatus::Stopped));
+        assert!(!is_ready(0.4, TransmissionStatus::Downloading));
+        assert!(!is_ready(1.0, TransmissionStatus::Verifying));
+        assert!(!is_ready(1.0, TransmissionStatus::QueuedVerify));
+    }
+
+    #[test]
+    fn destination_dirs_are_compared_on_disk() {
+        let dirs = Dirs::new("overlap");
+        let mut config = TransmissionConfig {
+            shows_dir: Some(dirs.downloads.display().to_string()),
+            ..dirs.config()
+        };
+        assert_eq!(Ok(()), check_destination_dirs(&config));
+
+        config.shows_dir = Some(dirs.movies.join("..").join("movies").display().to_string());
+        assert_eq!(
+            Err(SettingsProblem::SameDestinationDirs),
+            check_destination_dirs(&config)
+        );
+
+        config.shows_dir = Some(dirs.movies.join("Shows").display().to_string());
+        assert_eq!(
+            Err(SettingsProblem::NestedDestinationDirs),
+            check_destination_dirs(&config)
+        );
+
+        #[cfg(unix)]
+        {
+            let link = dirs.root.join("media");
+            std::os::unix::fs::symlink(&dirs.movies, &link).unwrap();
+            config.shows_dir = Some(link.display().to_string());
+            assert_eq!(
+                Err(SettingsProblem::SameDestinationDirs),
+                check_destination_dirs(&config)
+            );
+            config.shows_dir = Some(link.join("Shows").display().to_string());
+            assert_eq!(
+                Err(SettingsProblem::NestedDestinationDirs),
+                check_destination_dirs(&config)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rename_after_copy_finds_the_copy_under_the_old_name() {
+        let dirs = Dirs::new("rename-after");
+        std::fs::create_dir_all(dirs.movies.join(NAME)).unwrap();
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0].copy_state = CopyState::Copied;
+        let torrent = SourceTorrent {
+            name: Some("Renamed Movie".to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+        let mut events = RecordedEvents::default();
+
+        copier.run_cycle(&dirs.config(), &mut events).await;
+
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.name, "Renamed Movie");
+        assert_eq!(entry.previous_names, [NAME]);
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        assert!(
+            check_already_copied(&TokioFs::default(), &dirs.config(), &entry, "Renamed Movie")
+                .await
+        );
+        assert!(events.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rename_before_copy_copies_under_the_new_name() {
+        let dirs = Dirs::new("rename-before");
+        dirs.download_tree();
+        std::fs::rename(
+            dirs.downloads.join(NAME),
+            dirs.downloads.join("Renamed Movie"),
+        )
+        .unwrap();
+        let ledger = ledger();
+        let torrent = SourceTorrent {
+            name: Some("Renamed Movie".to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![torrent]), TokioFs::default());
+
+        copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        let entry = ledger.0.borrow()[0].clone();
+        assert_eq!(entry.name, "Renamed Movie");
+        assert_eq!(entry.copy_state, CopyState::Copied);
+        let dst = dirs.movies.join("Renamed Movie");
+        assert_eq!(entry.copied_path, Some(dst.display().to_string()));
+        assert!(dst.join("b.mkv").exists());
+        assert!(!dirs.movies.join(NAME).exists());
+    }
+
+    #[tokio::test]
+    async fn missing_source_is_left_pending() {
+        let dirs = Dirs::new("missing");
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+        assert!(events.0.is_empty());
+    }
+
+    fn listed(name: &str, length: u64, wanted: bool) -> TorrentFile {
+        TorrentFile {
+            name: format!("{NAME}/{name}"),
+            length,
+            wanted,
+        }
+    }
+
+    #[tokio::test]
+    async fn part_files_defer_the_copy_until_complete() {
+        let dirs = Dirs::new("part");
+        let src = dirs.downloads.join(NAME);
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("a.nfo"), "info").unwrap();
+        std::fs::write(src.join("b.mkv.part"), "mov").unwrap();
+        let ledger = ledger();
+        let files = vec![listed("a.nfo", 4, true), listed("b.mkv", 5, true)];
+        let source = ListedFiles(FixedSource(vec![dirs.torrent()]), files);
+        let mut copier = Copier::new(&ledger, source, TokioFs::default());
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+        assert!(events.0.is_empty());
+        assert!(!dirs.movies.join(NAME).exists());
+
+        // Still short of its length under the final name
+        std::fs::rename(src.join("b.mkv.part"), src.join("b.mkv")).unwrap();
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+
+        std::fs::write(src.join("b.mkv"), "movie").unwrap();
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(ledger.state(), CopyState::Copied);
+        let dst = dirs.movies.join(NAME);
+        assert_eq!(std::fs::read_to_string(dst.join("b.mkv")).unwrap(), "movie");
+    }
+
+    #[tokio::test]
+    async fn unwanted_files_dont_hold_back_the_copy() {
+        let dirs = Dirs::new("unwanted");
+        dirs.download_tree();
+        let ledger = ledger();
+        let files = vec![
+            listed("b.mkv", 5, true),
+            listed("Samples/sample.mkv", 1024, false),
+        ];
+        let source = ListedFiles(FixedSource(vec![dirs.torrent()]), files);
+        let mut copier = Copier::new(&ledger, source, TokioFs::default());
+
+        copier
+            .run_cycle(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(ledger.state(), CopyState::Copied);
+    }
+
+    #[tokio::test]
+    async fn quiet_hours_defer_copies_unless_copy_now() {
+        let dirs = Dirs::new("quiet");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        )
+        .with_clock(|| TimeOfDay::new(22, 0).unwrap());
+        let mut events = RecordedEvents::default();
+        let config = TransmissionConfig {
+            quiet_hours: Some((
+                TimeOfDay::new(21, 0).unwrap(),
+                TimeOfDay::new(23, 0).unwrap(),
+            )),
+            ..dirs.config()
+        };
+
+        let outcome = copier.run_cycle(&config, &mut events).await;
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+        assert!(!dirs.movies.join(NAME).exists());
+
+        ledger.0.borrow_mut()[0].copy_now = true;
+        let outcome = copier.run_cycle(&config, &mut events).await;
+        assert_eq!(outcome, CycleOutcome::Worked);
+        assert_eq!(ledger.state(), CopyState::Copied);
+        assert!(!ledger.0.borrow()[0].copy_now);
+    }
+
+    #[tokio::test]
+    async fn stopped_copier_starts_no_copies() {
+        let dirs = Dirs::new("stopped");
+        dirs.download_tree();
+        let ledger = ledger();
+        let (stop, stopped) = watch::channel(false);
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        )
+        .with_stop(stopped);
+        let mut events = RecordedEvents::default();
+
+        stop.send(true).unwrap();
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+        assert!(!dirs.movies.join(NAME).exists());
+        assert!(events.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn seeding_goal_stops_only_tracked_torrents() {
+        let dirs = Dirs::new("seeding");
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0].copy_state = CopyState::Copied;
+        let tracked = SourceTorrent {
+            upload_ratio: 1.2,
+            ..dirs.torrent()
+        };
+        let untracked = SourceTorrent {
+            info_hash: Some("0123456789abcdef0123456789abcdef01234567".parse().unwrap()),
+            name: Some("Managed.Elsewhere".to_string()),
+            upload_ratio: 9.0,
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![tracked, untracked]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+        let config = TransmissionConfig {
+            movies_seed_limits: SeedLimits {
+                seed_ratio_limit: Some(1.5),
+                seed_time_limit: None,
+            },
+            ..dirs.config()
+        };
+
+        copier.run_cycle(&config, &mut events).await;
+        assert!(!ledger.0.borrow()[0].seeding_completed);
+        assert_eq!(copier.source.0[0].status, TransmissionStatus::Seeding);
+
+        copier.source.0[0].upload_ratio = 1.5;
+        copier.run_cycle(&config, &mut events).await;
+        assert!(ledger.0.borrow()[0].seeding_completed);
+        assert_eq!(copier.source.0[0].status, TransmissionStatus::Stopped);
+        assert_eq!(copier.source.0[1].status, TransmissionStatus::Seeding);
+    }
+
+    #[tokio::test]
+    async fn reconcile_now_counts_what_it_finds() {
+        let dirs = Dirs::new("reconcile-now");
+        let copied = dirs.movies.join(NAME);
+        std::fs::create_dir_all(&copied).unwrap();
+        std::fs::write(copied.join("b.mkv"), "movie").unwrap();
+        let other = "Other.Movie.2023";
+        std::fs::create_dir_all(dirs.movies.join(other)).unwrap();
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0].copied_path = Some(copied.display().to_string());
+        let untracked = SourceTorrent {
+            info_hash: Some("0123456789abcdef0123456789abcdef01234567".parse().unwrap()),
+            name: Some(other.to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent(), untracked]),
+            TokioFs::default(),
+        );
+
+        let summary = copier
+            .reconcile_now(&dirs.config(), &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(
+            summary,
+            ReconcileSummary {
+                auto_added: 1,
+                marked_copied: 1,
+                ..Default::default()
+            }
+        );
+        let entries = ledger.0.borrow();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.copy_state == CopyState::Copied));
+        assert_eq!(entries[1].name, other);
+        assert_eq!(
+            (DownloadOrigin::User, DownloadOrigin::AutoDetected),
+            (entries[0].origin, entries[1].origin)
+        );
+    }
+
+    #[tokio::test]
+    async fn only_assigned_leaves_untracked_torrents_alone() {
+        let dirs = Dirs::new("only-assigned");
+        let other = "Other.Movie.2023";
+        std::fs::create_dir_all(dirs.movies.join(other)).unwrap();
+        let ledger = MemoryLedger(RefCell::new(vec![]));
+        let untracked = SourceTorrent {
+            name: Some(other.to_string()),
+            ..dirs.torrent()
+        };
+        let mut copier = Copier::new(&ledger, FixedSource(vec![untracked]), TokioFs::default());
+        let config = TransmissionConfig {
+            only_assigned: true,
+            ..dirs.config()
+        };
+
+        let summary = copier
+            .reconcile_now(&config, &mut RecordedEvents::default())
+            .await;
+
+        assert_eq!(ReconcileSummary::default(), summary);
+        assert!(ledger.0.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn paused_copier_defers_copies_even_copy_now() {
+        let dirs = Dirs::new("paused");
+        dirs.download_tree();
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0].copy_now = true;
+        let (pause, paused) = watch::channel(false);
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        )
+        .with_pause(paused);
+        let mut events = RecordedEvents::default();
+        let config = TransmissionConfig {
+            copying_paused: true,
+            ..dirs.config()
+        };
+
+        // Paused when the config was saved
+        let outcome = copier.run_cycle(&config, &mut events).await;
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::NotCopied);
+
+        // Paused since
+        pause.send(true).unwrap();
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert!(!dirs.movies.join(NAME).exists());
+
+        pause.send(false).unwrap();
+        let outcome = copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(outcome, CycleOutcome::Worked);
+        assert_eq!(ledger.state(), CopyState::Copied);
+    }
+
+    #[tokio::test]
+    async fn paused_copy_waits_before_its_next_file() {
+        let dirs = Dirs::new("suspended");
+        dirs.download_tree();
+        let (pause, paused) = watch::channel(true);
+        let mut suspend = Suspend {
+            pause: Some(paused),
+            stop: None,
+            waited: Duration::ZERO,
+        };
+        let src = dirs.downloads.join(NAME);
+        let dst = dirs.movies.join(NAME);
+        let mut stats = CopyStats::default();
+        let heartbeat = Heartbeat::default();
+        let fs = TokioFs::default();
+        let source = LocalSource(&fs);
+        {
+            let copy = copy_recursive(
+                &source,
+                &fs,
+                &src,
+                &dst,
+                &mut stats,
+                &mut suspend,
+                &heartbeat,
+            );
+            let mut copy = std::pin::pin!(copy);
+
+            let waited = tokio::time::timeout(Duration::from_millis(50), &mut copy).await;
+            assert!(waited.is_err());
+            assert!(!dst.join("a.nfo").exists());
+            assert!(!dst.join("b.mkv").exists());
+
+            pause.send(false).unwrap();
+            copy.await.unwrap();
+        }
+        assert!(dst.join("b.mkv").exists());
+        // Not counted as time spent copying
+        assert!(suspend.waited >= Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_copy_fails_and_removes_partial_copy() {
+        let dirs = Dirs::new("stalled");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            StallingFs("b.mkv"),
+        );
+        let mut events = RecordedEvents::default();
+        let config = TransmissionConfig {
+            copy_stall_minutes: 1,
+            ..dirs.config()
+        };
+
+        let outcome = copier.run_cycle(&config, &mut events).await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::Failed);
+        let failure = ledger.0.borrow()[0].failure.clone().unwrap();
+        assert!(failure.contains("stalled"), "{failure}");
+        assert!(!dirs.movies.join(NAME).exists());
+        assert_eq!(events.0, ["started".to_string(), format!("failed {NAME}")]);
+    }
+
+    #[tokio::test]
+    async fn interrupted_copies_are_failed_at_startup() {
+        let ledger = ledger();
+        ledger.0.borrow_mut()[0].copy_state = CopyState::Copying;
+        let copier = Copier::new(&ledger, FixedSource(vec![]), TokioFs::default());
+
+        copier.fail_interrupted().await;
+
+        assert_eq!(ledger.state(), CopyState::Failed);
+        let failure = ledger.0.borrow()[0].failure.clone().unwrap();
+        assert!(failure.contains("interrupted"), "{failure}");
+    }
+
+    #[test]
+    fn chunked_copy_copies_contents() {
+        let dirs = Dirs::new("chunked");
+        let src = dirs.downloads.join("big.bin");
+        let dst = dirs.movies.join("big.bin");
+        let contents: Vec<u8> = (0..COPY_BUFFER_BYTES * 2 + 17)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&src, &contents).unwrap();
+
+        let heartbeat = Heartbeat::default();
+        let copied = copy_chunked(&src, &dst, &AtomicBool::new(false), &heartbeat).unwrap();
+
+        assert_eq!(copied, contents.len() as u64);
+        assert_eq!(heartbeat.bytes(), copied);
+        assert_eq!(std::fs::read(&dst).unwrap(), contents);
+    }
+
+    #[test]
+    fn chunked_copy_stops_when_cancelled() {
+        let dirs = Dirs::new("cancelled");
+        let src = dirs.downloads.join("big.bin");
+        std::fs::write(&src, b"data").unwrap();
+
+        let error = copy_chunked(
+            &src,
+            &dirs.movies.join("big.bin"),
+            &AtomicBool::new(true),
+            &Heartbeat::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    /// Compares `tokio::fs::copy` with the large-file path on a 1 GiB file.
+    ///
+    /// `cargo test -p privateer --release -- --ignored --nocapture copy_throughput`
+    ///
+    /// Set `PRIVATEER_BENCH_DIR` to a directory on the drive to test, e.g. a
+    /// mounted NAS share.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn copy_throughput() {
+        let dirs = Dirs::new("throughput");
+        let dst_dir = std::env::var_os("PRIVATEER_BENCH_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| dirs.movies.clone());
+        let src = dirs.downloads.join("large.bin");
+        let chunk: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut file = std::fs::File::create(&src).unwrap();
+        for _ in 0..1024 {
+            file.write_all(&chunk).unwrap();
+        }
+        drop(file);
+        let megabytes = 1024.0;
+
+        let dst = dst_dir.join("privateer-bench-async.bin");
+        let start = std::time::Instant::now();
+        tokio::fs::copy(&src, &dst).await.unwrap();
+        let before = start.elapsed();
+        std::fs::remove_file(&dst).unwrap();
+
+        let dst = dst_dir.join("privateer-bench-chunked.bin");
+        let start = std::time::Instant::now();
+        TokioFs::default()
+            .copy_file(&src, &dst, &Heartbeat::default())
+            .await
+            .unwrap();
+        let after = start.elapsed();
+        std::fs::remove_file(&dst).unwrap();
+
+        println!(
+            "tokio::fs::copy: {before:.2?} ({:.0} MB/s), chunked: {after:.2?} ({:.0} MB/s)",
+            megabytes / before.as_secs_f64(),
+            megabytes / after.as_secs_f64(),
+        );
+    }
+
+    #[tokio::test]
+    async fn unconfigured_destination_waits_for_a_directory() {
+        let dirs = Dirs::new("unconfigured");
+        dirs.download_tree();
+        let ledger = ledger();
+        let mut copier = Copier::new(
+            &ledger,
+            FixedSource(vec![dirs.torrent()]),
+            TokioFs::default(),
+        );
+        let mut events = RecordedEvents::default();
+
+        let outcome = copier
+            .run_cycle(&TransmissionConfig::default(), &mut events)
+            .await;
+
+        assert_eq!(outcome, CycleOutcome::Idle);
+        assert_eq!(ledger.state(), CopyState::Unconfigured);
+        assert!(!dirs.movies.join(NAME).exists());
+        assert!(events.0.is_empty());
+
+        // Copied once the directory is set
+        copier.run_cycle(&dirs.config(), &mut events).await;
+        assert_eq!(ledger.state(), CopyState::Copied);
+    }
+
+    #[test]
+    fn source_dirs_are_mapped_unless_read_over_sftp() {
+        let mut config = TransmissionConfig {
+            path_mappings: vec![(
+                "/home/seed/downloads".to_string(),
+                "/mnt/seedbox".to_string(),
+            )],
+            ..Default::default()
+        };
+        assert_eq!(
+            PathBuf::from("/mnt/seedbox/tv"),
+            source_dir(&config, "/home/seed/downloads/tv")
+        );
+
+        config.copy_backend = CopyBackend::Sftp(privateer_wire_types::SftpConfig::default());
+        assert_eq!(
+            PathBuf::from("/home/seed/downloads/tv"),
+            source_dir(&config, "/home/seed/downloads/tv")
+        );
+    }
+
+    #[test]
+    fn copy_paths_use_the_library_name() {
+        let config = TransmissionConfig {
+            movies_dir: Some("/media/movies".to_string()),
+            clean_movie_names: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(PathBuf::from("/media/movies/Some Movie (2024)")),
+            copy_path(&config, Destination::Movies, NAME)
+        );
+        assert_eq!(None, copy_path(&config, Destination::Shows, NAME));
+        assert_eq!(None, copy_path(&config, Destination::Movies, "../.."));
+    }
+
+    #[tokio::test]
+    async fn locate_finds_the_download_and_its_copy() {
+        let dirs = Dirs::new("locate");
+        dirs.download_tree();
+        let copied = dirs.movies.join("Renamed");
+        std::fs::create_dir_all(&copied).unwrap();
+        std::fs::write(copied.join("b.mkv"), "movie").unwrap();
+        let mut entry = ledger().0.into_inner().remove(0);
+        entry.copy_state = CopyState::Copied;
+        entry.copied_path = Some(copied.display().to_string());
+
+        let fs = TokioFs::default();
+        let config = dirs.config();
+        let location = locate(
+            &fs,
+            &None,
+            &config,
+            &entry.info_hash,
+            Some(&dirs.torrent()),
+            Some(&entry),
+        )
+        .await;
+
+        assert!(location.in_client);
+        let source = location.source.unwrap();
+        assert!(source.exists);
+        assert_eq!(Some(13), source.bytes);
+        let [movies, shows] = &location.destinations[..] else {
+            panic!("{:?}", location.destinations);
+        };
+        assert_eq!(Destination::Movies, movies.destination);
+        assert_eq!(
+            Some(CopyState::Copied),
+            movies.target.as_ref().map(|t| t.copy_state)
+        );
+        let planned = movies.planned.as_ref().unwrap();
+        assert_eq!(dirs.movies.join(NAME).display().to_string(), planned.path);
+        assert!(!planned.exists);
+        let found = movies.found.as_ref().unwrap();
+        assert_eq!(copied.display().to_string(), found.path);
+        assert_eq!(Some(5), found.bytes);
+        assert!(shows.target.is_none() && shows.planned.is_none() && shows.found.is_none());
+
+        // Gone from the client, with the copy moved back to its own name
+        std::fs::rename(&copied, dirs.movies.join(NAME)).unwrap();
+        let fs = TokioFs::default();
+        let location = locate(&fs, &None, &config, &entry.info_hash, None, Some(&entry)).await;
+        assert!(!location.in_client && location.source.is_none());
+        let movies = &location.destinations[0];
+        assert!(movies.planned.as_ref().unwrap().exists);
+        assert_eq!(movies.planned, movies.found);
+    }
+}
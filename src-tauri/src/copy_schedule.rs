@@ -0,0 +1,157 @@
+//! When the background copy task runs its cycles.
+//!
+//! The first cycle runs as soon as the task starts so the ledger is
+//! reconciled before the Downloads view first asks for it.  After that the
+//! task waits for the idle interval or a wake-up from `add_download`, except
+//! that a cycle which copied something is followed by another after a brief
+//! pause, since more downloads may have completed in the meantime.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+/// How long to wait between cycles when there is nothing to do.
+pub const IDLE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Pause after a cycle that did work, so a busy ledger can't spin the task.
+pub const BUSY_PAUSE: Duration = Duration::from_secs(1);
+
+/// Pause after a wake-up so a burst of adds is handled by one cycle.
+pub const SETTLE_PAUSE: Duration = Duration::from_millis(250);
+
+/// What a copy cycle did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleOutcome {
+    /// Nothing was copied.
+    Idle,
+    /// At least one entry was copied.
+    Worked,
+}
+
+/// Why the next cycle is running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyWake {
+    /// The task just started.
+    Startup,
+    /// The previous cycle did work.
+    Busy,
+    /// The idle interval elapsed.
+    Interval,
+    /// `add_download` (or similar) signalled the task.
+    Notified,
+}
+
+/// Schedules the copy task's cycles.
+pub struct CopySchedule {
+    notify: Arc<Notify>,
+    previous: Option<CycleOutcome>,
+}
+
+impl CopySchedule {
+    pub fn new(notify: Arc<Notify>) -> Self {
+        Self {
+            notify,
+            previous: None,
+        }
+    }
+
+    /// Wait until the next cycle should run.
+    ///
+    /// Pending notifications are consumed on return: the cycle about to run
+    /// reads the ledger afterwards, so it sees everything they announced.
+    pub async fn wait(&mut self) -> CopyWake {
+        let wake = match self.previous {
+            None => CopyWake::Startup,
+            Some(CycleOutcome::Worked) => {
+                tokio::time::sleep(BUSY_PAUSE).await;
+                CopyWake::Busy
+            }
+            Some(CycleOutcome::Idle) => {
+                tokio::select! {
+                    _ = tokio::time::sleep(IDLE_INTERVAL) => CopyWake::Interval,
+                    _ = self.notify.notified() => {
+                        tokio::time::sleep(SETTLE_PAUSE).await;
+                        CopyWake::Notified
+                    }
+                }
+            }
+        };
+        self.drain();
+        wake
+    }
+
+    /// Record the outcome of the cycle that just ran.
+    pub fn finished(&mut self, outcome: CycleOutcome) {
+        self.previous = Some(outcome);
+    }
+
+    /// Consume a stored notification, if there is one.
+    fn drain(&self) {
+        let notified = std::pin::pin!(self.notify.notified());
+        notified.enable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn first_cycle_runs_immediately() {
+        let mut schedule = CopySchedule::new(Arc::new(Notify::new()));
+        let start = Instant::now();
+        assert_eq!(schedule.wait().await, CopyWake::Startup);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_of_notifications_wakes_once() {
+        let notify = Arc::new(Notify::new());
+        let mut schedule = CopySchedule::new(notify.clone());
+        schedule.wait().await;
+        schedule.finished(CycleOutcome::Idle);
+
+        notify.notify_one();
+        notify.notify_one();
+        let start = Instant::now();
+        assert_eq!(schedule.wait().await, CopyWake::Notified);
+        assert_eq!(start.elapsed(), SETTLE_PAUSE);
+
+        // A notification during the settle pause is covered by the same cycle
+        schedule.finished(CycleOutcome::Idle);
+        notify.notify_one();
+        let late = tokio::spawn({
+            let notify = notify.clone();
+            async move {
+                tokio::time::sleep(SETTLE_PAUSE / 2).await;
+                notify.notify_one();
+            }
+        });
+        assert_eq!(schedule.wait().await, CopyWake::Notified);
+        late.await.unwrap();
+        schedule.finished(CycleOutcome::Idle);
+
+        let start = Instant::now();
+        assert_eq!(schedule.wait().await, CopyWake::Interval);
+        assert_eq!(start.elapsed(), IDLE_INTERVAL);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn busy_cycle_skips_the_idle_wait() {
+        let notify = Arc::new(Notify::new());
+        let mut schedule = CopySchedule::new(notify.clone());
+        schedule.wait().await;
+        schedule.finished(CycleOutcome::Worked);
+
+        notify.notify_one();
+        let start = Instant::now();
+        assert_eq!(schedule.wait().await, CopyWake::Busy);
+        assert_eq!(start.elapsed(), BUSY_PAUSE);
+
+        // The notification was consumed by the busy cycle
+        schedule.finished(CycleOutcome::Idle);
+        assert_eq!(schedule.wait().await, CopyWake::Interval);
+    }
+}
@@ -0,0 +1,129 @@
+//! Where Privateer keeps its files.
+//!
+//! Everything lives in the app data dir by default.  The config and ledger
+//! can be pointed elsewhere, e.g. at a synced directory shared by the desktop
+//! app and a headless copy engine running next to Transmission.
+
+use std::path::{Path, PathBuf};
+
+/// Where headless keeps its files instead of the app data dir.
+pub const DATA_DIR_VAR: &str = "PRIVATEER_DATA_DIR";
+
+/// Overrides the Transmission config file.
+pub const CONFIG_VAR: &str = "PRIVATEER_CONFIG";
+
+/// Overrides the downloads ledger file.
+pub const LEDGER_VAR: &str = "PRIVATEER_LEDGER";
+
+/// The bundle identifier from `tauri.conf.json`, which Tauri names the app
+/// data dir after.
+const IDENTIFIER: &str = "com.schell.privateer";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPaths {
+    pub data_dir: PathBuf,
+    pub config: PathBuf,
+    pub ledger: PathBuf,
+}
+
+impl DataPaths {
+    /// The default file names inside `data_dir`.
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self {
+            config: data_dir.join("transmission_config.json"),
+            ledger: data_dir.join("downloads.json"),
+            data_dir,
+        }
+    }
+
+    /// Paths in `data_dir`, with the config and ledger overridden by
+    /// [`CONFIG_VAR`] and [`LEDGER_VAR`] when they are set.
+    pub fn from_env(data_dir: PathBuf, env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut paths = Self::new(data_dir);
+        if let Some(config) = env(CONFIG_VAR).filter(|value| !value.is_empty()) {
+            paths.config = config.into();
+        }
+        if let Some(ledger) = env(LEDGER_VAR).filter(|value| !value.is_empty()) {
+            paths.ledger = ledger.into();
+        }
+        paths
+    }
+
+    pub fn removed(&self) -> PathBuf {
+        removed_beside(&self.ledger)
+    }
+
+    pub fn metrics(&self) -> PathBuf {
+        self.data_dir.join("copy_metrics.json")
+    }
+
+    pub fn watchlist(&self) -> PathBuf {
+        self.data_dir.join("watchlist.json")
+    }
+
+    pub fn availability(&self) -> PathBuf {
+        self.data_dir.join("availability.json")
+    }
+
+    pub fn feeds_seen(&self) -> PathBuf {
+        self.data_dir.join("feeds_seen.json")
+    }
+
+    pub fn logs(&self) -> PathBuf {
+        self.data_dir.join("logs")
+    }
+}
+
+/// The ledger entries removed from the ledger at `ledger`, kept next to it
+/// so they follow it when it is pointed elsewhere.
+pub fn removed_beside(ledger: &Path) -> PathBuf {
+    ledger.with_file_name("removed.json")
+}
+
+/// The directory Tauri uses as the app data dir on this platform, for when
+/// there is no Tauri app to ask.
+pub fn default_data_dir(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env("HOME")?).join("Library/Application Support")
+    } else {
+        match env("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join(IDENTIFIER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_default_to_the_data_dir() {
+        let paths = DataPaths::from_env("/data".into(), |_| None);
+        assert_eq!(
+            PathBuf::from("/data/transmission_config.json"),
+            paths.config
+        );
+        assert_eq!(PathBuf::from("/data/downloads.json"), paths.ledger);
+        assert_eq!(PathBuf::from("/data/copy_metrics.json"), paths.metrics());
+    }
+
+    #[test]
+    fn env_overrides_the_config_and_ledger() {
+        let paths = DataPaths::from_env("/data".into(), |key| match key {
+            CONFIG_VAR => Some("/sync/config.json".to_string()),
+            LEDGER_VAR => Some(String::new()),
+            _ => None,
+        });
+        assert_eq!(PathBuf::from("/sync/config.json"), paths.config);
+        assert_eq!(PathBuf::from("/data/downloads.json"), paths.ledger);
+
+        let synced = DataPaths::from_env("/data".into(), |key| {
+            (key == LEDGER_VAR).then(|| "/sync/downloads.json".to_string())
+        });
+        assert_eq!(PathBuf::from("/sync/removed.json"), synced.removed());
+    }
+}
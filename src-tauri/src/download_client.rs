@@ -0,0 +1,227 @@
+//! The BitTorrent clients Privateer can drive, behind one interface.
+//!
+//! Commands and the copy task talk to a [`Client`] for the active server
+//! profile, whichever client it names.  Each client maps its torrents onto
+//! [`ClientTorrent`], with statuses as [`TransmissionStatus`], and its
+//! failures onto [`ClientError`].
+
+use std::time::Duration;
+
+use privateer_wire_types::{
+    ClientKind, DaemonInfo, InfoHash, TransmissionConfig, TransmissionStatus,
+};
+
+use crate::copier::TorrentFile;
+use crate::error::ClientError;
+use crate::http::ReqwestClient;
+use crate::qbittorrent::QBittorrentClient;
+use crate::transmission::TransmissionClient;
+
+/// A torrent as a download client reports it, in Transmission's terms.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientTorrent {
+    /// Transmission's id for the torrent, -1 for clients without one.
+    pub id: i64,
+    /// `None` if the client reported an info hash that isn't one.
+    pub hash: Option<InfoHash>,
+    pub name: String,
+    pub status: TransmissionStatus,
+    /// 0.0 to 1.0
+    pub percent_done: f64,
+    /// 0.0 to 1.0, while verifying.
+    pub recheck_progress: f64,
+    /// Bytes per second.
+    pub rate_download: i64,
+    /// Bytes per second.
+    pub rate_upload: i64,
+    /// Seconds until done, -1 if unknown.
+    pub eta: i64,
+    /// -1 if not available and -2 if infinite.
+    pub upload_ratio: f64,
+    pub uploaded_ever: i64,
+    pub seconds_seeding: i64,
+    /// Seconds since the epoch.
+    pub added_date: i64,
+    /// Seconds since the epoch, 0 until the download completes.
+    pub done_date: i64,
+    /// Whether the torrent finished seeding and was stopped.
+    pub is_finished: bool,
+    /// Bytes of the files selected for download.
+    pub size_when_done: i64,
+    pub peers_connected: i64,
+    pub peers_sending_to_us: i64,
+    pub peers_getting_from_us: i64,
+    /// Transmission's error code: 0 for none, 3 for a local error.
+    pub error: i64,
+    pub error_string: String,
+    /// Where the client keeps the torrent's data, as the client sees it.
+    pub download_dir: Option<String>,
+}
+
+/// What Privateer asks of a download client.  Torrents are named by info
+/// hash, which every client understands.
+pub trait DownloadClient {
+    /// The client's version, e.g. "4.0.5 (a6fe2a64aa)", and what it can do.
+    async fn daemon_info(&mut self) -> Result<DaemonInfo, ClientError>;
+
+    async fn torrents(&mut self) -> Result<Vec<ClientTorrent>, ClientError>;
+
+    /// The files of the torrent with the given info hash.
+    async fn files(&mut self, hash: &InfoHash) -> Result<Vec<TorrentFile>, ClientError>;
+
+    /// Add a torrent by magnet link or `.torrent` URL, returning its info
+    /// hash.  Adding a torrent the client already has isn't an error.
+    async fn add(&mut self, url: String) -> Result<InfoHash, ClientError>;
+
+    /// Remove torrents, and their downloaded data if `delete_data` is set.
+    async fn remove(&mut self, hashes: &[InfoHash], delete_data: bool) -> Result<(), ClientError>;
+
+    async fn pause(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError>;
+
+    async fn resume(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError>;
+
+    /// Check the downloaded data against the torrents' piece hashes.
+    async fn verify(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError>;
+
+    /// Ask the trackers for more peers now.
+    async fn reannounce(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError>;
+
+    /// Point torrents at `dir`, the client's path for where their data now
+    /// is.  Only call this once the data is gone from the old location:
+    /// Transmission takes it as already in `dir`, but qBittorrent moves
+    /// whatever it still has.  Verify the torrents afterwards.
+    async fn set_location(&mut self, hashes: &[InfoHash], dir: &str) -> Result<(), ClientError>;
+
+    /// Rename the file or folder at `old_path`, relative to the download
+    /// dir, to `new_name`.  Renaming the top level renames the torrent.
+    async fn rename_path(
+        &mut self,
+        hash: &InfoHash,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<(), ClientError>;
+
+    /// Bytes free in the client's default download directory.
+    async fn free_space(&mut self) -> Result<u64, ClientError>;
+}
+
+/// The client of the active server profile.
+pub enum Client {
+    Transmission(TransmissionClient),
+    QBittorrent(QBittorrentClient<ReqwestClient>),
+}
+
+impl Client {
+    /// Connect to the active profile's client, giving up on each call after
+    /// `timeout`.
+    pub fn new(config: &TransmissionConfig, timeout: Duration) -> Result<Self, ClientError> {
+        let profile = config.active();
+        Ok(match profile.client {
+            ClientKind::Transmission => {
+                Self::Transmission(TransmissionClient::new(config, timeout)?)
+            }
+            ClientKind::QBittorrent => Self::QBittorrent(QBittorrentClient::new(
+                &profile,
+                ReqwestClient::with_timeout(timeout),
+            )?),
+        })
+    }
+
+    pub fn kind(&self) -> ClientKind {
+        match self {
+            Self::Transmission(_) => ClientKind::Transmission,
+            Self::QBittorrent(_) => ClientKind::QBittorrent,
+        }
+    }
+}
+
+impl DownloadClient for Client {
+    async fn daemon_info(&mut self) -> Result<DaemonInfo, ClientError> {
+        match self {
+            Self::Transmission(client) => client.daemon_info().await,
+            Self::QBittorrent(client) => client.daemon_info().await,
+        }
+    }
+
+    async fn torrents(&mut self) -> Result<Vec<ClientTorrent>, ClientError> {
+        match self {
+            Self::Transmission(client) => client.torrents().await,
+            Self::QBittorrent(client) => client.torrents().await,
+        }
+    }
+
+    async fn files(&mut self, hash: &InfoHash) -> Result<Vec<TorrentFile>, ClientError> {
+        match self {
+            Self::Transmission(client) => client.files(hash).await,
+            Self::QBittorrent(client) => client.files(hash).await,
+        }
+    }
+
+    async fn add(&mut self, url: String) -> Result<InfoHash, ClientError> {
+        match self {
+            Self::Transmission(client) => client.add(url).await,
+            Self::QBittorrent(client) => client.add(url).await,
+        }
+    }
+
+    async fn remove(&mut self, hashes: &[InfoHash], delete_data: bool) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.remove(hashes, delete_data).await,
+            Self::QBittorrent(client) => client.remove(hashes, delete_data).await,
+        }
+    }
+
+    async fn pause(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.pause(hashes).await,
+            Self::QBittorrent(client) => client.pause(hashes).await,
+        }
+    }
+
+    async fn resume(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.resume(hashes).await,
+            Self::QBittorrent(client) => client.resume(hashes).await,
+        }
+    }
+
+    async fn verify(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.verify(hashes).await,
+            Self::QBittorrent(client) => client.verify(hashes).await,
+        }
+    }
+
+    async fn reannounce(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.reannounce(hashes).await,
+            Self::QBittorrent(client) => client.reannounce(hashes).await,
+        }
+    }
+
+    async fn set_location(&mut self, hashes: &[InfoHash], dir: &str) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.set_location(hashes, dir).await,
+            Self::QBittorrent(client) => client.set_location(hashes, dir).await,
+        }
+    }
+
+    async fn rename_path(
+        &mut self,
+        hash: &InfoHash,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<(), ClientError> {
+        match self {
+            Self::Transmission(client) => client.rename_path(hash, old_path, new_name).await,
+            Self::QBittorrent(client) => client.rename_path(hash, old_path, new_name).await,
+        }
+    }
+
+    async fn free_space(&mut self) -> Result<u64, ClientError> {
+        match self {
+            Self::Transmission(client) => client.free_space().await,
+            Self::QBittorrent(client) => client.free_space().await,
+        }
+    }
+}
@@ -1,15 +1,51 @@
 //! Domain-specific error types using `snafu`.
 //!
-//! Each domain (Privateer search, Transmission RPC, config I/O) has its own
-//! error enum. All variants carry context and the original source error.
+//! Each domain (Privateer search, Transmission RPC, qBittorrent Web API, config
+//! I/O, logging, media server, webhook, feeds, export) has its own error enum. All variants carry context
+//! and the original source error.
 //! Every domain enum converts into [`privateer_wire_types::AppError`] with the
-//! appropriate [`privateer_wire_types::ErrorKind`] so the frontend can branch on it.
+//! appropriate [`privateer_wire_types::ErrorKind`] so the frontend can branch on it,
+//! and says whether running the command again might help.
 
 use std::path::PathBuf;
 
-use privateer_wire_types::{AppError, ErrorKind};
+use privateer_wire_types::{AppError, ErrorKind, InfoHash, SettingsProblem};
 use snafu::Snafu;
 
+/// Whether a failure message reads like a timeout rather than a refusal.
+fn is_timeout(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("timed out") || lower.contains("timeout")
+}
+
+/// Whether an I/O failure was the disk filling up.
+fn is_disk_full(source: &std::io::Error) -> bool {
+    source.kind() == std::io::ErrorKind::StorageFull
+}
+
+/// The kind for another service answering with HTTP `status`, falling back to
+/// `kind`.  Server errors are worth retrying.
+fn status_error(kind: ErrorKind, status: u16, message: String) -> AppError {
+    match status {
+        401 | 403 => AppError::new(ErrorKind::Auth, message),
+        429 => AppError::new(ErrorKind::RateLimited, message),
+        _ => AppError::new(kind, message).with_retryable(status >= 500),
+    }
+}
+
+/// The kind for a request that got no response, falling back to `kind`.
+/// Either way it is worth retrying.
+fn request_error(kind: ErrorKind, source: &HttpError, message: String) -> AppError {
+    let HttpError::Transport {
+        message: reason, ..
+    } = source;
+    if is_timeout(reason) {
+        AppError::new(ErrorKind::Timeout, message)
+    } else {
+        AppError::new(kind, message).with_retryable(true)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Privateer search / info
 // ---------------------------------------------------------------------------
@@ -26,11 +62,22 @@ pub enum PirateError {
 
     #[snafu(display("Failed to get torrent info: {message}"))]
     Info { message: String },
+
+    #[snafu(display("Torrent {id} doesn't exist, it may have been taken down"))]
+    InfoNotFound { id: String },
+
+    #[snafu(display("Too many search requests at once, slow down and try again in a moment"))]
+    SlowDown,
 }
 
 impl From<PirateError> for AppError {
     fn from(e: PirateError) -> Self {
-        AppError::new(ErrorKind::PirateSearch, e.to_string())
+        let kind = match &e {
+            PirateError::SlowDown => ErrorKind::RateLimited,
+            PirateError::InfoNotFound { .. } => ErrorKind::ListingNotFound,
+            PirateError::Search { .. } | PirateError::Info { .. } => ErrorKind::PirateSearch,
+        };
+        AppError::new(kind, e.to_string())
     }
 }
 
@@ -51,21 +98,162 @@ pub enum TransmissionError {
     #[snafu(display("Failed to connect to Transmission: {message}"))]
     Connection { message: String },
 
+    #[snafu(display("Transmission rejected the credentials: {message}"))]
+    Auth { message: String },
+
     #[snafu(display("Transmission RPC error: {message}"))]
     Rpc { message: String },
+
+    #[snafu(display(
+        "Can't rename to '{name}': Transmission renames one file or folder at a time, \
+         so the new name can't be empty or contain '/'"
+    ))]
+    InvalidName { name: String },
+
+    #[snafu(display("{reason}; this is Transmission {version}"))]
+    TooOld { reason: String, version: String },
 }
 
 impl From<TransmissionError> for AppError {
     fn from(e: TransmissionError) -> Self {
         let kind = match &e {
             TransmissionError::InvalidUrl { .. } => ErrorKind::InvalidUrl,
+            TransmissionError::Connection { message } if is_timeout(message) => ErrorKind::Timeout,
             TransmissionError::Connection { .. } => ErrorKind::TransmissionConnection,
-            TransmissionError::Rpc { .. } => ErrorKind::TransmissionRpc,
+            TransmissionError::Auth { .. } => ErrorKind::TransmissionAuth,
+            TransmissionError::Rpc { .. }
+            | TransmissionError::InvalidName { .. }
+            | TransmissionError::TooOld { .. } => ErrorKind::TransmissionRpc,
         };
         AppError::new(kind, e.to_string())
     }
 }
 
+impl TransmissionError {
+    /// Classify a transport-level failure from the transmission-rpc client.
+    ///
+    /// The client doesn't expose the HTTP status, so 401/403 responses are
+    /// recognised from the error text (either the status line or
+    /// Transmission's "401: Unauthorized" page).  Everything else is treated
+    /// as a connection failure.
+    pub fn from_transport(message: String) -> Self {
+        let lower = message.to_lowercase();
+        let is_auth = lower
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| matches!(word, "401" | "403" | "unauthorized" | "forbidden"));
+        if is_auth {
+            TransmissionError::Auth { message }
+        } else {
+            TransmissionError::Connection { message }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// qBittorrent Web API
+// ---------------------------------------------------------------------------
+
+/// Errors from interacting with qBittorrent's Web API.  They map onto the
+/// same kinds as [`TransmissionError`], so the frontend treats both clients
+/// alike.
+///
+/// Variant names are prefixed with `QBittorrent` to avoid snafu
+/// context-selector collisions with [`TransmissionError`].
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum QBittorrentError {
+    #[snafu(display("Invalid qBittorrent URL '{url}': {source}"))]
+    QBittorrentUrl {
+        url: String,
+        source: url::ParseError,
+    },
+
+    #[snafu(display("Failed to connect to qBittorrent: {source}"))]
+    QBittorrentConnection { source: HttpError },
+
+    #[snafu(display("qBittorrent rejected the credentials: {message}"))]
+    QBittorrentAuth { message: String },
+
+    #[snafu(display("qBittorrent responded to {endpoint} with HTTP {status}: {message}"))]
+    QBittorrentStatus {
+        endpoint: String,
+        status: u16,
+        message: String,
+    },
+
+    #[snafu(display("Unreadable response from qBittorrent's {endpoint}: {source}"))]
+    QBittorrentResponse {
+        endpoint: String,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("qBittorrent didn't report the torrent added from '{url}'"))]
+    QBittorrentAdded { url: String },
+
+    #[snafu(display("{what} is only available with Transmission"))]
+    TransmissionOnly { what: String },
+}
+
+impl From<QBittorrentError> for AppError {
+    fn from(e: QBittorrentError) -> Self {
+        let message = e.to_string();
+        match e {
+            QBittorrentError::QBittorrentUrl { .. } => {
+                AppError::new(ErrorKind::InvalidUrl, message)
+            }
+            QBittorrentError::QBittorrentConnection { source } => {
+                request_error(ErrorKind::TransmissionConnection, &source, message)
+            }
+            QBittorrentError::QBittorrentAuth { .. } => {
+                AppError::new(ErrorKind::TransmissionAuth, message)
+            }
+            QBittorrentError::QBittorrentStatus { status, .. } => {
+                status_error(ErrorKind::TransmissionRpc, status, message)
+            }
+            _ => AppError::new(ErrorKind::TransmissionRpc, message),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Download clients
+// ---------------------------------------------------------------------------
+
+/// Errors from the download client of the active profile, whichever it is.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum ClientError {
+    #[snafu(context(false), display("{source}"))]
+    Transmission { source: TransmissionError },
+
+    #[snafu(context(false), display("{source}"))]
+    QBittorrent { source: QBittorrentError },
+}
+
+impl ClientError {
+    /// Whether the client couldn't be reached, e.g. because it timed out,
+    /// so the next call should reconnect.
+    pub fn is_connection(&self) -> bool {
+        matches!(
+            self,
+            Self::Transmission {
+                source: TransmissionError::Connection { .. }
+            } | Self::QBittorrent {
+                source: QBittorrentError::QBittorrentConnection { .. }
+            }
+        )
+    }
+}
+
+impl From<ClientError> for AppError {
+    fn from(e: ClientError) -> Self {
+        match e {
+            ClientError::Transmission { source } => source.into(),
+            ClientError::QBittorrent { source } => source.into(),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Config I/O
 // ---------------------------------------------------------------------------
@@ -86,13 +274,93 @@ pub enum ConfigError {
         source: std::io::Error,
     },
 
+    #[snafu(display("Failed to replace '{}': {source}", path.display()))]
+    ReplaceFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to save the downloads ledger to '{}': {source}", path.display()))]
+    LedgerWrite {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to read the downloads ledger '{}': {source}", path.display()))]
+    LedgerRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display(
+        "The downloads ledger '{}' is damaged and was left as it is: {source}",
+        path.display()
+    ))]
+    LedgerParse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
     #[snafu(display("Failed to serialize config: {source}"))]
     Serialize { source: serde_json::Error },
+
+    #[snafu(display("{source}"))]
+    InvalidSetting { source: SettingsProblem },
+
+    #[snafu(display("Path mapping for '{remote}' needs both a remote and a local prefix"))]
+    PathMappingIncomplete { remote: String },
+
+    #[snafu(display("Local path '{}' for mapping '{remote}' does not exist", local.display()))]
+    PathMappingMissing { remote: String, local: PathBuf },
+
+    #[snafu(display("Copying with rsync needs rsync 3.1 or newer: {message}"))]
+    RsyncUnavailable { message: String },
+
+    #[snafu(display("Invalid feed URL '{url}': {source}"))]
+    FeedUrl {
+        url: String,
+        source: url::ParseError,
+    },
+
+    #[snafu(display("Subscription to '{url}' needs a pattern to match titles against"))]
+    FeedPatternMissing { url: String },
+
+    #[snafu(display("Download {info_hash} needs a name"))]
+    DownloadNameMissing { info_hash: InfoHash },
+
+    #[snafu(display("'{name}' was given more than once"))]
+    DownloadRepeated { name: String },
+
+    #[snafu(display("Download {info_hash} has no destination, so it can't have a note"))]
+    DownloadNotTracked { info_hash: InfoHash },
+
+    #[snafu(display("Download {info_hash} isn't in the ledger"))]
+    DownloadNotInLedger { info_hash: InfoHash },
+
+    #[snafu(display("Download {info_hash} wasn't removed in the last {days} days"))]
+    DownloadNotRemoved { info_hash: InfoHash, days: i64 },
+
+    #[snafu(display("Invalid pattern '{pattern}': {source}"))]
+    FeedPattern {
+        pattern: String,
+        source: regex::Error,
+    },
+
+    #[snafu(display("{what} needs confirming first"))]
+    NotConfirmed { what: String },
 }
 
 impl From<ConfigError> for AppError {
     fn from(e: ConfigError) -> Self {
-        AppError::new(ErrorKind::Config, e.to_string())
+        match &e {
+            ConfigError::LedgerWrite { source, .. } if is_disk_full(source) => {
+                AppError::new(ErrorKind::DiskSpace, e.to_string())
+            }
+            ConfigError::LedgerWrite { .. }
+            | ConfigError::LedgerRead { .. }
+            | ConfigError::LedgerParse { .. } => AppError::new(ErrorKind::Ledger, e.to_string()),
+            _ => AppError::new(ErrorKind::Config, e.to_string()),
+        }
     }
 }
 
@@ -133,10 +401,520 @@ pub enum CopyError {
         path: PathBuf,
         source: std::io::Error,
     },
+
+    #[snafu(display("Destination not mounted: '{}'", path.display()))]
+    CopyNotMounted { path: PathBuf },
+
+    #[snafu(display("Destination not responding: '{}'", path.display()))]
+    CopyDestUnresponsive { path: PathBuf },
+
+    #[snafu(display("Can't read downloads from '{host}': {message}"))]
+    CopySourceUnavailable { host: String, message: String },
+
+    #[snafu(display("Failed to start rsync: {source}"))]
+    CopyRsyncSpawn { source: std::io::Error },
+
+    #[snafu(display("rsync failed ({status}): {stderr}"))]
+    CopyRsync { status: String, stderr: String },
+
+    #[snafu(display("Failed to move '{}' into place: {source}", path.display()))]
+    CopyRename {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to rename '{}' to '{}': {source}", from.display(), to.display()))]
+    CopyRenameFile {
+        from: PathBuf,
+        to: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Copy stalled after {minutes} minutes without progress"))]
+    CopyStalled { minutes: u32 },
+
+    #[snafu(display("The background copy task isn't running"))]
+    CopyTaskStopped,
 }
 
 impl From<CopyError> for AppError {
     fn from(e: CopyError) -> Self {
-        AppError::new(ErrorKind::Copy, e.to_string())
+        match &e {
+            CopyError::CopyCreateDir { source, .. }
+            | CopyError::CopyFile { source, .. }
+            | CopyError::CopyRename { source, .. }
+            | CopyError::CopyRenameFile { source, .. }
+                if is_disk_full(source) =>
+            {
+                AppError::new(ErrorKind::DiskSpace, e.to_string())
+            }
+            CopyError::CopyNotMounted { .. } | CopyError::CopyDestUnresponsive { .. } => {
+                AppError::new(ErrorKind::DestinationUnavailable, e.to_string())
+            }
+            CopyError::CopySourceUnavailable { message, .. } if is_timeout(message) => {
+                AppError::new(ErrorKind::Timeout, e.to_string())
+            }
+            CopyError::CopySourceUnavailable { .. } => {
+                AppError::new(ErrorKind::Copy, e.to_string()).with_retryable(true)
+            }
+            CopyError::CopyStalled { .. } => AppError::new(ErrorKind::Timeout, e.to_string()),
+            _ => AppError::new(ErrorKind::Copy, e.to_string()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Logging
+// ---------------------------------------------------------------------------
+
+/// Errors from reading the backend log file.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum LogError {
+    #[snafu(display("Failed to read log file '{}': {source}", path.display()))]
+    ReadLog {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl From<LogError> for AppError {
+    fn from(e: LogError) -> Self {
+        AppError::new(ErrorKind::Log, e.to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTTP
+// ---------------------------------------------------------------------------
+
+/// A request to another service couldn't be sent or got no response.
+///
+/// `reqwest::Error` is stringified so test doubles can produce it.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum HttpError {
+    #[snafu(display("Request to {url} failed: {message}"))]
+    Transport { url: String, message: String },
+}
+
+// ---------------------------------------------------------------------------
+// Media server
+// ---------------------------------------------------------------------------
+
+/// Errors from asking Plex or Jellyfin for a library scan.
+///
+/// Variant names are prefixed with `MediaServer` to avoid snafu
+/// context-selector collisions with [`TransmissionError`].
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum MediaServerError {
+    #[snafu(display("No media server is configured"))]
+    MediaServerNotConfigured,
+
+    #[snafu(display("Invalid media server URL '{url}': {source}"))]
+    MediaServerUrl {
+        url: String,
+        source: url::ParseError,
+    },
+
+    #[snafu(display("No Plex library section configured for {destination}"))]
+    MediaServerNoSection {
+        destination: privateer_wire_types::Destination,
+    },
+
+    #[snafu(display("Could not reach {kind}: {source}"))]
+    MediaServerRequest {
+        kind: privateer_wire_types::MediaServerKind,
+        source: HttpError,
+    },
+
+    #[snafu(display("{kind} responded with HTTP {status}"))]
+    MediaServerStatus {
+        kind: privateer_wire_types::MediaServerKind,
+        status: u16,
+    },
+}
+
+impl From<MediaServerError> for AppError {
+    fn from(e: MediaServerError) -> Self {
+        let message = e.to_string();
+        match e {
+            MediaServerError::MediaServerRequest { source, .. } => {
+                request_error(ErrorKind::MediaServer, &source, message)
+            }
+            MediaServerError::MediaServerStatus { status, .. } => {
+                status_error(ErrorKind::MediaServer, status, message)
+            }
+            _ => AppError::new(ErrorKind::MediaServer, message),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Webhook
+// ---------------------------------------------------------------------------
+
+/// Errors from posting to the webhook.
+///
+/// Variant names are prefixed with `Webhook` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum WebhookError {
+    #[snafu(display("No webhook is configured"))]
+    WebhookNotConfigured,
+
+    #[snafu(display("Invalid webhook URL '{url}': {source}"))]
+    WebhookUrl {
+        url: String,
+        source: url::ParseError,
+    },
+
+    #[snafu(display("Failed to serialize webhook payload: {source}"))]
+    WebhookSerialize { source: serde_json::Error },
+
+    #[snafu(display("Could not reach the webhook: {source}"))]
+    WebhookRequest { source: HttpError },
+
+    #[snafu(display("Webhook responded with HTTP {status}"))]
+    WebhookStatus { status: u16 },
+}
+
+impl From<WebhookError> for AppError {
+    fn from(e: WebhookError) -> Self {
+        let message = e.to_string();
+        match e {
+            WebhookError::WebhookRequest { source } => {
+                request_error(ErrorKind::Webhook, &source, message)
+            }
+            WebhookError::WebhookStatus { status } => {
+                status_error(ErrorKind::Webhook, status, message)
+            }
+            _ => AppError::new(ErrorKind::Webhook, message),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Post-copy hook
+// ---------------------------------------------------------------------------
+
+/// Errors from running the post-copy command.
+///
+/// Variant names are prefixed with `Hook` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum HookError {
+    #[snafu(display("No post-copy command is configured"))]
+    HookNotConfigured,
+
+    #[snafu(display("Failed to start the post-copy command: {source}"))]
+    HookSpawn { source: std::io::Error },
+
+    #[snafu(display("The post-copy command didn't finish within {secs}s"))]
+    HookTimeout { secs: u64 },
+
+    #[snafu(display("The post-copy command failed ({status}): {stderr}"))]
+    HookStatus { status: String, stderr: String },
+}
+
+impl From<HookError> for AppError {
+    fn from(e: HookError) -> Self {
+        match e {
+            HookError::HookTimeout { .. } => AppError::new(ErrorKind::Timeout, e.to_string()),
+            _ => AppError::new(ErrorKind::Hook, e.to_string()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Feeds
+// ---------------------------------------------------------------------------
+
+/// Errors from fetching a subscribed RSS feed.
+///
+/// Variant names are prefixed with `Feed` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum FeedError {
+    #[snafu(display("Could not fetch the feed: {source}"))]
+    FeedRequest { source: HttpError },
+
+    #[snafu(display("Feed {url} responded with HTTP {status}"))]
+    FeedStatus { url: String, status: u16 },
+
+    #[snafu(display("Could not read the feed at {url}: {source}"))]
+    FeedParse { url: String, source: rss::Error },
+}
+
+impl From<FeedError> for AppError {
+    fn from(e: FeedError) -> Self {
+        let message = e.to_string();
+        match e {
+            FeedError::FeedRequest { source } => request_error(ErrorKind::Feed, &source, message),
+            FeedError::FeedStatus { status, .. } => status_error(ErrorKind::Feed, status, message),
+            FeedError::FeedParse { .. } => AppError::new(ErrorKind::Feed, message),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// Errors from exporting the downloads list.
+///
+/// Variant names are prefixed with `Export` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum ExportError {
+    #[snafu(display("Could not write the CSV export: {source}"))]
+    ExportCsv { source: csv::Error },
+
+    #[snafu(display("Could not write the JSON export: {source}"))]
+    ExportJson { source: serde_json::Error },
+
+    #[snafu(display("Could not save the export to {}: {source}", path.display()))]
+    ExportSave {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl From<ExportError> for AppError {
+    fn from(e: ExportError) -> Self {
+        match &e {
+            ExportError::ExportSave { source, .. } if is_disk_full(source) => {
+                AppError::new(ErrorKind::DiskSpace, e.to_string())
+            }
+            _ => AppError::new(ErrorKind::Export, e.to_string()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Updates
+// ---------------------------------------------------------------------------
+
+/// Errors from looking for a newer release.
+///
+/// Variant names are prefixed with `Update` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum UpdateError {
+    #[snafu(display("Could not check for updates: {source}"))]
+    UpdateRequest { source: HttpError },
+
+    #[snafu(display("Checking for updates failed with HTTP {status}"))]
+    UpdateStatus { status: u16 },
+
+    #[snafu(display("Could not read the latest release: {source}"))]
+    UpdateParse { source: serde_json::Error },
+}
+
+impl From<UpdateError> for AppError {
+    fn from(e: UpdateError) -> Self {
+        let message = e.to_string();
+        match e {
+            UpdateError::UpdateRequest { source } => {
+                request_error(ErrorKind::Update, &source, message)
+            }
+            // GitHub answers 403 once the anonymous rate limit is used up
+            UpdateError::UpdateStatus {
+                status: 403 | 429, ..
+            } => AppError::new(ErrorKind::RateLimited, message),
+            UpdateError::UpdateStatus { status } => {
+                status_error(ErrorKind::Update, status, message)
+            }
+            UpdateError::UpdateParse { .. } => AppError::new(ErrorKind::Update, message),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Headless
+// ---------------------------------------------------------------------------
+
+/// Errors from starting headless.  These are only ever printed on the
+/// command line, so there is no conversion to [`AppError`].
+///
+/// Variant names are prefixed with `Headless` to avoid snafu context-selector
+/// collisions with the other domains.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum HeadlessError {
+    #[snafu(display("Unknown argument {argument}"))]
+    HeadlessUnknownArgument { argument: String },
+
+    #[snafu(display("{flag} needs a value"))]
+    HeadlessMissingValue { flag: String },
+
+    #[snafu(display("Could not start the async runtime: {source}"))]
+    HeadlessRuntime { source: std::io::Error },
+
+    #[snafu(display("Could not listen for SIGTERM: {source}"))]
+    HeadlessSignal { source: std::io::Error },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_auth(message: &str) -> bool {
+        matches!(
+            TransmissionError::from_transport(message.to_string()),
+            TransmissionError::Auth { .. }
+        )
+    }
+
+    #[test]
+    fn transport_errors_with_auth_status_are_auth() {
+        assert!(is_auth(
+            "HTTP status client error (401 Unauthorized) for url \
+             (http://localhost:9091/transmission/rpc)"
+        ));
+        assert!(is_auth("<h1>401: Unauthorized</h1>Unauthorized User"));
+        assert!(is_auth(
+            "<h1>403: Forbidden</h1><p>Unauthorized IP Address.</p>"
+        ));
+        assert!(is_auth("HTTP status client error (403 Forbidden)"));
+    }
+
+    #[test]
+    fn other_transport_errors_are_connection() {
+        assert!(!is_auth(
+            "error sending request for url (http://localhost:9091/transmission/rpc): \
+             error trying to connect: tcp connect error: Connection refused (os error 61)"
+        ));
+        // A port containing "401" is not an auth failure
+        assert!(!is_auth(
+            "error sending request for url (http://nas.local:4010/transmission/rpc): \
+             operation timed out"
+        ));
+        assert!(!is_auth("dns error: failed to lookup address information"));
+    }
+
+    #[test]
+    fn auth_errors_map_to_auth_kind() {
+        let error = AppError::from(TransmissionError::from_transport(
+            "401 Unauthorized".to_string(),
+        ));
+        assert_eq!(error.kind, ErrorKind::TransmissionAuth);
+        assert!(!error.retryable);
+    }
+
+    #[test]
+    fn qbittorrent_errors_map_like_transmission_ones() {
+        let refused = ClientError::from(QBittorrentError::QBittorrentConnection {
+            source: HttpError::Transport {
+                url: "http://nas.local:8080/api/v2/torrents/info".to_string(),
+                message: "Connection refused (os error 61)".to_string(),
+            },
+        });
+        assert!(refused.is_connection());
+        let error = AppError::from(refused);
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::TransmissionConnection, true)
+        );
+
+        let error = AppError::from(QBittorrentError::QBittorrentAuth {
+            message: "wrong username or password".to_string(),
+        });
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::TransmissionAuth, false)
+        );
+
+        let error = AppError::from(QBittorrentError::QBittorrentStatus {
+            endpoint: "torrents/add".to_string(),
+            status: 415,
+            message: "Torrent file is not valid".to_string(),
+        });
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::TransmissionRpc, false)
+        );
+
+        let rpc = ClientError::from(TransmissionError::Rpc {
+            message: "invalid or corrupt torrent file".to_string(),
+        });
+        assert!(!rpc.is_connection());
+    }
+
+    #[test]
+    fn kinds_say_whether_to_retry() {
+        let error = AppError::from(TransmissionError::Connection {
+            message: "timed out after 10s".to_string(),
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Timeout, true));
+
+        let error = AppError::from(TransmissionError::from_transport(
+            "Connection refused (os error 61)".to_string(),
+        ));
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::TransmissionConnection, true)
+        );
+
+        let error = AppError::from(CopyError::CopyFile {
+            src: "/a".into(),
+            dst: "/b".into(),
+            source: std::io::ErrorKind::StorageFull.into(),
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::DiskSpace, false));
+
+        let error = AppError::from(CopyError::CopyNotMounted { path: "/b".into() });
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::DestinationUnavailable, true)
+        );
+
+        let error = AppError::from(CopyError::CopyStalled { minutes: 10 });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Timeout, true));
+        assert_eq!(
+            error.message,
+            "Copy stalled after 10 minutes without progress"
+        );
+
+        let error = AppError::from(ConfigError::LedgerWrite {
+            path: "/ledger.json".into(),
+            source: std::io::ErrorKind::PermissionDenied.into(),
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Ledger, true));
+
+        let error = AppError::from(WebhookError::WebhookStatus { status: 401 });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Auth, false));
+
+        let error = AppError::from(FeedError::FeedStatus {
+            url: "https://example.com/rss".to_string(),
+            status: 503,
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Feed, true));
+
+        let error = AppError::from(UpdateError::UpdateStatus { status: 403 });
+        assert_eq!(
+            (error.kind, error.retryable),
+            (ErrorKind::RateLimited, true)
+        );
+
+        let error = AppError::from(MediaServerError::MediaServerRequest {
+            kind: privateer_wire_types::MediaServerKind::Plex,
+            source: HttpError::Transport {
+                url: "http://plex.local".to_string(),
+                message: "operation timed out".to_string(),
+            },
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Timeout, true));
+
+        let error = AppError::from(ConfigError::RsyncUnavailable {
+            message: "not found".to_string(),
+        });
+        assert_eq!((error.kind, error.retryable), (ErrorKind::Config, false));
     }
 }
@@ -0,0 +1,220 @@
+//! Exporting the downloads list as CSV or JSON, one row per torrent.
+//!
+//! Rows come from the ledger joined with whatever Transmission currently
+//! reports, so torrents already removed from Transmission are still listed
+//! (without a size or status), and torrents Transmission has that were never
+//! added through Privateer are listed after them.
+
+use privateer_wire_types::{
+    format_size, DownloadEntry, ExportFormat, InfoHash, SizeUnits, TransmissionTorrent,
+};
+use snafu::ResultExt;
+
+use crate::error::{ExportCsvSnafu, ExportError, ExportJsonSnafu};
+
+/// One line of the export.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExportRow {
+    pub name: String,
+    pub info_hash: String,
+    /// Total size in bytes, if Transmission still has the torrent.
+    pub size: Option<i64>,
+    /// The size as the app shows it, e.g. "4.7 GB".
+    pub size_text: Option<String>,
+    pub status: Option<String>,
+    pub destination: Option<String>,
+    pub copy_state: Option<String>,
+    /// Unix timestamp when the copy finished.
+    pub copied_at: Option<i64>,
+    /// The user's note on the download.
+    pub note: Option<String>,
+}
+
+/// Join the ledger with the torrents Transmission reports, with sizes shown
+/// in `units`.
+pub fn rows(
+    ledger: &[DownloadEntry],
+    torrents: &[TransmissionTorrent],
+    units: SizeUnits,
+) -> Vec<ExportRow> {
+    let size_text = |t: &TransmissionTorrent| format_size(t.size_when_done.max(0) as u64, units);
+    let find_torrent = |hash: &InfoHash| torrents.iter().find(|t| &t.hash_string == hash);
+    let mut rows: Vec<ExportRow> = ledger
+        .iter()
+        .map(|entry| {
+            let torrent = find_torrent(&entry.info_hash);
+            ExportRow {
+                name: torrent
+                    .map(|t| t.name.clone())
+                    .unwrap_or_else(|| entry.name.clone()),
+                info_hash: entry.info_hash.to_string(),
+                size: torrent.map(|t| t.size_when_done),
+                size_text: torrent.map(size_text),
+                status: torrent.map(|t| t.status.label()),
+                destination: Some(entry.destination.label().to_string()),
+                copy_state: Some(entry.overall_copy_state().label().to_string()),
+                copied_at: entry.copied_at,
+                note: entry.user_note.clone(),
+            }
+        })
+        .collect();
+    rows.extend(
+        torrents
+            .iter()
+            .filter(|t| !ledger.iter().any(|e| e.info_hash == t.hash_string))
+            .map(|t| ExportRow {
+                name: t.name.clone(),
+                info_hash: t.hash_string.to_string(),
+                size: Some(t.size_when_done),
+                size_text: Some(size_text(t)),
+                status: Some(t.status.label()),
+                destination: t.destination.map(|d| d.label().to_string()),
+                copy_state: t.destination.map(|_| t.copy_state.label().to_string()),
+                copied_at: None,
+                note: None,
+            }),
+    );
+    rows
+}
+
+/// Serialize the rows in the given format.
+pub fn to_document(rows: &[ExportRow], format: ExportFormat) -> Result<String, ExportError> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for row in rows {
+                writer.serialize(row).context(ExportCsvSnafu)?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| csv::Error::from(e.into_error()))
+                .context(ExportCsvSnafu)?;
+            // The writer only ever writes the UTF-8 of our strings.
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(rows).context(ExportJsonSnafu),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use privateer_wire_types::{CopyState, Destination, TransmissionStatus};
+
+    use super::*;
+
+    fn awkward_rows() -> Vec<ExportRow> {
+        vec![
+            ExportRow {
+                name: "Crouching Tiger, Hidden Dragon (2000)".into(),
+                info_hash: "abc123".into(),
+                size: Some(4_700_000_000),
+                size_text: Some("4.7 GB".into()),
+                status: Some("Seeding".into()),
+                destination: Some("Movies".into()),
+                copy_state: Some("Copied".into()),
+                copied_at: Some(1_760_000_000),
+                note: Some("For mum, and \"dad\"".into()),
+            },
+            ExportRow {
+                name: "The \"Quoted\" Show\nSeason 1".into(),
+                info_hash: "def456".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn torrent(hash: &str, name: &str) -> TransmissionTorrent {
+        TransmissionTorrent {
+            id: 1,
+            name: name.into(),
+            status: TransmissionStatus::Seeding,
+            percent_done: 1.0,
+            size_when_done: 1024,
+            ..TransmissionTorrent::new(hash.parse().unwrap())
+        }
+    }
+
+    #[test]
+    fn csv_round_trips_commas_quotes_and_newlines() {
+        let rows = awkward_rows();
+        let document = to_document(&rows, ExportFormat::Csv).unwrap();
+        assert!(document.starts_with(
+            "name,info_hash,size,size_text,status,destination,copy_state,copied_at,note\n"
+        ));
+        let read: Vec<ExportRow> = csv::Reader::from_reader(document.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, read);
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let rows = awkward_rows();
+        let document = to_document(&rows, ExportFormat::Json).unwrap();
+        let read: Vec<ExportRow> = serde_json::from_str(&document).unwrap();
+        assert_eq!(rows, read);
+    }
+
+    #[test]
+    fn ledger_is_joined_with_transmission() {
+        let ledger = vec![
+            DownloadEntry {
+                copy_state: CopyState::Copied,
+                copied_at: Some(42),
+                user_note: Some("for mum".into()),
+                ..DownloadEntry::new(
+                    "A".repeat(40).parse().unwrap(),
+                    "Ledger Name".into(),
+                    Destination::Movies,
+                )
+            },
+            DownloadEntry {
+                copy_state: CopyState::Failed,
+                ..DownloadEntry::new(
+                    "b".repeat(40).parse().unwrap(),
+                    "Removed From Transmission".into(),
+                    Destination::Shows,
+                )
+            },
+        ];
+        let torrents = vec![
+            torrent(&"a".repeat(40), "Transmission Name"),
+            torrent(&"c".repeat(40), "Added Elsewhere"),
+        ];
+        let rows = rows(&ledger, &torrents, SizeUnits::Binary);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.name.as_str(),
+                    r.size,
+                    r.size_text.as_deref(),
+                    r.copy_state.as_deref(),
+                    r.copied_at,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "Transmission Name",
+                    Some(1024),
+                    Some("1.0 KiB"),
+                    Some("Copied"),
+                    Some(42)
+                ),
+                (
+                    "Removed From Transmission",
+                    None,
+                    None,
+                    Some("Failed"),
+                    None
+                ),
+                ("Added Elsewhere", Some(1024), Some("1.0 KiB"), None, None),
+            ],
+            summary
+        );
+        assert_eq!(Some("for mum"), rows[0].note.as_deref());
+    }
+}
@@ -0,0 +1,398 @@
+//! Adding new torrents from subscribed RSS feeds.
+//!
+//! Each feed is fetched once per poll, however many subscriptions use it.
+//! An item is added for the first subscription whose patterns match its
+//! title.  The GUIDs of items already dealt with are persisted, so an item
+//! is added at most once; one that failed to add stays new and is retried
+//! on the next poll.  The first poll of a feed only records what's already
+//! in it, so subscribing adds new releases rather than the feed's backlog.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use privateer_wire_types::{FeedPreviewItem, FeedSubscription};
+use regex::{Regex, RegexBuilder};
+use snafu::{ensure, ResultExt};
+
+use crate::error::*;
+use crate::http::ReqwestClient;
+
+/// An item in a feed that links to a torrent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedItem {
+    /// The item's GUID, or its link if it has none.
+    pub guid: String,
+    pub title: String,
+    /// A magnet link or `.torrent` URL, as Transmission accepts either.
+    pub link: String,
+}
+
+/// A magnet link in the item's namespaced elements, e.g. torznab's
+/// `<torznab:attr name="magneturl" value="magnet:..."/>` or
+/// `<torrent:magnetURI>`.
+fn extension_magnet(item: &rss::Item) -> Option<String> {
+    item.extensions()
+        .values()
+        .flat_map(|elements| elements.values().flatten())
+        .flat_map(|extension| {
+            extension
+                .value()
+                .into_iter()
+                .chain(extension.attrs().values().map(String::as_str))
+        })
+        .find(|value| value.starts_with("magnet:"))
+        .map(str::to_string)
+}
+
+/// The torrent an item links to, preferring a magnet link.
+fn item_link(item: &rss::Item) -> Option<String> {
+    let enclosure = item.enclosure().map(|enclosure| enclosure.url());
+    let candidates = [enclosure, item.link()];
+    candidates
+        .iter()
+        .flatten()
+        .find(|link| link.starts_with("magnet:"))
+        .map(|link| link.to_string())
+        .or_else(|| extension_magnet(item))
+        .or_else(|| candidates.into_iter().flatten().next().map(str::to_string))
+}
+
+/// The items in an RSS document that have a title and a torrent link.
+pub fn parse(xml: &[u8]) -> Result<Vec<FeedItem>, rss::Error> {
+    let channel = rss::Channel::read_from(xml)?;
+    Ok(channel
+        .items()
+        .iter()
+        .filter_map(|item| {
+            let title = item.title()?.trim().to_string();
+            let link = item_link(item)?;
+            let guid = item
+                .guid()
+                .map(|guid| guid.value().to_string())
+                .unwrap_or_else(|| link.clone());
+            Some(FeedItem { guid, title, link })
+        })
+        .collect())
+}
+
+/// The subscription's feed URL.
+pub fn feed_url(subscription: &FeedSubscription) -> Result<url::Url, ConfigError> {
+    let url = subscription.url.trim();
+    url.parse().context(FeedUrlSnafu { url })
+}
+
+/// Fetch and parse a feed.
+pub async fn fetch(http: &ReqwestClient, url: url::Url) -> Result<Vec<FeedItem>, FeedError> {
+    let shown = url.to_string();
+    let (status, body) = http.get(url).await.context(FeedRequestSnafu)?;
+    ensure!(
+        (200..300).contains(&status),
+        FeedStatusSnafu {
+            url: shown.as_str(),
+            status
+        }
+    );
+    parse(&body).context(FeedParseSnafu { url: shown })
+}
+
+/// A subscription's compiled patterns.
+pub struct Matcher {
+    include: Regex,
+    exclude: Option<Regex>,
+}
+
+fn compile(pattern: &str) -> Result<Regex, ConfigError> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .context(FeedPatternSnafu { pattern })
+}
+
+impl Matcher {
+    pub fn new(subscription: &FeedSubscription) -> Result<Self, ConfigError> {
+        let include = subscription.include.trim();
+        ensure!(
+            !include.is_empty(),
+            FeedPatternMissingSnafu {
+                url: subscription.url.trim()
+            }
+        );
+        let exclude = subscription
+            .exclude
+            .as_deref()
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(compile)
+            .transpose()?;
+        Ok(Self {
+            include: compile(include)?,
+            exclude,
+        })
+    }
+
+    pub fn matches(&self, title: &str) -> bool {
+        self.include.is_match(title)
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(title))
+    }
+}
+
+/// Check every subscription's URL and patterns.
+pub fn validate(subscriptions: &[FeedSubscription]) -> Result<(), ConfigError> {
+    for subscription in subscriptions {
+        feed_url(subscription)?;
+        Matcher::new(subscription)?;
+    }
+    Ok(())
+}
+
+/// What `subscription` would do with each item currently in its feed.
+pub async fn preview(
+    http: &ReqwestClient,
+    subscription: &FeedSubscription,
+) -> Result<Vec<FeedPreviewItem>, privateer_wire_types::AppError> {
+    let matcher = Matcher::new(subscription)?;
+    let items = fetch(http, feed_url(subscription)?).await?;
+    Ok(items
+        .into_iter()
+        .map(|item| FeedPreviewItem {
+            matches: matcher.matches(&item.title),
+            title: item.title,
+        })
+        .collect())
+}
+
+/// The new items to add and the subscription each is added for: unseen
+/// items matched by one of `subscriptions`, all of which share a feed.
+/// `seen` is `None` on the feed's first poll, when nothing is added.
+pub fn new_matches<'a, S>(
+    items: &'a [FeedItem],
+    subscriptions: &'a [(S, Matcher)],
+    seen: Option<&HashSet<String>>,
+) -> Vec<(&'a FeedItem, &'a S)> {
+    let Some(seen) = seen else {
+        return vec![];
+    };
+    items
+        .iter()
+        .filter(|item| !seen.contains(&item.guid))
+        .filter_map(|item| {
+            subscriptions
+                .iter()
+                .find(|(_, matcher)| matcher.matches(&item.title))
+                .map(|(subscription, _)| (item, subscription))
+        })
+        .collect()
+}
+
+/// GUIDs of the items dealt with, by feed URL, kept on disk.
+pub struct SeenItems {
+    path: PathBuf,
+    feeds: HashMap<String, HashSet<String>>,
+}
+
+impl SeenItems {
+    pub fn load(path: PathBuf) -> Self {
+        let feeds = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(feeds) => Some(feeds),
+                Err(e) => {
+                    log::warn!("ignoring unreadable '{}': {e}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, feeds }
+    }
+
+    /// The GUIDs seen in `url`'s feed, or `None` if it was never polled.
+    pub fn get(&self, url: &str) -> Option<&HashSet<String>> {
+        self.feeds.get(url)
+    }
+
+    /// Record the items currently in `url`'s feed, except those that failed
+    /// to add.  Items that have left the feed are forgotten.
+    pub fn update<'a>(
+        &mut self,
+        url: &str,
+        items: &[FeedItem],
+        failed: impl IntoIterator<Item = &'a str>,
+    ) {
+        let failed: HashSet<&str> = failed.into_iter().collect();
+        let guids = items
+            .iter()
+            .map(|item| item.guid.as_str())
+            .filter(|guid| !failed.contains(guid))
+            .map(str::to_string)
+            .collect();
+        self.feeds.insert(url.to_string(), guids);
+    }
+
+    /// Forget feeds no longer subscribed to.
+    pub fn retain<'a>(&mut self, urls: impl IntoIterator<Item = &'a str>) {
+        let urls: HashSet<&str> = urls.into_iter().collect();
+        self.feeds.retain(|url, _| urls.contains(url.as_str()));
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context(CreateDirSnafu {
+                path: parent.to_path_buf(),
+            })?;
+        }
+        let json = serde_json::to_string_pretty(&self.feeds).context(SerializeSnafu)?;
+        std::fs::write(&self.path, json).context(WriteFileSnafu {
+            path: self.path.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use privateer_wire_types::Destination;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+  <channel>
+    <title>New episodes</title>
+    <link>https://tracker.example</link>
+    <description>Latest</description>
+    <item>
+      <title>Show Name S02E05 1080p WEB h264</title>
+      <guid>tracker-1001</guid>
+      <link>https://tracker.example/details/1001</link>
+      <enclosure url="https://tracker.example/download/1001.torrent" length="1" type="application/x-bittorrent"/>
+    </item>
+    <item>
+      <title>Show Name S02E05 2160p WEB h265</title>
+      <link>magnet:?xt=urn:btih:c9e15763f722f23e98a29decdfae341b98d53056</link>
+    </item>
+    <item>
+      <title>Another Show S01E01 720p</title>
+      <guid>tracker-1003</guid>
+      <link>https://tracker.example/details/1003</link>
+      <torznab:attr name="magneturl" value="magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567"/>
+    </item>
+    <item>
+      <guid>tracker-1004</guid>
+      <link>https://tracker.example/details/1004</link>
+    </item>
+  </channel>
+</rss>"#;
+
+    fn subscription(include: &str, exclude: Option<&str>) -> FeedSubscription {
+        FeedSubscription {
+            url: "https://tracker.example/rss".into(),
+            include: include.into(),
+            exclude: exclude.map(str::to_string),
+            destination: Destination::Shows,
+        }
+    }
+
+    #[test]
+    fn items_link_to_a_torrent_preferring_magnets() {
+        let items = parse(FEED.as_bytes()).unwrap();
+
+        assert_eq!(items.len(), 3, "the untitled item is skipped");
+        assert_eq!(items[0].guid, "tracker-1001");
+        assert_eq!(
+            items[0].link,
+            "https://tracker.example/download/1001.torrent"
+        );
+        // Without a GUID the link identifies the item
+        assert_eq!(items[1].guid, items[1].link);
+        assert!(items[1].link.starts_with("magnet:"));
+        assert!(items[2].link.starts_with("magnet:?xt=urn:btih:0123"));
+    }
+
+    #[test]
+    fn unreadable_feeds_are_errors() {
+        assert!(parse(b"<html><body>Not found</body></html>").is_err());
+    }
+
+    #[test]
+    fn patterns_ignore_case_and_exclude_wins() {
+        let matcher = Matcher::new(&subscription("show name s02", Some("2160p"))).unwrap();
+
+        assert!(matcher.matches("Show Name S02E05 1080p WEB h264"));
+        assert!(!matcher.matches("Show Name S02E05 2160p WEB h265"));
+        assert!(!matcher.matches("Another Show S01E01 720p"));
+
+        // A blank exclude pattern excludes nothing
+        let matcher = Matcher::new(&subscription("show", Some("  "))).unwrap();
+        assert!(matcher.matches("Show Name S02E05 2160p WEB h265"));
+    }
+
+    #[test]
+    fn invalid_subscriptions_are_rejected() {
+        assert!(matches!(
+            validate(&[subscription(" ", None)]),
+            Err(ConfigError::FeedPatternMissing { .. })
+        ));
+        assert!(matches!(
+            validate(&[subscription("S02E(", None)]),
+            Err(ConfigError::FeedPattern { pattern, .. }) if pattern == "S02E("
+        ));
+        assert!(matches!(
+            validate(&[subscription("show", Some("[2160p"))]),
+            Err(ConfigError::FeedPattern { .. })
+        ));
+        let mut no_url = subscription("show", None);
+        no_url.url = "tracker.example/rss".into();
+        assert!(matches!(
+            validate(&[no_url]),
+            Err(ConfigError::FeedUrl { .. })
+        ));
+        assert!(validate(&[subscription("show", Some("2160p"))]).is_ok());
+    }
+
+    #[test]
+    fn only_unseen_matches_are_added_for_the_first_matching_subscription() {
+        let items = parse(FEED.as_bytes()).unwrap();
+        let subscriptions = [
+            ("1080p", Matcher::new(&subscription("1080p", None)).unwrap()),
+            ("any", Matcher::new(&subscription("show", None)).unwrap()),
+        ];
+
+        assert!(
+            new_matches(&items, &subscriptions, None).is_empty(),
+            "the first poll adds nothing"
+        );
+
+        let seen = HashSet::from([items[2].guid.clone()]);
+        let added: Vec<_> = new_matches(&items, &subscriptions, Some(&seen))
+            .into_iter()
+            .map(|(item, subscription)| (item.guid.as_str(), *subscription))
+            .collect();
+        assert_eq!(
+            added,
+            [("tracker-1001", "1080p"), (items[1].guid.as_str(), "any")]
+        );
+    }
+
+    #[test]
+    fn seen_items_persist_except_failures() {
+        let path =
+            std::env::temp_dir().join(format!("privateer-seen-feeds-{}.json", std::process::id()));
+        let items = parse(FEED.as_bytes()).unwrap();
+        let url = "https://tracker.example/rss";
+
+        let mut seen = SeenItems::load(path.clone());
+        assert_eq!(seen.get(url), None);
+        seen.update(url, &items, ["tracker-1003"]);
+        seen.update("https://old.example/rss", &items, []);
+        seen.retain([url]);
+        seen.save().unwrap();
+
+        let seen = SeenItems::load(path.clone());
+        let guids = seen.get(url).unwrap();
+        assert!(guids.contains("tracker-1001"));
+        assert!(!guids.contains("tracker-1003"));
+        assert_eq!(seen.get("https://old.example/rss"), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+//! Turning torrent names into names that are safe to copy to.
+//!
+//! A torrent's name comes from whoever made the torrent.  Joined onto a
+//! library directory as it is, `Show: Season 1/Extras` makes an unintended
+//! subdirectory (and fails on an SMB share), and `../..` climbs out of the
+//! library altogether.  [`sanitize`] makes any name a single path component
+//! that every filesystem a library is likely to be on accepts.
+
+use std::path::{Component, Path};
+
+/// Longest file name most filesystems allow, in bytes.
+pub const MAX_BYTES: usize = 255;
+
+/// Longest extension kept when a long name is shortened, e.g. ".mkv".
+const MAX_EXTENSION_BYTES: usize = 16;
+
+/// Names Windows reserves for devices, whatever their extension.
+const DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// `name` without characters that aren't allowed in file names on Windows
+/// or SMB shares.
+pub fn strip_reserved(name: &str) -> String {
+    name.replace(": ", " - ")
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if c == ':' { '-' } else { c })
+        .filter(|c| !matches!(c, '<' | '>' | '"' | '/' | '\\' | '|' | '?' | '*'))
+        .collect()
+}
+
+/// `name` cut to at most `max` bytes without splitting a character.
+fn truncate(name: &str, max: usize) -> &str {
+    let mut end = name.len().min(max);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    &name[..end]
+}
+
+/// `name` as a single file name: path separators become dashes, reserved
+/// and control characters go, dots, dashes and spaces are trimmed from
+/// either end, device names get a `_` and the name is shortened to
+/// [`MAX_BYTES`], keeping its extension.  `None` if nothing is left.
+pub fn sanitize(name: &str) -> Option<String> {
+    let name = strip_reserved(&name.replace(['/', '\\'], "-"));
+    let name = name.trim_matches(|c: char| c == '.' || c == '-' || c.is_whitespace());
+    if name.is_empty() {
+        return None;
+    }
+    let device = name.split('.').next().unwrap_or_default();
+    let name = if DEVICE_NAMES
+        .iter()
+        .any(|reserved| device.eq_ignore_ascii_case(reserved))
+    {
+        format!("{device}_{}", &name[device.len()..])
+    } else {
+        name.to_string()
+    };
+    if name.len() <= MAX_BYTES {
+        return Some(name);
+    }
+
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.is_empty() && (1..=MAX_EXTENSION_BYTES).contains(&extension.len()) =>
+        {
+            (stem, Some(extension))
+        }
+        _ => (name.as_str(), None),
+    };
+    let budget = MAX_BYTES - extension.map_or(0, |extension| extension.len() + 1);
+    let stem = truncate(stem, budget).trim_end_matches(['.', ' ']);
+    Some(match extension {
+        Some(extension) => format!("{stem}.{extension}"),
+        None => stem.to_string(),
+    })
+}
+
+/// Whether `name` can be joined onto a directory as it is, naming an entry
+/// of that directory and nothing else.
+pub fn is_plain(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordinary_names_are_kept() {
+        for name in [
+            "Dune.Part.Two.2024.2160p.WEB-DL.DDP5.1.Atmos-FLUX",
+            "Blade Runner 2049 (2017)",
+            "episode.s01e02.mkv",
+            "Amélie (2001)",
+        ] {
+            assert_eq!(sanitize(name).as_deref(), Some(name));
+            assert!(is_plain(name));
+        }
+    }
+
+    #[test]
+    fn separators_make_no_subdirectories() {
+        assert_eq!(
+            sanitize("Show: Season 1/Extras").as_deref(),
+            Some("Show - Season 1-Extras")
+        );
+        assert_eq!(sanitize("Back\\Slash").as_deref(), Some("Back-Slash"));
+        assert!(!is_plain("Show: Season 1/Extras"));
+    }
+
+    #[test]
+    fn traversal_stays_in_the_library() {
+        for name in ["..", ".", "/", "../", "./.."] {
+            assert_eq!(sanitize(name), None, "{name:?}");
+            assert!(!is_plain(name), "{name:?}");
+        }
+        assert_eq!(sanitize(" .. "), None);
+        let escaped = sanitize("../../etc/passwd").unwrap();
+        assert_eq!(escaped, "etc-passwd");
+        assert_eq!(Path::new(&escaped).components().count(), 1);
+        assert_eq!(sanitize(".hidden").as_deref(), Some("hidden"));
+        assert!(!is_plain("../../etc/passwd"));
+    }
+
+    #[test]
+    fn windows_reserved_names_and_characters() {
+        assert_eq!(
+            sanitize("What? <Really> \"Now\" | *Yes*").as_deref(),
+            Some("What Really Now  Yes")
+        );
+        assert_eq!(sanitize("Trailing dot. ").as_deref(), Some("Trailing dot"));
+        assert_eq!(sanitize("CON").as_deref(), Some("CON_"));
+        assert_eq!(sanitize("nul.txt").as_deref(), Some("nul_.txt"));
+        assert_eq!(sanitize("Lpt1.tar.gz").as_deref(), Some("Lpt1_.tar.gz"));
+        assert_eq!(sanitize("Console").as_deref(), Some("Console"));
+        assert_eq!(sanitize("Bell\u{7}\tTab").as_deref(), Some("BellTab"));
+        assert_eq!(sanitize("???"), None);
+    }
+
+    #[test]
+    fn long_names_fit_the_filesystem() {
+        let long = format!("{}.mkv", "a".repeat(300));
+        let short = sanitize(&long).unwrap();
+        assert_eq!(short.len(), MAX_BYTES);
+        assert!(short.ends_with("a.mkv"));
+
+        // Multi-byte characters aren't split
+        let accents = "é".repeat(200);
+        let short = sanitize(&accents).unwrap();
+        assert!(short.len() <= MAX_BYTES);
+        assert!(short.chars().all(|c| c == 'é'));
+
+        // A dot deep in a long name isn't taken for an extension
+        let dotted = format!("{}.{}", "b".repeat(10), "c".repeat(300));
+        assert_eq!(sanitize(&dotted).unwrap().len(), MAX_BYTES);
+    }
+}
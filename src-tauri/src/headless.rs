@@ -0,0 +1,253 @@
+//! Running only the copy engine, without a window, e.g. as a daemon on the
+//! server next to Transmission:
+//!
+//! ```text
+//! privateer --headless [--data-dir DIR] [--config FILE] [--ledger FILE]
+//! ```
+//!
+//! The config and ledger are read from disk every cycle, so the desktop app
+//! can still change them, through a synced directory if need be.  SIGTERM or
+//! Ctrl-C stops the engine once the copy in progress, if any, has finished.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use privateer_wire_types::CopyEngineStatus;
+use snafu::{OptionExt, ResultExt};
+use tokio::sync::{watch, Notify};
+
+use crate::copier::TokioFs;
+use crate::data_paths::{self, DataPaths, DATA_DIR_VAR};
+use crate::error::*;
+use crate::http::ReqwestClient;
+use crate::logging::Logging;
+use crate::media_server::{self, MediaScanner};
+use crate::metrics::{MetricsFile, MetricsRecorder};
+use crate::post_copy::{self, PostCopyHook};
+use crate::webhook::{self, WebhookSender};
+
+const USAGE: &str = "\
+Usage: privateer --headless [--data-dir DIR] [--config FILE] [--ledger FILE]
+
+Runs the copy engine without a window until SIGTERM or Ctrl-C.
+
+  --data-dir DIR   where logs and copy metrics are kept, and the config and
+                   ledger unless given below [env: PRIVATEER_DATA_DIR]
+  --config FILE    Transmission config file [env: PRIVATEER_CONFIG]
+  --ledger FILE    downloads ledger file [env: PRIVATEER_LEDGER]";
+
+/// What the command line asked for.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Run(DataPaths),
+    Help,
+}
+
+/// Parse the arguments after the program name.  Arguments win over the
+/// environment, which wins over the desktop app's data dir.
+fn parse_args(
+    args: &[String],
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Command, HeadlessError> {
+    let mut data_dir = None;
+    let mut config = None;
+    let mut ledger = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--headless" => continue,
+            "-h" | "--help" => return Ok(Command::Help),
+            "--data-dir" => &mut data_dir,
+            "--config" => &mut config,
+            "--ledger" => &mut ledger,
+            _ => return HeadlessUnknownArgumentSnafu { argument: arg }.fail(),
+        };
+        let value = args
+            .next()
+            .filter(|value| !value.starts_with("--"))
+            .context(HeadlessMissingValueSnafu { flag: arg })?;
+        *slot = Some(PathBuf::from(value));
+    }
+
+    let data_dir = data_dir
+        .or_else(|| {
+            env(DATA_DIR_VAR)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        })
+        .or_else(|| data_paths::default_data_dir(&env))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut paths = DataPaths::from_env(data_dir, env);
+    if let Some(config) = config {
+        paths.config = config;
+    }
+    if let Some(ledger) = ledger {
+        paths.ledger = ledger;
+    }
+    Ok(Command::Run(paths))
+}
+
+/// Run headless with the arguments after the program name.
+pub fn run(args: &[String]) -> ExitCode {
+    let paths = match parse_args(args, |key| std::env::var(key).ok()) {
+        Ok(Command::Run(paths)) => paths,
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context(HeadlessRuntimeSnafu)
+        .and_then(|runtime| runtime.block_on(serve(paths)));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::error!("Headless: {e}");
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn serve(paths: DataPaths) -> Result<(), HeadlessError> {
+    let _logging = Logging::init(&paths.logs());
+    log::info!(
+        "Headless: copying with config {} and ledger {}",
+        paths.config.display(),
+        paths.ledger.display()
+    );
+
+    // Listen before starting so a signal can't go unnoticed
+    let shutdown = shutdown_signal()?;
+
+    let (scanner, scan_jobs) = MediaScanner::new();
+    let (webhook_sender, webhook_jobs) = WebhookSender::new();
+    let (hook, hook_jobs) = PostCopyHook::new();
+    let observers = (
+        MetricsRecorder::new(Arc::new(MetricsFile::new(paths.metrics()))),
+        (scanner, (webhook_sender, hook)),
+    );
+    tokio::spawn(media_server::scan_task(
+        scan_jobs,
+        ReqwestClient::default(),
+        |message| log::warn!("{message}"),
+    ));
+    tokio::spawn(webhook::webhook_task(
+        webhook_jobs,
+        ReqwestClient::with_timeout(webhook::TIMEOUT),
+    ));
+    tokio::spawn(post_copy::hook_task(hook_jobs, |message| {
+        log::warn!("{message}")
+    }));
+
+    let (stop, stopped) = watch::channel(false);
+    let copy_task = tokio::spawn(crate::copy_task_from_disk(
+        paths.config,
+        crate::LedgerFile::new(paths.ledger),
+        TokioFs::default(),
+        Arc::new(Notify::new()),
+        observers,
+        // Nothing asks for the copy engine's health
+        watch::Sender::new(CopyEngineStatus::default()),
+        // Paused copying is read from the config each cycle
+        None,
+        None,
+        Some(stopped),
+    ));
+
+    shutdown.await;
+    log::info!("Headless: stopping once the copy in progress, if any, finishes");
+    let _ = stop.send(true);
+    let _ = copy_task.await;
+    log::info!("Headless: stopped");
+    Ok(())
+}
+
+/// Resolves on SIGTERM (on Unix) or Ctrl-C.
+fn shutdown_signal() -> Result<impl std::future::Future<Output = ()>, HeadlessError> {
+    #[cfg(unix)]
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .context(HeadlessSignalSnafu)?;
+    Ok(async move {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = terminate.recv() => log::info!("Headless: received SIGTERM"),
+            _ = tokio::signal::ctrl_c() => log::info!("Headless: received Ctrl-C"),
+        }
+        #[cfg(not(unix))]
+        if tokio::signal::ctrl_c().await.is_ok() {
+            log::info!("Headless: received Ctrl-C");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn paths(command: Command) -> DataPaths {
+        match command {
+            Command::Run(paths) => paths,
+            Command::Help => panic!("expected paths"),
+        }
+    }
+
+    #[test]
+    fn arguments_override_the_environment() {
+        let env = |key: &str| match key {
+            DATA_DIR_VAR => Some("/env".to_string()),
+            data_paths::LEDGER_VAR => Some("/env/ledger.json".to_string()),
+            _ => None,
+        };
+        let parsed = paths(
+            parse_args(
+                &args(&["--headless", "--ledger", "/sync/downloads.json"]),
+                env,
+            )
+            .unwrap(),
+        );
+        assert_eq!(PathBuf::from("/env"), parsed.data_dir);
+        assert_eq!(
+            PathBuf::from("/env/transmission_config.json"),
+            parsed.config
+        );
+        assert_eq!(PathBuf::from("/sync/downloads.json"), parsed.ledger);
+    }
+
+    #[test]
+    fn data_dir_argument_moves_every_file() {
+        let parsed = paths(parse_args(&args(&["--data-dir", "/srv/privateer"]), |_| None).unwrap());
+        assert_eq!(DataPaths::new("/srv/privateer".into()), parsed);
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(matches!(
+            parse_args(&args(&["--config"]), |_| None),
+            Err(HeadlessError::HeadlessMissingValue { .. })
+        ));
+        assert!(matches!(
+            parse_args(&args(&["--config", "--ledger", "x"]), |_| None),
+            Err(HeadlessError::HeadlessMissingValue { .. })
+        ));
+        assert!(matches!(
+            parse_args(&args(&["--verbose"]), |_| None),
+            Err(HeadlessError::HeadlessUnknownArgument { .. })
+        ));
+        assert_eq!(
+            Command::Help,
+            parse_args(&args(&["--headless", "--help"]), |_| None).unwrap()
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! A small HTTP client interface for calling other services (media servers,
+//! webhooks, qBittorrent), so that code can be tested against a recording
+//! double instead of the network.
+
+use std::time::Duration;
+
+use crate::error::HttpError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: url::Url,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn get(url: url::Url) -> Self {
+        Self {
+            method: Method::Get,
+            url,
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    /// A POST with a JSON body.
+    pub fn post_json(url: url::Url, body: String) -> Self {
+        Self {
+            method: Method::Post,
+            url,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: Some(body),
+        }
+    }
+
+    /// A POST of `fields` as an HTML form.
+    pub fn post_form(url: url::Url, fields: &[(&str, &str)]) -> Self {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(fields)
+            .finish();
+        Self {
+            method: Method::Post,
+            url,
+            headers: vec![(
+                "Content-Type".into(),
+                "application/x-www-form-urlencoded".into(),
+            )],
+            body: Some(body),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+/// Sends requests, returning the response's HTTP status.
+pub trait HttpClient {
+    async fn send(&self, request: HttpRequest) -> Result<u16, HttpError>;
+}
+
+/// Sends requests, returning the whole response.
+pub trait HttpTransport {
+    async fn fetch(&self, request: HttpRequest) -> Result<HttpResponse, HttpError>;
+}
+
+/// Requests to other services give up after this long by default.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A response read in full.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// The first value of the header `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The real client.  Cheap to clone; clones share a connection pool.
+#[derive(Clone)]
+pub struct ReqwestClient(reqwest::Client);
+
+impl Default for ReqwestClient {
+    fn default() -> Self {
+        Self::with_timeout(TIMEOUT)
+    }
+}
+
+impl ReqwestClient {
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self(
+            reqwest::Client::builder()
+                .timeout(timeout)
+                // GitHub's API refuses requests without one
+                .user_agent(concat!("privateer/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// GET `url`, returning the response's HTTP status and body.
+    pub async fn get(&self, url: url::Url) -> Result<(u16, Vec<u8>), HttpError> {
+        let shown = shown_url(&url);
+        let transport = |e: reqwest::Error| HttpError::Transport {
+            url: shown.clone(),
+            message: e.without_url().to_string(),
+        };
+        let response = self.0.get(url).send().await.map_err(transport)?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await.map_err(transport)?;
+        Ok((status, body.to_vec()))
+    }
+
+    /// POST `request`, with HTTP basic auth if `auth` is given, returning
+    /// the whole response.
+    pub async fn post_json(
+        &self,
+        request: HttpRequest,
+        auth: Option<(&str, &str)>,
+    ) -> Result<HttpResponse, HttpError> {
+        self.exchange(request, auth).await
+    }
+
+    /// Send `request`, with HTTP basic auth if `auth` is given, and read the
+    /// whole response.
+    async fn exchange(
+        &self,
+        request: HttpRequest,
+        auth: Option<(&str, &str)>,
+    ) -> Result<HttpResponse, HttpError> {
+        let shown = shown_url(&request.url);
+        let transport = |e: reqwest::Error| HttpError::Transport {
+            url: shown.clone(),
+            message: e.without_url().to_string(),
+        };
+        let mut builder = match request.method {
+            Method::Get => self.0.get(request.url),
+            Method::Post => self.0.post(request.url),
+        };
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some((user, password)) = auth {
+            builder = builder.basic_auth(user, Some(password));
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        let response = builder.send().await.map_err(transport)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.bytes().await.map_err(transport)?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+}
+
+/// `url` for error messages.  The query can hold a token, so it's left out.
+fn shown_url(url: &url::Url) -> String {
+    let mut shown = url.clone();
+    shown.set_query(None);
+    shown.to_string()
+}
+
+impl HttpTransport for ReqwestClient {
+    async fn fetch(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        self.exchange(request, None).await
+    }
+}
+
+impl HttpClient for ReqwestClient {
+    async fn send(&self, request: HttpRequest) -> Result<u16, HttpError> {
+        let shown = shown_url(&request.url);
+        let mut builder = match request.method {
+            Method::Get => self.0.get(request.url),
+            Method::Post => self.0.post(request.url),
+        };
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        let response = builder.send().await.map_err(|e| HttpError::Transport {
+            url: shown,
+            message: e.without_url().to_string(),
+        })?;
+        Ok(response.status().as_u16())
+    }
+}
@@ -0,0 +1,720 @@
+//! Recording torrents in the downloads ledger, and looking them up.
+//!
+//! Assigning a destination inserts an entry, or resets an existing one so
+//! it is copied (again) to its new destination, and to any extra ones.  A
+//! batch is checked as a whole before any entry changes, so a bad request
+//! leaves the ledger as it was.
+//!
+//! The user's notes on entries are only ever changed by the user: whoever
+//! saves a ledger loaded before a note changed keeps the note on disk.
+//!
+//! Removed entries aren't deleted but moved to a list of their own, from
+//! which they can be restored for [`REMOVED_KEEP_DAYS`].  Likewise whoever
+//! saves a ledger loaded before an entry was removed leaves it removed.
+//!
+//! A download that needs replacing hands its entry over to the replacement,
+//! which is copied to the same destinations; the broken one's entry is
+//! removed like any other.
+
+use privateer_wire_types::{
+    AddDownloadResult, AddOutcome, CopyState, CopyTarget, Destination, DownloadEntry,
+    DownloadOrigin, InfoHash, LedgerPage, LedgerQuery, NewDownload, RemovedDownload,
+    REMOVED_KEEP_DAYS,
+};
+use snafu::{ensure, OptionExt};
+
+use crate::error::*;
+
+/// The extra targets for copying to the places of `extra` as well as to
+/// `destination`, not copied yet and each place once.  A second copy to the
+/// same place would only write to the same directory again.
+fn extra_targets(destination: Destination, extra: &[CopyTarget]) -> Vec<CopyTarget> {
+    let own = CopyTarget::new(destination);
+    let mut targets: Vec<CopyTarget> = vec![];
+    for extra in extra {
+        let target = match extra.dir.as_deref().map(str::trim) {
+            Some("") => continue,
+            Some(dir) => CopyTarget::in_dir(extra.destination, dir.to_string()),
+            None => CopyTarget::new(extra.destination),
+        };
+        if !target.same_place(&own) && !targets.iter().any(|t| t.same_place(&target)) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// Insert or update a ledger entry for the given torrent, copied to the
+/// places of `extra` as well, with the magnet link it was added with if
+/// there is one.
+pub fn record_download(
+    ledger: &mut Vec<DownloadEntry>,
+    info_hash: InfoHash,
+    name: String,
+    destination: Destination,
+    extra: &[CopyTarget],
+    magnet: Option<String>,
+    profile: u32,
+) -> AddOutcome {
+    let extra_targets = extra_targets(destination, extra);
+    // Check if already tracked
+    if let Some(entry) = ledger.iter_mut().find(|e| e.info_hash == info_hash) {
+        // Update destination if changed
+        let mut updated = entry.clone();
+        updated.destination = destination;
+        updated.copy_state = CopyState::NotCopied;
+        updated.copied_path = None;
+        updated.renamed_files.clear();
+        updated.note = None;
+        updated.failure = None;
+        updated.copied_at = None;
+        updated.copied_bytes = None;
+        updated.copy_seconds = None;
+        updated.copy_now = false;
+        updated.extra_targets = extra_targets;
+        updated.profile = profile;
+        // Assigning takes over an entry reconciliation added
+        updated.origin = DownloadOrigin::User;
+        if magnet.is_some() {
+            updated.magnet = magnet;
+        }
+        if updated == *entry {
+            return AddOutcome::Unchanged;
+        }
+        *entry = updated;
+        AddOutcome::Updated
+    } else {
+        ledger.push(DownloadEntry {
+            magnet,
+            profile,
+            extra_targets,
+            ..DownloadEntry::new(info_hash, name, destination)
+        });
+        AddOutcome::Added
+    }
+}
+
+/// Fail on a torrent without a name or one given twice.
+fn validate_downloads(downloads: &[NewDownload]) -> Result<(), ConfigError> {
+    for (i, download) in downloads.iter().enumerate() {
+        ensure!(
+            !download.name.trim().is_empty(),
+            DownloadNameMissingSnafu {
+                info_hash: download.info_hash.clone()
+            }
+        );
+        let repeated = downloads[..i]
+            .iter()
+            .any(|earlier| earlier.info_hash == download.info_hash);
+        ensure!(
+            !repeated,
+            DownloadRepeatedSnafu {
+                name: &download.name
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Record every torrent in `downloads`, or none of them if one is invalid.
+pub fn record_downloads(
+    ledger: &mut Vec<DownloadEntry>,
+    downloads: Vec<NewDownload>,
+    profile: u32,
+) -> Result<Vec<AddDownloadResult>, ConfigError> {
+    validate_downloads(&downloads)?;
+    Ok(downloads
+        .into_iter()
+        .map(|download| {
+            let extra = download.extra_targets();
+            AddDownloadResult {
+                info_hash: download.info_hash.clone(),
+                outcome: record_download(
+                    ledger,
+                    download.info_hash,
+                    download.name,
+                    download.destination,
+                    &extra,
+                    None,
+                    profile,
+                ),
+            }
+        })
+        .collect())
+}
+
+/// Set the user's note on the entry for `info_hash`, trimmed.  A blank note
+/// removes it.
+pub fn set_user_note(
+    ledger: &mut [DownloadEntry],
+    info_hash: &InfoHash,
+    note: Option<String>,
+) -> Result<(), ConfigError> {
+    let entry = ledger
+        .iter_mut()
+        .find(|e| &e.info_hash == info_hash)
+        .context(DownloadNotTrackedSnafu {
+            info_hash: info_hash.clone(),
+        })?;
+    entry.user_note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    Ok(())
+}
+
+/// Move the entry for `info_hash` from `ledger` to `removed`, as removed at
+/// `now`.  An older removal of the same torrent is replaced.
+pub fn remove_download(
+    ledger: &mut Vec<DownloadEntry>,
+    removed: &mut Vec<RemovedDownload>,
+    info_hash: &InfoHash,
+    now: i64,
+) -> Result<(), ConfigError> {
+    let index = ledger
+        .iter()
+        .position(|e| &e.info_hash == info_hash)
+        .context(DownloadNotInLedgerSnafu {
+            info_hash: info_hash.clone(),
+        })?;
+    let entry = ledger.remove(index);
+    removed.retain(|r| &r.entry.info_hash != info_hash);
+    removed.push(RemovedDownload {
+        entry,
+        removed_at: now,
+    });
+    Ok(())
+}
+
+/// Hand the entry for `old` over to its replacement `new`, as replaced at
+/// `now`.  The replacement is copied from scratch to the same destinations,
+/// keeps the user's note and remembers the old name; the old entry is
+/// removed, so it can still be restored.
+pub fn transfer_entry(
+    ledger: &mut Vec<DownloadEntry>,
+    removed: &mut Vec<RemovedDownload>,
+    old: &InfoHash,
+    new: &InfoHash,
+    now: i64,
+) -> Result<(), ConfigError> {
+    let entry = ledger
+        .iter()
+        .find(|e| &e.info_hash == old)
+        .cloned()
+        .context(DownloadNotInLedgerSnafu {
+            info_hash: old.clone(),
+        })?;
+    if old == new {
+        return Ok(());
+    }
+    let name = ledger
+        .iter()
+        .find(|e| &e.info_hash == new)
+        .map_or_else(|| entry.name.clone(), |e| e.name.clone());
+    record_download(
+        ledger,
+        new.clone(),
+        name,
+        entry.destination,
+        &entry.extra_targets,
+        None,
+        entry.profile,
+    );
+    if let Some(replacement) = ledger.iter_mut().find(|e| &e.info_hash == new) {
+        for name in entry.previous_names.iter().chain([&entry.name]) {
+            if name != &replacement.name && !replacement.previous_names.contains(name) {
+                replacement.previous_names.push(name.clone());
+            }
+        }
+        if replacement.user_note.is_none() {
+            replacement.user_note = entry.user_note;
+        }
+    }
+    remove_download(ledger, removed, old, now)
+}
+
+/// How far along an entry's copies are.
+fn copy_progress(entry: &DownloadEntry) -> u8 {
+    match entry.overall_copy_state() {
+        CopyState::NotCopied | CopyState::Unconfigured => 0,
+        CopyState::Failed => 1,
+        CopyState::Copying => 2,
+        CopyState::Copied => 3,
+    }
+}
+
+/// Move the entry for `info_hash` from `removed` back to `ledger`.
+///
+/// If the torrent was added again since, the entry whose copies are
+/// further along is kept, e.g. the record of a copy over a fresh entry
+/// that hasn't been copied yet, with the names and note of both.
+pub fn restore_download(
+    ledger: &mut Vec<DownloadEntry>,
+    removed: &mut Vec<RemovedDownload>,
+    info_hash: &InfoHash,
+) -> Result<(), ConfigError> {
+    let index = removed
+        .iter()
+        .position(|r| &r.entry.info_hash == info_hash)
+        .context(DownloadNotRemovedSnafu {
+            info_hash: info_hash.clone(),
+            days: REMOVED_KEEP_DAYS,
+        })?;
+    let restored = removed.remove(index).entry;
+    let Some(live) = ledger.iter_mut().find(|e| &e.info_hash == info_hash) else {
+        ledger.push(restored);
+        return Ok(());
+    };
+    let (mut kept, other) = if copy_progress(&restored) > copy_progress(live) {
+        (restored, live.clone())
+    } else {
+        (live.clone(), restored)
+    };
+    for name in other.previous_names.into_iter().chain([other.name]) {
+        if name != kept.name && !kept.previous_names.contains(&name) {
+            kept.previous_names.push(name);
+        }
+    }
+    if kept.user_note.is_none() {
+        kept.user_note = other.user_note;
+    }
+    if kept.magnet.is_none() {
+        kept.magnet = other.magnet;
+    }
+    *live = kept;
+    Ok(())
+}
+
+/// Drop the removed entries kept for long enough at `now`.  Returns whether
+/// any were.
+pub fn expire_removed(removed: &mut Vec<RemovedDownload>, now: i64) -> bool {
+    let before = removed.len();
+    removed.retain(|r| !r.is_expired(now));
+    removed.len() != before
+}
+
+/// Whether `entry` passes every filter of `query`.  Entries never copied
+/// fail either time bound.  An entry copied to several destinations is in
+/// the state of its copies taken together, and at each destination.
+fn matches(query: &LedgerQuery, entry: &DownloadEntry) -> bool {
+    let in_state =
+        query.copy_states.is_empty() || query.copy_states.contains(&entry.overall_copy_state());
+    let at_destination = query.destination.is_none_or(|dest| {
+        dest == entry.destination || entry.extra_targets.iter().any(|t| t.destination == dest)
+    });
+    let named = query.name.as_deref().is_none_or(|name| {
+        entry
+            .name
+            .to_lowercase()
+            .contains(&name.trim().to_lowercase())
+    });
+    let after = query
+        .copied_after
+        .is_none_or(|after| entry.copied_at.is_some_and(|at| at >= after));
+    let before = query
+        .copied_before
+        .is_none_or(|before| entry.copied_at.is_some_and(|at| at < before));
+    in_state && at_destination && named && after && before
+}
+
+/// The page of `ledger` that `query` asks for, in ledger order.
+pub fn query(ledger: &[DownloadEntry], query: &LedgerQuery) -> LedgerPage {
+    let matching = ledger.iter().filter(|entry| matches(query, entry));
+    LedgerPage {
+        total: matching.clone().count(),
+        entries: matching
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use privateer_wire_types::TargetDir;
+
+    fn hash(byte: char) -> InfoHash {
+        byte.to_string().repeat(40).parse().unwrap()
+    }
+
+    fn new_download(byte: char, destination: Destination) -> NewDownload {
+        NewDownload {
+            info_hash: hash(byte),
+            name: format!("Torrent {byte}"),
+            destination,
+            extra_destinations: vec![],
+            extra_dirs: vec![],
+        }
+    }
+
+    #[test]
+    fn batch_adds_updates_and_skips() {
+        let mut ledger = vec![];
+        let existing = vec![
+            new_download('a', Destination::Movies),
+            new_download('b', Destination::Movies),
+        ];
+        record_downloads(&mut ledger, existing, 0).unwrap();
+        ledger[1].copy_state = CopyState::Copied;
+        ledger[1].origin = DownloadOrigin::AutoDetected;
+
+        let results = record_downloads(
+            &mut ledger,
+            vec![
+                new_download('a', Destination::Movies),
+                new_download('b', Destination::Shows),
+                new_download('c', Destination::Shows),
+            ],
+            0,
+        )
+        .unwrap();
+
+        let outcomes = results.iter().map(|r| r.outcome).collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                AddOutcome::Unchanged,
+                AddOutcome::Updated,
+                AddOutcome::Added
+            ]
+        );
+        assert_eq!(results[2].info_hash, hash('c'));
+        assert_eq!(ledger.len(), 3);
+        assert_eq!(ledger[1].destination, Destination::Shows);
+        assert_eq!(ledger[1].copy_state, CopyState::NotCopied);
+        assert_eq!(ledger[1].origin, DownloadOrigin::User);
+        assert_eq!(ledger[2].name, "Torrent c");
+    }
+
+    #[test]
+    fn extra_destinations_are_targets_of_their_own() {
+        let mut ledger = vec![];
+        let both = NewDownload {
+            extra_destinations: vec![Destination::Movies, Destination::Shows, Destination::Movies],
+            ..new_download('a', Destination::Shows)
+        };
+        record_downloads(&mut ledger, vec![both.clone()], 0).unwrap();
+        assert_eq!(
+            ledger[0].extra_targets,
+            [CopyTarget::new(Destination::Movies)]
+        );
+
+        // Assigning again resets every copy
+        ledger[0].copy_state = CopyState::Copied;
+        ledger[0].extra_targets[0].copy_state = CopyState::Failed;
+        let results = record_downloads(&mut ledger, vec![both], 0).unwrap();
+        assert_eq!(results[0].outcome, AddOutcome::Updated);
+        assert_eq!(ledger[0].overall_copy_state(), CopyState::NotCopied);
+
+        let at_movies = LedgerQuery {
+            destination: Some(Destination::Movies),
+            ..Default::default()
+        };
+        assert_eq!(query(&ledger, &at_movies).total, 1);
+
+        let only_shows =
+            record_downloads(&mut ledger, vec![new_download('a', Destination::Shows)], 0);
+        assert_eq!(only_shows.unwrap()[0].outcome, AddOutcome::Updated);
+        assert!(ledger[0].extra_targets.is_empty());
+    }
+
+    #[test]
+    fn extra_dirs_are_targets_of_their_own() {
+        let mut ledger = vec![];
+        let scratch = |dir: &str| TargetDir {
+            destination: Destination::Shows,
+            dir: dir.to_string(),
+        };
+        let travel = NewDownload {
+            extra_destinations: vec![Destination::Shows],
+            extra_dirs: vec![
+                scratch("/Volumes/Scratch"),
+                scratch(" /Volumes/Scratch "),
+                scratch("  "),
+                TargetDir {
+                    destination: Destination::Movies,
+                    dir: "/Volumes/Scratch".to_string(),
+                },
+            ],
+            ..new_download('a', Destination::Shows)
+        };
+        record_downloads(&mut ledger, vec![travel], 0).unwrap();
+        assert_eq!(
+            ledger[0].extra_targets,
+            [
+                CopyTarget::in_dir(Destination::Shows, "/Volumes/Scratch".to_string()),
+                CopyTarget::in_dir(Destination::Movies, "/Volumes/Scratch".to_string()),
+            ]
+        );
+
+        // A replacement copies to the same places
+        let mut removed = vec![];
+        ledger[0].extra_targets[0].copy_state = CopyState::Copied;
+        transfer_entry(&mut ledger, &mut removed, &hash('a'), &hash('b'), 100).unwrap();
+        assert_eq!(
+            ledger[0].extra_targets[0],
+            CopyTarget::in_dir(Destination::Shows, "/Volumes/Scratch".to_string())
+        );
+    }
+
+    #[test]
+    fn notes_are_trimmed_and_blank_ones_removed() {
+        let mut ledger = vec![];
+        record_downloads(&mut ledger, vec![new_download('a', Destination::Movies)], 0).unwrap();
+
+        set_user_note(&mut ledger, &hash('a'), Some("  for mum ".to_string())).unwrap();
+        assert_eq!(ledger[0].user_note.as_deref(), Some("for mum"));
+        // Reassigning is no reason to forget it
+        record_downloads(&mut ledger, vec![new_download('a', Destination::Shows)], 0).unwrap();
+        assert_eq!(ledger[0].user_note.as_deref(), Some("for mum"));
+        set_user_note(&mut ledger, &hash('a'), Some(" ".to_string())).unwrap();
+        assert_eq!(ledger[0].user_note, None);
+
+        let untracked = set_user_note(&mut ledger, &hash('b'), Some("note".to_string()));
+        assert!(matches!(
+            untracked,
+            Err(ConfigError::DownloadNotTracked { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_batch_changes_nothing() {
+        let mut ledger = vec![];
+        let repeated = vec![
+            new_download('a', Destination::Movies),
+            new_download('b', Destination::Movies),
+            new_download('a', Destination::Shows),
+        ];
+        assert!(matches!(
+            record_downloads(&mut ledger, repeated, 0),
+            Err(ConfigError::DownloadRepeated { .. })
+        ));
+
+        let unnamed = vec![
+            new_download('a', Destination::Movies),
+            NewDownload {
+                name: " ".to_string(),
+                ..new_download('b', Destination::Movies)
+            },
+        ];
+        assert!(matches!(
+            record_downloads(&mut ledger, unnamed, 0),
+            Err(ConfigError::DownloadNameMissing { .. })
+        ));
+        assert!(ledger.is_empty());
+    }
+
+    /// Five entries, copied an hour apart except the last.
+    fn ledger() -> Vec<DownloadEntry> {
+        let mut ledger = vec![];
+        let downloads = vec![
+            new_download('a', Destination::Movies),
+            new_download('b', Destination::Shows),
+            new_download('c', Destination::Movies),
+            new_download('d', Destination::Shows),
+            new_download('e', Destination::Movies),
+        ];
+        record_downloads(&mut ledger, downloads, 0).unwrap();
+        for (i, entry) in ledger.iter_mut().enumerate().take(4) {
+            entry.copy_state = CopyState::Copied;
+            entry.copied_at = Some(i as i64 * 3600);
+        }
+        ledger[3].copy_state = CopyState::Failed;
+        ledger
+    }
+
+    fn names(page: &LedgerPage) -> Vec<&str> {
+        page.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    fn names_of(ledger: &[DownloadEntry]) -> Vec<&str> {
+        ledger.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn no_filters_return_everything() {
+        let ledger = ledger();
+        let page = query(&ledger, &LedgerQuery::default());
+        assert_eq!(page.total, 5);
+        assert_eq!(page.entries, ledger);
+    }
+
+    #[test]
+    fn each_filter_narrows_the_ledger() {
+        let ledger = ledger();
+        let by_state = LedgerQuery {
+            copy_states: vec![CopyState::Failed, CopyState::NotCopied],
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&query(&ledger, &by_state)),
+            ["Torrent d", "Torrent e"]
+        );
+
+        let by_destination = LedgerQuery {
+            destination: Some(Destination::Shows),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&query(&ledger, &by_destination)),
+            ["Torrent b", "Torrent d"]
+        );
+
+        let by_name = LedgerQuery {
+            name: Some(" TORRENT C".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&query(&ledger, &by_name)), ["Torrent c"]);
+
+        let after = LedgerQuery {
+            copied_after: Some(3600),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&query(&ledger, &after)),
+            ["Torrent b", "Torrent c", "Torrent d"]
+        );
+
+        let before = LedgerQuery {
+            copied_before: Some(3600),
+            ..Default::default()
+        };
+        assert_eq!(names(&query(&ledger, &before)), ["Torrent a"]);
+    }
+
+    #[test]
+    fn filters_combine_before_paging() {
+        let ledger = ledger();
+        let combined = LedgerQuery {
+            copy_states: vec![CopyState::Copied],
+            destination: Some(Destination::Movies),
+            copied_after: Some(0),
+            copied_before: Some(4 * 3600),
+            ..Default::default()
+        };
+        let page = query(&ledger, &combined);
+        assert_eq!(names(&page), ["Torrent a", "Torrent c"]);
+
+        let paged = LedgerQuery {
+            destination: Some(Destination::Movies),
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let page = query(&ledger, &paged);
+        assert_eq!(page.total, 3);
+        assert_eq!(names(&page), ["Torrent c"]);
+
+        let past_the_end = LedgerQuery {
+            offset: 10,
+            ..paged
+        };
+        let page = query(&ledger, &past_the_end);
+        assert_eq!(page.total, 3);
+        assert!(page.entries.is_empty());
+    }
+
+    #[test]
+    fn removed_entries_can_be_restored_until_they_expire() {
+        let mut ledger = vec![];
+        let mut removed = vec![];
+        let downloads = vec![
+            new_download('a', Destination::Movies),
+            new_download('b', Destination::Movies),
+        ];
+        record_downloads(&mut ledger, downloads, 0).unwrap();
+
+        remove_download(&mut ledger, &mut removed, &hash('a'), 100).unwrap();
+        assert_eq!(names_of(&ledger), ["Torrent b"]);
+        assert_eq!(removed[0].removed_at, 100);
+        let gone = remove_download(&mut ledger, &mut removed, &hash('a'), 100);
+        assert!(matches!(gone, Err(ConfigError::DownloadNotInLedger { .. })));
+
+        restore_download(&mut ledger, &mut removed, &hash('a')).unwrap();
+        assert_eq!(names_of(&ledger), ["Torrent b", "Torrent a"]);
+        assert!(removed.is_empty());
+        let restored_twice = restore_download(&mut ledger, &mut removed, &hash('a'));
+        assert!(matches!(
+            restored_twice,
+            Err(ConfigError::DownloadNotRemoved { .. })
+        ));
+
+        remove_download(&mut ledger, &mut removed, &hash('b'), 100).unwrap();
+        let kept_until = 100 + REMOVED_KEEP_DAYS * 86_400;
+        assert!(!expire_removed(&mut removed, kept_until - 1));
+        assert!(expire_removed(&mut removed, kept_until));
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn restoring_over_a_readded_entry_keeps_the_further_copy() {
+        let mut ledger = vec![];
+        let mut removed = vec![];
+        record_downloads(&mut ledger, vec![new_download('a', Destination::Movies)], 0).unwrap();
+        ledger[0].copy_state = CopyState::Copied;
+        ledger[0].copied_path = Some("/Media/Movies/Torrent a".to_string());
+        ledger[0].user_note = Some("for mum".to_string());
+        remove_download(&mut ledger, &mut removed, &hash('a'), 100).unwrap();
+
+        // Added again under a new name, not copied yet
+        record_downloads(&mut ledger, vec![new_download('a', Destination::Movies)], 0).unwrap();
+        ledger[0].rename("Torrent a (again)");
+        restore_download(&mut ledger, &mut removed, &hash('a')).unwrap();
+        assert_eq!(ledger.len(), 1);
+        assert_eq!(ledger[0].copy_state, CopyState::Copied);
+        assert_eq!(
+            ledger[0].copied_path.as_deref(),
+            Some("/Media/Movies/Torrent a")
+        );
+        assert_eq!(ledger[0].user_note.as_deref(), Some("for mum"));
+        assert_eq!(ledger[0].previous_names, ["Torrent a (again)"]);
+
+        // A live copy further along than the removed entry wins
+        remove_download(&mut ledger, &mut removed, &hash('a'), 100).unwrap();
+        removed[0].entry.copy_state = CopyState::Failed;
+        record_downloads(&mut ledger, vec![new_download('a', Destination::Movies)], 0).unwrap();
+        ledger[0].copy_state = CopyState::Copied;
+        restore_download(&mut ledger, &mut removed, &hash('a')).unwrap();
+        assert_eq!(ledger[0].copy_state, CopyState::Copied);
+        assert_eq!(ledger[0].user_note.as_deref(), Some("for mum"));
+    }
+
+    #[test]
+    fn replacements_take_over_the_entry() {
+        let mut ledger = vec![];
+        let mut removed = vec![];
+        let mut broken = new_download('a', Destination::Shows);
+        broken.extra_destinations = vec![Destination::Movies];
+        record_downloads(&mut ledger, vec![broken], 3).unwrap();
+        ledger[0].user_note = Some("for mum".to_string());
+        ledger[0].copy_state = CopyState::Failed;
+
+        // Added without a destination of its own
+        transfer_entry(&mut ledger, &mut removed, &hash('a'), &hash('b'), 100).unwrap();
+        assert_eq!(names_of(&ledger), ["Torrent a"]);
+        let replacement = &ledger[0];
+        assert_eq!(replacement.info_hash, hash('b'));
+        assert_eq!(replacement.destination, Destination::Shows);
+        assert_eq!(
+            replacement.extra_targets,
+            [CopyTarget::new(Destination::Movies)]
+        );
+        assert_eq!(replacement.copy_state, CopyState::NotCopied);
+        assert_eq!(replacement.profile, 3);
+        assert_eq!(replacement.user_note.as_deref(), Some("for mum"));
+        assert_eq!(removed[0].entry.info_hash, hash('a'));
+        assert_eq!(removed[0].removed_at, 100);
+
+        // Added to another destination under its own name
+        record_downloads(&mut ledger, vec![new_download('c', Destination::Movies)], 0).unwrap();
+        transfer_entry(&mut ledger, &mut removed, &hash('b'), &hash('c'), 200).unwrap();
+        assert_eq!(names_of(&ledger), ["Torrent c"]);
+        assert_eq!(ledger[0].destination, Destination::Shows);
+        assert_eq!(ledger[0].previous_names, ["Torrent a"]);
+        assert_eq!(removed.len(), 2);
+
+        let gone = transfer_entry(&mut ledger, &mut removed, &hash('a'), &hash('c'), 300);
+        assert!(matches!(gone, Err(ConfigError::DownloadNotInLedger { .. })));
+    }
+}
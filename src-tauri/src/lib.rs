@@ -1,18 +1,84 @@
 use piratebay::pirateclient::PirateClient;
 use privateer_wire_types::{
-    AppError, CopyState, Destination, DownloadEntry, Torrent, TorrentInfo, TransmissionConfig,
-    TransmissionStatus, TransmissionTorrent, WatchlistEntry,
+    format_duration, format_size, ActivitySummary, AddDownloadResult, AddOutcome, AppError,
+    AppInfo, AutoAssignPreview, ClientFeature, ClientKind, ConnectionStatus, CopyBackend,
+    CopyEngineStatus, CopyMetrics, CopyState, DaemonInfo, Destination, DestinationHealth,
+    DestinationUsage, DownloadEntry, DownloadLocation, DownloadOrigin, ExportFormat,
+    FeedPreviewItem, FeedSubscription, HealthReport, InfoHash, LedgerCounts, LedgerPage,
+    LedgerQuery, LogLevel, LogLine, MagnetLink, NewDownload, ProfileChoices, ReaddResult,
+    ReconcileSummary, RemovedDownload, RepairSummary, SearchQueuePlace, ServiceHealth,
+    SessionConfig, SettingsProblem, SizeUnits, TargetDir, Toast, Torrent, TorrentInfo,
+    TransmissionConfig, TransmissionStatus, TransmissionTorrent, UpdateInfo, WatchlistEntry,
+    REMOVED_KEEP_DAYS, UNHEALTHY_AFTER_CRASHES,
 };
-use std::path::PathBuf;
+use snafu::{OptionExt, ResultExt};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{Manager, State};
-use tokio::sync::{Mutex, Notify};
-use transmission_rpc::types::{BasicAuth, TorrentGetField};
-use transmission_rpc::TransClient;
-
+use std::time::{Duration, Instant};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, State, WindowEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{mpsc, oneshot, watch, Mutex, MutexGuard, Notify};
+
+mod auto_assign;
+mod availability;
+use availability::Availability;
+mod copier;
+use copier::{
+    Copier, CopyEvents, CopyStats, LedgerStore, SourceTorrent, TokioFs, TorrentFile, TorrentSource,
+};
+mod copy_schedule;
+use copy_schedule::{CopySchedule, CopyWake};
+mod data_paths;
+use data_paths::DataPaths;
+mod download_client;
+use download_client::{Client, ClientTorrent, DownloadClient};
 mod error;
 use error::*;
-use snafu::ResultExt;
+mod export;
+mod feeds;
+mod file_name;
+mod headless;
+mod http;
+use http::{HttpClient, HttpRequest, ReqwestClient};
+mod ledger;
+mod logging;
+use logging::Logging;
+mod media_server;
+use media_server::MediaScanner;
+mod metrics;
+use metrics::{tmp_path, MetricsFile, MetricsRecorder};
+#[cfg(test)]
+mod mock_transmission;
+mod mount;
+mod path_mapping;
+mod path_probe;
+mod post_copy;
+use post_copy::PostCopyHook;
+mod profiles;
+mod qbittorrent;
+mod rate_history;
+use rate_history::RateHistory;
+mod reachability;
+use reachability::{ReachabilityMonitor, Transition};
+mod release_name;
+mod relocation;
+mod request_gate;
+use request_gate::RequestGate;
+mod rsync;
+mod session;
+mod sftp;
+mod supervisor;
+mod torrent_errors;
+use torrent_errors::TorrentErrors;
+mod transmission;
+mod updates;
+mod webhook;
+use webhook::WebhookSender;
 
 // ---------------------------------------------------------------------------
 // App state
@@ -20,40 +86,124 @@ use snafu::ResultExt;
 
 struct App {
     client: PirateClient,
+    /// Throttles requests made with `client`.
+    search_gate: RequestGate,
     transmission_config: Mutex<TransmissionConfig>,
     config_path: PathBuf,
-    downloads_ledger: Mutex<Vec<DownloadEntry>>,
+    /// The ledger as last loaded or saved.  Its lock is held through every
+    /// read-modify-write of the ledger file, by the commands and the copy
+    /// task alike, see [`App::lock_ledger`] and [`LedgerFile`].
+    downloads_ledger: Arc<Mutex<Vec<DownloadEntry>>>,
     ledger_path: PathBuf,
+    /// Cumulative copy statistics, shared with the copy task.
+    copy_metrics: Arc<MetricsFile>,
     /// Signal the background copy task to wake up immediately.
     copy_notify: Arc<Notify>,
+    /// Checks the destinations for copies, sharing what it found with the
+    /// copy task.
+    fs: TokioFs,
+    /// Whether copying is paused, watched by the copy task.
+    copy_pause: watch::Sender<bool>,
+    /// Whether the copy task's cycles have been panicking, as it reports.
+    copy_engine: watch::Sender<CopyEngineStatus>,
+    /// Asks the copy task for a reconciliation pass between its cycles.
+    reconcile_requests: mpsc::Sender<ReconcileRequest>,
     watchlist: Mutex<Vec<WatchlistEntry>>,
     watchlist_path: PathBuf,
     next_watchlist_id: Mutex<u64>,
+    /// When each downloading torrent (by info hash) last had no peers
+    /// sending to us, kept across `get_torrents` calls for stall detection.
+    stalled_since: Mutex<HashMap<InfoHash, Instant>>,
+    /// Recent rates and peers of the downloading torrents, sampled by the
+    /// background torrents task.
+    rate_history: Mutex<RateHistory>,
+    /// Peers of the incomplete downloads over the last few days, sampled by
+    /// the background torrents task to spot dying torrents.
+    availability: Mutex<Availability>,
+    /// How many polls in a row each download has had an error, counted by
+    /// the background torrents task to spot those that need replacing.
+    torrent_errors: Mutex<TorrentErrors>,
+    /// Latest aggregate activity, kept current by the background status task.
+    activity: Mutex<ActivitySummary>,
+    /// Signal the background torrents task to poll immediately.
+    torrents_wake: Arc<Notify>,
+    /// The last torrent list fetched from the download client and when.
+    torrent_cache: Mutex<Option<(Instant, Vec<ClientTorrent>)>>,
+    /// Shared download client, created on first use. See
+    /// [`App::with_client`].
+    download_client: Mutex<Option<Client>>,
+    /// The download client last connected to and what it can do, learned
+    /// when connecting.
+    daemon: Mutex<Option<DaemonInfo>>,
+    /// Latest health report, refreshed by the background health task.
+    health: Mutex<Option<HealthReport>>,
+    /// How the torrent list polls have been going.
+    connection: Mutex<ConnectionStatus>,
+    /// Client for media servers.
+    http: ReqwestClient,
+    /// Client for the webhook, which gives up sooner.
+    webhook_http: ReqwestClient,
+    /// Magnet links opened from outside the app, waiting for the user to
+    /// pick a destination.
+    pending_magnets: Mutex<Vec<MagnetLink>>,
 }
 
 impl App {
-    fn new(config_path: PathBuf, ledger_path: PathBuf, watchlist_path: PathBuf) -> Self {
+    fn new(
+        config_path: PathBuf,
+        ledger_path: PathBuf,
+        metrics_path: PathBuf,
+        watchlist_path: PathBuf,
+        availability_path: PathBuf,
+        reconcile_requests: mpsc::Sender<ReconcileRequest>,
+    ) -> Self {
         let config = Self::load_config(&config_path);
-        let ledger = Self::load_ledger(&ledger_path);
+        let copy_pause = watch::Sender::new(config.copying_paused);
+        // Saves reload the file first, so one that can't be read is left
+        // alone rather than replaced by an empty ledger
+        let ledger = Self::load_ledger(&ledger_path).unwrap_or_else(|e| {
+            log::error!("{e}");
+            Vec::new()
+        });
         let watchlist: Vec<WatchlistEntry> = Self::load_json(&watchlist_path);
         let next_id = watchlist.iter().map(|e| e.id).max().unwrap_or(0) + 1;
         Self {
             client: PirateClient::new(),
+            search_gate: RequestGate::default(),
             transmission_config: Mutex::new(config),
             config_path,
-            downloads_ledger: Mutex::new(ledger),
+            downloads_ledger: Arc::new(Mutex::new(ledger)),
             ledger_path,
+            copy_metrics: Arc::new(MetricsFile::new(metrics_path)),
             copy_notify: Arc::new(Notify::new()),
+            fs: TokioFs::default(),
+            copy_pause,
+            copy_engine: watch::Sender::new(CopyEngineStatus::default()),
+            reconcile_requests,
             watchlist: Mutex::new(watchlist),
             watchlist_path,
             next_watchlist_id: Mutex::new(next_id),
+            stalled_since: Mutex::new(HashMap::new()),
+            activity: Mutex::new(ActivitySummary::default()),
+            rate_history: Mutex::new(RateHistory::default()),
+            availability: Mutex::new(Availability::load(availability_path)),
+            torrent_errors: Mutex::new(TorrentErrors::default()),
+            torrents_wake: Arc::new(Notify::new()),
+            torrent_cache: Mutex::new(None),
+            download_client: Mutex::new(None),
+            daemon: Mutex::new(None),
+            health: Mutex::new(None),
+            connection: Mutex::new(ConnectionStatus::default()),
+            http: ReqwestClient::default(),
+            webhook_http: ReqwestClient::with_timeout(webhook::TIMEOUT),
+            pending_magnets: Mutex::new(Vec::new()),
         }
     }
 
     fn load_config(path: &PathBuf) -> TransmissionConfig {
         if path.exists() {
             match std::fs::read_to_string(path) {
-                Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+                Ok(s) => profiles::parse(&s).unwrap_or_default(),
                 Err(_) => TransmissionConfig::default(),
             }
         } else {
@@ -74,30 +224,61 @@ impl App {
         Ok(())
     }
 
-    fn load_ledger(path: &PathBuf) -> Vec<DownloadEntry> {
-        if path.exists() {
-            match std::fs::read_to_string(path) {
-                Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
-                Err(_) => Vec::new(),
-            }
-        } else {
-            Vec::new()
-        }
+    /// The ledger in `path`, empty if there is no file yet.
+    fn load_ledger(path: &PathBuf) -> Result<Vec<DownloadEntry>, ConfigError> {
+        Self::load_entries(path)
+    }
+
+    /// The removed entries kept beside the ledger in `ledger_path`, empty if
+    /// there is no file yet.  As with the ledger, a file that can't be read
+    /// is an error, so it is never saved over with an empty list.
+    fn load_removed(ledger_path: &Path) -> Result<Vec<RemovedDownload>, ConfigError> {
+        Self::load_entries(&data_paths::removed_beside(ledger_path))
+    }
+
+    /// The list in `path`, empty if there is no file yet.
+    fn load_entries<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<Vec<T>, ConfigError> {
+        let s = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context(LedgerReadSnafu { path: path.clone() }),
+        };
+        serde_json::from_str(&s).context(LedgerParseSnafu { path: path.clone() })
     }
 
+    /// Save `ledger` to `path` through a temporary file, so a crash
+    /// mid-write leaves the previous ledger rather than a truncated one.
     fn save_ledger(path: &PathBuf, ledger: &[DownloadEntry]) -> Result<(), ConfigError> {
         if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).context(CreateDirSnafu {
-                path: parent.to_path_buf(),
+            std::fs::create_dir_all(parent).context(LedgerWriteSnafu {
+                path: path.to_path_buf(),
             })?;
         }
         let json = serde_json::to_string_pretty(ledger).context(SerializeSnafu)?;
-        std::fs::write(path, json).context(WriteFileSnafu {
+        let tmp = tmp_path(path);
+        std::fs::write(&tmp, json).context(LedgerWriteSnafu { path: &tmp })?;
+        std::fs::rename(&tmp, path).context(LedgerWriteSnafu {
             path: path.to_path_buf(),
         })?;
         Ok(())
     }
 
+    /// Lock the ledger, reloaded from disk, for a read-modify-write.  The
+    /// copy task saves under the same lock, so it can't save in between.
+    async fn lock_ledger(&self) -> Result<MutexGuard<'_, Vec<DownloadEntry>>, ConfigError> {
+        let mut ledger = self.downloads_ledger.lock().await;
+        *ledger = Self::load_ledger(&self.ledger_path)?;
+        Ok(ledger)
+    }
+
+    /// The ledger file for the copy task, sharing the commands' lock.
+    fn ledger_file(&self) -> LedgerFile {
+        LedgerFile {
+            path: self.ledger_path.clone(),
+            ledger: self.downloads_ledger.clone(),
+        }
+    }
+
     /// Generic JSON loader for any deserializable `Vec<T>`.
     fn load_json<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Vec<T> {
         if path.exists() {
@@ -110,6 +291,45 @@ impl App {
         }
     }
 
+    /// Run `f` with the shared download client, creating it from the
+    /// current config on first use.
+    ///
+    /// Reusing the client keeps Transmission's CSRF session id or
+    /// qBittorrent's session cookie, so only the first request after
+    /// connecting goes through the handshake.  A connection error (including
+    /// a timeout) drops the client so the next call reconnects.  Each new
+    /// client is asked for its version first, kept in `daemon`.
+    ///
+    /// Callers must not hold the `transmission_config` lock.
+    async fn with_client<T>(
+        &self,
+        f: impl for<'c> FnOnce(&'c mut Client) -> ClientFuture<'c, T>,
+    ) -> Result<T, ClientError> {
+        let mut client = self.download_client.lock().await;
+        if client.is_none() {
+            let mut connected = {
+                let config = self.transmission_config.lock().await;
+                Client::new(&config, config.rpc_timeout())?
+            };
+            match connected.daemon_info().await {
+                Ok(daemon) => *self.daemon.lock().await = Some(daemon),
+                Err(e) if e.is_connection() => return Err(e),
+                Err(e) => log::warn!("could not get the download client's version: {e}"),
+            }
+            *client = Some(connected);
+        }
+        let result = f(client.as_mut().expect("client was just created")).await;
+        if result.as_ref().is_err_and(ClientError::is_connection) {
+            *client = None;
+        }
+        result
+    }
+
+    /// Id of the server profile in use, recorded with new downloads.
+    async fn active_profile_id(&self) -> u32 {
+        self.transmission_config.lock().await.active().id
+    }
+
     /// Generic JSON saver for any serializable slice.
     fn save_json<T: serde::Serialize>(path: &PathBuf, data: &[T]) -> Result<(), ConfigError> {
         if let Some(parent) = path.parent() {
@@ -125,46 +345,49 @@ impl App {
     }
 }
 
+#[cfg(test)]
+impl App {
+    /// An app keeping its files in `dir` under their usual names, for
+    /// tests.  Reconcile requests go nowhere.
+    fn in_dir(dir: &Path) -> Self {
+        let paths = DataPaths::new(dir.to_path_buf());
+        let (reconcile_requests, _) = mpsc::channel(1);
+        Self::new(
+            paths.config.clone(),
+            paths.ledger.clone(),
+            paths.metrics(),
+            paths.watchlist(),
+            paths.availability(),
+            reconcile_requests,
+        )
+    }
+}
+
 // ---------------------------------------------------------------------------
-// Transmission helpers
+// Download client helpers
 // ---------------------------------------------------------------------------
 
-fn make_trans_client(config: &TransmissionConfig) -> Result<TransClient, TransmissionError> {
-    let url_str = format!("http://{}:{}/transmission/rpc", config.host, config.port);
-    let url: url::Url = url_str.parse().context(InvalidUrlSnafu {
-        url: url_str.clone(),
-    })?;
-
-    let client = if let (Some(user), Some(password)) = (&config.username, &config.password) {
-        if !user.is_empty() {
-            TransClient::with_auth(
-                url,
-                BasicAuth {
-                    user: user.clone(),
-                    password: password.clone(),
-                },
-            )
-        } else {
-            TransClient::new(url)
-        }
-    } else {
-        TransClient::new(url)
-    };
-
-    Ok(client)
+/// Future returned by the closure passed to [`App::with_client`].
+type ClientFuture<'c, T> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, ClientError>> + Send + 'c>>;
+
+/// Whether two configs point at the same daemon with the same credentials.
+fn same_connection(a: &TransmissionConfig, b: &TransmissionConfig) -> bool {
+    let (a, b) = (a.active(), b.active());
+    a.id == b.id
+        && a.client == b.client
+        && a.host == b.host
+        && a.port == b.port
+        && a.username == b.username
+        && a.password == b.password
 }
 
-fn transmission_status(status: i64) -> TransmissionStatus {
-    match status {
-        0 => TransmissionStatus::Stopped,
-        1 => TransmissionStatus::QueuedVerify,
-        2 => TransmissionStatus::Verifying,
-        3 => TransmissionStatus::QueuedDownload,
-        4 => TransmissionStatus::Downloading,
-        5 => TransmissionStatus::QueuedSeed,
-        6 => TransmissionStatus::Seeding,
-        _ => TransmissionStatus::Stopped,
-    }
+/// Seconds since the Unix epoch.
+pub(crate) fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
 }
 
 // ---------------------------------------------------------------------------
@@ -195,7 +418,7 @@ fn pb_torrent_to_wire(pb_t: piratebay::types::Torrent) -> Torrent {
         magnet,
     } = pb_t;
 
-    Torrent {
+    let mut torrent = Torrent {
         added,
         category,
         descr,
@@ -210,7 +433,10 @@ fn pb_torrent_to_wire(pb_t: piratebay::types::Torrent) -> Torrent {
         status,
         username,
         magnet,
-    }
+        health: None,
+    };
+    torrent.health = torrent.health_at(unix_now());
+    torrent
 }
 
 fn pb_torrent_info_to_wire(pb_ti: piratebay::types::TorrentInfo) -> TorrentInfo {
@@ -252,10 +478,32 @@ fn pb_torrent_info_to_wire(pb_ti: piratebay::types::TorrentInfo) -> TorrentInfo
 // Tauri commands – Privateer
 // ---------------------------------------------------------------------------
 
+/// Backend event carrying a search API request's place in the queue.
+const SEARCH_QUEUE: &str = "search-queue";
+
+fn emit_search_queue(app: &AppHandle, request: &str, place: usize) {
+    let place = SearchQueuePlace {
+        request: request.to_string(),
+        place,
+    };
+    if let Err(e) = app.emit(SEARCH_QUEUE, place) {
+        log::warn!("Could not emit search queue: {e}");
+    }
+}
+
 #[tauri::command]
-async fn search(state: State<'_, App>, query: &str) -> Result<Vec<Torrent>, AppError> {
+async fn search(
+    app: AppHandle,
+    state: State<'_, App>,
+    query: &str,
+) -> Result<Vec<Torrent>, AppError> {
     log::info!("searching: {query}");
-    let torrents = state.client.search(query).await.map_err(|e| {
+    let request = state.client.search(query);
+    let result = state
+        .search_gate
+        .run(request, |place| emit_search_queue(&app, query, place))
+        .await?;
+    let torrents = result.map_err(|e| {
         log::error!("{e}");
         PirateError::Search {
             message: e.to_string(),
@@ -266,129 +514,391 @@ async fn search(state: State<'_, App>, query: &str) -> Result<Vec<Torrent>, AppE
         .into_iter()
         .map(pb_torrent_to_wire)
         .collect::<Vec<_>>();
+    let mut unparsed = 0;
+    for torrent in torrents.iter() {
+        let fields = torrent.unparsed_fields();
+        if !fields.is_empty() {
+            log::debug!("'{}' has unknown {}", torrent.name, fields.join(", "));
+            unparsed += 1;
+        }
+    }
+    if unparsed > 0 {
+        log::warn!("{unparsed} results have values that couldn't be parsed");
+    }
     Ok(torrents)
 }
 
 #[tauri::command]
-async fn info(state: State<'_, App>, id: &str) -> Result<TorrentInfo, AppError> {
+async fn info(app: AppHandle, state: State<'_, App>, id: &str) -> Result<TorrentInfo, AppError> {
     log::info!("info: {id}");
-    let torrent = state
-        .client
-        .get_info(id)
-        .await
-        .map_err(|e| PirateError::Info {
-            message: e.to_string(),
-        })?;
-    Ok(pb_torrent_info_to_wire(torrent))
+    let request = state.client.get_info(id);
+    let result = state
+        .search_gate
+        .run(request, |place| emit_search_queue(&app, id, place))
+        .await?;
+    let torrent = result.map_err(|e| PirateError::Info {
+        message: e.to_string(),
+    })?;
+    let info = pb_torrent_info_to_wire(torrent);
+    if info.is_gone() {
+        return Err(PirateError::InfoNotFound { id: id.to_string() }.into());
+    }
+    Ok(info)
 }
 
 // ---------------------------------------------------------------------------
 // Tauri commands – Transmission config
 // ---------------------------------------------------------------------------
 
+/// Whether no config has been saved yet, so the first-run setup should be
+/// shown.  Saving the config, or skipping setup, writes one.
+#[tauri::command]
+fn needs_setup(state: State<'_, App>) -> bool {
+    !state.config_path.exists()
+}
+
 #[tauri::command]
 async fn get_transmission_config(state: State<'_, App>) -> Result<TransmissionConfig, AppError> {
     let config = state.transmission_config.lock().await;
-    Ok(config.clone())
+    Ok(config.clone().masked())
 }
 
 #[tauri::command]
 async fn set_transmission_config(
     state: State<'_, App>,
-    config: TransmissionConfig,
+    mut config: TransmissionConfig,
 ) -> Result<(), AppError> {
+    config.validate().context(InvalidSettingSnafu)?;
+    // Symlinks can make different paths the same directory
+    if let Err(source) = copier::check_destination_dirs(&config) {
+        if source.is_error() {
+            return Err(ConfigError::InvalidSetting { source }.into());
+        }
+    }
+    {
+        let current = state.transmission_config.lock().await;
+        config.keep_saved_password(&current);
+        // Pausing is up to the Downloads tab, not the form
+        config.copying_paused = current.copying_paused;
+    }
+    path_mapping::validate(&config.path_mappings)?;
+    feeds::validate(&config.subscriptions)?;
+    if config.copy_with_rsync {
+        rsync::check_available().await?;
+    }
     App::save_config(&state.config_path, &config)?;
-    let mut current = state.transmission_config.lock().await;
-    *current = config;
+    let reconnect = {
+        let mut current = state.transmission_config.lock().await;
+        let reconnect = !same_connection(&current, &config);
+        *current = config;
+        reconnect
+    };
+    if reconnect {
+        disconnect(&state).await;
+    } else {
+        // Run a cycle now, so changed copy settings apply straight away
+        state.copy_notify.notify_one();
+    }
     Ok(())
 }
 
+/// Forget everything learned from the daemon after switching to another,
+/// and poll the new one straight away.
+async fn disconnect(state: &App) {
+    *state.download_client.lock().await = None;
+    *state.daemon.lock().await = None;
+    // The cached list may be from a different daemon
+    *state.torrent_cache.lock().await = None;
+    *state.connection.lock().await = ConnectionStatus::default();
+    state.stalled_since.lock().await.clear();
+    state.torrents_wake.notify_one();
+    state.copy_notify.notify_one();
+}
+
 #[tauri::command]
-async fn test_transmission_connection(state: State<'_, App>) -> Result<String, AppError> {
-    let config = state.transmission_config.lock().await;
-    let mut client = make_trans_client(&config)?;
-    let response = client
-        .session_get()
-        .await
-        .map_err(|e| TransmissionError::Connection {
-            message: e.to_string(),
-        })?;
-    if response.is_ok() {
-        let version = if response.arguments.version.is_empty() {
-            "unknown".to_string()
-        } else {
-            response.arguments.version
-        };
-        Ok(format!("Connected to Transmission {version}"))
+async fn get_profiles(state: State<'_, App>) -> Result<ProfileChoices, AppError> {
+    Ok(state.transmission_config.lock().await.profile_choices())
+}
+
+/// Switch to another server profile from the navbar.
+#[tauri::command]
+async fn set_active_profile(state: State<'_, App>, index: usize) -> Result<(), AppError> {
+    let switched = {
+        let mut config = state.transmission_config.lock().await;
+        if index >= config.profiles.len() {
+            let source = SettingsProblem::NoSuchProfile;
+            return Err(ConfigError::InvalidSetting { source }.into());
+        }
+        let switched = config.active_profile != index;
+        config.active_profile = index;
+        App::save_config(&state.config_path, &config)?;
+        switched
+    };
+    if switched {
+        log::info!("switched to server profile {index}");
+        disconnect(&state).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn test_transmission_connection(state: State<'_, App>) -> Result<DaemonInfo, AppError> {
+    let daemon = state
+        .with_client(|client| Box::pin(client.daemon_info()))
+        .await?;
+    *state.daemon.lock().await = Some(daemon.clone());
+    Ok(daemon)
+}
+
+/// The download client last connected to, if any.
+#[tauri::command]
+async fn get_daemon_info(state: State<'_, App>) -> Result<Option<DaemonInfo>, AppError> {
+    Ok(state.daemon.lock().await.clone())
+}
+
+/// Fail with `TooOld` if the download client is known to lack `feature`,
+/// rather than with whatever error the daemon would give.
+async fn require_feature(state: &App, feature: ClientFeature) -> Result<(), TransmissionError> {
+    match &*state.daemon.lock().await {
+        Some(daemon) if !daemon.capabilities.has(feature) => TooOldSnafu {
+            reason: feature.unavailable_reason(),
+            version: daemon.version.clone(),
+        }
+        .fail(),
+        _ => Ok(()),
+    }
+}
+
+/// Free space in the download client's download directory, checked before
+/// adding a torrent.
+#[tauri::command]
+async fn get_free_space(state: State<'_, App>) -> Result<u64, AppError> {
+    require_feature(&state, ClientFeature::FreeSpace).await?;
+    Ok(state
+        .with_client(|client| Box::pin(client.free_space()))
+        .await?)
+}
+
+/// Transmission's own preferences edited from Settings.  See [`session`].
+#[tauri::command]
+async fn get_session_config(state: State<'_, App>) -> Result<SessionConfig, AppError> {
+    let config = state.transmission_config.lock().await.clone();
+    transmission_only(&config, "Daemon preferences")?;
+    let arguments = session::call(&config, &session::get_request()).await?;
+    Ok(session::parse_session(&arguments))
+}
+
+#[tauri::command]
+async fn set_session_config(state: State<'_, App>, session: SessionConfig) -> Result<(), AppError> {
+    session.validate().context(InvalidSettingSnafu)?;
+    let config = state.transmission_config.lock().await.clone();
+    transmission_only(&config, "Daemon preferences")?;
+    session::call(&config, &session::set_request(&session)).await?;
+    Ok(())
+}
+
+/// Fail with `NotConfirmed` unless a destructive command was confirmed.
+/// The frontend confirms once the user has had a few seconds to undo, so
+/// a stray call can't destroy anything.  A missing `confirm` is no
+/// confirmation, as in [`ConfirmArgs`](privateer_wire_types::ConfirmArgs).
+///
+/// Commands whose work can be undone afterwards, like `remove_download`
+/// and `transfer_ledger_entry`, which keep the old entry among the
+/// recently removed, don't ask.
+fn confirmed(confirm: Option<bool>, what: &str) -> Result<(), ConfigError> {
+    if confirm.unwrap_or_default() {
+        Ok(())
     } else {
-        Err(AppError::from(TransmissionError::Rpc {
-            message: response.result,
-        }))
+        NotConfirmedSnafu { what }.fail()
+    }
+}
+
+/// Fail with `TransmissionOnly` unless the active profile is Transmission.
+fn transmission_only(config: &TransmissionConfig, what: &str) -> Result<(), QBittorrentError> {
+    match config.active().client {
+        ClientKind::Transmission => Ok(()),
+        ClientKind::QBittorrent => TransmissionOnlySnafu { what }.fail(),
     }
 }
 
+/// Dry-run `subscription` against its feed's current items.
+#[tauri::command]
+async fn preview_subscription(
+    state: State<'_, App>,
+    subscription: FeedSubscription,
+) -> Result<Vec<FeedPreviewItem>, AppError> {
+    feeds::preview(&state.http, &subscription).await
+}
+
+#[tauri::command]
+async fn test_media_server(state: State<'_, App>) -> Result<String, AppError> {
+    let config = state.transmission_config.lock().await.media_server.clone();
+    let config = config.context(MediaServerNotConfiguredSnafu)?;
+    Ok(media_server::test(&state.http, &config).await?)
+}
+
+#[tauri::command]
+async fn send_test_webhook(state: State<'_, App>) -> Result<String, AppError> {
+    let config = state.transmission_config.lock().await.webhook.clone();
+    let config = config.context(WebhookNotConfiguredSnafu)?;
+    let status = webhook::send_test(&state.webhook_http, &config).await?;
+    Ok(format!("Webhook responded with HTTP {status}."))
+}
+
+/// Run the post-copy command for a made-up download, as after a copy.
+#[tauri::command]
+async fn test_post_copy_hook(state: State<'_, App>) -> Result<String, AppError> {
+    let config = state.transmission_config.lock().await.clone();
+    Ok(post_copy::test(&config).await?)
+}
+
 // ---------------------------------------------------------------------------
 // Tauri commands – Torrents & ledger
 // ---------------------------------------------------------------------------
 
+/// How long a fetched torrent list is served from the cache.
+const TORRENT_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Get all torrents.  A list fetched within the last couple of seconds is
+/// reused unless `force` is set, e.g. right after a pause or resume.
 #[tauri::command]
-async fn get_torrents(state: State<'_, App>) -> Result<Vec<TransmissionTorrent>, AppError> {
-    let config = state.transmission_config.lock().await;
-    let mut client = make_trans_client(&config)?;
-
-    let fields = vec![
-        TorrentGetField::Id,
-        TorrentGetField::Name,
-        TorrentGetField::HashString,
-        TorrentGetField::Status,
-        TorrentGetField::PercentDone,
-        TorrentGetField::RateDownload,
-        TorrentGetField::RateUpload,
-        TorrentGetField::Eta,
-        TorrentGetField::SizeWhenDone,
-        TorrentGetField::PeersConnected,
-        TorrentGetField::PeersSendingToUs,
-        TorrentGetField::PeersGettingFromUs,
-        TorrentGetField::Error,
-        TorrentGetField::ErrorString,
-        TorrentGetField::DownloadDir,
-    ];
-
-    let response = client.torrent_get(Some(fields), None).await.map_err(|e| {
-        TransmissionError::Connection {
-            message: e.to_string(),
+async fn get_torrents(
+    state: State<'_, App>,
+    force: bool,
+) -> Result<Vec<TransmissionTorrent>, AppError> {
+    fetch_torrents(&state, force).await
+}
+
+/// Fetch the raw torrent list from the download client, or from the cache
+/// if it is fresh enough.
+///
+/// The cache lock is held across the request so concurrent callers share
+/// one fetch rather than racing each other.
+async fn fetch_raw_torrents(state: &App, force: bool) -> Result<Vec<ClientTorrent>, ClientError> {
+    let mut cache = state.torrent_cache.lock().await;
+    if let Some((fetched_at, torrents)) = cache.as_ref() {
+        if !force && fetched_at.elapsed() < TORRENT_CACHE_TTL {
+            return Ok(torrents.clone());
         }
-    })?;
+    }
 
-    if !response.is_ok() {
-        return Err(AppError::from(TransmissionError::Rpc {
-            message: response.result,
-        }));
+    let result = state
+        .with_client(|client| Box::pin(client.torrents()))
+        .await;
+    state.connection.lock().await.record(
+        result.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        unix_now(),
+    );
+    let torrents = result?;
+    *cache = Some((Instant::now(), torrents.clone()));
+    Ok(torrents)
+}
+
+/// The destination a torrent's files were found at, when the ledger doesn't
+/// already say where they are: its own destination for a download that isn't
+/// copied yet, or either one for a torrent the ledger doesn't track.
+async fn find_copy(
+    fs: &TokioFs,
+    config: &TransmissionConfig,
+    entry: Option<&DownloadEntry>,
+    name: &str,
+) -> Option<Destination> {
+    match entry {
+        Some(entry) if entry.copy_state.is_pending() => {
+            copier::check_already_copied(fs, config, entry, name)
+                .await
+                .then_some(entry.destination)
+        }
+        Some(_) => None,
+        None if config.only_assigned => None,
+        None => copier::detect_destination(fs, config, name)
+            .await
+            .map(|(dest, _)| dest),
     }
+}
+
+/// Fetch all torrents and cross-reference them with the ledger.  Shared by
+/// `get_torrents` and the background torrents task.
+///
+/// The ledger cross-reference is recomputed even when the raw list comes
+/// from the cache.
+async fn fetch_torrents(state: &App, force: bool) -> Result<Vec<TransmissionTorrent>, AppError> {
+    let raw_torrents = fetch_raw_torrents(state, force).await?;
+    let config = Arc::new(state.transmission_config.lock().await.clone());
 
     let ledger = state.downloads_ledger.lock().await;
+    // An unreachable share makes each check wait out the probe's timeout, so
+    // every torrent is checked at once
+    let mut checks = tokio::task::JoinSet::new();
+    for (index, t) in raw_torrents.iter().enumerate() {
+        let entry = ledger
+            .iter()
+            .find(|e| t.hash.as_ref() == Some(&e.info_hash))
+            .cloned();
+        let (fs, config, name) = (state.fs.clone(), config.clone(), t.name.clone());
+        checks.spawn(async move {
+            let found = find_copy(&fs, &config, entry.as_ref(), &name).await;
+            (index, found)
+        });
+    }
+    let mut found_copies = vec![None; raw_torrents.len()];
+    while let Some(check) = checks.join_next().await {
+        if let Ok((index, found)) = check {
+            found_copies[index] = found;
+        }
+    }
 
-    let torrents = response
-        .arguments
-        .torrents
+    let mut stalled_since = state.stalled_since.lock().await;
+    let stall_timeout = Duration::from_secs(config.stall_minutes as u64 * 60);
+    let now = Instant::now();
+    let quiet_until = config.quiet_until(copier::local_time());
+    // Forget torrents that are no longer in the client
+    stalled_since.retain(|hash, _| raw_torrents.iter().any(|t| t.hash.as_ref() == Some(hash)));
+
+    let mut torrents = raw_torrents
         .into_iter()
-        .map(|t| {
-            let hash_string = t.hash_string.clone().unwrap_or_default();
+        .zip(found_copies)
+        .filter_map(|(t, found_copy)| {
+            let Some(hash_string) = t.hash.clone() else {
+                log::warn!("the client reported a torrent without a valid info hash");
+                return None;
+            };
             let download_dir = t.download_dir.clone();
-            let name = t.name.clone().unwrap_or_default();
+            let name = t.name.clone();
+            // Files found at a destination only count as copied when they
+            // aren't the download itself, mounted there via a path mapping.
+            let source = download_dir
+                .as_deref()
+                .map(|dir| path_mapping::to_local(&config.path_mappings, dir).join(&name));
+            let is_source = |dest: Destination| {
+                source.is_some()
+                    && config
+                        .dir_for(dest)
+                        .map(|dir| PathBuf::from(dir).join(&name))
+                        == source
+            };
 
             // Cross-reference with the ledger
-            let ledger_entry = ledger
-                .iter()
-                .find(|e| e.info_hash.eq_ignore_ascii_case(&hash_string));
-
-            let (destination, copy_state) = match ledger_entry {
+            let ledger_entry = ledger.iter().find(|e| e.info_hash == hash_string);
+
+            let copy_note = ledger_entry.and_then(|entry| entry.note.clone());
+            let user_note = ledger_entry.and_then(|entry| entry.user_note.clone());
+            let copy_failure = ledger_entry.and_then(|entry| {
+                entry
+                    .targets()
+                    .into_iter()
+                    .find_map(|target| target.failure)
+            });
+            let copy_throughput = ledger_entry.and_then(DownloadEntry::copy_throughput);
+            let copy_now = ledger_entry.is_some_and(|entry| entry.copy_now);
+            let (upload_ratio, seconds_seeding) = (t.upload_ratio, t.seconds_seeding);
+            let (destination, copy_state, origin) = match ledger_entry {
                 Some(entry) => {
                     let state = match entry.copy_state {
                         // If not yet copied, check whether it already exists
                         // at the destination (e.g. manually copied).
-                        CopyState::NotCopied | CopyState::Failed => {
-                            if check_already_copied(&config, entry.destination, &name) {
+                        state if state.is_pending() => {
+                            if found_copy.is_some_and(|dest| !is_source(dest)) {
                                 CopyState::Copied
                             } else {
                                 entry.copy_state
@@ -396,139 +906,860 @@ async fn get_torrents(state: State<'_, App>) -> Result<Vec<TransmissionTorrent>,
                         }
                         other => other,
                     };
-                    (Some(entry.destination), state)
+                    (Some(entry.destination), state, Some(entry.origin))
                 }
+                None if config.only_assigned => (None, CopyState::default(), None),
                 None => {
                     // Not in ledger — check whether the torrent's files
                     // already exist at either destination directory.
-                    match detect_destination(&config, &name) {
-                        Some((dest, state)) => (Some(dest), state),
-                        None => (None, CopyState::default()),
+                    match found_copy.filter(|dest| !is_source(*dest)) {
+                        Some(dest) => (
+                            Some(dest),
+                            CopyState::Copied,
+                            Some(DownloadOrigin::AutoDetected),
+                        ),
+                        None => (None, CopyState::default(), None),
                     }
                 }
             };
 
-            TransmissionTorrent {
-                id: t.id.unwrap_or(-1),
+            // A download with more than one destination is copied when
+            // every copy is
+            let targets = match ledger_entry {
+                Some(entry) if !entry.extra_targets.is_empty() => {
+                    let mut targets = entry.targets();
+                    targets[0].copy_state = copy_state;
+                    targets
+                }
+                _ => vec![],
+            };
+            let copy_state = match targets.as_slice() {
+                [] => copy_state,
+                targets => CopyState::combine(targets.iter().map(|target| target.copy_state)),
+            };
+
+            // The copy task stops the torrent once the goal is met
+            let seed_goal_met = ledger_entry.is_some_and(|entry| {
+                entry.seeding_completed
+                    || (copy_state == CopyState::Copied
+                        && config
+                            .seed_limits(entry.destination)
+                            .is_met(upload_ratio, seconds_seeding))
+            });
+
+            let copy_blocked_reason = destination
+                .filter(|_| copy_state.is_pending())
+                .and_then(|dest| config.copy_blocked_reason(dest));
+
+            let copy_deferred_until = quiet_until.filter(|_| !copy_now && copy_state.is_pending());
+            // Finished and waiting only for the copy task's next cycle
+            let copy_pending = copy_state == CopyState::NotCopied
+                && destination.is_some()
+                && copy_blocked_reason.is_none()
+                && copy_deferred_until.is_none()
+                && !config.copying_paused
+                && copier::is_ready(t.percent_done as f32, t.status);
+
+            let (status, peers_sending_to_us) = (t.status, t.peers_sending_to_us);
+            let stalled = if status == TransmissionStatus::Downloading && peers_sending_to_us == 0 {
+                let since = stalled_since.entry(hash_string.clone()).or_insert(now);
+                now.duration_since(*since) >= stall_timeout
+            } else {
+                stalled_since.remove(&hash_string);
+                false
+            };
+
+            Some(TransmissionTorrent {
+                id: t.id,
                 name,
                 hash_string,
-                status: transmission_status(t.status.map(|s| s as i64).unwrap_or(0)),
-                percent_done: t.percent_done.unwrap_or(0.0) as f64,
-                rate_download: t.rate_download.unwrap_or(0),
-                rate_upload: t.rate_upload.unwrap_or(0),
-                eta: t.eta.unwrap_or(-1),
-                size_when_done: t.size_when_done.unwrap_or(0),
-                peers_connected: t.peers_connected.unwrap_or(0),
-                peers_sending_to_us: t.peers_sending_to_us.unwrap_or(0),
-                peers_getting_from_us: t.peers_getting_from_us.unwrap_or(0),
-                error: t.error.map(|e| e as i64).unwrap_or(0),
-                error_string: t.error_string.unwrap_or_default(),
+                status,
+                percent_done: t.percent_done,
+                recheck_progress: t.recheck_progress,
+                rate_download: t.rate_download,
+                rate_upload: t.rate_upload,
+                eta: t.eta,
+                upload_ratio,
+                uploaded_ever: t.uploaded_ever,
+                seconds_seeding,
+                added_date: t.added_date,
+                done_date: t.done_date,
+                is_finished: t.is_finished,
+                size_when_done: t.size_when_done,
+                peers_connected: t.peers_connected,
+                peers_sending_to_us,
+                peers_getting_from_us: t.peers_getting_from_us,
+                stalled,
+                error: t.error,
+                error_string: t.error_string,
                 download_dir,
                 destination,
+                origin,
                 copy_state,
-            }
+                targets,
+                copy_note,
+                user_note,
+                copy_failure,
+                copy_throughput,
+                copy_deferred_until,
+                copy_pending,
+                seed_goal_met,
+                copy_blocked_reason,
+                history: vec![],
+                unavailable_since: None,
+                likely_dead: false,
+                needs_replacement: false,
+                search_query: None,
+            })
         })
-        .collect();
+        .collect::<Vec<_>>();
 
+    state.rate_history.lock().await.attach(&mut torrents);
+    state
+        .availability
+        .lock()
+        .await
+        .attach(&mut torrents, unix_now(), config.dead_after_hours);
+    state.torrent_errors.lock().await.attach(&mut torrents);
     Ok(torrents)
 }
 
-/// Check whether a torrent's files already exist at the destination.
-fn check_already_copied(config: &TransmissionConfig, dest: Destination, name: &str) -> bool {
-    if let Some(dir) = config.dir_for(dest) {
-        let dest_path = PathBuf::from(dir).join(name);
-        dest_path.exists()
-    } else {
-        false
+/// Takes [`AddDownloadArgs`](privateer_wire_types::AddDownloadArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn add_download(
+    state: State<'_, App>,
+    info_hash: InfoHash,
+    name: String,
+    destination: Destination,
+    extra_destinations: Vec<Destination>,
+    extra_dirs: Vec<TargetDir>,
+) -> Result<(), AppError> {
+    let download = NewDownload {
+        info_hash,
+        name,
+        destination,
+        extra_destinations,
+        extra_dirs,
+    };
+    add_downloads(state, vec![download]).await?;
+    Ok(())
+}
+
+/// Record torrents in the ledger under one lock and one save, and wake the
+/// copy task once, returning what happened to each.
+#[tauri::command]
+async fn add_downloads(
+    state: State<'_, App>,
+    downloads: Vec<NewDownload>,
+) -> Result<Vec<AddDownloadResult>, AppError> {
+    log::info!("adding {} downloads to downloads.json...", downloads.len());
+    let profile = state.active_profile_id().await;
+    let mut ledger = state.lock_ledger().await?;
+    let results = ledger::record_downloads(&mut ledger, downloads, profile)?;
+    if results.iter().any(|r| r.outcome != AddOutcome::Unchanged) {
+        App::save_ledger(&state.ledger_path, &ledger)?;
+        // Wake the background copy task so it picks up these entries
+        // immediately instead of waiting for the next 30-second cycle.
+        state.copy_notify.notify_one();
     }
+    log::info!("...done.");
+    Ok(results)
 }
 
-/// Detect whether a torrent already exists at either destination directory.
+/// Propose destinations for the torrents in the download client that aren't
+/// in the ledger, for the user to confirm with `add_downloads`.
 ///
-/// Checks `movies_dir` first, then `shows_dir`. Returns the destination
-/// and `CopyState::Copied` if the torrent's files are found on disk,
-/// or `None` if the torrent doesn't exist at either location.
-fn detect_destination(config: &TransmissionConfig, name: &str) -> Option<(Destination, CopyState)> {
-    for dest in [Destination::Movies, Destination::Shows] {
-        if let Some(dir) = config.dir_for(dest) {
-            if !dir.is_empty() {
-                let path = PathBuf::from(dir).join(name);
-                if path.exists() {
-                    return Some((dest, CopyState::Copied));
-                }
-            }
-        }
-    }
-    None
+/// Takes [`AutoAssignArgs`](privateer_wire_types::AutoAssignArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn preview_auto_assign(
+    state: State<'_, App>,
+    name_filter: Option<String>,
+) -> Result<AutoAssignPreview, AppError> {
+    let torrents = fetch_raw_torrents(&state, true).await?;
+    let ledger = state.downloads_ledger.lock().await;
+    let preview = auto_assign::propose(&torrents, &ledger, name_filter.as_deref());
+    log::info!(
+        "auto-assign would assign {} torrents and leave {} unassigned",
+        preview.assignments.len(),
+        preview.ambiguous.len()
+    );
+    Ok(preview)
+}
+
+/// Add a torrent to the download client by magnet link or `.torrent` URL,
+/// returning its info hash.
+async fn torrent_add(state: &App, filename: String) -> Result<InfoHash, ClientError> {
+    state
+        .with_client(|client| Box::pin(client.add(filename)))
+        .await
+}
+
+/// Record a new download in the ledger and wake the tasks that act on it.
+async fn track_download(
+    state: &App,
+    info_hash: InfoHash,
+    name: String,
+    destination: Destination,
+    magnet: Option<String>,
+) -> Result<(), ConfigError> {
+    let profile = state.active_profile_id().await;
+    let mut ledger = state.lock_ledger().await?;
+    ledger::record_download(
+        &mut ledger,
+        info_hash,
+        name,
+        destination,
+        &[],
+        magnet,
+        profile,
+    );
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    state.copy_notify.notify_one();
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Add a magnet link to Transmission and record it in the ledger, so it is
+/// copied to `destination` once it completes.
+async fn add_magnet_link(
+    state: &App,
+    magnet: MagnetLink,
+    destination: Destination,
+) -> Result<(), AppError> {
+    log::info!("adding magnet '{}' for {destination}...", magnet.name);
+    torrent_add(state, magnet.uri.clone()).await?;
+    state
+        .pending_magnets
+        .lock()
+        .await
+        .retain(|m| m.info_hash != magnet.info_hash);
+    track_download(
+        state,
+        magnet.info_hash,
+        magnet.name,
+        destination,
+        Some(magnet.uri),
+    )
+    .await?;
+    log::info!("...done.");
+    Ok(())
 }
 
 #[tauri::command]
-async fn add_download(
+async fn add_magnet(
     state: State<'_, App>,
-    info_hash: String,
-    name: String,
+    magnet: MagnetLink,
     destination: Destination,
 ) -> Result<(), AppError> {
-    log::info!("adding download '{name}' to downloads.json...");
-    let mut ledger = state.downloads_ledger.lock().await;
+    add_magnet_link(&state, magnet, destination).await
+}
 
-    // Check if already tracked
-    if let Some(entry) = ledger
-        .iter_mut()
-        .find(|e| e.info_hash.eq_ignore_ascii_case(&info_hash))
-    {
-        // Update destination if changed
-        entry.destination = destination;
-        entry.copy_state = CopyState::NotCopied;
-    } else {
-        ledger.push(DownloadEntry {
-            info_hash,
-            name,
-            destination,
-            copy_state: CopyState::NotCopied,
+/// Magnet links opened from outside the app that are waiting for a
+/// destination.
+#[tauri::command]
+async fn get_pending_magnets(state: State<'_, App>) -> Result<Vec<MagnetLink>, AppError> {
+    Ok(state.pending_magnets.lock().await.clone())
+}
+
+/// Forget an opened magnet link without adding it.
+///
+/// Takes [`InfoHashArgs`](privateer_wire_types::InfoHashArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn dismiss_magnet(state: State<'_, App>, info_hash: InfoHash) -> Result<(), AppError> {
+    state
+        .pending_magnets
+        .lock()
+        .await
+        .retain(|m| m.info_hash != info_hash);
+    Ok(())
+}
+
+/// Copy the given torrents as soon as they are complete, even during quiet
+/// hours, retrying failed copies.
+#[tauri::command]
+async fn copy_now(state: State<'_, App>, hashes: Vec<InfoHash>) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    let mut marked = 0;
+    for entry in ledger.iter_mut() {
+        if !hashes.contains(&entry.info_hash) {
+            continue;
+        }
+        let mut is_pending = false;
+        entry.update_targets(|target| {
+            if target.copy_state.is_pending() {
+                target.copy_state = CopyState::NotCopied;
+                target.failure = None;
+                is_pending = true;
+            }
         });
+        if is_pending {
+            entry.copy_now = true;
+            marked += 1;
+        }
     }
-
     App::save_ledger(&state.ledger_path, &ledger)?;
-    // Wake the background copy task so it picks up this entry immediately
-    // instead of waiting for the next 30-second cycle.
     state.copy_notify.notify_one();
-    log::info!("...done.");
+    log::info!("Copying {marked} downloads now");
     Ok(())
 }
 
+/// Start no new copies until [`resume_copying`], even after a restart.  A
+/// copy in progress suspends after its current file.
 #[tauri::command]
-async fn get_downloads_ledger(state: State<'_, App>) -> Result<Vec<DownloadEntry>, AppError> {
-    let ledger = state.downloads_ledger.lock().await;
-    Ok(ledger.clone())
+async fn pause_copying(state: State<'_, App>) -> Result<(), AppError> {
+    set_copying_paused(&state, true).await
 }
 
-// ---------------------------------------------------------------------------
-// Tauri commands – Watchlist
-// ---------------------------------------------------------------------------
+/// Let the copy task copy again, picking up a suspended copy where it left
+/// off.
+#[tauri::command]
+async fn resume_copying(state: State<'_, App>) -> Result<(), AppError> {
+    set_copying_paused(&state, false).await?;
+    state.copy_notify.notify_one();
+    Ok(())
+}
 
 #[tauri::command]
-async fn get_watchlist(state: State<'_, App>) -> Result<Vec<WatchlistEntry>, AppError> {
-    let watchlist = state.watchlist.lock().await;
-    Ok(watchlist.clone())
+async fn is_copying_paused(state: State<'_, App>) -> Result<bool, AppError> {
+    Ok(state.transmission_config.lock().await.copying_paused)
+}
+
+/// Save whether copying is paused and tell the copy task.
+async fn set_copying_paused(state: &App, paused: bool) -> Result<(), AppError> {
+    let mut config = state.transmission_config.lock().await;
+    config.copying_paused = paused;
+    App::save_config(&state.config_path, &config)?;
+    state.copy_pause.send_replace(paused);
+    log::info!("Copying {}", if paused { "paused" } else { "resumed" });
+    Ok(())
 }
 
+/// Reset failed copies of the given torrents so the copy task retries them
+/// right away.
 #[tauri::command]
-async fn add_to_watchlist(
-    state: State<'_, App>,
-    title: String,
-    destination: Destination,
-) -> Result<WatchlistEntry, AppError> {
+async fn retry_copies(state: State<'_, App>, hashes: Vec<InfoHash>) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    let mut retried = 0;
+    for entry in ledger.iter_mut() {
+        if !hashes.contains(&entry.info_hash) {
+            continue;
+        }
+        entry.update_targets(|target| {
+            if target.copy_state == CopyState::Failed {
+                target.copy_state = CopyState::NotCopied;
+                target.failure = None;
+                retried += 1;
+            }
+        });
+    }
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    state.copy_notify.notify_one();
+    log::info!("Retrying {retried} failed copies");
+    Ok(())
+}
+
+/// The ledger entries matching `query`, a page at a time.
+#[tauri::command]
+async fn get_downloads_ledger(
+    state: State<'_, App>,
+    query: LedgerQuery,
+) -> Result<LedgerPage, AppError> {
+    let ledger = state.downloads_ledger.lock().await;
+    Ok(ledger::query(&ledger, &query))
+}
+
+/// Move a download's ledger entry to the recently removed, from which it
+/// can be restored for [`REMOVED_KEEP_DAYS`].  Being undoable, it needs no
+/// `confirm`.
+///
+/// Takes [`InfoHashArgs`](privateer_wire_types::InfoHashArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn remove_download(state: State<'_, App>, info_hash: InfoHash) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    let removed_path = data_paths::removed_beside(&state.ledger_path);
+    let mut removed = App::load_removed(&state.ledger_path)?;
+    ledger::remove_download(&mut ledger, &mut removed, &info_hash, unix_now())?;
+    // Saved first, so the entry is never in neither
+    App::save_json(&removed_path, &removed)?;
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    log::info!("Removed {info_hash} from the ledger");
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Where a download's files currently live: where the download client has
+/// them and where they are, or would be, at each destination, resolved the
+/// way the copy engine resolves them.
+///
+/// Takes [`InfoHashArgs`](privateer_wire_types::InfoHashArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn locate_download(
+    state: State<'_, App>,
+    info_hash: InfoHash,
+) -> Result<DownloadLocation, AppError> {
+    let config = state.transmission_config.lock().await.clone();
+    let entry = {
+        let ledger = state.downloads_ledger.lock().await;
+        ledger.iter().find(|e| e.info_hash == info_hash).cloned()
+    };
+    // The files may be wanted most when the client is down
+    let (torrent, client_error) = match fetch_raw_torrents(&state, false).await {
+        Ok(torrents) => {
+            let torrent = torrents
+                .into_iter()
+                .find(|t| t.hash.as_ref() == Some(&info_hash))
+                .map(source_torrent);
+            (torrent, None)
+        }
+        Err(e) => (None, Some(e.to_string())),
+    };
+    if torrent.is_none() && entry.is_none() && client_error.is_none() {
+        return Err(DownloadNotInLedgerSnafu { info_hash }.build().into());
+    }
+
+    let remote = match &config.copy_backend {
+        CopyBackend::Sftp(sftp) if torrent.is_some() => Some(
+            sftp::SftpSource::connect(sftp)
+                .await
+                .map_err(|e| e.to_string()),
+        ),
+        _ => None,
+    };
+    let mut location = copier::locate(
+        &state.fs,
+        &remote,
+        &config,
+        &info_hash,
+        torrent.as_ref(),
+        entry.as_ref(),
+    )
+    .await;
+    location.client_error = client_error;
+    Ok(location)
+}
+
+/// The ledger entries removed in the last [`REMOVED_KEEP_DAYS`], most
+/// recently removed first.
+#[tauri::command]
+async fn get_removed_downloads(state: State<'_, App>) -> Result<Vec<RemovedDownload>, AppError> {
+    let mut removed = expire_removed_downloads(&state).await?;
+    removed.sort_by_key(|r| std::cmp::Reverse(r.removed_at));
+    Ok(removed)
+}
+
+/// Put a removed download back in the ledger.  If it was added again since,
+/// the entry whose copies are further along is kept.
+///
+/// Takes [`InfoHashArgs`](privateer_wire_types::InfoHashArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn restore_download(state: State<'_, App>, info_hash: InfoHash) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    let removed_path = data_paths::removed_beside(&state.ledger_path);
+    let mut removed = App::load_removed(&state.ledger_path)?;
+    ledger::restore_download(&mut ledger, &mut removed, &info_hash)?;
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    App::save_json(&removed_path, &removed)?;
+    log::info!("Restored {info_hash} to the ledger");
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Hand the ledger entry of a download that needs replacing over to its
+/// replacement, which is copied to the same destinations.  The old entry is
+/// removed, so it can be restored, and the command needs no `confirm`.
+///
+/// Takes [`TransferLedgerArgs`](privateer_wire_types::TransferLedgerArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn transfer_ledger_entry(
+    state: State<'_, App>,
+    old_hash: InfoHash,
+    new_hash: InfoHash,
+) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    let removed_path = data_paths::removed_beside(&state.ledger_path);
+    let mut removed = App::load_removed(&state.ledger_path)?;
+    ledger::transfer_entry(&mut ledger, &mut removed, &old_hash, &new_hash, unix_now())?;
+    App::save_json(&removed_path, &removed)?;
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    log::info!("Transferred the ledger entry of {old_hash} to {new_hash}");
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Drop the removed ledger entries kept for [`REMOVED_KEEP_DAYS`], and
+/// return the rest.
+async fn expire_removed_downloads(state: &App) -> Result<Vec<RemovedDownload>, ConfigError> {
+    // Removals and restores change the file under the ledger lock
+    let _ledger = state.downloads_ledger.lock().await;
+    let removed_path = data_paths::removed_beside(&state.ledger_path);
+    let mut removed = App::load_removed(&state.ledger_path)?;
+    if ledger::expire_removed(&mut removed, unix_now()) {
+        App::save_json(&removed_path, &removed)?;
+    }
+    Ok(removed)
+}
+
+/// Re-derive copy states from the destinations now rather than at the next
+/// cycle, waiting for a cycle in progress to finish first.
+#[tauri::command]
+async fn reconcile_now(state: State<'_, App>) -> Result<ReconcileSummary, AppError> {
+    let (reply, summary) = oneshot::channel();
+    state
+        .reconcile_requests
+        .send(reply)
+        .await
+        .map_err(|_| CopyError::CopyTaskStopped)?;
+    let summary = summary.await.map_err(|_| CopyError::CopyTaskStopped)?;
+    state.torrents_wake.notify_one();
+    Ok(summary)
+}
+
+/// Ledger entries not yet copied whose torrents Transmission no longer has,
+/// e.g. after it was reinstalled.
+fn missing_torrents(
+    ledger: &[DownloadEntry],
+    torrents: &[TransmissionTorrent],
+    profile: u32,
+) -> Vec<DownloadEntry> {
+    ledger
+        .iter()
+        // Torrents added to another server aren't missing from this one
+        .filter(|entry| entry.profile == profile)
+        .filter(|entry| entry.overall_copy_state() != CopyState::Copied)
+        .filter(|entry| !torrents.iter().any(|t| t.hash_string == entry.info_hash))
+        .cloned()
+        .collect()
+}
+
+#[tauri::command]
+async fn get_missing_torrents(state: State<'_, App>) -> Result<Vec<DownloadEntry>, AppError> {
+    let torrents = fetch_torrents(&state, false).await?;
+    let profile = state.active_profile_id().await;
+    let ledger = state.downloads_ledger.lock().await;
+    Ok(missing_torrents(&ledger, &torrents, profile))
+}
+
+/// Add the given ledger entries back to Transmission, by the magnet link
+/// they were added with or one made from their info hash.  Entries that
+/// Transmission has again, or that are already copied, are skipped.
+#[tauri::command]
+async fn readd_missing_torrents(
+    state: State<'_, App>,
+    hashes: Vec<InfoHash>,
+) -> Result<Vec<ReaddResult>, AppError> {
+    let torrents = fetch_torrents(&state, true).await?;
+    let profile = state.active_profile_id().await;
+    let missing = missing_torrents(&state.downloads_ledger.lock().await, &torrents, profile);
+    let requested = |entry: &DownloadEntry| hashes.contains(&entry.info_hash);
+    let mut results = vec![];
+    for entry in missing.into_iter().filter(requested) {
+        log::info!("re-adding '{}' to Transmission...", entry.name);
+        let error = match torrent_add(&state, entry.magnet_uri()).await {
+            Ok(_) => None,
+            Err(e) => {
+                log::error!("could not re-add '{}': {e}", entry.name);
+                Some(e.to_string())
+            }
+        };
+        results.push(ReaddResult {
+            info_hash: entry.info_hash,
+            name: entry.name,
+            error,
+        });
+    }
+    state.torrents_wake.notify_one();
+    Ok(results)
+}
+
+/// The ledger joined with Transmission's torrents, serialized in `format`.
+///
+/// When Transmission can't be reached the ledger alone is exported.
+async fn export_document(state: &App, format: ExportFormat) -> Result<String, AppError> {
+    let torrents = fetch_torrents(state, false).await.unwrap_or_else(|e| {
+        log::warn!("Exporting the ledger without Transmission data: {e}");
+        vec![]
+    });
+    let ledger = state.downloads_ledger.lock().await.clone();
+    let units = state.transmission_config.lock().await.size_units;
+    Ok(export::to_document(
+        &export::rows(&ledger, &torrents, units),
+        format,
+    )?)
+}
+
+#[tauri::command]
+async fn export_downloads(state: State<'_, App>, format: ExportFormat) -> Result<String, AppError> {
+    export_document(&state, format).await
+}
+
+/// Ask where to save an export and write it there, as JSON when the chosen
+/// file ends in `.json` and CSV otherwise.  Returns the path, or `None` if
+/// the dialog was cancelled.
+#[tauri::command]
+async fn save_downloads_export(
+    app: AppHandle,
+    state: State<'_, App>,
+) -> Result<Option<String>, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_file_name("privateer-downloads.csv")
+        .add_filter("CSV", &["csv"])
+        .add_filter("JSON", &["json"])
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let Some(path) = rx
+        .await
+        .ok()
+        .flatten()
+        .and_then(|path| path.into_path().ok())
+    else {
+        return Ok(None);
+    };
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let format = if is_json {
+        ExportFormat::Json
+    } else {
+        ExportFormat::Csv
+    };
+    let document = export_document(&state, format).await?;
+    std::fs::write(&path, document).context(ExportSaveSnafu { path: path.clone() })?;
+    log::info!("Exported downloads to {}", path.display());
+    Ok(Some(path.display().to_string()))
+}
+
+/// Ask for a directory, starting in `start` if it exists.  Returns the
+/// chosen directory without a trailing separator, or `None` if the dialog
+/// was cancelled.
+#[tauri::command]
+async fn pick_directory(app: AppHandle, start: Option<String>) -> Option<String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut dialog = app.dialog().file();
+    if let Some(start) = start.map(PathBuf::from).filter(|dir| dir.is_dir()) {
+        dialog = dialog.set_directory(start);
+    }
+    dialog.pick_folder(move |path| {
+        let _ = tx.send(path);
+    });
+    let path = rx.await.ok().flatten()?.into_path().ok()?;
+    let path = path.display().to_string();
+    Some(privateer_wire_types::normalize_dir(&path))
+}
+
+/// Number of torrents downloading plus ledger entries being copied, for the
+/// Downloads tab badge.
+#[tauri::command]
+async fn get_active_count(state: State<'_, App>) -> Result<usize, AppError> {
+    Ok(state.activity.lock().await.active())
+}
+
+#[tauri::command]
+async fn pause_torrents(state: State<'_, App>, hashes: Vec<InfoHash>) -> Result<(), AppError> {
+    log::info!("pausing {} torrents", hashes.len());
+    state
+        .with_client(|client| Box::pin(async move { client.pause(&hashes).await }))
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn resume_torrents(state: State<'_, App>, hashes: Vec<InfoHash>) -> Result<(), AppError> {
+    log::info!("resuming {} torrents", hashes.len());
+    state
+        .with_client(|client| Box::pin(async move { client.resume(&hashes).await }))
+        .await?;
+    Ok(())
+}
+
+/// Remove torrents from the download client, and their downloaded data if
+/// `delete_data` is set.  Their ledger entries, and any copies, are kept.
+///
+/// Takes [`RemoveTorrentsArgs`](privateer_wire_types::RemoveTorrentsArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn remove_torrents(
+    state: State<'_, App>,
+    hashes: Vec<InfoHash>,
+    delete_data: bool,
+    confirm: Option<bool>,
+) -> Result<(), AppError> {
+    confirmed(confirm, "Removing torrents")?;
+    log::info!(
+        "removing {} torrents{}",
+        hashes.len(),
+        if delete_data { " and their data" } else { "" }
+    );
+    state
+        .with_client(|client| Box::pin(async move { client.remove(&hashes, delete_data).await }))
+        .await?;
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Rename a file or folder of a torrent, and its files on disk, with
+/// Transmission's `torrent-rename-path` or qBittorrent's equivalents.
+///
+/// `old_path` is relative to the download dir, so the torrent itself is
+/// renamed by passing its name.  Only the last component of `old_path` is
+/// renamed, which is why `new_name` has to be a single name.
+///
+/// Takes [`RenameTorrentArgs`](privateer_wire_types::RenameTorrentArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn rename_torrent(
+    state: State<'_, App>,
+    info_hash: InfoHash,
+    old_path: String,
+    new_name: String,
+) -> Result<(), AppError> {
+    let new_name = new_name.trim().to_string();
+    if matches!(new_name.as_str(), "" | "." | "..") || new_name.contains(['/', '\\']) {
+        return Err(InvalidNameSnafu { name: new_name }.build().into());
+    }
+    require_feature(&state, ClientFeature::RenamePath).await?;
+    log::info!("renaming '{old_path}' of torrent {info_hash} to '{new_name}'");
+    let (hash, path, name) = (info_hash.clone(), old_path.clone(), new_name.clone());
+    state
+        .with_client(|client| {
+            Box::pin(async move { client.rename_path(&hash, &path, &name).await })
+        })
+        .await?;
+
+    // Renaming the top level renames the torrent, and where it's copied to
+    if !old_path.contains('/') {
+        let mut ledger = state.lock_ledger().await?;
+        if let Some(entry) = ledger.iter_mut().find(|e| e.info_hash == info_hash) {
+            entry.rename(&new_name);
+            App::save_ledger(&state.ledger_path, &ledger)?;
+            state.copy_notify.notify_one();
+        }
+    }
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Set the user's note on a download in the ledger, or remove it if blank.
+///
+/// Takes [`UpdateNoteArgs`](privateer_wire_types::UpdateNoteArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn update_download_note(
+    state: State<'_, App>,
+    info_hash: InfoHash,
+    note: Option<String>,
+) -> Result<(), AppError> {
+    let mut ledger = state.lock_ledger().await?;
+    ledger::set_user_note(&mut ledger, &info_hash, note)?;
+    App::save_ledger(&state.ledger_path, &ledger)?;
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Pause, or resume, every torrent.
+async fn pause_all(state: &App, pause: bool) -> Result<(), AppError> {
+    let hashes: Vec<InfoHash> = fetch_raw_torrents(state, true)
+        .await?
+        .into_iter()
+        .filter_map(|t| t.hash)
+        .collect();
+    state
+        .with_client(|client| {
+            Box::pin(async move {
+                if pause {
+                    client.pause(&hashes).await
+                } else {
+                    client.resume(&hashes).await
+                }
+            })
+        })
+        .await?;
+    state.torrents_wake.notify_one();
+    Ok(())
+}
+
+/// Fix an errored torrent in one go: point it at its files if the ledger
+/// knows a better location, then verify and reannounce.  The torrent is
+/// only pointed elsewhere when its files are gone from where it is, see
+/// [`DownloadClient::set_location`].
+///
+/// Takes [`InfoHashArgs`](privateer_wire_types::InfoHashArgs).
+#[tauri::command(rename_all = "camelCase")]
+async fn repair_torrent(
+    state: State<'_, App>,
+    info_hash: InfoHash,
+) -> Result<RepairSummary, AppError> {
+    log::info!("repairing torrent {info_hash}");
+    let config = state.transmission_config.lock().await.clone();
+    let destination = state
+        .downloads_ledger
+        .lock()
+        .await
+        .iter()
+        .find(|e| e.info_hash == info_hash)
+        .map(|e| e.destination);
+    let fs = state.fs.clone();
+
+    let summary = state
+        .with_client(|client| {
+            Box::pin(async move {
+                let hashes = std::slice::from_ref(&info_hash);
+
+                let torrent = client
+                    .torrents()
+                    .await?
+                    .into_iter()
+                    .find(|t| t.hash.as_ref() == Some(&info_hash))
+                    .ok_or_else(|| TransmissionError::Rpc {
+                        message: format!("no torrent with hash {info_hash}"),
+                    })?;
+                let name = torrent.name;
+
+                let mut summary = RepairSummary::default();
+                let download_dir = torrent.download_dir.as_deref();
+                let better =
+                    relocation::better_location(&fs, &config, destination, &name, download_dir);
+                if let Some(dir) = better.await {
+                    log::info!("repair: pointing '{name}' at {dir}");
+                    client.set_location(hashes, &dir).await?;
+                    summary.relocated_to = Some(dir);
+                }
+
+                client.verify(hashes).await?;
+                summary.verified = true;
+                client.reannounce(hashes).await?;
+                summary.reannounced = true;
+
+                log::info!("repair: '{name}': {}", summary.describe());
+                Ok(summary)
+            })
+        })
+        .await?;
+    Ok(summary)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands – Watchlist
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+async fn get_watchlist(state: State<'_, App>) -> Result<Vec<WatchlistEntry>, AppError> {
+    let watchlist = state.watchlist.lock().await;
+    Ok(watchlist.clone())
+}
+
+#[tauri::command]
+async fn add_to_watchlist(
+    state: State<'_, App>,
+    title: String,
+    destination: Destination,
+) -> Result<WatchlistEntry, AppError> {
     let mut watchlist = state.watchlist.lock().await;
     let mut next_id = state.next_watchlist_id.lock().await;
     let entry = WatchlistEntry {
         id: *next_id,
         title,
         destination,
-        added: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() as i64,
+        added: unix_now(),
     };
     *next_id += 1;
     watchlist.push(entry.clone());
@@ -537,8 +1768,14 @@ async fn add_to_watchlist(
     Ok(entry)
 }
 
+/// Takes [`RemoveWatchlistArgs`](privateer_wire_types::RemoveWatchlistArgs).
 #[tauri::command]
-async fn remove_from_watchlist(state: State<'_, App>, id: u64) -> Result<(), AppError> {
+async fn remove_from_watchlist(
+    state: State<'_, App>,
+    id: u64,
+    confirm: Option<bool>,
+) -> Result<(), AppError> {
+    confirmed(confirm, "Removing from the watchlist")?;
     let mut watchlist = state.watchlist.lock().await;
     watchlist.retain(|e| e.id != id);
     App::save_json(&state.watchlist_path, &watchlist)?;
@@ -653,60 +1890,111 @@ async fn check_episodes_exist(
 }
 
 // ---------------------------------------------------------------------------
-// Background copy task
+// Tauri commands – Logs
 // ---------------------------------------------------------------------------
 
-/// Recursively copy `src` to `dst` using async I/O (tokio::fs).
-///
-/// This avoids blocking the tokio runtime when copying large files to slow
-/// destinations (e.g. a NAS with spinning disks).
-async fn copy_recursive_async(
-    src: &std::path::Path,
-    dst: &std::path::Path,
-) -> Result<(), CopyError> {
-    if src.is_dir() {
-        tokio::fs::create_dir_all(dst)
-            .await
-            .context(CopyCreateDirSnafu {
-                path: dst.to_path_buf(),
-            })?;
-        let mut read_dir = tokio::fs::read_dir(src).await.context(CopyReadDirSnafu {
-            path: src.to_path_buf(),
-        })?;
-        while let Some(entry) = read_dir.next_entry().await.context(CopyReadDirSnafu {
-            path: src.to_path_buf(),
-        })? {
-            let child_src = entry.path();
-            let child_dst = dst.join(entry.file_name());
-            Box::pin(copy_recursive_async(&child_src, &child_dst)).await?;
-        }
-    } else {
-        // Single file
-        if let Some(parent) = dst.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .context(CopyCreateDirSnafu {
-                    path: parent.to_path_buf(),
-                })?;
-        }
-        tokio::fs::copy(src, dst).await.context(CopyFileSnafu {
-            src: src.to_path_buf(),
-            dst: dst.to_path_buf(),
-        })?;
+/// The last `lines` lines of the current log file, oldest first.
+#[tauri::command]
+async fn get_recent_logs(
+    logging: State<'_, Logging>,
+    lines: usize,
+) -> Result<Vec<LogLine>, AppError> {
+    Ok(logging.recent_lines(lines)?)
+}
+
+/// Path of the current log file.
+#[tauri::command]
+fn get_log_path(logging: State<'_, Logging>) -> String {
+    logging.path().display().to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands – About
+// ---------------------------------------------------------------------------
+
+/// Which build is running, where its files are and which download client
+/// it last connected to.
+#[tauri::command]
+async fn get_app_info(
+    state: State<'_, App>,
+    logging: State<'_, Logging>,
+) -> Result<AppInfo, AppError> {
+    Ok(AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("PRIVATEER_GIT_HASH").map(str::to_string),
+        tauri_version: tauri::VERSION.to_string(),
+        webview_version: tauri::webview_version().ok(),
+        config_path: state.config_path.display().to_string(),
+        ledger_path: state.ledger_path.display().to_string(),
+        log_path: logging.path().display().to_string(),
+        daemon: state.daemon.lock().await.clone(),
+    })
+}
+
+/// The latest release, if it is newer than this build and update checks
+/// are turned on.
+#[tauri::command]
+async fn check_for_update(state: State<'_, App>) -> Result<Option<UpdateInfo>, AppError> {
+    if !state.transmission_config.lock().await.check_for_updates {
+        return Ok(None);
     }
-    Ok(())
+    Ok(updates::check(&state.http, env!("CARGO_PKG_VERSION")).await?)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands – Copy metrics
+// ---------------------------------------------------------------------------
+
+/// Cumulative copy statistics.
+#[tauri::command]
+async fn get_copy_metrics(state: State<'_, App>) -> Result<CopyMetrics, AppError> {
+    Ok(state.copy_metrics.load())
+}
+
+/// Zero the copy statistics, returning the new (empty) totals.
+///
+/// Takes [`ConfirmArgs`](privateer_wire_types::ConfirmArgs).
+#[tauri::command]
+async fn reset_copy_metrics(
+    state: State<'_, App>,
+    confirm: Option<bool>,
+) -> Result<CopyMetrics, AppError> {
+    confirmed(confirm, "Resetting the statistics")?;
+    let metrics = state.copy_metrics.reset()?;
+    log::info!("Copy metrics reset");
+    Ok(metrics)
 }
 
 // ---------------------------------------------------------------------------
 // App entry point
 // ---------------------------------------------------------------------------
 
+/// Run only the copy engine, without a window.  `args` are the command-line
+/// arguments after the program name; see [`headless`].
+pub fn run_headless(args: &[String]) -> std::process::ExitCode {
+    headless::run(args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::builder().init();
     tauri::Builder::default()
+        // Must come first: a second launch, e.g. for a clicked magnet link,
+        // hands its link to the running instance and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            show_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
+            let app_data_dir = app
+                .path()
+                .app_data_dir()
+                .unwrap_or_else(|_| PathBuf::from("."));
+            let paths = DataPaths::from_env(app_data_dir, |key| std::env::var(key).ok());
+            app.manage(Logging::init(&paths.logs()));
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -714,290 +2002,1596 @@ pub fn run() {
                 // window.close_devtools();
             }
 
-            let app_data_dir = app
-                .path()
-                .app_data_dir()
-                .unwrap_or_else(|_| PathBuf::from("."));
-            let config_path = app_data_dir.join("transmission_config.json");
-            let ledger_path = app_data_dir.join("downloads.json");
-            let watchlist_path = app_data_dir.join("watchlist.json");
-
-            let app_state = App::new(config_path, ledger_path, watchlist_path);
+            let feeds_seen_path = paths.feeds_seen();
+            let (reconcile_requests, reconcile_jobs) = mpsc::channel(1);
+            let app_state = App::new(
+                paths.config.clone(),
+                paths.ledger.clone(),
+                paths.metrics(),
+                paths.watchlist(),
+                paths.availability(),
+                reconcile_requests,
+            );
 
             // Spawn the background copy task.
             // The task reads config and ledger from disk each cycle so it
-            // always sees the latest saved state without sharing Mutex refs.
+            // always sees the latest saved state.  It saves the ledger under
+            // the commands' lock.
             let copy_config_path = app_state.config_path.clone();
-            let copy_ledger_path = app_state.ledger_path.clone();
+            let copy_ledger = app_state.ledger_file();
             let copy_notify = app_state.copy_notify.clone();
+            let copy_fs = app_state.fs.clone();
+            let copy_pause = app_state.copy_pause.subscribe();
+            let copy_engine = app_state.copy_engine.clone();
+            let (scanner, scan_jobs) = MediaScanner::new();
+            let (webhook_sender, webhook_jobs) = WebhookSender::new();
+            let (hook, hook_jobs) = PostCopyHook::new();
+            let observers = (
+                Notifier::new(app.handle().clone()),
+                (
+                    MetricsRecorder::new(app_state.copy_metrics.clone()),
+                    (scanner, (webhook_sender, hook)),
+                ),
+            );
+            let scan_client = app_state.http.clone();
+            let webhook_client = app_state.webhook_http.clone();
 
             app.manage(app_state);
 
+            build_tray(app)?;
+
+            // macOS registers the scheme from the app bundle at install time
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            app.deep_link().register_all()?;
+            let link_app = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    open_magnet(&link_app, url.as_str());
+                }
+            });
+            // The link that launched the app, if any
+            for url in app.deep_link().get_current()?.unwrap_or_default() {
+                open_magnet(app.handle(), url.as_str());
+            }
+
+            let status_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                status_task(status_app).await;
+            });
+
+            let health_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                health_task(health_app).await;
+            });
+
+            let torrents_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                torrents_task(torrents_app).await;
+            });
+
+            let feeds_app = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                copy_task_from_disk(copy_config_path, copy_ledger_path, copy_notify).await;
+                feeds_task(feeds_app, feeds_seen_path).await;
+            });
+
+            let removed_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                removed_cleanup_task(removed_app).await;
+            });
+
+            let scan_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                media_server::scan_task(scan_jobs, scan_client, |message| {
+                    emit_toast(&scan_app, LogLevel::Warn, message)
+                })
+                .await;
+            });
+
+            tauri::async_runtime::spawn(async move {
+                webhook::webhook_task(webhook_jobs, webhook_client).await;
+            });
+
+            let hook_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                post_copy::hook_task(hook_jobs, |message| {
+                    emit_toast(&hook_app, LogLevel::Warn, message)
+                })
+                .await;
+            });
+
+            tauri::async_runtime::spawn(async move {
+                // The desktop app's copy task runs until the app exits
+                copy_task_from_disk(
+                    copy_config_path,
+                    copy_ledger,
+                    copy_fs,
+                    copy_notify,
+                    observers,
+                    copy_engine,
+                    Some(copy_pause),
+                    Some(reconcile_jobs),
+                    None,
+                )
+                .await;
             });
 
             Ok(())
         })
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
+                let state = window.state::<App>();
+                if App::load_config(&state.config_path).close_to_tray {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+            WindowEvent::Focused(true) => {
+                // Resume prompt torrent updates after backing off
+                window.state::<App>().torrents_wake.notify_one();
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             search,
             info,
+            needs_setup,
             get_transmission_config,
             set_transmission_config,
+            get_profiles,
+            set_active_profile,
             test_transmission_connection,
+            get_free_space,
+            get_daemon_info,
+            get_session_config,
+            set_session_config,
             get_torrents,
             add_download,
+            add_downloads,
+            preview_auto_assign,
+            add_magnet,
+            get_pending_magnets,
+            dismiss_magnet,
+            retry_copies,
+            copy_now,
+            pause_copying,
+            resume_copying,
+            is_copying_paused,
+            pause_torrents,
+            resume_torrents,
+            remove_torrents,
+            rename_torrent,
+            update_download_note,
+            repair_torrent,
             get_downloads_ledger,
+            remove_download,
+            get_removed_downloads,
+            locate_download,
+            restore_download,
+            transfer_ledger_entry,
+            reconcile_now,
+            get_missing_torrents,
+            readd_missing_torrents,
+            export_downloads,
+            save_downloads_export,
+            pick_directory,
+            get_active_count,
+            get_health,
+            check_destination,
+            get_destination_usage,
+            get_connection_status,
+            get_recent_logs,
+            get_log_path,
+            get_app_info,
+            check_for_update,
+            get_copy_metrics,
+            reset_copy_metrics,
+            test_media_server,
+            send_test_webhook,
+            test_post_copy_hook,
+            preview_subscription,
             get_watchlist,
             add_to_watchlist,
             remove_from_watchlist,
             check_movie_exists,
             check_episodes_exist,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Clicking the dock icon brings back a window hidden to the tray.
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Reopen { .. } = event {
+                show_main_window(app);
+            }
+            #[cfg(not(target_os = "macos"))]
+            let _ = (app, event);
+        });
 }
 
-/// Background copy task that reads config/ledger from disk each cycle.
-///
-/// Uses async I/O (`tokio::fs`) so large copies to slow NAS drives don't
-/// block the tokio runtime.  State transitions are persisted to the ledger
-/// file so the frontend can show real-time progress:
+// ---------------------------------------------------------------------------
+// Torrent updates
+// ---------------------------------------------------------------------------
+
+/// Event carrying the latest `Result<Vec<TransmissionTorrent>, AppError>`.
+const TORRENTS_UPDATED: &str = "torrents-updated";
+
+/// Whether the main window is on screen.
+fn main_window_is_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .map(|w| w.is_visible().unwrap_or(true) && !w.is_minimized().unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// Background task that polls Transmission and emits [`TORRENTS_UPDATED`]
+/// whenever the torrent list (or the error fetching it) changes, so the
+/// Downloads view doesn't have to poll.
 ///
-///   NotCopied/Failed  →  Copying  →  Copied | Failed
-async fn copy_task_from_disk(config_path: PathBuf, ledger_path: PathBuf, notify: Arc<Notify>) {
+/// Polls every 3 seconds while the window is visible and backs off to 30
+/// seconds while it is hidden or minimized.  Focusing the window wakes the
+/// task immediately.
+async fn torrents_task(app: AppHandle) {
+    let mut last: Option<Result<Vec<TransmissionTorrent>, AppError>> = None;
     loop {
-        // Wait for either the 30-second interval or an explicit wake-up
-        // from `add_download`.
-        tokio::select! {
-            _ = tokio::time::sleep(std::time::Duration::from_secs(30)) => {}
-            _ = notify.notified() => {
-                log::info!("Copy task: woken up by add_download");
+        let state = app.state::<App>();
+        let mut update = fetch_torrents(&state, false).await;
+        if let Ok(torrents) = &mut update {
+            let now = unix_now();
+            let mut history = state.rate_history.lock().await;
+            history.record(torrents, now);
+            history.attach(torrents);
+            drop(history);
+            let dead_after_hours = state.transmission_config.lock().await.dead_after_hours;
+            let mut availability = state.availability.lock().await;
+            if availability.record(torrents, now) {
+                if let Err(e) = availability.save() {
+                    log::warn!("Torrents task: could not save peer availability: {e}");
+                }
+            }
+            availability.attach(torrents, now, dead_after_hours);
+            drop(availability);
+            let mut errors = state.torrent_errors.lock().await;
+            errors.record(torrents);
+            errors.attach(torrents);
+        }
+        if last.as_ref() != Some(&update) {
+            if let Err(e) = app.emit(TORRENTS_UPDATED, &update) {
+                log::warn!("Torrents task: could not emit update: {e}");
             }
+            last = Some(update);
         }
 
-        let config = App::load_config(&config_path);
-        let mut ledger = App::load_ledger(&ledger_path);
+        let interval = if main_window_is_visible(&app) { 3 } else { 30 };
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+            _ = state.torrents_wake.notified() => {}
+        }
+    }
+}
 
-        // Connect to Transmission to get torrent statuses.
-        // We need the torrent list for both reconciliation and copying.
-        let mut client = match make_trans_client(&config) {
-            Ok(c) => c,
-            Err(e) => {
-                log::warn!("Copy task: cannot connect to Transmission: {e}");
-                continue;
+/// Background task that drops removed ledger entries once they have been
+/// kept for [`REMOVED_KEEP_DAYS`], checking every hour.
+async fn removed_cleanup_task(app: AppHandle) {
+    loop {
+        let state = app.state::<App>();
+        if let Err(e) = expire_removed_downloads(&state).await {
+            log::warn!("Could not drop expired removed downloads: {e}");
+        }
+        tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Feed subscriptions
+// ---------------------------------------------------------------------------
+
+/// Background task that polls the subscribed feeds every
+/// `feed_poll_minutes` and adds their new matching items.  See [`feeds`].
+async fn feeds_task(app: AppHandle, seen_path: PathBuf) {
+    let mut seen = feeds::SeenItems::load(seen_path);
+    loop {
+        let state = app.state::<App>();
+        let config = state.transmission_config.lock().await.clone();
+
+        // Fetch each feed once for all of its subscriptions
+        let mut by_url: Vec<(&str, Vec<(&FeedSubscription, feeds::Matcher)>)> = vec![];
+        for subscription in &config.subscriptions {
+            let matcher = match feeds::Matcher::new(subscription) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    log::warn!("skipping subscription to '{}': {e}", subscription.url);
+                    continue;
+                }
+            };
+            let url = subscription.url.trim();
+            match by_url.iter_mut().find(|(feed, _)| *feed == url) {
+                Some((_, subscriptions)) => subscriptions.push((subscription, matcher)),
+                None => by_url.push((url, vec![(subscription, matcher)])),
             }
-        };
+        }
+
+        for (url, subscriptions) in &by_url {
+            let items = match feeds::feed_url(subscriptions[0].0) {
+                Ok(feed_url) => feeds::fetch(&state.http, feed_url).await,
+                Err(e) => {
+                    log::warn!("skipping feed: {e}");
+                    continue;
+                }
+            };
+            let items = match items {
+                Ok(items) => items,
+                Err(e) => {
+                    log::warn!("feed {url}: {e}");
+                    continue;
+                }
+            };
+            let first_poll = seen.get(url).is_none();
+            let mut failed = vec![];
+            for (item, subscription) in feeds::new_matches(&items, subscriptions, seen.get(url)) {
+                log::info!("feed {url}: adding '{}'", item.title);
+                let added = match torrent_add(&state, item.link.clone()).await {
+                    Ok(info_hash) => track_download(
+                        &state,
+                        info_hash,
+                        item.title.clone(),
+                        subscription.destination,
+                        Some(item.link.clone())
+                            .filter(|link| privateer_wire_types::parse_magnet(link).is_some()),
+                    )
+                    .await
+                    .map_err(AppError::from),
+                    Err(e) => Err(e.into()),
+                };
+                match added {
+                    Ok(()) => emit_toast(
+                        &app,
+                        LogLevel::Info,
+                        format!("Added '{}' from a feed", item.title),
+                    ),
+                    Err(e) => {
+                        log::error!("feed {url}: could not add '{}': {e}", item.title);
+                        failed.push(item.guid.as_str());
+                    }
+                }
+            }
+            if first_poll {
+                log::info!(
+                    "feed {url}: skipping the {} items already in it",
+                    items.len()
+                );
+            }
+            seen.update(url, &items, failed);
+        }
+        seen.retain(by_url.iter().map(|(url, _)| *url));
+        if let Err(e) = seen.save() {
+            log::warn!("Feeds task: could not save seen items: {e}");
+        }
+
+        let minutes = config.feed_poll_minutes.max(1) as u64;
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tray
+// ---------------------------------------------------------------------------
 
-        let fields = vec![
-            TorrentGetField::HashString,
-            TorrentGetField::Name,
-            TorrentGetField::Status,
-            TorrentGetField::PercentDone,
-            TorrentGetField::DownloadDir,
-        ];
+const TRAY_ID: &str = "main";
 
-        let response = match client.torrent_get(Some(fields), None).await {
-            Ok(r) => r,
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Build the tray icon and its menu.  The tooltip is kept current by
+/// [`status_task`].
+fn build_tray(app: &tauri::App) -> tauri::Result<()> {
+    let open = MenuItem::with_id(app, "open", "Open Privateer", true, None::<&str>)?;
+    let pause_all = MenuItem::with_id(app, "pause_all", "Pause all", true, None::<&str>)?;
+    let resume_all = MenuItem::with_id(app, "resume_all", "Resume all", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &open,
+            &PredefinedMenuItem::separator(app)?,
+            &pause_all,
+            &resume_all,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Privateer")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "open" => show_main_window(app),
+            id @ ("pause_all" | "resume_all") => {
+                let pause = id == "pause_all";
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<App>();
+                    if let Err(e) = pause_all(&state, pause).await {
+                        log::error!("Tray: {e}");
+                    }
+                });
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+/// Count downloading torrents and in-progress copies.
+async fn poll_activity(state: &App) -> Result<ActivitySummary, ClientError> {
+    let torrents = fetch_raw_torrents(state, false).await?;
+    let downloading = torrents
+        .iter()
+        .filter(|t| t.status == TransmissionStatus::Downloading)
+        .count();
+    // The copy task persists its state transitions to disk, so the ledger
+    // file is more current than the in-memory copy.  One that can't be read
+    // is reported by the health check.
+    let copying = App::load_ledger(&state.ledger_path)
+        .unwrap_or_default()
+        .iter()
+        .filter(|e| e.overall_copy_state() == CopyState::Copying)
+        .count();
+    Ok(ActivitySummary {
+        downloading,
+        copying,
+    })
+}
+
+/// Background status task that polls Transmission and the ledger every few
+/// seconds, independently of the frontend, and publishes the result to
+/// `App::activity` and the tray tooltip.
+async fn status_task(app: AppHandle) {
+    loop {
+        let state = app.state::<App>();
+        let (summary, tooltip) = match poll_activity(&state).await {
+            Ok(summary) => (summary, summary.describe()),
             Err(e) => {
-                log::warn!("Copy task: torrent_get failed: {e}");
-                continue;
+                log::debug!("Status task: {e}");
+                (
+                    ActivitySummary::default(),
+                    "Download client unreachable".to_string(),
+                )
             }
         };
+        *state.activity.lock().await = summary;
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            let _ = tray.set_tooltip(Some(format!("Privateer \u{2014} {tooltip}")));
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
 
-        if !response.is_ok() {
-            log::warn!("Copy task: RPC error: {}", response.result);
-            continue;
+// ---------------------------------------------------------------------------
+// Health
+// ---------------------------------------------------------------------------
+
+/// Check a destination directory: does it exist, is its share mounted, can we
+/// write to it and how much space is left.
+fn destination_health(config: &TransmissionConfig, destination: Destination) -> DestinationHealth {
+    let mut health = unchecked_destination(config, destination);
+    let Some(dir) = health.path.clone().map(PathBuf::from) else {
+        return health;
+    };
+    health.reachable = dir.is_dir();
+    if config.expects_mount(destination) {
+        health.mounted = Some(mount::is_mounted(&dir));
+    }
+    if health.reachable {
+        let probe = dir.join(".privateer-health-check");
+        health.writable = std::fs::write(&probe, b"").is_ok();
+        let _ = std::fs::remove_file(&probe);
+        health.free_bytes = fs2::available_space(&dir).ok();
+    }
+    health
+}
+
+/// A destination as yet unchecked, so unreachable.
+fn unchecked_destination(
+    config: &TransmissionConfig,
+    destination: Destination,
+) -> DestinationHealth {
+    DestinationHealth {
+        destination,
+        path: config
+            .dir_for(destination)
+            .filter(|dir| !dir.is_empty())
+            .map(str::to_string),
+        reachable: false,
+        writable: false,
+        free_bytes: None,
+        mounted: None,
+        unreachable_since: None,
+    }
+}
+
+/// [`destination_health`] without blocking the runtime.  A directory the
+/// probe doesn't find is unreachable without further checks; the rest run
+/// on the blocking pool, and one that takes longer than
+/// [`path_probe::TIMEOUT`] counts as unreachable too.
+async fn probe_destination(
+    fs: &TokioFs,
+    config: &TransmissionConfig,
+    destination: Destination,
+) -> DestinationHealth {
+    let unreachable = unchecked_destination(config, destination);
+    if let Some(dir) = &unreachable.path {
+        if !fs.exists(Path::new(dir)).await {
+            return unreachable;
         }
+    }
+    let config = config.clone();
+    let checked = tokio::task::spawn_blocking(move || destination_health(&config, destination));
+    match tokio::time::timeout(path_probe::TIMEOUT, checked).await {
+        Ok(Ok(health)) => health,
+        _ => unreachable,
+    }
+}
 
-        let transmission_torrents = response.arguments.torrents;
+/// The search API's address, for the health check.
+const SEARCH_API_URL: &str = "https://apibay.org/";
+
+/// Whether the search API answers at all.  This isn't a search, so it
+/// neither waits for the [`RequestGate`] nor counts against apibay's limits.
+async fn search_health(http: &impl HttpClient) -> ServiceHealth {
+    let url = url::Url::parse(SEARCH_API_URL).expect("valid search API URL");
+    match http.send(HttpRequest::get(url)).await {
+        Ok(status) if status < 500 => ServiceHealth {
+            ok: true,
+            detail: "reachable".to_string(),
+        },
+        Ok(status) => ServiceHealth {
+            ok: false,
+            detail: format!("apibay responded with HTTP {status}"),
+        },
+        Err(e) => ServiceHealth {
+            ok: false,
+            detail: e.to_string(),
+        },
+    }
+}
 
-        // -----------------------------------------------------------------
-        // Reconciliation: scan Transmission torrents and update the ledger.
-        //
-        // 1. Untracked torrents whose files exist at a destination dir
-        //    → auto-add to ledger as Copied.
-        // 2. Stale states (NotCopied/Failed but files exist at dest)
-        //    → update to Copied.
-        // -----------------------------------------------------------------
-        let mut ledger_changed = false;
+/// What was copied to a destination, or `None` if it has no directory.
+/// How full its volume is is left to [`volume_space`].
+fn destination_usage(
+    config: &TransmissionConfig,
+    ledger: &[DownloadEntry],
+    destination: Destination,
+) -> Option<DestinationUsage> {
+    let path = config.dir_for(destination).filter(|dir| !dir.is_empty())?;
+    let mut usage = DestinationUsage {
+        destination,
+        path: path.to_string(),
+        total_bytes: None,
+        free_bytes: None,
+        copied: 0,
+        copied_bytes: 0,
+        pending: 0,
+    };
+    for entry in ledger {
+        // Every copy of a download is the same size as the one timed. Copies
+        // to directories of the user's own aren't on this volume.
+        let targets = entry.targets().into_iter();
+        for target in targets.filter(|t| t.destination == destination && t.dir.is_none()) {
+            if target.copy_state == CopyState::Copied {
+                usage.copied += 1;
+                usage.copied_bytes += entry.copied_bytes.unwrap_or(0);
+            } else if target.copy_state.is_pending() {
+                usage.pending += 1;
+            }
+        }
+    }
+    Some(usage)
+}
 
-        for tt in &transmission_torrents {
-            let hash = match tt.hash_string.as_deref() {
-                Some(h) => h,
-                None => continue,
-            };
-            let name = match tt.name.as_deref() {
-                Some(n) => n,
-                None => continue,
-            };
+/// Total and free bytes of the volume `dir` is on, both `None` if it isn't
+/// a directory.
+///
+/// Checked on the blocking pool, as a share that has gone away can hang the
+/// calls, and given up on after [`path_probe::TIMEOUT`].
+async fn volume_space(dir: PathBuf) -> (Option<u64>, Option<u64>) {
+    let checked = tokio::task::spawn_blocking(move || {
+        if dir.is_dir() {
+            (fs2::total_space(&dir).ok(), fs2::available_space(&dir).ok())
+        } else {
+            (None, None)
+        }
+    });
+    match tokio::time::timeout(path_probe::TIMEOUT, checked).await {
+        Ok(Ok(space)) => space,
+        _ => (None, None),
+    }
+}
 
-            let existing = ledger
-                .iter_mut()
-                .find(|e| e.info_hash.eq_ignore_ascii_case(hash));
+/// Disk usage of each destination that has a directory.
+#[tauri::command]
+async fn get_destination_usage(state: State<'_, App>) -> Result<Vec<DestinationUsage>, AppError> {
+    let config = state.transmission_config.lock().await.clone();
+    let ledger = App::load_ledger(&state.ledger_path)?;
+    let mut usages = Destination::ALL
+        .into_iter()
+        .filter_map(|dest| destination_usage(&config, &ledger, dest))
+        .collect::<Vec<_>>();
+    for usage in usages.iter_mut() {
+        (usage.total_bytes, usage.free_bytes) = volume_space(PathBuf::from(&usage.path)).await;
+    }
+    Ok(usages)
+}
 
-            match existing {
-                Some(entry) => {
-                    // Fix stale states: ledger says NotCopied/Failed but
-                    // files already exist at the destination.
-                    if matches!(entry.copy_state, CopyState::NotCopied | CopyState::Failed) {
-                        if check_already_copied(&config, entry.destination, name) {
-                            log::info!(
-                                "Reconcile: '{name}' already at {}, marking Copied",
-                                entry.destination
-                            );
-                            entry.copy_state = CopyState::Copied;
-                            ledger_changed = true;
-                        }
-                    }
+/// Bytes the unfinished torrents have yet to download.
+fn bytes_left(torrents: &[ClientTorrent]) -> u64 {
+    torrents
+        .iter()
+        .map(|t| (1.0 - t.percent_done).max(0.0) * t.size_when_done.max(0) as f64)
+        .sum::<f64>() as u64
+}
+
+/// Health of the download client: unhealthy when what its torrents have
+/// left to download doesn't fit in its download directory.
+fn client_health(client: &str, free: u64, left: u64, units: SizeUnits) -> ServiceHealth {
+    let free_text = format_size(free, units);
+    if left > free {
+        ServiceHealth {
+            ok: false,
+            detail: format!(
+                "{client} \u{b7} not enough space: {} still to download, {free_text} free",
+                format_size(left, units)
+            ),
+        }
+    } else {
+        ServiceHealth {
+            ok: true,
+            detail: format!("{client} \u{b7} {free_text} free"),
+        }
+    }
+}
+
+/// Check every subsystem.
+async fn check_health(state: &App) -> HealthReport {
+    let (kind, units) = {
+        let config = state.transmission_config.lock().await;
+        (config.active().client, config.size_units)
+    };
+    let transmission = match state
+        .with_client(|client| {
+            Box::pin(async move {
+                let daemon = client.daemon_info().await?;
+                let free = if daemon.capabilities.free_space {
+                    Some(client.free_space().await?)
+                } else {
+                    None
+                };
+                Ok((daemon, free))
+            })
+        })
+        .await
+    {
+        Ok((daemon, free)) => {
+            let name = format!("{kind} {}", daemon.version);
+            *state.daemon.lock().await = Some(daemon);
+            match free {
+                Some(free) => {
+                    // Torrents are only checked for room when they are added
+                    let left = fetch_raw_torrents(state, false)
+                        .await
+                        .map(|torrents| bytes_left(&torrents))
+                        .unwrap_or(0);
+                    client_health(&name, free, left, units)
                 }
-                None => {
-                    // Not in ledger — check whether files exist at either
-                    // destination. If so, auto-add as Copied.
-                    if let Some((dest, state)) = detect_destination(&config, name) {
-                        log::info!(
-                            "Reconcile: auto-adding '{name}' to ledger as {dest} ({:?})",
-                            state
-                        );
-                        ledger.push(DownloadEntry {
-                            info_hash: hash.to_string(),
-                            name: name.to_string(),
-                            destination: dest,
-                            copy_state: state,
-                        });
-                        ledger_changed = true;
-                    }
+                None => ServiceHealth {
+                    ok: true,
+                    detail: format!(
+                        "{name} \u{b7} {}",
+                        ClientFeature::FreeSpace.unavailable_reason()
+                    ),
+                },
+            }
+        }
+        Err(e) => ServiceHealth {
+            ok: false,
+            detail: e.to_string(),
+        },
+    };
+
+    let config = state.transmission_config.lock().await.clone();
+    let mut destinations = vec![];
+    for dest in Destination::ALL {
+        destinations.push(probe_destination(&state.fs, &config, dest).await);
+    }
+
+    let search = search_health(&state.http).await;
+
+    // The copy task persists its state to disk, so read the ledger file
+    let mut ledger = LedgerCounts::default();
+    let entries = App::load_ledger(&state.ledger_path).unwrap_or_else(|e| {
+        log::warn!("Health: {e}");
+        Vec::new()
+    });
+    for entry in entries {
+        match entry.overall_copy_state() {
+            CopyState::NotCopied => ledger.not_copied += 1,
+            CopyState::Copying => ledger.copying += 1,
+            CopyState::Copied => ledger.copied += 1,
+            CopyState::Failed => ledger.failed += 1,
+            CopyState::Unconfigured => ledger.unconfigured += 1,
+        }
+    }
+
+    HealthReport {
+        transmission,
+        destinations,
+        search,
+        copy_in_flight: ledger.copying > 0,
+        ledger,
+        copy_engine: state.copy_engine.borrow().clone(),
+        daemon: state.daemon.lock().await.clone(),
+    }
+}
+
+/// Latest health report.  Checked on demand if the health task hasn't
+/// produced one yet.
+#[tauri::command]
+async fn get_health(state: State<'_, App>) -> Result<HealthReport, AppError> {
+    if let Some(mut report) = state.health.lock().await.clone() {
+        // The client may have been connected to, or switched, since
+        report.daemon = state.daemon.lock().await.clone();
+        return Ok(report);
+    }
+    let report = check_health(&state).await;
+    *state.health.lock().await = Some(report.clone());
+    Ok(report)
+}
+
+/// Check a directory for a destination before it is saved, as the
+/// first-run setup does when one is chosen.
+#[tauri::command]
+async fn check_destination(
+    state: State<'_, App>,
+    destination: Destination,
+    path: String,
+) -> Result<DestinationHealth, AppError> {
+    let mut config = state.transmission_config.lock().await.clone();
+    let path = Some(privateer_wire_types::normalize_dir(&path)).filter(|path| !path.is_empty());
+    match destination {
+        Destination::Movies => config.movies_dir = path,
+        Destination::Shows => config.shows_dir = path,
+    }
+    Ok(probe_destination(&state.fs, &config, destination).await)
+}
+
+/// How the latest polls of Transmission went.
+#[tauri::command]
+async fn get_connection_status(state: State<'_, App>) -> Result<ConnectionStatus, AppError> {
+    Ok(state.connection.lock().await.clone())
+}
+
+/// Background task that re-checks every subsystem once a minute.
+///
+/// A destination going away or coming back is reported in a toast, and its
+/// return wakes the copy task so waiting copies don't sit out the idle
+/// interval.
+async fn health_task(app: AppHandle) {
+    let mut monitor = ReachabilityMonitor::default();
+    loop {
+        let state = app.state::<App>();
+        let mut report = check_health(&state).await;
+        for problem in report.problems() {
+            log::warn!("Health: {problem}");
+        }
+        for transition in monitor.observe(&mut report.destinations, unix_now()) {
+            let message = transition.describe();
+            match transition {
+                Transition::Lost { .. } => {
+                    log::warn!("Health: {message}");
+                    emit_toast(&app, LogLevel::Warn, message);
+                }
+                Transition::Recovered { .. } => {
+                    log::info!("Health: {message}, retrying copies");
+                    emit_toast(&app, LogLevel::Info, message);
+                    state.copy_notify.notify_one();
                 }
             }
         }
+        *state.health.lock().await = Some(report);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Desktop notifications
+// ---------------------------------------------------------------------------
+
+/// Event carrying a [`Toast`] for the frontend to pop up.
+const TOAST: &str = "toast";
+
+/// Show a message in the app window, for problems worth pointing out but not
+/// worth a desktop notification.
+fn emit_toast(app: &AppHandle, level: LogLevel, message: String) {
+    if let Err(e) = app.emit(TOAST, Toast { level, message }) {
+        log::warn!("Could not emit toast: {e}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Magnet links
+// ---------------------------------------------------------------------------
 
-        if ledger_changed {
-            if let Err(e) = App::save_ledger(&ledger_path, &ledger) {
-                log::error!("Copy task: failed to save ledger after reconciliation: {e}");
+/// Event telling the frontend that [`App::pending_magnets`] changed.
+const MAGNETS_OPENED: &str = "magnets-opened";
+
+/// Handle a link opened with Privateer: add it straight away if a magnet
+/// destination is configured, otherwise queue it for the frontend to ask
+/// where it should go.  Anything but a magnet link is reported in a toast.
+fn open_magnet(app: &AppHandle, url: &str) {
+    let Some(magnet) = privateer_wire_types::parse_magnet(url) else {
+        log::warn!("ignoring malformed magnet link '{url}'");
+        emit_toast(
+            app,
+            LogLevel::Warn,
+            format!("Can't add '{url}': not a magnet link with an info hash"),
+        );
+        return;
+    };
+    log::info!("opened magnet link for '{}'", magnet.name);
+    show_main_window(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<App>();
+        let destination = state.transmission_config.lock().await.magnet_destination;
+        if let Some(destination) = destination {
+            let name = magnet.name.clone();
+            match add_magnet_link(&state, magnet, destination).await {
+                Ok(()) => emit_toast(
+                    &app,
+                    LogLevel::Info,
+                    format!("Added '{name}' to {destination}"),
+                ),
+                Err(e) => {
+                    log::error!("could not add magnet '{name}': {e}");
+                    emit_toast(&app, LogLevel::Error, format!("Can't add '{name}': {e}"));
+                }
             }
+            return;
         }
 
-        // -----------------------------------------------------------------
-        // Copy pending entries
-        // -----------------------------------------------------------------
+        {
+            let mut pending = state.pending_magnets.lock().await;
+            // macOS can report the link that launched the app twice
+            if pending.iter().any(|m| m.info_hash == magnet.info_hash) {
+                return;
+            }
+            pending.push(magnet);
+        }
+        if let Err(e) = app.emit(MAGNETS_OPENED, ()) {
+            log::warn!("Could not emit opened magnets: {e}");
+        }
+    });
+}
 
-        // Find entries eligible for copying (not yet copied, not currently copying)
-        let pending: Vec<usize> = ledger
-            .iter()
-            .enumerate()
-            .filter(|(_, e)| matches!(e.copy_state, CopyState::NotCopied | CopyState::Failed))
-            .map(|(i, _)| i)
-            .collect();
+/// Desktop notifications raised by the copy task.
+///
+/// The copy task sees every torrent on every cycle, so the notifier keeps
+/// just enough state to fire on transitions rather than on each pass.
+///
+/// The notification plugin doesn't report clicks on desktop platforms, so
+/// clicking a notification only does what the OS does by default (on macOS,
+/// bring Privateer to the front).
+struct Notifier {
+    app: AppHandle,
+    /// Info hashes seen below 100%, which notify once when they complete.
+    /// Torrents that were already complete at startup never notify.
+    downloading: HashSet<String>,
+    /// Info hashes whose copy failure has already been reported.
+    failed: HashSet<String>,
+}
 
-        if pending.is_empty() {
-            continue;
+impl Notifier {
+    fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            downloading: HashSet::new(),
+            failed: HashSet::new(),
         }
+    }
 
-        for idx in pending {
-            // Gather all needed values upfront so we don't hold a borrow on
-            // `ledger` across the mutation points below.
-            let info_hash = ledger[idx].info_hash.clone();
-            let entry_name = ledger[idx].name.clone();
-            let destination = ledger[idx].destination;
+    fn show(&self, body: String) {
+        if let Err(e) = self
+            .app
+            .notification()
+            .builder()
+            .title("Privateer")
+            .body(body)
+            .show()
+        {
+            log::warn!("Could not show notification: {e}");
+        }
+    }
+}
 
-            // Find the matching torrent in Transmission
-            let trans_torrent = transmission_torrents.iter().find(|t| {
-                t.hash_string
-                    .as_deref()
-                    .map(|h| h.eq_ignore_ascii_case(&info_hash))
-                    .unwrap_or(false)
-            });
+impl CopyEvents for Notifier {
+    /// Track a torrent's download progress, notifying when it reaches 100%.
+    fn observe_download(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        percent: f32,
+    ) {
+        if percent < 1.0 {
+            self.downloading.insert(hash.to_string());
+        } else if self.downloading.remove(hash) && config.notifications.download_complete {
+            self.show(format!("Download complete: {name}"));
+        }
+    }
 
-            let trans_torrent = match trans_torrent {
-                Some(t) => t,
-                None => continue,
-            };
+    /// A copy is about to start. A copy that was reset to `NotCopied` (e.g.
+    /// by "Retry") may report its failure again.
+    fn copy_started(
+        &mut self,
+        _config: &TransmissionConfig,
+        hash: &str,
+        _name: &str,
+        _dest: Destination,
+        previous: CopyState,
+    ) {
+        if previous == CopyState::NotCopied {
+            self.failed.remove(hash);
+        }
+    }
 
-            let percent = trans_torrent.percent_done.unwrap_or(0.0);
-            if percent < 1.0 {
-                continue;
-            }
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        _path: &Path,
+        _stats: &CopyStats,
+    ) {
+        self.failed.remove(hash);
+        if config.notifications.copy_complete {
+            self.show(format!("Copied to {dest}: {name}"));
+        }
+    }
 
-            let torrent_name = trans_torrent
-                .name
-                .clone()
-                .unwrap_or_else(|| entry_name.clone());
-            let download_dir = match trans_torrent.download_dir.as_deref() {
-                Some(d) => d.to_string(),
-                None => {
-                    log::warn!("Copy task: no download_dir for torrent '{entry_name}'");
-                    continue;
-                }
-            };
+    /// Failed copies are retried every cycle, so only the first failure
+    /// notifies.
+    fn copy_failed(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        _dest: Destination,
+        reason: &str,
+    ) {
+        if self.failed.insert(hash.to_string()) && config.notifications.copy_failed {
+            self.show(format!("Copy FAILED: {name} \u{2014} {reason}"));
+        }
+    }
 
-            let dest_dir = match config.dir_for(destination) {
-                Some(d) if !d.is_empty() => d.to_string(),
-                _ => {
-                    log::debug!(
-                        "Copy task: no destination dir configured for {destination} (torrent '{entry_name}')",
-                    );
-                    continue;
-                }
-            };
+    fn copy_collided(
+        &mut self,
+        _config: &TransmissionConfig,
+        _hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+    ) {
+        emit_toast(
+            &self.app,
+            LogLevel::Warn,
+            format!(
+                "{dest} already has different files named like '{name}', copying to '{}'",
+                path.display()
+            ),
+        );
+    }
 
-            let src_path = PathBuf::from(&download_dir).join(&torrent_name);
-            let dst_path = PathBuf::from(&dest_dir).join(&torrent_name);
+    fn cycle_panicked(&mut self, message: &str, crashes: u32) {
+        let cooldown = format_duration(supervisor::cooldown(crashes).as_secs());
+        emit_toast(
+            &self.app,
+            LogLevel::Error,
+            format!("Copying hit an internal error and will restart in {cooldown}: {message}"),
+        );
+        if crashes == UNHEALTHY_AFTER_CRASHES {
+            self.show(format!(
+                "Copying keeps crashing ({crashes} times in a row), see the log for details"
+            ));
+        }
+    }
+}
 
-            // Already at destination — mark Copied without re-copying
-            if dst_path.exists() {
-                log::info!(
-                    "Copy task: '{}' already exists at destination, marking copied",
-                    torrent_name
-                );
-                ledger[idx].copy_state = CopyState::Copied;
-                let _ = App::save_ledger(&ledger_path, &ledger);
-                continue;
-            }
+// ---------------------------------------------------------------------------
+// Background copy task
+// ---------------------------------------------------------------------------
 
-            if !src_path.exists() {
-                log::warn!(
-                    "Copy task: source '{}' does not exist, skipping",
-                    src_path.display()
-                );
-                continue;
-            }
+/// Upper bound on the copy task's RPC timeout.
+const COPY_TASK_RPC_TIMEOUT: Duration = Duration::from_secs(5);
 
-            // Transition: → Copying  (persist immediately so the UI updates)
-            ledger[idx].copy_state = CopyState::Copying;
-            if let Err(e) = App::save_ledger(&ledger_path, &ledger) {
-                log::error!("Copy task: failed to save ledger (Copying): {e}");
-            }
+/// The downloads ledger file, saved under the lock on `ledger`, the
+/// commands' copy of it when run by the desktop app.
+struct LedgerFile {
+    path: PathBuf,
+    ledger: Arc<Mutex<Vec<DownloadEntry>>>,
+}
 
-            log::info!(
-                "Copy task: copying '{}' -> '{}'",
-                src_path.display(),
-                dst_path.display()
-            );
+impl LedgerFile {
+    /// The ledger file at `path`, locked by nothing else.
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            ledger: Arc::default(),
+        }
+    }
+}
 
-            match copy_recursive_async(&src_path, &dst_path).await {
-                Ok(()) => {
-                    log::info!("Copy task: successfully copied '{}'", torrent_name);
-                    ledger[idx].copy_state = CopyState::Copied;
-                }
-                Err(e) => {
-                    log::error!("Copy task: failed to copy '{}': {e}", torrent_name);
-                    ledger[idx].copy_state = CopyState::Failed;
-                    // Clean up partial copy on failure
-                    if dst_path.exists() {
-                        let _ = if dst_path.is_dir() {
-                            tokio::fs::remove_dir_all(&dst_path).await
-                        } else {
-                            tokio::fs::remove_file(&dst_path).await
-                        };
+impl LedgerStore for LedgerFile {
+    async fn load(&self) -> Result<Vec<DownloadEntry>, ConfigError> {
+        let mut shared = self.ledger.lock().await;
+        *shared = App::load_ledger(&self.path)?;
+        Ok(shared.clone())
+    }
+
+    async fn update(
+        &self,
+        change: impl FnOnce(&mut Vec<DownloadEntry>) -> bool,
+    ) -> Result<(), ConfigError> {
+        let mut shared = self.ledger.lock().await;
+        let mut ledger = App::load_ledger(&self.path)?;
+        if change(&mut ledger) {
+            App::save_ledger(&self.path, &ledger)?;
+        }
+        *shared = ledger;
+        Ok(())
+    }
+}
+
+/// Lists torrents with its own client, so the copy task never waits on the
+/// commands' shared client.  The client is replaced when the active profile
+/// changes, and dropped after a connection error.
+#[derive(Default)]
+struct ClientSource(Option<(TransmissionConfig, Client)>);
+
+impl ClientSource {
+    fn client(&mut self, config: &TransmissionConfig) -> Result<&mut Client, ClientError> {
+        let current = self.0.take().filter(|(c, _)| same_connection(c, config));
+        let client = match current {
+            Some((_, client)) => client,
+            // Use a short timeout so a dead daemon doesn't hold up the cycle
+            None => Client::new(config, config.rpc_timeout().min(COPY_TASK_RPC_TIMEOUT))?,
+        };
+        Ok(&mut self.0.insert((config.clone(), client)).1)
+    }
+
+    /// Pass `result` on, dropping the client if it couldn't connect.
+    fn checked<T>(&mut self, result: Result<T, ClientError>) -> Result<T, ClientError> {
+        if result.as_ref().is_err_and(ClientError::is_connection) {
+            self.0 = None;
+        }
+        result
+    }
+}
+
+/// A torrent from the download client, as the copy engine sees it.
+fn source_torrent(t: ClientTorrent) -> SourceTorrent {
+    SourceTorrent {
+        info_hash: t.hash,
+        name: Some(t.name),
+        percent_done: t.percent_done as f32,
+        status: t.status,
+        download_dir: t.download_dir,
+        done_date: Some(t.done_date).filter(|d| *d > 0),
+        upload_ratio: t.upload_ratio,
+        seconds_seeding: t.seconds_seeding,
+    }
+}
+
+impl TorrentSource for ClientSource {
+    async fn torrents(
+        &mut self,
+        config: &TransmissionConfig,
+    ) -> Result<Vec<SourceTorrent>, ClientError> {
+        let result = self.client(config)?.torrents().await;
+        Ok(self
+            .checked(result)?
+            .into_iter()
+            .map(source_torrent)
+            .collect())
+    }
+
+    async fn stop(
+        &mut self,
+        config: &TransmissionConfig,
+        hashes: &[InfoHash],
+    ) -> Result<(), ClientError> {
+        let result = self.client(config)?.pause(hashes).await;
+        self.checked(result)
+    }
+
+    async fn files(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &InfoHash,
+    ) -> Result<Vec<TorrentFile>, ClientError> {
+        let result = self.client(config)?.files(hash).await;
+        self.checked(result)
+    }
+}
+
+/// A `reconcile_now` waiting for the copy task's answer.
+type ReconcileRequest = oneshot::Sender<ReconcileSummary>;
+
+/// Background copy task that reads config/ledger from disk each cycle and
+/// hands them to the [`Copier`].
+///
+/// See [`CopySchedule`] for when cycles run.  While `pause` is set no copy
+/// starts, see [`Copier::with_pause`].  Requests on `reconcile` are answered
+/// between cycles, so they never race one.  Once `stop` is set the task
+/// returns, after the copy in progress if there is one.
+///
+/// A cycle that panics is logged and reported on `engine`, and the task
+/// carries on after a cooldown, see [`supervisor`].
+#[allow(clippy::too_many_arguments)]
+async fn copy_task_from_disk(
+    config_path: PathBuf,
+    ledger: LedgerFile,
+    fs: TokioFs,
+    notify: Arc<Notify>,
+    mut events: impl CopyEvents,
+    engine: watch::Sender<CopyEngineStatus>,
+    pause: Option<watch::Receiver<bool>>,
+    mut reconcile: Option<mpsc::Receiver<ReconcileRequest>>,
+    mut stop: Option<watch::Receiver<bool>>,
+) {
+    let mut copier = Copier::new(ledger, ClientSource::default(), fs);
+    if let Some(pause) = pause {
+        copier = copier.with_pause(pause);
+    }
+    copier.fail_interrupted().await;
+    if let Some(stop) = &stop {
+        copier = copier.with_stop(stop.clone());
+    }
+    let mut schedule = CopySchedule::new(notify);
+    let stopped = async {
+        if let Some(stop) = &mut stop {
+            if stop.wait_for(|stop| *stop).await.is_ok() {
+                return;
+            }
+        }
+        std::future::pending().await
+    };
+    let mut stopped = std::pin::pin!(stopped);
+    loop {
+        let request = async {
+            match &mut reconcile {
+                Some(requests) => requests.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+        let wake = tokio::select! {
+            wake = schedule.wait() => wake,
+            Some(reply) = request => {
+                let config = App::load_config(&config_path);
+                let reconciled = copier.reconcile_now(&config, &mut events);
+                match supervisor::catch_panic(reconciled).await {
+                    Ok(summary) => {
+                        log::info!("Copy task: {}", summary.describe());
+                        let _ = reply.send(summary);
                     }
+                    // Dropping the reply fails the request
+                    Err(message) => log::error!("Copy task: reconciling panicked: {message}"),
                 }
+                continue;
             }
+            _ = &mut stopped => break,
+        };
+        match wake {
+            CopyWake::Notified => log::info!("Copy task: woken up by add_download"),
+            CopyWake::Startup | CopyWake::Busy | CopyWake::Interval => {}
+        }
+        let config = App::load_config(&config_path);
+        match supervisor::catch_panic(copier.run_cycle(&config, &mut events)).await {
+            Ok(outcome) => {
+                engine.send_if_modified(|status| {
+                    let crashed = status.crashes > 0;
+                    *status = CopyEngineStatus::default();
+                    crashed
+                });
+                schedule.finished(outcome);
+            }
+            Err(message) => {
+                let crashes = engine.borrow().crashes + 1;
+                log::error!("Copy task: cycle panicked ({crashes} in a row): {message}");
+                engine.send_replace(CopyEngineStatus {
+                    crashes,
+                    last_panic: Some(message.clone()),
+                });
+                events.cycle_panicked(&message, crashes);
+                // The panic may have left a copy marked in progress
+                copier.fail_interrupted().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(supervisor::cooldown(crashes)) => {}
+                    _ = &mut stopped => break,
+                }
+            }
+        }
+    }
+    log::info!("Copy task: stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use copy_schedule::CycleOutcome;
+    use mock_transmission::MockTransmission;
+    use privateer_wire_types::ErrorKind;
+
+    const PENDING: &str = "1111111111111111111111111111111111111111";
+    const COPIED_BY_HAND: &str = "2222222222222222222222222222222222222222";
+    const UNTRACKED: &str = "3333333333333333333333333333333333333333";
+    const DOWNLOADING: &str = "4444444444444444444444444444444444444444";
+
+    /// A fresh data directory with `downloads` and `movies` in it, for one
+    /// test.
+    struct Dirs {
+        root: PathBuf,
+        downloads: PathBuf,
+        movies: PathBuf,
+    }
 
-            // Persist Copied/Failed state
-            if let Err(e) = App::save_ledger(&ledger_path, &ledger) {
-                log::error!("Copy task: failed to save ledger: {e}");
+    impl Dirs {
+        fn new(test: &str) -> Self {
+            let root =
+                std::env::temp_dir().join(format!("privateer-app-{}-{test}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            let downloads = root.join("downloads");
+            let movies = root.join("movies");
+            std::fs::create_dir_all(&downloads).unwrap();
+            std::fs::create_dir_all(&movies).unwrap();
+            Self {
+                root,
+                downloads,
+                movies,
             }
         }
+
+        fn paths(&self) -> DataPaths {
+            DataPaths::new(self.root.clone())
+        }
+
+        /// A torrent downloaded into `downloads`, as the mock lists it.
+        fn torrent(&self, id: i64, hash: &str, name: &str) -> serde_json::Value {
+            let dir = self.downloads.display().to_string();
+            mock_transmission::torrent(id, hash, name, &dir)
+        }
+
+        /// Put the single file of the torrent `name` in `dir`, as the mock
+        /// lists it.
+        fn files(dir: &Path, name: &str) {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            std::fs::write(dir.join(name).join(format!("{name}.mkv")), [0; 1000]).unwrap();
+        }
+
+        /// Save a config for `mock` with `movies` as the movies directory,
+        /// and `ledger`.
+        fn save(&self, mock: &MockTransmission, ledger: &[DownloadEntry]) -> TransmissionConfig {
+            let config = TransmissionConfig {
+                movies_dir: Some(self.movies.display().to_string()),
+                ..mock.config()
+            };
+            let paths = self.paths();
+            App::save_config(&paths.config, &config).unwrap();
+            App::save_ledger(&paths.ledger, ledger).unwrap();
+            config
+        }
+    }
+
+    impl Drop for Dirs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// A movie the user added, not copied yet.
+    fn entry(hash: &str, name: &str) -> DownloadEntry {
+        DownloadEntry::new(hash.parse().unwrap(), name.to_string(), Destination::Movies)
+    }
+
+    fn find<'a>(torrents: &'a [TransmissionTorrent], hash: &str) -> &'a TransmissionTorrent {
+        torrents
+            .iter()
+            .find(|t| t.hash_string.as_str() == hash)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn torrents_are_cross_referenced_with_the_ledger() {
+        let dirs = Dirs::new("torrents");
+        let mock = MockTransmission::start(vec![
+            dirs.torrent(1, PENDING, "Pending.Movie.2024"),
+            dirs.torrent(2, COPIED_BY_HAND, "Copied.Movie.2020"),
+            dirs.torrent(3, UNTRACKED, "Untracked.Movie.2021"),
+            dirs.torrent(4, "not-a-hash", "Broken.Movie.2022"),
+        ])
+        .await;
+        Dirs::files(&dirs.movies, "Copied.Movie.2020");
+        dirs.save(&mock, &[entry(PENDING, "Pending.Movie.2024")]);
+        let app = App::in_dir(&dirs.root);
+
+        let torrents = fetch_torrents(&app, true).await.unwrap();
+        assert_eq!(3, torrents.len());
+        let pending = find(&torrents, PENDING);
+        assert_eq!(
+            (
+                Some(Destination::Movies),
+                CopyState::NotCopied,
+                Some(DownloadOrigin::User)
+            ),
+            (pending.destination, pending.copy_state, pending.origin)
+        );
+        let copied = find(&torrents, COPIED_BY_HAND);
+        assert_eq!(
+            (
+                Some(Destination::Movies),
+                CopyState::Copied,
+                Some(DownloadOrigin::AutoDetected)
+            ),
+            (copied.destination, copied.copy_state, copied.origin)
+        );
+        let untracked = find(&torrents, UNTRACKED);
+        assert_eq!((None, None), (untracked.destination, untracked.origin));
+
+        // Recomputed from the cached list
+        app.transmission_config.lock().await.only_assigned = true;
+        let torrents = fetch_torrents(&app, false).await.unwrap();
+        let copied = find(&torrents, COPIED_BY_HAND);
+        assert_eq!((None, None), (copied.destination, copied.origin));
+        // The daemon is asked its version once, on connecting
+        assert_eq!(["session-get", "torrent-get"], mock.calls().as_slice());
+        let daemon = app.daemon.lock().await.clone().unwrap();
+        assert_eq!(Some(17), daemon.rpc_version);
+    }
+
+    /// A share whose server has gone away: every check hangs.
+    struct DeadShare;
+
+    impl path_probe::ExistsCheck for DeadShare {
+        fn exists(&self, path: &Path) -> bool {
+            std::thread::sleep(Duration::from_secs(2));
+            path.exists()
+        }
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_destination_does_not_hold_up_the_torrents() {
+        let dirs = Dirs::new("dead-share");
+        let mock = MockTransmission::start(vec![
+            dirs.torrent(1, PENDING, "Pending.Movie.2024"),
+            dirs.torrent(3, UNTRACKED, "Untracked.Movie.2021"),
+        ])
+        .await;
+        Dirs::files(&dirs.movies, "Pending.Movie.2024");
+        Dirs::files(&dirs.movies, "Untracked.Movie.2021");
+        dirs.save(&mock, &[entry(PENDING, "Pending.Movie.2024")]);
+        let mut app = App::in_dir(&dirs.root);
+        let probe =
+            path_probe::PathProbe::new(DeadShare, Duration::from_millis(20), path_probe::CACHE_TTL);
+        app.fs = TokioFs::new(probe);
+
+        let started = Instant::now();
+        let torrents = fetch_torrents(&app, true).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(CopyState::NotCopied, find(&torrents, PENDING).copy_state);
+        assert_eq!(None, find(&torrents, UNTRACKED).destination);
+    }
+
+    #[tokio::test]
+    async fn copy_cycle_copies_what_the_daemon_has_finished() {
+        let dirs = Dirs::new("copy-cycle");
+        let mut downloading = dirs.torrent(3, DOWNLOADING, "Downloading.Movie.2023");
+        downloading["status"] = 4.into();
+        downloading["percentDone"] = 0.5.into();
+        let mock = MockTransmission::start(vec![
+            dirs.torrent(1, PENDING, "Pending.Movie.2024"),
+            dirs.torrent(2, COPIED_BY_HAND, "Copied.Movie.2020"),
+            downloading,
+        ])
+        .await;
+        Dirs::files(&dirs.downloads, "Pending.Movie.2024");
+        Dirs::files(&dirs.downloads, "Downloading.Movie.2023");
+        Dirs::files(&dirs.movies, "Copied.Movie.2020");
+        let config = dirs.save(
+            &mock,
+            &[
+                entry(PENDING, "Pending.Movie.2024"),
+                entry(DOWNLOADING, "Downloading.Movie.2023"),
+            ],
+        );
+        let paths = dirs.paths();
+        let metrics = Arc::new(MetricsFile::new(paths.metrics()));
+        let mut events = MetricsRecorder::new(metrics.clone());
+        let mut copier = Copier::new(
+            LedgerFile::new(paths.ledger.clone()),
+            ClientSource::default(),
+            TokioFs::default(),
+        );
+
+        let outcome = copier.run_cycle(&config, &mut events).await;
+
+        assert_eq!(CycleOutcome::Worked, outcome);
+        let copy = dirs.movies.join("Pending.Movie.2024");
+        assert!(copy.join("Pending.Movie.2024.mkv").is_file());
+        assert!(!dirs.movies.join("Downloading.Movie.2023").exists());
+        let ledger = App::load_ledger(&paths.ledger).unwrap();
+        let state = |hash: &str| {
+            let entry = ledger.iter().find(|e| e.info_hash.as_str() == hash);
+            entry.map(|entry| (entry.copy_state, entry.origin))
+        };
+        assert_eq!(
+            Some((CopyState::Copied, DownloadOrigin::User)),
+            state(PENDING)
+        );
+        assert_eq!(
+            Some((CopyState::Copied, DownloadOrigin::AutoDetected)),
+            state(COPIED_BY_HAND)
+        );
+        assert_eq!(
+            Some((CopyState::NotCopied, DownloadOrigin::User)),
+            state(DOWNLOADING)
+        );
+        assert_eq!(1, metrics.load().copies_completed);
+    }
+
+    #[tokio::test]
+    async fn reconcile_now_copies_nothing() {
+        let dirs = Dirs::new("reconcile");
+        let mock = MockTransmission::start(vec![
+            dirs.torrent(1, PENDING, "Pending.Movie.2024"),
+            dirs.torrent(2, COPIED_BY_HAND, "Copied.Movie.2020"),
+        ])
+        .await;
+        Dirs::files(&dirs.downloads, "Pending.Movie.2024");
+        Dirs::files(&dirs.movies, "Copied.Movie.2020");
+        let config = dirs.save(&mock, &[entry(PENDING, "Pending.Movie.2024")]);
+        let paths = dirs.paths();
+        let mut copier = Copier::new(
+            LedgerFile::new(paths.ledger.clone()),
+            ClientSource::default(),
+            TokioFs::default(),
+        );
+        let mut events = MetricsRecorder::new(Arc::new(MetricsFile::new(paths.metrics())));
+
+        let summary = copier.reconcile_now(&config, &mut events).await;
+
+        assert_eq!(
+            ReconcileSummary {
+                auto_added: 1,
+                ..Default::default()
+            },
+            summary
+        );
+        assert!(!dirs.movies.join("Pending.Movie.2024").exists());
+        assert_eq!(2, App::load_ledger(&paths.ledger).unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn damaged_ledgers_are_left_alone() {
+        let dirs = Dirs::new("damaged-ledger");
+        let paths = dirs.paths();
+        let damaged = r#"[{"info_hash":"#;
+        std::fs::write(&paths.ledger, damaged).unwrap();
+        let app = App::in_dir(&dirs.root);
+
+        assert!(matches!(
+            App::load_ledger(&paths.ledger),
+            Err(ConfigError::LedgerParse { .. })
+        ));
+        assert!(app.lock_ledger().await.is_err());
+        let pending = entry(PENDING, "Pending.Movie.2024");
+        let added = app.ledger_file().update(|ledger| {
+            ledger.push(pending);
+            true
+        });
+        assert!(added.await.is_err());
+        assert_eq!(damaged, std::fs::read_to_string(&paths.ledger).unwrap());
+    }
+
+    #[tokio::test]
+    async fn damaged_removed_downloads_are_left_alone() {
+        let dirs = Dirs::new("damaged-removed");
+        let paths = dirs.paths();
+        let removed_path = data_paths::removed_beside(&paths.ledger);
+        let damaged = r#"[{"entry":"#;
+        std::fs::write(&removed_path, damaged).unwrap();
+        let app = App::in_dir(&dirs.root);
+
+        assert!(matches!(
+            App::load_removed(&paths.ledger),
+            Err(ConfigError::LedgerParse { .. })
+        ));
+        assert!(expire_removed_downloads(&app).await.is_err());
+        assert_eq!(damaged, std::fs::read_to_string(&removed_path).unwrap());
+    }
+
+    #[tokio::test]
+    async fn failed_polls_are_recorded() {
+        let dirs = Dirs::new("failed-poll");
+        let mock = MockTransmission::start(vec![]).await;
+        dirs.save(&mock, &[]);
+        let app = App::in_dir(&dirs.root);
+        mock.fail_with(500);
+
+        let error = fetch_torrents(&app, true).await.unwrap_err();
+        assert_eq!(ErrorKind::TransmissionConnection, error.kind);
+        assert_eq!(1, app.connection.lock().await.failures);
+    }
+
+    /// Answers every request with a fixed status, counting them.
+    struct Answering(Result<u16, String>, std::cell::Cell<usize>);
+
+    impl HttpClient for Answering {
+        async fn send(&self, request: HttpRequest) -> Result<u16, HttpError> {
+            self.1.set(self.1.get() + 1);
+            let url = request.url.to_string();
+            self.0
+                .clone()
+                .map_err(|message| HttpError::Transport { url, message })
+        }
+    }
+
+    #[tokio::test]
+    async fn search_health_only_asks_whether_apibay_answers() {
+        let answering = |status| Answering(status, std::cell::Cell::new(0));
+        let http = answering(Ok(200));
+        assert!(search_health(&http).await.ok);
+        assert_eq!(1, http.1.get());
+        assert!(search_health(&answering(Ok(404))).await.ok);
+
+        let down = search_health(&answering(Ok(502))).await;
+        assert!(!down.ok);
+        assert!(down.detail.contains("502"), "{}", down.detail);
+        let refused = answering(Err("connection refused".to_string()));
+        assert!(!search_health(&refused).await.ok);
+    }
+
+    #[test]
+    fn downloads_that_wont_fit_make_the_client_unhealthy() {
+        let torrent = |percent_done, size_when_done| ClientTorrent {
+            percent_done,
+            size_when_done,
+            ..Default::default()
+        };
+        let left = bytes_left(&[torrent(0.25, 4000), torrent(1.0, 9000)]);
+        assert_eq!(3000, left);
+
+        let units = SizeUnits::default();
+        assert!(client_health("Transmission 4.0.5", 3000, left, units).ok);
+        let health = client_health("Transmission 4.0.5", 2000, left, units);
+        assert!(!health.ok);
+        assert!(health.detail.contains("not enough space"));
+    }
+
+    #[tokio::test]
+    async fn volume_space_is_only_known_for_directories() {
+        let (total, free) = volume_space(std::env::temp_dir()).await;
+        assert!(total.is_some() && free.is_some());
+
+        let missing = std::env::temp_dir().join("privateer-no-such-volume");
+        assert_eq!((None, None), volume_space(missing).await);
     }
 }
@@ -0,0 +1,176 @@
+//! File logging.
+//!
+//! Records go to a size-rotated log file in the app data dir so there is a
+//! history of what the background tasks did, and to stderr as well in debug
+//! builds.  Key-value fields (e.g. `torrent = name`) are appended to each line
+//! as `key="value"` so they are easy to grep for.
+
+use std::path::{Path, PathBuf};
+
+use flexi_logger::{
+    Cleanup, Criterion, DeferredNow, Duplicate, FileSpec, FlexiLoggerError, Logger, LoggerHandle,
+    Naming,
+};
+use privateer_wire_types::{LogLevel, LogLine};
+use snafu::ResultExt;
+
+use crate::error::{LogError, ReadLogSnafu};
+
+/// Log file base name; the current file is `privateer.log`.
+const BASENAME: &str = "privateer";
+
+/// Rotate after this many bytes.
+const ROTATE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Number of rotated files to keep.
+const KEEP_FILES: usize = 5;
+
+/// Managed state for the file logger.
+pub struct Logging {
+    /// Keeps the logger alive; dropping it flushes and stops file logging.
+    _handle: Option<LoggerHandle>,
+    path: PathBuf,
+}
+
+impl Logging {
+    /// Start logging into `dir`.  If the file logger can't be started, falls
+    /// back to stderr so the app still runs.
+    ///
+    /// The level filter comes from `RUST_LOG`, defaulting to `info`.
+    /// Panics are logged too, see [`log_panics`].
+    pub fn init(dir: &Path) -> Self {
+        log_panics();
+        let path = dir.join(format!("{BASENAME}.log"));
+        match start(dir) {
+            Ok(handle) => Self {
+                _handle: Some(handle),
+                path,
+            },
+            Err(e) => {
+                eprintln!("Could not start file logging in {}: {e}", dir.display());
+                Self {
+                    _handle: Logger::try_with_env_or_str("info")
+                        .and_then(|logger| logger.start())
+                        .ok(),
+                    path,
+                }
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The last `count` lines of the current log file.
+    pub fn recent_lines(&self, count: usize) -> Result<Vec<LogLine>, LogError> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e).context(ReadLogSnafu { path: &self.path }),
+        };
+        let lines = parse_lines(&contents);
+        let skip = lines.len().saturating_sub(count);
+        Ok(lines.into_iter().skip(skip).collect())
+    }
+}
+
+/// Log every panic with its backtrace before the default hook reports it, so
+/// a background task that panics leaves a trace in the log file.
+fn log_panics() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        log::error!("{info}\n{backtrace}");
+        previous(info);
+    }));
+}
+
+fn start(dir: &Path) -> Result<LoggerHandle, FlexiLoggerError> {
+    let duplicate = if cfg!(debug_assertions) {
+        Duplicate::All
+    } else {
+        Duplicate::None
+    };
+    Logger::try_with_env_or_str("info")?
+        .log_to_file(
+            FileSpec::default()
+                .directory(dir)
+                .basename(BASENAME)
+                .suppress_timestamp(),
+        )
+        .append()
+        .rotate(
+            Criterion::Size(ROTATE_BYTES),
+            Naming::NumbersDirect,
+            Cleanup::KeepLogFiles(KEEP_FILES),
+        )
+        .format_for_files(file_format)
+        .duplicate_to_stderr(duplicate)
+        .start()
+}
+
+/// `[timestamp] LEVEL [module] message key="value"...`
+fn file_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &log::Record,
+) -> std::io::Result<()> {
+    write!(
+        w,
+        "[{}] {:<5} [{}] {}",
+        now.format(flexi_logger::TS_DASHES_BLANK_COLONS_DOT_BLANK),
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>"),
+        record.args(),
+    )?;
+    let mut fields = Fields { w, result: Ok(()) };
+    // Visiting only fails if a write does, which is kept in `result`
+    let _ = record.key_values().visit(&mut fields);
+    fields.result
+}
+
+/// Writes a record's key-value pairs as ` key="value"`.
+struct Fields<'a> {
+    w: &'a mut dyn std::io::Write,
+    result: std::io::Result<()>,
+}
+
+impl<'kvs> log::kv::VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        if let Err(e) = write!(self.w, " {key}=\"{value}\"") {
+            self.result = Err(e);
+            return Err(log::kv::Error::msg("write failed"));
+        }
+        Ok(())
+    }
+}
+
+/// Split log file contents into lines, tagging each with the level of the
+/// record it belongs to.
+fn parse_lines(contents: &str) -> Vec<LogLine> {
+    let mut level = LogLevel::Info;
+    contents
+        .lines()
+        .map(|text| {
+            if let Some(record_level) = record_level(text) {
+                level = record_level;
+            }
+            LogLine {
+                level,
+                text: text.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// The level of a line that starts a record, `None` for continuation lines.
+fn record_level(line: &str) -> Option<LogLevel> {
+    let rest = line.strip_prefix('[')?;
+    let (_timestamp, rest) = rest.split_once(']')?;
+    LogLevel::from_label(rest.split_whitespace().next()?)
+}
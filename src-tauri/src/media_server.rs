@@ -0,0 +1,367 @@
+//! Library scans on Plex or Jellyfin after a copy.
+//!
+//! Media servers only notice new files on their scheduled scan, which can be
+//! hours away.  After each copy the copy task queues a partial scan of the
+//! copied path; [`scan_task`] sends them one at a time.  A scan that fails is
+//! reported but never changes the copy's state.
+
+use std::path::{Path, PathBuf};
+
+use privateer_wire_types::{
+    CopyState, Destination, MediaServerConfig, MediaServerKind, TransmissionConfig,
+};
+use snafu::{OptionExt, ResultExt};
+use tokio::sync::mpsc;
+
+use crate::copier::{CopyEvents, CopyStats};
+use crate::error::*;
+use crate::http::{HttpClient, HttpRequest};
+
+/// Jellyfin's header for API keys.
+const JELLYFIN_TOKEN_HEADER: &str = "X-Emby-Token";
+
+/// `base_url` with `path` appended.
+fn endpoint(config: &MediaServerConfig, path: &str) -> Result<url::Url, MediaServerError> {
+    let url = format!("{}{path}", config.base_url.trim().trim_end_matches('/'));
+    url.parse().context(MediaServerUrlSnafu { url })
+}
+
+/// The request asking the server to rescan `path`, which was just copied to
+/// `destination`.
+pub fn scan_request(
+    config: &MediaServerConfig,
+    destination: Destination,
+    path: &Path,
+) -> Result<HttpRequest, MediaServerError> {
+    match config.kind {
+        MediaServerKind::Plex => {
+            let section = config
+                .section_for(destination)
+                .context(MediaServerNoSectionSnafu { destination })?;
+            let mut url = endpoint(config, &format!("/library/sections/{section}/refresh"))?;
+            url.query_pairs_mut()
+                .append_pair("path", &path.display().to_string())
+                .append_pair("X-Plex-Token", &config.token);
+            Ok(HttpRequest::get(url))
+        }
+        MediaServerKind::Jellyfin => {
+            let url = endpoint(config, "/Library/Media/Updated")?;
+            let body = serde_json::json!({
+                "Updates": [{
+                    "Path": path.display().to_string(),
+                    "UpdateType": "Created",
+                }]
+            });
+            Ok(HttpRequest::post_json(url, body.to_string())
+                .header(JELLYFIN_TOKEN_HEADER, &config.token))
+        }
+    }
+}
+
+/// A request that only succeeds with a reachable server and a valid token.
+fn test_request(config: &MediaServerConfig) -> Result<HttpRequest, MediaServerError> {
+    match config.kind {
+        MediaServerKind::Plex => {
+            let mut url = endpoint(config, "/library/sections")?;
+            url.query_pairs_mut()
+                .append_pair("X-Plex-Token", &config.token);
+            Ok(HttpRequest::get(url))
+        }
+        MediaServerKind::Jellyfin => Ok(HttpRequest::get(endpoint(config, "/System/Info")?)
+            .header(JELLYFIN_TOKEN_HEADER, &config.token)),
+    }
+}
+
+async fn send(
+    client: &impl HttpClient,
+    kind: MediaServerKind,
+    request: HttpRequest,
+) -> Result<u16, MediaServerError> {
+    let status = client
+        .send(request)
+        .await
+        .context(MediaServerRequestSnafu { kind })?;
+    if (200..300).contains(&status) {
+        Ok(status)
+    } else {
+        MediaServerStatusSnafu { kind, status }.fail()
+    }
+}
+
+/// Ask the server to rescan `path`.
+pub async fn scan(
+    client: &impl HttpClient,
+    config: &MediaServerConfig,
+    destination: Destination,
+    path: &Path,
+) -> Result<(), MediaServerError> {
+    let request = scan_request(config, destination, path)?;
+    send(client, config.kind, request).await?;
+    Ok(())
+}
+
+/// Check that the server is reachable and accepts the token.
+pub async fn test(
+    client: &impl HttpClient,
+    config: &MediaServerConfig,
+) -> Result<String, MediaServerError> {
+    let status = send(client, config.kind, test_request(config)?).await?;
+    Ok(format!("{} responded with HTTP {status}.", config.kind))
+}
+
+/// A scan queued by [`MediaScanner`].
+pub struct ScanJob {
+    config: MediaServerConfig,
+    destination: Destination,
+    path: PathBuf,
+    name: String,
+}
+
+/// Queues a scan for every completed copy, when a media server is set up.
+pub struct MediaScanner(mpsc::UnboundedSender<ScanJob>);
+
+impl MediaScanner {
+    /// The scanner and the queue to hand to [`scan_task`].
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<ScanJob>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self(tx), rx)
+    }
+}
+
+impl CopyEvents for MediaScanner {
+    fn observe_download(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: f32) {}
+
+    fn copy_started(
+        &mut self,
+        _: &TransmissionConfig,
+        _: &str,
+        _: &str,
+        _: Destination,
+        _: CopyState,
+    ) {
+    }
+
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        _hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+        _stats: &CopyStats,
+    ) {
+        let Some(media_server) = config.media_server.clone() else {
+            return;
+        };
+        let _ = self.0.send(ScanJob {
+            config: media_server,
+            destination: dest,
+            path: path.to_path_buf(),
+            name: name.to_string(),
+        });
+    }
+
+    fn copy_failed(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: Destination, _: &str) {}
+}
+
+/// Send queued scans until the scanner is dropped, calling `on_failure`
+/// with a message for each one that fails.
+pub async fn scan_task(
+    mut jobs: mpsc::UnboundedReceiver<ScanJob>,
+    client: impl HttpClient,
+    on_failure: impl Fn(String),
+) {
+    while let Some(job) = jobs.recv().await {
+        match scan(&client, &job.config, job.destination, &job.path).await {
+            Ok(()) => log::info!(
+                torrent = job.name.as_str();
+                "Asked {} to scan '{}'",
+                job.config.kind,
+                job.path.display()
+            ),
+            Err(e) => {
+                log::warn!(torrent = job.name.as_str(); "Library scan failed: {e}");
+                on_failure(format!("Library scan for '{}' failed: {e}", job.name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use std::cell::RefCell;
+
+    /// Records requests and answers with a fixed status.
+    struct MockClient {
+        status: Result<u16, String>,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+
+    impl MockClient {
+        fn answering(status: Result<u16, String>) -> Self {
+            Self {
+                status,
+                requests: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl HttpClient for MockClient {
+        async fn send(&self, request: HttpRequest) -> Result<u16, HttpError> {
+            let url = request.url.to_string();
+            self.requests.borrow_mut().push(request);
+            self.status
+                .clone()
+                .map_err(|message| HttpError::Transport { url, message })
+        }
+    }
+
+    fn plex() -> MediaServerConfig {
+        MediaServerConfig {
+            kind: MediaServerKind::Plex,
+            base_url: "http://nas.local:32400/".into(),
+            token: "secret".into(),
+            movies_section: Some(3),
+            shows_section: None,
+        }
+    }
+
+    fn jellyfin() -> MediaServerConfig {
+        MediaServerConfig {
+            kind: MediaServerKind::Jellyfin,
+            base_url: "http://nas.local:8096".into(),
+            token: "key".into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn plex_refreshes_the_destination_section() {
+        let client = MockClient::answering(Ok(200));
+        let path = Path::new("/Volumes/Media/Movies/Some Movie (2024)");
+
+        scan(&client, &plex(), Destination::Movies, path)
+            .await
+            .unwrap();
+
+        let requests = client.requests.borrow();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Get);
+        assert_eq!(
+            requests[0].url.as_str(),
+            "http://nas.local:32400/library/sections/3/refresh\
+             ?path=%2FVolumes%2FMedia%2FMovies%2FSome+Movie+%282024%29&X-Plex-Token=secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn plex_without_a_section_sends_nothing() {
+        let client = MockClient::answering(Ok(200));
+
+        let result = scan(&client, &plex(), Destination::Shows, Path::new("/tv/Show")).await;
+
+        assert!(matches!(
+            result,
+            Err(MediaServerError::MediaServerNoSection { .. })
+        ));
+        assert!(client.requests.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn jellyfin_reports_the_updated_path() {
+        let client = MockClient::answering(Ok(204));
+
+        scan(
+            &client,
+            &jellyfin(),
+            Destination::Shows,
+            Path::new("/tv/Show"),
+        )
+        .await
+        .unwrap();
+
+        let requests = client.requests.borrow();
+        let request = &requests[0];
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(
+            request.url.as_str(),
+            "http://nas.local:8096/Library/Media/Updated"
+        );
+        assert!(request
+            .headers
+            .contains(&(JELLYFIN_TOKEN_HEADER.to_string(), "key".to_string())));
+        let body: serde_json::Value =
+            serde_json::from_str(request.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["Updates"][0]["Path"], "/tv/Show");
+    }
+
+    #[tokio::test]
+    async fn error_statuses_and_transport_failures_are_errors() {
+        let client = MockClient::answering(Ok(401));
+        let result = scan(&client, &jellyfin(), Destination::Movies, Path::new("/m")).await;
+        assert!(matches!(
+            result,
+            Err(MediaServerError::MediaServerStatus { status: 401, .. })
+        ));
+
+        let client = MockClient::answering(Err("connection refused".into()));
+        let result = test(&client, &plex()).await;
+        assert!(matches!(
+            result,
+            Err(MediaServerError::MediaServerRequest { .. })
+        ));
+    }
+
+    #[test]
+    fn the_path_copied_to_is_scanned() {
+        let (mut scanner, mut jobs) = MediaScanner::new();
+        let config = TransmissionConfig {
+            movies_dir: Some("/movies".into()),
+            media_server: Some(jellyfin()),
+            ..Default::default()
+        };
+        // Copied beside other files with the same name
+        let path = Path::new("/movies/Movie (2)");
+        scanner.copy_complete(
+            &config,
+            "abc",
+            "Movie",
+            Destination::Movies,
+            path,
+            &CopyStats::default(),
+        );
+        assert_eq!(jobs.try_recv().unwrap().path, path);
+    }
+
+    #[tokio::test]
+    async fn failed_scans_are_reported() {
+        let (mut scanner, jobs) = MediaScanner::new();
+        let config = TransmissionConfig {
+            movies_dir: Some("/movies".into()),
+            media_server: Some(jellyfin()),
+            ..Default::default()
+        };
+        scanner.copy_complete(
+            &config,
+            "abc",
+            "Movie",
+            Destination::Movies,
+            Path::new("/movies/Movie"),
+            &CopyStats::default(),
+        );
+        drop(scanner);
+
+        let failures = RefCell::new(vec![]);
+        scan_task(jobs, MockClient::answering(Ok(500)), |message| {
+            failures.borrow_mut().push(message)
+        })
+        .await;
+
+        assert_eq!(
+            failures.into_inner(),
+            ["Library scan for 'Movie' failed: Jellyfin responded with HTTP 500"]
+        );
+    }
+}
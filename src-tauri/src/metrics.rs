@@ -0,0 +1,257 @@
+//! Cumulative copy statistics.
+//!
+//! The counters live in `copy_metrics.json` next to the downloads ledger and
+//! are updated by the copy task as copies finish.  Saves go to a temporary
+//! file that is then renamed over the old one, so a crash mid-write leaves
+//! the previous totals intact rather than a truncated file.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use privateer_wire_types::{CopyMetrics, CopyState, Destination, TransmissionConfig};
+use snafu::ResultExt;
+
+use crate::copier::{CopyEvents, CopyStats};
+use crate::error::*;
+use crate::unix_now;
+
+/// The metrics file.  Updates are serialized so the copy task and the
+/// reset command can't interleave a read-modify-write.
+pub struct MetricsFile {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl MetricsFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        // The guarded data is on disk, so a panic mid-update can't leave it
+        // half-changed in memory
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The current totals; missing or unreadable files count as zero.
+    pub fn load(&self) -> CopyMetrics {
+        let _guard = self.lock();
+        self.read()
+    }
+
+    /// Apply `update` to the totals and save them.
+    pub fn record(&self, update: impl FnOnce(&mut CopyMetrics)) -> Result<(), ConfigError> {
+        let _guard = self.lock();
+        let mut metrics = self.read();
+        update(&mut metrics);
+        if metrics.since.is_none() {
+            metrics.since = Some(unix_now());
+        }
+        self.write(&metrics)
+    }
+
+    /// Zero every counter.
+    pub fn reset(&self) -> Result<CopyMetrics, ConfigError> {
+        let _guard = self.lock();
+        let metrics = CopyMetrics::default();
+        self.write(&metrics)?;
+        Ok(metrics)
+    }
+
+    fn read(&self) -> CopyMetrics {
+        match std::fs::read_to_string(&self.path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+            Err(_) => CopyMetrics::default(),
+        }
+    }
+
+    fn write(&self, metrics: &CopyMetrics) -> Result<(), ConfigError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context(CreateDirSnafu {
+                path: parent.to_path_buf(),
+            })?;
+        }
+        let json = serde_json::to_string_pretty(metrics).context(SerializeSnafu)?;
+        let tmp = tmp_path(&self.path);
+        std::fs::write(&tmp, json).context(WriteFileSnafu { path: &tmp })?;
+        std::fs::rename(&tmp, &self.path).context(ReplaceFileSnafu { path: &self.path })
+    }
+}
+
+/// Where a save to `path` is written before it replaces the file.
+pub fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Counts copies into a [`MetricsFile`] as the copy task reports them.
+pub struct MetricsRecorder {
+    file: Arc<MetricsFile>,
+    /// Whether the copy in progress is a retry of a failed one.
+    retrying: bool,
+}
+
+impl MetricsRecorder {
+    pub fn new(file: Arc<MetricsFile>) -> Self {
+        Self {
+            file,
+            retrying: false,
+        }
+    }
+}
+
+impl CopyEvents for MetricsRecorder {
+    fn observe_download(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: f32) {}
+
+    fn copy_started(
+        &mut self,
+        _config: &TransmissionConfig,
+        _hash: &str,
+        _name: &str,
+        _dest: Destination,
+        previous: CopyState,
+    ) {
+        self.retrying = previous == CopyState::Failed;
+    }
+
+    fn copy_complete(
+        &mut self,
+        _config: &TransmissionConfig,
+        _hash: &str,
+        name: &str,
+        dest: Destination,
+        _path: &Path,
+        stats: &CopyStats,
+    ) {
+        self.retrying = false;
+        let result = self.file.record(|metrics| {
+            metrics.bytes_copied += stats.bytes;
+            metrics.files_copied += stats.files;
+            metrics.copies_completed += 1;
+            metrics.copy_seconds += stats.elapsed.as_secs_f64();
+            let dest = metrics.destination_mut(dest);
+            dest.bytes_copied += stats.bytes;
+            dest.files_copied += stats.files;
+            dest.copies_completed += 1;
+            dest.copy_seconds += stats.elapsed.as_secs_f64();
+        });
+        if let Err(e) = result {
+            log::warn!(torrent = name; "Could not record copy metrics: {e}");
+        }
+    }
+
+    /// Failed copies are retried every cycle; only the first failure of a
+    /// run of retries is counted.
+    fn copy_failed(
+        &mut self,
+        _config: &TransmissionConfig,
+        _hash: &str,
+        name: &str,
+        dest: Destination,
+        _reason: &str,
+    ) {
+        // A torrent that can't be copied fails without starting
+        if std::mem::take(&mut self.retrying) {
+            return;
+        }
+        let result = self.file.record(|metrics| {
+            metrics.copies_failed += 1;
+            metrics.destination_mut(dest).copies_failed += 1;
+        });
+        if let Err(e) = result {
+            log::warn!(torrent = name; "Could not record copy metrics: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn metrics_path(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("privateer-metrics-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("copy_metrics.json")
+    }
+
+    fn copy(recorder: &mut MetricsRecorder, previous: CopyState, ok: bool) {
+        let config = TransmissionConfig::default();
+        recorder.copy_started(&config, "abc", "name", Destination::Movies, previous);
+        if ok {
+            let stats = CopyStats {
+                bytes: 100,
+                files: 2,
+                elapsed: Duration::from_secs(3),
+            };
+            let path = Path::new("/movies/name");
+            recorder.copy_complete(&config, "abc", "name", Destination::Movies, path, &stats);
+        } else {
+            recorder.copy_failed(&config, "abc", "name", Destination::Shows, "disk full");
+        }
+    }
+
+    #[test]
+    fn totals_survive_a_restart() {
+        let path = metrics_path("restart");
+        let mut recorder = MetricsRecorder::new(Arc::new(MetricsFile::new(path.clone())));
+        copy(&mut recorder, CopyState::NotCopied, true);
+        copy(&mut recorder, CopyState::NotCopied, true);
+
+        let metrics = MetricsFile::new(path.clone()).load();
+
+        assert_eq!(metrics.bytes_copied, 200);
+        assert_eq!(metrics.files_copied, 4);
+        assert_eq!(metrics.copies_completed, 2);
+        assert_eq!(metrics.copy_seconds, 6.0);
+        assert_eq!(metrics.destinations.len(), 1);
+        assert_eq!(metrics.destinations[0].bytes_copied, 200);
+        assert_eq!(metrics.destinations[0].copy_seconds, 6.0);
+        assert!(metrics.since.is_some());
+        assert!(!tmp_path(&path).exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn retried_failures_count_once() {
+        let path = metrics_path("retries");
+        let file = Arc::new(MetricsFile::new(path.clone()));
+        let mut recorder = MetricsRecorder::new(file.clone());
+        copy(&mut recorder, CopyState::NotCopied, false);
+        copy(&mut recorder, CopyState::Failed, false);
+        copy(&mut recorder, CopyState::Failed, false);
+        // Another torrent that can't be copied at all
+        let config = TransmissionConfig::default();
+        recorder.copy_failed(&config, "def", "..", Destination::Shows, "bad name");
+
+        let mut metrics = file.load();
+
+        assert_eq!(metrics.copies_failed, 2);
+        assert_eq!(metrics.destination_mut(Destination::Shows).copies_failed, 2);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn reset_zeroes_the_totals() {
+        let path = metrics_path("reset");
+        let file = Arc::new(MetricsFile::new(path.clone()));
+        copy(
+            &mut MetricsRecorder::new(file.clone()),
+            CopyState::NotCopied,
+            true,
+        );
+
+        file.reset().unwrap();
+
+        assert_eq!(
+            MetricsFile::new(path.clone()).load(),
+            CopyMetrics::default()
+        );
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,392 @@
+//! A stand-in Transmission daemon for tests: a small HTTP server speaking
+//! enough of the RPC protocol for [`TransmissionClient`] and
+//! [`session::call`], starting with the 409 session id handshake.
+//!
+//! It serves `session-get`, `free-space`, `torrent-get`, `torrent-add`,
+//! `torrent-remove`, `torrent-start` and `torrent-stop` from a list of
+//! torrents kept as `torrent-get` would send them, see [`torrent`].
+//!
+//! [`TransmissionClient`]: crate::transmission::TransmissionClient
+//! [`session::call`]: crate::session::call
+
+use std::sync::{Arc, Mutex};
+
+use privateer_wire_types::{ServerProfile, TransmissionConfig};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Header carrying Transmission's CSRF token, handed out with a 409.
+const SESSION_ID_HEADER: &str = "X-Transmission-Session-Id";
+const SESSION_ID: &str = "mock-session-id";
+
+/// What the daemon reports as its version.
+pub const VERSION: &str = "4.0.5 (a6fe2a64aa)";
+/// The daemon's download directory, unless a test sets another.
+pub const DOWNLOAD_DIR: &str = "/downloads";
+/// What `free-space` reports for any path.
+pub const FREE_SPACE: u64 = 500_000_000_000;
+
+/// A finished, seeding torrent with one file, as `torrent-get` sends it.
+pub fn torrent(id: i64, hash: &str, name: &str, download_dir: &str) -> Value {
+    json!({
+        "id": id,
+        "hashString": hash,
+        "name": name,
+        "status": 6,
+        "percentDone": 1.0,
+        "recheckProgress": 0.0,
+        "rateDownload": 0,
+        "rateUpload": 1024,
+        "eta": -1,
+        "uploadRatio": 0.5,
+        "uploadedEver": 500,
+        "secondsSeeding": 600,
+        "addedDate": 1_700_000_000,
+        "doneDate": 1_700_000_600,
+        "isFinished": false,
+        "sizeWhenDone": 1000,
+        "peersConnected": 2,
+        "peersSendingToUs": 0,
+        "peersGettingFromUs": 1,
+        "error": 0,
+        "errorString": "",
+        "downloadDir": download_dir,
+        "files": [{
+            "name": format!("{name}/{name}.mkv"),
+            "length": 1000,
+            "bytesCompleted": 1000,
+        }],
+        "fileStats": [{ "bytesCompleted": 1000, "wanted": true, "priority": 0 }],
+    })
+}
+
+#[derive(Default)]
+struct State {
+    torrents: Vec<Value>,
+    /// Answer every request with this HTTP status instead.
+    status: Option<u16>,
+    /// How many requests were turned away for their session id.
+    handshakes: usize,
+    /// The methods called, in order.
+    calls: Vec<String>,
+}
+
+/// A running mock daemon on a free local port.  Stops when dropped.
+pub struct MockTransmission {
+    port: u16,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+impl Drop for MockTransmission {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl MockTransmission {
+    /// Start serving `torrents`.
+    pub async fn start(torrents: Vec<Value>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let state = Arc::new(Mutex::new(State {
+            torrents,
+            ..Default::default()
+        }));
+        let server = tokio::spawn(serve(listener, state.clone()));
+        Self {
+            port,
+            state,
+            server,
+        }
+    }
+
+    /// A config whose only profile is this daemon.
+    pub fn config(&self) -> TransmissionConfig {
+        TransmissionConfig {
+            profiles: vec![ServerProfile {
+                host: "127.0.0.1".into(),
+                port: self.port,
+                ..Default::default()
+            }],
+            rpc_timeout_secs: 5,
+            ..Default::default()
+        }
+    }
+
+    /// Answer every request from now on with the HTTP `status`, e.g. 401
+    /// for wrong credentials.  A 409 keeps asking for a new session id.
+    pub fn fail_with(&self, status: u16) {
+        self.state.lock().unwrap().status = Some(status);
+    }
+
+    /// The torrents as they are now.
+    pub fn torrents(&self) -> Vec<Value> {
+        self.state.lock().unwrap().torrents.clone()
+    }
+
+    /// How many requests were answered with a 409 for their session id.
+    pub fn handshakes(&self) -> usize {
+        self.state.lock().unwrap().handshakes
+    }
+
+    /// The methods called so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(respond(stream, state.clone()));
+    }
+}
+
+/// Answer one request, then close the connection.
+async fn respond(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    let response = state.lock().unwrap().handle(&request);
+    let _ = stream.write_all(&response.to_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+struct Request {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Read a request with a `Content-Length` body, or `None` if the
+/// connection closed first.
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    let head_end = loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    // Skip the request line
+    let headers = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    let mut request = Request {
+        headers,
+        body: buf.split_off(head_end + 4),
+    };
+    let length = request
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    while request.body.len() < length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        request.body.extend_from_slice(&chunk[..read]);
+    }
+    Some(request)
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    /// An error page, like the ones Transmission serves.
+    fn page(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", "text/html; charset=ISO-8859-1".into())],
+            body: format!("<h1>{status}: {}</h1>", reason(status)),
+        }
+    }
+
+    fn json(body: Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type", "application/json; charset=UTF-8".into())],
+            body: body.to_string(),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            response.push_str(&format!("{name}: {value}\r\n"));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        response.into_bytes()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        409 => "Conflict",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+/// The info hash in a magnet link, and its display name if it has one.
+fn magnet_parts(link: &str) -> Option<(String, Option<String>)> {
+    let url = url::Url::parse(link).ok()?;
+    let mut hash = None;
+    let mut name = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "xt" => hash = value.strip_prefix("urn:btih:").map(str::to_lowercase),
+            "dn" => name = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some((hash?, name))
+}
+
+/// Whether `torrent` is one of `ids`, which may be ids or hashes.  Missing
+/// ids mean every torrent.
+fn selected(torrent: &Value, ids: Option<&Value>) -> bool {
+    let Some(ids) = ids.and_then(Value::as_array) else {
+        return true;
+    };
+    ids.iter()
+        .any(|id| *id == torrent["id"] || *id == torrent["hashString"])
+}
+
+impl State {
+    fn handle(&mut self, request: &Request) -> Response {
+        match self.status {
+            Some(409) | None => {}
+            Some(status) => return Response::page(status),
+        }
+        if self.status == Some(409) || request.header(SESSION_ID_HEADER) != Some(SESSION_ID) {
+            self.handshakes += 1;
+            let mut response = Response::page(409);
+            response
+                .headers
+                .push((SESSION_ID_HEADER, SESSION_ID.to_string()));
+            return response;
+        }
+        let Ok(body) = serde_json::from_slice::<Value>(&request.body) else {
+            return Response::page(400);
+        };
+        let method = body["method"].as_str().unwrap_or_default();
+        self.calls.push(method.to_string());
+        let arguments = &body["arguments"];
+        let (result, arguments) = match self.call(method, arguments) {
+            Ok(arguments) => ("success", arguments),
+            Err(result) => (result, json!({})),
+        };
+        let mut response = json!({ "result": result, "arguments": arguments });
+        if let Some(tag) = body.get("tag") {
+            response["tag"] = tag.clone();
+        }
+        Response::json(response)
+    }
+
+    /// The arguments of a successful call, or its `result` otherwise.
+    fn call(&mut self, method: &str, arguments: &Value) -> Result<Value, &'static str> {
+        let ids = arguments.get("ids");
+        match method {
+            "session-get" => Ok(json!({
+                "version": VERSION,
+                "rpc-version": 17,
+                "rpc-version-minimum": 14,
+                "download-dir": DOWNLOAD_DIR,
+                "incomplete-dir": "/downloads/incomplete",
+                "incomplete-dir-enabled": false,
+                "speed-limit-down": 100,
+                "speed-limit-down-enabled": false,
+                "speed-limit-up": 100,
+                "speed-limit-up-enabled": false,
+                "seedRatioLimit": 2.0,
+                "seedRatioLimited": false,
+                "blocklist-enabled": false,
+                "encryption": "preferred",
+            })),
+            "free-space" => Ok(json!({
+                "path": arguments["path"],
+                "size-bytes": FREE_SPACE,
+                "total_size": FREE_SPACE * 2,
+            })),
+            "torrent-get" => {
+                let torrents = self.torrents.iter().filter(|t| selected(t, ids));
+                Ok(json!({ "torrents": torrents.collect::<Vec<_>>() }))
+            }
+            "torrent-add" => {
+                let link = arguments["filename"].as_str().unwrap_or_default();
+                let (hash, name) = magnet_parts(link).ok_or("invalid or corrupt torrent file")?;
+                if let Some(torrent) = self.torrents.iter().find(|t| t["hashString"] == hash) {
+                    let duplicate = json!({
+                        "id": torrent["id"],
+                        "hashString": hash,
+                        "name": torrent["name"],
+                    });
+                    return Ok(json!({ "torrent-duplicate": duplicate }));
+                }
+                let id = self.torrents.iter().filter_map(|t| t["id"].as_i64()).max();
+                let name = name.unwrap_or_else(|| hash.clone());
+                let mut torrent = torrent(id.unwrap_or(0) + 1, &hash, &name, DOWNLOAD_DIR);
+                torrent["status"] = json!(4);
+                torrent["percentDone"] = json!(0.0);
+                torrent["doneDate"] = json!(0);
+                let added = json!({
+                    "id": torrent["id"],
+                    "hashString": hash,
+                    "name": name,
+                });
+                self.torrents.push(torrent);
+                Ok(json!({ "torrent-added": added }))
+            }
+            "torrent-remove" => {
+                self.torrents.retain(|t| !selected(t, ids));
+                Ok(json!({}))
+            }
+            "torrent-start" | "torrent-stop" => {
+                for torrent in self.torrents.iter_mut().filter(|t| selected(t, ids)) {
+                    let done = torrent["percentDone"].as_f64() == Some(1.0);
+                    torrent["status"] = json!(match (method, done) {
+                        ("torrent-stop", _) => 0,
+                        (_, true) => 6,
+                        (_, false) => 4,
+                    });
+                }
+                Ok(json!({}))
+            }
+            _ => Err("method name not recognized"),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Telling a mounted share from the directory left behind without it.
+//!
+//! When a NAS share isn't mounted its mount point, e.g. `/Volumes/Media`,
+//! can linger as an empty directory on the system disk, and copying into it
+//! fills that disk instead.  A destination flagged as a mount must be a
+//! directory on a different volume than the system root and home directory.
+
+use std::path::Path;
+
+use privateer_wire_types::{Destination, TransmissionConfig};
+use snafu::ensure;
+
+use crate::error::*;
+use crate::path_probe;
+
+/// Whether `path` is on the same volume as `/` or the home directory.
+#[cfg(unix)]
+fn on_system_volume(path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = std::fs::metadata(path)?.dev();
+    let home = std::env::var_os("HOME");
+    let system = [Some(Path::new("/")), home.as_deref().map(Path::new)];
+    let on_system = system
+        .into_iter()
+        .flatten()
+        .any(|dir| std::fs::metadata(dir).is_ok_and(|metadata| metadata.dev() == device));
+    Ok(on_system)
+}
+
+/// Without device ids to compare, every existing directory counts as its
+/// own volume.
+#[cfg(not(unix))]
+fn on_system_volume(_path: &Path) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+/// Whether `dir` exists on a volume of its own.
+pub fn is_mounted(dir: &Path) -> bool {
+    dir.is_dir() && !on_system_volume(dir).unwrap_or(true)
+}
+
+fn is_empty_dir(dir: &Path) -> bool {
+    std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// Fail when `dest` is flagged as a mount and `dir` isn't mounted.  An
+/// unflagged destination that looks like an empty placeholder only gets a
+/// warning.
+pub fn check_mounted(
+    config: &TransmissionConfig,
+    dest: Destination,
+    dir: &Path,
+) -> Result<(), CopyError> {
+    if config.expects_mount(dest) {
+        ensure!(is_mounted(dir), CopyNotMountedSnafu { path: dir });
+    } else if is_empty_dir(dir) && on_system_volume(dir).unwrap_or(false) {
+        log::warn!(
+            "{dest} directory '{}' is empty and on the system disk; \
+             if it should be a mounted share, flag it in Settings",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// [`check_mounted`] on the blocking pool, failing if `dir` doesn't answer
+/// within [`path_probe::TIMEOUT`], so a hung share can't stall the copy
+/// task.
+pub async fn check_mounted_in_time(
+    config: &TransmissionConfig,
+    dest: Destination,
+    dir: &Path,
+) -> Result<(), CopyError> {
+    let (config, owned) = (config.clone(), dir.to_path_buf());
+    let check = tokio::task::spawn_blocking(move || check_mounted(&config, dest, &owned));
+    match tokio::time::timeout(path_probe::TIMEOUT, check).await {
+        Ok(Ok(checked)) => checked,
+        // The check timed out, or panicked
+        _ => CopyDestUnresponsiveSnafu { path: dir }.fail(),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_system_disk_is_not_a_mount() {
+        assert!(on_system_volume(Path::new("/")).unwrap());
+        assert!(!is_mounted(Path::new("/")));
+    }
+
+    #[test]
+    fn a_missing_directory_is_not_mounted() {
+        let config = TransmissionConfig {
+            shows_expect_mount: true,
+            ..Default::default()
+        };
+        let gone = std::env::temp_dir().join("privateer-no-such-share");
+
+        assert!(check_mounted(&config, Destination::Movies, &gone).is_ok());
+        assert!(matches!(
+            check_mounted(&config, Destination::Shows, &gone),
+            Err(CopyError::CopyNotMounted { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn checks_in_time_give_the_same_answer() {
+        let config = TransmissionConfig {
+            shows_expect_mount: true,
+            ..Default::default()
+        };
+        let gone = std::env::temp_dir().join("privateer-no-such-share");
+
+        let movies = check_mounted_in_time(&config, Destination::Movies, &gone).await;
+        assert!(movies.is_ok());
+        assert!(matches!(
+            check_mounted_in_time(&config, Destination::Shows, &gone).await,
+            Err(CopyError::CopyNotMounted { .. })
+        ));
+    }
+}
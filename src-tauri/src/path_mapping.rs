@@ -0,0 +1,193 @@
+//! Translating Transmission's paths into paths on this machine.
+//!
+//! When Transmission runs on another host its `download_dir` names a path on
+//! that host, e.g. `/home/seed/downloads`, while the same share is mounted
+//! here at `/Volumes/seedbox`.  Each mapping replaces a remote prefix with a
+//! local one; the longest matching prefix wins and paths that match none are
+//! used as-is.  Local paths handed to Transmission are mapped back the same
+//! way.
+
+use std::path::{Path, PathBuf};
+
+use snafu::ensure;
+
+use crate::error::*;
+
+/// `prefix` without surrounding whitespace or trailing slashes, so `/a/` and
+/// `/a` are the same mapping.  The root `/` becomes the empty string.
+fn normalize(prefix: &str) -> &str {
+    prefix.trim().trim_end_matches('/')
+}
+
+/// `path` with the longest `from` prefix of `mappings` replaced by its
+/// `to`, or as it is if none matches.
+fn replace_prefix<'a>(mappings: impl Iterator<Item = (&'a str, &'a str)>, path: &str) -> String {
+    let trimmed = normalize(path);
+    let best = mappings
+        .filter(|(from, to)| !from.trim().is_empty() && !to.trim().is_empty())
+        .filter_map(|(from, to)| {
+            let from = normalize(from);
+            let rest = trimmed.strip_prefix(from)?;
+            // Only match whole components: `/data` is not a prefix of `/database`
+            (rest.is_empty() || rest.starts_with('/')).then_some((from.len(), to, rest))
+        })
+        .max_by_key(|(len, _, _)| *len);
+    match best {
+        Some((_, to, rest)) => {
+            let replaced = format!("{}{rest}", normalize(to));
+            if replaced.is_empty() {
+                "/".to_string()
+            } else {
+                replaced
+            }
+        }
+        None => path.trim().to_string(),
+    }
+}
+
+/// The local path for Transmission's `remote` path.
+pub fn to_local(mappings: &[(String, String)], remote: &str) -> PathBuf {
+    let mappings = mappings.iter().map(|(r, l)| (r.as_str(), l.as_str()));
+    PathBuf::from(replace_prefix(mappings, remote))
+}
+
+/// Transmission's path for the `local` path, the reverse of [`to_local`].
+pub fn to_remote(mappings: &[(String, String)], local: &str) -> String {
+    let mappings = mappings.iter().map(|(r, l)| (l.as_str(), r.as_str()));
+    replace_prefix(mappings, local)
+}
+
+/// Check that every mapping is complete and its local prefix exists.
+pub fn validate(mappings: &[(String, String)]) -> Result<(), ConfigError> {
+    for (remote, local) in mappings {
+        ensure!(
+            !remote.trim().is_empty() && !local.trim().is_empty(),
+            PathMappingIncompleteSnafu {
+                remote: remote.trim()
+            }
+        );
+        let local = Path::new(local.trim());
+        ensure!(
+            local.is_dir(),
+            PathMappingMissingSnafu {
+                remote: remote.trim(),
+                local
+            }
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn unmapped_paths_are_unchanged() {
+        assert_eq!(
+            to_local(&[], "/home/seed/downloads"),
+            Path::new("/home/seed/downloads")
+        );
+        let seedbox = mappings(&[("/home/seed/downloads", "/Volumes/seedbox")]);
+        assert_eq!(to_local(&seedbox, "/srv/other"), Path::new("/srv/other"));
+    }
+
+    #[test]
+    fn prefix_is_replaced() {
+        let seedbox = mappings(&[("/home/seed/downloads", "/Volumes/seedbox")]);
+        assert_eq!(
+            to_local(&seedbox, "/home/seed/downloads"),
+            Path::new("/Volumes/seedbox")
+        );
+        assert_eq!(
+            to_local(&seedbox, "/home/seed/downloads/complete"),
+            Path::new("/Volumes/seedbox/complete")
+        );
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let nested = mappings(&[
+            ("/home/seed", "/Volumes/home"),
+            ("/home/seed/downloads", "/Volumes/seedbox"),
+        ]);
+        assert_eq!(
+            to_local(&nested, "/home/seed/downloads/tv"),
+            Path::new("/Volumes/seedbox/tv")
+        );
+        assert_eq!(
+            to_local(&nested, "/home/seed/incomplete"),
+            Path::new("/Volumes/home/incomplete")
+        );
+    }
+
+    #[test]
+    fn local_paths_map_back() {
+        let nested = mappings(&[
+            ("/home/seed", "/Volumes/home"),
+            ("/home/seed/downloads", "/Volumes/seedbox"),
+        ]);
+        assert_eq!(
+            to_remote(&nested, "/Volumes/seedbox/tv"),
+            "/home/seed/downloads/tv"
+        );
+        assert_eq!(to_remote(&nested, "/Volumes/home/"), "/home/seed");
+        assert_eq!(to_remote(&nested, "/Volumes/other"), "/Volumes/other");
+        let root = mappings(&[("/", "/mnt/seedbox/")]);
+        assert_eq!(to_remote(&root, "/mnt/seedbox"), "/");
+    }
+
+    #[test]
+    fn prefixes_match_whole_components() {
+        let data = mappings(&[("/data", "/Volumes/data")]);
+        assert_eq!(to_local(&data, "/database"), Path::new("/database"));
+    }
+
+    #[test]
+    fn trailing_slashes_are_ignored() {
+        let slashed = mappings(&[("/home/seed/downloads/", "/Volumes/seedbox/")]);
+        for remote in ["/home/seed/downloads", "/home/seed/downloads/"] {
+            assert_eq!(to_local(&slashed, remote), Path::new("/Volumes/seedbox"));
+        }
+        let bare = mappings(&[("/home/seed/downloads", "/Volumes/seedbox")]);
+        assert_eq!(
+            to_local(&bare, "/home/seed/downloads/"),
+            Path::new("/Volumes/seedbox")
+        );
+        let root = mappings(&[("/", "/mnt/seedbox/")]);
+        assert_eq!(
+            to_local(&root, "/downloads"),
+            Path::new("/mnt/seedbox/downloads")
+        );
+    }
+
+    #[test]
+    fn blank_mappings_are_skipped_but_rejected_on_save() {
+        let blank = mappings(&[("", "/Volumes/seedbox"), ("/home/seed", " ")]);
+        assert_eq!(to_local(&blank, "/home/seed/a"), Path::new("/home/seed/a"));
+        assert!(matches!(
+            validate(&blank),
+            Err(ConfigError::PathMappingIncomplete { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_local_prefix_is_rejected() {
+        let here = std::env::temp_dir().display().to_string();
+        assert!(validate(&mappings(&[("/home/seed", &here)])).is_ok());
+
+        let gone = std::env::temp_dir().join("privateer-no-such-mount");
+        let result = validate(&mappings(&[("/home/seed", &gone.display().to_string())]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::PathMappingMissing { .. })
+        ));
+    }
+}
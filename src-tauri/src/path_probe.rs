@@ -0,0 +1,188 @@
+//! Checking whether paths exist without stalling the app.
+//!
+//! `Path::exists` on a network share whose server has gone away can block
+//! for seconds, or far longer.  A [`PathProbe`] checks on the blocking pool
+//! and gives up after a short timeout.  The torrent list counts such a path
+//! as missing; the copy task leaves the download for a later cycle rather
+//! than copy over or beside something it couldn't see.  The probe
+//! remembers paths it found, and paths that didn't answer in time, for a
+//! few seconds, so the torrent list polls and the copy task don't ask a
+//! dead mount the same question over and over.
+//!
+//! Paths found missing aren't remembered: the copy task picks free names
+//! by checking, and must see a name as soon as it is taken.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// How long a check may take before it is given up on.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long an answer is remembered.
+pub const CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// The blocking check a [`PathProbe`] runs.
+pub trait ExistsCheck: Send + Sync + 'static {
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// Asks the filesystem.
+pub struct StdExists;
+
+impl ExistsCheck for StdExists {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// Checks paths with a timeout, remembering the answers for a while.
+/// Clones share what they remember.
+#[derive(Clone)]
+pub struct PathProbe {
+    check: Arc<dyn ExistsCheck>,
+    timeout: Duration,
+    ttl: Duration,
+    /// When each path was found, or gave up on, and whether it was found.
+    cache: Arc<Mutex<HashMap<PathBuf, (Instant, bool)>>>,
+}
+
+impl Default for PathProbe {
+    fn default() -> Self {
+        Self::new(StdExists, TIMEOUT, CACHE_TTL)
+    }
+}
+
+impl PathProbe {
+    pub fn new(check: impl ExistsCheck, timeout: Duration, ttl: Duration) -> Self {
+        Self {
+            check: Arc::new(check),
+            timeout,
+            ttl,
+            cache: Arc::default(),
+        }
+    }
+
+    fn cached(&self, path: &Path) -> Option<bool> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .get(path)
+            .filter(|(at, _)| at.elapsed() < self.ttl)
+            .map(|(_, exists)| *exists)
+    }
+
+    fn remember(&self, path: &Path, exists: bool) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        cache.insert(path.to_path_buf(), (Instant::now(), exists));
+    }
+
+    /// Whether something is at `path`, or `false` if the check takes longer
+    /// than the timeout.
+    pub async fn exists(&self, path: &Path) -> bool {
+        self.try_exists(path).await.unwrap_or(false)
+    }
+
+    /// Whether something is at `path`, or `None` if the check takes longer
+    /// than the timeout.
+    pub async fn try_exists(&self, path: &Path) -> Option<bool> {
+        // Missing paths aren't remembered, so `false` is a check given up on
+        if let Some(found) = self.cached(path) {
+            return found.then_some(true);
+        }
+        let check = self.check.clone();
+        let owned = path.to_path_buf();
+        let blocking = tokio::task::spawn_blocking(move || check.exists(&owned));
+        match tokio::time::timeout(self.timeout, blocking).await {
+            Ok(Ok(true)) => {
+                self.remember(path, true);
+                Some(true)
+            }
+            Ok(Ok(false)) => Some(false),
+            // The check panicked
+            Ok(Err(_)) => None,
+            Err(_) => {
+                log::warn!(
+                    "Checking {} took longer than {}s",
+                    path.display(),
+                    self.timeout.as_secs_f32()
+                );
+                self.remember(path, false);
+                None
+            }
+        }
+    }
+
+    /// Forget what is remembered about `path`, and about everything inside
+    /// it and above it, once it is created, removed or renamed.
+    pub fn forget(&self, path: &Path) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|cached, _| !cached.starts_with(path) && !path.starts_with(cached));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Says every path exists, after `delay`, counting the checks.
+    struct SlowCheck {
+        delay: Duration,
+        checks: Arc<AtomicUsize>,
+    }
+
+    impl ExistsCheck for SlowCheck {
+        fn exists(&self, _: &Path) -> bool {
+            self.checks.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(self.delay);
+            true
+        }
+    }
+
+    fn probe(delay: Duration, checks: &Arc<AtomicUsize>) -> PathProbe {
+        let check = SlowCheck {
+            delay,
+            checks: checks.clone(),
+        };
+        PathProbe::new(check, Duration::from_millis(50), CACHE_TTL)
+    }
+
+    #[tokio::test]
+    async fn slow_checks_count_as_missing_and_are_not_repeated() {
+        let checks = Arc::new(AtomicUsize::new(0));
+        let probe = probe(Duration::from_millis(500), &checks);
+        let path = Path::new("/mnt/gone/Movie.2024");
+
+        let started = Instant::now();
+        assert!(!probe.exists(path).await);
+        assert!(!probe.exists(path).await);
+        assert_eq!(None, probe.try_exists(path).await);
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert_eq!(1, checks.load(Ordering::Relaxed));
+
+        probe.forget(Path::new("/mnt/gone"));
+        assert!(!probe.exists(path).await);
+        assert_eq!(2, checks.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn found_paths_are_remembered_and_missing_ones_are_not() {
+        let root = std::env::temp_dir().join(format!("privateer-probe-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let probe = PathProbe::default();
+
+        assert!(!probe.exists(&root).await);
+        std::fs::create_dir_all(&root).unwrap();
+        assert!(probe.exists(&root).await);
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(probe.exists(&root).await);
+        probe.forget(&root);
+        assert!(!probe.exists(&root).await);
+    }
+}
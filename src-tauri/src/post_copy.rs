@@ -0,0 +1,287 @@
+//! Running the user's own command after each copy, e.g. a script that fixes
+//! permissions or posts to a chat.
+//!
+//! After each copy the copy task queues a run of `post_copy_command`;
+//! [`hook_task`] runs them one at a time.  The copy's details are only
+//! handed to the command in environment variables, never put in its command
+//! line, so a torrent's name can't inject anything into it.  Its output is
+//! logged.  A run that fails or takes longer than [`TIMEOUT`] is reported
+//! but never changes the copy's state.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use privateer_wire_types::{CopyState, Destination, TransmissionConfig};
+use snafu::{ensure, OptionExt, ResultExt};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use crate::copier::{CopyEvents, CopyStats};
+use crate::error::*;
+
+/// Each run is killed after this long.
+pub const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The name of the download the "Test hook" button runs the command for.
+const TEST_NAME: &str = "Privateer Test Download";
+
+/// What the command is told about a copy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HookEnv {
+    pub name: String,
+    pub destination: Destination,
+    pub path: PathBuf,
+    pub hash: String,
+}
+
+impl HookEnv {
+    /// The environment variables the command is run with.
+    fn vars(&self) -> [(&'static str, String); 4] {
+        [
+            ("PRIVATEER_NAME", self.name.clone()),
+            ("PRIVATEER_DEST", self.destination.to_string()),
+            ("PRIVATEER_PATH", self.path.display().to_string()),
+            ("PRIVATEER_HASH", self.hash.clone()),
+        ]
+    }
+}
+
+/// The configured command, `None` if it is blank.
+fn configured(config: &TransmissionConfig) -> Option<&str> {
+    config
+        .post_copy_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+}
+
+/// `command_line` run by the platform's shell, which only ever sees what
+/// the user typed.
+fn shell(command_line: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    }
+    #[cfg(not(windows))]
+    {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+/// Log each non-empty line of `output`.
+fn log_output(name: &str, stream: &str, output: &[u8]) {
+    for line in String::from_utf8_lossy(output).lines() {
+        let line = line.trim_end();
+        if !line.is_empty() {
+            log::info!(torrent = name; "post-copy {stream}: {line}");
+        }
+    }
+}
+
+/// Run `command_line` for the copy described by `env`, giving up after
+/// `timeout`.
+pub async fn run(command_line: &str, env: &HookEnv, timeout: Duration) -> Result<(), HookError> {
+    let child = shell(command_line)
+        .envs(env.vars())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(HookSpawnSnafu)?;
+    // Dropping the child on timeout kills it
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .ok()
+        .context(HookTimeoutSnafu {
+            secs: timeout.as_secs(),
+        })?
+        .context(HookSpawnSnafu)?;
+    log_output(&env.name, "stdout", &output.stdout);
+    log_output(&env.name, "stderr", &output.stderr);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    ensure!(
+        output.status.success(),
+        HookStatusSnafu {
+            status: output.status.to_string(),
+            stderr: stderr.lines().last().unwrap_or_default().trim(),
+        }
+    );
+    Ok(())
+}
+
+/// Run the configured command for a made-up download, as if it had just
+/// been copied to Movies.
+pub async fn test(config: &TransmissionConfig) -> Result<String, HookError> {
+    let command_line = configured(config).context(HookNotConfiguredSnafu)?;
+    let env = HookEnv {
+        name: TEST_NAME.to_string(),
+        destination: Destination::Movies,
+        path: Path::new(config.movies_dir.as_deref().unwrap_or_default()).join(TEST_NAME),
+        hash: "0".repeat(40),
+    };
+    run(command_line, &env, TIMEOUT).await?;
+    Ok("The command ran and exited successfully.".to_string())
+}
+
+/// A run queued by [`PostCopyHook`].
+pub struct HookJob {
+    command_line: String,
+    env: HookEnv,
+}
+
+/// Queues a run of the command for every completed copy, when one is set.
+pub struct PostCopyHook(mpsc::UnboundedSender<HookJob>);
+
+impl PostCopyHook {
+    /// The hook and the queue to hand to [`hook_task`].
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<HookJob>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self(tx), rx)
+    }
+}
+
+impl CopyEvents for PostCopyHook {
+    fn observe_download(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: f32) {}
+
+    fn copy_started(
+        &mut self,
+        _: &TransmissionConfig,
+        _: &str,
+        _: &str,
+        _: Destination,
+        _: CopyState,
+    ) {
+    }
+
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        path: &Path,
+        _stats: &CopyStats,
+    ) {
+        let Some(command_line) = configured(config) else {
+            return;
+        };
+        let _ = self.0.send(HookJob {
+            command_line: command_line.to_string(),
+            env: HookEnv {
+                name: name.to_string(),
+                destination: dest,
+                path: path.to_path_buf(),
+                hash: hash.to_string(),
+            },
+        });
+    }
+
+    fn copy_failed(&mut self, _: &TransmissionConfig, _: &str, _: &str, _: Destination, _: &str) {}
+}
+
+/// Run queued commands until the hook is dropped, calling `on_failure`
+/// with a message for each one that fails.
+pub async fn hook_task(mut jobs: mpsc::UnboundedReceiver<HookJob>, on_failure: impl Fn(String)) {
+    while let Some(job) = jobs.recv().await {
+        let name = job.env.name.as_str();
+        match run(&job.command_line, &job.env, TIMEOUT).await {
+            Ok(()) => log::info!(torrent = name; "Ran the post-copy command for '{name}'"),
+            Err(e) => {
+                log::warn!(torrent = name; "Post-copy command failed: {e}");
+                on_failure(format!("Post-copy command for '{name}' failed: {e}"));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> HookEnv {
+        HookEnv {
+            name: name.to_string(),
+            destination: Destination::Shows,
+            path: PathBuf::from("/Media/Shows").join(name),
+            hash: "a".repeat(40),
+        }
+    }
+
+    fn scratch(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("privateer-post-copy-{test}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn the_copy_is_only_described_in_the_environment() {
+        let dir = scratch("env");
+        let out = dir.join("out.txt");
+        // A name that would run a command if it were put in the command line
+        let name = "Evil $(touch pwned) `touch pwned`; touch pwned";
+        let command = format!(
+            "cd '{}' && printf '%s|%s|%s|%s' \"$PRIVATEER_NAME\" \"$PRIVATEER_DEST\" \
+             \"$PRIVATEER_PATH\" \"$PRIVATEER_HASH\" > out.txt",
+            dir.display()
+        );
+
+        run(&command, &env(name), TIMEOUT).await.unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(
+            written,
+            format!("{name}|Shows|/Media/Shows/{name}|{}", "a".repeat(40))
+        );
+        assert!(!dir.join("pwned").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failures_and_timeouts_are_errors() {
+        let failed = run(
+            "echo first >&2; echo 'no such file' >&2; exit 3",
+            &env("a"),
+            TIMEOUT,
+        )
+        .await;
+        let Err(HookError::HookStatus { status, stderr }) = failed else {
+            panic!("expected a failed status, got {failed:?}");
+        };
+        assert!(status.contains('3'), "{status}");
+        assert_eq!(stderr, "no such file");
+
+        let slow = run("sleep 5", &env("a"), Duration::from_millis(100)).await;
+        assert!(matches!(slow, Err(HookError::HookTimeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn only_a_configured_command_is_queued() {
+        let (mut hook, mut jobs) = PostCopyHook::new();
+        let stats = CopyStats::default();
+        let mut config = TransmissionConfig {
+            shows_dir: Some("/Media/Shows".to_string()),
+            post_copy_command: Some("  ".to_string()),
+            ..Default::default()
+        };
+        // Copied beside other files with the same name
+        let path = Path::new("/Media/Shows/Show (2)");
+        hook.copy_complete(&config, "hash", "Show", Destination::Shows, path, &stats);
+        assert!(jobs.try_recv().is_err());
+        assert!(matches!(
+            test(&config).await,
+            Err(HookError::HookNotConfigured)
+        ));
+
+        config.post_copy_command = Some("./fix-permissions.sh".to_string());
+        hook.copy_complete(&config, "hash", "Show", Destination::Shows, path, &stats);
+        let job = jobs.try_recv().unwrap();
+        assert_eq!(job.command_line, "./fix-permissions.sh");
+        assert_eq!(job.env.path, path);
+    }
+}
@@ -0,0 +1,89 @@
+//! Reading configs saved before server profiles, which kept one daemon's
+//! connection fields at the top level.
+
+use privateer_wire_types::{ServerProfile, TransmissionConfig};
+use serde_json::{json, Value};
+
+/// The connection fields of a config from before profiles.
+const LEGACY_FIELDS: [&str; 4] = ["host", "port", "username", "password"];
+
+/// Move the top-level connection fields of an old config into a "Default"
+/// profile.  Configs that already have profiles are left alone.
+pub fn migrate(config: &mut Value) {
+    let Some(fields) = config.as_object_mut() else {
+        return;
+    };
+    let legacy: Vec<(&str, Value)> = LEGACY_FIELDS
+        .iter()
+        .filter_map(|&key| Some((key, fields.remove(key)?)))
+        .collect();
+    if fields.contains_key("profiles") || legacy.is_empty() {
+        return;
+    }
+    let mut profile = json!(ServerProfile::default());
+    for (key, value) in legacy {
+        profile[key] = value;
+    }
+    fields.insert("profiles".into(), json!([profile]));
+    fields.insert("active_profile".into(), json!(0));
+}
+
+/// Parse a saved config, migrating it first, and make sure it has a
+/// profile to connect with.
+pub fn parse(saved: &str) -> Option<TransmissionConfig> {
+    let mut value: Value = serde_json::from_str(saved).ok()?;
+    migrate(&mut value);
+    let mut config: TransmissionConfig = serde_json::from_value(value).ok()?;
+    if config.profiles.is_empty() {
+        config.profiles.push(ServerProfile::default());
+    }
+    if config.active_profile >= config.profiles.len() {
+        config.active_profile = 0;
+    }
+    Some(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use privateer_wire_types::ClientKind;
+
+    use super::*;
+
+    #[test]
+    fn flat_config_becomes_default_profile() {
+        let saved = r#"{
+            "host": "seedbox.local",
+            "port": 9092,
+            "username": "me",
+            "password": "hunter2",
+            "movies_dir": "/Media/Movies"
+        }"#;
+        let config = parse(saved).unwrap();
+        assert_eq!(
+            vec![ServerProfile {
+                id: 0,
+                name: "Default".into(),
+                client: ClientKind::Transmission,
+                host: "seedbox.local".into(),
+                port: 9092,
+                username: Some("me".into()),
+                password: Some("hunter2".into()),
+            }],
+            config.profiles
+        );
+        assert_eq!(0, config.active_profile);
+        assert_eq!(Some("/Media/Movies"), config.movies_dir.as_deref());
+
+        // Saved again and read back, nothing changes
+        let resaved = serde_json::to_string(&config).unwrap();
+        assert_eq!(Some(config), parse(&resaved));
+    }
+
+    #[test]
+    fn profiles_are_never_empty() {
+        let config = parse(r#"{ "profiles": [], "active_profile": 3 }"#).unwrap();
+        assert_eq!(vec![ServerProfile::default()], config.profiles);
+        assert_eq!(0, config.active_profile);
+        assert_eq!(None, parse("not json"));
+    }
+}
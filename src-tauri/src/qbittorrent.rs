@@ -0,0 +1,760 @@
+//! qBittorrent as a [`DownloadClient`], through its Web API (v2).
+//!
+//! The Web API uses a session cookie rather than HTTP auth: requests are
+//! sent with the last cookie, and a 403 logs in again and retries once.
+//! qBittorrent 5 renamed pause and resume to stop and start, so those fall
+//! back to the new names when the old ones are gone.
+
+use std::time::Duration;
+
+use privateer_wire_types::{
+    parse_magnet, sanitize_fraction, sanitize_rate, ClientCapabilities, ClientKind, DaemonInfo,
+    InfoHash, ServerProfile, TransmissionStatus,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use snafu::ResultExt;
+
+use crate::copier::TorrentFile;
+use crate::download_client::{ClientTorrent, DownloadClient};
+use crate::error::*;
+use crate::http::{HttpRequest, HttpResponse, HttpTransport};
+
+/// qBittorrent's ETA for torrents that won't finish.
+const ETA_INFINITY: i64 = 8_640_000;
+
+/// A torrent added by URL shows up once qBittorrent has fetched the
+/// `.torrent` file, so look for it this many times...
+const ADDED_POLLS: usize = 10;
+/// ...this far apart.
+const ADDED_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A torrent from `torrents/info`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Torrent {
+    hash: String,
+    name: String,
+    state: String,
+    progress: f64,
+    dlspeed: i64,
+    upspeed: i64,
+    eta: i64,
+    ratio: f64,
+    uploaded: i64,
+    seeding_time: i64,
+    added_on: i64,
+    completion_on: i64,
+    /// Bytes of the files selected for download.
+    size: i64,
+    num_seeds: i64,
+    num_leechs: i64,
+    save_path: String,
+}
+
+impl From<Torrent> for ClientTorrent {
+    fn from(t: Torrent) -> Self {
+        let status = TransmissionStatus::from_qbittorrent(&t.state);
+        let error_string = match t.state.as_str() {
+            "error" => "qBittorrent stopped the torrent after an error",
+            "missingFiles" => "The torrent's files are missing",
+            _ => "",
+        };
+        let save_path = t.save_path.trim_end_matches(['/', '\\']);
+        let download_dir = match save_path {
+            "" if t.save_path.is_empty() => None,
+            "" => Some(t.save_path.clone()),
+            dir => Some(dir.to_string()),
+        };
+        ClientTorrent {
+            id: -1,
+            hash: t.hash.parse().ok(),
+            name: t.name,
+            recheck_progress: if status.is_verifying() {
+                t.progress
+            } else {
+                0.0
+            },
+            status,
+            percent_done: sanitize_fraction(t.progress),
+            rate_download: sanitize_rate(t.dlspeed),
+            rate_upload: sanitize_rate(t.upspeed),
+            eta: if t.eta < 0 || t.eta >= ETA_INFINITY {
+                -1
+            } else {
+                t.eta
+            },
+            upload_ratio: t.ratio,
+            uploaded_ever: t.uploaded,
+            seconds_seeding: t.seeding_time,
+            added_date: t.added_on.max(0),
+            // -1, or even a timestamp, before the download completes
+            done_date: if t.progress >= 1.0 {
+                t.completion_on.max(0)
+            } else {
+                0
+            },
+            is_finished: matches!(t.state.as_str(), "pausedUP" | "stoppedUP"),
+            size_when_done: t.size.max(0),
+            peers_connected: t.num_seeds + t.num_leechs,
+            peers_sending_to_us: t.num_seeds,
+            peers_getting_from_us: t.num_leechs,
+            error: if error_string.is_empty() { 0 } else { 3 },
+            error_string: error_string.to_string(),
+            download_dir,
+        }
+    }
+}
+
+/// A file from `torrents/files`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct File {
+    name: String,
+    size: i64,
+    /// 0 for files not to download.
+    priority: i64,
+}
+
+/// The part of `sync/maindata` Privateer reads.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MainData {
+    server_state: ServerState,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ServerState {
+    free_space_on_disk: u64,
+}
+
+/// The response body as text, e.g. "Ok." or "Fails.".
+fn body_text(response: &HttpResponse) -> String {
+    String::from_utf8_lossy(&response.body).trim().to_string()
+}
+
+/// Hashes as the Web API takes them, e.g. in `torrents/delete`.
+fn joined(hashes: &[InfoHash]) -> String {
+    hashes
+        .iter()
+        .map(InfoHash::as_str)
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// A qBittorrent instance.  Reusing one keeps its session cookie, so only
+/// the first request logs in.
+pub struct QBittorrentClient<H> {
+    http: H,
+    /// The API root, e.g. `http://localhost:8080/api/v2/`.
+    base: url::Url,
+    username: String,
+    password: String,
+    /// `SID=...`, once logged in.
+    cookie: Option<String>,
+}
+
+impl<H: HttpTransport> QBittorrentClient<H> {
+    pub fn new(profile: &ServerProfile, http: H) -> Result<Self, QBittorrentError> {
+        let url = format!("http://{}:{}/api/v2/", profile.host, profile.port);
+        let base = url
+            .parse()
+            .context(QBittorrentUrlSnafu { url: url.clone() })?;
+        Ok(Self {
+            http,
+            base,
+            username: profile.username.clone().unwrap_or_default(),
+            password: profile.password.clone().unwrap_or_default(),
+            cookie: None,
+        })
+    }
+
+    fn url(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<url::Url, QBittorrentError> {
+        let mut url = self
+            .base
+            .join(endpoint)
+            .context(QBittorrentUrlSnafu { url: endpoint })?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
+
+    /// Send `request` with the session cookie, if there is one.
+    async fn fetch(&self, request: HttpRequest) -> Result<HttpResponse, QBittorrentError> {
+        let request = match &self.cookie {
+            Some(cookie) => request.header("Cookie", cookie.clone()),
+            None => request,
+        };
+        self.http
+            .fetch(request)
+            .await
+            .context(QBittorrentConnectionSnafu)
+    }
+
+    async fn log_in(&mut self) -> Result<(), QBittorrentError> {
+        let request = HttpRequest::post_form(
+            self.url("auth/login", &[])?,
+            &[("username", &self.username), ("password", &self.password)],
+        );
+        self.cookie = None;
+        let response = self.fetch(request).await?;
+        match response.status {
+            200 if body_text(&response) == "Ok." => {
+                // qBittorrent 5 names the cookie after the port, e.g. QBT_SID_8080
+                self.cookie = response
+                    .header("Set-Cookie")
+                    .and_then(|cookie| cookie.split(';').next())
+                    .map(|cookie| cookie.trim().to_string());
+                Ok(())
+            }
+            200 => QBittorrentAuthSnafu {
+                message: "wrong username or password",
+            }
+            .fail(),
+            403 => QBittorrentAuthSnafu {
+                message: "too many failed logins, try again later",
+            }
+            .fail(),
+            status => QBittorrentStatusSnafu {
+                endpoint: "auth/login",
+                status,
+                message: body_text(&response),
+            }
+            .fail(),
+        }
+    }
+
+    /// Send `request`, logging in and trying again if the session expired
+    /// (or never started).
+    async fn send(
+        &mut self,
+        endpoint: &str,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, QBittorrentError> {
+        let mut response = self.fetch(request.clone()).await?;
+        if response.status == 403 {
+            self.log_in().await?;
+            response = self.fetch(request).await?;
+        }
+        if (200..300).contains(&response.status) {
+            Ok(response)
+        } else {
+            QBittorrentStatusSnafu {
+                endpoint,
+                status: response.status,
+                message: body_text(&response),
+            }
+            .fail()
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &mut self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, QBittorrentError> {
+        let request = HttpRequest::get(self.url(endpoint, query)?);
+        let response = self.send(endpoint, request).await?;
+        serde_json::from_slice(&response.body).context(QBittorrentResponseSnafu { endpoint })
+    }
+
+    async fn post(
+        &mut self,
+        endpoint: &str,
+        fields: &[(&str, &str)],
+    ) -> Result<HttpResponse, QBittorrentError> {
+        let request = HttpRequest::post_form(self.url(endpoint, &[])?, fields);
+        self.send(endpoint, request).await
+    }
+
+    /// Post to `endpoint`, or to `fallback` if qBittorrent answers
+    /// `fallback_status`, e.g. 404 from a version without `endpoint`.
+    async fn post_or(
+        &mut self,
+        endpoint: &str,
+        fallback: &str,
+        fallback_status: u16,
+        fields: &[(&str, &str)],
+    ) -> Result<(), QBittorrentError> {
+        match self.post(endpoint, fields).await {
+            Err(QBittorrentError::QBittorrentStatus { status, .. })
+                if status == fallback_status =>
+            {
+                self.post(fallback, fields).await?;
+                Ok(())
+            }
+            result => result.map(drop),
+        }
+    }
+
+    /// Post `hashes` to `endpoint`, unless there are none.
+    async fn post_hashes(
+        &mut self,
+        endpoint: &str,
+        fallback: Option<&str>,
+        hashes: &[InfoHash],
+    ) -> Result<(), ClientError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let hashes = joined(hashes);
+        let fields = [("hashes", hashes.as_str())];
+        match fallback {
+            Some(fallback) => self.post_or(endpoint, fallback, 404, &fields).await?,
+            None => {
+                self.post(endpoint, &fields).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn hashes(&mut self) -> Result<Vec<InfoHash>, QBittorrentError> {
+        let torrents: Vec<Torrent> = self.get("torrents/info", &[]).await?;
+        Ok(torrents
+            .into_iter()
+            .filter_map(|t| t.hash.parse().ok())
+            .collect())
+    }
+}
+
+impl<H: HttpTransport> DownloadClient for QBittorrentClient<H> {
+    async fn daemon_info(&mut self) -> Result<DaemonInfo, ClientError> {
+        let request = HttpRequest::get(self.url("app/version", &[])?);
+        let response = self.send("app/version", request).await?;
+        let version = body_text(&response);
+        Ok(DaemonInfo {
+            client: ClientKind::QBittorrent,
+            version: version.strip_prefix('v').unwrap_or(&version).to_string(),
+            rpc_version: None,
+            capabilities: ClientCapabilities::ALL,
+        })
+    }
+
+    async fn torrents(&mut self) -> Result<Vec<ClientTorrent>, ClientError> {
+        let torrents: Vec<Torrent> = self.get("torrents/info", &[]).await?;
+        Ok(torrents.into_iter().map(ClientTorrent::from).collect())
+    }
+
+    async fn files(&mut self, hash: &InfoHash) -> Result<Vec<TorrentFile>, ClientError> {
+        let files: Vec<File> = self
+            .get("torrents/files", &[("hash", hash.as_str())])
+            .await?;
+        Ok(files
+            .into_iter()
+            .map(|file| TorrentFile {
+                name: file.name,
+                length: file.size.max(0) as u64,
+                wanted: file.priority != 0,
+            })
+            .collect())
+    }
+
+    async fn add(&mut self, url: String) -> Result<InfoHash, ClientError> {
+        if let Some(magnet) = parse_magnet(&url) {
+            let accepted = match self.post("torrents/add", &[("urls", &url)]).await {
+                Ok(response) => body_text(&response) != "Fails.",
+                Err(QBittorrentError::QBittorrentStatus { .. }) => false,
+                Err(e) => return Err(e.into()),
+            };
+            // qBittorrent refuses torrents it already has
+            if accepted || self.hashes().await?.contains(&magnet.info_hash) {
+                return Ok(magnet.info_hash);
+            }
+            return Err(QBittorrentAddedSnafu { url }.build().into());
+        }
+
+        // The hash is in the .torrent file, which qBittorrent fetches, so
+        // look for a torrent that wasn't there before
+        let before = self.hashes().await?;
+        let response = self.post("torrents/add", &[("urls", &url)]).await?;
+        if body_text(&response) != "Fails." {
+            for _ in 0..ADDED_POLLS {
+                tokio::time::sleep(ADDED_POLL_INTERVAL).await;
+                let added = self
+                    .hashes()
+                    .await?
+                    .into_iter()
+                    .find(|h| !before.contains(h));
+                if let Some(hash) = added {
+                    return Ok(hash);
+                }
+            }
+        }
+        Err(QBittorrentAddedSnafu { url }.build().into())
+    }
+
+    async fn remove(&mut self, hashes: &[InfoHash], delete_data: bool) -> Result<(), ClientError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let hashes = joined(hashes);
+        let delete_files = if delete_data { "true" } else { "false" };
+        self.post(
+            "torrents/delete",
+            &[("hashes", &hashes), ("deleteFiles", delete_files)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn pause(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.post_hashes("torrents/pause", Some("torrents/stop"), hashes)
+            .await
+    }
+
+    async fn resume(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.post_hashes("torrents/resume", Some("torrents/start"), hashes)
+            .await
+    }
+
+    async fn verify(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.post_hashes("torrents/recheck", None, hashes).await
+    }
+
+    async fn reannounce(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.post_hashes("torrents/reannounce", None, hashes).await
+    }
+
+    /// qBittorrent moves whatever data is at the old location, unlike
+    /// Transmission which is told the data is already in `dir`.  With
+    /// nothing left there, only the location changes.
+    async fn set_location(&mut self, hashes: &[InfoHash], dir: &str) -> Result<(), ClientError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let hashes = joined(hashes);
+        self.post(
+            "torrents/setLocation",
+            &[("hashes", &hashes), ("location", dir)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn rename_path(
+        &mut self,
+        hash: &InfoHash,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<(), ClientError> {
+        let new_path = match old_path.rsplit_once('/') {
+            Some((parent, _)) => format!("{parent}/{new_name}"),
+            None => new_name.to_string(),
+        };
+        let fields = [
+            ("hash", hash.as_str()),
+            ("oldPath", old_path),
+            ("newPath", &new_path),
+        ];
+        // A 409 for a path that isn't a folder
+        self.post_or("torrents/renameFolder", "torrents/renameFile", 409, &fields)
+            .await?;
+        if !old_path.contains('/') {
+            // As Transmission does, rename the torrent with its top level
+            self.post(
+                "torrents/rename",
+                &[("hash", hash.as_str()), ("name", new_name)],
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn free_space(&mut self) -> Result<u64, ClientError> {
+        let data: MainData = self.get("sync/maindata", &[]).await?;
+        Ok(data.server_state.free_space_on_disk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use std::cell::RefCell;
+    use std::collections::{HashMap, VecDeque};
+
+    const HASH: &str = "c9e15763f722f23e98a29decdfae341b98d53056";
+
+    /// Answers each endpoint from a queue of responses, repeating the last
+    /// one, and records requests.  Endpoints without answers are a 404.
+    #[derive(Default)]
+    struct MockServer {
+        answers: RefCell<HashMap<String, VecDeque<Result<HttpResponse, String>>>>,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+
+    impl MockServer {
+        fn answer(self, endpoint: &str, status: u16, body: &str) -> Self {
+            self.answer_with(
+                endpoint,
+                Ok(HttpResponse {
+                    status,
+                    headers: vec![],
+                    body: body.as_bytes().to_vec(),
+                }),
+            )
+        }
+
+        fn answer_with(self, endpoint: &str, answer: Result<HttpResponse, String>) -> Self {
+            self.answers
+                .borrow_mut()
+                .entry(endpoint.to_string())
+                .or_default()
+                .push_back(answer);
+            self
+        }
+
+        fn logging_in(self) -> Self {
+            self.answer_with(
+                "auth/login",
+                Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("set-cookie".into(), "SID=abc123; HttpOnly; path=/".into())],
+                    body: b"Ok.".to_vec(),
+                }),
+            )
+        }
+
+        /// The endpoints requested, in order.
+        fn endpoints(&self) -> Vec<String> {
+            self.requests
+                .borrow()
+                .iter()
+                .map(|request| {
+                    request
+                        .url
+                        .path()
+                        .trim_start_matches("/api/v2/")
+                        .to_string()
+                })
+                .collect()
+        }
+    }
+
+    impl HttpTransport for &MockServer {
+        async fn fetch(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+            let endpoint = request
+                .url
+                .path()
+                .trim_start_matches("/api/v2/")
+                .to_string();
+            let url = request.url.to_string();
+            self.requests.borrow_mut().push(request);
+            let mut answers = self.answers.borrow_mut();
+            let answer = match answers.get_mut(&endpoint) {
+                Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+                Some(queue) => queue.front().unwrap().clone(),
+                None => Ok(HttpResponse {
+                    status: 404,
+                    ..Default::default()
+                }),
+            };
+            answer.map_err(|message| HttpError::Transport { url, message })
+        }
+    }
+
+    fn client(server: &MockServer) -> QBittorrentClient<&MockServer> {
+        let profile = ServerProfile {
+            client: privateer_wire_types::ClientKind::QBittorrent,
+            port: 8080,
+            username: Some("admin".into()),
+            password: Some("secret".into()),
+            ..Default::default()
+        };
+        QBittorrentClient::new(&profile, server).unwrap()
+    }
+
+    fn torrents_json(hashes: &[&str]) -> String {
+        let torrents: Vec<_> = hashes
+            .iter()
+            .map(|hash| serde_json::json!({ "hash": hash, "name": "Some.Movie.2024" }))
+            .collect();
+        serde_json::to_string(&torrents).unwrap()
+    }
+
+    #[tokio::test]
+    async fn logs_in_when_refused_and_reuses_the_cookie() {
+        let server = MockServer::default()
+            .logging_in()
+            .answer("app/version", 403, "Forbidden")
+            .answer("app/version", 200, "v4.6.5")
+            .answer("torrents/info", 200, "[]");
+        let mut client = client(&server);
+
+        assert_eq!("4.6.5", client.daemon_info().await.unwrap().version);
+        assert!(client.torrents().await.unwrap().is_empty());
+
+        assert_eq!(
+            vec!["app/version", "auth/login", "app/version", "torrents/info"],
+            server.endpoints()
+        );
+        let requests = server.requests.borrow();
+        assert_eq!(Method::Post, requests[1].method);
+        assert_eq!(
+            Some("username=admin&password=secret"),
+            requests[1].body.as_deref()
+        );
+        for request in &requests[2..] {
+            assert!(request
+                .headers
+                .contains(&("Cookie".to_string(), "SID=abc123".to_string())));
+        }
+    }
+
+    #[tokio::test]
+    async fn wrong_credentials_are_an_auth_error() {
+        let server = MockServer::default()
+            .answer("auth/login", 200, "Fails.")
+            .answer("torrents/info", 403, "Forbidden");
+        let mut client = client(&server);
+
+        let error = client.torrents().await.unwrap_err();
+        assert!(matches!(
+            error,
+            ClientError::QBittorrent {
+                source: QBittorrentError::QBittorrentAuth { .. }
+            }
+        ));
+        assert!(!error.is_connection());
+    }
+
+    #[tokio::test]
+    async fn unreachable_servers_are_connection_errors() {
+        let server =
+            MockServer::default().answer_with("torrents/info", Err("connection refused".into()));
+        let mut client = client(&server);
+
+        assert!(client.torrents().await.unwrap_err().is_connection());
+    }
+
+    #[tokio::test]
+    async fn pause_falls_back_to_stop_on_qbittorrent_5() {
+        let server = MockServer::default().answer("torrents/stop", 200, "");
+        let mut client = client(&server);
+        let other = "0123456789abcdef0123456789abcdef01234567";
+
+        client
+            .pause(&[HASH.parse().unwrap(), other.parse().unwrap()])
+            .await
+            .unwrap();
+
+        assert_eq!(vec!["torrents/pause", "torrents/stop"], server.endpoints());
+        let body = server.requests.borrow()[1].body.clone().unwrap();
+        assert_eq!(format!("hashes={HASH}%7C{other}"), body);
+    }
+
+    #[tokio::test]
+    async fn adding_a_magnet_it_already_has_returns_its_hash() {
+        let server = MockServer::default()
+            .answer("torrents/add", 200, "Fails.")
+            .answer("torrents/info", 200, &torrents_json(&[HASH]));
+        let mut client = client(&server);
+
+        let hash = client
+            .add(format!("magnet:?xt=urn:btih:{HASH}&dn=Some.Movie.2024"))
+            .await
+            .unwrap();
+        assert_eq!(HASH, hash.as_str());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn adding_a_url_finds_the_new_torrent() {
+        let server = MockServer::default()
+            .answer("torrents/info", 200, "[]")
+            .answer("torrents/info", 200, "[]")
+            .answer("torrents/info", 200, &torrents_json(&[HASH]))
+            .answer("torrents/add", 200, "Ok.");
+        let mut client = client(&server);
+
+        let hash = client
+            .add("https://tracker.example/t/1.torrent".into())
+            .await
+            .unwrap();
+        assert_eq!(HASH, hash.as_str());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn urls_that_never_show_up_fail() {
+        let server = MockServer::default()
+            .answer("torrents/info", 200, "[]")
+            .answer("torrents/add", 200, "Ok.");
+        let mut client = client(&server);
+
+        let error = client
+            .add("https://tracker.example/t/1.torrent".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ClientError::QBittorrent {
+                source: QBittorrentError::QBittorrentAdded { .. }
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn files_without_priority_are_unwanted() {
+        let files = r#"[
+            {"name": "Show/S01E01.mkv", "size": 100, "priority": 1},
+            {"name": "Show/sample.mkv", "size": 10, "priority": 0}
+        ]"#;
+        let server = MockServer::default().answer("torrents/files", 200, files);
+        let mut client = client(&server);
+
+        let files = client.files(&HASH.parse().unwrap()).await.unwrap();
+        assert_eq!(
+            vec![
+                TorrentFile {
+                    name: "Show/S01E01.mkv".into(),
+                    length: 100,
+                    wanted: true,
+                },
+                TorrentFile {
+                    name: "Show/sample.mkv".into(),
+                    length: 10,
+                    wanted: false,
+                },
+            ],
+            files
+        );
+        assert_eq!(
+            Some(format!("hash={HASH}").as_str()),
+            server.requests.borrow()[0].url.query()
+        );
+    }
+
+    #[test]
+    fn torrents_map_onto_transmissions_terms() {
+        let torrent: Torrent = serde_json::from_value(serde_json::json!({
+            "hash": HASH,
+            "name": "Some.Movie.2024",
+            "state": "missingFiles",
+            "progress": 1.0,
+            "eta": ETA_INFINITY,
+            "ratio": 1.5,
+            "completion_on": 1_700_000_000,
+            "num_seeds": 2,
+            "num_leechs": 3,
+            "save_path": "/downloads/",
+        }))
+        .unwrap();
+
+        let torrent = ClientTorrent::from(torrent);
+        assert_eq!(Some(HASH.parse().unwrap()), torrent.hash);
+        assert_eq!(TransmissionStatus::Stopped, torrent.status);
+        assert_eq!((-1, -1), (torrent.id, torrent.eta));
+        assert_eq!(1_700_000_000, torrent.done_date);
+        assert_eq!(
+            (5, 2, 3),
+            (
+                torrent.peers_connected,
+                torrent.peers_sending_to_us,
+                torrent.peers_getting_from_us
+            )
+        );
+        assert_eq!(3, torrent.error);
+        assert_eq!(Some("/downloads".into()), torrent.download_dir);
+    }
+}
@@ -0,0 +1,121 @@
+//! The recent download rate and peers of each downloading torrent, for the
+//! sparklines in the Downloads table.
+//!
+//! The torrents task samples every poll, so the history builds up whether
+//! or not Downloads is open.  Only downloading torrents are sampled, and a
+//! torrent's samples are dropped once it stops downloading or leaves
+//! Transmission, so at most [`RATE_HISTORY_SAMPLES`] are kept for each
+//! torrent downloading now.
+
+use std::collections::{HashMap, VecDeque};
+
+use privateer_wire_types::{
+    InfoHash, RateSample, TransmissionStatus, TransmissionTorrent, RATE_HISTORY_SAMPLES,
+};
+
+/// Recent samples of each downloading torrent, by info hash.
+#[derive(Default)]
+pub struct RateHistory {
+    samples: HashMap<InfoHash, VecDeque<RateSample>>,
+}
+
+fn is_downloading(t: &TransmissionTorrent) -> bool {
+    t.status == TransmissionStatus::Downloading
+}
+
+impl RateHistory {
+    /// Add a sample taken at `now` for each downloading torrent, forgetting
+    /// the oldest beyond [`RATE_HISTORY_SAMPLES`] and the torrents no
+    /// longer downloading.
+    pub fn record(&mut self, torrents: &[TransmissionTorrent], now: i64) {
+        self.samples.retain(|hash, _| {
+            torrents
+                .iter()
+                .any(|t| &t.hash_string == hash && is_downloading(t))
+        });
+        for t in torrents.iter().filter(|t| is_downloading(t)) {
+            let samples = self.samples.entry(t.hash_string.clone()).or_default();
+            if samples.len() == RATE_HISTORY_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(RateSample {
+                at: now,
+                rate_download: t.rate_download,
+                peers_connected: t.peers_connected,
+                peers_sending_to_us: t.peers_sending_to_us,
+            });
+        }
+    }
+
+    /// Fill in the history of each torrent.
+    pub fn attach(&self, torrents: &mut [TransmissionTorrent]) {
+        for t in torrents {
+            t.history = self
+                .samples
+                .get(&t.hash_string)
+                .map(|samples| samples.iter().copied().collect())
+                .unwrap_or_default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(byte: char, status: TransmissionStatus, rate_download: i64) -> TransmissionTorrent {
+        TransmissionTorrent {
+            id: 1,
+            name: format!("Torrent {byte}"),
+            status,
+            percent_done: 0.5,
+            rate_download,
+            size_when_done: 1024,
+            peers_connected: 3,
+            peers_sending_to_us: 1,
+            ..TransmissionTorrent::new(byte.to_string().repeat(40).parse().unwrap())
+        }
+    }
+
+    #[test]
+    fn keeps_only_the_latest_samples() {
+        let mut history = RateHistory::default();
+        for i in 0..RATE_HISTORY_SAMPLES as i64 + 5 {
+            history.record(&[torrent('a', TransmissionStatus::Downloading, i)], i * 3);
+        }
+
+        let mut torrents = [torrent('a', TransmissionStatus::Downloading, 0)];
+        history.attach(&mut torrents);
+        let samples = &torrents[0].history;
+        assert_eq!(samples.len(), RATE_HISTORY_SAMPLES);
+        assert_eq!(samples[0].rate_download, 5);
+        assert_eq!(samples[0].at, 15);
+        assert_eq!(
+            samples.last().unwrap().rate_download,
+            RATE_HISTORY_SAMPLES as i64 + 4
+        );
+        assert_eq!(samples[0].peers_sending_to_us, 1);
+    }
+
+    #[test]
+    fn forgets_torrents_that_stop_downloading_or_go_away() {
+        let mut history = RateHistory::default();
+        history.record(
+            &[
+                torrent('a', TransmissionStatus::Downloading, 100),
+                torrent('b', TransmissionStatus::Downloading, 200),
+                torrent('c', TransmissionStatus::Seeding, 0),
+            ],
+            0,
+        );
+        assert_eq!(history.samples.len(), 2);
+
+        // 'a' finished and 'b' was removed
+        history.record(&[torrent('a', TransmissionStatus::Seeding, 0)], 3);
+        assert!(history.samples.is_empty());
+
+        let mut torrents = [torrent('b', TransmissionStatus::Downloading, 0)];
+        history.attach(&mut torrents);
+        assert!(torrents[0].history.is_empty());
+    }
+}
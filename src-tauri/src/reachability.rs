@@ -0,0 +1,201 @@
+//! Noticing when a destination goes away and when it comes back.
+//!
+//! The health task checks every destination once a minute.  A
+//! [`ReachabilityMonitor`] remembers what the previous check found, so a
+//! NAS dropping off the network is reported once, and so is its return,
+//! which is when waiting copies should be retried.
+
+use std::collections::HashMap;
+
+use privateer_wire_types::{Destination, DestinationHealth};
+
+/// A destination became unusable or usable again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transition {
+    Lost {
+        destination: Destination,
+        path: String,
+    },
+    Recovered {
+        destination: Destination,
+        path: String,
+        /// How long the destination was unreachable, in seconds.
+        after_secs: i64,
+    },
+}
+
+impl Transition {
+    pub fn describe(&self) -> String {
+        match self {
+            Transition::Lost { destination, path } => {
+                format!("{destination} directory is unreachable: {path}")
+            }
+            Transition::Recovered {
+                destination,
+                path,
+                after_secs,
+            } => format!(
+                "{destination} directory is reachable again after {} minutes: {path}",
+                after_secs / 60
+            ),
+        }
+    }
+}
+
+/// What the last check found for one destination.
+struct LastKnown {
+    path: String,
+    /// When it was first found unreachable, `None` while it is reachable.
+    unreachable_since: Option<i64>,
+}
+
+/// The last known reachability of each configured destination.
+#[derive(Default)]
+pub struct ReachabilityMonitor {
+    last: HashMap<Destination, LastKnown>,
+}
+
+/// Whether copies can reach `health`'s directory.
+fn is_reachable(health: &DestinationHealth) -> bool {
+    health.reachable && health.mounted != Some(false)
+}
+
+impl ReachabilityMonitor {
+    /// Compare a health check made at `now` with the previous one, filling
+    /// in how long each destination has been unreachable.
+    ///
+    /// The first check of a destination, or of a new directory for it,
+    /// establishes its state without reporting a transition.
+    pub fn observe(&mut self, destinations: &mut [DestinationHealth], now: i64) -> Vec<Transition> {
+        let mut transitions = vec![];
+        for health in destinations.iter_mut() {
+            let Some(path) = health.path.clone() else {
+                self.last.remove(&health.destination);
+                continue;
+            };
+            let reachable = is_reachable(health);
+            let last = self
+                .last
+                .get(&health.destination)
+                .filter(|last| last.path == path);
+            let unreachable_since = match (last, reachable) {
+                (Some(last), true) => {
+                    if let Some(since) = last.unreachable_since {
+                        transitions.push(Transition::Recovered {
+                            destination: health.destination,
+                            path: path.clone(),
+                            after_secs: now - since,
+                        });
+                    }
+                    None
+                }
+                (Some(last), false) => match last.unreachable_since {
+                    Some(since) => Some(since),
+                    None => {
+                        transitions.push(Transition::Lost {
+                            destination: health.destination,
+                            path: path.clone(),
+                        });
+                        Some(now)
+                    }
+                },
+                (None, reachable) => (!reachable).then_some(now),
+            };
+            health.unreachable_since = unreachable_since;
+            self.last.insert(
+                health.destination,
+                LastKnown {
+                    path,
+                    unreachable_since,
+                },
+            );
+        }
+        transitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(reachable: bool) -> DestinationHealth {
+        DestinationHealth {
+            destination: Destination::Movies,
+            path: Some("/Volumes/Media/Movies".to_string()),
+            reachable,
+            writable: reachable,
+            free_bytes: None,
+            mounted: None,
+            unreachable_since: None,
+        }
+    }
+
+    #[test]
+    fn reports_each_transition_once() {
+        let mut monitor = ReachabilityMonitor::default();
+        let path = "/Volumes/Media/Movies".to_string();
+
+        let mut check = [health(true)];
+        assert_eq!(monitor.observe(&mut check, 0), []);
+
+        let mut check = [health(false)];
+        assert_eq!(
+            monitor.observe(&mut check, 60),
+            [Transition::Lost {
+                destination: Destination::Movies,
+                path: path.clone(),
+            }]
+        );
+        assert_eq!(check[0].unreachable_since, Some(60));
+
+        let mut check = [health(false)];
+        assert_eq!(monitor.observe(&mut check, 120), []);
+        assert_eq!(check[0].unreachable_since, Some(60));
+
+        let mut check = [health(true)];
+        assert_eq!(
+            monitor.observe(&mut check, 660),
+            [Transition::Recovered {
+                destination: Destination::Movies,
+                path,
+                after_secs: 600,
+            }]
+        );
+        assert_eq!(check[0].unreachable_since, None);
+    }
+
+    #[test]
+    fn an_unmounted_share_is_unreachable() {
+        let mut monitor = ReachabilityMonitor::default();
+        monitor.observe(&mut [health(true)], 0);
+
+        let mut check = [DestinationHealth {
+            mounted: Some(false),
+            ..health(true)
+        }];
+
+        assert_eq!(monitor.observe(&mut check, 60).len(), 1);
+        assert_eq!(check[0].unreachable_since, Some(60));
+    }
+
+    #[test]
+    fn a_new_directory_starts_over() {
+        let mut monitor = ReachabilityMonitor::default();
+        let mut check = [health(false)];
+        assert_eq!(monitor.observe(&mut check, 0), []);
+        assert_eq!(check[0].unreachable_since, Some(0));
+
+        let mut check = [DestinationHealth {
+            path: Some("/Volumes/Other/Movies".to_string()),
+            ..health(true)
+        }];
+        assert_eq!(monitor.observe(&mut check, 60), []);
+
+        let mut check = [DestinationHealth {
+            path: None,
+            ..health(false)
+        }];
+        assert_eq!(monitor.observe(&mut check, 120), []);
+        assert_eq!(check[0].unreachable_since, None);
+    }
+}
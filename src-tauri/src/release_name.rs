@@ -0,0 +1,820 @@
+//! Pulling a movie's title and year out of a release name, so that
+//! `Dune.Part.Two.2024.2160p.WEB-DL.DDP5.1.Atmos-FLUX` can be copied into
+//! `Dune Part Two (2024)`, the folder name media servers match best.
+//!
+//! Release names are hostile: words are joined by dots, underscores or
+//! spaces, the year may or may not be bracketed, sites stick tags on the
+//! front, and titles can contain years of their own (`Blade Runner 2049`,
+//! `2001 A Space Odyssey`).  The year is taken to be the last one before the
+//! first quality or encoding tag, and everything ahead of it is the title.
+//!
+//! Episodes are found by their `S02E05` or `2x05` tag, with the show's name
+//! ahead of it, so their files can be renamed by a template such as
+//! `{show} - S{season:02}E{episode:02}`.
+
+use std::collections::HashSet;
+
+use crate::file_name::strip_reserved;
+
+/// Characters that separate the words of a release name.
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '.' | '_' | '(' | ')' | '[' | ']' | '{' | '}')
+}
+
+/// A word of a release name and the separator just before it.
+#[derive(Clone, Copy)]
+struct Word<'a> {
+    text: &'a str,
+    after_dot: bool,
+}
+
+fn words(name: &str) -> Vec<Word<'_>> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut after_dot = false;
+    for (i, c) in name.char_indices() {
+        match (is_separator(c), start) {
+            (true, Some(s)) => {
+                words.push(Word {
+                    text: &name[s..i],
+                    after_dot,
+                });
+                start = None;
+                after_dot = c == '.';
+            }
+            (true, None) => after_dot = c == '.',
+            (false, None) => start = Some(i),
+            (false, Some(_)) => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(Word {
+            text: &name[s..],
+            after_dot,
+        });
+    }
+    words
+}
+
+/// A site or group tag at the front, e.g. `[YTS.MX] ` or
+/// `www.Torrenting.com - `.
+fn strip_prefix_tag(name: &str) -> &str {
+    let name = name.trim_start();
+    if let Some(rest) = name.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            return &rest[end + 1..];
+        }
+    }
+    if name
+        .get(..4)
+        .is_some_and(|www| www.eq_ignore_ascii_case("www."))
+    {
+        if let Some(end) = name.find(" - ") {
+            return &name[end + 3..];
+        }
+    }
+    name
+}
+
+fn parse_year(word: &str) -> Option<u16> {
+    if word.len() != 4 || !word.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year = word.parse().ok()?;
+    (1895..=2099).contains(&year).then_some(year)
+}
+
+/// Tags that only appear after the title: resolution, source and codec.
+fn is_quality_tag(word: &str) -> bool {
+    is_resolution(word)
+        || matches!(
+            word.to_ascii_lowercase().as_str(),
+            "4k" | "uhd"
+                | "hdr"
+                | "hdr10"
+                | "web-dl"
+                | "webdl"
+                | "webrip"
+                | "web-rip"
+                | "bluray"
+                | "blu-ray"
+                | "bdrip"
+                | "brrip"
+                | "bdremux"
+                | "remux"
+                | "dvdrip"
+                | "dvdscr"
+                | "hdrip"
+                | "hdtv"
+                | "hdcam"
+                | "x264"
+                | "x265"
+                | "h264"
+                | "h265"
+                | "hevc"
+                | "xvid"
+                | "10bit"
+        )
+}
+
+fn is_resolution(word: &str) -> bool {
+    let word = word.to_ascii_lowercase();
+    word.strip_suffix('p')
+        .or_else(|| word.strip_suffix('i'))
+        .is_some_and(|digits| {
+            (3..=4).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+fn is_punctuation(word: &str) -> bool {
+    word.chars().all(|c| !c.is_alphanumeric())
+}
+
+/// `words` without punctuation at either end.
+fn trim_punctuation<'a, 'b>(mut words: &'b [Word<'a>]) -> &'b [Word<'a>] {
+    while let [rest @ .., last] = words {
+        if !is_punctuation(last.text) {
+            break;
+        }
+        words = rest;
+    }
+    while let [first, rest @ ..] = words {
+        if !is_punctuation(first.text) {
+            break;
+        }
+        words = rest;
+    }
+    words
+}
+
+/// Join title words with spaces, keeping dotted initials like `S.W.A.T.`
+/// together.
+fn join_title(words: &[Word]) -> String {
+    let is_initial =
+        |word: &Word| word.text.chars().count() == 1 && word.text.chars().all(char::is_alphabetic);
+    let mut title = String::new();
+    let mut i = 0;
+    while i < words.len() {
+        let mut end = i + 1;
+        if is_initial(&words[i]) {
+            while end < words.len() && is_initial(&words[end]) && words[end].after_dot {
+                end += 1;
+            }
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        if end - i > 1 {
+            for word in &words[i..end] {
+                title.push_str(word.text);
+                title.push('.');
+            }
+        } else {
+            title.push_str(words[i].text);
+        }
+        i = end;
+    }
+    title
+}
+
+/// Capitalise each word of an all lowercase title.
+fn capitalise(title: &str) -> String {
+    if title.chars().any(char::is_uppercase) {
+        return title.to_string();
+    }
+    let mut capitalised = String::with_capacity(title.len());
+    let mut word_start = true;
+    for c in title.chars() {
+        if word_start {
+            capitalised.extend(c.to_uppercase());
+        } else {
+            capitalised.push(c);
+        }
+        word_start = c == ' ' || c == '.' || c == '-';
+    }
+    capitalised
+}
+
+/// A movie's title and release year.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Movie {
+    pub title: String,
+    pub year: u16,
+}
+
+impl Movie {
+    /// The title and year in a release name, or `None` without a year.
+    pub fn parse(name: &str) -> Option<Self> {
+        let mut words = words(strip_prefix_tag(name));
+        if let Some(end) = words.iter().position(|word| is_quality_tag(word.text)) {
+            words.truncate(end);
+        }
+        let (at, year) = words
+            .iter()
+            .enumerate()
+            .skip(1)
+            .rev()
+            .find_map(|(i, word)| Some((i, parse_year(word.text)?)))?;
+        let title = trim_punctuation(&words[..at]);
+        if title.is_empty() {
+            return None;
+        }
+        Some(Self {
+            title: capitalise(&join_title(title)),
+            year,
+        })
+    }
+
+    /// `Title (Year)`, without characters that aren't allowed in file names
+    /// on Windows or SMB shares.
+    pub fn folder_name(&self) -> String {
+        format!("{} ({})", strip_reserved(&self.title).trim(), self.year)
+    }
+}
+
+/// The `Title (Year)` folder for a release name, or `None` when no year
+/// can be found in it.
+pub fn movie_folder(name: &str) -> Option<String> {
+    Movie::parse(name).map(|movie| movie.folder_name())
+}
+
+/// The season and episode in an `S02E05` or `2x05` tag.  Only the first
+/// episode of a multi-episode tag like `S01E01E02` is kept.
+fn parse_episode_tag(word: &str) -> Option<(u32, u32)> {
+    /// The number at the start of `digits`, if it has `len` digits, and
+    /// what follows it.
+    fn number(digits: &str, len: std::ops::RangeInclusive<usize>) -> Option<(u32, &str)> {
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        if !len.contains(&end) {
+            return None;
+        }
+        Some((digits[..end].parse().ok()?, &digits[end..]))
+    }
+
+    let word = word.to_ascii_lowercase();
+    if let Some(rest) = word.strip_prefix('s') {
+        let (season, rest) = number(rest, 1..=2)?;
+        let (episode, rest) = number(rest.strip_prefix('e')?, 1..=3)?;
+        (rest.is_empty() || rest.starts_with('e') || rest.starts_with('-'))
+            .then_some((season, episode))
+    } else {
+        let (season, rest) = number(&word, 1..=2)?;
+        let (episode, rest) = number(rest.strip_prefix('x')?, 2..=3)?;
+        rest.is_empty().then_some((season, episode))
+    }
+}
+
+/// A TV episode, as named in a release.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Episode {
+    pub show: String,
+    pub season: u32,
+    pub episode: u32,
+    /// The resolution after the episode tag, e.g. `1080p`.
+    pub quality: Option<String>,
+}
+
+impl Episode {
+    /// The show, season and episode in a release name, or `None` without an
+    /// episode tag or a show ahead of it.
+    pub fn parse(name: &str) -> Option<Self> {
+        let words = words(strip_prefix_tag(name));
+        let (at, (season, episode)) = words
+            .iter()
+            .enumerate()
+            .skip(1)
+            .find_map(|(i, word)| Some((i, parse_episode_tag(word.text)?)))?;
+        let mut show = trim_punctuation(&words[..at]);
+        // `Doctor.Who.2005.S01E01` is the 2005 show, `Doctor Who (2005)`
+        let year = match show {
+            [rest @ .., last] if !trim_punctuation(rest).is_empty() => {
+                parse_year(last.text).map(|year| (year, trim_punctuation(rest)))
+            }
+            _ => None,
+        };
+        if let Some((_, rest)) = year {
+            show = rest;
+        }
+        if show.is_empty() {
+            return None;
+        }
+        let mut show = capitalise(&join_title(show));
+        if let Some((year, _)) = year {
+            show = format!("{show} ({year})");
+        }
+        let quality = words[at + 1..]
+            .iter()
+            .find(|word| is_resolution(word.text))
+            .map(|word| word.text.to_string());
+        Some(Self {
+            show,
+            season,
+            episode,
+            quality,
+        })
+    }
+
+    /// `template` with `{show}`, `{season}`, `{episode}` and `{quality}`
+    /// filled in.  A width pads numbers with zeros, e.g. `{season:02}`.
+    /// Brackets and separators left empty by a missing quality are dropped.
+    pub fn format(&self, template: &str) -> String {
+        let mut formatted = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            formatted.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let token = &rest[start + 1..start + end];
+            let (name, width) = token.split_once(':').unwrap_or((token, ""));
+            let width = width.parse().unwrap_or(0);
+            match name {
+                "show" => formatted.push_str(&self.show),
+                "season" => formatted.push_str(&format!("{:0width$}", self.season)),
+                "episode" => formatted.push_str(&format!("{:0width$}", self.episode)),
+                "quality" => formatted.push_str(self.quality.as_deref().unwrap_or_default()),
+                _ => formatted.push_str(&rest[start..=start + end]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        formatted.push_str(rest);
+        let formatted = formatted.replace("[]", "").replace("()", "");
+        let mut formatted = formatted.split_whitespace().collect::<Vec<_>>().join(" ");
+        while formatted.contains(" - - ") {
+            formatted = formatted.replace(" - - ", " - ");
+        }
+        strip_reserved(formatted.trim_matches(|c: char| c == '-' || c == '.' || c.is_whitespace()))
+    }
+}
+
+/// Extensions of the video files that are renamed.
+const VIDEO_EXTENSIONS: [&str; 7] = ["mkv", "mp4", "avi", "m4v", "mov", "wmv", "ts"];
+
+/// How the files in one directory are renamed by an episode template.
+#[derive(Debug, Default, PartialEq)]
+pub struct EpisodeRenames {
+    /// `(name, new name)` pairs.
+    pub renamed: Vec<(String, String)>,
+    /// Videos whose episode can't be told from their names, which keep them.
+    pub unparsed: Vec<String>,
+}
+
+/// Rename the episode videos among `names`, the files of one directory, by
+/// `template`, and the `.srt` subtitles named after them to match.
+///
+/// Samples are left alone.  A video whose new name is already taken keeps
+/// its name and is listed as unparsed, as is one without an episode tag.
+pub fn rename_episodes(template: &str, names: &[String]) -> EpisodeRenames {
+    let mut renames = EpisodeRenames::default();
+    let mut taken: HashSet<String> = HashSet::new();
+    // `(stem, new stem)` of each renamed video
+    let mut videos: Vec<(&str, String)> = vec![];
+    for name in names {
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            continue;
+        };
+        let extension_lower = extension.to_ascii_lowercase();
+        if !VIDEO_EXTENSIONS.contains(&extension_lower.as_str()) {
+            continue;
+        }
+        if words(stem)
+            .iter()
+            .any(|word| word.text.eq_ignore_ascii_case("sample"))
+        {
+            continue;
+        }
+        let new_stem = Episode::parse(stem)
+            .map(|episode| episode.format(template))
+            .filter(|new_stem| !new_stem.is_empty());
+        let new_name = new_stem
+            .as_ref()
+            .map(|new_stem| format!("{new_stem}.{extension}"));
+        match new_stem.zip(new_name) {
+            Some((new_stem, new_name)) if taken.insert(new_name.to_lowercase()) => {
+                if new_name != *name {
+                    renames.renamed.push((name.clone(), new_name));
+                }
+                videos.push((stem, new_stem));
+            }
+            _ => renames.unparsed.push(name.clone()),
+        }
+    }
+    for name in names {
+        let Some(stem) = name
+            .strip_suffix(".srt")
+            .or_else(|| name.strip_suffix(".SRT"))
+        else {
+            continue;
+        };
+        // The longest video name the subtitle starts with, e.g. `.en` after it
+        let video = videos
+            .iter()
+            .filter_map(|(video_stem, new_stem)| {
+                let language = stem.strip_prefix(video_stem)?;
+                (language.is_empty() || language.starts_with('.')).then_some((
+                    video_stem.len(),
+                    new_stem,
+                    language,
+                ))
+            })
+            .max_by_key(|(len, _, _)| *len);
+        if let Some((_, new_stem, language)) = video {
+            let new_name = format!("{new_stem}{language}.srt");
+            if new_name != *name && taken.insert(new_name.to_lowercase()) {
+                renames.renamed.push((name.clone(), new_name));
+            }
+        }
+    }
+    renames
+}
+
+/// What to search for to find another release of `name`: the show and
+/// episode tag of an episode, the title and year of a movie, or else the
+/// words before the quality tags.
+pub fn search_query(name: &str) -> String {
+    if let Some(episode) = Episode::parse(name) {
+        let show = episode.show.replace(['(', ')'], "");
+        return format!("{show} S{:02}E{:02}", episode.season, episode.episode);
+    }
+    if let Some(movie) = Movie::parse(name) {
+        return format!("{} {}", movie.title, movie.year);
+    }
+    let mut words = words(strip_prefix_tag(name));
+    if let Some(end) = words.iter().position(|word| is_quality_tag(word.text)) {
+        words.truncate(end);
+    }
+    let words = trim_punctuation(&words);
+    if words.is_empty() {
+        return name.trim().to_string();
+    }
+    words
+        .iter()
+        .map(|word| word.text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_names() {
+        let cases = [
+            (
+                "Dune.Part.Two.2024.2160p.WEB-DL.DDP5.1.Atmos.DV.HDR.H.265-FLUX",
+                "Dune Part Two (2024)",
+            ),
+            (
+                "The.Matrix.1999.1080p.BluRay.x264-GROUP",
+                "The Matrix (1999)",
+            ),
+            (
+                "Oppenheimer.2023.IMAX.720p.WEBRip.x264",
+                "Oppenheimer (2023)",
+            ),
+            ("Heat.1995.REMASTERED.1080p.BluRay.x265", "Heat (1995)"),
+            ("Alien_1979_Directors_Cut_720p", "Alien (1979)"),
+            (
+                "Past Lives 2023 1080p WEB-DL H264 AC3-EVO",
+                "Past Lives (2023)",
+            ),
+            ("Amelie.2001.FRENCH.1080p.BluRay", "Amelie (2001)"),
+            (
+                "Spider-Man.No.Way.Home.2021.2160p.UHD",
+                "Spider-Man No Way Home (2021)",
+            ),
+            ("Ocean's.Eleven.2001.1080p", "Ocean's Eleven (2001)"),
+            ("Nosferatu.1922.720p.BluRay", "Nosferatu (1922)"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(movie_folder(name).as_deref(), Some(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn bracketed_years_and_tags() {
+        let cases = [
+            (
+                "Godzilla Minus One (2023) [1080p] [WEBRip] [5.1] [YTS.MX]",
+                "Godzilla Minus One (2023)",
+            ),
+            ("[YTS.MX] Arrival (2016) [2160p]", "Arrival (2016)"),
+            ("Parasite [2019] 1080p", "Parasite (2019)"),
+            ("www.Torrenting.com - Tenet.2020.1080p", "Tenet (2020)"),
+            ("[ www.Speed.cd ] - Drive (2011) 720p", "Drive (2011)"),
+            ("Her.2013.720p.BRRip.x264-[YTS.AM]", "Her (2013)"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(movie_folder(name).as_deref(), Some(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn years_in_titles() {
+        let cases = [
+            (
+                "Blade.Runner.2049.2017.1080p.BluRay",
+                "Blade Runner 2049 (2017)",
+            ),
+            (
+                "2001.A.Space.Odyssey.1968.2160p",
+                "2001 A Space Odyssey (1968)",
+            ),
+            ("1917.2019.1080p.WEB-DL", "1917 (2019)"),
+            ("Wonder Woman 1984 (2020) 1080p", "Wonder Woman 1984 (2020)"),
+            ("2012.2009.720p.BluRay", "2012 (2009)"),
+            ("Apollo.13.1995.1080p", "Apollo 13 (1995)"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(movie_folder(name).as_deref(), Some(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn initials_stay_together() {
+        let cases = [
+            ("S.W.A.T.2003.1080p", "S.W.A.T. (2003)"),
+            ("L.A.Confidential.1997.720p", "L.A. Confidential (1997)"),
+            (
+                "E.T.the.Extra-Terrestrial.1982.1080p",
+                "E.T. the Extra-Terrestrial (1982)",
+            ),
+            ("A.Beautiful.Mind.2001.1080p", "A Beautiful Mind (2001)"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(movie_folder(name).as_deref(), Some(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn lowercase_names_are_capitalised() {
+        assert_eq!(
+            movie_folder("the.grand.budapest.hotel.2014.1080p").as_deref(),
+            Some("The Grand Budapest Hotel (2014)")
+        );
+        assert_eq!(
+            movie_folder("l.a.confidential.1997").as_deref(),
+            Some("L.A. Confidential (1997)")
+        );
+        // Mixed case is left as released
+        assert_eq!(
+            movie_folder("Il.buono.il.brutto.il.cattivo.1966").as_deref(),
+            Some("Il buono il brutto il cattivo (1966)")
+        );
+    }
+
+    #[test]
+    fn single_file_names() {
+        assert_eq!(
+            movie_folder("Moonlight.2016.1080p.BluRay.x264.mkv").as_deref(),
+            Some("Moonlight (2016)")
+        );
+        assert_eq!(
+            movie_folder("Moonlight 2016.mp4").as_deref(),
+            Some("Moonlight (2016)")
+        );
+    }
+
+    #[test]
+    fn file_name_characters_are_replaced() {
+        let movie = Movie {
+            title: "Mission: Impossible".to_string(),
+            year: 1996,
+        };
+        assert_eq!(movie.folder_name(), "Mission - Impossible (1996)");
+        let movie = Movie {
+            title: "What/If? \"Cut\" 10:30*".to_string(),
+            year: 2020,
+        };
+        assert_eq!(movie.folder_name(), "WhatIf Cut 10-30 (2020)");
+    }
+
+    #[test]
+    fn dangling_punctuation_is_dropped() {
+        assert_eq!(
+            movie_folder("Mission Impossible - Dead Reckoning Part One - 2023 - 1080p").as_deref(),
+            Some("Mission Impossible - Dead Reckoning Part One (2023)")
+        );
+        assert_eq!(
+            movie_folder("- Heat - 1995").as_deref(),
+            Some("Heat (1995)")
+        );
+    }
+
+    #[test]
+    fn names_without_a_year_are_not_parsed() {
+        let names = [
+            "Some.Home.Video.1080p.WEB-DL",
+            "1917.1080p.BluRay",
+            "2012",
+            "Fahrenheit.451.720p",
+            "Movie.Title.1080p.2019",
+            "Movie-2019-1080p",
+            "Ocean's Thirteen 3000 1080p",
+            "",
+            "[YTS.MX]",
+            "... 2019",
+        ];
+        for name in names {
+            assert_eq!(Movie::parse(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn parses_title_and_year() {
+        assert_eq!(
+            Movie::parse("Heat.1995.1080p"),
+            Some(Movie {
+                title: "Heat".to_string(),
+                year: 1995,
+            })
+        );
+    }
+
+    #[test]
+    fn search_queries() {
+        let cases = [
+            ("The.Bear.S02E05.1080p.WEB.h264-ETHEL", "The Bear S02E05"),
+            ("Doctor.Who.2005.S01E01.720p", "Doctor Who 2005 S01E01"),
+            ("[YTS.MX] Heat (1995) [1080p]", "Heat 1995"),
+            ("Some.Album.FLAC", "Some Album FLAC"),
+            ("Live.Concert.1080p.WEB", "Live Concert"),
+        ];
+        for (name, query) in cases {
+            assert_eq!(search_query(name), query, "{name}");
+        }
+    }
+
+    fn episode(name: &str) -> Option<(String, u32, u32, Option<String>)> {
+        Episode::parse(name).map(|e| (e.show, e.season, e.episode, e.quality))
+    }
+
+    #[test]
+    fn episode_names() {
+        let cases = [
+            (
+                "The.Bear.S02E05.1080p.WEB.h264-ETHEL",
+                "The Bear",
+                2,
+                5,
+                Some("1080p"),
+            ),
+            (
+                "Severance S01E09 720p HDTV x264",
+                "Severance",
+                1,
+                9,
+                Some("720p"),
+            ),
+            (
+                "shogun.2024.s01e10.2160p",
+                "Shogun (2024)",
+                1,
+                10,
+                Some("2160p"),
+            ),
+            (
+                "Doctor.Who.2005.S13E01.HDTV",
+                "Doctor Who (2005)",
+                13,
+                1,
+                None,
+            ),
+            ("Taskmaster.S16E100", "Taskmaster", 16, 100, None),
+            (
+                "Friends.1x05.The.One.With.The.East.German.Laundry.Detergent",
+                "Friends",
+                1,
+                5,
+                None,
+            ),
+            (
+                "[eztv] Slow.Horses.S04E01.1080p",
+                "Slow Horses",
+                4,
+                1,
+                Some("1080p"),
+            ),
+            (
+                "S.W.A.T.2017.S07E03.720p",
+                "S.W.A.T. (2017)",
+                7,
+                3,
+                Some("720p"),
+            ),
+            ("1923.S02E01.1080p", "1923", 2, 1, Some("1080p")),
+            ("Andor - S01E01E02 - 1080p", "Andor", 1, 1, Some("1080p")),
+        ];
+        for (name, show, season, number, quality) in cases {
+            assert_eq!(
+                episode(name),
+                Some((
+                    show.to_string(),
+                    season,
+                    number,
+                    quality.map(str::to_string)
+                )),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn names_without_an_episode_are_not_parsed() {
+        let names = [
+            "The.Bear.Season.2.1080p",
+            "S02E05.1080p",
+            "The.Matrix.1999.1080p.x264",
+            "Show.S2024E01",
+            "Show.2x5",
+            "Show.S01.Complete",
+            "",
+        ];
+        for name in names {
+            assert_eq!(Episode::parse(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn templates() {
+        let episode = Episode::parse("The.Bear.S02E05.1080p.WEB").unwrap();
+        let cases = [
+            ("{show} - S{season:02}E{episode:02}", "The Bear - S02E05"),
+            ("{show} {season}x{episode:02}", "The Bear 2x05"),
+            (
+                "{show} - S{season:02}E{episode:03} [{quality}]",
+                "The Bear - S02E005 [1080p]",
+            ),
+            ("{show}: {unknown} {episode}", "The Bear - {unknown} 5"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(episode.format(template), expected, "{template}");
+        }
+
+        let no_quality = Episode::parse("The.Bear.S02E05").unwrap();
+        assert_eq!(
+            no_quality.format("{show} - S{season:02}E{episode:02} - {quality}"),
+            "The Bear - S02E05"
+        );
+        assert_eq!(
+            no_quality.format("{show} - {quality} - S{season:02}E{episode:02} [{quality}]"),
+            "The Bear - S02E05"
+        );
+    }
+
+    #[test]
+    fn renames_videos_and_their_subtitles() {
+        let names = [
+            "The.Bear.S02E05.1080p.WEB.mkv",
+            "The.Bear.S02E05.1080p.WEB.srt",
+            "The.Bear.S02E05.1080p.WEB.en.srt",
+            "The.Bear.S02E05.1080p.WEB.nfo",
+            "sample.mkv",
+            "The.Bear.S02E05.sample.mkv",
+            "Behind.The.Scenes.mkv",
+        ]
+        .map(str::to_string);
+
+        let renames = rename_episodes("{show} - S{season:02}E{episode:02}", &names);
+
+        assert_eq!(
+            renames.renamed,
+            [
+                ("The.Bear.S02E05.1080p.WEB.mkv", "The Bear - S02E05.mkv"),
+                ("The.Bear.S02E05.1080p.WEB.srt", "The Bear - S02E05.srt"),
+                (
+                    "The.Bear.S02E05.1080p.WEB.en.srt",
+                    "The Bear - S02E05.en.srt"
+                ),
+            ]
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+        );
+        assert_eq!(renames.unparsed, ["Behind.The.Scenes.mkv"]);
+    }
+
+    #[test]
+    fn season_packs_rename_each_episode() {
+        let names = [
+            "Severance.S01E01.720p.mkv",
+            "Severance.S01E02.720p.mkv",
+            "Severance.S01E02.1080p.mkv",
+        ]
+        .map(str::to_string);
+
+        let renames = rename_episodes("{show} - S{season:02}E{episode:02}", &names);
+
+        assert_eq!(
+            renames.renamed,
+            [
+                ("Severance.S01E01.720p.mkv", "Severance - S01E01.mkv"),
+                ("Severance.S01E02.720p.mkv", "Severance - S01E02.mkv"),
+            ]
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+        );
+        // Two files can't take the same name
+        assert_eq!(renames.unparsed, ["Severance.S01E02.1080p.mkv"]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Where to point a torrent whose files have gone missing, e.g. after its
+//! data was moved to another disk by hand, for `repair_torrent`.
+//!
+//! Paths are checked through a [`Filesystem`], so a share that has gone
+//! away can't hold the command up.
+
+use std::path::Path;
+
+use privateer_wire_types::{Destination, TransmissionConfig};
+
+use crate::copier::Filesystem;
+use crate::path_mapping;
+
+/// Pick a better data location for a torrent whose files have gone missing.
+///
+/// If the files aren't in the client's `download_dir` but do exist in the
+/// ledger destination's directory (e.g. after moving disks around), that
+/// directory is returned, as the client's path for it.  With the files
+/// gone from `download_dir`, a client that moves data has nothing to move.
+pub async fn better_location(
+    fs: &impl Filesystem,
+    config: &TransmissionConfig,
+    destination: Option<Destination>,
+    name: &str,
+    download_dir: Option<&str>,
+) -> Option<String> {
+    if let Some(dir) = download_dir {
+        let local = path_mapping::to_local(&config.path_mappings, dir);
+        if fs.exists(&local.join(name)).await {
+            return None;
+        }
+    }
+    let dir = config.dir_for(destination?)?;
+    if dir.is_empty() || !fs.exists(&Path::new(dir).join(name)).await {
+        return None;
+    }
+    let remote = path_mapping::to_remote(&config.path_mappings, dir);
+    (Some(remote.as_str()) != download_dir).then_some(remote)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::copier::TokioFs;
+
+    const NAME: &str = "Some.Movie.2024";
+
+    /// A fresh directory with `downloads` and `movies` in it, for one test.
+    struct Dirs {
+        root: PathBuf,
+        downloads: PathBuf,
+        movies: PathBuf,
+    }
+
+    impl Dirs {
+        fn new(test: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "privateer-relocation-{}-{test}",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&root);
+            let downloads = root.join("downloads");
+            let movies = root.join("movies");
+            std::fs::create_dir_all(&downloads).unwrap();
+            std::fs::create_dir_all(&movies).unwrap();
+            Self {
+                root,
+                downloads,
+                movies,
+            }
+        }
+
+        fn config(&self) -> TransmissionConfig {
+            TransmissionConfig {
+                movies_dir: Some(self.movies.display().to_string()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Drop for Dirs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    async fn location(config: &TransmissionConfig, download_dir: &str) -> Option<String> {
+        let fs = TokioFs::default();
+        let movies = Some(Destination::Movies);
+        better_location(&fs, config, movies, NAME, Some(download_dir)).await
+    }
+
+    #[tokio::test]
+    async fn files_moved_to_the_destination_are_found() {
+        let dirs = Dirs::new("moved");
+        std::fs::create_dir_all(dirs.movies.join(NAME)).unwrap();
+        let downloads = dirs.downloads.display().to_string();
+
+        let movies = dirs.movies.display().to_string();
+        assert_eq!(Some(movies), location(&dirs.config(), &downloads).await);
+
+        // Nowhere to point a torrent without a destination
+        let fs = TokioFs::default();
+        let found = better_location(&fs, &dirs.config(), None, NAME, Some(&downloads)).await;
+        assert_eq!(None, found);
+    }
+
+    #[tokio::test]
+    async fn torrents_with_their_files_stay() {
+        let dirs = Dirs::new("in-place");
+        std::fs::create_dir_all(dirs.movies.join(NAME)).unwrap();
+        std::fs::create_dir_all(dirs.downloads.join(NAME)).unwrap();
+        let downloads = dirs.downloads.display().to_string();
+        assert_eq!(None, location(&dirs.config(), &downloads).await);
+
+        // Already pointed at the destination
+        let movies = dirs.movies.display().to_string();
+        assert_eq!(None, location(&dirs.config(), &movies).await);
+    }
+
+    #[tokio::test]
+    async fn files_found_nowhere_leave_it_as_it_is() {
+        let dirs = Dirs::new("nowhere");
+        let downloads = dirs.downloads.display().to_string();
+        assert_eq!(None, location(&dirs.config(), &downloads).await);
+    }
+
+    #[tokio::test]
+    async fn remote_clients_are_given_their_own_path() {
+        let dirs = Dirs::new("remote");
+        std::fs::create_dir_all(dirs.movies.join(NAME)).unwrap();
+        let config = TransmissionConfig {
+            path_mappings: vec![("/srv".to_string(), dirs.root.display().to_string())],
+            ..dirs.config()
+        };
+        assert_eq!(
+            Some("/srv/movies".to_string()),
+            location(&config, "/srv/downloads").await
+        );
+        assert_eq!(None, location(&config, "/srv/movies").await);
+    }
+}
@@ -0,0 +1,236 @@
+//! Throttling for requests to the Privateer search API.
+//!
+//! apibay rate-limits clients that send overlapping requests, and once it
+//! does the app is locked out for minutes.  Every search/info request goes
+//! through a [`RequestGate`], which runs them one at a time, spaces them out,
+//! and turns away requests once a short queue is full.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::sync::{watch, Mutex, Semaphore};
+use tokio::time::Instant;
+
+use crate::error::PirateError;
+
+/// Requests allowed in flight at once.
+const CONCURRENT_REQUESTS: usize = 1;
+
+/// Minimum time between the starts of two requests.
+const MIN_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Requests allowed to wait for a turn; any more are rejected.
+const MAX_WAITING: usize = 3;
+
+pub struct RequestGate {
+    permits: Semaphore,
+    /// Ticket of the next request to arrive.
+    next_ticket: AtomicU64,
+    /// Tickets of the requests waiting for a turn, first in line first.
+    /// Every change is seen by all of them, to report their new places.
+    waiting: watch::Sender<VecDeque<u64>>,
+    /// When the last request started.
+    last_start: Mutex<Option<Instant>>,
+}
+
+/// Holds a place in the queue, giving it up when dropped.
+struct QueueSlot<'a> {
+    waiting: &'a watch::Sender<VecDeque<u64>>,
+    ticket: u64,
+}
+
+impl QueueSlot<'_> {
+    /// The place in line, 1 being next.
+    fn position(&self, waiting: &VecDeque<u64>) -> usize {
+        waiting
+            .iter()
+            .position(|&ticket| ticket == self.ticket)
+            .map_or(0, |index| index + 1)
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.waiting
+            .send_modify(|waiting| waiting.retain(|&ticket| ticket != self.ticket));
+    }
+}
+
+impl Default for RequestGate {
+    fn default() -> Self {
+        Self {
+            permits: Semaphore::new(CONCURRENT_REQUESTS),
+            next_ticket: AtomicU64::new(0),
+            waiting: watch::Sender::new(VecDeque::new()),
+            last_start: Mutex::new(None),
+        }
+    }
+}
+
+impl RequestGate {
+    /// Run `request` when it's its turn.
+    ///
+    /// `on_queue` is called with the request's place in line, 1 being next,
+    /// when it joins the queue and whenever a request ahead of it leaves,
+    /// and with 0 once it is its turn.
+    pub async fn run<T>(
+        &self,
+        request: impl Future<Output = T>,
+        on_queue: impl Fn(usize),
+    ) -> Result<T, PirateError> {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::SeqCst);
+        let mut joined = false;
+        self.waiting.send_if_modified(|waiting| {
+            joined = waiting.len() < MAX_WAITING;
+            if joined {
+                waiting.push_back(ticket);
+            }
+            joined
+        });
+        if !joined {
+            return Err(PirateError::SlowDown);
+        }
+        let slot = QueueSlot {
+            waiting: &self.waiting,
+            ticket,
+        };
+
+        let mut changes = self.waiting.subscribe();
+        let mut position = slot.position(&changes.borrow_and_update());
+        on_queue(position);
+        // Kept across the loop, as the semaphore serves its waiters in turn
+        let acquire = self.permits.acquire();
+        tokio::pin!(acquire);
+        let _permit = loop {
+            tokio::select! {
+                // A place taken by the request ahead is reported, even if
+                // that one was done by the time of asking
+                biased;
+                _ = changes.changed() => {
+                    let now = slot.position(&changes.borrow_and_update());
+                    if now != position {
+                        position = now;
+                        on_queue(position);
+                    }
+                }
+                permit = &mut acquire => {
+                    break permit.expect("the request gate is never closed");
+                }
+            }
+        };
+        drop(slot);
+        on_queue(0);
+
+        {
+            let mut last_start = self.last_start.lock().await;
+            if let Some(last) = *last_start {
+                let since = last.elapsed();
+                if since < MIN_INTERVAL {
+                    tokio::time::sleep(MIN_INTERVAL - since).await;
+                }
+            }
+            *last_start = Some(Instant::now());
+        }
+
+        Ok(request.await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    /// The places in line a request was told of.
+    #[derive(Default)]
+    struct Places(StdMutex<Vec<usize>>);
+
+    impl Places {
+        fn push(&self, place: usize) {
+            self.0.lock().unwrap().push(place);
+        }
+
+        fn told(&self) -> Vec<usize> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waiting_requests_hear_their_place_in_line() {
+        let gate = RequestGate::default();
+        let start = Instant::now();
+        let (release, released) = oneshot::channel::<()>();
+        let places: [Places; 3] = Default::default();
+
+        let first = gate.run(async { released.await.unwrap() }, |p| places[0].push(p));
+        let second = gate.run(async { Instant::now() }, |p| places[1].push(p));
+        let third = gate.run(async { Instant::now() }, |p| places[2].push(p));
+        let release = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            release.send(()).unwrap();
+        };
+        let (first, second, third, ()) = tokio::join!(first, second, third, release);
+
+        assert!(first.is_ok());
+        assert_eq!(Duration::from_secs(5), second.unwrap() - start);
+        // Spaced out from the second
+        assert_eq!(Duration::from_secs(6), third.unwrap() - start);
+        assert_eq!(vec![1, 0], places[0].told());
+        assert_eq!(vec![1, 0], places[1].told());
+        assert_eq!(vec![2, 1, 0], places[2].told());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_giving_up_leave_the_queue() {
+        let gate = RequestGate::default();
+        let (release, released) = oneshot::channel::<()>();
+        let places: [Places; 2] = Default::default();
+
+        let first = gate.run(async { released.await.unwrap() }, |_| {});
+        let second = gate.run(async {}, |p| places[0].push(p));
+        let second = tokio::time::timeout(Duration::from_secs(1), second);
+        let third = gate.run(async {}, |p| places[1].push(p));
+        let release = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            release.send(()).unwrap();
+        };
+        let (first, second, third, ()) = tokio::join!(first, second, third, release);
+
+        assert!(first.is_ok());
+        assert!(second.is_err());
+        assert!(third.is_ok());
+        assert_eq!(vec![1], places[0].told());
+        assert_eq!(vec![2, 1, 0], places[1].told());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_beyond_a_full_queue_are_turned_away() {
+        let gate = RequestGate::default();
+        let (release, released) = oneshot::channel::<()>();
+
+        let running = gate.run(async { released.await.unwrap() }, |_| {});
+        // MAX_WAITING of them
+        let a = gate.run(async {}, |_| {});
+        let b = gate.run(async {}, |_| {});
+        let c = gate.run(async {}, |_| {});
+        let turned_away = gate.run(async {}, |_| panic!("not in the queue"));
+        let release = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            release.send(()).unwrap();
+        };
+        let (running, a, b, c, turned_away, ()) =
+            tokio::join!(running, a, b, c, turned_away, release);
+
+        assert!(running.is_ok());
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        assert!(matches!(turned_away, Err(PirateError::SlowDown)));
+
+        // There is room again once the queue is through
+        assert!(gate.run(async {}, |_| {}).await.is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+//! Copying with an external `rsync`, for large copies over flaky links.
+//!
+//! rsync copies into a hidden staging path next to the destination with
+//! `--partial --inplace`.  A failed copy leaves the staging path behind so
+//! the next attempt picks up where it stopped; only a finished copy is
+//! renamed into place, so a half-copied download never looks copied.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use privateer_wire_types::{format_size, SizeUnits};
+use snafu::{ensure, ResultExt};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+
+use crate::copier::{CopyStats, Heartbeat};
+use crate::error::*;
+
+const PROGRAM: &str = "rsync";
+
+/// `--info=progress2` first appeared in rsync 3.1.
+const MIN_VERSION: (u32, u32) = (3, 1);
+
+/// Log progress each time it passes another multiple of this percentage.
+const LOG_EVERY_PERCENT: u32 = 10;
+
+/// The `major.minor` version in the output of `rsync --version`.
+fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let first_line = output.lines().next()?;
+    let mut words = first_line.split_whitespace();
+    words.find(|word| *word == "version")?;
+    let mut parts = words.next()?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Check that a recent enough rsync is on the `PATH`.
+pub async fn check_available() -> Result<(), ConfigError> {
+    let output = Command::new(PROGRAM)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| ConfigError::RsyncUnavailable {
+            message: format!("can't run '{PROGRAM}': {e}"),
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = parse_version(&stdout);
+    ensure!(
+        version.is_some_and(|version| version >= MIN_VERSION),
+        RsyncUnavailableSnafu {
+            message: format!(
+                "found '{}'",
+                stdout.lines().next().unwrap_or_default().trim()
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// One `--info=progress2` update: bytes so far and percent of the total.
+#[derive(Debug, PartialEq, Eq)]
+struct Progress {
+    bytes: u64,
+    percent: u32,
+}
+
+/// Parse a progress line like `  1,234,567  45%  10.00MB/s  0:00:10 (xfr#1, to-chk=0/3)`.
+fn parse_progress(line: &str) -> Option<Progress> {
+    let mut words = line.split_whitespace();
+    let bytes = words.next()?.replace(',', "").parse().ok()?;
+    let percent = words.next()?.strip_suffix('%')?.parse().ok()?;
+    Some(Progress { bytes, percent })
+}
+
+/// The number after `label` in `--stats` output, e.g. `Total file size: 1,234 bytes`.
+fn parse_stat(line: &str, label: &str) -> Option<u64> {
+    let rest = line.trim().strip_prefix(label)?;
+    let number = rest.split_whitespace().next()?;
+    number.replace(',', "").parse().ok()
+}
+
+/// Fills in `stats` from the `--stats` summary.
+fn read_stats(line: &str, stats: &mut CopyStats) {
+    if let Some(bytes) = parse_stat(line, "Total file size:") {
+        stats.bytes = bytes;
+    }
+    // `Number of files: 5 (reg: 3, dir: 2)`
+    if let Some(counts) = line.trim().strip_prefix("Number of files:") {
+        if let Some(files) = counts
+            .split(['(', ',', ')'])
+            .find_map(|part| parse_stat(part, "reg:"))
+        {
+            stats.files = files;
+        }
+    }
+}
+
+/// Where a copy to `dst` is staged until it finishes.
+fn staging_path(dst: &Path) -> PathBuf {
+    let name = dst.file_name().unwrap_or_default().to_string_lossy();
+    dst.with_file_name(format!(".{name}.partial"))
+}
+
+/// rsync's `src` and `dst` arguments: a trailing slash on directories copies
+/// their contents rather than nesting the directory inside `dst`.
+fn arguments(src: &Path, dst: &Path) -> (String, String) {
+    let (src_arg, dst_arg) = (src.display().to_string(), dst.display().to_string());
+    if src.is_dir() {
+        (
+            format!("{}/", src_arg.trim_end_matches('/')),
+            format!("{}/", dst_arg.trim_end_matches('/')),
+        )
+    } else {
+        (src_arg, dst_arg)
+    }
+}
+
+/// Copy `src` to `dst` with rsync, logging progress in `units`, counting it
+/// on `heartbeat` and filling in `stats`.
+pub async fn copy(
+    src: &Path,
+    dst: &Path,
+    name: &str,
+    stats: &mut CopyStats,
+    heartbeat: &Heartbeat,
+    units: SizeUnits,
+) -> Result<(), CopyError> {
+    let staging = staging_path(dst);
+    if let Some(parent) = dst.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context(CopyCreateDirSnafu {
+                path: parent.to_path_buf(),
+            })?;
+    }
+    let (src_arg, staging_arg) = arguments(src, &staging);
+    let mut child = Command::new(PROGRAM)
+        .args([
+            "--archive",
+            "--partial",
+            "--inplace",
+            "--no-inc-recursive",
+            "--info=progress2",
+            "--stats",
+        ])
+        .arg(src_arg)
+        .arg(staging_arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(CopyRsyncSpawnSnafu)?;
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let read_stdout = async {
+        // Progress updates end in '\r', the stats summary in '\n'
+        let mut reader = BufReader::new(stdout);
+        let mut chunk = vec![];
+        let mut logged = 0;
+        let mut moved = 0;
+        while reader.read_until(b'\r', &mut chunk).await.unwrap_or(0) > 0 {
+            for line in String::from_utf8_lossy(&chunk).split(['\r', '\n']) {
+                if let Some(progress) = parse_progress(line) {
+                    heartbeat.beat(progress.bytes.saturating_sub(moved));
+                    moved = moved.max(progress.bytes);
+                    let step = progress.percent / LOG_EVERY_PERCENT * LOG_EVERY_PERCENT;
+                    if step > logged {
+                        logged = step;
+                        log::info!(
+                            torrent = name;
+                            "rsync: '{name}' {}% ({})",
+                            progress.percent,
+                            format_size(progress.bytes, units)
+                        );
+                    }
+                }
+                read_stats(line, stats);
+            }
+            chunk.clear();
+        }
+    };
+    let mut errors = String::new();
+    let read_stderr = stderr.read_to_string(&mut errors);
+    let _ = tokio::join!(read_stdout, read_stderr);
+
+    let status = child.wait().await.context(CopyRsyncSpawnSnafu)?;
+    ensure!(
+        status.success(),
+        CopyRsyncSnafu {
+            status: status.to_string(),
+            stderr: errors.trim(),
+        }
+    );
+    tokio::fs::rename(&staging, dst)
+        .await
+        .context(CopyRenameSnafu {
+            path: staging.clone(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        assert_eq!(
+            parse_version("rsync  version 3.2.7  protocol version 31\nCopyright..."),
+            Some((3, 2))
+        );
+        // macOS's bundled rsync predates --info=progress2
+        let old = parse_version("rsync  version 2.6.9  protocol version 29").unwrap();
+        assert!(old < MIN_VERSION);
+        assert_eq!(parse_version("openrsync: protocol version 29"), None);
+    }
+
+    #[test]
+    fn progress_lines() {
+        assert_eq!(
+            parse_progress("     1,234,567  45%   10.00MB/s    0:00:10 (xfr#1, to-chk=0/3)"),
+            Some(Progress {
+                bytes: 1_234_567,
+                percent: 45
+            })
+        );
+        assert_eq!(parse_progress("sending incremental file list"), None);
+        assert_eq!(parse_progress(""), None);
+    }
+
+    #[test]
+    fn stats_summary() {
+        let mut stats = CopyStats::default();
+        for line in [
+            "Number of files: 5 (reg: 3, dir: 2)",
+            "Number of regular files transferred: 1",
+            "Total file size: 1,048,576 bytes",
+            "Total transferred file size: 524,288 bytes",
+        ] {
+            read_stats(line, &mut stats);
+        }
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.bytes, 1_048_576);
+    }
+
+    #[test]
+    fn directories_are_copied_by_content_into_a_hidden_staging_path() {
+        let staging = staging_path(Path::new("/movies/Some.Movie"));
+        assert_eq!(staging, Path::new("/movies/.Some.Movie.partial"));
+
+        let dir = std::env::temp_dir();
+        let (src, dst) = arguments(&dir, &staging);
+        assert!(src.ends_with('/'));
+        assert_eq!(dst, "/movies/.Some.Movie.partial/");
+
+        let (src, dst) = arguments(Path::new("/downloads/movie.mkv"), &staging);
+        assert_eq!(src, "/downloads/movie.mkv");
+        assert_eq!(dst, "/movies/.Some.Movie.partial");
+    }
+}
@@ -0,0 +1,215 @@
+//! Transmission's own preferences, its "session" in RPC terms, for the
+//! Daemon preferences card in Settings.
+//!
+//! These calls are made with plain JSON-RPC rather than transmission-rpc's
+//! `session_get`/`session_set`: its session types fill in defaults for the
+//! fields an older daemon doesn't have, and those fields must be shown
+//! read-only instead of being saved back.
+
+use privateer_wire_types::{SessionConfig, TransmissionConfig};
+use serde_json::{json, Map, Value};
+
+use crate::error::TransmissionError;
+use crate::http::{HttpRequest, ReqwestClient};
+
+/// Header carrying Transmission's CSRF token, handed out with a 409.
+const SESSION_ID_HEADER: &str = "X-Transmission-Session-Id";
+
+const DOWNLOAD_DIR: &str = "download-dir";
+const INCOMPLETE_DIR_ENABLED: &str = "incomplete-dir-enabled";
+const INCOMPLETE_DIR: &str = "incomplete-dir";
+const SPEED_LIMIT_DOWN_ENABLED: &str = "speed-limit-down-enabled";
+const SPEED_LIMIT_DOWN: &str = "speed-limit-down";
+const SPEED_LIMIT_UP_ENABLED: &str = "speed-limit-up-enabled";
+const SPEED_LIMIT_UP: &str = "speed-limit-up";
+const SEED_RATIO_LIMITED: &str = "seedRatioLimited";
+const SEED_RATIO_LIMIT: &str = "seedRatioLimit";
+
+/// Every field in [`SessionConfig`], by its RPC name.
+const FIELDS: [&str; 9] = [
+    DOWNLOAD_DIR,
+    INCOMPLETE_DIR_ENABLED,
+    INCOMPLETE_DIR,
+    SPEED_LIMIT_DOWN_ENABLED,
+    SPEED_LIMIT_DOWN,
+    SPEED_LIMIT_UP_ENABLED,
+    SPEED_LIMIT_UP,
+    SEED_RATIO_LIMITED,
+    SEED_RATIO_LIMIT,
+];
+
+/// Body of a `session-get` for the fields in [`SessionConfig`].
+pub fn get_request() -> Value {
+    json!({
+        "method": "session-get",
+        "arguments": { "fields": FIELDS },
+    })
+}
+
+/// Read the fields from `session-get` arguments, leaving out any the daemon
+/// didn't send.
+pub fn parse_session(arguments: &Value) -> SessionConfig {
+    let string = |key| arguments.get(key)?.as_str().map(str::to_string);
+    let flag = |key| arguments.get(key)?.as_bool();
+    let limit = |key| u32::try_from(arguments.get(key)?.as_u64()?).ok();
+    SessionConfig {
+        download_dir: string(DOWNLOAD_DIR),
+        incomplete_dir_enabled: flag(INCOMPLETE_DIR_ENABLED),
+        incomplete_dir: string(INCOMPLETE_DIR),
+        speed_limit_down_enabled: flag(SPEED_LIMIT_DOWN_ENABLED),
+        speed_limit_down: limit(SPEED_LIMIT_DOWN),
+        speed_limit_up_enabled: flag(SPEED_LIMIT_UP_ENABLED),
+        speed_limit_up: limit(SPEED_LIMIT_UP),
+        seed_ratio_limited: flag(SEED_RATIO_LIMITED),
+        seed_ratio_limit: arguments.get(SEED_RATIO_LIMIT).and_then(Value::as_f64),
+    }
+}
+
+/// Body of a `session-set` for the fields of `session` the daemon has.
+pub fn set_request(session: &SessionConfig) -> Value {
+    let mut arguments = Map::new();
+    let mut set = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            arguments.insert(key.to_string(), value);
+        }
+    };
+    set(DOWNLOAD_DIR, session.download_dir.clone().map(Value::from));
+    set(
+        INCOMPLETE_DIR_ENABLED,
+        session.incomplete_dir_enabled.map(Value::from),
+    );
+    set(
+        INCOMPLETE_DIR,
+        session.incomplete_dir.clone().map(Value::from),
+    );
+    set(
+        SPEED_LIMIT_DOWN_ENABLED,
+        session.speed_limit_down_enabled.map(Value::from),
+    );
+    set(SPEED_LIMIT_DOWN, session.speed_limit_down.map(Value::from));
+    set(
+        SPEED_LIMIT_UP_ENABLED,
+        session.speed_limit_up_enabled.map(Value::from),
+    );
+    set(SPEED_LIMIT_UP, session.speed_limit_up.map(Value::from));
+    set(
+        SEED_RATIO_LIMITED,
+        session.seed_ratio_limited.map(Value::from),
+    );
+    set(SEED_RATIO_LIMIT, session.seed_ratio_limit.map(Value::from));
+    json!({ "method": "session-set", "arguments": arguments })
+}
+
+/// The arguments of a response, or its `result` as the error when it isn't
+/// "success".
+fn response_arguments(response: &[u8]) -> Result<Value, TransmissionError> {
+    let response: Value = serde_json::from_slice(response).map_err(|e| TransmissionError::Rpc {
+        message: format!("unreadable response: {e}"),
+    })?;
+    match response.get("result").and_then(Value::as_str) {
+        Some("success") => Ok(response.get("arguments").cloned().unwrap_or_default()),
+        Some(result) => Err(TransmissionError::Rpc {
+            message: result.to_string(),
+        }),
+        None => Err(TransmissionError::Rpc {
+            message: "response has no result".to_string(),
+        }),
+    }
+}
+
+/// Make an RPC call, going through the CSRF handshake, and return the
+/// response's arguments.
+pub async fn call(config: &TransmissionConfig, body: &Value) -> Result<Value, TransmissionError> {
+    let url = crate::transmission::rpc_url(config)?;
+    let http = ReqwestClient::with_timeout(config.rpc_timeout());
+    let profile = config.active();
+    let auth = match (profile.username.as_deref(), profile.password.as_deref()) {
+        (Some(user), Some(password)) if !user.is_empty() => Some((user, password)),
+        _ => None,
+    };
+    let mut session_id = None;
+    // The first request only fetches the session id
+    for _ in 0..2 {
+        let mut request = HttpRequest::post_json(url.clone(), body.to_string());
+        if let Some(id) = &session_id {
+            request = request.header(SESSION_ID_HEADER, id);
+        }
+        let sent = http.post_json(request, auth).await;
+        let response = sent.map_err(|e| TransmissionError::Connection {
+            message: e.to_string(),
+        })?;
+        match response.status {
+            409 => {
+                session_id = response.header(SESSION_ID_HEADER).map(str::to_string);
+            }
+            401 | 403 => {
+                return Err(TransmissionError::Auth {
+                    message: format!("HTTP {}", response.status),
+                })
+            }
+            200..=299 => return response_arguments(&response.body),
+            status => {
+                return Err(TransmissionError::Rpc {
+                    message: format!("HTTP {status}"),
+                })
+            }
+        }
+    }
+    Err(TransmissionError::Rpc {
+        message: "Transmission kept asking for a new session id".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_are_unsupported() {
+        // As sent by a daemon that predates the incomplete directory
+        let arguments = json!({
+            "download-dir": "/downloads",
+            "speed-limit-down-enabled": true,
+            "speed-limit-down": 500,
+            "speed-limit-up-enabled": false,
+            "speed-limit-up": 100,
+            "seedRatioLimited": true,
+            "seedRatioLimit": 2.5,
+        });
+        let session = parse_session(&arguments);
+        assert_eq!(
+            SessionConfig {
+                download_dir: Some("/downloads".into()),
+                incomplete_dir_enabled: None,
+                incomplete_dir: None,
+                speed_limit_down_enabled: Some(true),
+                speed_limit_down: Some(500),
+                speed_limit_up_enabled: Some(false),
+                speed_limit_up: Some(100),
+                seed_ratio_limited: Some(true),
+                seed_ratio_limit: Some(2.5),
+            },
+            session
+        );
+
+        // Unsupported fields aren't sent back
+        let request = set_request(&session);
+        assert_eq!("session-set", request["method"]);
+        let sent = request["arguments"].as_object().unwrap();
+        assert_eq!(7, sent.len());
+        assert!(!sent.contains_key(INCOMPLETE_DIR));
+        assert_eq!(arguments, request["arguments"]);
+    }
+
+    #[test]
+    fn rpc_failures_are_returned_verbatim() {
+        let ok = response_arguments(br#"{"result":"success","arguments":{"a":1}}"#);
+        assert_eq!(json!({ "a": 1 }), ok.unwrap());
+        let failed = response_arguments(br#"{"result":"download directory path is not absolute"}"#);
+        assert!(matches!(
+            failed,
+            Err(TransmissionError::Rpc { message })
+                if message == "download directory path is not absolute"
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+//! Reading completed downloads from the Transmission host over SFTP.
+//!
+//! For a seedbox with no share mounted locally: the copy engine streams each
+//! file from Transmission's download dir into the local destination.  The
+//! host key must already be in `~/.ssh/known_hosts` — logging in once with
+//! `ssh` adds it — so a stranger answering on that address is refused.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use privateer_wire_types::SftpConfig;
+use russh::client;
+use russh::keys::{self, PrivateKeyWithHashAlg};
+use russh_sftp::client::SftpSession;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::copier::{Heartbeat, SourceFs, COPY_BUFFER_BYTES};
+use crate::error::*;
+
+/// How long connecting and logging in may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Drop the connection after this long without traffic.
+const INACTIVITY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Accepts the server only if its key is in `~/.ssh/known_hosts`.
+struct KnownHosts {
+    host: String,
+    port: u16,
+}
+
+impl client::Handler for KnownHosts {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, key: &keys::PublicKey) -> Result<bool, Self::Error> {
+        let known = keys::check_known_hosts(&self.host, self.port, key)?;
+        if !known {
+            log::warn!(
+                "SFTP: {} is not in ~/.ssh/known_hosts; connect once with ssh to trust it",
+                self.host
+            );
+        }
+        Ok(known)
+    }
+}
+
+/// An open SFTP session on the Transmission host.
+pub struct SftpSource {
+    sftp: SftpSession,
+    /// Keeps the SSH connection up for as long as the SFTP session.
+    _session: client::Handle<KnownHosts>,
+}
+
+impl SftpSource {
+    /// Connect and log in, naming the host in any error.
+    pub async fn connect(config: &SftpConfig) -> Result<Self, CopyError> {
+        let host = config.host.trim().to_string();
+        let opened = tokio::time::timeout(CONNECT_TIMEOUT, Self::open(config, &host))
+            .await
+            .unwrap_or_else(|_| Err("timed out connecting".to_string()));
+        opened.map_err(|message| CopyError::CopySourceUnavailable { host, message })
+    }
+
+    async fn open(config: &SftpConfig, host: &str) -> Result<Self, String> {
+        let ssh_config = Arc::new(client::Config {
+            inactivity_timeout: Some(INACTIVITY_TIMEOUT),
+            ..Default::default()
+        });
+        let handler = KnownHosts {
+            host: host.to_string(),
+            port: config.port,
+        };
+        let mut session = client::connect(ssh_config, (host, config.port), handler)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let key_path = config
+            .key_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty());
+        let auth = match key_path {
+            Some(path) => {
+                let key = keys::load_secret_key(path, config.password.as_deref())
+                    .map_err(|e| format!("can't load key '{path}': {e}"))?;
+                let hash = session
+                    .best_supported_rsa_hash()
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .flatten();
+                session
+                    .authenticate_publickey(
+                        &config.user,
+                        PrivateKeyWithHashAlg::new(Arc::new(key), hash),
+                    )
+                    .await
+            }
+            None => {
+                session
+                    .authenticate_password(
+                        &config.user,
+                        config.password.clone().unwrap_or_default(),
+                    )
+                    .await
+            }
+        }
+        .map_err(|e| e.to_string())?;
+        if !auth.success() {
+            return Err(format!("login as '{}' was refused", config.user));
+        }
+
+        let channel = session
+            .channel_open_session()
+            .await
+            .map_err(|e| e.to_string())?;
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .map_err(|e| e.to_string())?;
+        let sftp = SftpSession::new(channel.into_stream())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            sftp,
+            _session: session,
+        })
+    }
+}
+
+/// `path` as the server expects it.
+fn remote(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+impl SourceFs for SftpSource {
+    async fn exists(&self, path: &Path) -> bool {
+        self.sftp.try_exists(remote(path)).await.unwrap_or(false)
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        self.sftp
+            .metadata(remote(path))
+            .await
+            .is_ok_and(|metadata| metadata.is_dir())
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let entries = self
+            .sftp
+            .read_dir(remote(path))
+            .await
+            .map_err(std::io::Error::other)?;
+        Ok(entries
+            .map(|entry| entry.file_name())
+            .filter(|name| name != "." && name != "..")
+            .map(|name| path.join(name))
+            .collect())
+    }
+
+    async fn len(&self, path: &Path) -> std::io::Result<u64> {
+        let metadata = self
+            .sftp
+            .metadata(remote(path))
+            .await
+            .map_err(std::io::Error::other)?;
+        Ok(metadata.size.unwrap_or_default())
+    }
+
+    async fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        heartbeat: &Heartbeat,
+    ) -> std::io::Result<u64> {
+        let mut file = self
+            .sftp
+            .open(remote(src))
+            .await
+            .map_err(std::io::Error::other)?;
+        let mut writer = tokio::fs::File::create(dst).await?;
+        let mut buffer = vec![0; COPY_BUFFER_BYTES];
+        let mut copied = 0;
+        loop {
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read]).await?;
+            heartbeat.beat(read as u64);
+            copied += read as u64;
+        }
+        writer.flush().await?;
+        Ok(copied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connection_failures_name_the_host() {
+        let config = SftpConfig {
+            host: " 127.0.0.1 ".into(),
+            port: 1,
+            user: "seed".into(),
+            ..Default::default()
+        };
+
+        let error = SftpSource::connect(&config).await.err().unwrap();
+
+        assert!(matches!(
+            &error,
+            CopyError::CopySourceUnavailable { host, .. } if host == "127.0.0.1"
+        ));
+        assert!(error
+            .to_string()
+            .starts_with("Can't read downloads from '127.0.0.1'"));
+    }
+}
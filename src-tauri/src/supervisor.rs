@@ -0,0 +1,91 @@
+//! Keeps the background copy task alive when one of its cycles panics.
+//!
+//! Each cycle runs through [`catch_panic`], so a panic ends the cycle
+//! rather than the task.  The task then waits out a [`cooldown`] that grows
+//! with every panic in a row, so a cycle that panics every time can't spin,
+//! and tries again.  The panic itself, with its backtrace, is written to the
+//! log by the hook in [`crate::logging`].
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::task::Poll;
+use std::time::Duration;
+
+/// Wait after the first panic in a row.
+pub const CRASH_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Longest wait between cycles that keep panicking.
+pub const MAX_CRASH_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+/// Run `future` to completion, or to the point it panics, returning the
+/// panic's message.
+///
+/// Whatever the future borrowed may be left half-updated by the panic; the
+/// caller is expected to put it right before using it again.
+pub async fn catch_panic<F: Future>(future: F) -> Result<F::Output, String> {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(|cx| {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(panic_message(payload.as_ref()))),
+        }
+    })
+    .await
+}
+
+/// The message a panic was raised with.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// How long to wait after `crashes` panics in a row: [`CRASH_COOLDOWN`],
+/// doubling with each further panic up to [`MAX_CRASH_COOLDOWN`].
+pub fn cooldown(crashes: u32) -> Duration {
+    let doublings = crashes.saturating_sub(1).min(16);
+    CRASH_COOLDOWN
+        .saturating_mul(1 << doublings)
+        .min(MAX_CRASH_COOLDOWN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn panics_become_errors() {
+        assert_eq!(Ok(3), catch_panic(async { 1 + 2 }).await);
+
+        // Panics after the first poll are caught too
+        let index = 7;
+        let result = catch_panic(async move {
+            tokio::task::yield_now().await;
+            let entries: Vec<u32> = vec![];
+            entries[index]
+        })
+        .await;
+        assert_eq!(
+            Err("index out of bounds: the len is 0 but the index is 7".to_string()),
+            result
+        );
+
+        let result = catch_panic(async { std::panic::panic_any(42) }).await;
+        assert_eq!(Err::<(), _>("unknown panic".to_string()), result);
+    }
+
+    #[test]
+    fn cooldown_doubles_up_to_a_limit() {
+        assert_eq!(CRASH_COOLDOWN, cooldown(0));
+        assert_eq!(CRASH_COOLDOWN, cooldown(1));
+        assert_eq!(CRASH_COOLDOWN * 2, cooldown(2));
+        assert_eq!(CRASH_COOLDOWN * 4, cooldown(3));
+        assert_eq!(MAX_CRASH_COOLDOWN, cooldown(7));
+        assert_eq!(MAX_CRASH_COOLDOWN, cooldown(u32::MAX));
+    }
+}
@@ -0,0 +1,122 @@
+//! Downloads the client keeps reporting an error for, which likely won't
+//! recover on their own, e.g. a torrent the tracker no longer knows.
+//!
+//! The torrents task counts the polls in a row each download in the ledger
+//! has had an error.  After [`BROKEN_AFTER_POLLS`] it needs replacing, and
+//! the Downloads tab offers a search for another release.  A poll without
+//! the error starts the count again.  The counts are only kept in memory.
+
+use std::collections::HashMap;
+
+use privateer_wire_types::{InfoHash, TransmissionTorrent};
+
+use crate::release_name;
+
+/// Polls in a row with an error before a download needs replacing, at
+/// least half a minute.
+pub const BROKEN_AFTER_POLLS: u32 = 10;
+
+/// Whether `t`'s errors are counted: it is in the ledger and has an error.
+fn has_error(t: &TransmissionTorrent) -> bool {
+    t.destination.is_some() && t.error != 0
+}
+
+/// How many polls in a row each download has had an error, by info hash.
+#[derive(Default)]
+pub struct TorrentErrors {
+    polls: HashMap<InfoHash, u32>,
+}
+
+impl TorrentErrors {
+    /// Count a poll of `torrents`, forgetting those without an error.
+    pub fn record(&mut self, torrents: &[TransmissionTorrent]) {
+        self.polls.retain(|hash, _| {
+            torrents
+                .iter()
+                .any(|t| &t.hash_string == hash && has_error(t))
+        });
+        for t in torrents.iter().filter(|t| has_error(t)) {
+            let polls = self.polls.entry(t.hash_string.clone()).or_default();
+            *polls = polls.saturating_add(1);
+        }
+    }
+
+    /// Flag the downloads that have had an error for long enough, with a
+    /// search for a replacement.
+    pub fn attach(&self, torrents: &mut [TransmissionTorrent]) {
+        for t in torrents {
+            t.needs_replacement = has_error(t)
+                && self
+                    .polls
+                    .get(&t.hash_string)
+                    .is_some_and(|polls| *polls >= BROKEN_AFTER_POLLS);
+            if t.needs_replacement && t.search_query.is_none() {
+                t.search_query = Some(release_name::search_query(&t.name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use privateer_wire_types::{Destination, TransmissionStatus};
+
+    use super::*;
+
+    fn torrent(error: i64) -> TransmissionTorrent {
+        TransmissionTorrent {
+            id: 1,
+            name: "The.Bear.S02E05.1080p.WEB.h264-ETHEL".to_string(),
+            status: TransmissionStatus::Downloading,
+            percent_done: 0.5,
+            error,
+            error_string: "Torrent not registered with this tracker".to_string(),
+            destination: Some(Destination::Shows),
+            ..TransmissionTorrent::new("a".repeat(40).parse().unwrap())
+        }
+    }
+
+    fn poll(errors: &mut TorrentErrors, t: &TransmissionTorrent, times: u32) {
+        for _ in 0..times {
+            errors.record(std::slice::from_ref(t));
+        }
+    }
+
+    #[test]
+    fn persistent_errors_need_replacing() {
+        let mut errors = TorrentErrors::default();
+        poll(&mut errors, &torrent(2), BROKEN_AFTER_POLLS - 1);
+        let mut torrents = [torrent(2)];
+        errors.attach(&mut torrents);
+        assert!(!torrents[0].needs_replacement);
+        assert_eq!(None, torrents[0].search_query);
+
+        poll(&mut errors, &torrent(2), 1);
+        errors.attach(&mut torrents);
+        assert!(torrents[0].needs_replacement);
+        assert_eq!(Some("The Bear S02E05"), torrents[0].search_query.as_deref());
+    }
+
+    #[test]
+    fn a_poll_without_the_error_starts_again() {
+        let mut errors = TorrentErrors::default();
+        poll(&mut errors, &torrent(2), BROKEN_AFTER_POLLS - 1);
+        poll(&mut errors, &torrent(0), 1);
+        poll(&mut errors, &torrent(2), BROKEN_AFTER_POLLS - 1);
+        let mut torrents = [torrent(2)];
+        errors.attach(&mut torrents);
+        assert!(!torrents[0].needs_replacement);
+    }
+
+    #[test]
+    fn only_downloads_in_the_ledger_need_replacing() {
+        let mut errors = TorrentErrors::default();
+        let mut untracked = torrent(3);
+        untracked.destination = None;
+        poll(&mut errors, &untracked, BROKEN_AFTER_POLLS);
+        let mut torrents = [untracked];
+        errors.attach(&mut torrents);
+        assert!(!torrents[0].needs_replacement);
+        assert!(errors.polls.is_empty());
+    }
+}
@@ -0,0 +1,495 @@
+//! The Transmission RPC daemon as a [`DownloadClient`], through
+//! transmission-rpc.
+
+use std::time::Duration;
+
+use privateer_wire_types::{
+    sanitize_fraction, sanitize_rate, DaemonInfo, InfoHash, TransmissionConfig, TransmissionStatus,
+};
+use serde_json::{json, Value};
+use snafu::ResultExt;
+use transmission_rpc::types::{
+    BasicAuth, Id, RpcResponse, RpcResponseArgument, Torrent, TorrentAction, TorrentAddArgs,
+    TorrentAddedOrDuplicate, TorrentGetField,
+};
+use transmission_rpc::TransClient;
+
+use crate::copier::TorrentFile;
+use crate::download_client::{ClientTorrent, DownloadClient};
+use crate::error::*;
+use crate::session;
+
+/// The fields of [`ClientTorrent`], as `torrent-get` names them.
+fn torrent_fields() -> Vec<TorrentGetField> {
+    vec![
+        TorrentGetField::Id,
+        TorrentGetField::Name,
+        TorrentGetField::HashString,
+        TorrentGetField::Status,
+        TorrentGetField::PercentDone,
+        TorrentGetField::RecheckProgress,
+        TorrentGetField::RateDownload,
+        TorrentGetField::RateUpload,
+        TorrentGetField::Eta,
+        TorrentGetField::UploadRatio,
+        TorrentGetField::UploadedEver,
+        TorrentGetField::SecondsSeeding,
+        TorrentGetField::AddedDate,
+        TorrentGetField::DoneDate,
+        TorrentGetField::IsFinished,
+        TorrentGetField::SizeWhenDone,
+        TorrentGetField::PeersConnected,
+        TorrentGetField::PeersSendingToUs,
+        TorrentGetField::PeersGettingFromUs,
+        TorrentGetField::Error,
+        TorrentGetField::ErrorString,
+        TorrentGetField::DownloadDir,
+    ]
+}
+
+/// Fail an RPC call with a `Connection` error if it takes longer than
+/// `timeout`, e.g. when the Transmission host is asleep.
+async fn rpc_timeout<T>(
+    timeout: Duration,
+    call: impl std::future::Future<Output = Result<T, TransmissionError>>,
+) -> Result<T, TransmissionError> {
+    tokio::time::timeout(timeout, call)
+        .await
+        .unwrap_or_else(|_| {
+            Err(TransmissionError::Connection {
+                message: format!("timed out after {}s", timeout.as_secs()),
+            })
+        })
+}
+
+/// The active profile's RPC endpoint.
+pub(crate) fn rpc_url(config: &TransmissionConfig) -> Result<url::Url, TransmissionError> {
+    let profile = config.active();
+    let url_str = format!("http://{}:{}/transmission/rpc", profile.host, profile.port);
+    url_str.parse().context(InvalidUrlSnafu {
+        url: url_str.clone(),
+    })
+}
+
+fn make_trans_client(config: &TransmissionConfig) -> Result<TransClient, TransmissionError> {
+    let url = rpc_url(config)?;
+
+    let profile = config.active();
+    let client = if let (Some(user), Some(password)) = (&profile.username, &profile.password) {
+        if !user.is_empty() {
+            TransClient::with_auth(
+                url,
+                BasicAuth {
+                    user: user.clone(),
+                    password: password.clone(),
+                },
+            )
+        } else {
+            TransClient::new(url)
+        }
+    } else {
+        TransClient::new(url)
+    };
+
+    Ok(client)
+}
+
+/// Unwrap a Transmission RPC call into its response arguments, mapping
+/// transport failures to `Connection` (or `Auth` for 401/403) and
+/// non-success results to `Rpc`.
+fn rpc_result<T: RpcResponseArgument, E: std::fmt::Display>(
+    result: Result<RpcResponse<T>, E>,
+) -> Result<T, TransmissionError> {
+    let response = result.map_err(|e| TransmissionError::from_transport(e.to_string()))?;
+    if response.is_ok() {
+        Ok(response.arguments)
+    } else {
+        Err(TransmissionError::Rpc {
+            message: response.result,
+        })
+    }
+}
+
+fn ids(hashes: &[InfoHash]) -> Vec<Id> {
+    hashes.iter().map(|h| Id::Hash(h.to_string())).collect()
+}
+
+/// A torrent from `torrent-get`, with Transmission's defaults for the
+/// fields it left out.
+fn client_torrent(t: Torrent) -> ClientTorrent {
+    ClientTorrent {
+        id: t.id.unwrap_or(-1),
+        hash: t.hash_string.and_then(|hash| hash.parse().ok()),
+        name: t.name.unwrap_or_default(),
+        status: TransmissionStatus::from_code(t.status.map(|s| s as i64).unwrap_or(0)),
+        percent_done: sanitize_fraction(t.percent_done.unwrap_or(0.0) as f64),
+        recheck_progress: sanitize_fraction(t.recheck_progress.unwrap_or(0.0) as f64),
+        rate_download: sanitize_rate(t.rate_download.unwrap_or(0)),
+        rate_upload: sanitize_rate(t.rate_upload.unwrap_or(0)),
+        eta: t.eta.unwrap_or(-1),
+        upload_ratio: t.upload_ratio.unwrap_or(-1.0) as f64,
+        uploaded_ever: t.uploaded_ever.unwrap_or(0),
+        seconds_seeding: t.seconds_seeding.unwrap_or(0),
+        added_date: t.added_date.map(|d| d.timestamp()).unwrap_or(0),
+        done_date: t.done_date.map(|d| d.timestamp()).unwrap_or(0),
+        is_finished: t.is_finished.unwrap_or(false),
+        size_when_done: t.size_when_done.unwrap_or(0),
+        peers_connected: t.peers_connected.unwrap_or(0),
+        peers_sending_to_us: t.peers_sending_to_us.unwrap_or(0),
+        peers_getting_from_us: t.peers_getting_from_us.unwrap_or(0),
+        error: t.error.map(|e| e as i64).unwrap_or(0),
+        error_string: t.error_string.unwrap_or_default(),
+        download_dir: t.download_dir,
+    }
+}
+
+/// A Transmission daemon.  Reusing one keeps Transmission's CSRF session
+/// id, so only the first request goes through the 409 handshake.
+pub struct TransmissionClient {
+    client: TransClient,
+    /// For the calls made with plain JSON-RPC, see [`session`].
+    config: TransmissionConfig,
+    timeout: Duration,
+}
+
+impl TransmissionClient {
+    pub fn new(config: &TransmissionConfig, timeout: Duration) -> Result<Self, TransmissionError> {
+        Ok(Self {
+            client: make_trans_client(config)?,
+            config: config.clone(),
+            timeout,
+        })
+    }
+
+    /// Run a torrent action (start, stop, ...) on the given torrents.
+    async fn action(
+        &mut self,
+        action: TorrentAction,
+        hashes: &[InfoHash],
+    ) -> Result<(), ClientError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let ids = ids(hashes);
+        rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_action(action, ids).await)
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+impl DownloadClient for TransmissionClient {
+    async fn daemon_info(&mut self) -> Result<DaemonInfo, ClientError> {
+        let session = rpc_timeout(self.timeout, async {
+            rpc_result(self.client.session_get().await)
+        })
+        .await?;
+        let version = if session.version.is_empty() {
+            "unknown".to_string()
+        } else {
+            session.version
+        };
+        Ok(DaemonInfo::transmission(
+            version,
+            i64::from(session.rpc_version),
+        ))
+    }
+
+    async fn torrents(&mut self) -> Result<Vec<ClientTorrent>, ClientError> {
+        let fields = torrent_fields();
+        let response = rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_get(Some(fields), None).await)
+        })
+        .await?;
+        Ok(response.torrents.into_iter().map(client_torrent).collect())
+    }
+
+    async fn files(&mut self, hash: &InfoHash) -> Result<Vec<TorrentFile>, ClientError> {
+        let fields = vec![TorrentGetField::Files, TorrentGetField::FileStats];
+        let ids = ids(std::slice::from_ref(hash));
+        let response = rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_get(Some(fields), Some(ids)).await)
+        })
+        .await?;
+        let Some(torrent) = response.torrents.into_iter().next() else {
+            return Ok(vec![]);
+        };
+        let stats = torrent.file_stats.unwrap_or_default();
+        Ok(torrent
+            .files
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(i, file)| TorrentFile {
+                name: file.name,
+                length: file.length.max(0) as u64,
+                wanted: stats.get(i).is_none_or(|stat| stat.wanted),
+            })
+            .collect())
+    }
+
+    async fn add(&mut self, url: String) -> Result<InfoHash, ClientError> {
+        let args = TorrentAddArgs {
+            filename: Some(url),
+            ..Default::default()
+        };
+        let added = rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_add(args).await)
+        })
+        .await?;
+        let hash = match added {
+            TorrentAddedOrDuplicate::TorrentAdded(torrent)
+            | TorrentAddedOrDuplicate::TorrentDuplicate(torrent) => torrent.hash_string,
+            TorrentAddedOrDuplicate::Error => None,
+        };
+        let hash = hash.and_then(|hash| hash.parse().ok());
+        Ok(hash.ok_or_else(|| TransmissionError::Rpc {
+            message: "Transmission didn't report the added torrent".into(),
+        })?)
+    }
+
+    async fn remove(&mut self, hashes: &[InfoHash], delete_data: bool) -> Result<(), ClientError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let ids = ids(hashes);
+        rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_remove(ids, delete_data).await)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn pause(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.action(TorrentAction::Stop, hashes).await
+    }
+
+    async fn resume(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.action(TorrentAction::Start, hashes).await
+    }
+
+    async fn verify(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.action(TorrentAction::Verify, hashes).await
+    }
+
+    async fn reannounce(&mut self, hashes: &[InfoHash]) -> Result<(), ClientError> {
+        self.action(TorrentAction::Reannounce, hashes).await
+    }
+
+    async fn set_location(&mut self, hashes: &[InfoHash], dir: &str) -> Result<(), ClientError> {
+        let ids = ids(hashes);
+        let dir = dir.to_string();
+        // Not moved: the files are already there
+        rpc_timeout(self.timeout, async {
+            rpc_result(
+                self.client
+                    .torrent_set_location(ids, dir, Some(false))
+                    .await,
+            )
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn rename_path(
+        &mut self,
+        hash: &InfoHash,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<(), ClientError> {
+        let ids = ids(std::slice::from_ref(hash));
+        let (path, name) = (old_path.to_string(), new_name.to_string());
+        rpc_timeout(self.timeout, async {
+            rpc_result(self.client.torrent_rename_path(ids, path, name).await)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn free_space(&mut self) -> Result<u64, ClientError> {
+        let request = json!({
+            "method": "session-get",
+            "arguments": { "fields": ["download-dir"] },
+        });
+        let session = session::call(&self.config, &request).await?;
+        let dir = session
+            .get("download-dir")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let request = json!({
+            "method": "free-space",
+            "arguments": { "path": dir },
+        });
+        let free = session::call(&self.config, &request).await?;
+        Ok(free
+            .get("size-bytes")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| TransmissionError::Rpc {
+                message: format!("free-space didn't report the size of '{dir}'"),
+            })?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_transmission::{self, MockTransmission};
+    use privateer_wire_types::{AppError, ClientCapabilities, ErrorKind};
+
+    const HASH: &str = "c9e15763f722f23e98a29decdfae341b98d53056";
+
+    #[test]
+    fn torrent_get_fields_become_client_torrents() {
+        // As in a torrent-get response
+        let torrent: Torrent = serde_json::from_value(json!({
+            "id": 7,
+            "hashString": HASH,
+            "name": "Some.Movie.2024",
+            "status": 4,
+            "percentDone": 0.5,
+            "rateDownload": 2048,
+            "eta": 600,
+            "uploadRatio": 0.25,
+            "addedDate": 1700000000,
+            "doneDate": 0,
+            "peersConnected": 5,
+            "peersSendingToUs": 3,
+            "error": 0,
+            "errorString": "",
+            "downloadDir": "/downloads",
+        }))
+        .unwrap();
+
+        let torrent = client_torrent(torrent);
+        assert_eq!(
+            ClientTorrent {
+                id: 7,
+                hash: Some(HASH.parse().unwrap()),
+                name: "Some.Movie.2024".into(),
+                status: TransmissionStatus::Downloading,
+                percent_done: 0.5,
+                rate_download: 2048,
+                eta: 600,
+                upload_ratio: 0.25,
+                added_date: 1_700_000_000,
+                peers_connected: 5,
+                peers_sending_to_us: 3,
+                download_dir: Some("/downloads".into()),
+                ..Default::default()
+            },
+            torrent
+        );
+    }
+
+    #[test]
+    fn missing_fields_get_transmissions_defaults() {
+        let torrent: Torrent = serde_json::from_value(json!({ "hashString": "nope" })).unwrap();
+        let torrent = client_torrent(torrent);
+        assert_eq!(None, torrent.hash);
+        assert_eq!(
+            (-1, -1, -1.0),
+            (torrent.id, torrent.eta, torrent.upload_ratio)
+        );
+        assert_eq!(TransmissionStatus::Stopped, torrent.status);
+    }
+
+    const ADDED: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn client(mock: &MockTransmission) -> TransmissionClient {
+        TransmissionClient::new(&mock.config(), Duration::from_secs(5)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn talks_to_the_daemon_with_one_session_id() {
+        let mock = MockTransmission::start(vec![mock_transmission::torrent(
+            1,
+            HASH,
+            "Some.Movie.2024",
+            "/downloads",
+        )])
+        .await;
+        let mut client = client(&mock);
+
+        let daemon = client.daemon_info().await.unwrap();
+        assert_eq!(
+            (mock_transmission::VERSION, Some(17)),
+            (daemon.version.as_str(), daemon.rpc_version)
+        );
+        assert_eq!(ClientCapabilities::ALL, daemon.capabilities);
+        let torrents = client.torrents().await.unwrap();
+        assert_eq!(1, torrents.len());
+        assert_eq!(Some(HASH.parse().unwrap()), torrents[0].hash);
+        assert_eq!(TransmissionStatus::Seeding, torrents[0].status);
+        assert_eq!(1.0, torrents[0].percent_done);
+
+        let hash = HASH.parse().unwrap();
+        let files = client.files(&hash).await.unwrap();
+        assert_eq!(
+            vec![TorrentFile {
+                name: "Some.Movie.2024/Some.Movie.2024.mkv".into(),
+                length: 1000,
+                wanted: true,
+            }],
+            files
+        );
+
+        let magnet = format!("magnet:?xt=urn:btih:{ADDED}&dn=Another.Movie.2023");
+        let added = client.add(magnet.clone()).await.unwrap();
+        assert_eq!(ADDED, added.as_str());
+        // Adding it again reports the one already there
+        assert_eq!(added, client.add(magnet).await.unwrap());
+        assert_eq!(2, mock.torrents().len());
+
+        client.remove(&[hash], false).await.unwrap();
+        let left = mock.torrents();
+        assert_eq!(1, left.len());
+        assert_eq!(ADDED, left[0]["hashString"]);
+
+        assert_eq!(1, mock.handshakes());
+        assert_eq!(
+            [
+                "session-get",
+                "torrent-get",
+                "torrent-get",
+                "torrent-add",
+                "torrent-add",
+                "torrent-remove"
+            ],
+            mock.calls().as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn free_space_is_read_for_the_download_dir() {
+        let mock = MockTransmission::start(vec![]).await;
+        let free = client(&mock).free_space().await.unwrap();
+        assert_eq!(mock_transmission::FREE_SPACE, free);
+        assert_eq!(["session-get", "free-space"], mock.calls().as_slice());
+    }
+
+    fn kind(result: Result<impl std::fmt::Debug, ClientError>) -> ErrorKind {
+        AppError::from(result.unwrap_err()).kind
+    }
+
+    #[tokio::test]
+    async fn http_errors_map_to_error_kinds() {
+        let mock = MockTransmission::start(vec![]).await;
+        let mut client = client(&mock);
+
+        mock.fail_with(401);
+        assert_eq!(ErrorKind::TransmissionAuth, kind(client.free_space().await));
+
+        // A daemon that never accepts the session id it hands out
+        mock.fail_with(409);
+        assert_eq!(ErrorKind::TransmissionRpc, kind(client.free_space().await));
+
+        mock.fail_with(500);
+        assert_eq!(ErrorKind::TransmissionRpc, kind(client.free_space().await));
+        // transmission-rpc doesn't expose the status, so it can only tell
+        // that the daemon didn't answer
+        let daemon = client.daemon_info().await;
+        assert!(daemon.as_ref().is_err_and(ClientError::is_connection));
+        assert!(mock.calls().is_empty());
+    }
+}
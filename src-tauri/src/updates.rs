@@ -0,0 +1,91 @@
+//! Looking for a release newer than the running build, on GitHub.
+
+use privateer_wire_types::UpdateInfo;
+use snafu::{ensure, ResultExt};
+
+use crate::error::{UpdateError, UpdateParseSnafu, UpdateRequestSnafu, UpdateStatusSnafu};
+use crate::http::ReqwestClient;
+
+/// The latest published release, not counting drafts and pre-releases.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/schell/privateer/releases/latest";
+
+/// The parts of GitHub's release JSON we use.
+#[derive(serde::Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+}
+
+/// The numbers of a version like "v0.2.0" or "0.2.0-beta.1", ignoring any
+/// pre-release suffix.
+fn version_numbers(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let release = version.split(['-', '+']).next()?;
+    release.split('.').map(|n| n.parse().ok()).collect()
+}
+
+/// Whether `tag` is a later version than `current`.
+fn is_newer(tag: &str, current: &str) -> bool {
+    match (version_numbers(tag), version_numbers(current)) {
+        (Some(tag), Some(current)) => tag > current,
+        _ => false,
+    }
+}
+
+/// The release in a response, if it is newer than `current`.
+fn newer_release(body: &[u8], current: &str) -> Result<Option<UpdateInfo>, UpdateError> {
+    let release: Release = serde_json::from_slice(body).context(UpdateParseSnafu)?;
+    Ok(is_newer(&release.tag_name, current).then_some(UpdateInfo {
+        version: release.tag_name,
+        url: release.html_url,
+    }))
+}
+
+/// Ask GitHub for the latest release, returning it if it is newer than
+/// `current`.
+pub async fn check(http: &ReqwestClient, current: &str) -> Result<Option<UpdateInfo>, UpdateError> {
+    let url = LATEST_RELEASE_URL
+        .parse()
+        .expect("the release URL is valid");
+    let (status, body) = http.get(url).await.context(UpdateRequestSnafu)?;
+    // No release has been published yet
+    if status == 404 {
+        return Ok(None);
+    }
+    ensure!((200..300).contains(&status), UpdateStatusSnafu { status });
+    newer_release(&body, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_newer("v0.10.0", "0.9.3"));
+        assert!(is_newer("0.2.1", "0.2.0"));
+        assert!(is_newer("v1.0", "0.2.0"));
+        assert!(!is_newer("v0.2.0", "0.2.0"));
+        assert!(!is_newer("v0.2.0-beta.1", "0.2.0"));
+        assert!(!is_newer("v0.1.9", "0.2.0"));
+        assert!(!is_newer("nightly", "0.2.0"));
+    }
+
+    #[test]
+    fn only_newer_releases_are_offered() {
+        let body = br#"{
+            "tag_name": "v0.3.0",
+            "html_url": "https://github.com/schell/privateer/releases/tag/v0.3.0",
+            "draft": false
+        }"#;
+        assert_eq!(
+            Some(UpdateInfo {
+                version: "v0.3.0".into(),
+                url: "https://github.com/schell/privateer/releases/tag/v0.3.0".into(),
+            }),
+            newer_release(body, "0.2.0").unwrap()
+        );
+        assert_eq!(None, newer_release(body, "0.3.0").unwrap());
+        assert!(newer_release(b"<html>rate limited</html>", "0.2.0").is_err());
+    }
+}
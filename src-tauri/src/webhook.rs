@@ -0,0 +1,495 @@
+//! Posting download and copy events to a webhook, e.g. for Home Assistant.
+//!
+//! Each event is posted as a JSON [`WebhookPayload`].  Failed posts are
+//! retried a couple of times; events queue up behind them so receivers see
+//! them in order.  With a shared secret configured, the body is signed with
+//! HMAC-SHA256 so receivers can check it came from Privateer.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use privateer_wire_types::{
+    CopyState, Destination, TransmissionConfig, WebhookConfig, WebhookEvents,
+};
+use sha2::Sha256;
+use snafu::ResultExt;
+use tokio::sync::mpsc;
+
+use crate::copier::{CopyEvents, CopyStats};
+use crate::error::*;
+use crate::http::{HttpClient, HttpRequest};
+use crate::unix_now;
+
+/// Header carrying `sha256=<hex HMAC of the body>`.
+const SIGNATURE_HEADER: &str = "X-Privateer-Signature";
+
+/// Each post gives up after this long.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts made for an event before giving up.
+const ATTEMPTS: u32 = 3;
+
+/// Pause before the first retry, doubled before each one after.
+const RETRY_PAUSE: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    DownloadComplete,
+    CopyStarted,
+    CopyCompleted,
+    CopyFailed,
+    /// Sent by the "Send test webhook" button.
+    Test,
+}
+
+impl WebhookEvent {
+    fn is_enabled(self, events: &WebhookEvents) -> bool {
+        match self {
+            Self::DownloadComplete => events.download_complete,
+            Self::CopyStarted => events.copy_started,
+            Self::CopyCompleted => events.copy_completed,
+            Self::CopyFailed => events.copy_failed,
+            Self::Test => true,
+        }
+    }
+}
+
+/// The JSON body of a webhook post.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub name: String,
+    pub hash: String,
+    /// `None` for downloads, which aren't tied to a destination.
+    pub destination: Option<Destination>,
+    /// Unix timestamp of the event.
+    pub timestamp: i64,
+    /// Unix timestamp the copy started, for copy events.
+    pub copy_started_at: Option<i64>,
+    /// Why the copy failed, for `copy_failed`.
+    pub error: Option<String>,
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `body` keyed by `secret`.
+pub fn signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+fn request(config: &WebhookConfig, payload: &WebhookPayload) -> Result<HttpRequest, WebhookError> {
+    let url = config.url.trim();
+    let url = url.parse().context(WebhookUrlSnafu { url })?;
+    let body = serde_json::to_string(payload).context(WebhookSerializeSnafu)?;
+    let signature = config
+        .secret
+        .as_deref()
+        .filter(|secret| !secret.is_empty())
+        .map(|secret| signature(secret, &body));
+    let mut request = HttpRequest::post_json(url, body);
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    Ok(request)
+}
+
+/// Post `payload` once, returning the response status.
+async fn post(
+    client: &impl HttpClient,
+    config: &WebhookConfig,
+    payload: &WebhookPayload,
+) -> Result<u16, WebhookError> {
+    let status = client
+        .send(request(config, payload)?)
+        .await
+        .context(WebhookRequestSnafu)?;
+    if (200..300).contains(&status) {
+        Ok(status)
+    } else {
+        WebhookStatusSnafu { status }.fail()
+    }
+}
+
+/// Post `payload`, retrying when the receiver can't be reached or has a
+/// server error.
+pub async fn deliver(
+    client: &impl HttpClient,
+    config: &WebhookConfig,
+    payload: &WebhookPayload,
+) -> Result<u16, WebhookError> {
+    let mut pause = RETRY_PAUSE;
+    let mut attempt = 1;
+    loop {
+        let result = post(client, config, payload).await;
+        let is_transient = match &result {
+            Err(WebhookError::WebhookRequest { .. }) => true,
+            Err(WebhookError::WebhookStatus { status }) => *status >= 500,
+            _ => false,
+        };
+        if !is_transient || attempt == ATTEMPTS {
+            return result;
+        }
+        log::debug!("Webhook attempt {attempt} failed, retrying in {pause:?}");
+        tokio::time::sleep(pause).await;
+        pause *= 2;
+        attempt += 1;
+    }
+}
+
+/// Post a synthetic event once, returning the response status.
+pub async fn send_test(
+    client: &impl HttpClient,
+    config: &WebhookConfig,
+) -> Result<u16, WebhookError> {
+    let payload = WebhookPayload {
+        event: WebhookEvent::Test,
+        name: "Privateer test event".into(),
+        hash: String::new(),
+        destination: None,
+        timestamp: unix_now(),
+        copy_started_at: None,
+        error: None,
+    };
+    post(client, config, &payload).await
+}
+
+/// An event queued by [`WebhookSender`].
+pub struct WebhookJob {
+    config: WebhookConfig,
+    payload: WebhookPayload,
+}
+
+/// Queues the events the webhook asks for.
+///
+/// Failed copies are retried every cycle; a retry posts neither
+/// `copy_started` nor `copy_failed` again, only `copy_completed` if it
+/// succeeds.
+pub struct WebhookSender {
+    jobs: mpsc::UnboundedSender<WebhookJob>,
+    /// Info hashes seen below 100%; see `Notifier` in `lib.rs`.
+    downloading: HashSet<String>,
+    /// Whether the copy in progress is a retry of a failed one.
+    retrying: bool,
+    /// When the copy in progress started.
+    copy_started_at: Option<i64>,
+}
+
+impl WebhookSender {
+    /// The sender and the queue to hand to [`webhook_task`].
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<WebhookJob>) {
+        let (jobs, rx) = mpsc::unbounded_channel();
+        let sender = Self {
+            jobs,
+            downloading: HashSet::new(),
+            retrying: false,
+            copy_started_at: None,
+        };
+        (sender, rx)
+    }
+
+    /// Queue `payload` if the webhook is set up and wants its event.
+    fn queue(&self, config: &TransmissionConfig, payload: WebhookPayload) {
+        let Some(webhook) = config.webhook.as_ref() else {
+            return;
+        };
+        if webhook.url.trim().is_empty() || !payload.event.is_enabled(&webhook.events) {
+            return;
+        }
+        let _ = self.jobs.send(WebhookJob {
+            config: webhook.clone(),
+            payload,
+        });
+    }
+
+    /// A payload for the copy in progress.
+    fn copy_payload(
+        &self,
+        event: WebhookEvent,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+    ) -> WebhookPayload {
+        WebhookPayload {
+            event,
+            name: name.to_string(),
+            hash: hash.to_string(),
+            destination: Some(dest),
+            timestamp: unix_now(),
+            copy_started_at: self.copy_started_at,
+            error: None,
+        }
+    }
+}
+
+impl CopyEvents for WebhookSender {
+    fn observe_download(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        percent: f32,
+    ) {
+        if percent < 1.0 {
+            self.downloading.insert(hash.to_string());
+        } else if self.downloading.remove(hash) {
+            let payload = WebhookPayload {
+                event: WebhookEvent::DownloadComplete,
+                name: name.to_string(),
+                hash: hash.to_string(),
+                destination: None,
+                timestamp: unix_now(),
+                copy_started_at: None,
+                error: None,
+            };
+            self.queue(config, payload);
+        }
+    }
+
+    fn copy_started(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        previous: CopyState,
+    ) {
+        self.retrying = previous == CopyState::Failed;
+        self.copy_started_at = Some(unix_now());
+        if !self.retrying {
+            let payload = self.copy_payload(WebhookEvent::CopyStarted, hash, name, dest);
+            self.queue(config, payload);
+        }
+    }
+
+    fn copy_complete(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        _path: &Path,
+        _stats: &CopyStats,
+    ) {
+        let payload = self.copy_payload(WebhookEvent::CopyCompleted, hash, name, dest);
+        self.queue(config, payload);
+        self.retrying = false;
+        self.copy_started_at = None;
+    }
+
+    fn copy_failed(
+        &mut self,
+        config: &TransmissionConfig,
+        hash: &str,
+        name: &str,
+        dest: Destination,
+        reason: &str,
+    ) {
+        // A torrent that can't be copied fails without starting
+        if !std::mem::take(&mut self.retrying) {
+            let payload = WebhookPayload {
+                error: Some(reason.to_string()),
+                ..self.copy_payload(WebhookEvent::CopyFailed, hash, name, dest)
+            };
+            self.queue(config, payload);
+        }
+        self.copy_started_at = None;
+    }
+}
+
+/// Post queued events until the sender is dropped.
+pub async fn webhook_task(mut jobs: mpsc::UnboundedReceiver<WebhookJob>, client: impl HttpClient) {
+    while let Some(job) = jobs.recv().await {
+        let name = job.payload.name.as_str();
+        match deliver(&client, &job.config, &job.payload).await {
+            Ok(_) => log::debug!(torrent = name; "Posted {:?} to the webhook", job.payload.event),
+            Err(e) => log::warn!(
+                torrent = name;
+                "Could not post {:?} to the webhook: {e}",
+                job.payload.event
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// Answers with queued statuses, recording each request.
+    struct MockClient {
+        answers: RefCell<VecDeque<Result<u16, String>>>,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+
+    impl MockClient {
+        fn answering(answers: impl IntoIterator<Item = Result<u16, String>>) -> Self {
+            Self {
+                answers: RefCell::new(answers.into_iter().collect()),
+                requests: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl HttpClient for MockClient {
+        async fn send(&self, request: HttpRequest) -> Result<u16, HttpError> {
+            let url = request.url.to_string();
+            self.requests.borrow_mut().push(request);
+            let answer = self.answers.borrow_mut().pop_front().expect("an answer");
+            answer.map_err(|message| HttpError::Transport { url, message })
+        }
+    }
+
+    fn webhook(secret: Option<&str>) -> WebhookConfig {
+        WebhookConfig {
+            url: "http://homeassistant.local:8123/api/webhook/privateer".into(),
+            events: WebhookEvents::default(),
+            secret: secret.map(str::to_string),
+        }
+    }
+
+    fn payload() -> WebhookPayload {
+        WebhookPayload {
+            event: WebhookEvent::CopyFailed,
+            name: "Some.Movie.2024".into(),
+            hash: "abc123".into(),
+            destination: Some(Destination::Movies),
+            timestamp: 1_700_000_100,
+            copy_started_at: Some(1_700_000_000),
+            error: Some("disk full".into()),
+        }
+    }
+
+    #[test]
+    fn signature_is_hex_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_signed_json() {
+        let client = MockClient::answering([Ok(200)]);
+
+        deliver(&client, &webhook(Some("s3cret")), &payload())
+            .await
+            .unwrap();
+
+        let requests = client.requests.borrow();
+        let body = requests[0].body.as_deref().unwrap();
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["event"], "copy_failed");
+        assert_eq!(json["destination"], "Movies");
+        assert_eq!(json["error"], "disk full");
+        assert!(requests[0]
+            .headers
+            .contains(&(SIGNATURE_HEADER.to_string(), signature("s3cret", body))));
+    }
+
+    #[tokio::test]
+    async fn unsigned_without_a_secret() {
+        let client = MockClient::answering([Ok(204)]);
+
+        deliver(&client, &webhook(None), &payload()).await.unwrap();
+
+        let requests = client.requests.borrow();
+        assert!(requests[0]
+            .headers
+            .iter()
+            .all(|(name, _)| name != SIGNATURE_HEADER));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_transient_failures() {
+        let client = MockClient::answering([Err("connection refused".into()), Ok(502), Ok(200)]);
+
+        let status = deliver(&client, &webhook(None), &payload()).await;
+
+        assert_eq!(status.unwrap(), 200);
+        assert_eq!(client.requests.borrow().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_the_last_attempt() {
+        let client = MockClient::answering([Ok(500), Ok(500), Ok(503)]);
+
+        let result = deliver(&client, &webhook(None), &payload()).await;
+
+        assert!(matches!(
+            result,
+            Err(WebhookError::WebhookStatus { status: 503 })
+        ));
+        assert_eq!(client.requests.borrow().len(), ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let client = MockClient::answering([Ok(404)]);
+
+        let result = deliver(&client, &webhook(None), &payload()).await;
+
+        assert!(matches!(
+            result,
+            Err(WebhookError::WebhookStatus { status: 404 })
+        ));
+        assert_eq!(client.requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn sender_queues_only_wanted_events_once_per_failure() {
+        let (mut sender, mut jobs) = WebhookSender::new();
+        let config = TransmissionConfig {
+            webhook: Some(WebhookConfig {
+                events: WebhookEvents {
+                    copy_started: false,
+                    ..Default::default()
+                },
+                ..webhook(None)
+            }),
+            ..Default::default()
+        };
+        let dest = Destination::Shows;
+
+        sender.observe_download(&config, "abc", "Show", 0.5);
+        sender.observe_download(&config, "abc", "Show", 1.0);
+        sender.copy_started(&config, "abc", "Show", dest, CopyState::NotCopied);
+        sender.copy_failed(&config, "abc", "Show", dest, "disk full");
+        // The retry on the next cycle
+        sender.copy_started(&config, "abc", "Show", dest, CopyState::Failed);
+        sender.copy_failed(&config, "abc", "Show", dest, "disk full");
+        sender.copy_started(&config, "abc", "Show", dest, CopyState::Failed);
+        sender.copy_failed(&config, "abc", "Show", dest, "disk full");
+        // Another torrent that can't be copied at all
+        sender.copy_failed(&config, "def", "..", dest, "bad name");
+        sender.copy_started(&config, "abc", "Show", dest, CopyState::Failed);
+        let path = Path::new("/tv/Show");
+        sender.copy_complete(&config, "abc", "Show", dest, path, &CopyStats::default());
+
+        let mut events = vec![];
+        while let Ok(job) = jobs.try_recv() {
+            events.push((job.payload.event, job.payload.copy_started_at.is_some()));
+        }
+        assert_eq!(
+            events,
+            [
+                (WebhookEvent::DownloadComplete, false),
+                (WebhookEvent::CopyFailed, true),
+                (WebhookEvent::CopyFailed, false),
+                (WebhookEvent::CopyCompleted, true),
+            ]
+        );
+    }
+}
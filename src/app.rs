@@ -1,10 +1,17 @@
 use std::borrow::Cow;
 use std::ops::Deref;
 
-use detail::{TorrentDetail, TorrentDetailPhase};
-use downloads::DownloadsView;
+use connection::ConnectionIndicator;
+use detail::{add_torrent, SpaceCheck, StoredDetail, TorrentDetail, TorrentDetailPhase};
+use downloads::{DownloadsBadge, DownloadsNav, DownloadsView, UnassignedBanner};
+use health::HealthIndicator;
+use magnet::MagnetPrompt;
+use pending::PendingActions;
+use profiles::ProfileSwitcher;
+use replace::Replacing;
+use setup::SetupWizard;
+use toast::Toasts;
 use futures_lite::FutureExt;
-use human_repr::HumanCount;
 use iti::components::alert::Alert;
 use iti::components::button::Button;
 use iti::components::icon::{Icon, IconGlyph, IconSize};
@@ -17,9 +24,21 @@ use privateer_wire_types::*;
 use settings::SettingsView;
 use wasm_bindgen::prelude::*;
 
+mod connection;
 mod detail;
 mod downloads;
+mod format;
+mod health;
+pub mod layout;
+mod magnet;
+mod pending;
+mod profiles;
+mod replace;
 mod settings;
+mod setup;
+mod storage;
+pub mod theme;
+mod toast;
 pub mod watching;
 
 pub mod invoke {
@@ -62,6 +81,53 @@ pub mod invoke {
     }
 }
 
+pub mod events {
+    use super::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], catch)]
+        async fn listen(event: &str, handler: &JsValue) -> Result<JsValue, JsValue>;
+    }
+
+    /// The object Tauri passes to event handlers.
+    #[derive(serde::Deserialize)]
+    struct Event<T> {
+        payload: T,
+    }
+
+    /// Subscribe to a backend event for the lifetime of the app.
+    ///
+    /// The returned channel only holds the most recent payload, so a view
+    /// that isn't listening (e.g. on another tab) picks up the latest state
+    /// when it comes back instead of a backlog.
+    pub fn subscribe<T: serde::de::DeserializeOwned + 'static>(
+        name: &'static str,
+    ) -> async_channel::Receiver<T> {
+        let (tx, rx) = async_channel::bounded(1);
+        let handler = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            match serde_wasm_bindgen::from_value::<Event<T>>(event) {
+                Ok(event) => {
+                    let _ = tx.force_send(event.payload);
+                }
+                Err(e) => log::error!("could not deserialize '{name}' event: {e}"),
+            }
+        });
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = listen(name, handler.as_ref()).await {
+                log::error!("could not listen for '{name}': {e:?}");
+            }
+            // The handler must outlive the subscription, which is forever
+            handler.forget();
+        });
+        rx
+    }
+}
+
+/// Backend event carrying a search API request's place in the queue, as a
+/// [`SearchQueuePlace`].
+const SEARCH_QUEUE: &str = "search-queue";
+
 pub async fn search(query: &str) -> Result<Vec<Torrent>, AppError> {
     #[derive(serde::Serialize)]
     struct Query<'a> {
@@ -81,29 +147,49 @@ pub async fn info(id: &str) -> Result<TorrentInfo, AppError> {
 }
 
 pub async fn add_download(
-    info_hash: &str,
+    info_hash: &InfoHash,
     name: &str,
     destination: Destination,
 ) -> Result<(), AppError> {
-    #[derive(serde::Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct AddDownloadArgs<'a> {
-        info_hash: &'a str,
-        name: &'a str,
-        destination: Destination,
-    }
-
     invoke::cmd(
         "add_download",
         &AddDownloadArgs {
-            info_hash,
-            name,
+            info_hash: info_hash.clone(),
+            name: name.to_string(),
             destination,
+            extra_destinations: vec![],
+            extra_dirs: vec![],
         },
     )
     .await
 }
 
+/// Add a magnet link to Transmission and record it in the ledger.
+pub async fn add_magnet(magnet: &MagnetLink, destination: Destination) -> Result<(), AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        magnet: &'a MagnetLink,
+        destination: Destination,
+    }
+    invoke::cmd("add_magnet", &Args { magnet, destination }).await
+}
+
+/// Record several torrents at once, with what happened to each.
+pub async fn add_downloads(downloads: &[NewDownload]) -> Result<Vec<AddDownloadResult>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        downloads: &'a [NewDownload],
+    }
+    invoke::cmd("add_downloads", &Args { downloads }).await
+}
+
+/// Free space in the download client's download directory.
+pub async fn free_space() -> Result<u64, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_free_space", &Empty {}).await
+}
+
 pub async fn get_watchlist() -> Result<Vec<WatchlistEntry>, AppError> {
     #[derive(serde::Serialize)]
     struct Empty {}
@@ -122,18 +208,18 @@ pub async fn add_to_watchlist(
     invoke::cmd("add_to_watchlist", &Args { title, destination }).await
 }
 
-pub async fn remove_from_watchlist(id: u64) -> Result<(), AppError> {
-    #[derive(serde::Serialize)]
-    struct Args {
-        id: u64,
-    }
-    invoke::cmd("remove_from_watchlist", &Args { id }).await
+/// Unconfirmed removals are refused; see [`pending`].
+pub async fn remove_from_watchlist(id: u64, confirm: bool) -> Result<(), AppError> {
+    let args = RemoveWatchlistArgs { id, confirm };
+    invoke::cmd("remove_from_watchlist", &args).await
 }
 
-pub async fn get_downloads_ledger() -> Result<Vec<DownloadEntry>, AppError> {
+pub async fn get_downloads_ledger(query: &LedgerQuery) -> Result<LedgerPage, AppError> {
     #[derive(serde::Serialize)]
-    struct Empty {}
-    invoke::cmd("get_downloads_ledger", &Empty {}).await
+    struct Args<'a> {
+        query: &'a LedgerQuery,
+    }
+    invoke::cmd("get_downloads_ledger", &Args { query }).await
 }
 
 pub async fn check_movie_exists(title: &str) -> Result<bool, AppError> {
@@ -161,57 +247,178 @@ struct TorrentView<V: View> {
     #[child]
     wrapper: V::Element,
     on_click: V::EventListener,
+    select_input: V::Element,
+    /// Whether the window is narrow; hides the low-priority cells and offers
+    /// their values in the row detail instead.
+    narrow: Proxy<bool>,
+    /// Whether the row detail is open.
+    expanded: Proxy<bool>,
+    is_expanded: bool,
+    expand_text: V::Text,
     torrent: Torrent,
 }
 
-pub fn format_unix_timestamp_with_locale(seconds: i64) -> String {
-    // Convert seconds to milliseconds
-    let milliseconds = seconds as f64 * 1000.0;
-    // Create a new Date object
-    let date = web_sys::js_sys::Date::new(&milliseconds.into());
-    // Get the user's locale
-    let user_locale =
-        web_sys::js_sys::Reflect::get(&web_sys::js_sys::global(), &"navigator".into())
-            .and_then(|navigator| web_sys::js_sys::Reflect::get(&navigator, &"language".into()))
-            .unwrap_or_else(|_| JsValue::from_str("en-US"))
-            .as_string()
-            .unwrap_or_else(|| "en-US".to_string());
-    // Format the date using the user's locale
-    date.to_locale_string(&user_locale, &JsValue::undefined())
-        .into()
+/// A click on a search result row.
+enum RowClick {
+    /// The row was clicked to open the torrent's details.
+    Open,
+    /// The row's checkbox was clicked.
+    Select,
+    /// The caret that opens or closes the row detail was clicked.
+    Expand,
+}
+
+/// Seconds since the unix epoch by the browser's clock.
+pub fn unix_now() -> i64 {
+    (web_sys::js_sys::Date::now() / 1000.0) as i64
+}
+
+/// Whether a click event originated inside an element matching `selector`.
+fn is_inside<V: View>(ev: &V::Event, selector: &str) -> bool {
+    ev.dyn_ev(|ev: &web_sys::Event| {
+        ev.target()
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            .and_then(|el| el.closest(selector).ok().flatten())
+            .is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// A value from a search result, or "?" if the API sent something that
+/// isn't a number.
+fn or_unknown(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "?".to_string(), |value| value.to_string())
+}
+
+/// Filled and empty dots showing a search result's health score.
+fn health_dots(health: Option<u8>) -> (String, String) {
+    let filled = health.unwrap_or(0).min(HEALTH_DOTS);
+    (
+        "\u{25cf}".repeat(filled as usize),
+        "\u{25cb}".repeat((HEALTH_DOTS - filled) as usize),
+    )
+}
+
+/// Order search results by a value that may be unknown.  Unknown values
+/// sort last whichever way the column is sorted.
+fn cmp_known<T: Ord>(a: Option<T>, b: Option<T>, direction: Direction) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if direction == Direction::Descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
 }
 
 impl<V: View> TorrentView<V> {
-    fn new(torrent: Torrent) -> Self {
-        let added = if V::is_view::<Web>() {
-            format_unix_timestamp_with_locale(torrent.added_i64())
+    fn new(torrent: Torrent, is_narrow: bool) -> Self {
+        let (added, added_title) = if V::is_view::<Web>() {
+            format::age(torrent.added_i64())
         } else {
-            torrent.added.clone()
+            (torrent.added.clone(), String::new())
         };
+        let (filled, empty) = health_dots(torrent.health);
+        let leechers = or_unknown(torrent.leechers_i64());
+        let detail = format!(
+            "Leechers: {leechers} \u{00b7} Uploader: {}",
+            torrent.username
+        );
+        let mut narrow = Proxy::new(is_narrow);
+        let mut expanded = Proxy::new(false);
         rsx! {
             let wrapper = tr(
                 class = "search-result-item",
                 on:click = on_click,
                 style:cursor = "pointer",
             ) {
-                td(class = "torrent-name") { {&torrent.name} }
-                td() { {&added} }
-                td() { {&torrent.seeders} }
-                td() { {&torrent.leechers} }
-                td() { {format!("{}", torrent.size_bytes().human_count_bytes())} }
-                td(class = "torrent-username") { {&torrent.username} }
+                td(class = "torrent-select") {
+                    let select_input = input(class = "form-check-input", type = "checkbox"){}
+                }
+                td(class = "torrent-name") {
+                    button(
+                        class = "btn btn-link btn-sm p-0 me-1 row-expand",
+                        type = "button",
+                        title = "Show the leechers and uploader",
+                        style:display = narrow(n => if *n { "" } else { "none" }),
+                    ) {
+                        let expand_text = "\u{25b8}"
+                    }
+                    {&torrent.name}
+                    div(style:display = narrow(n => if *n { "" } else { "none" })) {
+                        div(
+                            class = "row-detail small text-body-secondary",
+                            style:display = expanded(e => if *e { "" } else { "none" }),
+                        ) {
+                            {&detail}
+                        }
+                    }
+                }
+                let added_cell = td() { {&added} }
+                td() { {or_unknown(torrent.seeders_i64())} }
+                td(style:display = narrow(n => if *n { "none" } else { "" })) { {&leechers} }
+                let health_cell = td(class = "torrent-health") {
+                    span(class = "text-success") { {&filled} }
+                    span(class = "text-body-tertiary") { {&empty} }
+                }
+                td() { {or_unknown(torrent.size_bytes().map(|size| format::size(size as u64)))} }
+                td(
+                    class = "torrent-username",
+                    style:display = narrow(n => if *n { "none" } else { "" }),
+                ) {
+                    {&torrent.username}
+                }
             }
         }
+        if V::is_view::<Web>() {
+            health_cell.set_property("title", &torrent.health_breakdown(unix_now()));
+            added_cell.set_property("title", &added_title);
+        }
         Self {
             wrapper,
             on_click,
+            select_input,
+            narrow,
+            expanded,
+            is_expanded: false,
+            expand_text,
             torrent,
         }
     }
 
-    async fn step(&self) -> &Torrent {
-        self.on_click.next().await;
-        &self.torrent
+    async fn step(&self) -> RowClick {
+        let ev = self.on_click.next().await;
+        if is_inside::<V>(&ev, ".torrent-select") {
+            RowClick::Select
+        } else if is_inside::<V>(&ev, ".row-expand") {
+            RowClick::Expand
+        } else {
+            RowClick::Open
+        }
+    }
+
+    fn set_narrow(&mut self, is_narrow: bool) {
+        self.narrow.set(is_narrow);
+    }
+
+    /// Open or close the row detail.
+    fn toggle_detail(&mut self) {
+        self.is_expanded = !self.is_expanded;
+        self.expanded.set(self.is_expanded);
+        self.expand_text.set_text(if self.is_expanded {
+            "\u{25be}"
+        } else {
+            "\u{25b8}"
+        });
+    }
+
+    fn is_selected(&self) -> bool {
+        self.select_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or(false)
+    }
+
+    fn set_selected(&self, selected: bool) {
+        self.select_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(selected));
     }
 }
 
@@ -221,17 +428,71 @@ enum SortColumn {
     Date,
     Seeders,
     Leechers,
+    Health,
     Size,
     Uploader,
 }
 
+/// Relative width of the checkbox column of the search results.
+const SELECT_WEIGHT: u32 = 4;
+
 impl SortColumn {
+    /// All columns, in table order.
+    const ALL: [SortColumn; 7] = [
+        SortColumn::Name,
+        SortColumn::Date,
+        SortColumn::Seeders,
+        SortColumn::Leechers,
+        SortColumn::Health,
+        SortColumn::Size,
+        SortColumn::Uploader,
+    ];
+
+    /// Relative width, used to compute the colgroup percentages from the
+    /// visible columns.
+    fn weight(&self) -> u32 {
+        match self {
+            SortColumn::Name => 36,
+            SortColumn::Date => 14,
+            SortColumn::Seeders => 8,
+            SortColumn::Leechers => 8,
+            SortColumn::Health => 10,
+            SortColumn::Size => 9,
+            SortColumn::Uploader => 11,
+        }
+    }
+
+    /// Whether a narrow window hides the column, leaving its value to the
+    /// row detail.
+    fn is_low_priority(&self) -> bool {
+        matches!(self, SortColumn::Leechers | SortColumn::Uploader)
+    }
+
+    /// Widths of the visible columns, checkboxes first, as percentages of
+    /// the table.
+    fn widths(is_narrow: bool) -> Vec<f32> {
+        let weights: Vec<u32> = std::iter::once(SELECT_WEIGHT)
+            .chain(
+                SortColumn::ALL
+                    .into_iter()
+                    .filter(|c| !(is_narrow && c.is_low_priority()))
+                    .map(|c| c.weight()),
+            )
+            .collect();
+        let total: u32 = weights.iter().sum();
+        weights
+            .into_iter()
+            .map(|w| w as f32 * 100.0 / total as f32)
+            .collect()
+    }
+
     fn header_view<V: View>(&self, current_sorting: &Sort) -> V::Element {
         let name = match self {
             SortColumn::Name => "Name",
             SortColumn::Date => "Date Added",
             SortColumn::Seeders => "Seeders",
             SortColumn::Leechers => "Leechers",
+            SortColumn::Health => "Health",
             SortColumn::Size => "Size",
             SortColumn::Uploader => "Uploader",
         };
@@ -271,12 +532,32 @@ struct SearchResults<V: View> {
     #[child]
     wrapper: V::Element,
     table: V::Element,
+    colgroup: V::Element,
+    cols: Vec<V::Element>,
+    /// Whether the window is narrow; hides the low-priority columns.
+    narrow: Proxy<bool>,
+    is_narrow: bool,
+    /// When the window becomes narrow or wide again.
+    narrow_changes: async_channel::Receiver<bool>,
     torrents: Vec<TorrentView<V>>,
     sort: Proxy<Sort>,
+    /// How many results are checked; the batch bar shows while any are.
+    selected: Proxy<usize>,
+    add_selected_text: V::Text,
+    on_click_add_selected: V::EventListener,
+    on_click_add_movies: V::EventListener,
+    on_click_add_shows: V::EventListener,
+    /// Whether the batch destination menu is open.
+    menu_open: Proxy<bool>,
+    is_menu_open: bool,
+    /// What happened to each torrent of the last batch.
+    batch_list: V::Element,
+    batch_items: Vec<V::Element>,
     on_click_name: V::EventListener,
     on_click_date: V::EventListener,
     on_click_seeders: V::EventListener,
     on_click_leechers: V::EventListener,
+    on_click_health: V::EventListener,
     on_click_size: V::EventListener,
     on_click_uploader: V::EventListener,
 }
@@ -285,27 +566,68 @@ impl<V: View> Default for SearchResults<V> {
     fn default() -> Self {
         use SortColumn::*;
         let mut sort = Proxy::<Sort>::default();
+        let mut selected = Proxy::new(0usize);
+        let mut menu_open = Proxy::new(false);
+        let add_selected_text = V::Text::new("");
+        let is_narrow = layout::is_narrow::<V>();
+        let mut narrow = Proxy::new(is_narrow);
         rsx! {
             let wrapper = div(class = "search-results mt-3", style:display = "none") {
                 h5(class = "mb-2") { "Results" }
+                div(
+                    class = "btn-group mb-2",
+                    style:display = selected(n => if *n > 0 { "" } else { "none" }),
+                ) {
+                    button(
+                        class = "btn btn-sm btn-outline-primary dropdown-toggle",
+                        type = "button",
+                        on:click = on_click_add_selected,
+                    ) {
+                        {&add_selected_text}
+                    }
+                    ul(
+                        class = menu_open(is_open => if *is_open {
+                            "dropdown-menu show"
+                        } else {
+                            "dropdown-menu"
+                        }),
+                    ) {
+                        li() {
+                            a(
+                                class = "dropdown-item",
+                                href = "#",
+                                on:click = on_click_add_movies,
+                            ) { "Movies" }
+                        }
+                        li() {
+                            a(
+                                class = "dropdown-item",
+                                href = "#",
+                                on:click = on_click_add_shows,
+                            ) { "Shows" }
+                        }
+                    }
+                }
+                let batch_list = ul(class = "list-unstyled small mb-2") {}
                 div(class = "table-responsive") {
                     let table = table(class = "table table-striped table-hover") {
-                        colgroup() {
-                            col(style:width = "35%"){}
-                            col(style:width = "20%"){}
-                            col(style:width = "9%"){}
-                            col(style:width = "9%"){}
-                            col(style:width = "9%"){}
-                            col(style:width = "9%"){}
-                        }
+                        let colgroup = colgroup() {}
                         thead() {
                             tr() {
+                                th() {}
                                 th(on:click = on_click_name) {{sort(s => Name.header_view::<V>(s))}}
                                 th(on:click = on_click_date) {{sort(s => Date.header_view::<V>(s))}}
                                 th(on:click = on_click_seeders) {{sort(s => Seeders.header_view::<V>(s))}}
-                                th(on:click = on_click_leechers) {{sort(s => Leechers.header_view::<V>(s))}}
+                                th(
+                                    style:display = narrow(n => if *n { "none" } else { "" }),
+                                    on:click = on_click_leechers,
+                                ) {{sort(s => Leechers.header_view::<V>(s))}}
+                                th(on:click = on_click_health) {{sort(s => Health.header_view::<V>(s))}}
                                 th(on:click = on_click_size) {{sort(s => Size.header_view::<V>(s))}}
-                                th(on:click = on_click_uploader) {{sort(s => Uploader.header_view::<V>(s))}}
+                                th(
+                                    style:display = narrow(n => if *n { "none" } else { "" }),
+                                    on:click = on_click_uploader,
+                                ) {{sort(s => Uploader.header_view::<V>(s))}}
                             }
                         }
                     }
@@ -313,18 +635,35 @@ impl<V: View> Default for SearchResults<V> {
             }
         }
 
-        Self {
+        let mut results = Self {
             wrapper,
             table,
+            colgroup,
+            cols: vec![],
+            narrow,
+            is_narrow,
+            narrow_changes: layout::narrow_changes::<V>(),
             torrents: vec![],
             on_click_name,
             on_click_date,
             on_click_seeders,
             on_click_leechers,
+            on_click_health,
             on_click_size,
             on_click_uploader,
             sort,
-        }
+            selected,
+            add_selected_text,
+            on_click_add_selected,
+            on_click_add_movies,
+            on_click_add_shows,
+            menu_open,
+            is_menu_open: false,
+            batch_list,
+            batch_items: vec![],
+        };
+        results.rebuild_colgroup();
+        results
     }
 }
 
@@ -334,6 +673,16 @@ enum SearchResultsStep {
         direction: Direction,
     },
     TorrentSelected(Box<Torrent>),
+    /// A row's checkbox was clicked.
+    SelectionChanged,
+    /// The batch destination menu was opened or closed.
+    ToggleMenu,
+    /// The checked results should be added to the destination.
+    AddSelected(Destination),
+    /// The detail of the row at this index was opened or closed.
+    ToggleDetail(usize),
+    /// The window became narrow (`true`) or wide again (`false`).
+    Narrow(bool),
 }
 
 impl<V: View> SearchResults<V> {
@@ -347,6 +696,7 @@ impl<V: View> SearchResults<V> {
                 .next()
                 .map(|_| Leechers)
                 .boxed_local(),
+            self.on_click_health.next().map(|_| Health).boxed_local(),
             self.on_click_size.next().map(|_| Size).boxed_local(),
             self.on_click_uploader
                 .next()
@@ -368,32 +718,200 @@ impl<V: View> SearchResults<V> {
     }
 
     async fn select_event(&self) -> SearchResultsStep {
-        let torrent = mogwai::future::race_all(self.torrents.iter().map(|view| view.step())).await;
-        SearchResultsStep::TorrentSelected(Box::new(torrent.clone()))
+        let (i, click) = mogwai::future::race_all(
+            self.torrents
+                .iter()
+                .enumerate()
+                .map(|(i, view)| view.step().map(move |click| (i, click))),
+        )
+        .await;
+        match click {
+            RowClick::Open => {
+                SearchResultsStep::TorrentSelected(Box::new(self.torrents[i].torrent.clone()))
+            }
+            RowClick::Select => SearchResultsStep::SelectionChanged,
+            RowClick::Expand => SearchResultsStep::ToggleDetail(i),
+        }
+    }
+
+    async fn narrow_event(&self) -> SearchResultsStep {
+        match self.narrow_changes.recv().await {
+            Ok(is_narrow) => SearchResultsStep::Narrow(is_narrow),
+            // Outside the browser the window never resizes
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// Recreate the `<col>` elements to fit the visible columns.
+    fn rebuild_colgroup(&mut self) {
+        for col in self.cols.drain(..) {
+            self.colgroup.remove_child(&col);
+        }
+        for width in SortColumn::widths(self.is_narrow) {
+            rsx! {
+                let col = col(style:width = format!("{width:.1}%")){}
+            }
+            self.colgroup.append_child(&col);
+            self.cols.push(col);
+        }
+    }
+
+    /// Hide or show the low-priority columns.
+    fn set_narrow(&mut self, is_narrow: bool) {
+        if is_narrow == self.is_narrow {
+            return;
+        }
+        self.is_narrow = is_narrow;
+        self.narrow.set(is_narrow);
+        for view in self.torrents.iter_mut() {
+            view.set_narrow(is_narrow);
+        }
+        self.rebuild_colgroup();
+    }
+
+    async fn batch_event(&self) -> SearchResultsStep {
+        self.on_click_add_selected
+            .next()
+            .map(|_| SearchResultsStep::ToggleMenu)
+            .or(self
+                .on_click_add_movies
+                .next()
+                .map(|_| SearchResultsStep::AddSelected(Destination::Movies)))
+            .or(self
+                .on_click_add_shows
+                .next()
+                .map(|_| SearchResultsStep::AddSelected(Destination::Shows)))
+            .await
+    }
+
+    fn set_menu_open(&mut self, open: bool) {
+        self.is_menu_open = open;
+        self.menu_open.set(open);
+    }
+
+    /// Count the checked results, updating the batch button.
+    fn update_selected(&mut self) {
+        let count = self
+            .torrents
+            .iter()
+            .filter(|view| view.is_selected())
+            .count();
+        self.add_selected_text
+            .set_text(format!("Add {count} selected\u{2026}"));
+        self.selected.set(count);
+        if count == 0 {
+            self.set_menu_open(false);
+        }
+    }
+
+    fn clear_batch_list(&mut self) {
+        for item in self.batch_items.drain(..) {
+            self.batch_list.remove_child(&item);
+        }
+    }
+
+    /// Fetch the details of each checked result and add it to
+    /// `destination`, listing what happened to each.  A failure doesn't
+    /// stop the rest from being added.
+    async fn add_selected(&mut self, destination: Destination) {
+        self.set_menu_open(false);
+        self.clear_batch_list();
+        let torrents = self
+            .torrents
+            .iter()
+            .filter(|view| view.is_selected())
+            .map(|view| view.torrent.clone())
+            .collect::<Vec<_>>();
+        let total = torrents.len();
+        log::info!("adding {total} search results to {destination}...");
+        // Counted down as torrents are added, since the client only
+        // allocates their space as they download
+        let mut free = free_space().await;
+        for (i, torrent) in torrents.iter().enumerate() {
+            self.add_selected_text
+                .set_text(format!("Adding {} of {total}\u{2026}", i + 1));
+            let result = match info(&torrent.id).await {
+                Ok(info) => match SpaceCheck::new(info.size, &free) {
+                    // Adding it anyway is left to its details
+                    SpaceCheck::Short(short) => Ok(Some(short)),
+                    check => add_torrent(&info, destination).await.map(|()| {
+                        if let Ok(free) = &mut free {
+                            *free = free.saturating_sub(info.size);
+                        }
+                        if let SpaceCheck::Unknown(notice) = check {
+                            log::warn!("{notice}");
+                        }
+                        None
+                    }),
+                },
+                Err(e) => Err(e),
+            };
+            let (class, text) = match result {
+                Ok(None) => ("text-success", format!("\u{2713} {}", torrent.name)),
+                Ok(Some(short)) => (
+                    "text-warning",
+                    format!(
+                        "\u{2717} {}: {short}, add it from its details",
+                        torrent.name
+                    ),
+                ),
+                Err(e) => {
+                    log::error!("adding '{}' failed: {e}", torrent.name);
+                    ("text-danger", format!("\u{2717} {}: {e}", torrent.name))
+                }
+            };
+            rsx! {
+                let item = li(class = class) { {&text} }
+            }
+            self.batch_list.append_child(&item);
+            self.batch_items.push(item);
+        }
+        log::info!("...done.");
+        for view in self.torrents.iter() {
+            view.set_selected(false);
+        }
+        self.update_selected();
     }
 
     /// Resolves to the first selected torrent.
     async fn step(&mut self) -> Torrent {
         loop {
-            match self.sort_event().or(self.select_event()).await {
+            let event = self
+                .sort_event()
+                .or(self.select_event())
+                .or(self.batch_event())
+                .or(self.narrow_event())
+                .await;
+            match event {
                 SearchResultsStep::Sort { column, direction } => {
                     let current_sort = self.sort.deref();
                     if Some(column) != current_sort.column || direction != current_sort.direction {
                         self.torrents.sort_by(|a, b| {
                             let a = &a.torrent;
                             let b = &b.torrent;
-                            let ord = match column {
-                                SortColumn::Name => a.name.cmp(&b.name),
-                                SortColumn::Date => a.added_i64().cmp(&b.added_i64()),
-                                SortColumn::Seeders => a.seeders_i64().cmp(&b.seeders_i64()),
-                                SortColumn::Leechers => a.leechers_i64().cmp(&b.leechers_i64()),
-                                SortColumn::Size => a.size_bytes().cmp(&b.size_bytes()),
-                                SortColumn::Uploader => a.username.cmp(&b.username),
+                            let directed = |ord: std::cmp::Ordering| {
+                                if direction == Direction::Descending {
+                                    ord.reverse()
+                                } else {
+                                    ord
+                                }
                             };
-                            if direction == Direction::Descending {
-                                ord.reverse()
-                            } else {
-                                ord
+                            match column {
+                                SortColumn::Name => directed(a.name.cmp(&b.name)),
+                                SortColumn::Date => {
+                                    cmp_known(a.added_i64(), b.added_i64(), direction)
+                                }
+                                SortColumn::Seeders => {
+                                    cmp_known(a.seeders_i64(), b.seeders_i64(), direction)
+                                }
+                                SortColumn::Leechers => {
+                                    cmp_known(a.leechers_i64(), b.leechers_i64(), direction)
+                                }
+                                SortColumn::Health => cmp_known(a.health, b.health, direction),
+                                SortColumn::Size => {
+                                    cmp_known(a.size_bytes(), b.size_bytes(), direction)
+                                }
+                                SortColumn::Uploader => directed(a.username.cmp(&b.username)),
                             }
                         });
                     }
@@ -408,6 +926,16 @@ impl<V: View> SearchResults<V> {
                     }
                 }
                 SearchResultsStep::TorrentSelected(t) => return *t,
+                SearchResultsStep::SelectionChanged => self.update_selected(),
+                SearchResultsStep::ToggleMenu => {
+                    let open = !self.is_menu_open;
+                    self.set_menu_open(open);
+                }
+                SearchResultsStep::AddSelected(destination) => {
+                    self.add_selected(destination).await;
+                }
+                SearchResultsStep::ToggleDetail(i) => self.torrents[i].toggle_detail(),
+                SearchResultsStep::Narrow(is_narrow) => self.set_narrow(is_narrow),
             }
         }
     }
@@ -419,12 +947,14 @@ impl<V: View> SearchResults<V> {
         let views = torrents
             .into_iter()
             .map(|t| {
-                let view = TorrentView::new(t);
+                let view = TorrentView::new(t, self.is_narrow);
                 self.table.append_child(&view);
                 view
             })
             .collect();
         self.torrents = views;
+        self.clear_batch_list();
+        self.update_selected();
     }
 }
 
@@ -437,6 +967,10 @@ pub struct SearchView<V: View> {
     search_button: Button<V>,
     status_alert: Alert<V>,
     search_results: SearchResults<V>,
+    queue: async_channel::Receiver<SearchQueuePlace>,
+    /// The download a replacement is being searched for, until the user
+    /// runs a search of their own.
+    replacing: Option<Replacing>,
 }
 
 impl<V: View> Default for SearchView<V> {
@@ -446,6 +980,11 @@ impl<V: View> Default for SearchView<V> {
         search_button
             .get_icon_mut()
             .set_glyph(IconGlyph::MagnifyingGlass);
+        let queue = if V::is_view::<Web>() {
+            events::subscribe(SEARCH_QUEUE)
+        } else {
+            async_channel::bounded(1).1
+        };
         rsx! {
             let wrapper = div(class = "container-fluid") {
                 div(class = "mb-3") {
@@ -470,6 +1009,8 @@ impl<V: View> Default for SearchView<V> {
             search_button,
             status_alert,
             search_results,
+            queue,
+            replacing: None,
         }
     }
 }
@@ -495,6 +1036,7 @@ impl<V: View> SearchView<V> {
                 Step::Results(t) => return *t,
                 Step::Submit(ev) => {
                     ev.dyn_ev(|ev: &web_sys::Event| ev.prevent_default());
+                    self.replacing = None;
                     let search_query = self
                         .input
                         .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
@@ -505,14 +1047,8 @@ impl<V: View> SearchView<V> {
                     self.search_button.start_spinner();
                     self.search_button.disable();
 
-                    match search(&search_query).await {
-                        Ok(torrents) => {
-                            self.status_alert
-                                .set_text(format!("Found {} results.", torrents.len()));
-                            self.status_alert.set_flavor(Flavor::Success);
-                            self.search_results.set_search_results(torrents);
-                            self.search_results.wrapper.set_style("display", "block");
-                        }
+                    match self.queued_search(&search_query).await {
+                        Ok(torrents) => self.show_found(torrents),
                         Err(e) => {
                             self.status_alert.set_text(e.to_string());
                             self.status_alert.set_flavor(Flavor::Danger);
@@ -525,6 +1061,75 @@ impl<V: View> SearchView<V> {
         }
     }
 
+    /// Show the results of a search, only those at least as good as the
+    /// download being replaced while there is one.
+    fn show_found(&mut self, torrents: Vec<Torrent>) {
+        let found = torrents.len();
+        let (torrents, text) = match &self.replacing {
+            Some(replacing) => {
+                let torrents = torrents
+                    .into_iter()
+                    .filter(|t| replacing.accepts(&t.name))
+                    .collect::<Vec<_>>();
+                let text = match replacing.min_resolution() {
+                    Some(min) => format!(
+                        "Found {found} results, {} at {min}p or better to replace '{}'.",
+                        torrents.len(),
+                        replacing.name
+                    ),
+                    None => format!("Found {found} results to replace '{}'.", replacing.name),
+                };
+                (torrents, text)
+            }
+            None => (torrents, format!("Found {found} results.")),
+        };
+        self.status_alert.set_text(text);
+        self.status_alert.set_flavor(Flavor::Success);
+        self.search_results.set_search_results(torrents);
+        self.search_results.wrapper.set_style("display", "block");
+    }
+
+    /// Run a search, showing the request's place in the backend's queue
+    /// while it waits for a turn.
+    async fn queued_search(&mut self, query: &str) -> Result<Vec<Torrent>, AppError> {
+        enum Queued {
+            Done(Result<Vec<Torrent>, AppError>),
+            Waiting(usize),
+        }
+
+        // Ignore a place left over from an earlier request
+        let _ = self.queue.try_recv();
+        let mut request = std::pin::pin!(search(query));
+        loop {
+            let queue_update = async {
+                loop {
+                    match self.queue.recv().await {
+                        Ok(queued) if queued.request == query => {
+                            break Queued::Waiting(queued.place)
+                        }
+                        // Another request's place, e.g. the detail pane's
+                        Ok(_) => {}
+                        // Never closes on the web; elsewhere there are no events
+                        Err(_) => std::future::pending().await,
+                    }
+                }
+            };
+            let next = request.as_mut().map(Queued::Done).or(queue_update).await;
+            match next {
+                Queued::Done(result) => return result,
+                Queued::Waiting(0) => {
+                    self.status_alert
+                        .set_text(format!("Searching for '{query}'..."));
+                }
+                Queued::Waiting(place) => {
+                    self.status_alert.set_text(format!(
+                        "Waiting to search for '{query}' (number {place} in the queue)..."
+                    ));
+                }
+            }
+        }
+    }
+
     /// Programmatically run a search query.  Sets the input value, executes the
     /// search, and populates results — the same as if the user had typed the
     /// query and pressed Enter.
@@ -537,14 +1142,8 @@ impl<V: View> SearchView<V> {
         self.search_button.start_spinner();
         self.search_button.disable();
 
-        match search(query).await {
-            Ok(torrents) => {
-                self.status_alert
-                    .set_text(format!("Found {} results.", torrents.len()));
-                self.status_alert.set_flavor(Flavor::Success);
-                self.search_results.set_search_results(torrents);
-                self.search_results.wrapper.set_style("display", "block");
-            }
+        match self.queued_search(query).await {
+            Ok(torrents) => self.show_found(torrents),
             Err(e) => {
                 self.status_alert.set_text(e.to_string());
                 self.status_alert.set_flavor(Flavor::Danger);
@@ -620,27 +1219,6 @@ impl<V: View> Default for SearchTabContent<V> {
 }
 
 impl<V: View> SearchTabContent<V> {
-    fn store_state(info: Option<TorrentInfo>) {
-        if V::is_view::<Web>() {
-            let storage = mogwai::web::window()
-                .local_storage()
-                .unwrap_throw()
-                .unwrap_throw();
-            storage
-                .set_item("store-state", &serde_json::to_string(&info).unwrap_throw())
-                .unwrap_throw();
-        }
-    }
-
-    fn get_state() -> Option<TorrentInfo> {
-        let storage = mogwai::web::window()
-            .local_storage()
-            .unwrap_throw()
-            .unwrap_throw();
-        let s = storage.get_item("store-state").unwrap_throw()?;
-        serde_json::from_str(&s).unwrap_throw()
-    }
-
     fn search_view_mut(&mut self) -> &mut SearchView<V> {
         match self
             .panes
@@ -675,10 +1253,20 @@ impl<V: View> SearchTabContent<V> {
     /// tab will switch to its search pane, populate the input, run the query,
     /// and display results.
     pub fn set_pending_search(&mut self, query: String) {
+        self.search_view_mut().replacing = None;
         self.pending_search = Some(query);
         self.is_in_search = true;
     }
 
+    /// Queue a search for a replacement of a download with a lasting error,
+    /// showing only the results at least as good.  Adding one offers to
+    /// replace the download with it.
+    pub fn set_pending_replacement(&mut self, replacing: Replacing) {
+        self.pending_search = Some(replacing.query.clone());
+        self.search_view_mut().replacing = Some(replacing);
+        self.is_in_search = true;
+    }
+
     fn set_info(&mut self, state: Option<TorrentInfo>) {
         self.is_in_search = state.is_none();
         if let Some(info) = state {
@@ -693,37 +1281,47 @@ impl<V: View> SearchTabContent<V> {
 
     pub async fn step(&mut self) {
         if self.is_startup {
-            let state = Self::get_state();
+            // The restored listing may be long gone, so it is looked up again
+            let state = StoredDetail::load::<V>();
+            let is_restored = state.is_some();
             self.set_info(state);
+            if is_restored {
+                self.detail_view_mut().check_restored();
+            }
             self.is_startup = false;
         } else if let Some(query) = self.pending_search.take() {
             // A cross-tab search was requested (e.g. from the Watching tab).
             log::info!("running pending search: {query}");
-            Self::store_state(None);
+            StoredDetail::store::<V>(None);
             self.show_search();
             self.search_view_mut().run_search(&query).await;
             // Don't wait for result click — just show results and return.
             // The next step() will be a normal `is_in_search` step.
         } else if self.is_in_search {
             log::info!("in search");
-            Self::store_state(None);
+            StoredDetail::store::<V>(None);
             self.show_search();
             let torrent = self.search_view_mut().step().await;
             log::info!("getting info");
             let id = torrent.id.clone();
+            let replacing = self.search_view_mut().replacing.take();
+            self.detail_view_mut().set_replacing(replacing);
             self.detail_view_mut()
                 .set_phase(TorrentDetailPhase::Getting(torrent));
             self.show_detail();
             match info(&id).await {
                 Ok(info) => {
-                    self.set_info(Some(info.clone()));
-                    Self::store_state(Some(info));
+                    StoredDetail::store::<V>(Some(&info));
+                    self.set_info(Some(info));
                 }
                 Err(e) => self.detail_view_mut().set_phase(TorrentDetailPhase::Err(e)),
             }
         } else {
             log::info!("in detail");
             self.detail_view_mut().step().await;
+            // Back to the results, still replacing unless it was done
+            let replacing = self.detail_view_mut().take_replacing();
+            self.search_view_mut().replacing = replacing;
             self.is_in_search = true;
             log::info!("leaving detail");
         }
@@ -760,6 +1358,25 @@ pub struct App<V: View> {
     #[child]
     container: V::Element,
     tab_list: TabList<V, V::Element>,
+    downloads_badge: DownloadsBadge<V>,
+    connection: ConnectionIndicator<V>,
+    profiles: ProfileSwitcher<V>,
+    health: HealthIndicator<V>,
+    toasts: Toasts<V>,
+    /// Destructive commands counting down, over every tab.
+    pending: PendingActions<V>,
+    magnets: MagnetPrompt<V>,
+    /// Completed downloads without a destination, over every tab.
+    unassigned: UnassignedBanner<V>,
+    setup: SetupWizard<V>,
+    /// The navbar's indicators and tabs, hidden during setup.
+    nav_items: V::Element,
+    /// The tabs' content, hidden during setup.
+    tabs_content: V::Element,
+    /// Whether it has been checked that setup isn't needed, or it has run.
+    setup_checked: bool,
+    /// When the navbar indicators were last refreshed, in ms since the epoch.
+    nav_refreshed_at: f64,
     panes: Panes<V, TabContent<V>>,
     active_tab: usize,
     settings_loaded: bool,
@@ -773,9 +1390,15 @@ impl<V: View> Default for App<V> {
         rsx! {
             let search_label = span() { "Search" }
         }
-        rsx! {
-            let downloads_label = span() { "Downloads" }
-        }
+        let (downloads_label, downloads_badge) = DownloadsBadge::new();
+        let connection = ConnectionIndicator::<V>::new();
+        let profiles = ProfileSwitcher::<V>::new();
+        let health = HealthIndicator::<V>::new();
+        let toasts = Toasts::<V>::new();
+        let pending = PendingActions::<V>::new();
+        let magnets = MagnetPrompt::<V>::new();
+        let unassigned = UnassignedBanner::<V>::new();
+        let setup = SetupWizard::<V>::default();
         rsx! {
             let watching_label = span() { "Watching" }
         }
@@ -828,7 +1451,12 @@ impl<V: View> Default for App<V> {
                             ){}
                             "Privateer"
                         }
-                        {&tab_list}
+                        let nav_items = div(style:display = "contents") {
+                            {&connection.wrapper}
+                            {&profiles.wrapper}
+                            {&tab_list}
+                            {&health.wrapper}
+                        }
                     }
                 }
                 div(
@@ -837,14 +1465,34 @@ impl<V: View> Default for App<V> {
                     style:overflow_y = "auto",
                     style:padding_top = "1rem",
                 ) {
-                    {&panes}
+                    {&setup}
+                    let tabs_content = div() {
+                        {&unassigned.wrapper}
+                        {&panes}
+                    }
                 }
+                {&toasts.wrapper}
+                {&pending.wrapper}
+                {&magnets.wrapper}
             }
         }
 
         Self {
             container,
             tab_list,
+            downloads_badge,
+            connection,
+            profiles,
+            health,
+            toasts,
+            pending,
+            magnets,
+            unassigned,
+            setup,
+            nav_items,
+            tabs_content,
+            setup_checked: false,
+            nav_refreshed_at: 0.0,
             panes,
             active_tab: TAB_SEARCH,
             settings_loaded: false,
@@ -858,8 +1506,100 @@ enum AppStepResult {
     TabClicked(usize),
     /// The current tab's content finished a step (no tab change needed).
     ContentStep,
-    /// The Watching tab wants to navigate to the Search tab with a query.
+    /// Watching or Downloads wants to navigate to the Search tab with a
+    /// query.
     NavigateToSearch(String),
+    /// Downloads wants a replacement found for a download with a lasting
+    /// error.
+    NavigateToReplace(Replacing),
+    /// Another server profile was chosen in the navbar.
+    ProfileSwitched,
+    /// "Review" was clicked on the banner about downloads without a
+    /// destination.
+    ReviewUnassigned,
+}
+
+/// How often the navbar indicators are refreshed.
+const NAV_REFRESH_MILLIS: f64 = 5000.0;
+
+/// Navbar state borrowed while waiting for a tab click.
+struct Navbar<'a, V: View> {
+    tab_list: &'a mut TabList<V, V::Element>,
+    badge: &'a mut DownloadsBadge<V>,
+    connection: &'a mut ConnectionIndicator<V>,
+    profiles: &'a mut ProfileSwitcher<V>,
+    health: &'a mut HealthIndicator<V>,
+    toasts: &'a mut Toasts<V>,
+    pending: &'a mut PendingActions<V>,
+    magnets: &'a mut MagnetPrompt<V>,
+    unassigned: &'a mut UnassignedBanner<V>,
+    refreshed_at: &'a mut f64,
+}
+
+/// Wait for a tab click, a click on the connection dot, which opens
+/// Settings, or a switch of server profile.  In the meantime, refresh the
+/// Downloads tab badge, the connection dot, the profile switcher and the
+/// health indicator every few seconds so they stay
+/// current whichever tab is showing, open/close the health popover, show
+/// backend toasts, count down destructive commands, ask where opened
+/// magnet links should go and keep the banner about downloads without a
+/// destination current.
+///
+/// The refresh time is kept across calls because the current pane's step
+/// often finishes first, which restarts this wait.
+async fn next_tab_click<V: View>(nav: Navbar<'_, V>) -> AppStepResult {
+    enum NavEvent {
+        Tab(usize),
+        Connection,
+        Profile(bool),
+        ToggleHealth,
+        Toast,
+        Pending,
+        Magnet,
+        ReviewUnassigned,
+        Tick,
+    }
+
+    loop {
+        let now = web_sys::js_sys::Date::now();
+        if now - *nav.refreshed_at >= NAV_REFRESH_MILLIS {
+            nav.badge.refresh().await;
+            nav.connection.refresh().await;
+            nav.profiles.refresh().await;
+            nav.health.refresh().await;
+            *nav.refreshed_at = now;
+        }
+        let remaining = (NAV_REFRESH_MILLIS - (now - *nav.refreshed_at)).max(0.0) as _;
+
+        let event = nav
+            .tab_list
+            .step()
+            .map(|event| {
+                let TabListEvent::ItemClicked { index, .. } = event;
+                NavEvent::Tab(index)
+            })
+            .or(nav.connection.step().map(|_| NavEvent::Connection))
+            .or(nav.profiles.step().map(NavEvent::Profile))
+            .or(nav.health.step().map(|_| NavEvent::ToggleHealth))
+            .or(nav.toasts.step().map(|_| NavEvent::Toast))
+            .or(nav.pending.step().map(|_| NavEvent::Pending))
+            .or(nav.magnets.step().map(|_| NavEvent::Magnet))
+            .or(nav.unassigned.step().map(|_| NavEvent::ReviewUnassigned))
+            .or(async {
+                mogwai::time::wait_millis(remaining).await;
+                NavEvent::Tick
+            })
+            .await;
+        match event {
+            NavEvent::Tab(index) => return AppStepResult::TabClicked(index),
+            NavEvent::Connection => return AppStepResult::TabClicked(TAB_SETTINGS),
+            NavEvent::Profile(true) => return AppStepResult::ProfileSwitched,
+            NavEvent::ReviewUnassigned => return AppStepResult::ReviewUnassigned,
+            NavEvent::Profile(false) => {}
+            NavEvent::ToggleHealth => nav.health.toggle_menu(),
+            NavEvent::Toast | NavEvent::Pending | NavEvent::Magnet | NavEvent::Tick => {}
+        }
+    }
 }
 
 impl<V: View> App<V> {
@@ -869,7 +1609,56 @@ impl<V: View> App<V> {
         self.panes.select(index);
     }
 
+    /// Switch to the tab at `index`, unless that would leave Settings with
+    /// edits the user wants to keep.  Returns whether it switched.
+    async fn switch_tab(&mut self, index: usize) -> bool {
+        if self.active_tab == TAB_SETTINGS && index != TAB_SETTINGS {
+            let settings = match self
+                .panes
+                .get_pane_at_mut(TAB_SETTINGS)
+                .expect("settings tab")
+            {
+                TabContent::Settings(s) => s,
+                _ => panic!("expected settings tab"),
+            };
+            if settings.has_unsaved_changes() && !settings.confirm_leave().await {
+                // Stay, with the edits as they are
+                self.tab_list.select(TAB_SETTINGS);
+                return false;
+            }
+        }
+        self.select_tab(index);
+        true
+    }
+
+    /// Walk a new install through setup before showing the tabs.
+    async fn run_setup(&mut self) {
+        match setup::needs_setup().await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                log::error!("Could not tell whether setup is needed: {e}");
+                return;
+            }
+        }
+        self.nav_items.set_style("display", "none");
+        self.tabs_content.set_style("display", "none");
+        self.setup.run().await;
+        self.nav_items.set_style("display", "contents");
+        self.tabs_content.remove_style("display");
+        // Show how the new connection is doing straight away
+        self.nav_refreshed_at = 0.0;
+    }
+
     pub async fn step(&mut self) {
+        if !self.setup_checked {
+            self.setup_checked = true;
+            self.run_setup().await;
+            // Before any dates or sizes are shown
+            format::load().await;
+            self.unassigned.refresh().await;
+        }
+
         // We need to race "tab click" against "current pane step" without
         // taking conflicting &self / &mut self borrows.  The trick: split the
         // borrows so tab_list and panes are borrowed independently.
@@ -880,10 +1669,18 @@ impl<V: View> App<V> {
                     TabContent::Search(s) => s,
                     _ => panic!("expected search tab"),
                 };
-                let tab_click = async {
-                    let TabListEvent::ItemClicked { index, .. } = self.tab_list.step().await;
-                    AppStepResult::TabClicked(index)
-                };
+                let tab_click = next_tab_click(Navbar {
+                    tab_list: &mut self.tab_list,
+                    badge: &mut self.downloads_badge,
+                    connection: &mut self.connection,
+                    profiles: &mut self.profiles,
+                    health: &mut self.health,
+                    toasts: &mut self.toasts,
+                    pending: &mut self.pending,
+                    magnets: &mut self.magnets,
+                    unassigned: &mut self.unassigned,
+                    refreshed_at: &mut self.nav_refreshed_at,
+                });
                 let content_step = async {
                     search.step().await;
                     AppStepResult::ContentStep
@@ -899,13 +1696,27 @@ impl<V: View> App<V> {
                     TabContent::Downloads(d) => d,
                     _ => panic!("expected downloads tab"),
                 };
-                let tab_click = async {
-                    let TabListEvent::ItemClicked { index, .. } = self.tab_list.step().await;
-                    AppStepResult::TabClicked(index)
-                };
+                let tab_click = next_tab_click(Navbar {
+                    tab_list: &mut self.tab_list,
+                    badge: &mut self.downloads_badge,
+                    connection: &mut self.connection,
+                    profiles: &mut self.profiles,
+                    health: &mut self.health,
+                    toasts: &mut self.toasts,
+                    pending: &mut self.pending,
+                    magnets: &mut self.magnets,
+                    unassigned: &mut self.unassigned,
+                    refreshed_at: &mut self.nav_refreshed_at,
+                });
                 let content_step = async {
-                    downloads.step().await;
-                    AppStepResult::ContentStep
+                    match downloads.step().await {
+                        Some(DownloadsNav::Settings) => AppStepResult::TabClicked(TAB_SETTINGS),
+                        Some(DownloadsNav::Search(query)) => AppStepResult::NavigateToSearch(query),
+                        Some(DownloadsNav::Replace(replacing)) => {
+                            AppStepResult::NavigateToReplace(replacing)
+                        }
+                        None => AppStepResult::ContentStep,
+                    }
                 };
                 tab_click.or(content_step).await
             }
@@ -918,10 +1729,18 @@ impl<V: View> App<V> {
                     TabContent::Watching(w) => w,
                     _ => panic!("expected watching tab"),
                 };
-                let tab_click = async {
-                    let TabListEvent::ItemClicked { index, .. } = self.tab_list.step().await;
-                    AppStepResult::TabClicked(index)
-                };
+                let tab_click = next_tab_click(Navbar {
+                    tab_list: &mut self.tab_list,
+                    badge: &mut self.downloads_badge,
+                    connection: &mut self.connection,
+                    profiles: &mut self.profiles,
+                    health: &mut self.health,
+                    toasts: &mut self.toasts,
+                    pending: &mut self.pending,
+                    magnets: &mut self.magnets,
+                    unassigned: &mut self.unassigned,
+                    refreshed_at: &mut self.nav_refreshed_at,
+                });
                 let content_step = async {
                     match watching.step().await {
                         Some(query) => AppStepResult::NavigateToSearch(query),
@@ -943,10 +1762,18 @@ impl<V: View> App<V> {
                     settings.load().await;
                     self.settings_loaded = true;
                 }
-                let tab_click = async {
-                    let TabListEvent::ItemClicked { index, .. } = self.tab_list.step().await;
-                    AppStepResult::TabClicked(index)
-                };
+                let tab_click = next_tab_click(Navbar {
+                    tab_list: &mut self.tab_list,
+                    badge: &mut self.downloads_badge,
+                    connection: &mut self.connection,
+                    profiles: &mut self.profiles,
+                    health: &mut self.health,
+                    toasts: &mut self.toasts,
+                    pending: &mut self.pending,
+                    magnets: &mut self.magnets,
+                    unassigned: &mut self.unassigned,
+                    refreshed_at: &mut self.nav_refreshed_at,
+                });
                 let content_step = async {
                     settings.step().await;
                     AppStepResult::ContentStep
@@ -954,14 +1781,38 @@ impl<V: View> App<V> {
                 tab_click.or(content_step).await
             }
             _ => {
-                let TabListEvent::ItemClicked { index, .. } = self.tab_list.step().await;
-                AppStepResult::TabClicked(index)
+                next_tab_click(Navbar {
+                    tab_list: &mut self.tab_list,
+                    badge: &mut self.downloads_badge,
+                    connection: &mut self.connection,
+                    profiles: &mut self.profiles,
+                    health: &mut self.health,
+                    toasts: &mut self.toasts,
+                    pending: &mut self.pending,
+                    magnets: &mut self.magnets,
+                    unassigned: &mut self.unassigned,
+                    refreshed_at: &mut self.nav_refreshed_at,
+                })
+                .await
             }
         };
 
         match result {
             AppStepResult::TabClicked(index) => {
-                self.select_tab(index);
+                self.switch_tab(index).await;
+            }
+            AppStepResult::ReviewUnassigned => {
+                if self.switch_tab(TAB_DOWNLOADS).await {
+                    let downloads = match self
+                        .panes
+                        .get_pane_at_mut(TAB_DOWNLOADS)
+                        .expect("downloads tab")
+                    {
+                        TabContent::Downloads(d) => d,
+                        _ => panic!("expected downloads tab"),
+                    };
+                    downloads.show_unassigned();
+                }
             }
             AppStepResult::NavigateToSearch(query) => {
                 // Switch to the Search tab and queue the search query.
@@ -976,6 +1827,31 @@ impl<V: View> App<V> {
                 search_tab.set_pending_search(query);
                 self.select_tab(TAB_SEARCH);
             }
+            AppStepResult::NavigateToReplace(replacing) => {
+                let search_tab = match self.panes.get_pane_at_mut(TAB_SEARCH).expect("search tab") {
+                    TabContent::Search(s) => s,
+                    _ => panic!("expected search tab"),
+                };
+                search_tab.set_pending_replacement(replacing);
+                self.select_tab(TAB_SEARCH);
+            }
+            AppStepResult::ProfileSwitched => {
+                // Show how the new server is doing straight away
+                self.nav_refreshed_at = 0.0;
+                let settings = match self
+                    .panes
+                    .get_pane_at_mut(TAB_SETTINGS)
+                    .expect("settings tab")
+                {
+                    TabContent::Settings(s) => s,
+                    _ => panic!("expected settings tab"),
+                };
+                // Reload Settings to select the new profile, unless that
+                // would lose edits
+                if !settings.has_unsaved_changes() {
+                    self.settings_loaded = false;
+                }
+            }
             AppStepResult::ContentStep => {}
         }
     }
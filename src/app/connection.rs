@@ -0,0 +1,78 @@
+//! Navbar dot showing how the latest polls of Transmission went.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, ConnectionLevel, ConnectionStatus};
+
+use super::{format, invoke};
+
+async fn get_connection_status() -> Result<ConnectionStatus, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_connection_status", &Empty {}).await
+}
+
+/// A dot next to the brand: green while Transmission answers, amber after a
+/// failed poll and red once polls keep failing.  Clicking it opens Settings.
+pub struct ConnectionIndicator<V: View> {
+    pub wrapper: V::Element,
+    on_click: V::EventListener,
+    level: Proxy<ConnectionLevel>,
+    title: Proxy<String>,
+}
+
+impl<V: View> ConnectionIndicator<V> {
+    pub fn new() -> Self {
+        let mut level = Proxy::new(ConnectionLevel::Unknown);
+        let mut title = Proxy::new("Connecting to Transmission\u{2026}".to_string());
+        rsx! {
+            let wrapper = button(
+                class = "btn btn-sm btn-link text-decoration-none p-0",
+                type = "button",
+                title = title(t => t.clone()),
+                on:click = on_click,
+            ) {
+                span(
+                    class = level(l => match l {
+                        ConnectionLevel::Unknown => "text-secondary",
+                        ConnectionLevel::Connected => "text-success",
+                        ConnectionLevel::Flaky => "text-warning",
+                        ConnectionLevel::Down => "text-danger",
+                    }),
+                ) { "\u{25CF}" }
+            }
+        }
+        Self {
+            wrapper,
+            on_click,
+            level,
+            title,
+        }
+    }
+
+    /// Fetch the latest status from the backend and update the dot.
+    pub async fn refresh(&mut self) {
+        let status = match get_connection_status().await {
+            Ok(status) => status,
+            Err(e) => {
+                log::debug!("Failed to get connection status: {e}");
+                return;
+            }
+        };
+        self.level.set(status.level());
+        let Some(checked_at) = status.checked_at else {
+            return;
+        };
+        let checked_at = format::timestamp(checked_at);
+        self.title.set(match (&status.last_error, status.failures) {
+            (None, _) => format!("Connected to Transmission\nChecked {checked_at}"),
+            (Some(e), 1) => format!("Polling Transmission failed at {checked_at}:\n{e}"),
+            (Some(e), failures) => {
+                format!("{failures} polls in a row failed, the last at {checked_at}:\n{e}")
+            }
+        });
+    }
+
+    /// Wait for a click on the dot.
+    pub async fn step(&self) {
+        self.on_click.next().await;
+    }
+}
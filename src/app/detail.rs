@@ -1,30 +1,25 @@
 //! Torrent detail view.
+//!
+//! Details restored from before a restart are looked up again in the
+//! background.  Until the listing is confirmed to still exist it can't be
+//! added, so a dead torrent never makes it into the ledger.
 use std::ops::Deref;
 
 use futures_lite::FutureExt;
-use human_repr::HumanCount;
 use iti::components::alert::Alert;
 use iti::components::button::Button;
 use iti::components::icon::IconGlyph;
 use iti::components::Flavor;
 use mogwai::{future::MogwaiFutureExt, web::prelude::*};
-use privateer_wire_types::{AppError, Destination, Torrent, TorrentInfo};
-use wasm_bindgen::prelude::*;
+use privateer_wire_types::{
+    parse_magnet, AppError, Destination, ErrorKind, InfoHash, MagnetLink, Torrent, TorrentInfo,
+};
 
-mod open {
-    use super::*;
+use super::pending::{self, PendingAction};
+use super::replace::Replacing;
 
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "opener"])]
-        async fn openUrl(path: &str);
-    }
-
-    pub async fn path(path: &str) {
-        log::info!("opening path: {path}");
-        openUrl(path).await
-    }
-}
+mod stored;
+pub use stored::StoredDetail;
 
 #[derive(Clone, Default, Debug, PartialEq)]
 pub enum TorrentDetailPhase {
@@ -35,6 +30,23 @@ pub enum TorrentDetailPhase {
     Details(TorrentInfo),
 }
 
+/// Event from the detail view.
+enum DetailAction {
+    Back,
+    Add(MagnetAction),
+    /// Adding was confirmed despite too little free space.
+    AddAnyway,
+    CancelAdd,
+    /// The torrent just added should replace the broken download.
+    Replace,
+    /// The broken download should be kept alongside it.
+    KeepBoth,
+    /// The restored listing was looked up again.
+    Checked(Result<TorrentInfo, AppError>),
+    /// "Refresh" was clicked on the notice that the listing may be gone.
+    Recheck,
+}
+
 /// Event from the detail view magnet/add button area.
 enum MagnetAction {
     /// The primary "Add to <dest>" button was clicked.
@@ -46,6 +58,8 @@ enum MagnetAction {
 /// Holds the split button group UI for adding a torrent with a destination.
 struct AddButtonGroup<V: View> {
     wrapper: V::Element,
+    primary_button: V::Element,
+    toggle_button: V::Element,
     on_click_primary: V::EventListener,
     on_click_toggle: V::EventListener,
     on_click_movies: V::EventListener,
@@ -65,14 +79,14 @@ impl<V: View> AddButtonGroup<V> {
 
         rsx! {
             let wrapper = div(class = "btn-group mb-3") {
-                button(
+                let primary_button = button(
                     class = "btn btn-outline-primary",
                     type = "button",
                     on:click = on_click_primary,
                 ) {
                     {&label_text}
                 }
-                button(
+                let toggle_button = button(
                     class = "btn btn-outline-primary dropdown-toggle dropdown-toggle-split",
                     type = "button",
                     on:click = on_click_toggle,
@@ -106,6 +120,8 @@ impl<V: View> AddButtonGroup<V> {
 
         Self {
             wrapper,
+            primary_button,
+            toggle_button,
             on_click_primary,
             on_click_toggle,
             on_click_movies,
@@ -127,6 +143,13 @@ impl<V: View> AddButtonGroup<V> {
         self.menu_open.set(false);
     }
 
+    fn set_enabled(&mut self, enabled: bool) {
+        self.hide_menu();
+        for button in [&self.primary_button, &self.toggle_button] {
+            button.dyn_el(|button: &web_sys::HtmlButtonElement| button.set_disabled(!enabled));
+        }
+    }
+
     fn set_selected(&mut self, dest: Destination) {
         self.selected = dest;
         self.label_text.set_text(format!("Add to {}", dest.label()));
@@ -168,12 +191,91 @@ impl<V: View> AddButtonGroup<V> {
     }
 }
 
+/// How a torrent fits in the download client's free space.
+pub(super) enum SpaceCheck {
+    Fits,
+    /// It doesn't, so adding it waits for confirmation.
+    Short(String),
+    /// The torrent's size or the free space isn't known.  Adding goes
+    /// ahead, with this notice.
+    Unknown(String),
+}
+
+impl SpaceCheck {
+    /// Compare a torrent's `size` with the client's answer for its free
+    /// space.
+    pub(super) fn new(size: u64, free: &Result<u64, AppError>) -> Self {
+        match free {
+            _ if size == 0 => Self::Unknown(
+                "The torrent's size isn't known, so free space wasn't checked.".to_string(),
+            ),
+            Ok(free) if size > *free => Self::Short(format!(
+                "Not enough space: need {}, {} free",
+                super::format::size(size),
+                super::format::size(*free)
+            )),
+            Ok(_) => Self::Fits,
+            Err(e) => Self::Unknown(format!("Free space couldn't be checked: {e}")),
+        }
+    }
+}
+
+/// Add a torrent to the download client by its magnet link and record it
+/// in the backend ledger, or only record it if it has no usable link.
+pub(super) async fn add_torrent(
+    info: &TorrentInfo,
+    destination: Destination,
+) -> Result<(), AppError> {
+    match info.magnet.as_deref().and_then(parse_magnet) {
+        Some(magnet) => {
+            log::info!("Adding magnet for '{}'...", info.name);
+            let magnet = MagnetLink {
+                name: info.name.clone(),
+                ..magnet
+            };
+            super::add_magnet(&magnet, destination).await
+        }
+        None => {
+            log::info!("Recording download '{}'...", info.name);
+            let info_hash: InfoHash = info
+                .info_hash
+                .parse()
+                .map_err(|e: String| AppError::new(ErrorKind::PirateSearch, e))?;
+            super::add_download(&info_hash, &info.name, destination).await
+        }
+    }
+}
+
 #[derive(ViewChild)]
 pub struct TorrentDetail<V: View> {
     #[child]
     wrapper: V::Element,
     back_button: Button<V>,
     status_alert: Alert<V>,
+    /// Warning that the torrent won't fit, asking whether to add it anyway.
+    space_text: V::Text,
+    on_click_add_anyway: V::EventListener,
+    on_click_cancel_add: V::EventListener,
+    confirming: Proxy<bool>,
+    /// Where the torrent goes if adding it is confirmed.
+    pending_add: Option<Destination>,
+    /// Offer to replace the broken download with the torrent just added.
+    replace_text: V::Text,
+    on_click_replace: V::EventListener,
+    on_click_keep_both: V::EventListener,
+    offering: Proxy<bool>,
+    /// The download with a lasting error this torrent may replace.
+    replacing: Option<Replacing>,
+    /// The torrent added, while replacing with it is offered.
+    added: Option<InfoHash>,
+    /// Whether the listing, restored from before a restart, is being
+    /// looked up again.
+    checking: bool,
+    /// Notice that the restored listing may be gone, with a button to
+    /// look it up again.
+    stale_text: V::Text,
+    on_click_recheck: V::EventListener,
+    stale: Proxy<bool>,
     phase: Proxy<TorrentDetailPhase>,
     detail_form: Option<V::Element>,
     add_button_group: Option<AddButtonGroup<V>>,
@@ -186,6 +288,9 @@ impl<V: View> Default for TorrentDetail<V> {
         back_button.get_icon_mut().set_glyph(IconGlyph::ArrowLeft);
         let status_alert = Alert::new("", Flavor::Info);
         status_alert.set_is_visible(false);
+        let mut confirming = Proxy::new(false);
+        let mut offering = Proxy::new(false);
+        let mut stale = Proxy::new(false);
         rsx! {
             let wrapper = div() {
                 div(class = "mb-3") {
@@ -194,12 +299,70 @@ impl<V: View> Default for TorrentDetail<V> {
                 div(class = "mb-3") {
                     {&status_alert}
                 }
+                div(
+                    class = "alert alert-warning d-flex align-items-center gap-2 mb-3",
+                    style:display = confirming(c => if *c { "" } else { "none" }),
+                ) {
+                    span(class = "me-auto") { let space_text = "" }
+                    button(
+                        class = "btn btn-warning btn-sm",
+                        type = "button",
+                        on:click = on_click_add_anyway,
+                    ) { "Add anyway" }
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_cancel_add,
+                    ) { "Cancel" }
+                }
+                div(
+                    class = "alert alert-info d-flex align-items-center gap-2 mb-3",
+                    style:display = offering(o => if *o { "" } else { "none" }),
+                ) {
+                    span(class = "me-auto") { let replace_text = "" }
+                    button(
+                        class = "btn btn-primary btn-sm",
+                        type = "button",
+                        on:click = on_click_replace,
+                    ) { "Replace" }
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_keep_both,
+                    ) { "Keep both" }
+                }
+                div(
+                    class = "alert alert-warning d-flex align-items-center gap-2 mb-3",
+                    style:display = stale(s => if *s { "" } else { "none" }),
+                ) {
+                    span(class = "me-auto") { let stale_text = "" }
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_recheck,
+                    ) { "Refresh" }
+                }
             }
         }
         Self {
             wrapper,
             back_button,
             status_alert,
+            space_text,
+            on_click_add_anyway,
+            on_click_cancel_add,
+            confirming,
+            pending_add: None,
+            replace_text,
+            on_click_replace,
+            on_click_keep_both,
+            offering,
+            replacing: None,
+            added: None,
+            checking: false,
+            stale_text,
+            on_click_recheck,
+            stale,
             phase,
             detail_form: None,
             add_button_group: None,
@@ -216,6 +379,7 @@ impl<V: View> TorrentDetail<V> {
             .magnet
             .as_ref()
             .map(|_| AddButtonGroup::<V>::new(default_dest));
+        let (added, added_title) = super::format::age(Some(info.added).filter(|added| *added > 0));
 
         rsx! {
             let wrapper = div(style:text_align = "left") {
@@ -238,11 +402,11 @@ impl<V: View> TorrentDetail<V> {
                         tbody() {
                             tr() {
                                 td() { {&info.name} }
-                                td() { {super::format_unix_timestamp_with_locale(info.added)} }
+                                let added_cell = td() { {&added} }
                                 td() { {info.seeders.to_string()} }
                                 td() { {info.leechers.to_string()} }
                                 td() { {info.num_files.map(|i| i.to_string()).unwrap_or("unknown".to_string())} }
-                                td() { {info.size.human_count_bytes().to_string()} }
+                                td() { {super::format::size(info.size)} }
                                 td() { {info.download_count.clone().unwrap_or("?".into())} }
                                 td() { {&info.status} }
                                 td() { {&info.username} }
@@ -259,11 +423,16 @@ impl<V: View> TorrentDetail<V> {
                 }
             }
         }
+        added_cell.set_property("title", &added_title);
         (wrapper, add_group)
     }
 
     pub fn set_phase(&mut self, phase: TorrentDetailPhase) {
         self.add_button_group.take();
+        self.set_pending_add(None);
+        self.set_added(None);
+        self.checking = false;
+        self.stale.set(false);
         if let Some(detail) = self.detail_form.take() {
             self.wrapper.remove_child(&detail);
         }
@@ -293,64 +462,201 @@ impl<V: View> TorrentDetail<V> {
         self.phase.set(phase);
     }
 
-    /// Record the download in the backend ledger.
-    async fn record_download(
-        info_hash: &str,
-        name: &str,
-        destination: Destination,
-    ) -> Result<(), AppError> {
-        log::info!("Recording download '{name}'...");
-        super::add_download(info_hash, name, destination).await
+    /// Look the listing up again in the background, as it was restored
+    /// from before a restart and may be long gone.  It can't be added
+    /// until it is found.
+    pub fn check_restored(&mut self) {
+        let TorrentDetailPhase::Details(info) = self.phase.deref() else {
+            return;
+        };
+        self.status_alert
+            .set_text(format!("Checking '{}' is still listed...", info.name));
+        self.status_alert.set_flavor(Flavor::Info);
+        self.status_alert.set_is_visible(true);
+        self.stale.set(false);
+        if let Some(add_group) = self.add_button_group.as_mut() {
+            add_group.set_enabled(false);
+        }
+        self.checking = true;
+    }
+
+    /// Show the listing as looked up again: with its numbers updated if it
+    /// was found, otherwise with a notice and adding left disabled.
+    fn checked(&mut self, result: Result<TorrentInfo, AppError>) {
+        self.checking = false;
+        match result {
+            Ok(info) => {
+                StoredDetail::store::<V>(Some(&info));
+                self.set_phase(TorrentDetailPhase::Details(info));
+            }
+            Err(e) => {
+                log::warn!("Restored listing couldn't be found again: {e}");
+                self.status_alert.set_is_visible(false);
+                let notice = if e.kind == ErrorKind::ListingNotFound {
+                    "This listing may no longer exist, so it can't be added.".to_string()
+                } else {
+                    format!("Couldn't check this listing still exists, so it can't be added: {e}")
+                };
+                self.stale_text.set_text(notice);
+                self.stale.set(true);
+            }
+        }
+    }
+
+    /// Show or hide the warning asking whether to add a torrent that won't
+    /// fit.
+    fn set_pending_add(&mut self, destination: Option<Destination>) {
+        self.pending_add = destination;
+        self.confirming.set(destination.is_some());
+    }
+
+    /// Offer any torrent added from here as a replacement for `replacing`.
+    pub fn set_replacing(&mut self, replacing: Option<Replacing>) {
+        self.replacing = replacing;
+    }
+
+    /// The download still to be replaced, if there is one.
+    pub fn take_replacing(&mut self) -> Option<Replacing> {
+        self.set_added(None);
+        self.replacing.take()
+    }
+
+    /// Show or hide the offer to replace the broken download with `added`.
+    fn set_added(&mut self, added: Option<InfoHash>) {
+        if let (Some(replacing), Some(_)) = (&self.replacing, &added) {
+            self.replace_text.set_text(format!(
+                "Added. Replace '{}' with it? It takes over the ledger entry, and '{}' is \
+                 removed from the download client.",
+                replacing.name, replacing.name
+            ));
+        }
+        self.offering.set(added.is_some());
+        self.added = added;
+    }
+
+    /// Add the torrent if the download client has room for it, otherwise
+    /// ask first.
+    async fn add_checked(&mut self, destination: Destination) {
+        let TorrentDetailPhase::Details(info) = self.phase.deref() else {
+            return;
+        };
+        let size = info.size;
+        match SpaceCheck::new(size, &super::free_space().await) {
+            SpaceCheck::Fits => {}
+            SpaceCheck::Short(short) => {
+                self.space_text
+                    .set_text(format!("{short} \u{2014} add anyway?"));
+                self.set_pending_add(Some(destination));
+                return;
+            }
+            SpaceCheck::Unknown(notice) => {
+                self.status_alert.set_flavor(Flavor::Info);
+                self.status_alert.set_text(notice);
+                self.status_alert.set_is_visible(true);
+            }
+        }
+        self.add(destination).await;
+    }
+
+    async fn add(&mut self, destination: Destination) {
+        if let TorrentDetailPhase::Details(info) = self.phase.deref() {
+            // Added over RPC rather than by opening the link, which would
+            // only come back to Privateer as the magnet handler.
+            let added = info.info_hash.parse::<InfoHash>().ok();
+            match add_torrent(info, destination).await {
+                Ok(()) => {
+                    log::info!("...done.");
+                    if self.replacing.is_some() {
+                        self.set_added(added);
+                    }
+                }
+                Err(e) => {
+                    log::error!("...adding failed: {e}");
+                    self.status_alert.set_flavor(Flavor::Danger);
+                    self.status_alert.set_text(format!("Error: {e}"));
+                    self.status_alert.set_is_visible(true);
+                }
+            }
+        }
     }
 
     pub async fn step(&mut self) {
         loop {
-            if let Some(add_group) = self.add_button_group.as_mut() {
-                log::info!("step details with add button");
-
-                let clicked_back = self
-                    .back_button
-                    .step()
-                    .map(|_| None)
-                    .or(add_group.step().map(Some))
-                    .await;
-
-                match clicked_back {
-                    None => break, // back button
-                    Some(action) => {
-                        let destination = match &action {
-                            MagnetAction::AddPrimary => self
-                                .add_button_group
-                                .as_ref()
-                                .map(|g| g.selected)
-                                .unwrap_or_default(),
-                            MagnetAction::AddAlternate(d) => *d,
-                        };
-
-                        if let TorrentDetailPhase::Details(info) = self.phase.deref() {
-                            // Record in the ledger first — open::path may
-                            // disrupt the WASM context by handing focus to
-                            // the OS magnet handler.
-                            log::info!("Recording the download...");
-                            match Self::record_download(&info.info_hash, &info.name, destination)
-                                .await
-                            {
-                                Ok(()) => {
-                                    log::info!("...done.");
-                                    // Then open the magnet link via OS handler
-                                    if let Some(link) = info.magnet.as_ref() {
-                                        log::info!("...opening the magnet link.");
-                                        open::path(link).await;
-                                    }
-                                }
-                                Err(e) => log::error!("...recording failed: {e}"),
-                            }
-                        }
+            let add_group = self.add_button_group.as_mut();
+            let check = match self.phase.deref() {
+                TorrentDetailPhase::Details(info) if self.checking => Some(info.id.to_string()),
+                _ => None,
+            };
+
+            let action = self
+                .back_button
+                .step()
+                .map(|_| DetailAction::Back)
+                .or(async move {
+                    match add_group {
+                        Some(add_group) => DetailAction::Add(add_group.step().await),
+                        None => std::future::pending().await,
                     }
+                })
+                .or(async move {
+                    match check {
+                        Some(id) => DetailAction::Checked(super::info(&id).await),
+                        None => std::future::pending().await,
+                    }
+                })
+                .or(self
+                    .on_click_add_anyway
+                    .next()
+                    .map(|_| DetailAction::AddAnyway))
+                .or(self
+                    .on_click_cancel_add
+                    .next()
+                    .map(|_| DetailAction::CancelAdd))
+                .or(self.on_click_replace.next().map(|_| DetailAction::Replace))
+                .or(self
+                    .on_click_keep_both
+                    .next()
+                    .map(|_| DetailAction::KeepBoth))
+                .or(self.on_click_recheck.next().map(|_| DetailAction::Recheck))
+                .await;
+
+            match action {
+                DetailAction::Back => break,
+                DetailAction::Add(action) => {
+                    let destination = match &action {
+                        MagnetAction::AddPrimary => self
+                            .add_button_group
+                            .as_ref()
+                            .map(|g| g.selected)
+                            .unwrap_or_default(),
+                        MagnetAction::AddAlternate(d) => *d,
+                    };
+                    self.add_checked(destination).await;
+                }
+                DetailAction::AddAnyway => {
+                    if let Some(destination) = self.pending_add {
+                        self.set_pending_add(None);
+                        self.add(destination).await;
+                    }
+                }
+                DetailAction::CancelAdd => self.set_pending_add(None),
+                DetailAction::Replace => {
+                    if let (Some(replacing), Some(new_hash)) =
+                        (self.replacing.take(), self.added.clone())
+                    {
+                        let label = format!("Replacing '{}'", replacing.name);
+                        pending::enqueue(PendingAction::new(label, move |confirm| {
+                            replacing.replace_with(new_hash, confirm)
+                        }));
+                    }
+                    self.set_added(None);
+                }
+                DetailAction::KeepBoth => {
+                    self.replacing = None;
+                    self.set_added(None);
                 }
-            } else {
-                self.back_button.step().await;
-                break;
+                DetailAction::Checked(result) => self.checked(result),
+                DetailAction::Recheck => self.check_restored(),
             }
         }
     }
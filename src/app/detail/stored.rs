@@ -0,0 +1,116 @@
+//! The torrent open in the Search tab's detail pane, remembered across
+//! restarts.
+//!
+//! It is kept in localStorage as a versioned [`StoredDetail`].  Anything
+//! stored in another shape, e.g. by an older version, or that isn't JSON
+//! at all, is discarded rather than restored.  A restored torrent may be
+//! long gone from the search API, so the detail pane checks it again.
+use mogwai::web::prelude::*;
+use privateer_wire_types::TorrentInfo;
+
+use crate::app::storage;
+
+/// localStorage key for the torrent.
+const STORAGE_KEY: &str = "store-state";
+/// The current [`StoredDetail::version`].
+const VERSION: u32 = 1;
+
+/// The torrent in the detail pane, as stored.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StoredDetail {
+    /// The version of the shape this was stored in.
+    pub version: u32,
+    pub info: TorrentInfo,
+}
+
+impl StoredDetail {
+    /// The torrent in a stored detail, `None` if it isn't one of this
+    /// version.
+    fn parse(s: &str) -> Option<TorrentInfo> {
+        serde_json::from_str::<Self>(s)
+            .ok()
+            .filter(|stored| stored.version == VERSION)
+            .map(|stored| stored.info)
+    }
+
+    /// Load the stored torrent, discarding whatever can't be restored.
+    pub fn load<V: View>() -> Option<TorrentInfo> {
+        let s = storage::local::<V>()?
+            .get_item(STORAGE_KEY)
+            .ok()
+            .flatten()?;
+        let info = Self::parse(&s);
+        if info.is_none() {
+            log::info!("discarding the stored search detail, which can't be restored");
+            storage::remove::<V>(STORAGE_KEY);
+        }
+        info
+    }
+
+    /// Remember `info` as the torrent in the detail pane, or forget it.
+    pub fn store<V: View>(info: Option<&TorrentInfo>) {
+        let Some(info) = info else {
+            storage::remove::<V>(STORAGE_KEY);
+            return;
+        };
+        let stored = Self {
+            version: VERSION,
+            info: info.clone(),
+        };
+        storage::set::<V, _>(STORAGE_KEY, &stored);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> TorrentInfo {
+        TorrentInfo {
+            added: 1700000000,
+            category: 208,
+            descr: Some("Episode five".to_string()),
+            download_count: None,
+            id: 71234567,
+            info_hash: "a".repeat(40),
+            leechers: 3,
+            name: "The.Bear.S02E05.1080p.WEB.h264-ETHEL".to_string(),
+            num_files: Some(1),
+            seeders: 40,
+            size: 1_400_000_000,
+            status: "vip".to_string(),
+            username: "ethel".to_string(),
+            magnet: None,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let stored = StoredDetail {
+            version: VERSION,
+            info: info(),
+        };
+        let s = serde_json::to_string(&stored).unwrap();
+        assert_eq!(Some(info()), StoredDetail::parse(&s));
+    }
+
+    #[test]
+    fn other_shapes_are_discarded() {
+        // Stored bare by older versions
+        let bare = serde_json::to_string(&info()).unwrap();
+        assert_eq!(None, StoredDetail::parse(&bare));
+        assert_eq!(None, StoredDetail::parse("null"));
+        assert_eq!(None, StoredDetail::parse("not json"));
+        assert_eq!(
+            None,
+            StoredDetail::parse(r#"{"version":1,"info":{"id":7}}"#)
+        );
+
+        let newer = StoredDetail {
+            version: VERSION + 1,
+            info: info(),
+        };
+        let newer = serde_json::to_string(&newer).unwrap();
+        assert_eq!(None, StoredDetail::parse(&newer));
+    }
+}
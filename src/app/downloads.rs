@@ -1,19 +1,215 @@
 //! Downloads view - shows Transmission torrent progress.
+use std::collections::HashMap;
+
 use futures_lite::FutureExt;
-use human_repr::HumanCount;
 use iti::components::alert::Alert;
 use iti::components::progress::Progress;
 use iti::components::Flavor;
 use mogwai::future::MogwaiFutureExt;
 use mogwai::web::prelude::*;
-use privateer_wire_types::{Destination, ErrorKind, TransmissionStatus, TransmissionTorrent};
+use privateer_wire_types::{
+    format_duration, format_eta, format_relative, AddOutcome, AppError, ClientFeature, CopyState,
+    CopyTarget, DaemonInfo, Destination, ErrorKind, HashesArgs, InfoHash, InfoHashArgs,
+    NewDownload, ReconcileSummary, RemoveTorrentsArgs, RenameTorrentArgs, RepairSummary,
+    TransmissionStatus, TransmissionTorrent, UpdateNoteArgs,
+};
+
+use super::pending::{self, PendingAction};
+use super::replace::Replacing;
+use super::{format, invoke, is_inside, unix_now};
+
+mod auto_assign;
+use auto_assign::{AutoAssignAction, AutoAssignModal};
+mod columns;
+use columns::{Column, ColumnAction, ColumnChooser, HiddenColumns};
+mod location;
+use location::LocationModal;
+mod missing;
+use missing::{MissingAction, MissingTorrents};
+mod removed;
+use removed::RemovedDownloads;
+mod sections;
+use sections::{SectionAction, Sections, Stage};
+mod sparkline;
+use sparkline::Sparkline;
+mod ui_state;
+use ui_state::UiState;
+mod unassigned;
+pub use unassigned::UnassignedBanner;
+mod usage;
+use usage::UsageHeader;
+
+pub async fn get_torrents(force: bool) -> Result<Vec<TransmissionTorrent>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args {
+        force: bool,
+    }
+    invoke::cmd("get_torrents", &Args { force }).await
+}
+
+async fn pause_torrents(hashes: &[InfoHash]) -> Result<(), AppError> {
+    let hashes = hashes.to_vec();
+    invoke::cmd("pause_torrents", &HashesArgs { hashes }).await
+}
+
+async fn resume_torrents(hashes: &[InfoHash]) -> Result<(), AppError> {
+    let hashes = hashes.to_vec();
+    invoke::cmd("resume_torrents", &HashesArgs { hashes }).await
+}
+
+async fn retry_copies(hashes: &[InfoHash]) -> Result<(), AppError> {
+    let hashes = hashes.to_vec();
+    invoke::cmd("retry_copies", &HashesArgs { hashes }).await
+}
+
+async fn copy_now(hashes: &[InfoHash]) -> Result<(), AppError> {
+    let hashes = hashes.to_vec();
+    invoke::cmd("copy_now", &HashesArgs { hashes }).await
+}
+
+/// Unconfirmed removals are refused; see [`pending`].
+pub(super) async fn remove_torrents(hashes: Vec<InfoHash>, confirm: bool) -> Result<(), AppError> {
+    let args = RemoveTorrentsArgs {
+        hashes,
+        delete_data: false,
+        confirm,
+    };
+    invoke::cmd("remove_torrents", &args).await
+}
+
+async fn rename_torrent(
+    info_hash: &InfoHash,
+    old_path: &str,
+    new_name: &str,
+) -> Result<(), AppError> {
+    invoke::cmd(
+        "rename_torrent",
+        &RenameTorrentArgs {
+            info_hash: info_hash.clone(),
+            old_path: old_path.to_string(),
+            new_name: new_name.to_string(),
+        },
+    )
+    .await
+}
+
+async fn update_download_note(info_hash: &InfoHash, note: &str) -> Result<(), AppError> {
+    invoke::cmd(
+        "update_download_note",
+        &UpdateNoteArgs {
+            info_hash: info_hash.clone(),
+            note: Some(note.to_string()),
+        },
+    )
+    .await
+}
+
+async fn repair_torrent(info_hash: &InfoHash) -> Result<RepairSummary, AppError> {
+    let info_hash = info_hash.clone();
+    invoke::cmd("repair_torrent", &InfoHashArgs { info_hash }).await
+}
+
+/// Ask where to save an export of the downloads list and save it there.
+/// Returns the chosen path, or `None` if the dialog was cancelled.
+async fn save_downloads_export() -> Result<Option<String>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("save_downloads_export", &Empty {}).await
+}
+
+/// Pause copying when `paused`, otherwise resume it.
+async fn set_copying_paused(paused: bool) -> Result<(), AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    let cmd = if paused {
+        "pause_copying"
+    } else {
+        "resume_copying"
+    };
+    invoke::cmd(cmd, &Empty {}).await
+}
+
+/// Look through the libraries for downloads already there, now rather than
+/// at the next copy cycle.
+async fn reconcile_now() -> Result<ReconcileSummary, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("reconcile_now", &Empty {}).await
+}
+
+async fn is_copying_paused() -> Result<bool, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("is_copying_paused", &Empty {}).await
+}
 
-use super::invoke;
+async fn get_daemon_info() -> Result<Option<DaemonInfo>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_daemon_info", &Empty {}).await
+}
 
-pub async fn get_torrents() -> Result<Vec<TransmissionTorrent>, privateer_wire_types::AppError> {
+async fn get_active_count() -> Result<usize, AppError> {
     #[derive(serde::Serialize)]
     struct Empty {}
-    invoke::cmd("get_torrents", &Empty {}).await
+    invoke::cmd("get_active_count", &Empty {}).await
+}
+
+/// Count badge in the "Downloads" tab label showing how many torrents are
+/// downloading or being copied.  Hidden when nothing is active.
+pub struct DownloadsBadge<V: View> {
+    count: Proxy<usize>,
+    count_text: V::Text,
+}
+
+impl<V: View> DownloadsBadge<V> {
+    /// Create the badge along with the tab label that contains it.
+    pub fn new() -> (V::Element, Self) {
+        let mut count = Proxy::new(0);
+        rsx! {
+            let label = span() {
+                "Downloads"
+                span(
+                    class = "badge rounded-pill text-bg-primary ms-1",
+                    style:display = count(n => if *n == 0 { "none" } else { "" }),
+                ) {
+                    let count_text = ""
+                }
+            }
+        }
+        (label, Self { count, count_text })
+    }
+
+    /// Fetch the active count from the backend and update the badge.
+    pub async fn refresh(&mut self) {
+        match get_active_count().await {
+            Ok(n) => {
+                self.count_text.set_text(n.to_string());
+                self.count.set(n);
+            }
+            Err(e) => {
+                // Transmission is probably unreachable; the Downloads tab
+                // reports that itself, so just hide the badge.
+                log::debug!("Failed to get active count: {e}");
+                self.count.set(0);
+            }
+        }
+    }
+}
+
+/// How many of the rows `new`, in order, are already in place at the front
+/// of a parent that held `old`.
+///
+/// Those stay put and everything after the first mismatch, including new
+/// rows and rows moving in from another parent, is (re-)appended in order.
+/// Rows of `old` that aren't in `new` are removed beforehand, so they don't
+/// count as a mismatch.
+fn settled_rows<K: PartialEq>(old: &[K], new: &[K]) -> usize {
+    let in_dom = old.iter().filter(|key| new.contains(key));
+    new.iter()
+        .zip(in_dom)
+        .take_while(|(new, old)| new == old)
+        .count()
 }
 
 fn status_flavor(status: &TransmissionStatus) -> Flavor {
@@ -23,7 +219,122 @@ fn status_flavor(status: &TransmissionStatus) -> Flavor {
         TransmissionStatus::Stopped => Flavor::Secondary,
         TransmissionStatus::QueuedDownload | TransmissionStatus::QueuedSeed => Flavor::Warning,
         TransmissionStatus::Verifying | TransmissionStatus::QueuedVerify => Flavor::Info,
+        TransmissionStatus::Unknown(_) => Flavor::Secondary,
+    }
+}
+
+/// Fraction shown in the progress column.
+///
+/// While Transmission is verifying a torrent the download percentage is
+/// stale, so the verification progress is shown instead.
+fn progress_fraction(t: &TransmissionTorrent) -> f64 {
+    if t.status == TransmissionStatus::Verifying {
+        t.recheck_progress
+    } else {
+        t.percent_done
+    }
+}
+
+/// Flavor of the progress bar, Info while a verification is under way
+/// whatever the download did before.
+fn progress_flavor(t: &TransmissionTorrent) -> Flavor {
+    if t.status.is_verifying() {
+        Flavor::Info
+    } else {
+        status_flavor(&t.status)
+    }
+}
+
+/// Status badge text, with Transmission's error message for errored
+/// torrents.
+fn status_label(t: &TransmissionTorrent) -> String {
+    if t.error != 0 && !t.error_string.is_empty() {
+        format!("{}: {}", t.status.label(), t.error_string)
+    } else {
+        t.status.label()
+    }
+}
+
+/// Upload ratio with two decimals; Transmission uses -1 for "not available"
+/// and -2 for infinite.
+fn ratio_label(ratio: f64) -> String {
+    if ratio == -2.0 {
+        "\u{221E}".to_string()
+    } else if ratio < 0.0 {
+        "\u{2014}".to_string()
+    } else {
+        format!("{ratio:.2}")
+    }
+}
+
+/// Tooltip of the progress cell: the transfer rates, and the time left
+/// while downloading.
+fn progress_title(t: &TransmissionTorrent) -> String {
+    let rates = format!(
+        "\u{2193} {} \u{2191} {}",
+        format::rate(t.rate_download),
+        format::rate(t.rate_upload)
+    );
+    if t.percent_done < 1.0 {
+        format!("{rates}\nTime left: {}", format_eta(t.eta))
+    } else {
+        rates
+    }
+}
+
+/// How long before `now` the torrent was added, e.g. "3d 4h ago".
+fn added_label(added_date: i64, now: i64) -> String {
+    if added_date <= 0 {
+        return String::new();
+    }
+    format_relative(now, added_date)
+}
+
+/// Tooltip of the "Added" cell: the local date and time it was added, and
+/// when it completed.
+fn added_title(t: &TransmissionTorrent) -> String {
+    if t.added_date <= 0 {
+        return String::new();
     }
+    let mut title = format!("Added {}", format::timestamp(t.added_date));
+    if t.done_date > 0 {
+        let done = format::timestamp(t.done_date);
+        title.push_str(&format!("\nCompleted {done}"));
+    }
+    title
+}
+
+/// Tooltip of the destination badge: whether the user assigned it or
+/// Privateer found the torrent already in a library.
+fn origin_title(t: &TransmissionTorrent) -> &'static str {
+    t.origin.map(|o| o.label()).unwrap_or_default()
+}
+
+/// Compact peers cell: connected peers, with the number sending to us.
+/// Tooltip of the "likely dead" badge: since when no peer has sent data.
+fn dead_title(t: &TransmissionTorrent, now: i64) -> String {
+    match t.unavailable_since {
+        Some(since) => format!(
+            "No peer has sent any data since {} ({})",
+            format::timestamp(since),
+            format_relative(now, since)
+        ),
+        None => String::new(),
+    }
+}
+
+fn peers_label(t: &TransmissionTorrent) -> String {
+    format!("{} ({}\u{2193})", t.peers_connected, t.peers_sending_to_us)
+}
+
+/// The values of the columns a narrow window hides, for the row detail.
+fn row_detail(t: &TransmissionTorrent, now: i64) -> String {
+    let dest = t.destination.map_or("\u{2014}", |d| d.label());
+    format!(
+        "Peers: {} \u{00b7} Added: {} \u{00b7} Dest: {dest}",
+        peers_label(t),
+        added_label(t.added_date, now)
+    )
 }
 
 fn dest_flavor(dest: &Destination) -> Flavor {
@@ -33,59 +344,440 @@ fn dest_flavor(dest: &Destination) -> Flavor {
     }
 }
 
-/// Event emitted by an assign button in a torrent row.
-struct AssignEvent {
-    hash_string: String,
-    name: String,
-    destination: Destination,
+/// Event emitted by the assign dropdown in a torrent row.
+enum AssignAction {
+    /// The "Assign…" toggle was clicked.
+    Toggle,
+    /// A destination was picked from the menu and awaits confirmation.
+    Pick(Destination),
+    /// The pending destination was confirmed.
+    Confirm,
+    /// The pending destination was dismissed.
+    Cancel,
+}
+
+/// Event emitted by the inline name editor in a torrent row.
+enum RenameAction {
+    /// The pencil next to the name was clicked.
+    Edit,
+    /// The new name was submitted.
+    Save,
+    Cancel,
+}
+
+/// Event emitted by the inline note editor in a torrent row.
+enum NoteAction {
+    /// The note icon was clicked.
+    Edit,
+    /// The note was submitted.
+    Save,
+    Cancel,
+}
+
+/// Event emitted by a torrent row.
+enum RowAction {
+    Assign(AssignAction),
+    Rename(RenameAction),
+    Note(NoteAction),
+    /// The row's selection checkbox was clicked.
+    Select,
+    /// The "Fix" button of an errored torrent was clicked.
+    Repair,
+    /// The copy indicator was clicked.
+    ClickCopied,
+    /// The caret that opens or closes the row detail was clicked.
+    ToggleDetail,
+    /// "Find another" was clicked on a likely dead download.
+    FindAnother,
+    /// "Find replacement" was clicked on a download with a lasting error.
+    FindReplacement,
+    /// "Where are my files?" was clicked.
+    Locate,
+}
+
+/// Event emitted by the batch action bar.
+enum BatchAction {
+    Assign(AssignAction),
+    /// The select-all checkbox in the header was clicked.
+    SelectAll,
+    Pause,
+    Resume,
+    /// Reset failed copies so they are retried right away.
+    Retry,
+    /// Copy as soon as possible, even during quiet hours.
+    CopyNow,
+    /// Remove from the download client, after a countdown.
+    Remove,
+}
+
+/// A command whose failure is shown in the status alert, to run again when
+/// Retry is clicked.
+enum FailedCommand {
+    /// Fetching the torrents.
+    Poll,
+    Export,
+    Readd(Vec<InfoHash>),
+}
+
+/// Dropdown for assigning (or re-assigning) a torrent to a destination.
+///
+/// Picking a destination doesn't write the ledger right away: the choice
+/// has to be confirmed first, so a stray click can't kick off a copy.
+struct AssignDropdown<V: View> {
+    wrapper: V::Element,
+    on_click_toggle: V::EventListener,
+    on_click_movies: V::EventListener,
+    on_click_shows: V::EventListener,
+    on_click_confirm: V::EventListener,
+    on_click_cancel: V::EventListener,
+    menu_open: Proxy<bool>,
+    is_menu_open: bool,
+    pending: Proxy<Option<Destination>>,
+    pending_destination: Option<Destination>,
+    confirm_text: V::Text,
+}
+
+impl<V: View> AssignDropdown<V> {
+    fn new() -> Self {
+        let mut menu_open = Proxy::new(false);
+        let mut pending = Proxy::<Option<Destination>>::new(None);
+        rsx! {
+            let wrapper = div(class = "assign-dropdown d-inline-flex align-items-center gap-1") {
+                div(
+                    class = "btn-group",
+                    style:display = pending(p => if p.is_some() { "none" } else { "" }),
+                ) {
+                    button(
+                        class = "btn btn-outline-secondary btn-sm dropdown-toggle",
+                        type = "button",
+                        on:click = on_click_toggle,
+                    ) { "Assign\u{2026}" }
+                    ul(
+                        class = menu_open(is_open => if *is_open {
+                            "dropdown-menu show"
+                        } else {
+                            "dropdown-menu"
+                        }),
+                    ) {
+                        li() {
+                            a(
+                                class = "dropdown-item",
+                                href = "#",
+                                on:click = on_click_movies,
+                            ) { "Movies" }
+                        }
+                        li() {
+                            a(
+                                class = "dropdown-item",
+                                href = "#",
+                                on:click = on_click_shows,
+                            ) { "Shows" }
+                        }
+                    }
+                }
+                span(
+                    class = "d-inline-flex align-items-center gap-1",
+                    style:display = pending(p => if p.is_some() { "" } else { "none" }),
+                ) {
+                    span(class = "small text-nowrap") { let confirm_text = "" }
+                    button(
+                        class = "btn btn-primary btn-sm",
+                        type = "button",
+                        on:click = on_click_confirm,
+                    ) { "Confirm" }
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_cancel,
+                    ) { "Cancel" }
+                }
+            }
+        }
+
+        Self {
+            wrapper,
+            on_click_toggle,
+            on_click_movies,
+            on_click_shows,
+            on_click_confirm,
+            on_click_cancel,
+            menu_open,
+            is_menu_open: false,
+            pending,
+            pending_destination: None,
+            confirm_text,
+        }
+    }
+
+    fn toggle_menu(&mut self) {
+        self.is_menu_open = !self.is_menu_open;
+        self.menu_open.set(self.is_menu_open);
+    }
+
+    fn hide_menu(&mut self) {
+        if self.is_menu_open {
+            self.is_menu_open = false;
+            self.menu_open.set(false);
+        }
+    }
+
+    fn set_pending(&mut self, destination: Option<Destination>) {
+        self.pending_destination = destination;
+        if let Some(dest) = destination {
+            self.confirm_text
+                .set_text(format!("Assign to {}?", dest.label()));
+        }
+        self.pending.set(destination);
+    }
+
+    /// Wait for the next click inside the dropdown.
+    async fn step(&self) -> AssignAction {
+        self.on_click_toggle
+            .next()
+            .map(|_| AssignAction::Toggle)
+            .or(self
+                .on_click_movies
+                .next()
+                .map(|_| AssignAction::Pick(Destination::Movies)))
+            .or(self
+                .on_click_shows
+                .next()
+                .map(|_| AssignAction::Pick(Destination::Shows)))
+            .or(self.on_click_confirm.next().map(|_| AssignAction::Confirm))
+            .or(self.on_click_cancel.next().map(|_| AssignAction::Cancel))
+            .await
+    }
 }
 
 /// A single row in the downloads table.
 struct TorrentRow<V: View> {
     wrapper: V::Element,
     name_text: V::Text,
+    /// Whether the name is being edited; swaps the name for the editor.
+    renaming: Proxy<bool>,
+    /// Disabled when the client is too old to rename.
+    rename_button: V::Element,
+    on_click_edit_name: V::EventListener,
+    rename_input: V::Element,
+    on_submit_rename: V::EventListener,
+    on_click_cancel_rename: V::EventListener,
+    /// Transmission's error from the last rename, verbatim.
+    rename_error_text: V::Text,
+    /// Whether the download is in the ledger, so can have a note.
+    is_tracked: Proxy<bool>,
+    /// Whether it has a note; puts a dot on the note icon.
+    has_note: Proxy<bool>,
+    /// The note icon; its tooltip is the note.
+    note_button: V::Element,
+    on_click_note: V::EventListener,
+    on_click_locate: V::EventListener,
+    /// Whether the note is being edited.
+    noting: Proxy<bool>,
+    note_input: V::Element,
+    on_submit_note: V::EventListener,
+    on_click_cancel_note: V::EventListener,
+    note_error_text: V::Text,
+    user_note: Option<String>,
+    /// Whether the window is narrow; shows the caret that opens the detail.
+    narrow: Proxy<bool>,
+    /// Whether the row detail is open.
+    expanded: Proxy<bool>,
+    is_expanded: bool,
+    expand_text: V::Text,
+    on_click_expand: V::EventListener,
+    /// The values of the columns a narrow window hides.
+    detail_text: V::Text,
     progress: Progress<V>,
+    /// Its tooltip has the transfer rates and time left.
+    progress_cell: V::Element,
     pct_text: V::Text,
+    /// Recent download rate while downloading.
+    sparkline: Sparkline<V>,
     status_badge: Proxy<TransmissionStatus>,
     status_text: V::Text,
+    /// Whether Transmission reports an error; shows the "Fix" button.
+    has_error: Proxy<bool>,
+    on_click_repair: V::EventListener,
+    peers_text: V::Text,
+    ratio_cell: V::Element,
+    ratio_text: V::Text,
+    /// Whether the torrent has seeded up to its destination's limits.
+    seed_goal_met: Proxy<bool>,
+    /// Whether the torrent is stalled; tints the row amber.
+    stalled: Proxy<bool>,
+    /// Whether no peer has sent data for too long; shows the badge and
+    /// "Find another".
+    likely_dead: Proxy<bool>,
+    /// Its tooltip says since when.
+    dead_badge: V::Element,
+    on_click_find_another: V::EventListener,
+    /// Whether the client has reported an error for too long; shows "Find
+    /// replacement".
+    needs_replacement: Proxy<bool>,
+    on_click_find_replacement: V::EventListener,
+    /// What "Find another" and "Find replacement" search for.
+    search_query: Option<String>,
     size_text: V::Text,
+    /// Holds the added time; its tooltip is the absolute date.
+    added_cell: V::Element,
+    added_text: V::Text,
+    /// Holds the destination; its tooltip says who assigned it.
+    dest_badge: V::Element,
     dest_text: V::Text,
     dest_badge_class: Proxy<Option<Destination>>,
+    /// Holds the indicator; its tooltip is the ledger's note on the copy.
+    copied_cell: V::Element,
+    on_click_copied: V::EventListener,
+    /// Whether clicking the indicator does something: open Settings when no
+    /// directory is set, or start a queued copy.
+    clickable: Proxy<bool>,
     /// The indicator text (checkmark, hourglass, etc.) — shown when assigned.
     copied_text: V::Text,
-    /// Whether the assign buttons are currently visible.
-    has_assign_buttons: Proxy<bool>,
-    /// Click listener for the "M" (Movies) button.
-    on_click_movies: V::EventListener,
-    /// Click listener for the "S" (Shows) button.
-    on_click_shows: V::EventListener,
-    torrent_id: i64,
-    hash_string: String,
+    /// Holds a badge per destination when there is more than one.
+    targets_list: V::Element,
+    target_badges: Vec<V::Element>,
+    /// Dropdown for assigning or re-assigning the destination.
+    assign: AssignDropdown<V>,
+    /// Which columns are hidden; drives the `display` of each cell.
+    columns: Proxy<HiddenColumns>,
+    select_input: V::Element,
+    on_click_select: V::EventListener,
+    /// Whether the row is selected for batch actions.
+    is_selected: bool,
+    /// Whether nothing can be copied until a directory is set; clicking the
+    /// indicator then opens Settings.
+    is_unconfigured: bool,
+    /// Whether the torrent is finished and only waits for the next copy
+    /// cycle; clicking the indicator then starts it now.
+    is_copy_pending: bool,
+    /// The section the torrent belongs in when the table is grouped.
+    stage: Stage,
+    /// The section the row is in, `None` for the flat table.
+    section: Option<Stage>,
+    hash_string: InfoHash,
     torrent_name: String,
 }
 
 impl<V: View> TorrentRow<V> {
-    fn new(t: &TransmissionTorrent) -> Self {
-        let pct = (t.percent_done * 100.0) as u8;
-        let progress = Progress::<V>::new(pct, status_flavor(&t.status));
+    fn new(t: &TransmissionTorrent, hidden: &HiddenColumns, is_narrow: bool, now: i64) -> Self {
+        let pct = (progress_fraction(t) * 100.0).round() as u8;
+        let progress = Progress::<V>::new(pct, progress_flavor(t));
         let mut status_badge = Proxy::new(t.status);
         let mut dest_badge_class = Proxy::new(t.destination);
-        let show_buttons = t.destination.is_none();
-        let mut has_assign_buttons = Proxy::new(show_buttons);
+        let mut columns = Proxy::new(hidden.clone());
+        let mut stalled = Proxy::new(t.stalled);
+        let mut likely_dead = Proxy::new(t.likely_dead);
+        let mut needs_replacement = Proxy::new(t.needs_replacement);
+        let mut has_error = Proxy::new(t.error != 0);
+        let mut seed_goal_met = Proxy::new(t.seed_goal_met);
+        let mut renaming = Proxy::new(false);
+        let mut is_tracked = Proxy::new(t.destination.is_some());
+        let mut has_note = Proxy::new(t.user_note.is_some());
+        let mut noting = Proxy::new(false);
+        let mut narrow = Proxy::new(is_narrow);
+        let mut expanded = Proxy::new(false);
+        let mut clickable = Proxy::new(is_copied_clickable(t));
+        let assign = AssignDropdown::<V>::new();
+        let sparkline = Sparkline::<V>::new();
         rsx! {
-            let wrapper = tr() {
+            let wrapper = tr(class = stalled(s => if *s { "table-warning" } else { "" })) {
+                td() {
+                    let select_input = input(
+                        class = "form-check-input",
+                        type = "checkbox",
+                        on:click = on_click_select,
+                    ){}
+                }
                 td(class = "torrent-name", style:text_align = "left") {
-                    let name_text = ""
+                    div(style:display = renaming(r => if *r { "none" } else { "" })) {
+                        button(
+                            class = "btn btn-link btn-sm p-0 me-1 row-expand",
+                            type = "button",
+                            title = "Show the hidden columns",
+                            style:display = narrow(n => if *n { "" } else { "none" }),
+                            on:click = on_click_expand,
+                        ) {
+                            let expand_text = "\u{25b8}"
+                        }
+                        span() { let name_text = "" }
+                        let rename_button = button(
+                            class = "btn btn-link btn-sm p-0 ms-1 text-body-secondary",
+                            type = "button",
+                            title = "Rename",
+                            on:click = on_click_edit_name,
+                        ) { "\u{270e}" }
+                        let note_button = button(
+                            class = "btn btn-link btn-sm p-0 ms-1 text-body-secondary position-relative",
+                            type = "button",
+                            style:display = is_tracked(t => if *t { "" } else { "none" }),
+                            on:click = on_click_note,
+                        ) {
+                            "\u{1f5d2}"
+                            span(
+                                class = "position-absolute top-0 start-100 translate-middle p-1 bg-primary rounded-circle",
+                                style:display = has_note(n => if *n { "" } else { "none" }),
+                            ){}
+                        }
+                        button(
+                            class = "btn btn-link btn-sm p-0 ms-1 text-body-secondary",
+                            type = "button",
+                            title = "Where are my files?",
+                            on:click = on_click_locate,
+                        ) { "\u{1f4c2}" }
+                    }
+                    form(
+                        style:display = renaming(r => if *r { "" } else { "none" }),
+                        on:submit = on_submit_rename,
+                    ) {
+                        div(class = "input-group input-group-sm") {
+                            let rename_input = input(class = "form-control", type = "text"){}
+                            button(class = "btn btn-outline-primary", type = "submit") { "Save" }
+                            button(
+                                class = "btn btn-outline-secondary",
+                                type = "button",
+                                on:click = on_click_cancel_rename,
+                            ) { "Cancel" }
+                        }
+                        div(class = "small text-danger") { let rename_error_text = "" }
+                    }
+                    form(
+                        style:display = noting(n => if *n { "" } else { "none" }),
+                        on:submit = on_submit_note,
+                    ) {
+                        div(class = "input-group input-group-sm") {
+                            let note_input = input(
+                                class = "form-control",
+                                type = "text",
+                                placeholder = "Note",
+                            ){}
+                            button(class = "btn btn-outline-primary", type = "submit") { "Save" }
+                            button(
+                                class = "btn btn-outline-secondary",
+                                type = "button",
+                                on:click = on_click_cancel_note,
+                            ) { "Cancel" }
+                        }
+                        div(class = "small text-danger") { let note_error_text = "" }
+                    }
+                    div(style:display = narrow(n => if *n { "" } else { "none" })) {
+                        div(
+                            class = "row-detail small text-body-secondary",
+                            style:display = expanded(e => if *e { "" } else { "none" }),
+                        ) {
+                            let detail_text = ""
+                        }
+                    }
                 }
-                td() {
+                let progress_cell = td(style:display = columns(c => c.display(Column::Progress))) {
                     div(class = "d-flex align-items-center gap-2") {
                         div(style:flex = "1", style:min_width = "80px") {
                             {&progress}
                         }
                         span() { let pct_text = "" }
+                        {&sparkline.wrapper}
                     }
                 }
-                td() {
+                td(style:display = columns(c => c.display(Column::Status))) {
                     span(
                         class = status_badge(s => {
                             format!("badge text-bg-{}", status_flavor(s))
@@ -93,10 +785,46 @@ impl<V: View> TorrentRow<V> {
                     ) {
                         let status_text = ""
                     }
+                    button(
+                        class = "btn btn-outline-danger btn-sm ms-1",
+                        type = "button",
+                        style:display = has_error(e => if *e { "" } else { "none" }),
+                        on:click = on_click_repair,
+                    ) { "Fix" }
+                    button(
+                        class = "btn btn-outline-primary btn-sm ms-1",
+                        type = "button",
+                        title = "Search for another release, as good or better",
+                        style:display = needs_replacement(n => if *n { "" } else { "none" }),
+                        on:click = on_click_find_replacement,
+                    ) { "Find replacement" }
+                    let dead_badge = span(
+                        class = "badge text-bg-danger ms-1",
+                        style:display = likely_dead(d => if *d { "" } else { "none" }),
+                    ) { "likely dead" }
+                    button(
+                        class = "btn btn-outline-primary btn-sm ms-1",
+                        type = "button",
+                        title = "Search for another copy",
+                        style:display = likely_dead(d => if *d { "" } else { "none" }),
+                        on:click = on_click_find_another,
+                    ) { "Find another" }
                 }
-                td() { let size_text = "" }
-                td() {
+                td(style:display = columns(c => c.display(Column::Peers))) { let peers_text = "" }
+                td(style:display = columns(c => c.display(Column::Ratio))) {
+                    let ratio_cell = span() { let ratio_text = "" }
                     span(
+                        class = "badge text-bg-success ms-1",
+                        title = "Reached its destination's seeding limits, so it is stopped",
+                        style:display = seed_goal_met(m => if *m { "" } else { "none" }),
+                    ) { "goal met" }
+                }
+                td(style:display = columns(c => c.display(Column::Size))) { let size_text = "" }
+                td(style:display = columns(c => c.display(Column::Added))) {
+                    let added_cell = span() { let added_text = "" }
+                }
+                td(style:display = columns(c => c.display(Column::Dest))) {
+                    let dest_badge = span(
                         class = dest_badge_class(d => match d {
                             Some(dest) => format!("badge text-bg-{}", dest_flavor(dest)),
                             None => "".into(),
@@ -105,32 +833,19 @@ impl<V: View> TorrentRow<V> {
                         let dest_text = ""
                     }
                 }
-                td(style:text_align = "center") {
-                    // Indicator text (shown when destination is assigned)
-                    span(
-                        style:display = has_assign_buttons(show => {
-                            if *show { "none" } else { "" }
-                        }),
-                    ) {
-                        let copied_text = ""
-                    }
-                    // Assign buttons (shown when destination is NOT assigned)
-                    div(
-                        class = "btn-group btn-group-sm",
-                        style:display = has_assign_buttons(show => {
-                            if *show { "" } else { "none" }
-                        }),
-                    ) {
-                        button(
-                            class = "btn btn-outline-info btn-sm",
-                            type = "button",
-                            on:click = on_click_movies,
-                        ) { "M" }
-                        button(
-                            class = "btn btn-outline-warning btn-sm",
-                            type = "button",
-                            on:click = on_click_shows,
-                        ) { "S" }
+                td(
+                    style:text_align = "center",
+                    style:display = columns(c => c.display(Column::Copied)),
+                ) {
+                    div(class = "d-flex align-items-center justify-content-center gap-2") {
+                        let copied_cell = span(
+                            style:cursor = clickable(c => if *c { "pointer" } else { "" }),
+                            on:click = on_click_copied,
+                        ) {
+                            let copied_text = ""
+                            let targets_list = div(class = "d-flex flex-column gap-1"){}
+                        }
+                        {&assign.wrapper}
                     }
                 }
             }
@@ -138,146 +853,1250 @@ impl<V: View> TorrentRow<V> {
 
         // Set initial text values
         name_text.set_text(&t.name);
-        pct_text.set_text(format!("{:.1}%", t.percent_done * 100.0));
-        status_text.set_text(t.status.label());
-        size_text.set_text((t.size_when_done as usize).human_count_bytes().to_string());
+        pct_text.set_text(format!("{:.1}%", progress_fraction(t) * 100.0));
+        progress_cell.set_property("title", &progress_title(t));
+        sparkline.update(t);
+        status_text.set_text(status_label(t));
+        peers_text.set_text(peers_label(t));
+        dead_badge.set_property("title", &dead_title(t, now));
+        size_text.set_text(format::size(t.size_when_done.max(0) as u64));
+        added_text.set_text(added_label(t.added_date, now));
+        added_cell.set_property("title", &added_title(t));
+        detail_text.set_text(row_detail(t, now));
         dest_text.set_text(
             t.destination
                 .map(|d| d.label().to_string())
                 .unwrap_or_default(),
         );
-        copied_text.set_text(t.copy_state.indicator());
+        dest_badge.set_property("title", origin_title(t));
+        copied_text.set_text(copied_indicator(t));
+        copied_cell.set_property("title", &copied_title(t));
+        note_button.set_property("title", &note_title(t));
 
-        Self {
+        let mut row = Self {
             wrapper,
             name_text,
+            renaming,
+            rename_button,
+            on_click_edit_name,
+            rename_input,
+            on_submit_rename,
+            on_click_cancel_rename,
+            rename_error_text,
+            is_tracked,
+            has_note,
+            note_button,
+            on_click_note,
+            on_click_locate,
+            noting,
+            note_input,
+            on_submit_note,
+            on_click_cancel_note,
+            note_error_text,
+            user_note: t.user_note.clone(),
+            narrow,
+            expanded,
+            is_expanded: false,
+            expand_text,
+            on_click_expand,
+            detail_text,
             progress,
+            progress_cell,
             pct_text,
+            sparkline,
             status_badge,
             status_text,
+            has_error,
+            on_click_repair,
+            peers_text,
+            ratio_cell,
+            ratio_text,
+            seed_goal_met,
+            stalled,
+            likely_dead,
+            dead_badge,
+            on_click_find_another,
+            needs_replacement,
+            on_click_find_replacement,
+            search_query: t.search_query.clone(),
             size_text,
+            added_cell,
+            added_text,
+            dest_badge,
             dest_text,
             dest_badge_class,
+            copied_cell,
+            on_click_copied,
+            clickable,
             copied_text,
-            has_assign_buttons,
-            on_click_movies,
-            on_click_shows,
-            torrent_id: t.id,
+            targets_list,
+            target_badges: vec![],
+            assign,
+            columns,
+            select_input,
+            on_click_select,
+            is_selected: false,
+            is_unconfigured: is_unconfigured(t),
+            is_copy_pending: t.copy_pending,
+            stage: Stage::of(t),
+            section: None,
             hash_string: t.hash_string.clone(),
             torrent_name: t.name.clone(),
+        };
+        row.set_ratio(t);
+        row.set_targets(&t.targets);
+        row
+    }
+
+    /// Stack a badge with its own indicator for each destination, replacing
+    /// the previous ones.
+    fn set_targets(&mut self, targets: &[CopyTarget]) {
+        for badge in self.target_badges.drain(..) {
+            self.targets_list.remove_child(&badge);
         }
+        for target in targets {
+            let dest = target.destination;
+            let text = format!("{} {}", dest.label(), target.copy_state.indicator());
+            rsx! {
+                let badge = span(class = format!("badge text-bg-{}", dest_flavor(&dest))) {
+                    {text.into_text::<V>()}
+                }
+            }
+            let mut title = target
+                .dir
+                .as_ref()
+                .map(|dir| format!("Copies to {dir}"))
+                .unwrap_or_default();
+            if let Some(failure) = &target.failure {
+                if !title.is_empty() {
+                    title.push_str(". ");
+                }
+                title.push_str(&format!("Copy failed: {failure}"));
+            }
+            if !title.is_empty() {
+                badge.set_property("title", &title);
+            }
+            self.targets_list.append_child(&badge);
+            self.target_badges.push(badge);
+        }
+    }
+
+    fn set_ratio(&self, t: &TransmissionTorrent) {
+        self.ratio_text.set_text(&ratio_label(t.upload_ratio));
+        self.ratio_cell.set_property(
+            "class",
+            if t.upload_ratio >= 1.0 || t.upload_ratio == -2.0 {
+                "text-success"
+            } else {
+                ""
+            },
+        );
+        self.ratio_cell.set_property(
+            "title",
+            &format!(
+                "Uploaded {}, seeding for {}",
+                format::size(t.uploaded_ever.max(0) as u64),
+                format_duration(t.seconds_seeding.max(0) as u64)
+            ),
+        );
+    }
+
+    /// Wait for the next click on the row's controls.
+    async fn step(&self) -> RowAction {
+        self.assign
+            .step()
+            .map(RowAction::Assign)
+            .or(self.on_click_select.next().map(|_| RowAction::Select))
+            .or(self.on_click_repair.next().map(|_| RowAction::Repair))
+            .or(self.on_click_copied.next().map(|_| RowAction::ClickCopied))
+            .or(self.on_click_expand.next().map(|_| RowAction::ToggleDetail))
+            .or(self
+                .on_click_find_another
+                .next()
+                .map(|_| RowAction::FindAnother))
+            .or(self
+                .on_click_find_replacement
+                .next()
+                .map(|_| RowAction::FindReplacement))
+            .or(self.on_click_locate.next().map(|_| RowAction::Locate))
+            .or(self
+                .on_click_edit_name
+                .next()
+                .map(|_| RowAction::Rename(RenameAction::Edit)))
+            .or(self.on_submit_rename.next().map(|ev| {
+                ev.dyn_ev(|ev: &web_sys::Event| ev.prevent_default());
+                RowAction::Rename(RenameAction::Save)
+            }))
+            .or(self
+                .on_click_cancel_rename
+                .next()
+                .map(|_| RowAction::Rename(RenameAction::Cancel)))
+            .or(self
+                .on_click_note
+                .next()
+                .map(|_| RowAction::Note(NoteAction::Edit)))
+            .or(self.on_submit_note.next().map(|ev| {
+                ev.dyn_ev(|ev: &web_sys::Event| ev.prevent_default());
+                RowAction::Note(NoteAction::Save)
+            }))
+            .or(self
+                .on_click_cancel_note
+                .next()
+                .map(|_| RowAction::Note(NoteAction::Cancel)))
+            .await
+    }
+
+    /// Open the note editor holding the note.
+    fn start_note(&mut self) {
+        let note = self.user_note.clone().unwrap_or_default();
+        self.note_input.dyn_el(|input: &web_sys::HtmlInputElement| {
+            input.set_value(&note);
+            input.select();
+        });
+        self.note_error_text.set_text("");
+        self.noting.set(true);
+    }
+
+    fn stop_note(&mut self) {
+        self.noting.set(false);
     }
 
-    fn update(&mut self, t: &TransmissionTorrent) {
-        let pct = (t.percent_done * 100.0) as u8;
+    /// The note typed into the editor.
+    fn typed_note(&self) -> String {
+        self.note_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    /// Swap the name for an editor holding it.
+    fn start_rename(&mut self) {
+        let name = self.torrent_name.clone();
+        self.rename_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&name);
+                input.select();
+            });
+        self.rename_error_text.set_text("");
+        self.renaming.set(true);
+    }
+
+    fn stop_rename(&mut self) {
+        self.renaming.set(false);
+    }
+
+    /// The name typed into the editor.
+    fn new_name(&self) -> String {
+        self.rename_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    /// Show a transient note in the status badge, until the next update.
+    fn set_status_note(&self, note: &str) {
+        self.status_text.set_text(note);
+    }
+
+    /// Read the selection checkbox into `is_selected`.
+    fn read_selected(&mut self) {
+        self.is_selected = self
+            .select_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or(false);
+    }
+
+    fn set_selected(&mut self, selected: bool) {
+        self.is_selected = selected;
+        self.select_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(selected));
+    }
+
+    fn set_hidden_columns(&mut self, hidden: &HiddenColumns) {
+        self.columns.set(hidden.clone());
+    }
+
+    fn set_narrow(&mut self, is_narrow: bool) {
+        self.narrow.set(is_narrow);
+    }
+
+    /// Disable renaming, saying why, if the client can't rename.
+    fn set_can_rename(&self, can_rename: bool) {
+        let title = if can_rename {
+            "Rename".to_string()
+        } else {
+            ClientFeature::RenamePath.unavailable_reason()
+        };
+        self.rename_button
+            .dyn_el(|button: &web_sys::HtmlButtonElement| {
+                button.set_disabled(!can_rename);
+                button.set_title(&title);
+            });
+    }
+
+    /// Open or close the row detail.
+    fn toggle_detail(&mut self) {
+        self.is_expanded = !self.is_expanded;
+        self.expanded.set(self.is_expanded);
+        self.expand_text.set_text(if self.is_expanded {
+            "\u{25be}"
+        } else {
+            "\u{25b8}"
+        });
+    }
+
+    fn update(&mut self, t: &TransmissionTorrent, now: i64) {
+        let pct = (progress_fraction(t) * 100.0).round() as u8;
         self.name_text.set_text(&t.name);
         self.progress.set_value(pct);
-        self.progress.set_flavor(status_flavor(&t.status));
+        self.progress.set_flavor(progress_flavor(t));
         self.pct_text
-            .set_text(format!("{:.1}%", t.percent_done * 100.0));
+            .set_text(format!("{:.1}%", progress_fraction(t) * 100.0));
+        self.progress_cell.set_property("title", &progress_title(t));
+        self.sparkline.update(t);
         self.status_badge.set(t.status);
-        self.status_text.set_text(t.status.label());
+        self.status_text.set_text(status_label(t));
+        self.has_error.set(t.error != 0);
+        self.peers_text.set_text(peers_label(t));
+        self.set_ratio(t);
+        self.seed_goal_met.set(t.seed_goal_met);
+        self.stalled.set(t.stalled);
+        self.likely_dead.set(t.likely_dead);
+        self.needs_replacement.set(t.needs_replacement);
+        self.dead_badge.set_property("title", &dead_title(t, now));
+        self.search_query.clone_from(&t.search_query);
         self.size_text
-            .set_text((t.size_when_done as usize).human_count_bytes().to_string());
+            .set_text(format::size(t.size_when_done.max(0) as u64));
+        self.added_text.set_text(added_label(t.added_date, now));
+        self.added_cell.set_property("title", &added_title(t));
+        self.detail_text.set_text(row_detail(t, now));
         self.dest_badge_class.set(t.destination);
         self.dest_text.set_text(
             t.destination
                 .map(|d| d.label().to_string())
                 .unwrap_or_default(),
         );
-        self.copied_text.set_text(t.copy_state.indicator());
-        self.has_assign_buttons.set(t.destination.is_none());
+        self.dest_badge.set_property("title", origin_title(t));
+        self.copied_text.set_text(copied_indicator(t));
+        self.copied_cell.set_property("title", &copied_title(t));
+        self.set_targets(&t.targets);
+        self.is_tracked.set(t.destination.is_some());
+        self.has_note.set(t.user_note.is_some());
+        self.note_button.set_property("title", &note_title(t));
+        self.user_note.clone_from(&t.user_note);
+        self.clickable.set(is_copied_clickable(t));
+        self.is_unconfigured = is_unconfigured(t);
+        self.is_copy_pending = t.copy_pending;
+        self.stage = Stage::of(t);
         self.hash_string.clone_from(&t.hash_string);
         self.torrent_name.clone_from(&t.name);
     }
 }
 
+/// The status alert note for downloads that can't be copied until a
+/// directory is set.
+fn unconfigured_message(torrents: &[TransmissionTorrent]) -> Option<String> {
+    match torrents.iter().filter(|t| is_unconfigured(t)).count() {
+        0 => None,
+        1 => Some("1 download waiting on configuration.".to_string()),
+        waiting => Some(format!("{waiting} downloads waiting on configuration.")),
+    }
+}
+
+/// The status alert note while quiet hours hold back copies.
+fn quiet_hours_message(torrents: &[TransmissionTorrent]) -> Option<String> {
+    let until = torrents.iter().find_map(|t| t.copy_deferred_until)?;
+    Some(format!("Auto-copy paused until {until} (quiet hours)."))
+}
+
+/// Whether the torrent waits for a destination directory to be set, either
+/// once downloaded or already before.
+fn is_unconfigured(t: &TransmissionTorrent) -> bool {
+    t.copy_state == CopyState::Unconfigured || t.copy_blocked_reason.is_some()
+}
+
+/// Whether clicking the copy indicator does something.
+fn is_copied_clickable(t: &TransmissionTorrent) -> bool {
+    is_unconfigured(t) || t.copy_pending
+}
+
+/// The copy indicator, a gear while waiting on Settings and a clock while
+/// queued for the next copy cycle.  Downloads with more than one
+/// destination show a badge for each instead.
+fn copied_indicator(t: &TransmissionTorrent) -> &'static str {
+    if !t.targets.is_empty() {
+        ""
+    } else if is_unconfigured(t) {
+        CopyState::Unconfigured.indicator()
+    } else if t.copy_pending {
+        "\u{1F552}" // clock
+    } else {
+        t.copy_state.indicator()
+    }
+}
+
+/// Tooltip of the note icon: the note, if there is one.
+fn note_title(t: &TransmissionTorrent) -> String {
+    t.user_note
+        .clone()
+        .unwrap_or_else(|| "Add a note".to_string())
+}
+
+/// Tooltip of the copy indicator.
+fn copied_title(t: &TransmissionTorrent) -> String {
+    if let (CopyState::Failed, Some(failure)) = (t.copy_state, &t.copy_failure) {
+        return format!("Copy failed: {failure}");
+    }
+    if let Some(reason) = &t.copy_blocked_reason {
+        return format!("{reason}. Click to open Settings.");
+    }
+    if t.copy_state == CopyState::Unconfigured {
+        let dest = t.destination.map(|d| d.label()).unwrap_or("destination");
+        return format!("No {dest} directory is set. Click to open Settings.");
+    }
+    if t.copy_pending {
+        return "Queued for copy. Click to start now.".to_string();
+    }
+    let copied = t
+        .copy_throughput
+        .map(|copy| format!("Copied in {}", format::throughput(&copy)));
+    let note = match (&t.copy_note, t.copy_deferred_until) {
+        (Some(note), _) => Some(note.clone()),
+        (None, Some(until)) => Some(format!("Waiting for quiet hours to end at {until}")),
+        (None, None) => None,
+    };
+    copied
+        .into_iter()
+        .chain(note)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the status alert note for stalled torrents, if there are any.
+///
+/// Stalled torrents that are already assigned a destination are called out
+/// separately, since their copy will never trigger until they finish.
+fn stalled_message(torrents: &[TransmissionTorrent]) -> Option<String> {
+    let stalled: Vec<&TransmissionTorrent> = torrents.iter().filter(|t| t.stalled).collect();
+    if stalled.is_empty() {
+        return None;
+    }
+    let names = stalled
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut msg = format!(
+        "{} stalled download(s) with no peers sending data: {names}.",
+        stalled.len()
+    );
+    let assigned = stalled
+        .iter()
+        .filter_map(|t| t.destination.map(|d| format!("{} ({})", t.name, d.label())))
+        .collect::<Vec<_>>();
+    if !assigned.is_empty() {
+        msg.push_str(&format!(
+            " These won't be copied until they finish: {}.",
+            assigned.join(", ")
+        ));
+    }
+    Some(msg)
+}
+
+/// Backend event carrying the latest torrent list.
+const TORRENTS_UPDATED: &str = "torrents-updated";
+
+/// Payload of the [`TORRENTS_UPDATED`] event.
+type TorrentsUpdate = Result<Vec<TransmissionTorrent>, AppError>;
+
+/// How many torrent updates to wait between checks of the destinations'
+/// disk usage.
+const USAGE_EVERY_POLLS: u32 = 10;
+
+/// Where the Downloads view sends the user.
+pub enum DownloadsNav {
+    /// Settings, to set a missing directory.
+    Settings,
+    /// Search, for another copy of a likely dead download.
+    Search(String),
+    /// Search, for a replacement of a download with a lasting error.
+    Replace(Replacing),
+}
+
 /// Downloads tab view.
 #[derive(ViewChild)]
 pub struct DownloadsView<V: View> {
     #[child]
     wrapper: V::Element,
     status_alert: Alert<V>,
+    retry_button: V::Element,
+    on_click_retry_failed: V::EventListener,
+    /// The command that failed in a way worth retrying.
+    retry: Option<FailedCommand>,
+    /// "Copying paused", with a button to resume.
+    paused_strip: V::Element,
+    on_click_resume_copying: V::EventListener,
+    pause_copying_button: V::Element,
+    on_click_pause_copying: V::EventListener,
     table_wrapper: V::Element,
+    colgroup: V::Element,
+    cols: Vec<V::Element>,
+    header_columns: Proxy<HiddenColumns>,
+    /// Hidden columns, grouping and the like, remembered across restarts.
+    ui_state: UiState,
+    /// The only stage whose rows are showing, if the rows are filtered.
+    filter: Option<Stage>,
+    /// "Showing only ...", with a button to show everything again.
+    filter_chip: V::Element,
+    filter_text: V::Text,
+    on_click_clear_filter: V::EventListener,
+    /// Whether the window is narrow; hides the low-priority columns too.
+    is_narrow: bool,
+    /// When the window becomes narrow or wide again.
+    narrow_changes: async_channel::Receiver<bool>,
+    column_chooser: ColumnChooser<V>,
+    /// Holds the rows of the flat table.
     tbody: V::Element,
+    /// Hold the rows of the grouped table.
+    sections: Sections<V>,
     rows: Vec<TorrentRow<V>>,
+    /// Ledger entries Transmission no longer has.
+    missing: MissingTorrents<V>,
+    /// Ledger entries removed lately, which can be restored.
+    removed: RemovedDownloads<V>,
+    /// Where a download's files are, when a row asks.
+    location: LocationModal<V>,
+    /// Destinations proposed for the unassigned torrents, when asked.
+    auto_assign: AutoAssignModal<V>,
+    /// How full each destination is.
+    usage: UsageHeader<V>,
+    /// Torrent updates left before the usage is checked again.
+    polls_until_usage: u32,
+    /// Clicks anywhere in the view, used to close open menus.
+    on_click_view: V::EventListener,
+    // Batch actions
+    select_all_input: V::Element,
+    on_click_select_all: V::EventListener,
+    action_bar: V::Element,
+    selection_text: V::Text,
+    batch_assign: AssignDropdown<V>,
+    on_click_pause: V::EventListener,
+    on_click_resume: V::EventListener,
+    on_click_retry: V::EventListener,
+    on_click_copy_now: V::EventListener,
+    on_click_remove: V::EventListener,
+    // Torrent updates
+    /// Torrent lists pushed by the backend's `torrents-updated` event.
+    updates: async_channel::Receiver<TorrentsUpdate>,
+    on_click_refresh: V::EventListener,
+    on_click_rescan: V::EventListener,
+    on_click_export: V::EventListener,
+    on_click_auto_assign: V::EventListener,
+    /// Whether the initial fetch has happened.
+    has_loaded: bool,
+    /// Whether the download client can rename torrents; old Transmission
+    /// daemons can't.
+    can_rename: bool,
 }
 
 impl<V: View> Default for DownloadsView<V> {
     fn default() -> Self {
         let status_alert = Alert::new("Connecting to Transmission...", Flavor::Info);
+        let ui_state = UiState::load::<V>();
+        let is_narrow = super::layout::is_narrow::<V>();
+        let mut header_columns = Proxy::new(if is_narrow {
+            ui_state.hidden_columns.narrowed()
+        } else {
+            ui_state.hidden_columns.clone()
+        });
+        let column_chooser = ColumnChooser::<V>::new(&ui_state.hidden_columns);
+        let batch_assign = AssignDropdown::<V>::new();
+        let missing = MissingTorrents::<V>::new();
+        let removed = RemovedDownloads::<V>::new();
+        let location = LocationModal::<V>::new();
+        let auto_assign = AutoAssignModal::<V>::new();
+        let usage = UsageHeader::<V>::new(ui_state.usage_collapsed);
+        let sections = Sections::<V>::new(ui_state.grouped, ui_state.collapsed_sections.clone());
+        let updates = if V::is_view::<Web>() {
+            super::events::subscribe(TORRENTS_UPDATED)
+        } else {
+            async_channel::bounded(1).1
+        };
         rsx! {
-            let wrapper = div(class = "container-fluid") {
+            let wrapper = div(class = "container-fluid", on:click = on_click_view) {
                 div(class = "mb-3") {
                     {&status_alert}
+                    let retry_button = button(
+                        class = "btn btn-outline-danger btn-sm",
+                        type = "button",
+                        style:display = "none",
+                        on:click = on_click_retry_failed,
+                    ) { "Retry" }
                 }
-                let table_wrapper = div(class = "table-responsive", style:display = "none") {
-                    table(class = "table table-striped table-hover") {
-                        colgroup() {
-                            col(style:width = "30%"){}
-                            col(style:width = "25%"){}
-                            col(style:width = "12%"){}
-                            col(style:width = "12%"){}
-                            col(style:width = "12%"){}
-                            col(style:width = "9%"){}
-                        }
-                        thead() {
-                            tr() {
-                                th() { "Name" }
-                                th() { "Progress" }
-                                th() { "Status" }
-                                th() { "Size" }
-                                th() { "Dest" }
-                                th() { "Copied" }
-                            }
-                        }
-                        let tbody = tbody() {}
-                    }
+                let paused_strip = div(
+                    class = "alert alert-warning d-flex align-items-center gap-2 py-2",
+                    style:display = "none",
+                ) {
+                    span() { "Copying paused \u{2014} no new copies start until it is resumed." }
+                    button(
+                        class = "btn btn-warning btn-sm ms-auto",
+                        type = "button",
+                        on:click = on_click_resume_copying,
+                    ) { "Resume" }
                 }
-            }
-        }
-        Self {
-            wrapper,
-            status_alert,
+                {&usage.wrapper}
+                div(class = "d-flex align-items-center gap-2 mb-2") {
+                    let action_bar = div(
+                        class = "d-flex align-items-center gap-2",
+                        style:display = "none",
+                    ) {
+                        span(class = "small text-nowrap") { let selection_text = "" }
+                        {&batch_assign.wrapper}
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            on:click = on_click_pause,
+                        ) { "Pause" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            on:click = on_click_resume,
+                        ) { "Resume" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            on:click = on_click_retry,
+                        ) { "Retry copy" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Copy once downloaded, even during quiet hours",
+                            on:click = on_click_copy_now,
+                        ) { "Copy now" }
+                        button(
+                            class = "btn btn-outline-danger btn-sm",
+                            type = "button",
+                            title = "Remove from the download client, keeping the downloaded files",
+                            on:click = on_click_remove,
+                        ) { "Remove" }
+                    }
+                    let filter_chip = span(
+                        class = "badge text-bg-secondary d-flex align-items-center gap-1",
+                        style:display = "none",
+                    ) {
+                        let filter_text = ""
+                        button(
+                            class = "btn-close btn-close-white",
+                            type = "button",
+                            title = "Show every download",
+                            style:font_size = "0.6rem",
+                            on:click = on_click_clear_filter,
+                        ){}
+                    }
+                    div(class = "ms-auto d-flex gap-2") {
+                        let pause_copying_button = button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Start no new copies until resumed",
+                            on:click = on_click_pause_copying,
+                        ) { "Pause copying" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Assign a destination to each unassigned torrent by its name",
+                            on:click = on_click_auto_assign,
+                        ) { "Auto-assign\u{2026}" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Export the downloads list as CSV or JSON",
+                            on:click = on_click_export,
+                        ) { "Export" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Look for downloads already in the libraries",
+                            on:click = on_click_rescan,
+                        ) { "Re-scan libraries" }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            title = "Refresh",
+                            on:click = on_click_refresh,
+                        ) { "\u{21bb}" }
+                        {&sections.toggle_button}
+                        {&column_chooser.wrapper}
+                    }
+                }
+                let table_wrapper = div(style:display = "none") {
+                    div(class = "table-responsive") {
+                        let table = table(class = "table table-striped table-hover") {
+                            let colgroup = colgroup() {}
+                            thead() {
+                                tr() {
+                                    th() {
+                                        let select_all_input = input(
+                                            class = "form-check-input",
+                                            type = "checkbox",
+                                            on:click = on_click_select_all,
+                                        ){}
+                                    }
+                                    th() { "Name" }
+                                    th(style:display = header_columns(c => c.display(Column::Progress))) { "Progress" }
+                                    th(style:display = header_columns(c => c.display(Column::Status))) { "Status" }
+                                    th(style:display = header_columns(c => c.display(Column::Peers))) { "Peers" }
+                                    th(style:display = header_columns(c => c.display(Column::Ratio))) { "Ratio" }
+                                    th(style:display = header_columns(c => c.display(Column::Size))) { "Size" }
+                                    th(style:display = header_columns(c => c.display(Column::Added))) { "Added" }
+                                    th(style:display = header_columns(c => c.display(Column::Dest))) { "Dest" }
+                                    th(style:display = header_columns(c => c.display(Column::Copied))) { "Copied" }
+                                }
+                            }
+                            let tbody = tbody() {}
+                        }
+                    }
+                }
+                {&missing.wrapper}
+                {&removed.wrapper}
+                {&location.wrapper}
+                {&auto_assign.wrapper}
+            }
+        }
+        sections.append_to(&table);
+        let mut view = Self {
+            wrapper,
+            status_alert,
+            retry_button,
+            on_click_retry_failed,
+            retry: None,
+            paused_strip,
+            on_click_resume_copying,
+            pause_copying_button,
+            on_click_pause_copying,
             table_wrapper,
+            colgroup,
+            cols: vec![],
+            header_columns,
+            ui_state,
+            filter: None,
+            filter_chip,
+            filter_text,
+            on_click_clear_filter,
+            is_narrow,
+            narrow_changes: super::layout::narrow_changes::<V>(),
+            column_chooser,
             tbody,
+            sections,
             rows: vec![],
-        }
+            missing,
+            removed,
+            location,
+            auto_assign,
+            usage,
+            polls_until_usage: 0,
+            on_click_view,
+            select_all_input,
+            on_click_select_all,
+            action_bar,
+            selection_text,
+            batch_assign,
+            on_click_pause,
+            on_click_resume,
+            on_click_retry,
+            on_click_copy_now,
+            on_click_remove,
+            updates,
+            on_click_refresh,
+            on_click_rescan,
+            on_click_export,
+            on_click_auto_assign,
+            has_loaded: false,
+            can_rename: true,
+        };
+        view.rebuild_colgroup();
+        view
     }
 }
 
 impl<V: View> DownloadsView<V> {
+    /// Recreate the `<col>` elements so the widths of the visible columns
+    /// add up to 100%.
+    fn rebuild_colgroup(&mut self) {
+        for col in self.cols.drain(..) {
+            self.colgroup.remove_child(&col);
+        }
+        for (_, width) in self.shown_columns().widths() {
+            rsx! {
+                let col = col(style:width = format!("{width:.1}%")){}
+            }
+            self.colgroup.append_child(&col);
+            self.cols.push(col);
+        }
+    }
+
+    /// Show or hide a column in the header and every row, and persist the
+    /// choice.
+    fn toggle_column(&mut self, column: Column) {
+        self.ui_state.hidden_columns.toggle(column);
+        self.show_hidden_columns();
+        self.save_ui_state();
+    }
+
+    /// The columns the table hides: the user's choice, and the low-priority
+    /// columns while the window is narrow.
+    fn shown_columns(&self) -> HiddenColumns {
+        if self.is_narrow {
+            self.ui_state.hidden_columns.narrowed()
+        } else {
+            self.ui_state.hidden_columns.clone()
+        }
+    }
+
+    /// Apply the hidden columns to the header, the chooser and every row.
+    fn show_hidden_columns(&mut self) {
+        let hidden = self.shown_columns();
+        self.header_columns.set(hidden.clone());
+        self.column_chooser
+            .set_hidden(&self.ui_state.hidden_columns);
+        for row in self.rows.iter_mut() {
+            row.set_hidden_columns(&hidden);
+            row.set_narrow(self.is_narrow);
+        }
+        self.rebuild_colgroup();
+    }
+
+    /// Hide or show the low-priority columns.
+    fn set_narrow(&mut self, is_narrow: bool) {
+        if is_narrow != self.is_narrow {
+            self.is_narrow = is_narrow;
+            self.show_hidden_columns();
+        }
+    }
+
+    async fn next_narrow_change(&self) -> bool {
+        match self.narrow_changes.recv().await {
+            Ok(is_narrow) => is_narrow,
+            // Outside the browser the window never resizes
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// Remember the layout after any change to it.
+    fn save_ui_state(&mut self) {
+        self.ui_state.grouped = self.sections.is_grouped();
+        self.ui_state.collapsed_sections = self.sections.collapsed().to_vec();
+        self.ui_state.usage_collapsed = self.usage.is_collapsed();
+        self.ui_state.store::<V>();
+    }
+
+    /// Forget the remembered layout and go back to the defaults.
+    fn reset_view(&mut self) {
+        UiState::clear::<V>();
+        self.ui_state = UiState::default();
+        self.show_hidden_columns();
+        let old_order = self.placement();
+        self.sections.set_layout(
+            self.ui_state.grouped,
+            self.ui_state.collapsed_sections.clone(),
+        );
+        self.place_rows(&old_order);
+        self.usage.set_collapsed(self.ui_state.usage_collapsed);
+    }
+
+    /// The element holding the rows of `section`, or of the flat table.
+    fn rows_parent(&self, section: Option<Stage>) -> &V::Element {
+        match section {
+            Some(stage) => self.sections.rows_body(stage),
+            None => &self.tbody,
+        }
+    }
+
+    /// Diff the incoming torrents against the existing rows, keyed by
+    /// `hash_string`.
+    ///
+    /// Transmission ids are not stable across daemon restarts, so rows are
+    /// matched by info hash: matching rows are updated in place, new rows are
+    /// created and departed rows are removed.  Rows keep their DOM nodes (and
+    /// event listeners) as long as their torrent is still present.
     fn update_torrents(&mut self, torrents: &[TransmissionTorrent]) {
-        // Check if we need to rebuild (different count or different IDs)
-        let needs_rebuild = self.rows.len() != torrents.len()
-            || self
-                .rows
+        let old_order = self.placement();
+        let mut by_hash: HashMap<InfoHash, TorrentRow<V>> = self
+            .rows
+            .drain(..)
+            .map(|row| (row.hash_string.clone(), row))
+            .collect();
+
+        let now = unix_now();
+        let hidden = self.shown_columns();
+        let mut rows = Vec::with_capacity(torrents.len());
+        for t in torrents {
+            let row = match by_hash.remove(&t.hash_string) {
+                Some(mut row) => {
+                    row.update(t, now);
+                    row
+                }
+                None => {
+                    let row = TorrentRow::<V>::new(t, &hidden, self.is_narrow, now);
+                    if !self.can_rename {
+                        row.set_can_rename(false);
+                    }
+                    row
+                }
+            };
+            rows.push(row);
+        }
+
+        // Remove rows whose torrents are gone
+        for row in by_hash.into_values() {
+            self.rows_parent(row.section).remove_child(&row.wrapper);
+        }
+
+        self.rows = rows;
+        self.place_rows(&old_order);
+        self.sync_selection();
+    }
+
+    /// Each row's info hash and the section it is in.
+    fn placement(&self) -> Vec<(InfoHash, Option<Stage>)> {
+        self.rows
+            .iter()
+            .map(|r| (r.hash_string.clone(), r.section))
+            .collect()
+    }
+
+    /// Put every row in its section (or the flat table) in order, given
+    /// where the rows were before.
+    ///
+    /// Reorders minimally, see [`settled_rows`].
+    fn place_rows(&mut self, old_order: &[(InfoHash, Option<Stage>)]) {
+        let is_grouped = self.sections.is_grouped();
+        for row in self.rows.iter_mut() {
+            row.section = is_grouped.then_some(row.stage);
+        }
+        let sections = if is_grouped {
+            Stage::ALL.map(Some).to_vec()
+        } else {
+            vec![None]
+        };
+        for section in sections {
+            let in_section = || self.rows.iter().filter(|r| r.section == section);
+            let new = in_section().map(|r| &r.hash_string).collect::<Vec<_>>();
+            let old = old_order
                 .iter()
-                .zip(torrents.iter())
-                .any(|(r, t)| r.torrent_id != t.id);
+                .filter(|(_, old)| *old == section)
+                .map(|(hash, _)| hash)
+                .collect::<Vec<_>>();
+            let settled = settled_rows(&old, &new);
+            let parent = self.rows_parent(section);
+            for row in in_section().skip(settled) {
+                parent.append_child(&row.wrapper);
+            }
+        }
+        self.sections.set_counts(self.rows.iter().map(|r| r.stage));
+        self.apply_filter();
+        if is_grouped {
+            self.tbody.set_style("display", "none");
+        } else {
+            self.tbody.remove_style("display");
+        }
+    }
 
-        if needs_rebuild {
-            // Remove old rows
-            for row in self.rows.drain(..) {
-                self.tbody.remove_child(&row.wrapper);
+    /// Show only the rows in `stage`, or every row.
+    fn set_filter(&mut self, stage: Option<Stage>) {
+        self.filter = stage;
+        match stage {
+            Some(stage) => {
+                self.filter_text
+                    .set_text(format!("Showing only {}", stage.label()));
+                self.filter_chip.remove_style("display");
             }
-            // Build new rows
-            for t in torrents {
-                let row = TorrentRow::<V>::new(t);
-                self.tbody.append_child(&row.wrapper);
-                self.rows.push(row);
+            None => self.filter_chip.set_style("display", "none"),
+        }
+        self.apply_filter();
+    }
+
+    /// Show only the completed downloads without a destination.
+    pub fn show_unassigned(&mut self) {
+        self.set_filter(Some(Stage::Unassigned));
+    }
+
+    /// Hide the rows the filter leaves out.
+    fn apply_filter(&self) {
+        for row in self.rows.iter() {
+            if self.filter.is_none_or(|stage| stage == row.stage) {
+                row.wrapper.remove_style("display");
+            } else {
+                row.wrapper.set_style("display", "none");
             }
+        }
+    }
+
+    /// Info hashes of the selected rows.
+    fn selected_hashes(&self) -> Vec<InfoHash> {
+        self.rows
+            .iter()
+            .filter(|r| r.is_selected)
+            .map(|r| r.hash_string.clone())
+            .collect()
+    }
+
+    /// Update the select-all checkbox and the action bar to match the
+    /// selected rows.
+    fn sync_selection(&self) {
+        let selected = self.rows.iter().filter(|r| r.is_selected).count();
+        let all = selected > 0 && selected == self.rows.len();
+        let some = selected > 0 && !all;
+        self.select_all_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_checked(all);
+                input.set_indeterminate(some);
+            });
+        if selected == 0 {
+            self.action_bar.set_style("display", "none");
         } else {
-            // Just update existing rows
-            for (row, t) in self.rows.iter_mut().zip(torrents.iter()) {
-                row.update(t);
-            }
+            self.selection_text.set_text(format!("{selected} selected"));
+            self.action_bar.remove_style("display");
+        }
+    }
+
+    /// Offer to run `command` again if `error` says that might help, or hide
+    /// the Retry button.
+    fn offer_retry(&mut self, command: FailedCommand, error: Option<&AppError>) {
+        self.retry = error.filter(|e| e.retryable).map(|_| command);
+        if self.retry.is_some() {
+            self.retry_button.remove_style("display");
+        } else {
+            self.retry_button.set_style("display", "none");
         }
     }
 
     /// Poll once: fetch torrents and update the view.
-    pub async fn poll(&mut self) {
-        match get_torrents().await {
+    ///
+    /// The backend may answer from a short-lived cache unless `force` is
+    /// set, which is needed right after an action changes the torrents.
+    pub async fn poll(&mut self, force: bool) {
+        let update = get_torrents(force).await;
+        self.show_torrents(update);
+        self.poll_missing().await;
+        self.poll_usage().await;
+        self.poll_copying_paused().await;
+        self.poll_capabilities().await;
+    }
+
+    /// Keep the rows' rename buttons in line with what the client can do.
+    async fn poll_capabilities(&mut self) {
+        let can_rename = match get_daemon_info().await {
+            Ok(daemon) => daemon.is_none_or(|d| d.capabilities.rename_path),
+            Err(e) => {
+                log::error!("Failed to get the download client's version: {e}");
+                return;
+            }
+        };
+        if can_rename != self.can_rename {
+            self.can_rename = can_rename;
+            for row in &self.rows {
+                row.set_can_rename(can_rename);
+            }
+        }
+    }
+
+    /// Show the "Copying paused" strip, or the button to pause copying.
+    fn show_copying_paused(&self, paused: bool) {
+        if paused {
+            self.paused_strip.remove_style("display");
+            self.pause_copying_button.set_style("display", "none");
+        } else {
+            self.paused_strip.set_style("display", "none");
+            self.pause_copying_button.remove_style("display");
+        }
+    }
+
+    async fn poll_copying_paused(&self) {
+        match is_copying_paused().await {
+            Ok(paused) => self.show_copying_paused(paused),
+            Err(e) => log::error!("Failed to check whether copying is paused: {e}"),
+        }
+    }
+
+    /// Pause or resume copying, reporting a failure in the status alert.
+    async fn pause_or_resume_copying(&mut self, paused: bool) {
+        match set_copying_paused(paused).await {
+            Ok(()) => self.show_copying_paused(paused),
+            Err(e) => {
+                let verb = if paused { "pause" } else { "resume" };
+                log::error!("Failed to {verb} copying: {e}");
+                self.status_alert
+                    .set_text(format!("Could not {verb} copying: {e}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+            }
+        }
+    }
+
+    /// Check the destinations' disk usage every [`USAGE_EVERY_POLLS`]
+    /// polls; reading it is cheap, but not worth doing every few seconds.
+    async fn poll_usage(&mut self) {
+        if self.polls_until_usage == 0 {
+            self.usage.refresh().await;
+            self.polls_until_usage = USAGE_EVERY_POLLS;
+        }
+        self.polls_until_usage -= 1;
+    }
+
+    /// Fetch the ledger entries Transmission no longer has, and those
+    /// removed lately, and list them.
+    async fn poll_missing(&mut self) {
+        match missing::get_missing_torrents().await {
+            Ok(entries) => self.missing.set_entries(&entries),
+            Err(e) => {
+                log::error!("Failed to get missing torrents: {e}");
+                self.missing.set_entries(&[]);
+            }
+        }
+        match removed::get_removed_downloads().await {
+            Ok(removed) => self.removed.set_entries(&removed, unix_now()),
+            Err(e) => {
+                log::error!("Failed to get removed downloads: {e}");
+                self.removed.set_entries(&[], unix_now());
+            }
+        }
+    }
+
+    /// After removing or restoring a ledger entry, say if `what` failed and
+    /// list the entries again.
+    async fn show_removal(&mut self, what: &str, result: Result<(), AppError>) {
+        if let Err(e) = result {
+            log::error!("{what} failed: {e}");
+            self.status_alert.set_text(format!("{what} failed: {e}"));
+            self.status_alert.set_flavor(Flavor::Danger);
+            self.status_alert.set_is_visible(true);
+        }
+        self.poll_missing().await;
+    }
+
+    /// Add the given ledger entries back to Transmission and report how
+    /// that went.
+    async fn readd(&mut self, hashes: Vec<InfoHash>) {
+        match missing::readd_missing_torrents(&hashes).await {
+            Ok(results) => {
+                self.poll(true).await;
+                self.missing.show_results(&results);
+                let failed = results.iter().filter(|r| r.error.is_some()).count();
+                let added = results.len() - failed;
+                let msg = match failed {
+                    0 => format!("Re-added {added} torrents to Transmission."),
+                    _ => format!(
+                        "Re-added {added} torrents to Transmission, {failed} failed \
+                         (see Missing from Transmission below)."
+                    ),
+                };
+                self.status_alert.set_text(msg);
+                self.status_alert.set_flavor(if failed == 0 {
+                    Flavor::Info
+                } else {
+                    Flavor::Warning
+                });
+                self.status_alert.set_is_visible(true);
+                self.offer_retry(FailedCommand::Readd(hashes), None);
+            }
+            Err(e) => {
+                log::error!("Failed to re-add torrents: {e}");
+                self.status_alert.set_text(format!("Re-adding failed: {e}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+                self.offer_retry(FailedCommand::Readd(hashes), Some(&e));
+            }
+        }
+    }
+
+    /// Reconcile the ledger with the libraries and report what changed.
+    async fn rescan(&mut self) {
+        match reconcile_now().await {
+            Ok(summary) => {
+                self.poll(true).await;
+                self.status_alert.set_text(summary.describe());
+                self.status_alert.set_flavor(if summary.errors.is_empty() {
+                    Flavor::Info
+                } else {
+                    Flavor::Warning
+                });
+                self.status_alert.set_is_visible(true);
+            }
+            Err(e) => {
+                log::error!("Failed to re-scan libraries: {e}");
+                self.status_alert.set_text(format!("Re-scan failed: {e}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+            }
+        }
+    }
+
+    /// Ask for destinations for the unassigned torrents and show them in
+    /// the auto-assign modal.
+    async fn preview_auto_assign(&mut self) {
+        self.auto_assign.start_preview();
+        let name_filter = self.auto_assign.name_filter();
+        let result = auto_assign::preview_auto_assign(name_filter).await;
+        self.auto_assign.show(result);
+    }
+
+    /// Record the confirmed destinations in one batch and report how that
+    /// went.
+    async fn auto_assign_all(&mut self, downloads: &[NewDownload]) {
+        match super::add_downloads(downloads).await {
+            Ok(results) => {
+                let message = match results.len() {
+                    1 => "Auto-assigned 1 torrent".to_string(),
+                    n => format!("Auto-assigned {n} torrents"),
+                };
+                log::info!("{message}");
+                self.poll(false).await;
+                self.status_alert.set_text(message);
+                self.status_alert.set_flavor(Flavor::Info);
+                self.status_alert.set_is_visible(true);
+            }
+            Err(e) => {
+                log::error!("Failed to auto-assign torrents: {e}");
+                self.status_alert
+                    .set_text(format!("Auto-assign failed: {e}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+            }
+        }
+    }
+
+    /// Save the downloads list where the user picks and report how that
+    /// went.
+    async fn export(&mut self) {
+        match save_downloads_export().await {
+            Ok(Some(path)) => {
+                self.status_alert.set_text(format!("Exported downloads to {path}"));
+                self.status_alert.set_flavor(Flavor::Info);
+                self.status_alert.set_is_visible(true);
+                self.offer_retry(FailedCommand::Export, None);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Failed to export downloads: {e}");
+                self.status_alert.set_text(format!("Export failed: {e}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+                self.offer_retry(FailedCommand::Export, Some(&e));
+            }
+        }
+    }
+
+    /// Wait for the next torrent list pushed by the backend.
+    async fn next_update(&self) -> TorrentsUpdate {
+        match self.updates.recv().await {
+            Ok(update) => update,
+            // The subscription never closes on the web; elsewhere there are
+            // no events at all.
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// Show a fetched torrent list, or the error fetching it.
+    fn show_torrents(&mut self, update: TorrentsUpdate) {
+        match update {
             Ok(torrents) => {
                 if torrents.is_empty() {
                     self.status_alert
@@ -286,114 +2105,603 @@ impl<V: View> DownloadsView<V> {
                     self.status_alert.set_is_visible(true);
                     self.table_wrapper.set_style("display", "none");
                 } else {
-                    self.status_alert.set_is_visible(false);
+                    // Stalled and unconfigured downloads need attention,
+                    // quiet hours are only worth knowing about
+                    let warnings = [stalled_message(&torrents), unconfigured_message(&torrents)]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    let flavor = if warnings.is_empty() {
+                        Flavor::Info
+                    } else {
+                        Flavor::Warning
+                    };
+                    let notes = warnings
+                        .into_iter()
+                        .chain(quiet_hours_message(&torrents))
+                        .collect::<Vec<_>>();
+                    if notes.is_empty() {
+                        self.status_alert.set_is_visible(false);
+                    } else {
+                        self.status_alert.set_text(notes.join(" "));
+                        self.status_alert.set_flavor(flavor);
+                        self.status_alert.set_is_visible(true);
+                    }
                     self.table_wrapper.set_style("display", "block");
                     self.update_torrents(&torrents);
                 }
+                self.offer_retry(FailedCommand::Poll, None);
             }
             Err(e) => {
                 let msg = match e.kind {
-                    ErrorKind::TransmissionConnection => format!(
+                    ErrorKind::TransmissionConnection | ErrorKind::Timeout => format!(
                         "Could not connect to Transmission: {}. \
                          Make sure Transmission is running and remote access \
                          is enabled in Preferences > Remote.",
                         e.message
                     ),
+                    ErrorKind::TransmissionAuth => {
+                        "Authentication failed \u{2014} check username/password in Settings."
+                            .to_string()
+                    }
                     _ => e.to_string(),
                 };
                 self.status_alert.set_text(msg);
                 self.status_alert.set_flavor(Flavor::Danger);
                 self.status_alert.set_is_visible(true);
                 self.table_wrapper.set_style("display", "none");
+                self.offer_retry(FailedCommand::Poll, Some(&e));
             }
         }
     }
 
-    /// Build a future that resolves when any assign button is clicked.
+    /// Build a future that resolves when any row's controls are clicked,
+    /// with the index of that row.
     ///
     /// `EventListener::next()` takes `&self` and returns a cloned future,
     /// so we can safely race listeners from multiple rows without borrow
     /// conflicts.
-    async fn wait_for_assign(&self) -> AssignEvent {
+    async fn wait_for_row(&self) -> (usize, RowAction) {
         if self.rows.is_empty() {
             // No rows — never resolve so the caller's .or() picks the
-            // other branch (timeout).
+            // other branches.
             return std::future::pending().await;
         }
 
         let futures: Vec<_> = self
             .rows
             .iter()
-            .flat_map(|row| {
-                let hash = row.hash_string.clone();
-                let name = row.torrent_name.clone();
-                let hash2 = hash.clone();
-                let name2 = name.clone();
-
-                let movies_fut = row.on_click_movies.next().map(move |_| AssignEvent {
-                    hash_string: hash,
-                    name,
-                    destination: Destination::Movies,
-                });
-                let shows_fut = row.on_click_shows.next().map(move |_| AssignEvent {
-                    hash_string: hash2,
-                    name: name2,
-                    destination: Destination::Shows,
-                });
+            .enumerate()
+            .map(|(i, row)| row.step().map(move |action| (i, action)).boxed_local())
+            .collect();
+
+        mogwai::future::race_all(futures).await
+    }
+
+    /// Wait for a click in the batch action bar or the select-all checkbox.
+    async fn wait_for_batch(&self) -> BatchAction {
+        self.batch_assign
+            .step()
+            .map(BatchAction::Assign)
+            .or(self.on_click_select_all.next().map(|_| BatchAction::SelectAll))
+            .or(self.on_click_pause.next().map(|_| BatchAction::Pause))
+            .or(self.on_click_resume.next().map(|_| BatchAction::Resume))
+            .or(self.on_click_retry.next().map(|_| BatchAction::Retry))
+            .or(self.on_click_copy_now.next().map(|_| BatchAction::CopyNow))
+            .or(self.on_click_remove.next().map(|_| BatchAction::Remove))
+            .await
+    }
+
+    /// The assign dropdown of row `index`, or the batch dropdown for `None`.
+    fn assign_dropdown_mut(&mut self, index: Option<usize>) -> &mut AssignDropdown<V> {
+        match index {
+            Some(i) => &mut self.rows[i].assign,
+            None => &mut self.batch_assign,
+        }
+    }
+
+    /// Close every open assign menu except the one given by `keep`.
+    fn hide_assign_menus(&mut self, keep: Option<Option<usize>>) {
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if keep != Some(Some(i)) {
+                row.assign.hide_menu();
+            }
+        }
+        if keep != Some(None) {
+            self.batch_assign.hide_menu();
+        }
+    }
+
+    /// Call `add_download` for the row's torrent.
+    async fn assign(&self, index: usize, destination: Destination) {
+        let row = &self.rows[index];
+        match super::add_download(&row.hash_string, &row.torrent_name, destination).await {
+            Ok(()) => {
+                log::info!(
+                    "Assigned '{}' to {}",
+                    row.torrent_name,
+                    destination.label()
+                );
+            }
+            Err(e) => {
+                log::error!("Failed to assign download: {e}");
+            }
+        }
+    }
 
-                [movies_fut.boxed_local(), shows_fut.boxed_local()]
+    /// Call `add_downloads` for every selected torrent.
+    async fn assign_selected(&self, destination: Destination) {
+        let downloads: Vec<NewDownload> = self
+            .rows
+            .iter()
+            .filter(|r| r.is_selected)
+            .map(|r| NewDownload {
+                info_hash: r.hash_string.clone(),
+                name: r.torrent_name.clone(),
+                destination,
+                extra_destinations: vec![],
+                extra_dirs: vec![],
             })
             .collect();
+        match super::add_downloads(&downloads).await {
+            Ok(results) => {
+                let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+                log::info!(
+                    "Assigned {} torrents to {}: {} added, {} updated, {} unchanged",
+                    results.len(),
+                    destination.label(),
+                    count(AddOutcome::Added),
+                    count(AddOutcome::Updated),
+                    count(AddOutcome::Unchanged)
+                );
+            }
+            Err(e) => {
+                log::error!("Failed to assign downloads: {e}");
+            }
+        }
+    }
 
-        mogwai::future::race_all(futures).await
+    /// Handle a click in an assign dropdown (`None` is the batch dropdown).
+    ///
+    /// Returns `true` when a destination was confirmed and recorded.
+    async fn handle_assign(&mut self, index: Option<usize>, action: AssignAction) -> bool {
+        match action {
+            AssignAction::Toggle => {
+                self.hide_assign_menus(Some(index));
+                self.assign_dropdown_mut(index).toggle_menu();
+            }
+            AssignAction::Pick(destination) => {
+                let dropdown = self.assign_dropdown_mut(index);
+                dropdown.hide_menu();
+                dropdown.set_pending(Some(destination));
+            }
+            AssignAction::Cancel => {
+                self.assign_dropdown_mut(index).set_pending(None);
+            }
+            AssignAction::Confirm => {
+                let dropdown = self.assign_dropdown_mut(index);
+                let pending = dropdown.pending_destination;
+                dropdown.set_pending(None);
+                if let Some(destination) = pending {
+                    match index {
+                        Some(i) => self.assign(i, destination).await,
+                        None => self.assign_selected(destination).await,
+                    }
+                    return true;
+                }
+            }
+        }
+        false
     }
 
-    /// Run one poll cycle, then wait for the next tick.
-    /// While waiting, also listen for assign button clicks. If a button is
-    /// clicked, record the download and re-poll immediately.
-    /// Returns after one tick so the caller can race with tab switches.
-    pub async fn step(&mut self) {
-        // Poll first
-        self.poll().await;
+    /// Wait for the next torrent update pushed by the backend and show it.
+    /// While waiting, also handle the row controls and batch actions. If a
+    /// destination is confirmed, a batch action runs or "Refresh" is
+    /// clicked, poll immediately.
+    /// Returns after each update so the caller can race with tab switches,
+    /// and where to go if the user asked to leave the view.
+    pub async fn step(&mut self) -> Option<DownloadsNav> {
+        if !self.has_loaded {
+            self.poll(false).await;
+            self.has_loaded = true;
+        }
 
-        // Now race the 3-second timer against assign button clicks
+        // Race backend updates against clicks in the view
         enum WaitResult {
-            Timeout,
-            Assign(AssignEvent),
+            Update(TorrentsUpdate),
+            Refresh,
+            /// "Re-scan libraries" was clicked.
+            Rescan,
+            Export,
+            /// "Auto-assign…" was clicked.
+            OpenAutoAssign,
+            AutoAssign(AutoAssignAction),
+            /// Retry was clicked under a failure in the status alert.
+            RetryFailed,
+            Row(usize, RowAction),
+            Batch(BatchAction),
+            Missing(MissingAction),
+            /// A removed entry's "Restore" was clicked.
+            Restore(InfoHash),
+            /// The files modal was closed.
+            CloseLocation,
+            Columns(ColumnAction),
+            /// The usage header was clicked.
+            ToggleUsage,
+            Sections(SectionAction),
+            /// "Pause copying" (`true`) or its "Resume" (`false`) was
+            /// clicked.
+            PauseCopying(bool),
+            /// The window became narrow (`true`) or wide again (`false`).
+            Narrow(bool),
+            /// The filter's clear button was clicked.
+            ClearFilter,
+            /// A click somewhere in the view, and whether it landed inside
+            /// an assign dropdown or the column chooser.
+            ClickView { in_dropdown: bool, in_chooser: bool },
         }
 
-        let result = async {
-            mogwai::time::wait_millis(3000).await;
-            WaitResult::Timeout
-        }
-        .or(async { WaitResult::Assign(self.wait_for_assign().await) })
-        .await;
+        loop {
+            let result = self
+                .next_update()
+                .map(WaitResult::Update)
+                .or(self.on_click_refresh.next().map(|_| WaitResult::Refresh))
+                .or(self.on_click_rescan.next().map(|_| WaitResult::Rescan))
+                .or(self.on_click_export.next().map(|_| WaitResult::Export))
+                .or(self
+                    .on_click_auto_assign
+                    .next()
+                    .map(|_| WaitResult::OpenAutoAssign))
+                .or(self.auto_assign.step().map(WaitResult::AutoAssign))
+                .or(self
+                    .on_click_retry_failed
+                    .next()
+                    .map(|_| WaitResult::RetryFailed))
+                .or(async {
+                    let (i, action) = self.wait_for_row().await;
+                    WaitResult::Row(i, action)
+                })
+                .or(self.wait_for_batch().map(WaitResult::Batch))
+                .or(self.missing.step().map(WaitResult::Missing))
+                .or(self.removed.step().map(WaitResult::Restore))
+                .or(self.location.step().map(|_| WaitResult::CloseLocation))
+                .or(self.column_chooser.step().map(WaitResult::Columns))
+                .or(self.usage.step().map(|_| WaitResult::ToggleUsage))
+                .or(self.sections.step().map(WaitResult::Sections))
+                .or(self
+                    .on_click_pause_copying
+                    .next()
+                    .map(|_| WaitResult::PauseCopying(true)))
+                .or(self
+                    .on_click_resume_copying
+                    .next()
+                    .map(|_| WaitResult::PauseCopying(false)))
+                .or(self.next_narrow_change().map(WaitResult::Narrow))
+                .or(self
+                    .on_click_clear_filter
+                    .next()
+                    .map(|_| WaitResult::ClearFilter))
+                .or(self.on_click_view.next().map(|ev| WaitResult::ClickView {
+                    in_dropdown: is_inside::<V>(&ev, ".assign-dropdown"),
+                    in_chooser: is_inside::<V>(&ev, ".column-chooser"),
+                }))
+                .await;
 
-        match result {
-            WaitResult::Timeout => {}
-            WaitResult::Assign(event) => {
-                // Call add_download, then re-poll immediately
-                match super::add_download(
-                    &event.hash_string,
-                    &event.name,
-                    event.destination,
-                )
-                .await
-                {
-                    Ok(()) => {
-                        log::info!(
-                            "Assigned '{}' to {}",
-                            event.name,
-                            event.destination.label()
-                        );
+            match result {
+                WaitResult::Update(update) => {
+                    self.show_torrents(update);
+                    self.poll_missing().await;
+                    self.poll_usage().await;
+                    self.poll_copying_paused().await;
+                    break;
+                }
+                WaitResult::Refresh => {
+                    self.poll(true).await;
+                    break;
+                }
+                WaitResult::Rescan => self.rescan().await,
+                WaitResult::Export => self.export().await,
+                WaitResult::OpenAutoAssign | WaitResult::AutoAssign(AutoAssignAction::Preview) => {
+                    self.preview_auto_assign().await
+                }
+                WaitResult::AutoAssign(AutoAssignAction::Assign) => {
+                    let downloads = self.auto_assign.close();
+                    self.auto_assign_all(&downloads).await;
+                    break;
+                }
+                WaitResult::AutoAssign(AutoAssignAction::Close) => {
+                    self.auto_assign.close();
+                }
+                WaitResult::RetryFailed => match self.retry.take() {
+                    Some(FailedCommand::Poll) => {
+                        self.poll(true).await;
+                        break;
+                    }
+                    Some(FailedCommand::Export) => self.export().await,
+                    Some(FailedCommand::Readd(hashes)) => self.readd(hashes).await,
+                    None => {}
+                },
+                WaitResult::Missing(MissingAction::Remove(hash)) => {
+                    let result = missing::remove_download(&hash).await;
+                    self.show_removal("Removing from the ledger", result).await;
+                }
+                // Keep waiting after re-adding so the results stay up until
+                // the next update
+                WaitResult::Missing(MissingAction::Readd(hash)) => self.readd(vec![hash]).await,
+                WaitResult::Missing(MissingAction::ReaddAll) => {
+                    let hashes = self.missing.hashes();
+                    self.readd(hashes).await;
+                }
+                WaitResult::Restore(hash) => {
+                    let result = removed::restore_download(&hash).await;
+                    self.show_removal("Restoring to the ledger", result).await;
+                }
+                WaitResult::CloseLocation => {}
+                WaitResult::ClickView {
+                    in_dropdown,
+                    in_chooser,
+                } => {
+                    // Clicks inside a menu are handled by the menu itself
+                    if !in_dropdown {
+                        self.hide_assign_menus(None);
+                    }
+                    if !in_chooser {
+                        self.column_chooser.hide_menu();
+                    }
+                }
+                WaitResult::ToggleUsage => {
+                    self.usage.toggle();
+                    self.save_ui_state();
+                }
+                WaitResult::Sections(SectionAction::ToggleGrouped) => {
+                    let old_order = self.placement();
+                    self.sections.toggle_grouped();
+                    self.place_rows(&old_order);
+                    self.save_ui_state();
+                }
+                WaitResult::Sections(SectionAction::ToggleSection(stage)) => {
+                    self.sections.toggle_section(stage);
+                    self.save_ui_state();
+                }
+                WaitResult::PauseCopying(paused) => self.pause_or_resume_copying(paused).await,
+                WaitResult::Narrow(is_narrow) => self.set_narrow(is_narrow),
+                WaitResult::ClearFilter => self.set_filter(None),
+                WaitResult::Columns(ColumnAction::Toggle) => {
+                    self.column_chooser.toggle_menu();
+                }
+                WaitResult::Columns(ColumnAction::ToggleColumn(column)) => {
+                    self.toggle_column(column);
+                }
+                WaitResult::Columns(ColumnAction::ResetView) => {
+                    self.column_chooser.hide_menu();
+                    self.reset_view();
+                }
+                WaitResult::Row(i, RowAction::Select) => {
+                    self.rows[i].read_selected();
+                    self.sync_selection();
+                }
+                WaitResult::Row(i, RowAction::Repair) => {
+                    self.rows[i].set_status_note("Repairing\u{2026}");
+                    match repair_torrent(&self.rows[i].hash_string).await {
+                        Ok(summary) => {
+                            log::info!(
+                                "Repaired '{}': {}",
+                                self.rows[i].torrent_name,
+                                summary.describe()
+                            );
+                            self.rows[i]
+                                .set_status_note(&format!("Fixed: {}", summary.describe()));
+                        }
+                        Err(e) => {
+                            log::error!("Failed to repair torrent: {e}");
+                            self.rows[i].set_status_note(&format!("Fix failed: {e}"));
+                        }
+                    }
+                    // Keep waiting so the note stays up until the next update
+                }
+                WaitResult::Row(i, RowAction::ToggleDetail) => self.rows[i].toggle_detail(),
+                WaitResult::Row(i, RowAction::ClickCopied) => {
+                    if self.rows[i].is_unconfigured {
+                        return Some(DownloadsNav::Settings);
+                    }
+                    if self.rows[i].is_copy_pending {
+                        let hash = self.rows[i].hash_string.clone();
+                        if let Err(e) = copy_now(&[hash]).await {
+                            log::error!("Failed to copy now: {e}");
+                        }
+                        self.poll(false).await;
+                        break;
+                    }
+                }
+                WaitResult::Row(i, RowAction::FindAnother) => {
+                    if let Some(query) = self.rows[i].search_query.clone() {
+                        return Some(DownloadsNav::Search(query));
+                    }
+                }
+                WaitResult::Row(i, RowAction::FindReplacement) => {
+                    let row = &self.rows[i];
+                    if let Some(query) = row.search_query.clone() {
+                        return Some(DownloadsNav::Replace(Replacing {
+                            info_hash: row.hash_string.clone(),
+                            name: row.torrent_name.clone(),
+                            query,
+                        }));
+                    }
+                }
+                WaitResult::Row(i, RowAction::Locate) => {
+                    self.location.open(&self.rows[i].torrent_name);
+                    let result = location::locate_download(&self.rows[i].hash_string).await;
+                    self.location.show(result);
+                }
+                WaitResult::Row(i, RowAction::Rename(RenameAction::Edit)) => {
+                    self.rows[i].start_rename();
+                }
+                WaitResult::Row(i, RowAction::Rename(RenameAction::Cancel)) => {
+                    self.rows[i].stop_rename();
+                }
+                WaitResult::Row(i, RowAction::Rename(RenameAction::Save)) => {
+                    let row = &self.rows[i];
+                    let new_name = row.new_name().trim().to_string();
+                    if new_name.is_empty() || new_name == row.torrent_name {
+                        self.rows[i].stop_rename();
+                        continue;
+                    }
+                    match rename_torrent(&row.hash_string, &row.torrent_name, &new_name).await {
+                        Ok(()) => {
+                            log::info!("Renamed '{}' to '{new_name}'", row.torrent_name);
+                            self.rows[i].stop_rename();
+                            self.poll(true).await;
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Failed to rename torrent: {e}");
+                            row.rename_error_text.set_text(e.to_string());
+                        }
+                    }
+                }
+                WaitResult::Row(i, RowAction::Note(NoteAction::Edit)) => {
+                    self.rows[i].start_note();
+                }
+                WaitResult::Row(i, RowAction::Note(NoteAction::Cancel)) => {
+                    self.rows[i].stop_note();
+                }
+                WaitResult::Row(i, RowAction::Note(NoteAction::Save)) => {
+                    let row = &self.rows[i];
+                    match update_download_note(&row.hash_string, &row.typed_note()).await {
+                        Ok(()) => {
+                            self.rows[i].stop_note();
+                            // Only the ledger changed
+                            self.poll(false).await;
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Failed to save the note: {e}");
+                            row.note_error_text.set_text(e.to_string());
+                        }
+                    }
+                }
+                WaitResult::Row(i, RowAction::Assign(action)) => {
+                    if self.handle_assign(Some(i), action).await {
+                        // Re-poll to update the UI immediately.  Only the
+                        // ledger changed, so the cached torrent list is fine.
+                        self.poll(false).await;
+                        break;
+                    }
+                }
+                WaitResult::Batch(BatchAction::Assign(action)) => {
+                    if self.handle_assign(None, action).await {
+                        self.poll(false).await;
+                        break;
+                    }
+                }
+                WaitResult::Batch(BatchAction::SelectAll) => {
+                    let all = self
+                        .select_all_input
+                        .dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+                        .unwrap_or(false);
+                    for row in self.rows.iter_mut() {
+                        row.set_selected(all);
+                    }
+                    self.sync_selection();
+                }
+                WaitResult::Batch(BatchAction::Pause) => {
+                    if let Err(e) = pause_torrents(&self.selected_hashes()).await {
+                        log::error!("Failed to pause torrents: {e}");
+                    }
+                    self.poll(true).await;
+                    break;
+                }
+                WaitResult::Batch(BatchAction::Resume) => {
+                    if let Err(e) = resume_torrents(&self.selected_hashes()).await {
+                        log::error!("Failed to resume torrents: {e}");
+                    }
+                    self.poll(true).await;
+                    break;
+                }
+                WaitResult::Batch(BatchAction::Retry) => {
+                    if let Err(e) = retry_copies(&self.selected_hashes()).await {
+                        log::error!("Failed to retry copies: {e}");
                     }
-                    Err(e) => {
-                        log::error!("Failed to assign download: {e}");
+                    self.poll(false).await;
+                    break;
+                }
+                WaitResult::Batch(BatchAction::CopyNow) => {
+                    if let Err(e) = copy_now(&self.selected_hashes()).await {
+                        log::error!("Failed to copy now: {e}");
                     }
+                    self.poll(false).await;
+                    break;
+                }
+                WaitResult::Batch(BatchAction::Remove) => {
+                    let hashes = self.selected_hashes();
+                    let label = match hashes.len() {
+                        1 => "Removing 1 download".to_string(),
+                        n => format!("Removing {n} downloads"),
+                    };
+                    // The list updates itself once the client has them gone
+                    pending::enqueue(PendingAction::new(label, move |confirm| {
+                        remove_torrents(hashes, confirm)
+                    }));
                 }
-                // Re-poll to update the UI immediately
-                self.poll().await;
             }
         }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(status: TransmissionStatus) -> TransmissionTorrent {
+        TransmissionTorrent {
+            status,
+            percent_done: 1.0,
+            recheck_progress: 0.25,
+            ..TransmissionTorrent::new("a".repeat(40).parse().unwrap())
+        }
+    }
+
+    #[test]
+    fn verifying_rows_show_the_verification() {
+        let verifying = torrent(TransmissionStatus::Verifying);
+        assert_eq!(0.25, progress_fraction(&verifying));
+        assert_eq!("info", progress_flavor(&verifying).to_string());
+
+        let queued = torrent(TransmissionStatus::QueuedVerify);
+        assert_eq!(1.0, progress_fraction(&queued));
+        assert_eq!("info", progress_flavor(&queued).to_string());
+
+        let seeding = torrent(TransmissionStatus::Seeding);
+        assert_eq!(1.0, progress_fraction(&seeding));
+        assert_eq!("success", progress_flavor(&seeding).to_string());
+    }
+
+    #[test]
+    fn unchanged_rows_stay_put() {
+        assert_eq!(3, settled_rows(&["a", "b", "c"], &["a", "b", "c"]));
+        assert_eq!(0, settled_rows::<&str>(&[], &[]));
+    }
+
+    #[test]
+    fn new_rows_are_appended_after_the_settled_ones() {
+        assert_eq!(2, settled_rows(&["a", "b"], &["a", "b", "c"]));
+        // Anything after a new row has to move behind it
+        assert_eq!(1, settled_rows(&["a", "b"], &["a", "c", "b"]));
+        assert_eq!(0, settled_rows(&["a", "b"], &["c", "a", "b"]));
+    }
+
+    #[test]
+    fn removed_rows_leave_the_rest_in_place() {
+        assert_eq!(2, settled_rows(&["a", "b", "c"], &["a", "c"]));
+        assert_eq!(2, settled_rows(&["a", "b", "c"], &["b", "c"]));
+        assert_eq!(0, settled_rows(&["a", "b"], &[]));
+    }
+
+    #[test]
+    fn rows_after_a_reordering_are_appended() {
+        let old = ["a", "b", "c", "d"];
+        assert_eq!(1, settled_rows(&old, &["a", "c", "b", "d"]));
+        assert_eq!(0, settled_rows(&["a", "b"], &["b", "a"]));
     }
 }
@@ -0,0 +1,244 @@
+//! "Auto-assign…": destinations for the unassigned torrents, proposed by the
+//! backend's rules and only recorded once the user confirms them.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{AppError, AutoAssignArgs, AutoAssignPreview, NewDownload};
+
+use crate::app::invoke;
+
+pub async fn preview_auto_assign(
+    name_filter: Option<String>,
+) -> Result<AutoAssignPreview, AppError> {
+    invoke::cmd("preview_auto_assign", &AutoAssignArgs { name_filter }).await
+}
+
+/// Event from the auto-assign modal.
+pub enum AutoAssignAction {
+    /// "Preview" was clicked, to look again with the name filter.
+    Preview,
+    /// "Assign" was clicked.
+    Assign,
+    /// The modal was closed without assigning anything.
+    Close,
+}
+
+/// "1 torrent" or "N torrents".
+fn torrents(n: usize) -> String {
+    match n {
+        1 => "1 torrent".to_string(),
+        n => format!("{n} torrents"),
+    }
+}
+
+/// The proposals as a table, followed by the torrents left unassigned.
+fn report<V: View>(preview: &AutoAssignPreview) -> V::Element {
+    let summary = match (preview.assignments.len(), preview.ambiguous.len()) {
+        (0, 0) => "There are no unassigned torrents.".to_string(),
+        (assigned, 0) => format!("{} can be assigned.", torrents(assigned)),
+        (assigned, left) => format!(
+            "{} can be assigned. {} left unassigned, as the rules can't tell where \
+             they go.",
+            torrents(assigned),
+            torrents(left)
+        ),
+    };
+    rsx! {
+        let content = div() {
+            p(class = "small text-body-secondary") { {summary.into_text::<V>()} }
+            let table = table(class = "table table-sm mb-0", style:display = "none") {
+                thead() {
+                    tr() {
+                        th() { "Name" }
+                        th() { "Dest" }
+                    }
+                }
+                let tbody = tbody() {}
+            }
+        }
+    }
+    let assigned = preview
+        .assignments
+        .iter()
+        .map(|a| (a.name.as_str(), Some(a.destination)));
+    let ambiguous = preview.ambiguous.iter().map(|name| (name.as_str(), None));
+    for (name, destination) in assigned.chain(ambiguous) {
+        let (dest, class) = match destination {
+            Some(destination) => (destination.label(), ""),
+            None => ("\u{2014}", "text-body-secondary"),
+        };
+        rsx! {
+            let row = tr(class = class) {
+                td(class = "small text-break") { {name.into_text::<V>()} }
+                td() { {dest.into_text::<V>()} }
+            }
+        }
+        tbody.append_child(&row);
+        table.remove_style("display");
+    }
+    content
+}
+
+/// The proposals in a modal over the view, hidden until "Auto-assign…" is
+/// clicked.
+pub struct AutoAssignModal<V: View> {
+    pub wrapper: V::Element,
+    filter_input: V::Element,
+    on_click_preview: V::EventListener,
+    body: V::Element,
+    content: Option<V::Element>,
+    assign_button: V::Element,
+    assign_text: V::Text,
+    on_click_assign: V::EventListener,
+    on_click_close: V::EventListener,
+    on_click_cancel: V::EventListener,
+    /// The torrents "Assign" records.
+    assignments: Vec<NewDownload>,
+}
+
+impl<V: View> AutoAssignModal<V> {
+    pub fn new() -> Self {
+        rsx! {
+            let wrapper = div(style:display = "none") {
+                div(class = "modal-backdrop show"){}
+                div(class = "modal d-block", tabindex = "-1", role = "dialog") {
+                    div(class = "modal-dialog modal-lg modal-dialog-scrollable") {
+                        div(class = "modal-content") {
+                            div(class = "modal-header") {
+                                h5(class = "modal-title") { "Auto-assign unassigned torrents" }
+                                button(
+                                    class = "btn-close",
+                                    type = "button",
+                                    title = "Close",
+                                    on:click = on_click_close,
+                                ){}
+                            }
+                            div(class = "modal-body") {
+                                p(class = "small") {
+                                    "Names with an episode tag like S02E05 go to Shows, and \
+                                     names with a year but no episode tag go to Movies. \
+                                     Nothing is assigned until you click Assign."
+                                }
+                                div(class = "input-group input-group-sm mb-3") {
+                                    let filter_input = input(
+                                        class = "form-control",
+                                        type = "text",
+                                        placeholder = "Only names containing\u{2026}",
+                                    ){}
+                                    button(
+                                        class = "btn btn-outline-secondary",
+                                        type = "button",
+                                        on:click = on_click_preview,
+                                    ) { "Preview" }
+                                }
+                                let body = div() {}
+                            }
+                            div(class = "modal-footer") {
+                                button(
+                                    class = "btn btn-secondary",
+                                    type = "button",
+                                    on:click = on_click_cancel,
+                                ) { "Cancel" }
+                                let assign_button = button(
+                                    class = "btn btn-primary",
+                                    type = "button",
+                                    on:click = on_click_assign,
+                                ) { let assign_text = "Assign" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            filter_input,
+            on_click_preview,
+            body,
+            content: None,
+            assign_button,
+            assign_text,
+            on_click_assign,
+            on_click_close,
+            on_click_cancel,
+            assignments: vec![],
+        }
+    }
+
+    fn set_content(&mut self, content: V::Element) {
+        if let Some(old) = self.content.take() {
+            self.body.remove_child(&old);
+        }
+        self.body.append_child(&content);
+        self.content = Some(content);
+    }
+
+    /// Show "Assign N", disabled when there is nothing to assign.
+    fn set_assignments(&mut self, assignments: Vec<NewDownload>) {
+        self.assign_text.set_text(match assignments.len() {
+            0 => "Assign".to_string(),
+            n => format!("Assign {n}"),
+        });
+        let is_empty = assignments.is_empty();
+        self.assign_button
+            .dyn_el(|button: &web_sys::HtmlButtonElement| button.set_disabled(is_empty));
+        self.assignments = assignments;
+    }
+
+    /// The name filter, `None` if it is blank.
+    pub fn name_filter(&self) -> Option<String> {
+        let filter = self
+            .filter_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default();
+        let filter = filter.trim();
+        (!filter.is_empty()).then(|| filter.to_string())
+    }
+
+    /// Show that the proposals are being worked out, opening the modal if
+    /// it is closed.
+    pub fn start_preview(&mut self) {
+        rsx! {
+            let content = p(class = "text-body-secondary mb-0") { "Looking\u{2026}" }
+        }
+        self.set_content(content);
+        self.set_assignments(vec![]);
+        self.wrapper.remove_style("display");
+    }
+
+    /// Show the proposals, or why there are none.
+    pub fn show(&mut self, result: Result<AutoAssignPreview, AppError>) {
+        match result {
+            Ok(preview) => {
+                self.set_content(report::<V>(&preview));
+                self.set_assignments(preview.assignments);
+            }
+            Err(e) => {
+                let message = format!("Couldn't work out the destinations: {e}");
+                rsx! {
+                    let content = p(class = "text-danger mb-0") { {message.into_text::<V>()} }
+                }
+                self.set_content(content);
+            }
+        }
+    }
+
+    /// Hide the modal, handing back the torrents to assign.
+    pub fn close(&mut self) -> Vec<NewDownload> {
+        self.wrapper.set_style("display", "none");
+        std::mem::take(&mut self.assignments)
+    }
+
+    /// Wait for a click in the modal.
+    pub async fn step(&self) -> AutoAssignAction {
+        self.on_click_preview
+            .next()
+            .map(|_| AutoAssignAction::Preview)
+            .or(self
+                .on_click_assign
+                .next()
+                .map(|_| AutoAssignAction::Assign))
+            .or(self.on_click_close.next().map(|_| AutoAssignAction::Close))
+            .or(self.on_click_cancel.next().map(|_| AutoAssignAction::Close))
+            .await
+    }
+}
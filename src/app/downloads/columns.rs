@@ -0,0 +1,289 @@
+//! Column chooser for the Downloads table.
+//!
+//! Every column except the selection checkboxes and "Name" can be hidden.  The hidden set is part of
+//! the view's [`UiState`](super::ui_state::UiState), so the table keeps its shape across restarts.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+
+/// A column of the Downloads table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Column {
+    Select,
+    Name,
+    Progress,
+    Status,
+    Peers,
+    Ratio,
+    Size,
+    Added,
+    Dest,
+    Copied,
+}
+
+impl Column {
+    /// All columns, in table order.
+    pub const ALL: [Column; 10] = [
+        Column::Select,
+        Column::Name,
+        Column::Progress,
+        Column::Status,
+        Column::Peers,
+        Column::Ratio,
+        Column::Size,
+        Column::Added,
+        Column::Dest,
+        Column::Copied,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Select => "Select",
+            Column::Name => "Name",
+            Column::Progress => "Progress",
+            Column::Status => "Status",
+            Column::Peers => "Peers",
+            Column::Ratio => "Ratio",
+            Column::Size => "Size",
+            Column::Added => "Added",
+            Column::Dest => "Dest",
+            Column::Copied => "Copied",
+        }
+    }
+
+    /// Relative width, used to compute the colgroup percentages from the
+    /// visible columns.
+    fn weight(&self) -> u32 {
+        match self {
+            Column::Select => 3,
+            Column::Name => 28,
+            Column::Progress => 20,
+            Column::Status => 11,
+            Column::Peers => 8,
+            Column::Ratio => 7,
+            Column::Size => 10,
+            Column::Added => 9,
+            Column::Dest => 10,
+            Column::Copied => 21,
+        }
+    }
+
+    /// Whether the user may hide this column.
+    fn is_hideable(&self) -> bool {
+        !matches!(self, Column::Select | Column::Name)
+    }
+
+    /// Whether a narrow window hides the column, leaving its value to the
+    /// row detail.
+    fn is_low_priority(&self) -> bool {
+        matches!(self, Column::Peers | Column::Added | Column::Dest)
+    }
+}
+
+/// The set of columns the user has hidden.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HiddenColumns(Vec<Column>);
+
+impl FromIterator<Column> for HiddenColumns {
+    fn from_iter<I: IntoIterator<Item = Column>>(columns: I) -> Self {
+        let mut hidden = Self::default();
+        for column in columns {
+            if column.is_hideable() && !hidden.is_hidden(column) {
+                hidden.0.push(column);
+            }
+        }
+        hidden
+    }
+}
+
+impl HiddenColumns {
+    pub fn is_hidden(&self, column: Column) -> bool {
+        self.0.contains(&column)
+    }
+
+    /// CSS `display` value for a cell in `column`.
+    pub fn display(&self, column: Column) -> &'static str {
+        if self.is_hidden(column) {
+            "none"
+        } else {
+            ""
+        }
+    }
+
+    pub fn toggle(&mut self, column: Column) {
+        if !column.is_hideable() {
+            return;
+        }
+        if self.is_hidden(column) {
+            self.0.retain(|c| *c != column);
+        } else {
+            self.0.push(column);
+        }
+    }
+
+    /// These columns and the low-priority ones, for a narrow window.
+    pub fn narrowed(&self) -> Self {
+        let mut hidden = self.clone();
+        for column in Column::ALL.into_iter().filter(|c| c.is_low_priority()) {
+            if !hidden.is_hidden(column) {
+                hidden.0.push(column);
+            }
+        }
+        hidden
+    }
+
+    /// Visible columns with their width as a percentage of the table.
+    pub fn widths(&self) -> Vec<(Column, f32)> {
+        let visible: Vec<Column> = Column::ALL
+            .into_iter()
+            .filter(|c| !self.is_hidden(*c))
+            .collect();
+        let total: u32 = visible.iter().map(|c| c.weight()).sum();
+        visible
+            .into_iter()
+            .map(|c| (c, c.weight() as f32 * 100.0 / total.max(1) as f32))
+            .collect()
+    }
+}
+
+/// Event from the column chooser.
+pub enum ColumnAction {
+    /// The gear button was clicked.
+    Toggle,
+    /// A column's checkbox was clicked.
+    ToggleColumn(Column),
+    /// "Reset view" was clicked.
+    ResetView,
+}
+
+/// A single checkbox in the chooser menu.
+struct ColumnCheckbox<V: View> {
+    column: Column,
+    input: V::Element,
+    on_click: V::EventListener,
+}
+
+/// Gear button with a checklist of the hideable columns.
+pub struct ColumnChooser<V: View> {
+    pub wrapper: V::Element,
+    on_click_toggle: V::EventListener,
+    on_click_reset: V::EventListener,
+    menu_open: Proxy<bool>,
+    is_menu_open: bool,
+    checkboxes: Vec<ColumnCheckbox<V>>,
+}
+
+impl<V: View> ColumnChooser<V> {
+    pub fn new(hidden: &HiddenColumns) -> Self {
+        let mut menu_open = Proxy::new(false);
+        rsx! {
+            let menu = ul(
+                class = menu_open(is_open => if *is_open {
+                    "dropdown-menu dropdown-menu-end show"
+                } else {
+                    "dropdown-menu dropdown-menu-end"
+                }),
+            ) {}
+        }
+
+        let mut checkboxes = vec![];
+        for column in Column::ALL.into_iter().filter(|c| c.is_hideable()) {
+            rsx! {
+                let item = li() {
+                    label(class = "dropdown-item d-flex align-items-center gap-2") {
+                        let input = input(
+                            class = "form-check-input mt-0",
+                            type = "checkbox",
+                            on:click = on_click,
+                        ){}
+                        {column.label().into_text::<V>()}
+                    }
+                }
+            }
+            let is_visible = !hidden.is_hidden(column);
+            input.dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(is_visible));
+            menu.append_child(&item);
+            checkboxes.push(ColumnCheckbox {
+                column,
+                input,
+                on_click,
+            });
+        }
+        rsx! {
+            let reset_items = li() {
+                hr(class = "dropdown-divider"){}
+                button(
+                    class = "dropdown-item",
+                    type = "button",
+                    title = "Show every column and forget the grouping and collapsed sections",
+                    on:click = on_click_reset,
+                ) { "Reset view" }
+            }
+        }
+        menu.append_child(&reset_items);
+
+        rsx! {
+            let wrapper = div(class = "column-chooser btn-group") {
+                button(
+                    class = "btn btn-outline-secondary btn-sm dropdown-toggle",
+                    type = "button",
+                    title = "Columns",
+                    on:click = on_click_toggle,
+                ) { "\u{2699}" }
+                {&menu}
+            }
+        }
+
+        Self {
+            wrapper,
+            on_click_toggle,
+            on_click_reset,
+            menu_open,
+            is_menu_open: false,
+            checkboxes,
+        }
+    }
+
+    pub fn toggle_menu(&mut self) {
+        self.is_menu_open = !self.is_menu_open;
+        self.menu_open.set(self.is_menu_open);
+    }
+
+    pub fn hide_menu(&mut self) {
+        if self.is_menu_open {
+            self.is_menu_open = false;
+            self.menu_open.set(false);
+        }
+    }
+
+    /// Sync the checkboxes with the hidden set.
+    pub fn set_hidden(&self, hidden: &HiddenColumns) {
+        for checkbox in self.checkboxes.iter() {
+            let is_visible = !hidden.is_hidden(checkbox.column);
+            checkbox
+                .input
+                .dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(is_visible));
+        }
+    }
+
+    /// Wait for the next click on the gear button or a checkbox.
+    pub async fn step(&self) -> ColumnAction {
+        let toggles = self
+            .checkboxes
+            .iter()
+            .map(|checkbox| {
+                let column = checkbox.column;
+                checkbox
+                    .on_click
+                    .next()
+                    .map(move |_| ColumnAction::ToggleColumn(column))
+                    .boxed_local()
+            })
+            .collect::<Vec<_>>();
+        self.on_click_toggle
+            .next()
+            .map(|_| ColumnAction::Toggle)
+            .or(self.on_click_reset.next().map(|_| ColumnAction::ResetView))
+            .or(mogwai::future::race_all(toggles))
+            .await
+    }
+}
@@ -0,0 +1,227 @@
+//! "Where are my files?": where a download's files are in the download
+//! client and at each destination, resolved the way the copy engine
+//! resolves them.
+use futures_lite::FutureExt;
+use mogwai::web::prelude::*;
+use privateer_wire_types::{
+    AppError, DestinationLocation, DownloadLocation, InfoHash, InfoHashArgs, LocatedPath,
+};
+
+use crate::app::{format, invoke};
+
+pub async fn locate_download(info_hash: &InfoHash) -> Result<DownloadLocation, AppError> {
+    let info_hash = info_hash.clone();
+    invoke::cmd("locate_download", &InfoHashArgs { info_hash }).await
+}
+
+/// What is at `path`, e.g. "found, 1.4 GB".
+fn describe(path: &LocatedPath) -> String {
+    match (path.exists, path.bytes) {
+        (true, Some(bytes)) => format!("found, {}", format::size(bytes)),
+        (true, None) => "found".to_string(),
+        (false, _) => "missing".to_string(),
+    }
+}
+
+/// A path with what is there, or `none` if there is no path.
+fn path_cell<V: View>(path: Option<&LocatedPath>, none: &str) -> V::Element {
+    match path {
+        Some(path) => {
+            let flavor = if path.exists {
+                "text-success"
+            } else {
+                "text-danger"
+            };
+            rsx! {
+                let cell = td(class = "small text-break") {
+                    div() { {path.path.as_str().into_text::<V>()} }
+                    div(class = flavor) { {describe(path).into_text::<V>()} }
+                }
+            }
+            cell
+        }
+        None => {
+            rsx! {
+                let cell = td(class = "small text-body-secondary") { {none.into_text::<V>()} }
+            }
+            cell
+        }
+    }
+}
+
+fn destination_row<V: View>(location: &DestinationLocation) -> V::Element {
+    let ledger = match &location.target {
+        Some(target) => match &target.failure {
+            Some(failure) => format!("{} ({failure})", target.copy_state.label()),
+            None => target.copy_state.label().to_string(),
+        },
+        None => "\u{2014}".to_string(),
+    };
+    let label = match &location.target {
+        Some(target) => target.label(),
+        None => location.destination.label().to_string(),
+    };
+    let planned = path_cell::<V>(location.planned.as_ref(), "No directory set");
+    let found = path_cell::<V>(location.found.as_ref(), "Not found");
+    rsx! {
+        let row = tr() {
+            td() { {label.into_text::<V>()} }
+            td(class = "small") { {ledger.into_text::<V>()} }
+            {&planned}
+            {&found}
+        }
+    }
+    row
+}
+
+/// What the download client has, and where the copy engine reads it from.
+fn source_lines(location: &DownloadLocation) -> Vec<String> {
+    let mut lines = vec![];
+    match (&location.download_dir, &location.client_error) {
+        (_, Some(e)) => lines.push(format!("Couldn't ask the download client: {e}")),
+        (Some(dir), None) => lines.push(format!("The download client saves it in {dir}")),
+        (None, None) if location.in_client => {
+            lines.push("The download client doesn't say where it saves it".to_string())
+        }
+        (None, None) => lines.push("Not in the download client".to_string()),
+    }
+    if let Some(source) = &location.source {
+        lines.push(format!(
+            "Copied from {} ({})",
+            source.path,
+            describe(source)
+        ));
+    }
+    if let Some(e) = &location.source_error {
+        lines.push(format!("Couldn't check where it is copied from: {e}"));
+    }
+    if location.entry.is_none() {
+        lines.push("Not in the ledger, so it isn't copied".to_string());
+    }
+    lines
+}
+
+/// Build the body of the report.
+fn report<V: View>(location: &DownloadLocation) -> V::Element {
+    rsx! {
+        let content = div() {
+            let lines = ul(class = "list-unstyled small text-break") {}
+            table(class = "table table-sm mb-0") {
+                thead() {
+                    tr() {
+                        th() { "Dest" }
+                        th() { "Ledger" }
+                        th() { "Copied To" }
+                        th() { "Found At" }
+                    }
+                }
+                let tbody = tbody() {}
+            }
+        }
+    }
+    for line in source_lines(location) {
+        rsx! {
+            let item = li() { {line.into_text::<V>()} }
+        }
+        lines.append_child(&item);
+    }
+    for destination in &location.destinations {
+        tbody.append_child(&destination_row::<V>(destination));
+    }
+    content
+}
+
+/// The report in a modal over the view, hidden until a row asks for it.
+pub struct LocationModal<V: View> {
+    pub wrapper: V::Element,
+    title_text: V::Text,
+    body: V::Element,
+    content: Option<V::Element>,
+    on_click_close: V::EventListener,
+    on_click_done: V::EventListener,
+}
+
+impl<V: View> LocationModal<V> {
+    pub fn new() -> Self {
+        rsx! {
+            let wrapper = div(style:display = "none") {
+                div(class = "modal-backdrop show"){}
+                div(class = "modal d-block", tabindex = "-1", role = "dialog") {
+                    div(class = "modal-dialog modal-lg modal-dialog-scrollable") {
+                        div(class = "modal-content") {
+                            div(class = "modal-header") {
+                                h5(class = "modal-title text-break") { let title_text = "" }
+                                button(
+                                    class = "btn-close",
+                                    type = "button",
+                                    title = "Close",
+                                    on:click = on_click_close,
+                                ){}
+                            }
+                            let body = div(class = "modal-body") {}
+                            div(class = "modal-footer") {
+                                button(
+                                    class = "btn btn-secondary",
+                                    type = "button",
+                                    on:click = on_click_done,
+                                ) { "Close" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            title_text,
+            body,
+            content: None,
+            on_click_close,
+            on_click_done,
+        }
+    }
+
+    fn set_content(&mut self, content: V::Element) {
+        if let Some(old) = self.content.take() {
+            self.body.remove_child(&old);
+        }
+        self.body.append_child(&content);
+        self.content = Some(content);
+    }
+
+    /// Open the modal for the download `name` while its files are looked
+    /// for.
+    pub fn open(&mut self, name: &str) {
+        self.title_text
+            .set_text(format!("Where are the files of {name}?"));
+        rsx! {
+            let content = p(class = "text-body-secondary mb-0") { "Looking\u{2026}" }
+        }
+        self.set_content(content);
+        self.wrapper.remove_style("display");
+    }
+
+    /// Show where the files were found, or why they couldn't be looked for.
+    pub fn show(&mut self, result: Result<DownloadLocation, AppError>) {
+        let content = match result {
+            Ok(location) => report::<V>(&location),
+            Err(e) => {
+                let message = format!("Couldn't look for the files: {e}");
+                rsx! {
+                    let content = p(class = "text-danger mb-0") { {message.into_text::<V>()} }
+                }
+                content
+            }
+        };
+        self.set_content(content);
+    }
+
+    /// Wait for the modal to be closed, and hide it.
+    pub async fn step(&self) {
+        self.on_click_close
+            .next()
+            .or(self.on_click_done.next())
+            .await;
+        self.wrapper.set_style("display", "none");
+    }
+}
@@ -0,0 +1,209 @@
+//! Ledger entries whose torrents Transmission no longer has, e.g. after it
+//! was reinstalled, with buttons to add them back.
+//!
+//! Only entries that aren't copied yet are listed; a copied torrent has
+//! nothing left to download.  An entry that isn't wanted any more can be
+//! removed from the ledger instead, to Recently Removed.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{
+    AppError, DownloadEntry, HashesArgs, InfoHash, InfoHashArgs, ReaddResult,
+};
+
+use crate::app::invoke;
+
+pub async fn get_missing_torrents() -> Result<Vec<DownloadEntry>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_missing_torrents", &Empty {}).await
+}
+
+pub async fn readd_missing_torrents(hashes: &[InfoHash]) -> Result<Vec<ReaddResult>, AppError> {
+    let hashes = hashes.to_vec();
+    invoke::cmd("readd_missing_torrents", &HashesArgs { hashes }).await
+}
+
+pub async fn remove_download(info_hash: &InfoHash) -> Result<(), AppError> {
+    let info_hash = info_hash.clone();
+    invoke::cmd("remove_download", &InfoHashArgs { info_hash }).await
+}
+
+/// Event from the missing torrents list.
+pub enum MissingAction {
+    /// A row's "Re-add" button was clicked, with the row's info hash.
+    Readd(InfoHash),
+    /// "Re-add all" was clicked.
+    ReaddAll,
+    /// A row's "Remove" button was clicked, with the row's info hash.
+    Remove(InfoHash),
+}
+
+/// A single missing torrent.
+struct MissingRow<V: View> {
+    wrapper: V::Element,
+    /// How the last attempt to re-add it went.
+    result_text: V::Text,
+    on_click_readd: V::EventListener,
+    on_click_remove: V::EventListener,
+    info_hash: InfoHash,
+}
+
+impl<V: View> MissingRow<V> {
+    fn new(entry: &DownloadEntry) -> Self {
+        rsx! {
+            let wrapper = tr() {
+                td(class = "torrent-name", style:text_align = "left") {
+                    {entry.name.as_str().into_text::<V>()}
+                }
+                td() { {entry.destination.label().into_text::<V>()} }
+                td() { {entry.overall_copy_state().label().into_text::<V>()} }
+                td(class = "text-end text-nowrap") {
+                    span(class = "small text-body-secondary me-2") { let result_text = "" }
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_readd,
+                    ) { "Re-add" }
+                    button(
+                        class = "btn btn-outline-danger btn-sm ms-1",
+                        type = "button",
+                        title = "Remove from the ledger; it can be restored for a while",
+                        on:click = on_click_remove,
+                    ) { "Remove" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            result_text,
+            on_click_readd,
+            on_click_remove,
+            info_hash: entry.info_hash.clone(),
+        }
+    }
+}
+
+/// The missing torrents table, hidden while there are none.
+pub struct MissingTorrents<V: View> {
+    pub wrapper: V::Element,
+    count_text: V::Text,
+    tbody: V::Element,
+    rows: Vec<MissingRow<V>>,
+    on_click_readd_all: V::EventListener,
+}
+
+impl<V: View> MissingTorrents<V> {
+    pub fn new() -> Self {
+        rsx! {
+            let wrapper = div(class = "mt-4", style:display = "none") {
+                div(class = "d-flex align-items-center gap-2 mb-2") {
+                    h5(class = "mb-0") { let count_text = "" }
+                    button(
+                        class = "btn btn-outline-primary btn-sm ms-auto",
+                        type = "button",
+                        title = "Add every torrent below back to Transmission",
+                        on:click = on_click_readd_all,
+                    ) { "Re-add all" }
+                }
+                div(class = "table-responsive") {
+                    table(class = "table table-sm") {
+                        thead() {
+                            tr() {
+                                th() { "Name" }
+                                th() { "Dest" }
+                                th() { "Copy" }
+                                th() {}
+                            }
+                        }
+                        let tbody = tbody() {}
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            count_text,
+            tbody,
+            rows: vec![],
+            on_click_readd_all,
+        }
+    }
+
+    /// Show the missing entries.  Rows of entries that are still missing
+    /// are kept, with the result of the last attempt to re-add them.
+    pub fn set_entries(&mut self, entries: &[DownloadEntry]) {
+        let (kept, gone): (Vec<_>, Vec<_>) = self
+            .rows
+            .drain(..)
+            .partition(|row| entries.iter().any(|entry| entry.info_hash == row.info_hash));
+        for row in gone {
+            self.tbody.remove_child(&row.wrapper);
+        }
+        self.rows = kept;
+        for entry in entries {
+            if !self.rows.iter().any(|row| row.info_hash == entry.info_hash) {
+                let row = MissingRow::<V>::new(entry);
+                self.tbody.append_child(&row.wrapper);
+                self.rows.push(row);
+            }
+        }
+
+        if self.rows.is_empty() {
+            self.wrapper.set_style("display", "none");
+        } else {
+            self.count_text
+                .set_text(format!("Missing from Transmission ({})", self.rows.len()));
+            self.wrapper.remove_style("display");
+        }
+    }
+
+    /// Info hashes of every listed entry.
+    pub fn hashes(&self) -> Vec<InfoHash> {
+        self.rows.iter().map(|row| row.info_hash.clone()).collect()
+    }
+
+    /// Note on each row how re-adding it went.
+    pub fn show_results(&self, results: &[ReaddResult]) {
+        for result in results {
+            let Some(row) = self
+                .rows
+                .iter()
+                .find(|row| row.info_hash == result.info_hash)
+            else {
+                continue;
+            };
+            match &result.error {
+                Some(e) => row.result_text.set_text(format!("Failed: {e}")),
+                None => row.result_text.set_text("Added"),
+            }
+        }
+    }
+
+    /// Wait for a click on "Re-add all" or a row's "Re-add" or "Remove".
+    pub async fn step(&self) -> MissingAction {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let readd = row.info_hash.clone();
+                let remove = row.info_hash.clone();
+                row.on_click_readd
+                    .next()
+                    .map(move |_| MissingAction::Readd(readd))
+                    .or(row
+                        .on_click_remove
+                        .next()
+                        .map(move |_| MissingAction::Remove(remove)))
+                    .boxed_local()
+            })
+            .collect::<Vec<_>>();
+        let readd_all = self
+            .on_click_readd_all
+            .next()
+            .map(|_| MissingAction::ReaddAll);
+        if rows.is_empty() {
+            return readd_all.await;
+        }
+        readd_all.or(mogwai::future::race_all(rows)).await
+    }
+}
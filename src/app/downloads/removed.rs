@@ -0,0 +1,141 @@
+//! Ledger entries removed in the last few weeks, with buttons to put them
+//! back, so removing one doesn't lose the record of where it was copied.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{
+    format_relative, AppError, InfoHash, InfoHashArgs, RemovedDownload, REMOVED_KEEP_DAYS,
+};
+
+use crate::app::{format, invoke};
+
+pub async fn get_removed_downloads() -> Result<Vec<RemovedDownload>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_removed_downloads", &Empty {}).await
+}
+
+pub async fn restore_download(info_hash: &InfoHash) -> Result<(), AppError> {
+    let info_hash = info_hash.clone();
+    invoke::cmd("restore_download", &InfoHashArgs { info_hash }).await
+}
+
+/// A single removed entry.
+struct RemovedRow<V: View> {
+    wrapper: V::Element,
+    on_click_restore: V::EventListener,
+    info_hash: InfoHash,
+}
+
+impl<V: View> RemovedRow<V> {
+    fn new(removed: &RemovedDownload, now: i64) -> Self {
+        let entry = &removed.entry;
+        let copied_to = entry.copied_path.as_deref().unwrap_or("\u{2014}");
+        rsx! {
+            let wrapper = tr() {
+                td(class = "torrent-name", style:text_align = "left") {
+                    {entry.name.as_str().into_text::<V>()}
+                }
+                td() { {entry.destination.label().into_text::<V>()} }
+                td() { {entry.overall_copy_state().label().into_text::<V>()} }
+                td(class = "small text-break") { {copied_to.into_text::<V>()} }
+                td(title = format::timestamp(removed.removed_at)) {
+                    {format_relative(now, removed.removed_at).into_text::<V>()}
+                }
+                td(class = "text-end") {
+                    button(
+                        class = "btn btn-outline-secondary btn-sm",
+                        type = "button",
+                        on:click = on_click_restore,
+                    ) { "Restore" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            on_click_restore,
+            info_hash: entry.info_hash.clone(),
+        }
+    }
+}
+
+/// The recently removed table, hidden while there are none.
+pub struct RemovedDownloads<V: View> {
+    pub wrapper: V::Element,
+    count_text: V::Text,
+    tbody: V::Element,
+    rows: Vec<RemovedRow<V>>,
+}
+
+impl<V: View> RemovedDownloads<V> {
+    pub fn new() -> Self {
+        let help = format!("Kept for {REMOVED_KEEP_DAYS} days, then dropped for good.");
+        rsx! {
+            let wrapper = div(class = "mt-4", style:display = "none") {
+                div(class = "d-flex align-items-baseline gap-2 mb-2") {
+                    h5(class = "mb-0") { let count_text = "" }
+                    span(class = "small text-body-secondary") { {help.into_text::<V>()} }
+                }
+                div(class = "table-responsive") {
+                    table(class = "table table-sm") {
+                        thead() {
+                            tr() {
+                                th() { "Name" }
+                                th() { "Dest" }
+                                th() { "Copy" }
+                                th() { "Copied To" }
+                                th() { "Removed" }
+                                th() {}
+                            }
+                        }
+                        let tbody = tbody() {}
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            count_text,
+            tbody,
+            rows: vec![],
+        }
+    }
+
+    /// Show the removed entries, most recently removed first.
+    pub fn set_entries(&mut self, removed: &[RemovedDownload], now: i64) {
+        for row in self.rows.drain(..) {
+            self.tbody.remove_child(&row.wrapper);
+        }
+        for removed in removed {
+            let row = RemovedRow::<V>::new(removed, now);
+            self.tbody.append_child(&row.wrapper);
+            self.rows.push(row);
+        }
+
+        if self.rows.is_empty() {
+            self.wrapper.set_style("display", "none");
+        } else {
+            self.count_text
+                .set_text(format!("Recently Removed ({})", self.rows.len()));
+            self.wrapper.remove_style("display");
+        }
+    }
+
+    /// Wait for a row's "Restore" to be clicked, with the row's info hash.
+    pub async fn step(&self) -> InfoHash {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let info_hash = row.info_hash.clone();
+                row.on_click_restore
+                    .next()
+                    .map(move |_| info_hash)
+                    .boxed_local()
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return std::future::pending().await;
+        }
+        mogwai::future::race_all(rows).await
+    }
+}
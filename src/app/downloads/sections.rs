@@ -0,0 +1,250 @@
+//! Grouping of the Downloads table into sections by copy pipeline stage.
+//!
+//! Each section is a pair of `<tbody>` elements in the same table: one with
+//! the collapsible header and one holding the section's rows, so rows can
+//! move between sections without being rebuilt.  Whether the table is
+//! grouped, and which sections are collapsed, are part of the view's
+//! [`UiState`](super::ui_state::UiState).
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{CopyState, TransmissionTorrent};
+
+/// Where a torrent is on its way from Transmission to a library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Stage {
+    Downloading,
+    Waiting,
+    Copying,
+    Copied,
+    Failed,
+    Unassigned,
+}
+
+impl Stage {
+    /// All stages, in table order.
+    pub const ALL: [Stage; 6] = [
+        Stage::Downloading,
+        Stage::Waiting,
+        Stage::Copying,
+        Stage::Copied,
+        Stage::Failed,
+        Stage::Unassigned,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stage::Downloading => "Downloading",
+            Stage::Waiting => "Waiting to copy",
+            Stage::Copying => "Copying",
+            Stage::Copied => "Copied",
+            Stage::Failed => "Failed",
+            Stage::Unassigned => "Unassigned",
+        }
+    }
+
+    /// The stage of `t`.  Torrents without a destination are never copied,
+    /// so they are Unassigned however far they have downloaded.
+    pub fn of(t: &TransmissionTorrent) -> Self {
+        match t.copy_state {
+            CopyState::Copying => Stage::Copying,
+            CopyState::Copied => Stage::Copied,
+            CopyState::Failed => Stage::Failed,
+            CopyState::NotCopied | CopyState::Unconfigured => {
+                if t.destination.is_none() {
+                    Stage::Unassigned
+                } else if t.percent_done < 1.0 {
+                    Stage::Downloading
+                } else {
+                    Stage::Waiting
+                }
+            }
+        }
+    }
+}
+
+/// Event from the sections.
+pub enum SectionAction {
+    /// The grouped/flat toggle was clicked.
+    ToggleGrouped,
+    /// A section's header was clicked.
+    ToggleSection(Stage),
+}
+
+/// One stage's header and rows.
+struct Section<V: View> {
+    stage: Stage,
+    header_body: V::Element,
+    header_text: V::Text,
+    on_click_header: V::EventListener,
+    rows_body: V::Element,
+    count: usize,
+}
+
+/// The sections of the grouped table, with the toggle between grouped and
+/// flat views.
+pub struct Sections<V: View> {
+    pub toggle_button: V::Element,
+    on_click_toggle: V::EventListener,
+    sections: Vec<Section<V>>,
+    is_grouped: bool,
+    collapsed: Vec<Stage>,
+}
+
+impl<V: View> Sections<V> {
+    pub fn new(is_grouped: bool, collapsed: Vec<Stage>) -> Self {
+        let mut sections = vec![];
+        for stage in Stage::ALL {
+            rsx! {
+                let header_body = tbody(style:display = "none") {
+                    tr(class = "table-secondary") {
+                        // Spans every column of the table
+                        td(colspan = "10") {
+                            button(
+                                class = "btn btn-link btn-sm p-0 fw-semibold text-body text-decoration-none",
+                                type = "button",
+                                on:click = on_click_header,
+                            ) { let header_text = "" }
+                        }
+                    }
+                }
+                let rows_body = tbody(style:display = "none") {}
+            }
+            sections.push(Section {
+                stage,
+                header_body,
+                header_text,
+                on_click_header,
+                rows_body,
+                count: 0,
+            });
+        }
+        rsx! {
+            let toggle_button = button(
+                class = "btn btn-outline-secondary btn-sm",
+                type = "button",
+                title = "Group downloads by stage",
+                on:click = on_click_toggle,
+            ) { "Group" }
+        }
+        let view = Self {
+            toggle_button,
+            on_click_toggle,
+            sections,
+            is_grouped,
+            collapsed,
+        };
+        view.redraw();
+        view
+    }
+
+    /// Add the header and rows of every section to the end of `table`.
+    pub fn append_to(&self, table: &V::Element) {
+        for section in self.sections.iter() {
+            table.append_child(&section.header_body);
+            table.append_child(&section.rows_body);
+        }
+    }
+
+    pub fn is_grouped(&self) -> bool {
+        self.is_grouped
+    }
+
+    pub fn collapsed(&self) -> &[Stage] {
+        &self.collapsed
+    }
+
+    /// Set whether the table is grouped and which sections are collapsed.
+    pub fn set_layout(&mut self, is_grouped: bool, collapsed: Vec<Stage>) {
+        self.is_grouped = is_grouped;
+        self.collapsed = collapsed;
+        self.redraw();
+    }
+
+    /// The element holding the rows of `stage`.
+    pub fn rows_body(&self, stage: Stage) -> &V::Element {
+        // Sections are created in the order the stages are declared
+        &self.sections[stage as usize].rows_body
+    }
+
+    /// Switch between the grouped and flat views.
+    pub fn toggle_grouped(&mut self) {
+        self.is_grouped = !self.is_grouped;
+        self.redraw();
+    }
+
+    /// Collapse or expand the section of `stage`.
+    pub fn toggle_section(&mut self, stage: Stage) {
+        if self.collapsed.contains(&stage) {
+            self.collapsed.retain(|s| *s != stage);
+        } else {
+            self.collapsed.push(stage);
+        }
+        self.redraw();
+    }
+
+    /// Update the counts in the headers from the stage of every row.
+    pub fn set_counts(&mut self, stages: impl IntoIterator<Item = Stage>) {
+        for section in self.sections.iter_mut() {
+            section.count = 0;
+        }
+        for stage in stages {
+            if let Some(section) = self.sections.iter_mut().find(|s| s.stage == stage) {
+                section.count += 1;
+            }
+        }
+        self.redraw();
+    }
+
+    /// Show the non-empty sections with their counts while grouped, and
+    /// nothing otherwise.
+    fn redraw(&self) {
+        self.toggle_button.set_property(
+            "class",
+            if self.is_grouped {
+                "btn btn-outline-secondary btn-sm active"
+            } else {
+                "btn btn-outline-secondary btn-sm"
+            },
+        );
+        for section in self.sections.iter() {
+            let is_collapsed = self.collapsed.contains(&section.stage);
+            let arrow = if is_collapsed { "\u{25b8}" } else { "\u{25be}" };
+            section.header_text.set_text(format!(
+                "{arrow} {} ({})",
+                section.stage.label(),
+                section.count
+            ));
+            if self.is_grouped && section.count > 0 {
+                section.header_body.remove_style("display");
+            } else {
+                section.header_body.set_style("display", "none");
+            }
+            if self.is_grouped && section.count > 0 && !is_collapsed {
+                section.rows_body.remove_style("display");
+            } else {
+                section.rows_body.set_style("display", "none");
+            }
+        }
+    }
+
+    /// Wait for the next click on the toggle or a section header.
+    pub async fn step(&self) -> SectionAction {
+        let headers = self
+            .sections
+            .iter()
+            .map(|section| {
+                let stage = section.stage;
+                section
+                    .on_click_header
+                    .next()
+                    .map(move |_| SectionAction::ToggleSection(stage))
+                    .boxed_local()
+            })
+            .collect::<Vec<_>>();
+        self.on_click_toggle
+            .next()
+            .map(|_| SectionAction::ToggleGrouped)
+            .or(mogwai::future::race_all(headers))
+            .await
+    }
+}
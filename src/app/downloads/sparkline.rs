@@ -0,0 +1,110 @@
+//! A tiny inline chart of a downloading torrent's recent download rate,
+//! from the samples the backend keeps in [`TransmissionTorrent::history`].
+//!
+//! Hovering it shows the lowest, average and highest rate and peers over
+//! those samples.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{
+    format_duration, SampleRange, TransmissionStatus, TransmissionTorrent, RATE_HISTORY_SAMPLES,
+};
+
+use crate::app::format;
+
+/// Size of the chart in pixels.
+const WIDTH: f64 = 60.0;
+const HEIGHT: f64 = 16.0;
+
+/// The chart, hidden until a downloading torrent has two samples to join.
+pub struct Sparkline<V: View> {
+    pub wrapper: V::Element,
+    line: V::Element,
+}
+
+/// The polyline's points, newest at the right edge so the line grows
+/// leftwards until the history is full.  Rates are scaled to the highest.
+fn points(rates: &[i64]) -> String {
+    let step = WIDTH / (RATE_HISTORY_SAMPLES - 1) as f64;
+    let max = rates.iter().copied().max().unwrap_or_default().max(1) as f64;
+    let start = WIDTH - rates.len().saturating_sub(1) as f64 * step;
+    rates
+        .iter()
+        .enumerate()
+        .map(|(i, rate)| {
+            let x = start + i as f64 * step;
+            // Keep a pixel clear at the top and bottom for the stroke
+            let y = HEIGHT - 1.0 - (*rate).max(0) as f64 / max * (HEIGHT - 2.0);
+            format!("{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The tooltip, e.g. "Last 3m: ↓ min 0 B/s, avg 1.2 MB/s, max 3.4 MB/s".
+fn title(t: &TransmissionTorrent) -> String {
+    let (Some(first), Some(last)) = (t.history.first(), t.history.last()) else {
+        return String::new();
+    };
+    let span = format_duration(last.at.saturating_sub(first.at).max(0) as u64);
+    let mut lines = vec![format!("Last {span}")];
+    if let Some(rate) = SampleRange::of(t.history.iter().map(|s| s.rate_download)) {
+        lines.push(format!(
+            "\u{2193} min {}, avg {}, max {}",
+            format::rate(rate.min),
+            format::rate(rate.avg),
+            format::rate(rate.max)
+        ));
+    }
+    if let Some(peers) = SampleRange::of(t.history.iter().map(|s| s.peers_sending_to_us)) {
+        lines.push(format!(
+            "Peers sending: min {}, avg {}, max {}",
+            peers.min, peers.avg, peers.max
+        ));
+    }
+    if let Some(peers) = SampleRange::of(t.history.iter().map(|s| s.peers_connected)) {
+        lines.push(format!(
+            "Peers connected: min {}, avg {}, max {}",
+            peers.min, peers.avg, peers.max
+        ));
+    }
+    lines.join("\n")
+}
+
+impl<V: View> Sparkline<V> {
+    pub fn new() -> Self {
+        rsx! {
+            let wrapper = span(class = "text-primary", style:display = "none") {
+                svg(
+                    xmlns = "http://www.w3.org/2000/svg",
+                    width = "60",
+                    height = "16",
+                    viewBox = "0 0 60 16",
+                ) {
+                    let line = polyline(
+                        xmlns = "http://www.w3.org/2000/svg",
+                        fill = "none",
+                        stroke = "currentColor",
+                    ) {}
+                }
+            }
+        }
+        Self { wrapper, line }
+    }
+
+    /// Draw the history of `t`, or hide the chart if it isn't downloading.
+    pub fn update(&self, t: &TransmissionTorrent) {
+        if t.status != TransmissionStatus::Downloading || t.history.len() < 2 {
+            self.wrapper.set_style("display", "none");
+            return;
+        }
+        let rates = t
+            .history
+            .iter()
+            .map(|s| s.rate_download)
+            .collect::<Vec<_>>();
+        self.line.dyn_el(|line: &web_sys::Element| {
+            let _ = line.set_attribute("points", &points(&rates));
+        });
+        self.wrapper.set_property("title", &title(t));
+        self.wrapper.remove_style("display");
+    }
+}
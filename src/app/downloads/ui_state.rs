@@ -0,0 +1,212 @@
+//! The layout of the Downloads view, remembered across restarts.
+//!
+//! The whole layout is kept in localStorage under one key as a versioned
+//! [`UiState`].  Fields missing from what was stored take their defaults,
+//! so states saved by older versions still load, and columns or sections
+//! this version doesn't know are dropped one by one.  A state of another
+//! version is discarded.  Before this, each part of the layout had its own
+//! key; those are read once and then removed.
+use mogwai::web::prelude::*;
+
+use super::columns::HiddenColumns;
+use super::sections::Stage;
+use crate::app::storage;
+
+/// localStorage key for the layout.
+const STORAGE_KEY: &str = "downloads-ui-state";
+/// The current [`UiState::version`].
+const VERSION: u32 = 1;
+
+/// localStorage keys of the hidden columns, grouped flag, collapsed
+/// sections and collapsed usage header, from before [`UiState`].
+const LEGACY_COLUMNS_KEY: &str = "downloads-hidden-columns";
+const LEGACY_GROUPED_KEY: &str = "downloads-grouped";
+const LEGACY_COLLAPSED_KEY: &str = "downloads-collapsed-sections";
+const LEGACY_USAGE_KEY: &str = "downloads-usage-collapsed";
+
+/// How the Downloads view is laid out.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// The version of the shape this was stored in.
+    pub version: u32,
+    #[serde(deserialize_with = "known")]
+    pub hidden_columns: HiddenColumns,
+    /// Whether the table is grouped into sections by stage.
+    pub grouped: bool,
+    #[serde(deserialize_with = "known")]
+    pub collapsed_sections: Vec<Stage>,
+    /// Whether the capacity bars above the table are collapsed.
+    pub usage_collapsed: bool,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            hidden_columns: HiddenColumns::default(),
+            grouped: false,
+            collapsed_sections: vec![],
+            usage_collapsed: false,
+        }
+    }
+}
+
+/// Read a list, dropping the entries this version doesn't know, e.g. a
+/// column added by a newer one.
+fn known<'de, D, T, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+    C: FromIterator<T>,
+{
+    let values: Vec<serde_json::Value> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
+
+impl UiState {
+    /// Read a stored state, falling back to the defaults if it isn't one
+    /// of this version.
+    fn parse(s: &str) -> Self {
+        match serde_json::from_str::<Self>(s) {
+            Ok(state) if state.version == VERSION => state,
+            Ok(state) => {
+                log::info!("discarding a Downloads layout of version {}", state.version);
+                Self::default()
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Gather the state from the keys each part was stored under before
+    /// [`UiState`], as read by `get`.
+    fn from_legacy(get: impl Fn(&str) -> Option<String>) -> Self {
+        fn json<T: serde::de::DeserializeOwned + Default>(value: Option<String>) -> T {
+            value
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default()
+        }
+
+        Self {
+            hidden_columns: json(get(LEGACY_COLUMNS_KEY)),
+            grouped: json(get(LEGACY_GROUPED_KEY)),
+            collapsed_sections: json(get(LEGACY_COLLAPSED_KEY)),
+            usage_collapsed: get(LEGACY_USAGE_KEY).is_some_and(|value| value == "true"),
+            ..Self::default()
+        }
+    }
+
+    /// Load the layout from localStorage, moving it over from the older
+    /// per-part keys the first time.
+    pub fn load<V: View>() -> Self {
+        let Some(local) = storage::local::<V>() else {
+            return Self::default();
+        };
+        if let Some(s) = local.get_item(STORAGE_KEY).ok().flatten() {
+            return Self::parse(&s);
+        }
+        let state = Self::from_legacy(|key| local.get_item(key).ok().flatten());
+        state.store::<V>();
+        for key in [
+            LEGACY_COLUMNS_KEY,
+            LEGACY_GROUPED_KEY,
+            LEGACY_COLLAPSED_KEY,
+            LEGACY_USAGE_KEY,
+        ] {
+            storage::remove::<V>(key);
+        }
+        state
+    }
+
+    /// Persist the layout to localStorage.
+    pub fn store<V: View>(&self) {
+        storage::set::<V, _>(STORAGE_KEY, self);
+    }
+
+    /// Forget the stored layout, so the defaults apply from now on.
+    pub fn clear<V: View>() {
+        storage::remove::<V>(STORAGE_KEY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::columns::Column;
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let mut hidden_columns = HiddenColumns::default();
+        hidden_columns.toggle(Column::Peers);
+        let state = UiState {
+            hidden_columns,
+            grouped: true,
+            collapsed_sections: vec![Stage::Copied],
+            usage_collapsed: true,
+            ..UiState::default()
+        };
+        let s = serde_json::to_string(&state).unwrap();
+        assert_eq!(state, UiState::parse(&s));
+    }
+
+    #[test]
+    fn older_shapes_take_defaults() {
+        let state = UiState::parse(r#"{"version":1,"grouped":true}"#);
+        assert!(state.grouped);
+        assert_eq!(HiddenColumns::default(), state.hidden_columns);
+        assert!(state.collapsed_sections.is_empty());
+
+        assert_eq!(UiState::default(), UiState::parse("{}"));
+        assert_eq!(UiState::default(), UiState::parse("not json"));
+    }
+
+    #[test]
+    fn unknown_columns_and_sections_are_dropped() {
+        let state = UiState::parse(
+            r#"{"version":1,"grouped":true,"hidden_columns":["Sparkline","Peers"],
+                "collapsed_sections":["Seeding","Copied"]}"#,
+        );
+        assert!(state.grouped);
+        assert_eq!(
+            [Column::Peers].into_iter().collect::<HiddenColumns>(),
+            state.hidden_columns
+        );
+        assert_eq!(vec![Stage::Copied], state.collapsed_sections);
+    }
+
+    #[test]
+    fn other_versions_are_discarded() {
+        let newer = format!(r#"{{"version":{},"grouped":true}}"#, VERSION + 1);
+        assert_eq!(UiState::default(), UiState::parse(&newer));
+        assert_eq!(
+            UiState::default(),
+            UiState::parse(r#"{"version":0,"grouped":true}"#)
+        );
+    }
+
+    #[test]
+    fn moves_over_the_older_keys() {
+        let get = |key: &str| {
+            match key {
+                LEGACY_COLUMNS_KEY => Some(r#"["Ratio","Added"]"#),
+                LEGACY_GROUPED_KEY => Some("true"),
+                LEGACY_COLLAPSED_KEY => Some(r#"["Failed"]"#),
+                LEGACY_USAGE_KEY => Some("true"),
+                _ => None,
+            }
+            .map(String::from)
+        };
+        let state = UiState::from_legacy(get);
+        assert!(state.hidden_columns.is_hidden(Column::Ratio));
+        assert!(state.hidden_columns.is_hidden(Column::Added));
+        assert!(!state.hidden_columns.is_hidden(Column::Peers));
+        assert!(state.grouped);
+        assert_eq!(vec![Stage::Failed], state.collapsed_sections);
+        assert!(state.usage_collapsed);
+
+        assert_eq!(UiState::default(), UiState::from_legacy(|_| None));
+    }
+}
@@ -0,0 +1,149 @@
+//! Banner over every tab about completed downloads that have no
+//! destination, so are never copied.
+//!
+//! Dismissing it remembers which downloads it was about, in localStorage:
+//! it stays away for those and comes back when another one finishes.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{InfoHash, TransmissionTorrent};
+
+use super::sections::Stage;
+use super::{get_torrents, TorrentsUpdate, TORRENTS_UPDATED};
+use crate::app::{events, storage};
+
+/// localStorage key for the info hashes the banner was dismissed for.
+const STORAGE_KEY: &str = "unassigned-dismissed";
+
+/// Whether `t` has finished downloading but can't be copied for want of a
+/// destination.
+fn is_unassigned_complete(t: &TransmissionTorrent) -> bool {
+    Stage::of(t) == Stage::Unassigned && t.percent_done >= 1.0
+}
+
+fn message(count: usize) -> String {
+    if count == 1 {
+        "1 completed download has no destination.".to_string()
+    } else {
+        format!("{count} completed downloads have no destination.")
+    }
+}
+
+enum BannerEvent {
+    Update(TorrentsUpdate),
+    Review,
+    Dismiss,
+}
+
+/// "N completed downloads have no destination", with a button to review
+/// them in the Downloads tab.
+pub struct UnassignedBanner<V: View> {
+    pub wrapper: V::Element,
+    message_text: V::Text,
+    on_click_review: V::EventListener,
+    on_click_dismiss: V::EventListener,
+    /// Torrent lists pushed by the backend after each poll.
+    updates: async_channel::Receiver<TorrentsUpdate>,
+    /// The completed downloads without a destination.
+    hashes: Vec<InfoHash>,
+    /// The downloads the banner was last dismissed for.
+    dismissed: Vec<InfoHash>,
+}
+
+impl<V: View> UnassignedBanner<V> {
+    pub fn new() -> Self {
+        let updates = if V::is_view::<Web>() {
+            events::subscribe(TORRENTS_UPDATED)
+        } else {
+            async_channel::bounded(1).1
+        };
+        rsx! {
+            let wrapper = div(
+                class = "alert alert-warning d-flex align-items-center gap-2 py-2",
+                style:display = "none",
+            ) {
+                span() { let message_text = "" }
+                button(
+                    class = "btn btn-warning btn-sm",
+                    type = "button",
+                    on:click = on_click_review,
+                ) { "Review" }
+                button(
+                    class = "btn-close ms-auto",
+                    type = "button",
+                    title = "Dismiss until another download needs a destination",
+                    on:click = on_click_dismiss,
+                ){}
+            }
+        }
+        Self {
+            wrapper,
+            message_text,
+            on_click_review,
+            on_click_dismiss,
+            updates,
+            hashes: vec![],
+            dismissed: storage::get::<V, _>(STORAGE_KEY).unwrap_or_default(),
+        }
+    }
+
+    /// Fetch the torrents and show the banner if it is needed.
+    pub async fn refresh(&mut self) {
+        match get_torrents(false).await {
+            Ok(torrents) => self.set_torrents(&torrents),
+            Err(e) => log::debug!("Failed to get torrents for the unassigned banner: {e}"),
+        }
+    }
+
+    fn set_torrents(&mut self, torrents: &[TransmissionTorrent]) {
+        self.hashes = torrents
+            .iter()
+            .filter(|t| is_unassigned_complete(t))
+            .map(|t| t.hash_string.clone())
+            .collect();
+        self.redraw();
+    }
+
+    /// Show the banner while any of the downloads it is about weren't
+    /// dismissed.
+    fn redraw(&self) {
+        let is_needed = self.hashes.iter().any(|h| !self.dismissed.contains(h));
+        if is_needed {
+            self.message_text.set_text(message(self.hashes.len()));
+            self.wrapper.remove_style("display");
+        } else {
+            self.wrapper.set_style("display", "none");
+        }
+    }
+
+    fn dismiss(&mut self) {
+        self.dismissed.clone_from(&self.hashes);
+        storage::set::<V, _>(STORAGE_KEY, &self.dismissed);
+        self.redraw();
+    }
+
+    /// Keep up with the torrent updates and handle dismissal, until
+    /// "Review" is clicked.
+    pub async fn step(&mut self) {
+        loop {
+            let updates = &self.updates;
+            let next_update = async {
+                match updates.recv().await {
+                    Ok(update) => BannerEvent::Update(update),
+                    // Never closes on the web; elsewhere there are no events
+                    Err(_) => std::future::pending().await,
+                }
+            };
+            let event = next_update
+                .or(self.on_click_review.next().map(|_| BannerEvent::Review))
+                .or(self.on_click_dismiss.next().map(|_| BannerEvent::Dismiss))
+                .await;
+            match event {
+                BannerEvent::Update(Ok(torrents)) => self.set_torrents(&torrents),
+                // The Downloads tab and the connection dot report failures
+                BannerEvent::Update(Err(_)) => {}
+                BannerEvent::Review => return,
+                BannerEvent::Dismiss => self.dismiss(),
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! How full each destination's volume is, as small capacity bars above the
+//! Downloads table.
+//!
+//! The bars turn amber when a volume is nearly full and red when it is full,
+//! with a warning when downloads are still waiting to be copied there.  The
+//! header can be collapsed, which is remembered in the view's
+//! [`UiState`](super::ui_state::UiState).
+use iti::components::progress::Progress;
+use iti::components::Flavor;
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, DestinationUsage, UsageLevel};
+
+use crate::app::{format, invoke};
+
+pub async fn get_destination_usage() -> Result<Vec<DestinationUsage>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_destination_usage", &Empty {}).await
+}
+
+fn level_flavor(level: UsageLevel) -> Flavor {
+    match level {
+        UsageLevel::Ok => Flavor::Success,
+        UsageLevel::NearlyFull => Flavor::Warning,
+        UsageLevel::Full => Flavor::Danger,
+    }
+}
+
+/// Free space and what was copied, e.g. "1.2 TB free of 4.0 TB · 120
+/// copied (800.0 GB) · 3 waiting".
+fn usage_label(usage: &DestinationUsage) -> String {
+    let mut parts = vec![];
+    match (usage.free_bytes, usage.total_bytes) {
+        (Some(free), Some(total)) => parts.push(format!(
+            "{} free of {}",
+            format::size(free),
+            format::size(total)
+        )),
+        _ => parts.push("Size unknown".to_string()),
+    }
+    if usage.copied > 0 {
+        parts.push(format!(
+            "{} copied ({})",
+            usage.copied,
+            format::size(usage.copied_bytes)
+        ));
+    }
+    if usage.pending > 0 {
+        parts.push(format!("{} waiting", usage.pending));
+    }
+    parts.join(" \u{00b7} ")
+}
+
+/// Tooltip of a bar: the directory, and a warning when downloads waiting to
+/// be copied may not fit.
+fn usage_title(usage: &DestinationUsage) -> String {
+    let warning = match usage.level() {
+        UsageLevel::Ok => return usage.path.clone(),
+        UsageLevel::NearlyFull => "nearly full",
+        UsageLevel::Full => "full",
+    };
+    if usage.pending > 0 {
+        format!(
+            "{}\nThis volume is {warning}; {} downloads waiting to be copied may not fit.",
+            usage.path, usage.pending
+        )
+    } else {
+        format!("{}\nThis volume is {warning}.", usage.path)
+    }
+}
+
+/// One destination's bar.
+struct UsageBar<V: View> {
+    wrapper: V::Element,
+    _progress: Progress<V>,
+}
+
+impl<V: View> UsageBar<V> {
+    fn new(usage: &DestinationUsage) -> Self {
+        let used = usage.used_fraction().unwrap_or_default();
+        let progress =
+            Progress::<V>::new((used * 100.0).round() as u8, level_flavor(usage.level()));
+        rsx! {
+            let wrapper = div(style:flex = "1", style:min_width = "14rem") {
+                div(class = "d-flex justify-content-between small") {
+                    span() { {usage.destination.label().into_text::<V>()} }
+                    span(class = "text-body-secondary") {
+                        {usage_label(usage).into_text::<V>()}
+                    }
+                }
+                {&progress}
+            }
+        }
+        wrapper.set_property("title", &usage_title(usage));
+        Self {
+            wrapper,
+            _progress: progress,
+        }
+    }
+}
+
+/// The collapsible header of capacity bars, hidden until a destination has
+/// a directory.
+pub struct UsageHeader<V: View> {
+    pub wrapper: V::Element,
+    toggle_text: V::Text,
+    on_click_toggle: V::EventListener,
+    bars_wrapper: V::Element,
+    bars: Vec<UsageBar<V>>,
+    is_collapsed: bool,
+}
+
+impl<V: View> UsageHeader<V> {
+    pub fn new(is_collapsed: bool) -> Self {
+        rsx! {
+            let wrapper = div(class = "mb-2", style:display = "none") {
+                button(
+                    class = "btn btn-link btn-sm p-0 text-body-secondary text-decoration-none",
+                    type = "button",
+                    on:click = on_click_toggle,
+                ) { let toggle_text = "" }
+                let bars_wrapper = div(class = "d-flex flex-wrap gap-3 mt-1") {}
+            }
+        }
+        let mut header = Self {
+            wrapper,
+            toggle_text,
+            on_click_toggle,
+            bars_wrapper,
+            bars: vec![],
+            is_collapsed,
+        };
+        header.set_collapsed(is_collapsed);
+        header
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.is_collapsed
+    }
+
+    pub fn set_collapsed(&mut self, is_collapsed: bool) {
+        self.is_collapsed = is_collapsed;
+        if is_collapsed {
+            self.toggle_text.set_text("\u{25b8} Libraries");
+            self.bars_wrapper.set_style("display", "none");
+        } else {
+            self.toggle_text.set_text("\u{25be} Libraries");
+            self.bars_wrapper.remove_style("display");
+        }
+    }
+
+    /// Fetch the usage of every destination and redraw the bars.
+    pub async fn refresh(&mut self) {
+        let usage = match get_destination_usage().await {
+            Ok(usage) => usage,
+            Err(e) => {
+                log::error!("Failed to get destination usage: {e}");
+                return;
+            }
+        };
+        for bar in self.bars.drain(..) {
+            self.bars_wrapper.remove_child(&bar.wrapper);
+        }
+        for usage in &usage {
+            let bar = UsageBar::<V>::new(usage);
+            self.bars_wrapper.append_child(&bar.wrapper);
+            self.bars.push(bar);
+        }
+        if self.bars.is_empty() {
+            self.wrapper.set_style("display", "none");
+        } else {
+            self.wrapper.remove_style("display");
+        }
+    }
+
+    /// Wait for the header to be clicked.
+    pub async fn step(&self) {
+        self.on_click_toggle.next().await;
+    }
+
+    /// Collapse or expand the bars.
+    pub fn toggle(&mut self) {
+        self.set_collapsed(!self.is_collapsed);
+    }
+}
@@ -0,0 +1,92 @@
+//! Dates, sizes and rates shown the way the user prefers.
+//!
+//! The locale and size units are part of the saved settings.  They are kept
+//! here once loaded so any view can format a value without fetching them,
+//! and replaced whenever Settings are saved.
+use std::cell::RefCell;
+
+use privateer_wire_types::{
+    format_rate, format_relative, format_size, CopyThroughput, SizeUnits, TransmissionConfig,
+};
+use wasm_bindgen::JsValue;
+
+/// Locale used when neither the settings nor the browser name one.
+const FALLBACK_LOCALE: &str = "en-US";
+
+#[derive(Default)]
+struct Preferences {
+    /// `None` follows the browser's locale.
+    locale: Option<String>,
+    units: SizeUnits,
+}
+
+thread_local! {
+    static PREFERENCES: RefCell<Preferences> = RefCell::default();
+}
+
+/// Format with the preferences in `config` from now on.
+pub fn set(config: &TransmissionConfig) {
+    PREFERENCES.with_borrow_mut(|prefs| {
+        *prefs = Preferences {
+            locale: config.locale.clone(),
+            units: config.size_units,
+        }
+    });
+}
+
+/// Format with the saved preferences, or the defaults if they can't be
+/// loaded.
+pub async fn load() {
+    match super::settings::get_transmission_config().await {
+        Ok(config) => set(&config),
+        Err(e) => log::error!("Failed to load formatting preferences: {e}"),
+    }
+}
+
+/// The chosen locale, or the browser's.
+pub fn locale() -> String {
+    PREFERENCES
+        .with_borrow(|prefs| prefs.locale.clone())
+        .or_else(|| mogwai::web::window().navigator().language())
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
+
+/// A unix timestamp as a local date and time, e.g. "17/10/2026, 09:30:00".
+pub fn timestamp(seconds: i64) -> String {
+    let milliseconds = seconds as f64 * 1000.0;
+    let date = web_sys::js_sys::Date::new(&milliseconds.into());
+    date.to_locale_string(&locale(), &JsValue::undefined())
+        .into()
+}
+
+/// How long ago a unix timestamp was, e.g. "3 months ago", and the exact
+/// [`timestamp`] for a tooltip.  Unknown times are "unknown" with no
+/// tooltip.
+pub fn age(seconds: Option<i64>) -> (String, String) {
+    match seconds {
+        Some(seconds) => (
+            format_relative(super::unix_now(), seconds),
+            timestamp(seconds),
+        ),
+        None => ("unknown".to_string(), String::new()),
+    }
+}
+
+fn units() -> SizeUnits {
+    PREFERENCES.with_borrow(|prefs| prefs.units)
+}
+
+/// A size in bytes in the chosen units, e.g. "1.5 GB" or "1.4 GiB".
+pub fn size(bytes: u64) -> String {
+    format_size(bytes, units())
+}
+
+/// A transfer rate in the chosen units, e.g. "1.2 MB/s".
+pub fn rate(bytes_per_second: i64) -> String {
+    format_rate(bytes_per_second, units())
+}
+
+/// How long a copy took and how fast it went, e.g. "9m 12s (62.0 MB/s)".
+pub fn throughput(copy: &CopyThroughput) -> String {
+    copy.describe(units())
+}
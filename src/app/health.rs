@@ -0,0 +1,187 @@
+//! Navbar health indicator summarizing every backend subsystem.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, HealthReport};
+
+use super::{format, invoke};
+
+async fn get_health() -> Result<HealthReport, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_health", &Empty {}).await
+}
+
+/// Overall state shown by the indicator dot.
+#[derive(Clone, Copy, PartialEq)]
+enum Overall {
+    Unknown,
+    Healthy,
+    Unhealthy,
+}
+
+/// A dot in the navbar that turns red when anything is unhealthy, with a
+/// popover listing each subsystem.
+pub struct HealthIndicator<V: View> {
+    pub wrapper: V::Element,
+    on_click_toggle: V::EventListener,
+    overall: Proxy<Overall>,
+    title: Proxy<String>,
+    menu_open: Proxy<bool>,
+    is_menu_open: bool,
+    menu: V::Element,
+    items: Vec<V::Element>,
+}
+
+impl<V: View> HealthIndicator<V> {
+    pub fn new() -> Self {
+        let mut overall = Proxy::new(Overall::Unknown);
+        let mut title = Proxy::new("Checking health\u{2026}".to_string());
+        let mut menu_open = Proxy::new(false);
+        rsx! {
+            let wrapper = div(class = "btn-group ms-auto") {
+                button(
+                    class = "btn btn-sm btn-link text-decoration-none",
+                    type = "button",
+                    title = title(t => t.clone()),
+                    on:click = on_click_toggle,
+                ) {
+                    span(
+                        class = overall(o => match o {
+                            Overall::Unknown => "text-secondary",
+                            Overall::Healthy => "text-success",
+                            Overall::Unhealthy => "text-danger",
+                        }),
+                    ) { "\u{25CF}" }
+                }
+                let menu = ul(
+                    class = menu_open(is_open => if *is_open {
+                        "dropdown-menu dropdown-menu-end show"
+                    } else {
+                        "dropdown-menu dropdown-menu-end"
+                    }),
+                    style:min_width = "22rem",
+                ) {}
+            }
+        }
+        Self {
+            wrapper,
+            on_click_toggle,
+            overall,
+            title,
+            menu_open,
+            is_menu_open: false,
+            menu,
+            items: vec![],
+        }
+    }
+
+    pub fn toggle_menu(&mut self) {
+        self.is_menu_open = !self.is_menu_open;
+        self.menu_open.set(self.is_menu_open);
+    }
+
+    /// Replace the popover's contents with one line per subsystem.
+    fn set_lines(&mut self, lines: Vec<(bool, String)>) {
+        for item in self.items.drain(..) {
+            self.menu.remove_child(&item);
+        }
+        for (ok, text) in lines {
+            let mark = if ok { "\u{2705}" } else { "\u{274C}" };
+            rsx! {
+                let item = li(class = "dropdown-item-text small") {
+                    {format!("{mark} {text}").into_text::<V>()}
+                }
+            }
+            self.menu.append_child(&item);
+            self.items.push(item);
+        }
+    }
+
+    /// Fetch the latest report from the backend and update the indicator.
+    pub async fn refresh(&mut self) {
+        let report = match get_health().await {
+            Ok(report) => report,
+            Err(e) => {
+                log::debug!("Failed to get health: {e}");
+                self.overall.set(Overall::Unknown);
+                self.title.set(format!("Health unknown: {e}"));
+                return;
+            }
+        };
+
+        let problems = report.problems();
+        if problems.is_empty() {
+            self.overall.set(Overall::Healthy);
+            self.title.set("All systems healthy".to_string());
+        } else {
+            self.overall.set(Overall::Unhealthy);
+            self.title.set(problems.join("\n"));
+        }
+
+        let mut lines = vec![(
+            report.transmission.ok,
+            format!("Transmission: {}", report.transmission.detail),
+        )];
+        for dest in report.destinations.iter() {
+            let text = match &dest.path {
+                None => format!("{}: not configured", dest.destination),
+                Some(path) if !dest.reachable => match dest.unreachable_since {
+                    Some(since) => format!(
+                        "{}: {path} unreachable since {}",
+                        dest.destination,
+                        format::timestamp(since)
+                    ),
+                    None => format!("{}: {path} unreachable", dest.destination),
+                },
+                Some(path) if dest.mounted == Some(false) => {
+                    format!("{}: {path} not mounted", dest.destination)
+                }
+                Some(path) if !dest.writable => {
+                    format!("{}: {path} not writable", dest.destination)
+                }
+                Some(path) => match dest.free_bytes {
+                    Some(free) => {
+                        format!("{}: {path} ({} free)", dest.destination, format::size(free))
+                    }
+                    None => format!("{}: {path}", dest.destination),
+                },
+            };
+            lines.push((dest.is_ok(), text));
+        }
+        lines.push((
+            report.search.ok,
+            format!("Search: {}", report.search.detail),
+        ));
+        let ledger = report.ledger;
+        lines.push((
+            ledger.failed == 0 && ledger.unconfigured == 0,
+            format!(
+                "Ledger: {} copied, {} pending, {} copying, {} failed, {} with no destination",
+                ledger.copied,
+                ledger.not_copied,
+                ledger.copying,
+                ledger.failed,
+                ledger.unconfigured
+            ),
+        ));
+        if report.copy_in_flight {
+            lines.push((true, "A copy is in progress".to_string()));
+        }
+        let engine = &report.copy_engine;
+        if engine.crashes > 0 {
+            lines.push((
+                engine.is_healthy(),
+                format!(
+                    "Copy engine: crashed {} time(s) in a row: {}",
+                    engine.crashes,
+                    engine.last_panic.as_deref().unwrap_or_default()
+                ),
+            ));
+        }
+        self.set_lines(lines);
+    }
+
+    /// Wait for a click on the indicator.
+    pub async fn step(&self) {
+        self.on_click_toggle.next().await;
+    }
+}
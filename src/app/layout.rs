@@ -0,0 +1,101 @@
+//! Row density and the layout for narrow windows.
+//!
+//! Density is applied with a `data-density` attribute on the document and
+//! kept in localStorage, like the theme.  Narrow windows are handled by the
+//! tables themselves: below [`NARROW_WIDTH`] they hide their low-priority
+//! columns and offer those values in an expandable row detail instead.
+use std::cell::Cell;
+
+use mogwai::web::prelude::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use super::storage;
+
+/// localStorage key for the chosen density.
+const STORAGE_KEY: &str = "density";
+
+/// Window width in CSS pixels below which the tables drop their
+/// low-priority columns.
+const NARROW_WIDTH: f64 = 992.0;
+
+/// How much padding table rows get.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl Density {
+    pub const ALL: [Density; 2] = [Density::Comfortable, Density::Compact];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "Comfortable",
+            Density::Compact => "Compact",
+        }
+    }
+
+    /// The chosen density, or [`Density::Comfortable`] if none was.
+    pub fn load() -> Self {
+        storage::get::<Web, _>(STORAGE_KEY).unwrap_or_default()
+    }
+
+    /// Remember the density and apply it.
+    pub fn store(self) {
+        storage::set::<Web, _>(STORAGE_KEY, &self);
+        self.apply();
+    }
+
+    /// Set `data-density` to "comfortable" or "compact".
+    fn apply(self) {
+        if let Some(root) = mogwai::web::document().document_element() {
+            let _ = root.set_attribute("data-density", &self.label().to_lowercase());
+        }
+    }
+}
+
+/// Apply the chosen density.
+pub fn init() {
+    Density::load().apply();
+}
+
+/// Whether the window is narrower than [`NARROW_WIDTH`].
+///
+/// Always `false` outside the browser.
+pub fn is_narrow<V: View>() -> bool {
+    V::is_view::<Web>() && window_is_narrow()
+}
+
+fn window_is_narrow() -> bool {
+    mogwai::web::window()
+        .inner_width()
+        .ok()
+        .and_then(|width| width.as_f64())
+        .is_some_and(|width| width < NARROW_WIDTH)
+}
+
+/// Listen for the window crossing [`NARROW_WIDTH`], for the lifetime of the
+/// app.
+///
+/// Resizing fires many events; the channel only gets one when the window
+/// becomes narrow (`true`) or wide again (`false`), and only holds the most
+/// recent.  Outside the browser the channel is closed straight away.
+pub fn narrow_changes<V: View>() -> async_channel::Receiver<bool> {
+    let (tx, rx) = async_channel::bounded(1);
+    if !V::is_view::<Web>() {
+        return rx;
+    }
+    let was_narrow = Cell::new(window_is_narrow());
+    let on_resize = Closure::<dyn Fn()>::new(move || {
+        let is_narrow = window_is_narrow();
+        if was_narrow.replace(is_narrow) != is_narrow {
+            let _ = tx.force_send(is_narrow);
+        }
+    });
+    let _ = mogwai::web::window()
+        .add_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref());
+    // Listens for the lifetime of the page
+    on_resize.forget();
+    rx
+}
@@ -0,0 +1,170 @@
+//! Asks where a magnet link opened from outside the app should go.
+use futures_lite::FutureExt;
+use iti::components::alert::Alert;
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{AppError, Destination, InfoHash, InfoHashArgs, MagnetLink};
+
+use super::{events, invoke};
+
+/// Backend event sent when a magnet link is queued for a destination.
+const MAGNETS_OPENED: &str = "magnets-opened";
+
+async fn get_pending_magnets() -> Result<Vec<MagnetLink>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_pending_magnets", &Empty {}).await
+}
+
+async fn dismiss_magnet(info_hash: &InfoHash) -> Result<(), AppError> {
+    let info_hash = info_hash.clone();
+    invoke::cmd("dismiss_magnet", &InfoHashArgs { info_hash }).await
+}
+
+/// A dialog over the window for each opened magnet link in turn, with a
+/// button per destination.
+pub struct MagnetPrompt<V: View> {
+    pub wrapper: V::Element,
+    name_text: V::Text,
+    movies_button: Button<V>,
+    shows_button: Button<V>,
+    dismiss_button: Button<V>,
+    error_alert: Alert<V>,
+    opened: async_channel::Receiver<()>,
+    /// The link being asked about.
+    current: Option<MagnetLink>,
+    /// Whether links opened before the window was listening have been
+    /// fetched.
+    loaded: bool,
+}
+
+enum PromptEvent {
+    Opened,
+    Add(Destination),
+    Dismiss,
+}
+
+impl<V: View> MagnetPrompt<V> {
+    pub fn new() -> Self {
+        let mut movies_button = Button::new("Movies", Some(Flavor::Primary));
+        movies_button.get_icon_mut().set_glyph(IconGlyph::Download);
+        let mut shows_button = Button::new("Shows", Some(Flavor::Primary));
+        shows_button.get_icon_mut().set_glyph(IconGlyph::Download);
+        let dismiss_button = Button::new("Cancel", Some(Flavor::Secondary));
+        let error_alert = Alert::new("", Flavor::Danger);
+        error_alert.set_is_visible(false);
+        let opened = if V::is_view::<Web>() {
+            events::subscribe(MAGNETS_OPENED)
+        } else {
+            async_channel::bounded(1).1
+        };
+        let name_text = V::Text::new("");
+        rsx! {
+            let wrapper = div(
+                class = "position-fixed top-0 start-0 w-100 h-100 d-flex \
+                         align-items-center justify-content-center",
+                style:display = "none",
+                style:z_index = "1050",
+                style:background = "rgba(0, 0, 0, 0.5)",
+            ) {
+                div(class = "card", style:max_width = "32rem") {
+                    div(class = "card-body") {
+                        h5(class = "card-title") { "Add magnet link" }
+                        p(class = "card-text text-break") { {&name_text} }
+                        p(class = "card-text") { "Copy it to which destination when it completes?" }
+                        {&error_alert}
+                        div(class = "d-flex gap-2") {
+                            {&movies_button}
+                            {&shows_button}
+                            {&dismiss_button}
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            name_text,
+            movies_button,
+            shows_button,
+            dismiss_button,
+            error_alert,
+            opened,
+            current: None,
+            loaded: false,
+        }
+    }
+
+    /// Show the first pending link, or hide when there are none.
+    async fn refresh(&mut self) {
+        let next = match get_pending_magnets().await {
+            Ok(pending) => pending.into_iter().next(),
+            Err(e) => {
+                log::error!("could not get opened magnet links: {e}");
+                None
+            }
+        };
+        self.error_alert.set_is_visible(false);
+        match &next {
+            Some(magnet) => {
+                self.name_text.set_text(&magnet.name);
+                self.wrapper.remove_style("display");
+            }
+            None => self.wrapper.set_style("display", "none"),
+        }
+        self.current = next;
+    }
+
+    /// Fetch newly opened links, or add or dismiss the one showing.
+    pub async fn step(&mut self) {
+        if !self.loaded {
+            self.refresh().await;
+            self.loaded = true;
+            return;
+        }
+
+        let opened = &self.opened;
+        let next = async {
+            match opened.recv().await {
+                Ok(()) => PromptEvent::Opened,
+                // Never closes on the web; elsewhere there are no events
+                Err(_) => std::future::pending().await,
+            }
+        };
+        let event = next
+            .or(self
+                .movies_button
+                .step()
+                .map(|_| PromptEvent::Add(Destination::Movies)))
+            .or(self
+                .shows_button
+                .step()
+                .map(|_| PromptEvent::Add(Destination::Shows)))
+            .or(self.dismiss_button.step().map(|_| PromptEvent::Dismiss))
+            .await;
+
+        let Some(magnet) = self.current.clone() else {
+            self.refresh().await;
+            return;
+        };
+        match event {
+            PromptEvent::Opened => self.refresh().await,
+            PromptEvent::Add(destination) => match super::add_magnet(&magnet, destination).await {
+                Ok(()) => self.refresh().await,
+                Err(e) => {
+                    log::error!("could not add magnet '{}': {e}", magnet.name);
+                    self.error_alert.set_text(e.to_string());
+                    self.error_alert.set_is_visible(true);
+                }
+            },
+            PromptEvent::Dismiss => {
+                if let Err(e) = dismiss_magnet(&magnet.info_hash).await {
+                    log::error!("could not dismiss magnet '{}': {e}", magnet.name);
+                }
+                self.refresh().await;
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Destructive commands held back for a few seconds so they can be undone.
+//!
+//! Views [`enqueue`] a [`PendingAction`] instead of invoking the command
+//! themselves.  The app shows it in the corner with a countdown and an Undo
+//! button, and only runs it, confirmed, when the countdown is over.  The
+//! queue belongs to the app rather than a tab, so switching tabs neither
+//! cancels an action nor runs it early.  It is only kept in memory: actions
+//! still counting down when the app quits never run.
+//!
+//! Actions count down one after another, and their commands run one at a
+//! time in the order they were queued, so two never race.
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::AppError;
+
+/// How long an action can be undone for.
+const COUNTDOWN_MILLIS: f64 = 5000.0;
+
+/// How long a failure stays up unless another action comes along.
+const FAILURE_MILLIS: f64 = 8000.0;
+
+const COUNTDOWN_CLASS: &str = "alert alert-dark d-flex align-items-center gap-3 mb-0";
+const FAILED_CLASS: &str = "alert alert-danger d-flex align-items-center gap-3 mb-0";
+
+type Run = Box<dyn FnOnce(bool) -> Pin<Box<dyn Future<Output = Result<(), AppError>>>>>;
+
+/// A destructive command waiting to run.
+pub struct PendingAction {
+    /// What is about to happen, e.g. "Removing 2 downloads".
+    label: String,
+    /// Invokes the command, passing on whether it is confirmed.
+    run: Run,
+    /// Told when the command has run without error.
+    done: Option<async_channel::Sender<()>>,
+}
+
+impl PendingAction {
+    /// `run` invokes the command with the `confirm` it is given; the queue
+    /// only confirms once the countdown is over.
+    pub fn new<F, Fut>(label: impl Into<String>, run: F) -> Self
+    where
+        F: FnOnce(bool) -> Fut + 'static,
+        Fut: Future<Output = Result<(), AppError>> + 'static,
+    {
+        Self {
+            label: label.into(),
+            run: Box::new(move |confirm| Box::pin(run(confirm))),
+            done: None,
+        }
+    }
+
+    /// Tell `done` once the command has run, so the view can refresh.
+    pub fn notify(mut self, done: &async_channel::Sender<()>) -> Self {
+        self.done = Some(done.clone());
+        self
+    }
+}
+
+thread_local! {
+    /// Actions waiting for the app to pick them up.
+    static QUEUE: (
+        async_channel::Sender<PendingAction>,
+        async_channel::Receiver<PendingAction>,
+    ) = async_channel::unbounded();
+}
+
+/// Queue `action` to run once it can no longer be undone.
+pub fn enqueue(action: PendingAction) {
+    QUEUE.with(|(tx, _)| {
+        let _ = tx.try_send(action);
+    });
+}
+
+/// Run the actions handed over one at a time, for the lifetime of the app,
+/// reporting the failures.  Runs apart from [`PendingActions::step`], which
+/// is dropped whenever another part of the app finishes a step first.
+fn spawn_runner(
+    actions: async_channel::Receiver<PendingAction>,
+    failures: async_channel::Sender<(String, AppError)>,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        while let Ok(action) = actions.recv().await {
+            log::info!("{}", action.label);
+            match (action.run)(true).await {
+                Ok(()) => {
+                    if let Some(done) = action.done {
+                        let _ = done.try_send(());
+                    }
+                }
+                Err(e) => {
+                    log::error!("{} failed: {e}", action.label);
+                    let _ = failures.try_send((action.label, e));
+                }
+            }
+        }
+    });
+}
+
+/// Whole seconds left of `remaining` milliseconds, rounded up.
+fn seconds_left(remaining: f64) -> u32 {
+    (remaining / 1000.0).ceil().max(0.0) as u32
+}
+
+enum PendingEvent {
+    Queued(PendingAction),
+    Undo,
+    Failed(String, AppError),
+    Tick,
+}
+
+/// The action counting down, with Undo, in the corner of the window.
+pub struct PendingActions<V: View> {
+    pub wrapper: V::Element,
+    message_text: V::Text,
+    undo_button: V::Element,
+    on_click_undo: V::EventListener,
+    /// Whether the box shows a failure rather than a countdown.
+    failed: Proxy<bool>,
+    queued: async_channel::Receiver<PendingAction>,
+    /// Hands actions whose countdown is over to the runner.
+    to_run: async_channel::Sender<PendingAction>,
+    failures: async_channel::Receiver<(String, AppError)>,
+    /// The action counting down, and when it runs, in ms since the epoch.
+    current: Option<(PendingAction, f64)>,
+    /// Actions queued behind it.
+    waiting: VecDeque<PendingAction>,
+    /// When the failure showing hides, in ms since the epoch.
+    hide_failure_at: Option<f64>,
+}
+
+impl<V: View> PendingActions<V> {
+    pub fn new() -> Self {
+        let queued = QUEUE.with(|(_, rx)| rx.clone());
+        let (to_run, actions) = async_channel::unbounded();
+        let (report, failures) = async_channel::unbounded();
+        if V::is_view::<Web>() {
+            spawn_runner(actions, report);
+        }
+        let mut failed = Proxy::new(false);
+        rsx! {
+            let wrapper = div(
+                class = "position-fixed bottom-0 start-0 p-3",
+                style:z_index = "1100",
+                style:max_width = "28rem",
+                style:display = "none",
+            ) {
+                div(
+                    class = failed(f => if *f { FAILED_CLASS } else { COUNTDOWN_CLASS }),
+                    role = "status",
+                ) {
+                    span() { let message_text = "" }
+                    let undo_button = button(
+                        class = "btn btn-outline-light btn-sm ms-auto",
+                        type = "button",
+                        on:click = on_click_undo,
+                    ) { "Undo" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            message_text,
+            undo_button,
+            on_click_undo,
+            failed,
+            queued,
+            to_run,
+            failures,
+            current: None,
+            waiting: VecDeque::new(),
+            hide_failure_at: None,
+        }
+    }
+
+    /// Show the countdown, the failure or nothing.
+    fn redraw(&mut self, now: f64) {
+        if let Some((action, run_at)) = &self.current {
+            let mut text = format!("{} in {}s", action.label, seconds_left(run_at - now));
+            if !self.waiting.is_empty() {
+                text.push_str(&format!(" \u{b7} {} more queued", self.waiting.len()));
+            }
+            self.message_text.set_text(text);
+            self.failed.set(false);
+            self.undo_button.remove_style("display");
+            self.wrapper.remove_style("display");
+        } else if self.hide_failure_at.is_some() {
+            self.failed.set(true);
+            self.undo_button.set_style("display", "none");
+            self.wrapper.remove_style("display");
+        } else {
+            self.wrapper.set_style("display", "none");
+        }
+    }
+
+    /// Start counting down the next action, if there is one and nothing
+    /// else is counting down.
+    fn start_next(&mut self, now: f64) {
+        if self.current.is_none() {
+            if let Some(next) = self.waiting.pop_front() {
+                self.current = Some((next, now + COUNTDOWN_MILLIS));
+                // A new countdown takes the place of an old failure
+                self.hide_failure_at = None;
+            }
+        }
+    }
+
+    /// Wait for an action to be queued or undone, a failure, or the
+    /// countdown to move on, and show it.
+    pub async fn step(&mut self) {
+        let now = web_sys::js_sys::Date::now();
+        // Wake when the seconds shown change, or the failure hides
+        let wake_at = match &self.current {
+            Some((_, run_at)) => {
+                let remaining = run_at - now;
+                Some(now + remaining - (seconds_left(remaining).max(1) - 1) as f64 * 1000.0)
+            }
+            None => self.hide_failure_at,
+        };
+        let tick = async {
+            match wake_at {
+                Some(at) => {
+                    mogwai::time::wait_millis((at - now).max(0.0) as _).await;
+                    PendingEvent::Tick
+                }
+                None => std::future::pending().await,
+            }
+        };
+        let queued = &self.queued;
+        let next_queued = async {
+            match queued.recv().await {
+                Ok(action) => PendingEvent::Queued(action),
+                // The app holds the sender for as long as it runs
+                Err(_) => std::future::pending().await,
+            }
+        };
+        let failures = &self.failures;
+        let next_failure = async {
+            match failures.recv().await {
+                Ok((label, e)) => PendingEvent::Failed(label, e),
+                // Outside the browser there is no runner
+                Err(_) => std::future::pending().await,
+            }
+        };
+        let event = next_queued
+            .or(next_failure)
+            .or(self.on_click_undo.next().map(|_| PendingEvent::Undo))
+            .or(tick)
+            .await;
+
+        let now = web_sys::js_sys::Date::now();
+        match event {
+            PendingEvent::Queued(action) => self.waiting.push_back(action),
+            PendingEvent::Undo => {
+                if let Some((action, _)) = self.current.take() {
+                    log::info!("Undid: {}", action.label);
+                }
+            }
+            PendingEvent::Failed(label, e) => {
+                self.message_text.set_text(format!("{label} failed: {e}"));
+                self.hide_failure_at = Some(now + FAILURE_MILLIS);
+            }
+            PendingEvent::Tick => {
+                if self.current.as_ref().is_some_and(|(_, at)| *at <= now) {
+                    if let Some((action, _)) = self.current.take() {
+                        let _ = self.to_run.try_send(action);
+                    }
+                }
+                if self.hide_failure_at.is_some_and(|at| at <= now) {
+                    self.hide_failure_at = None;
+                }
+            }
+        }
+        self.start_next(now);
+        self.redraw(now);
+    }
+}
@@ -0,0 +1,147 @@
+//! Server profiles: the navbar switcher, and the option list it shares with
+//! Settings.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, ProfileChoices};
+
+use super::invoke;
+
+async fn get_profiles() -> Result<ProfileChoices, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_profiles", &Empty {}).await
+}
+
+async fn set_active_profile(index: usize) -> Result<(), AppError> {
+    #[derive(serde::Serialize)]
+    struct Args {
+        index: usize,
+    }
+    invoke::cmd("set_active_profile", &Args { index }).await
+}
+
+/// The options of a `<select>` of profiles, valued by index.
+pub struct ProfileOptions<V: View> {
+    pub select: V::Element,
+    options: Vec<V::Element>,
+    names: Vec<String>,
+}
+
+impl<V: View> ProfileOptions<V> {
+    pub fn new(select: V::Element) -> Self {
+        Self {
+            select,
+            options: vec![],
+            names: vec![],
+        }
+    }
+
+    /// List `names`, rebuilding the options only if they changed.
+    pub fn set(&mut self, names: &[String], selected: usize) {
+        if self.names != names {
+            for option in self.options.drain(..) {
+                self.select.remove_child(&option);
+            }
+            for (index, name) in names.iter().enumerate() {
+                rsx! {
+                    let item = option(value = index.to_string()) {
+                        {name.as_str().into_text::<V>()}
+                    }
+                }
+                self.select.append_child(&item);
+                self.options.push(item);
+            }
+            self.names = names.to_vec();
+        }
+        self.select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.set_value(&selected.to_string()));
+    }
+
+    /// Follow a rename as it is typed.
+    pub fn rename(&mut self, index: usize, name: &str) {
+        let (Some(option), Some(current)) = (self.options.get(index), self.names.get_mut(index))
+        else {
+            return;
+        };
+        if current != name {
+            option.dyn_el(|option: &web_sys::HtmlOptionElement| option.set_text(name));
+            *current = name.to_string();
+        }
+    }
+
+    /// Index of the chosen profile.
+    pub fn selected(&self) -> Option<usize> {
+        self.select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| value.parse().ok())
+    }
+}
+
+/// A dropdown next to the connection dot for switching servers, hidden
+/// while there is only one.
+pub struct ProfileSwitcher<V: View> {
+    pub wrapper: V::Element,
+    options: ProfileOptions<V>,
+    on_change: V::EventListener,
+    active: usize,
+}
+
+impl<V: View> ProfileSwitcher<V> {
+    pub fn new() -> Self {
+        rsx! {
+            let wrapper = div(style:display = "none") {
+                let select = select(
+                    class = "form-select form-select-sm bg-dark text-light border-secondary",
+                    title = "Transmission server",
+                    on:change = on_change,
+                ) {}
+            }
+        }
+        Self {
+            wrapper,
+            options: ProfileOptions::new(select),
+            on_change,
+            active: 0,
+        }
+    }
+
+    /// Fetch the profiles from the backend, which Settings may have changed.
+    pub async fn refresh(&mut self) {
+        let choices = match get_profiles().await {
+            Ok(choices) => choices,
+            Err(e) => {
+                log::debug!("Failed to get server profiles: {e}");
+                return;
+            }
+        };
+        self.active = choices.active;
+        self.options.set(&choices.names, choices.active);
+        if choices.names.len() > 1 {
+            self.wrapper.remove_style("display");
+        } else {
+            self.wrapper.set_style("display", "none");
+        }
+    }
+
+    /// Wait for another profile to be chosen, and switch to it.  Returns
+    /// whether the backend switched.
+    pub async fn step(&mut self) -> bool {
+        self.on_change.next().await;
+        let Some(index) = self.options.selected() else {
+            return false;
+        };
+        if index == self.active {
+            return false;
+        }
+        match set_active_profile(index).await {
+            Ok(()) => {
+                self.active = index;
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to switch server profile: {e}");
+                self.refresh().await;
+                false
+            }
+        }
+    }
+}
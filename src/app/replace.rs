@@ -0,0 +1,55 @@
+//! Replacing a download the client keeps reporting an error for with
+//! another release of it.
+//!
+//! "Find replacement" on its row searches for the release, showing only the
+//! results at the same resolution or better.  Adding one from its details
+//! offers to hand the broken download's ledger entry over to it and remove
+//! the broken one from the client, which can be undone for a few seconds.
+use privateer_wire_types::{release_resolution, AppError, InfoHash, TransferLedgerArgs};
+
+use crate::app::{downloads, invoke};
+
+pub async fn transfer_ledger_entry(
+    old_hash: &InfoHash,
+    new_hash: &InfoHash,
+) -> Result<(), AppError> {
+    let args = TransferLedgerArgs {
+        old_hash: old_hash.clone(),
+        new_hash: new_hash.clone(),
+    };
+    invoke::cmd("transfer_ledger_entry", &args).await
+}
+
+/// A download that needs replacing, and what to search for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replacing {
+    pub info_hash: InfoHash,
+    pub name: String,
+    pub query: String,
+}
+
+impl Replacing {
+    /// The resolution a replacement needs, if the name says.
+    pub fn min_resolution(&self) -> Option<u32> {
+        release_resolution(&self.name)
+    }
+
+    /// Whether the release `name` is at least as good as the download
+    /// being replaced.  Releases that don't say are left out when the
+    /// download does.
+    pub fn accepts(&self, name: &str) -> bool {
+        match self.min_resolution() {
+            Some(min) => release_resolution(name).is_some_and(|res| res >= min),
+            None => true,
+        }
+    }
+
+    /// Move the ledger entry over to `new_hash` and remove the broken
+    /// download from the client, once `confirm`ed.
+    pub async fn replace_with(self, new_hash: InfoHash, confirm: bool) -> Result<(), AppError> {
+        if confirm {
+            transfer_ledger_entry(&self.info_hash, &new_hash).await?;
+        }
+        downloads::remove_torrents(vec![self.info_hash], confirm).await
+    }
+}
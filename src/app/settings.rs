@@ -1,21 +1,52 @@
-//! Settings view for configuring Transmission connection and copy destinations.
+//! Settings view for configuring the download client connection and copy destinations.
 use futures_lite::FutureExt;
 use iti::components::alert::Alert;
 use iti::components::button::Button;
 use iti::components::icon::IconGlyph;
 use iti::components::Flavor;
 use mogwai::{future::MogwaiFutureExt, web::prelude::*};
-use privateer_wire_types::{AppError, ErrorKind, TransmissionConfig};
+use privateer_wire_types::{
+    check_credentials, check_destination_dirs, check_dir, check_host, check_locale, normalize_dir,
+    parse_port, AppError, ClientKind, DaemonInfo, Destination, ErrorKind, NotificationConfig,
+    ServerProfile, SettingsProblem, SizeUnits, TimeOfDay, TransmissionConfig, SAVED_PASSWORD,
+};
 
+use super::format;
 use super::invoke;
+use super::layout::Density;
+use super::profiles::ProfileOptions;
+use super::theme::Theme;
 
-async fn get_transmission_config() -> Result<TransmissionConfig, AppError> {
+mod about;
+use about::AboutCard;
+mod copy_backend;
+use copy_backend::CopyBackendSection;
+mod daemon;
+use daemon::DaemonSection;
+mod logs;
+use logs::LogsSection;
+mod media_server;
+use media_server::MediaServerSection;
+mod path_mappings;
+use path_mappings::PathMappingsSection;
+mod post_copy;
+use post_copy::PostCopySection;
+mod seeding;
+use seeding::SeedingSection;
+mod statistics;
+use statistics::StatisticsCard;
+mod subscriptions;
+use subscriptions::SubscriptionsSection;
+mod webhook;
+use webhook::WebhookSection;
+
+pub(super) async fn get_transmission_config() -> Result<TransmissionConfig, AppError> {
     #[derive(serde::Serialize)]
     struct Empty {}
     invoke::cmd("get_transmission_config", &Empty {}).await
 }
 
-async fn set_transmission_config(config: &TransmissionConfig) -> Result<(), AppError> {
+pub(super) async fn set_transmission_config(config: &TransmissionConfig) -> Result<(), AppError> {
     #[derive(serde::Serialize)]
     struct Wrapper {
         config: TransmissionConfig,
@@ -29,28 +60,186 @@ async fn set_transmission_config(config: &TransmissionConfig) -> Result<(), AppE
     .await
 }
 
-async fn test_transmission_connection() -> Result<String, AppError> {
+pub(super) async fn test_transmission_connection() -> Result<DaemonInfo, AppError> {
     #[derive(serde::Serialize)]
     struct Empty {}
     invoke::cmd("test_transmission_connection", &Empty {}).await
 }
 
+/// Ask for a directory with the native picker, starting in `start`.
+/// Returns `None` if the picker was cancelled.
+pub(super) async fn pick_directory(start: &str) -> Result<Option<String>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        start: Option<&'a str>,
+    }
+    let start = Some(start).filter(|start| !start.is_empty());
+    invoke::cmd("pick_directory", &Args { start }).await
+}
+
+/// What a connection test found, as a warning if the client is too old
+/// for some features.
+pub(super) fn connection_success(daemon: &DaemonInfo) -> (String, Flavor) {
+    let connected = format!("Connected to {}.", daemon.describe());
+    let limitations = daemon.limitations();
+    if limitations.is_empty() {
+        (connected, Flavor::Success)
+    } else {
+        (
+            format!("{connected} {}.", limitations.join("; ")),
+            Flavor::Warning,
+        )
+    }
+}
+
+/// Why a connection test failed, with what to check.
+pub(super) fn connection_failure(e: &AppError) -> String {
+    match e.kind {
+        ErrorKind::TransmissionConnection | ErrorKind::Timeout => format!(
+            "Connection failed: {}. \
+             Make sure the client is running and remote access is enabled: \
+             Preferences \u{203a} Remote in Transmission, or the Web UI in qBittorrent.",
+            e.message
+        ),
+        ErrorKind::TransmissionAuth => {
+            "Authentication failed \u{2014} check the username and password.".to_string()
+        }
+        _ => format!("Connection failed: {e}"),
+    }
+}
+
+const NO_PASSWORD_PLACEHOLDER: &str = "Leave blank if no auth";
+const SAVED_PASSWORD_PLACEHOLDER: &str =
+    "\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}\u{2022} saved";
+
+/// A message under a settings field, hidden while the field is fine.
+struct Feedback<V: View> {
+    wrapper: V::Element,
+    text: V::Text,
+}
+
+impl<V: View> Feedback<V> {
+    /// Show the problem, if any.  Errors also mark the inputs invalid;
+    /// warnings only show the message.
+    fn show(&self, inputs: &[&V::Element], problem: Option<SettingsProblem>) {
+        let is_error = problem.is_some_and(|problem| problem.is_error());
+        for input in inputs {
+            input.set_property(
+                "class",
+                if is_error {
+                    "form-control is-invalid"
+                } else {
+                    "form-control"
+                },
+            );
+        }
+        match problem {
+            Some(problem) => {
+                self.text.set_text(problem.message());
+                self.wrapper.set_property(
+                    "class",
+                    if is_error {
+                        "invalid-feedback d-block"
+                    } else {
+                        "form-text text-warning"
+                    },
+                );
+                self.wrapper.remove_style("display");
+            }
+            None => self.wrapper.set_style("display", "none"),
+        }
+    }
+}
+
 /// Settings view for configuring Transmission RPC connection and copy destinations.
 #[derive(ViewChild)]
 pub struct SettingsView<V: View> {
     #[child]
     wrapper: V::Element,
+    on_input: V::EventListener,
+    /// The config as last loaded or saved, read back from the form, to tell
+    /// whether there are unsaved changes.
+    saved: Option<TransmissionConfig>,
+    /// Whether leaving the tab with unsaved changes awaits confirmation.
+    leaving: Proxy<bool>,
+    on_click_discard: V::EventListener,
+    on_click_stay: V::EventListener,
+    /// Every server profile, with the shown one as it was when another was
+    /// last chosen.  The form's fields hold the shown one as edited.
+    profiles: Vec<ServerProfile>,
+    /// Index of the profile shown, which becomes the active one on saving.
+    shown_profile: usize,
+    profile_options: ProfileOptions<V>,
+    on_click_add_profile: V::EventListener,
+    on_click_delete_profile: V::EventListener,
+    profile_name_input: V::Element,
+    profile_name_feedback: Feedback<V>,
+    client_select: V::Element,
     host_input: V::Element,
+    host_feedback: Feedback<V>,
     port_input: V::Element,
+    port_feedback: Feedback<V>,
     username_input: V::Element,
     password_input: V::Element,
+    /// A password is saved and nothing has been typed over it, so saving
+    /// keeps it.
+    password_untouched: bool,
+    password_shown: bool,
+    password_toggle_text: V::Text,
+    on_click_password_toggle: V::EventListener,
+    credentials_feedback: Feedback<V>,
+    rpc_timeout_input: V::Element,
     movies_dir_input: V::Element,
+    movies_dir_feedback: Feedback<V>,
     shows_dir_input: V::Element,
+    shows_dir_feedback: Feedback<V>,
+    on_click_browse_movies: V::EventListener,
+    on_click_browse_shows: V::EventListener,
+    movies_expect_mount_input: V::Element,
+    clean_movie_names_input: V::Element,
+    shows_expect_mount_input: V::Element,
+    rename_episodes_input: V::Element,
+    episode_template_input: V::Element,
+    stall_minutes_input: V::Element,
+    dead_after_hours_input: V::Element,
+    magnet_destination_select: V::Element,
+    notify_download_input: V::Element,
+    notify_copied_input: V::Element,
+    notify_failed_input: V::Element,
+    close_to_tray_input: V::Element,
+    theme_select: V::Element,
+    on_change_theme: V::EventListener,
+    density_select: V::Element,
+    on_change_density: V::EventListener,
+    locale_input: V::Element,
+    locale_feedback: Feedback<V>,
+    size_units_select: V::Element,
+    rsync_input: V::Element,
+    only_assigned_input: V::Element,
+    quiet_hours_input: V::Element,
+    quiet_start_input: V::Element,
+    quiet_end_input: V::Element,
+    copy_stall_minutes_input: V::Element,
     save_button: Button<V>,
     test_button: Button<V>,
     on_click_save: V::EventListener,
     on_click_test: V::EventListener,
     status_alert: Alert<V>,
+    retry_button: V::Element,
+    on_click_retry: V::EventListener,
+    /// The save or test that failed in a way worth retrying.
+    retry: Option<SettingsAction>,
+    copy_backend: CopyBackendSection<V>,
+    daemon: DaemonSection<V>,
+    path_mappings: PathMappingsSection<V>,
+    seeding: SeedingSection<V>,
+    media_server: MediaServerSection<V>,
+    webhook: WebhookSection<V>,
+    post_copy: PostCopySection<V>,
+    subscriptions: SubscriptionsSection<V>,
+    statistics: StatisticsCard<V>,
+    logs: LogsSection<V>,
+    about: AboutCard<V>,
 }
 
 impl<V: View> Default for SettingsView<V> {
@@ -64,9 +253,78 @@ impl<V: View> Default for SettingsView<V> {
         let mut test_button = Button::new("Test Connection", Some(Flavor::Secondary));
         test_button.get_icon_mut().set_glyph(IconGlyph::Globe);
 
+        let copy_backend = CopyBackendSection::default();
+        let daemon = DaemonSection::default();
+        let path_mappings = PathMappingsSection::default();
+        let seeding = SeedingSection::default();
+        let media_server = MediaServerSection::default();
+        let webhook = WebhookSection::default();
+        let post_copy = PostCopySection::default();
+        let subscriptions = SubscriptionsSection::default();
+        let statistics = StatisticsCard::default();
+        let logs = LogsSection::default();
+        let about = AboutCard::default();
+        let mut leaving = Proxy::new(false);
+
         rsx! {
-            let wrapper = div(class = "container-fluid") {
-                h5(class = "mb-3") { "Transmission Settings" }
+            let wrapper = div(class = "container-fluid", on:input = on_input) {
+                div(
+                    class = "sticky-top",
+                    style:display = leaving(l => if *l { "" } else { "none" }),
+                ) {
+                    div(class = "alert alert-warning d-flex align-items-center gap-2") {
+                        span(class = "me-auto") { "Settings have unsaved changes." }
+                        button(
+                            class = "btn btn-outline-secondary btn-sm",
+                            type = "button",
+                            on:click = on_click_stay,
+                        ) { "Stay" }
+                        button(
+                            class = "btn btn-warning btn-sm",
+                            type = "button",
+                            on:click = on_click_discard,
+                        ) { "Discard changes" }
+                    }
+                }
+                h5(class = "mb-3") { "Download Client Settings" }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Server profile" }
+                    div(class = "input-group") {
+                        let profile_select = select(class = "form-select") {}
+                        button(
+                            class = "btn btn-outline-secondary",
+                            type = "button",
+                            on:click = on_click_add_profile,
+                        ) { "Add" }
+                        button(
+                            class = "btn btn-outline-danger",
+                            type = "button",
+                            on:click = on_click_delete_profile,
+                        ) { "Delete" }
+                    }
+                    div(class = "form-text") {
+                        "The profile shown is the one used once saved. \
+                         Switch between them from the navbar too."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Profile name" }
+                    let profile_name_input = input(class = "form-control", type = "text"){}
+                    let profile_name_feedback = div(style:display = "none") {
+                        let profile_name_feedback_text = ""
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Client" }
+                    let client_select = select(class = "form-select") {
+                        option(value = "Transmission") { "Transmission" }
+                        option(value = "qBittorrent") { "qBittorrent" }
+                    }
+                    div(class = "form-text") {
+                        "qBittorrent needs its Web UI enabled. \
+                         Daemon preferences are only available with Transmission."
+                    }
+                }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Host" }
                     let host_input = input(
@@ -75,6 +333,7 @@ impl<V: View> Default for SettingsView<V> {
                         value = "localhost",
                         placeholder = "localhost",
                     ){}
+                    let host_feedback = div(style:display = "none") { let host_feedback_text = "" }
                 }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Port" }
@@ -84,6 +343,7 @@ impl<V: View> Default for SettingsView<V> {
                         value = "9091",
                         placeholder = "9091",
                     ){}
+                    let port_feedback = div(style:display = "none") { let port_feedback_text = "" }
                 }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Username (optional)" }
@@ -95,33 +355,347 @@ impl<V: View> Default for SettingsView<V> {
                 }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Password (optional)" }
-                    let password_input = input(
+                    div(class = "input-group") {
+                        let password_input = input(
+                            class = "form-control",
+                            type = "password",
+                            placeholder = NO_PASSWORD_PLACEHOLDER,
+                        ){}
+                        button(
+                            class = "btn btn-outline-secondary",
+                            type = "button",
+                            title = "Show or hide the password",
+                            on:click = on_click_password_toggle,
+                        ) { let password_toggle_text = "Show" }
+                    }
+                    let credentials_feedback = div(style:display = "none") {
+                        let credentials_feedback_text = ""
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "RPC Timeout (seconds)" }
+                    let rpc_timeout_input = input(
                         class = "form-control",
-                        type = "password",
-                        placeholder = "Leave blank if no auth",
+                        type = "number",
+                        min = "1",
+                        value = "10",
+                        placeholder = "10",
                     ){}
+                    div(class = "form-text") {
+                        "Raise this for a remote seedbox on a slow link."
+                    }
                 }
+                {&daemon}
                 h5(class = "mb-3 mt-4") { "Copy Destinations" }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Movies Directory" }
-                    let movies_dir_input = input(
-                        class = "form-control",
-                        type = "text",
-                        placeholder = "/Volumes/Media/Movies",
-                    ){}
+                    div(class = "input-group") {
+                        let movies_dir_input = input(
+                            class = "form-control",
+                            type = "text",
+                            placeholder = "/Volumes/Media/Movies",
+                        ){}
+                        button(
+                            class = "btn btn-outline-secondary",
+                            type = "button",
+                            on:click = on_click_browse_movies,
+                        ) { "Browse\u{2026}" }
+                    }
+                    let movies_dir_feedback = div(style:display = "none") {
+                        let movies_dir_feedback_text = ""
+                    }
                     div(class = "form-text") {
                         "Completed movie torrents will be copied here."
                     }
+                    div(class = "form-check mt-1") {
+                        label(class = "form-check-label") {
+                            let movies_expect_mount_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "On a mounted share \u{2014} don't copy while it isn't mounted"
+                        }
+                    }
                 }
                 div(class = "mb-3") {
                     label(class = "form-label") { "Shows Directory" }
-                    let shows_dir_input = input(
+                    div(class = "input-group") {
+                        let shows_dir_input = input(
+                            class = "form-control",
+                            type = "text",
+                            placeholder = "/Volumes/Media/TV Shows",
+                        ){}
+                        button(
+                            class = "btn btn-outline-secondary",
+                            type = "button",
+                            on:click = on_click_browse_shows,
+                        ) { "Browse\u{2026}" }
+                    }
+                    let shows_dir_feedback = div(style:display = "none") {
+                        let shows_dir_feedback_text = ""
+                    }
+                    div(class = "form-text") {
+                        "Completed TV show torrents will be copied here."
+                    }
+                    div(class = "form-check mt-1") {
+                        label(class = "form-check-label") {
+                            let shows_expect_mount_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "On a mounted share \u{2014} don't copy while it isn't mounted"
+                        }
+                    }
+                }
+                h5(class = "mb-3 mt-4") { "Copying" }
+                p(class = "form-text mt-0") {
+                    "How completed downloads get to the destinations. Changes \
+                     apply to the next copy as soon as they're saved."
+                }
+                h6(class = "mb-2") { "Source" }
+                {&copy_backend}
+                h6(class = "mb-2") { "Torrents" }
+                div(class = "form-check mb-3") {
+                    label(class = "form-check-label") {
+                        let only_assigned_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                        ){}
+                        "Only manage torrents I explicitly assign"
+                    }
+                    div(class = "form-text") {
+                        "Torrents found already in a library aren't added to \
+                         the ledger, so ones other tools manage are left alone."
+                    }
+                }
+                h6(class = "mb-2") { "Method" }
+                div(class = "form-check mb-3") {
+                    label(class = "form-check-label") {
+                        let rsync_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                        ){}
+                        "Copy with rsync"
+                    }
+                    div(class = "form-text") {
+                        "Resumes large copies interrupted part way through. \
+                         Needs rsync 3.1 or newer; not used over SFTP."
+                    }
+                }
+                h6(class = "mb-2") { "Naming" }
+                div(class = "mb-3") {
+                    div(class = "form-check") {
+                        label(class = "form-check-label") {
+                            let clean_movie_names_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "Clean movie names \u{2014} copy into a \"Title (Year)\" folder"
+                        }
+                    }
+                    div(class = "form-text mt-0 mb-2") {
+                        "Media servers match a tidy folder name more reliably \
+                         than a release name."
+                    }
+                    div(class = "form-check") {
+                        label(class = "form-check-label") {
+                            let rename_episodes_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "Rename episode files"
+                        }
+                    }
+                    let episode_template_input = input(
+                        class = "form-control form-control-sm mt-1",
+                        type = "text",
+                        placeholder = "{show} - S{season:02}E{episode:02}",
+                    ){}
+                    div(class = "form-text") {
+                        "Tokens: {show}, {season}, {episode} and {quality}; \
+                         {season:02} pads to two digits. Subtitles named after \
+                         a video are renamed with it."
+                    }
+                }
+                h6(class = "mb-2") { "Schedule" }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let quiet_hours_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                        ){}
+                        "Quiet hours"
+                    }
+                }
+                div(class = "input-group input-group-sm mb-1", style:width = "auto") {
+                    span(class = "input-group-text") { "From" }
+                    let quiet_start_input = input(
+                        class = "form-control",
+                        type = "time",
+                        value = "21:00",
+                    ){}
+                    span(class = "input-group-text") { "until" }
+                    let quiet_end_input = input(
+                        class = "form-control",
+                        type = "time",
+                        value = "23:00",
+                    ){}
+                }
+                div(class = "form-text mb-3") {
+                    "No new copies start between these local times, so they don't \
+                     compete with streaming from the destination. A copy already \
+                     running finishes, and \"Copy now\" in Downloads still copies."
+                }
+                h6(class = "mb-2") { "Stalled copies" }
+                div(class = "input-group input-group-sm mb-1", style:width = "auto") {
+                    span(class = "input-group-text") { "Give up after" }
+                    let copy_stall_minutes_input = input(
+                        class = "form-control",
+                        type = "number",
+                        min = "0",
+                        value = "10",
+                    ){}
+                    span(class = "input-group-text") { "minutes without progress" }
+                }
+                div(class = "form-text mb-3") {
+                    "A copy that writes nothing for this long, e.g. to a share that \
+                     stopped responding, is marked failed so the next download can \
+                     be copied. 0 waits forever."
+                }
+                h5(class = "mb-3 mt-4") { "Seeding" }
+                {&seeding}
+                h5(class = "mb-3 mt-4") { "Path Mappings" }
+                {&path_mappings}
+                h5(class = "mb-3 mt-4") { "Media Server" }
+                {&media_server}
+                h5(class = "mb-3 mt-4") { "Webhook" }
+                {&webhook}
+                h5(class = "mb-3 mt-4") { "After Each Copy" }
+                {&post_copy}
+                h5(class = "mb-3 mt-4") { "Downloads" }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Stall Timeout (minutes)" }
+                    let stall_minutes_input = input(
+                        class = "form-control",
+                        type = "number",
+                        min = "1",
+                        value = "10",
+                        placeholder = "10",
+                    ){}
+                    div(class = "form-text") {
+                        "Downloads with no peers sending data for this long are flagged as stalled."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Likely Dead After (hours)" }
+                    let dead_after_hours_input = input(
+                        class = "form-control",
+                        type = "number",
+                        min = "0",
+                        value = "24",
+                        placeholder = "24",
+                    ){}
+                    div(class = "form-text") {
+                        "Downloads with no peers sending data for this long are flagged as likely dead, with a search for another copy. 0 never flags them."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Magnet Links Opened in Privateer" }
+                    let magnet_destination_select = select(class = "form-select") {
+                        option(value = "", selected = "") { "Ask for a destination" }
+                        option(value = "Movies") { "Add to Movies" }
+                        option(value = "Shows") { "Add to Shows" }
+                    }
+                    div(class = "form-text") {
+                        "For magnet links clicked in a browser or another app."
+                    }
+                }
+                h5(class = "mb-3 mt-4") { "Feed Subscriptions" }
+                {&subscriptions}
+                h5(class = "mb-3 mt-4") { "Notifications" }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let notify_download_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Download complete"
+                    }
+                }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let notify_copied_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Copied to destination"
+                    }
+                }
+                div(class = "form-check mb-3") {
+                    label(class = "form-check-label") {
+                        let notify_failed_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Copy failed"
+                    }
+                }
+                h5(class = "mb-3 mt-4") { "Window" }
+                div(class = "form-check mb-3") {
+                    label(class = "form-check-label") {
+                        let close_to_tray_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Keep running in the tray when the window is closed"
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Theme" }
+                    let theme_select = select(class = "form-select", on:change = on_change_theme) {
+                        option(value = "System") { "Same as the system" }
+                        option(value = "Light") { "Light" }
+                        option(value = "Dark") { "Dark" }
+                    }
+                    div(class = "form-text") {
+                        "Applies straight away, on this computer only."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Row density" }
+                    let density_select = select(class = "form-select", on:change = on_change_density) {
+                        option(value = "Comfortable") { "Comfortable" }
+                        option(value = "Compact") { "Compact" }
+                    }
+                    div(class = "form-text") {
+                        "How much room each row of the tables gets. Applies straight away, on this computer only."
+                    }
+                }
+                h5(class = "mb-3 mt-4") { "Formats" }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Locale" }
+                    let locale_input = input(
                         class = "form-control",
                         type = "text",
-                        placeholder = "/Volumes/Media/TV Shows",
+                        placeholder = "Same as the system",
                     ){}
+                    let locale_feedback = div(style:display = "none") { let locale_feedback_text = "" }
                     div(class = "form-text") {
-                        "Completed TV show torrents will be copied here."
+                        "How dates and times are written, e.g. en-GB or de-DE."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Sizes" }
+                    let size_units_select = select(class = "form-select") {
+                        option(value = "SI") { "SI: 1 kB is 1000 bytes" }
+                        option(value = "Binary") { "Binary: 1 KiB is 1024 bytes" }
+                    }
+                    div(class = "form-text") {
+                        "Used in exported downloads lists too."
                     }
                 }
                 div(class = "d-flex gap-2") {
@@ -134,71 +708,480 @@ impl<V: View> Default for SettingsView<V> {
                 }
                 div(class = "mb-3 mt-2") {
                     {&status_alert}
+                    let retry_button = button(
+                        class = "btn btn-outline-danger btn-sm",
+                        type = "button",
+                        style:display = "none",
+                        on:click = on_click_retry,
+                    ) { "Retry" }
                 }
+                h5(class = "mb-3 mt-4") { "Statistics" }
+                {&statistics}
+                h5(class = "mb-3 mt-4") { "Logs" }
+                {&logs}
+                h5(class = "mb-3 mt-4") { "About" }
+                {&about}
             }
         }
+        theme_select.dyn_el(|select: &web_sys::HtmlSelectElement| {
+            select.set_value(Theme::load().label());
+        });
+        density_select.dyn_el(|select: &web_sys::HtmlSelectElement| {
+            select.set_value(Density::load().label());
+        });
         Self {
             wrapper,
+            on_input,
+            saved: None,
+            leaving,
+            on_click_discard,
+            on_click_stay,
+            profiles: vec![ServerProfile::default()],
+            shown_profile: 0,
+            profile_options: ProfileOptions::new(profile_select),
+            on_click_add_profile,
+            on_click_delete_profile,
+            profile_name_input,
+            profile_name_feedback: Feedback {
+                wrapper: profile_name_feedback,
+                text: profile_name_feedback_text,
+            },
+            client_select,
             host_input,
+            host_feedback: Feedback {
+                wrapper: host_feedback,
+                text: host_feedback_text,
+            },
             port_input,
+            port_feedback: Feedback {
+                wrapper: port_feedback,
+                text: port_feedback_text,
+            },
             username_input,
             password_input,
+            password_untouched: false,
+            password_shown: false,
+            password_toggle_text,
+            on_click_password_toggle,
+            credentials_feedback: Feedback {
+                wrapper: credentials_feedback,
+                text: credentials_feedback_text,
+            },
+            rpc_timeout_input,
             movies_dir_input,
+            movies_dir_feedback: Feedback {
+                wrapper: movies_dir_feedback,
+                text: movies_dir_feedback_text,
+            },
             shows_dir_input,
+            shows_dir_feedback: Feedback {
+                wrapper: shows_dir_feedback,
+                text: shows_dir_feedback_text,
+            },
+            on_click_browse_movies,
+            on_click_browse_shows,
+            movies_expect_mount_input,
+            clean_movie_names_input,
+            shows_expect_mount_input,
+            rename_episodes_input,
+            episode_template_input,
+            stall_minutes_input,
+            dead_after_hours_input,
+            magnet_destination_select,
+            notify_download_input,
+            notify_copied_input,
+            notify_failed_input,
+            close_to_tray_input,
+            theme_select,
+            on_change_theme,
+            density_select,
+            on_change_density,
+            locale_input,
+            locale_feedback: Feedback {
+                wrapper: locale_feedback,
+                text: locale_feedback_text,
+            },
+            size_units_select,
+            rsync_input,
+            only_assigned_input,
+            quiet_hours_input,
+            quiet_start_input,
+            quiet_end_input,
+            copy_stall_minutes_input,
             save_button,
             test_button,
             on_click_save,
             on_click_test,
             status_alert,
+            retry_button,
+            on_click_retry,
+            retry: None,
+            copy_backend,
+            daemon,
+            path_mappings,
+            seeding,
+            media_server,
+            webhook,
+            post_copy,
+            subscriptions,
+            statistics,
+            logs,
+            about,
         }
     }
 }
 
+#[derive(Clone, Copy)]
 enum SettingsAction {
+    /// A field was edited.
+    Validate,
+    /// Show or hide the password.
+    TogglePassword,
+    AddProfile,
+    DeleteProfile,
+    /// Another theme was chosen.
+    Theme,
+    /// Another row density was chosen.
+    Density,
     Save,
     Test,
+    /// Run the save or test that failed again.
+    Retry,
+    /// Pick the directory of a destination.
+    Browse(Destination),
+    TestMediaServer,
+    TestWebhook,
+    TestPostCopyHook,
+    Daemon,
+    PathMappings,
+    Subscriptions,
+    Statistics,
+    Logs,
+    About,
 }
 
 impl<V: View> SettingsView<V> {
-    fn read_config(&self) -> TransmissionConfig {
-        let host = self
-            .host_input
-            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
-            .unwrap_or_else(|| "localhost".into());
-        let port_str = self
-            .port_input
-            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
-            .unwrap_or_else(|| "9091".into());
-        let port: u16 = port_str.parse().unwrap_or(9091);
-        let username = self
-            .username_input
-            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
-            .unwrap_or_default();
-        let password = self
-            .password_input
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    /// Check the fields as typed, flagging the ones with problems.  Save and
+    /// Test Connection are disabled while any field is invalid.
+    fn validate(&mut self) {
+        // Choosing a profile is an input event too
+        if let Some(index) = self.profile_options.selected() {
+            if index != self.shown_profile && index < self.profiles.len() {
+                self.stash_profile();
+                self.show_profile(index);
+            }
+        }
+        // Switching clients moves the port along if it was the old default
+        let client = self.selected_client();
+        if let Some(profile) = self.profiles.get_mut(self.shown_profile) {
+            if profile.client != client {
+                let port = Self::value(&self.port_input);
+                if port.trim() == profile.client.default_port().to_string() {
+                    self.port_input.dyn_el(|input: &web_sys::HtmlInputElement| {
+                        input.set_value(&client.default_port().to_string())
+                    });
+                }
+                profile.client = client;
+            }
+        }
+        if !Self::value(&self.password_input).is_empty() {
+            self.password_untouched = false;
+        }
+        let name = Self::value(&self.profile_name_input);
+        let name = name.trim();
+        self.profile_options.rename(self.shown_profile, name);
+        let profile_name = if name.is_empty() {
+            Some(SettingsProblem::ProfileName)
+        } else if self.profiles.iter().enumerate().any(|(i, profile)| {
+            i != self.shown_profile && profile.name.trim().eq_ignore_ascii_case(name)
+        }) {
+            Some(SettingsProblem::DuplicateProfileName)
+        } else {
+            None
+        };
+        let host = check_host(&Self::value(&self.host_input)).err();
+        let port = parse_port(&Self::value(&self.port_input)).err();
+        let credentials =
+            check_credentials(&Self::value(&self.username_input), &self.password()).err();
+        let dir = |el: &V::Element| {
+            let dir = Self::value(el);
+            if dir.trim().is_empty() {
+                None
+            } else {
+                check_dir(&dir).err()
+            }
+        };
+        let movies_dir = dir(&self.movies_dir_input);
+        let shows_dir = dir(&self.shows_dir_input);
+        let overlap = check_destination_dirs(
+            &Self::value(&self.movies_dir_input),
+            &Self::value(&self.shows_dir_input),
+        )
+        .err();
+        let locale = Self::value(&self.locale_input);
+        let locale = if locale.trim().is_empty() {
+            None
+        } else {
+            check_locale(&locale).err()
+        };
+
+        self.profile_name_feedback
+            .show(&[&self.profile_name_input], profile_name);
+        self.host_feedback.show(&[&self.host_input], host);
+        self.port_feedback.show(&[&self.port_input], port);
+        self.credentials_feedback
+            .show(&[&self.username_input, &self.password_input], credentials);
+        self.movies_dir_feedback
+            .show(&[&self.movies_dir_input], movies_dir);
+        self.shows_dir_feedback
+            .show(&[&self.shows_dir_input], shows_dir.or(overlap));
+        self.locale_feedback.show(&[&self.locale_input], locale);
+
+        let is_valid = [
+            profile_name,
+            host,
+            port,
+            credentials,
+            movies_dir,
+            shows_dir,
+            overlap,
+            locale,
+        ]
+        .into_iter()
+        .flatten()
+        .all(|problem| !problem.is_error());
+        for button in [&mut self.save_button, &mut self.test_button] {
+            if is_valid {
+                button.enable();
+            } else {
+                button.disable();
+            }
+        }
+    }
+
+    /// The password to save: [`SAVED_PASSWORD`] to keep the saved one, empty
+    /// to clear it, or a new one.  Clearing the username clears a saved
+    /// password too.
+    fn password(&self) -> String {
+        let typed = Self::value(&self.password_input);
+        if self.password_untouched
+            && typed.is_empty()
+            && !Self::value(&self.username_input).is_empty()
+        {
+            SAVED_PASSWORD.to_string()
+        } else {
+            typed
+        }
+    }
+
+    /// Empty the password field, marking whether a password is saved.
+    fn reset_password(&mut self, is_saved: bool) {
+        self.password_untouched = is_saved;
+        self.password_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value("");
+                input.set_placeholder(if is_saved {
+                    SAVED_PASSWORD_PLACEHOLDER
+                } else {
+                    NO_PASSWORD_PLACEHOLDER
+                });
+            });
+    }
+
+    fn selected_client(&self) -> ClientKind {
+        self.client_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| ClientKind::from_label(&value))
+            .unwrap_or_default()
+    }
+
+    /// The shown profile as edited.  An invalid port is left as 0 for
+    /// [`TransmissionConfig::validate`] to point out.
+    fn edited_profile(&self) -> ServerProfile {
+        let non_empty = |value: String| (!value.is_empty()).then_some(value);
+        ServerProfile {
+            id: self.profiles.get(self.shown_profile).map_or(0, |p| p.id),
+            name: Self::value(&self.profile_name_input).trim().to_string(),
+            client: self.selected_client(),
+            host: Self::value(&self.host_input).trim().to_string(),
+            port: parse_port(&Self::value(&self.port_input)).unwrap_or(0),
+            username: non_empty(Self::value(&self.username_input)),
+            password: non_empty(self.password()),
+        }
+    }
+
+    /// Keep the edits to the shown profile before showing another.
+    fn stash_profile(&mut self) {
+        let edited = self.edited_profile();
+        if let Some(profile) = self.profiles.get_mut(self.shown_profile) {
+            *profile = edited;
+        }
+    }
+
+    /// Fill the connection fields from a profile.
+    fn show_profile(&mut self, index: usize) {
+        let Some(profile) = self.profiles.get(index).cloned() else {
+            return;
+        };
+        self.shown_profile = index;
+        let names: Vec<String> = self.profiles.iter().map(|p| p.name.clone()).collect();
+        self.profile_options.set(&names, index);
+        let set_value = |el: &V::Element, value: &str| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(value));
+        };
+        set_value(&self.profile_name_input, &profile.name);
+        self.client_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.set_value(profile.client.label()));
+        set_value(&self.host_input, &profile.host);
+        set_value(&self.port_input, &profile.port.to_string());
+        set_value(
+            &self.username_input,
+            profile.username.as_deref().unwrap_or(""),
+        );
+        // The backend sends SAVED_PASSWORD in place of a saved password
+        match profile.password.as_deref() {
+            Some(SAVED_PASSWORD) => self.reset_password(true),
+            Some(typed) => {
+                self.reset_password(false);
+                set_value(&self.password_input, typed);
+            }
+            None => self.reset_password(false),
+        }
+    }
+
+    fn add_profile(&mut self) {
+        self.stash_profile();
+        let id = self.profiles.iter().map(|p| p.id + 1).max().unwrap_or(0);
+        let number = self.profiles.len() + 1;
+        self.profiles.push(ServerProfile {
+            id,
+            name: format!("Server {number}"),
+            ..Default::default()
+        });
+        self.show_profile(self.profiles.len() - 1);
+        self.validate();
+    }
+
+    fn delete_profile(&mut self) {
+        if self.profiles.len() < 2 {
+            self.status_alert
+                .set_text("Keep at least one server profile.");
+            self.status_alert.set_flavor(Flavor::Warning);
+            self.status_alert.set_is_visible(true);
+            return;
+        }
+        self.profiles.remove(self.shown_profile);
+        self.show_profile(self.shown_profile.min(self.profiles.len() - 1));
+        self.validate();
+    }
+
+    fn toggle_password(&mut self) {
+        self.password_shown = !self.password_shown;
+        let (input_type, label) = if self.password_shown {
+            ("text", "Hide")
+        } else {
+            ("password", "Show")
+        };
+        self.password_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_type(input_type));
+        self.password_toggle_text.set_text(label);
+    }
+
+    /// The config in the form, or the first problem with it.
+    fn read_config(&self) -> Result<TransmissionConfig, SettingsProblem> {
+        let mut profiles = self.profiles.clone();
+        if let Some(profile) = profiles.get_mut(self.shown_profile) {
+            *profile = self.edited_profile();
+        }
+        let rpc_timeout_str = self
+            .rpc_timeout_input
             .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
             .unwrap_or_default();
+        let rpc_timeout_secs = rpc_timeout_str
+            .parse()
+            .unwrap_or(TransmissionConfig::default().rpc_timeout_secs);
         let movies_dir = self
             .movies_dir_input
-            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .dyn_el(|input: &web_sys::HtmlInputElement| normalize_dir(&input.value()))
             .unwrap_or_default();
         let shows_dir = self
             .shows_dir_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| normalize_dir(&input.value()))
+            .unwrap_or_default();
+        let stall_minutes_str = self
+            .stall_minutes_input
             .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
             .unwrap_or_default();
-        TransmissionConfig {
-            host,
-            port,
-            username: if username.is_empty() {
-                None
-            } else {
-                Some(username)
-            },
-            password: if password.is_empty() {
-                None
-            } else {
-                Some(password)
-            },
+        let stall_minutes = stall_minutes_str
+            .parse()
+            .unwrap_or(TransmissionConfig::default().stall_minutes);
+        let copy_stall_minutes = self
+            .copy_stall_minutes_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .and_then(|minutes| minutes.trim().parse().ok())
+            .unwrap_or(TransmissionConfig::default().copy_stall_minutes);
+        let dead_after_hours = self
+            .dead_after_hours_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .and_then(|hours| hours.trim().parse().ok())
+            .unwrap_or(TransmissionConfig::default().dead_after_hours);
+        let is_checked = |el: &V::Element| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+                .unwrap_or(true)
+        };
+        let notifications = NotificationConfig {
+            download_complete: is_checked(&self.notify_download_input),
+            copy_complete: is_checked(&self.notify_copied_input),
+            copy_failed: is_checked(&self.notify_failed_input),
+        };
+        let close_to_tray = is_checked(&self.close_to_tray_input);
+        let is_opted_in = |el: &V::Element| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+                .unwrap_or_default()
+        };
+        let movies_expect_mount = is_opted_in(&self.movies_expect_mount_input);
+        let shows_expect_mount = is_opted_in(&self.shows_expect_mount_input);
+        let copy_with_rsync = is_opted_in(&self.rsync_input);
+        let only_assigned = is_opted_in(&self.only_assigned_input);
+        let time = |el: &V::Element| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+                .and_then(|value| TimeOfDay::parse(&value))
+        };
+        let quiet_hours = is_opted_in(&self.quiet_hours_input)
+            .then(|| Some((time(&self.quiet_start_input)?, time(&self.quiet_end_input)?)))
+            .flatten();
+        let clean_movie_names = is_opted_in(&self.clean_movie_names_input);
+        let rename_episodes = is_opted_in(&self.rename_episodes_input);
+        let episode_template = self
+            .episode_template_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .map(|template| template.trim().to_string())
+            .filter(|template| !template.is_empty())
+            .unwrap_or_else(TransmissionConfig::default_episode_template);
+        let (movies_seed_limits, shows_seed_limits) = self.seeding.read()?;
+        let magnet_destination = self
+            .magnet_destination_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| {
+                Destination::ALL
+                    .into_iter()
+                    .find(|dest| dest.label() == value)
+            });
+        let locale = Some(Self::value(&self.locale_input).trim().to_string())
+            .filter(|locale| !locale.is_empty());
+        let size_units = self
+            .size_units_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| SizeUnits::ALL.into_iter().find(|u| u.label() == value))
+            .unwrap_or_default();
+        let config = TransmissionConfig {
+            profiles,
+            active_profile: self.shown_profile,
             movies_dir: if movies_dir.is_empty() {
                 None
             } else {
@@ -209,23 +1192,111 @@ impl<V: View> SettingsView<V> {
             } else {
                 Some(shows_dir)
             },
+            stall_minutes,
+            dead_after_hours,
+            notifications,
+            close_to_tray,
+            movies_expect_mount,
+            shows_expect_mount,
+            rpc_timeout_secs,
+            media_server: self.media_server.read(),
+            webhook: self.webhook.read(),
+            post_copy_command: self.post_copy.read(),
+            path_mappings: self.path_mappings.read(),
+            copy_backend: self.copy_backend.read(),
+            copy_with_rsync,
+            clean_movie_names,
+            rename_episodes,
+            episode_template,
+            magnet_destination,
+            subscriptions: self.subscriptions.read(),
+            feed_poll_minutes: self
+                .subscriptions
+                .poll_minutes()
+                .unwrap_or(TransmissionConfig::default().feed_poll_minutes),
+            quiet_hours,
+            copy_stall_minutes,
+            check_for_updates: self.about.read(),
+            // Kept as it is by the backend
+            copying_paused: false,
+            movies_seed_limits,
+            shows_seed_limits,
+            locale,
+            size_units,
+            only_assigned,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Save the config, remembering it as saved on success.
+    async fn save(&mut self, config: TransmissionConfig) -> Result<(), AppError> {
+        set_transmission_config(&config).await?;
+        format::set(&config);
+        self.profiles = config.masked().profiles;
+        self.show_profile(self.shown_profile);
+        self.saved = self.read_config().ok();
+        Ok(())
+    }
+
+    /// Whether the form differs from the config as last loaded or saved.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.saved.is_some() && self.read_config().ok() != self.saved
+    }
+
+    /// Ask whether to discard unsaved changes before leaving the tab.
+    /// Returns `true` after restoring the saved config, `false` to stay.
+    pub async fn confirm_leave(&mut self) -> bool {
+        self.leaving.set(true);
+        let discard = self
+            .on_click_discard
+            .next()
+            .map(|_| true)
+            .or(self.on_click_stay.next().map(|_| false))
+            .await;
+        self.leaving.set(false);
+        if discard {
+            if let Some(config) = self.saved.clone() {
+                self.set_config_values(&config);
+            }
         }
+        discard
     }
 
-    fn set_config_values(&self, config: &TransmissionConfig) {
-        self.host_input.dyn_el(|input: &web_sys::HtmlInputElement| {
-            input.set_value(&config.host);
-        });
-        self.port_input.dyn_el(|input: &web_sys::HtmlInputElement| {
-            input.set_value(&config.port.to_string());
-        });
-        self.username_input
-            .dyn_el(|input: &web_sys::HtmlInputElement| {
-                input.set_value(config.username.as_deref().unwrap_or(""));
-            });
-        self.password_input
+    /// Offer to run `action` again if `error` says that might help, or hide
+    /// the Retry button.
+    fn offer_retry(&mut self, action: SettingsAction, error: Option<&AppError>) {
+        self.retry = error.filter(|e| e.retryable).map(|_| action);
+        if self.retry.is_some() {
+            self.retry_button.remove_style("display");
+        } else {
+            self.retry_button.set_style("display", "none");
+        }
+    }
+
+    /// The config in the form, or `None` after pointing out its problem.
+    fn checked_config(&self) -> Option<TransmissionConfig> {
+        match self.read_config() {
+            Ok(config) => Some(config),
+            Err(problem) => {
+                self.status_alert
+                    .set_text(format!("Check the highlighted settings: {problem}"));
+                self.status_alert.set_flavor(Flavor::Danger);
+                self.status_alert.set_is_visible(true);
+                None
+            }
+        }
+    }
+
+    fn set_config_values(&mut self, config: &TransmissionConfig) {
+        self.profiles = config.profiles.clone();
+        if self.profiles.is_empty() {
+            self.profiles.push(ServerProfile::default());
+        }
+        self.show_profile(config.active_profile.min(self.profiles.len() - 1));
+        self.rpc_timeout_input
             .dyn_el(|input: &web_sys::HtmlInputElement| {
-                input.set_value(config.password.as_deref().unwrap_or(""));
+                input.set_value(&config.rpc_timeout_secs.to_string());
             });
         self.movies_dir_input
             .dyn_el(|input: &web_sys::HtmlInputElement| {
@@ -235,10 +1306,75 @@ impl<V: View> SettingsView<V> {
             .dyn_el(|input: &web_sys::HtmlInputElement| {
                 input.set_value(config.shows_dir.as_deref().unwrap_or(""));
             });
+        self.stall_minutes_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&config.stall_minutes.to_string());
+            });
+        self.dead_after_hours_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&config.dead_after_hours.to_string());
+            });
+        self.copy_stall_minutes_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&config.copy_stall_minutes.to_string());
+            });
+        let set_checked = |el: &V::Element, checked: bool| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(checked));
+        };
+        set_checked(
+            &self.notify_download_input,
+            config.notifications.download_complete,
+        );
+        set_checked(&self.notify_copied_input, config.notifications.copy_complete);
+        set_checked(&self.notify_failed_input, config.notifications.copy_failed);
+        set_checked(&self.close_to_tray_input, config.close_to_tray);
+        set_checked(&self.rsync_input, config.copy_with_rsync);
+        set_checked(&self.only_assigned_input, config.only_assigned);
+        set_checked(&self.quiet_hours_input, config.quiet_hours.is_some());
+        if let Some((start, end)) = config.quiet_hours {
+            for (el, time) in [(&self.quiet_start_input, start), (&self.quiet_end_input, end)] {
+                el.dyn_el(|input: &web_sys::HtmlInputElement| {
+                    input.set_value(&time.to_string());
+                });
+            }
+        }
+        set_checked(&self.clean_movie_names_input, config.clean_movie_names);
+        set_checked(&self.rename_episodes_input, config.rename_episodes);
+        self.episode_template_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&config.episode_template);
+            });
+        set_checked(&self.movies_expect_mount_input, config.movies_expect_mount);
+        set_checked(&self.shows_expect_mount_input, config.shows_expect_mount);
+        self.media_server.set(config.media_server.as_ref());
+        self.webhook.set(config.webhook.as_ref());
+        self.post_copy.set(config.post_copy_command.as_deref());
+        self.path_mappings.set(&config.path_mappings);
+        self.seeding
+            .set(config.movies_seed_limits, config.shows_seed_limits);
+        self.copy_backend.set(&config.copy_backend);
+        self.subscriptions
+            .set(&config.subscriptions, config.feed_poll_minutes);
+        self.about.set(config.check_for_updates);
+        self.magnet_destination_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| {
+                select.set_value(config.magnet_destination.map_or("", |dest| dest.label()));
+            });
+        self.locale_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(config.locale.as_deref().unwrap_or(""));
+            });
+        self.size_units_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| {
+                select.set_value(config.size_units.label());
+            });
+        self.validate();
+        self.saved = self.read_config().ok();
     }
 
-    /// Load settings from backend on initial display.
-    pub async fn load(&self) {
+    /// Load settings, copy statistics, recent logs and the build info from
+    /// backend on initial display.
+    pub async fn load(&mut self) {
         match get_transmission_config().await {
             Ok(config) => {
                 self.set_config_values(&config);
@@ -247,67 +1383,205 @@ impl<V: View> SettingsView<V> {
                 log::error!("Failed to load config: {e}");
             }
         }
+        self.statistics.load().await;
+        self.logs.load().await;
+        self.about.load().await;
     }
 
     pub async fn step(&mut self) {
         let action = self
-            .on_click_save
+            .on_input
             .next()
-            .map(|_| SettingsAction::Save)
+            .map(|_| SettingsAction::Validate)
+            .or(self
+                .on_click_password_toggle
+                .next()
+                .map(|_| SettingsAction::TogglePassword))
+            .or(self
+                .on_click_add_profile
+                .next()
+                .map(|_| SettingsAction::AddProfile))
+            .or(self
+                .on_click_delete_profile
+                .next()
+                .map(|_| SettingsAction::DeleteProfile))
+            .or(self.on_change_theme.next().map(|_| SettingsAction::Theme))
+            .or(self
+                .on_change_density
+                .next()
+                .map(|_| SettingsAction::Density))
+            .or(self.on_click_save.next().map(|_| SettingsAction::Save))
             .or(self.on_click_test.next().map(|_| SettingsAction::Test))
+            .or(self.on_click_retry.next().map(|_| SettingsAction::Retry))
+            .or(self
+                .on_click_browse_movies
+                .next()
+                .map(|_| SettingsAction::Browse(Destination::Movies)))
+            .or(self
+                .on_click_browse_shows
+                .next()
+                .map(|_| SettingsAction::Browse(Destination::Shows)))
+            .or(self
+                .media_server
+                .test_clicked()
+                .map(|_| SettingsAction::TestMediaServer))
+            .or(self
+                .webhook
+                .test_clicked()
+                .map(|_| SettingsAction::TestWebhook))
+            .or(self
+                .post_copy
+                .test_clicked()
+                .map(|_| SettingsAction::TestPostCopyHook))
+            .or(self.daemon.step().map(|_| SettingsAction::Daemon))
+            .or(self
+                .path_mappings
+                .step()
+                .map(|_| SettingsAction::PathMappings))
+            .or(self
+                .subscriptions
+                .step()
+                .map(|_| SettingsAction::Subscriptions))
+            .or(self
+                .statistics
+                .step()
+                .map(|_| SettingsAction::Statistics))
+            .or(self.logs.step().map(|_| SettingsAction::Logs))
+            .or(self.about.step().map(|_| SettingsAction::About))
             .await;
+        let action = match action {
+            SettingsAction::Retry => match self.retry.take() {
+                Some(action) => action,
+                None => return,
+            },
+            action => action,
+        };
 
         match action {
+            SettingsAction::Validate => self.validate(),
+            SettingsAction::TogglePassword => self.toggle_password(),
+            SettingsAction::AddProfile => self.add_profile(),
+            SettingsAction::DeleteProfile => self.delete_profile(),
+            SettingsAction::Theme => {
+                let chosen = self
+                    .theme_select
+                    .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+                    .and_then(|value| Theme::ALL.into_iter().find(|t| t.label() == value));
+                if let Some(theme) = chosen {
+                    theme.store();
+                }
+            }
+            SettingsAction::Density => {
+                let chosen = self
+                    .density_select
+                    .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+                    .and_then(|value| Density::ALL.into_iter().find(|d| d.label() == value));
+                if let Some(density) = chosen {
+                    density.store();
+                }
+            }
             SettingsAction::Save => {
-                let config = self.read_config();
+                let Some(config) = self.checked_config() else {
+                    return;
+                };
                 self.save_button.start_spinner();
                 self.save_button.disable();
-                match set_transmission_config(&config).await {
+                let failure = match self.save(config).await {
                     Ok(()) => {
                         self.status_alert.set_text("Settings saved.");
                         self.status_alert.set_flavor(Flavor::Success);
                         self.status_alert.set_is_visible(true);
+                        None
                     }
                     Err(e) => {
                         self.status_alert.set_text(format!("Failed to save: {e}"));
                         self.status_alert.set_flavor(Flavor::Danger);
                         self.status_alert.set_is_visible(true);
+                        Some(e)
                     }
-                }
+                };
+                self.offer_retry(SettingsAction::Save, failure.as_ref());
                 self.save_button.stop_spinner();
-                self.save_button.enable();
+                self.validate();
             }
             SettingsAction::Test => {
                 // Save first, then test
-                let config = self.read_config();
+                let Some(config) = self.checked_config() else {
+                    return;
+                };
                 self.test_button.start_spinner();
                 self.test_button.disable();
                 // Save before testing so the backend uses the current values
-                let _ = set_transmission_config(&config).await;
-                match test_transmission_connection().await {
-                    Ok(msg) => {
+                let _ = self.save(config).await;
+                let failure = match test_transmission_connection().await {
+                    Ok(daemon) => {
+                        let (msg, flavor) = connection_success(&daemon);
                         self.status_alert.set_text(msg);
-                        self.status_alert.set_flavor(Flavor::Success);
+                        self.status_alert.set_flavor(flavor);
                         self.status_alert.set_is_visible(true);
+                        None
                     }
                     Err(e) => {
-                        let msg = match e.kind {
-                            ErrorKind::TransmissionConnection => format!(
-                                "Connection failed: {}. \
-                                 Make sure Transmission is running and remote \
-                                 access is enabled in Preferences \u{203a} Remote.",
-                                e.message
-                            ),
-                            _ => format!("Connection failed: {e}"),
-                        };
-                        self.status_alert.set_text(msg);
+                        self.status_alert.set_text(connection_failure(&e));
                         self.status_alert.set_flavor(Flavor::Danger);
                         self.status_alert.set_is_visible(true);
+                        Some(e)
                     }
-                }
+                };
+                self.offer_retry(SettingsAction::Test, failure.as_ref());
                 self.test_button.stop_spinner();
-                self.test_button.enable();
+                self.validate();
+            }
+            SettingsAction::Browse(destination) => {
+                let input = match destination {
+                    Destination::Movies => &self.movies_dir_input,
+                    Destination::Shows => &self.shows_dir_input,
+                };
+                let current = input
+                    .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+                    .unwrap_or_default();
+                match pick_directory(&current).await {
+                    Ok(Some(dir)) => {
+                        input.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(&dir));
+                        self.validate();
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.status_alert
+                            .set_text(format!("Could not open the folder picker: {e}"));
+                        self.status_alert.set_flavor(Flavor::Danger);
+                        self.status_alert.set_is_visible(true);
+                    }
+                }
+            }
+            SettingsAction::TestMediaServer => {
+                // Save before testing so the backend uses the current values
+                if let Ok(config) = self.read_config() {
+                    let _ = self.save(config).await;
+                }
+                self.media_server.test().await;
+            }
+            SettingsAction::TestWebhook => {
+                // Save before testing so the backend uses the current values
+                if let Ok(config) = self.read_config() {
+                    let _ = self.save(config).await;
+                }
+                self.webhook.test().await;
+            }
+            SettingsAction::TestPostCopyHook => {
+                // Save before testing so the backend uses the current values
+                if let Ok(config) = self.read_config() {
+                    let _ = self.save(config).await;
+                }
+                self.post_copy.test().await;
             }
+            SettingsAction::Retry
+            | SettingsAction::Daemon
+            | SettingsAction::PathMappings
+            | SettingsAction::Subscriptions
+            | SettingsAction::Statistics
+            | SettingsAction::Logs
+            | SettingsAction::About => {}
         }
     }
 }
@@ -0,0 +1,181 @@
+//! Settings card saying which build is running, for bug reports, and
+//! whether a newer release is out.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{AppError, AppInfo, UpdateInfo};
+use wasm_bindgen::prelude::*;
+
+use crate::app::invoke;
+
+async fn get_app_info() -> Result<AppInfo, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_app_info", &Empty {}).await
+}
+
+async fn check_for_update() -> Result<Option<UpdateInfo>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("check_for_update", &Empty {}).await
+}
+
+mod js {
+    use super::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "opener"], js_name = openUrl)]
+        async fn open_url(url: &str);
+
+        #[wasm_bindgen(catch, js_namespace = ["navigator", "clipboard"], js_name = writeText)]
+        async fn write_text(text: &str) -> Result<JsValue, JsValue>;
+    }
+
+    pub async fn open(url: &str) {
+        log::info!("opening {url}");
+        open_url(url).await
+    }
+
+    /// Put `text` on the clipboard, returning whether it worked.
+    pub async fn copy(text: &str) -> bool {
+        write_text(text).await.is_ok()
+    }
+}
+
+enum AboutAction {
+    Copy,
+    OpenUpdate,
+}
+
+/// Version, commit, Tauri and webview versions and file paths, with a
+/// button to copy them, and a link to a newer release when there is one.
+#[derive(ViewChild)]
+pub struct AboutCard<V: View> {
+    #[child]
+    wrapper: V::Element,
+    info_text: V::Text,
+    check_updates_input: V::Element,
+    update_link: V::Element,
+    update_text: V::Text,
+    on_click_update: V::EventListener,
+    on_click_copy: V::EventListener,
+    copy_text: V::Text,
+    info: Option<AppInfo>,
+    update: Option<UpdateInfo>,
+}
+
+impl<V: View> Default for AboutCard<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div(class = "card mb-3") {
+                div(class = "card-body") {
+                    pre(class = "small mb-2", style:white_space = "pre-wrap") {
+                        let info_text = "Loading\u{2026}"
+                    }
+                    div(class = "d-flex align-items-center gap-2 mb-2") {
+                        button(
+                            class = "btn btn-sm btn-outline-secondary",
+                            type = "button",
+                            on:click = on_click_copy,
+                        ) { let copy_text = "Copy" }
+                        let update_link = button(
+                            class = "btn btn-sm btn-link p-0",
+                            type = "button",
+                            style:display = "none",
+                            on:click = on_click_update,
+                        ) { let update_text = "" }
+                    }
+                    div(class = "form-check") {
+                        label(class = "form-check-label") {
+                            let check_updates_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "Look for a newer release when Settings first opens"
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            info_text,
+            check_updates_input,
+            update_link,
+            update_text,
+            on_click_update,
+            on_click_copy,
+            copy_text,
+            info: None,
+            update: None,
+        }
+    }
+}
+
+impl<V: View> AboutCard<V> {
+    /// Whether to check for updates.
+    pub fn read(&self) -> bool {
+        self.check_updates_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, check_for_updates: bool) {
+        self.check_updates_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(check_for_updates));
+    }
+
+    /// Fetch the build info, and the latest release if the backend is set
+    /// to check.  Being offline only leaves the update link hidden.
+    pub async fn load(&mut self) {
+        match get_app_info().await {
+            Ok(info) => {
+                self.info_text.set_text(info.describe());
+                self.info = Some(info);
+            }
+            Err(e) => self
+                .info_text
+                .set_text(format!("Could not get the app's details: {e}")),
+        }
+        self.update = match check_for_update().await {
+            Ok(update) => update,
+            Err(e) => {
+                log::warn!("{e}");
+                None
+            }
+        };
+        match &self.update {
+            Some(update) => {
+                self.update_text
+                    .set_text(format!("Update available: {}", update.version));
+                self.update_link.remove_style("display");
+            }
+            None => self.update_link.set_style("display", "none"),
+        }
+    }
+
+    pub async fn step(&mut self) {
+        let action = self
+            .on_click_copy
+            .next()
+            .map(|_| AboutAction::Copy)
+            .or(self.on_click_update.next().map(|_| AboutAction::OpenUpdate))
+            .await;
+
+        match action {
+            AboutAction::Copy => {
+                let Some(info) = &self.info else {
+                    return;
+                };
+                let copied = js::copy(&info.describe()).await;
+                self.copy_text
+                    .set_text(if copied { "Copied" } else { "Could not copy" });
+            }
+            AboutAction::OpenUpdate => {
+                if let Some(update) = &self.update {
+                    js::open(&update.url).await;
+                }
+            }
+        }
+    }
+}
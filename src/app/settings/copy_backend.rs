@@ -0,0 +1,136 @@
+//! Settings section choosing where completed downloads are copied from.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{CopyBackend, SftpConfig};
+
+/// "This computer" or an SFTP login on the Transmission host.
+#[derive(ViewChild)]
+pub struct CopyBackendSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    kind_select: V::Element,
+    host_input: V::Element,
+    port_input: V::Element,
+    user_input: V::Element,
+    key_path_input: V::Element,
+    password_input: V::Element,
+}
+
+impl<V: View> Default for CopyBackendSection<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div() {
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Read downloads from" }
+                    let kind_select = select(class = "form-select") {
+                        option(value = "Local", selected = "") { "This computer" }
+                        option(value = "Sftp") { "SFTP" }
+                    }
+                    div(class = "form-text") {
+                        "Use SFTP when Transmission's download folder isn't mounted here. \
+                         The host must already be in ~/.ssh/known_hosts."
+                    }
+                }
+                div(class = "row mb-3") {
+                    div(class = "col-8") {
+                        label(class = "form-label") { "SFTP host" }
+                        let host_input = input(
+                            class = "form-control",
+                            type = "text",
+                            placeholder = "seedbox.example.com",
+                        ){}
+                    }
+                    div(class = "col-4") {
+                        label(class = "form-label") { "Port" }
+                        let port_input = input(
+                            class = "form-control",
+                            type = "number",
+                            min = "1",
+                            max = "65535",
+                            value = "22",
+                        ){}
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "SFTP user" }
+                    let user_input = input(class = "form-control", type = "text"){}
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Private key (optional)" }
+                    let key_path_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "/Users/me/.ssh/id_ed25519",
+                    ){}
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Password or key passphrase (optional)" }
+                    let password_input = input(class = "form-control", type = "password"){}
+                }
+            }
+        }
+        Self {
+            wrapper,
+            kind_select,
+            host_input,
+            port_input,
+            user_input,
+            key_path_input,
+            password_input,
+        }
+    }
+}
+
+impl<V: View> CopyBackendSection<V> {
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn set_value(el: &V::Element, value: &str) {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(value));
+    }
+
+    fn optional(el: &V::Element) -> Option<String> {
+        let value = Self::value(el);
+        (!value.trim().is_empty()).then_some(value)
+    }
+
+    pub fn read(&self) -> CopyBackend {
+        let kind = self
+            .kind_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .unwrap_or_default();
+        if kind != "Sftp" {
+            return CopyBackend::Local;
+        }
+        CopyBackend::Sftp(SftpConfig {
+            host: Self::value(&self.host_input).trim().to_string(),
+            port: Self::value(&self.port_input)
+                .parse()
+                .unwrap_or(SftpConfig::default().port),
+            user: Self::value(&self.user_input).trim().to_string(),
+            key_path: Self::optional(&self.key_path_input).map(|p| p.trim().to_string()),
+            password: Self::optional(&self.password_input),
+        })
+    }
+
+    pub fn set(&self, backend: &CopyBackend) {
+        let (kind, sftp) = match backend {
+            CopyBackend::Local => ("Local", SftpConfig::default()),
+            CopyBackend::Sftp(sftp) => ("Sftp", sftp.clone()),
+        };
+        self.kind_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.set_value(kind));
+        Self::set_value(&self.host_input, &sftp.host);
+        Self::set_value(&self.port_input, &sftp.port.to_string());
+        Self::set_value(&self.user_input, &sftp.user);
+        Self::set_value(
+            &self.key_path_input,
+            sftp.key_path.as_deref().unwrap_or_default(),
+        );
+        Self::set_value(
+            &self.password_input,
+            sftp.password.as_deref().unwrap_or_default(),
+        );
+    }
+}
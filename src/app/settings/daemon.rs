@@ -0,0 +1,348 @@
+//! Settings card for Transmission's own preferences, loaded from the daemon
+//! when the card is expanded and saved with its own button.
+use futures_lite::FutureExt;
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{
+    parse_seed_ratio, parse_speed_limit, AppError, SessionConfig, SettingsProblem,
+};
+
+use crate::app::invoke;
+
+async fn get_session_config() -> Result<SessionConfig, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_session_config", &Empty {}).await
+}
+
+async fn set_session_config(session: &SessionConfig) -> Result<(), AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        session: &'a SessionConfig,
+    }
+    invoke::cmd("set_session_config", &Args { session }).await
+}
+
+const UNSUPPORTED: &str = "Not supported by this Transmission";
+
+enum DaemonAction {
+    Toggle,
+    Save,
+}
+
+/// Download directory, incomplete directory, speed limits and seed ratio.
+#[derive(ViewChild)]
+pub struct DaemonSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    on_click_toggle: V::EventListener,
+    toggle_text: V::Text,
+    body: V::Element,
+    download_dir_input: V::Element,
+    incomplete_dir_enabled_input: V::Element,
+    incomplete_dir_input: V::Element,
+    speed_limit_down_enabled_input: V::Element,
+    speed_limit_down_input: V::Element,
+    speed_limit_up_enabled_input: V::Element,
+    speed_limit_up_input: V::Element,
+    seed_ratio_limited_input: V::Element,
+    seed_ratio_limit_input: V::Element,
+    save_button: Button<V>,
+    on_click_save: V::EventListener,
+    result_text: V::Text,
+    is_open: bool,
+    /// The preferences as the daemon last reported them, which tells which
+    /// fields it supports.  `None` until loaded.
+    loaded: Option<SessionConfig>,
+}
+
+impl<V: View> Default for DaemonSection<V> {
+    fn default() -> Self {
+        let mut save_button = Button::new("Save daemon preferences", Some(Flavor::Primary));
+        save_button.get_icon_mut().set_glyph(IconGlyph::Check);
+
+        rsx! {
+            let wrapper = div(class = "card mb-3") {
+                div(class = "card-header") {
+                    button(
+                        class = "btn btn-link p-0 text-decoration-none",
+                        type = "button",
+                        on:click = on_click_toggle,
+                    ) { let toggle_text = "\u{25B8} Show daemon preferences" }
+                }
+                let body = div(class = "card-body", style:display = "none") {
+                    div(class = "mb-3") {
+                        label(class = "form-label") { "Download directory" }
+                        let download_dir_input = input(class = "form-control", type = "text"){}
+                        div(class = "form-text") {
+                            "On the machine Transmission runs on."
+                        }
+                    }
+                    div(class = "mb-3") {
+                        div(class = "form-check") {
+                            label(class = "form-check-label") {
+                                let incomplete_dir_enabled_input = input(
+                                    class = "form-check-input",
+                                    type = "checkbox",
+                                ){}
+                                "Keep incomplete downloads in"
+                            }
+                        }
+                        let incomplete_dir_input = input(class = "form-control", type = "text"){}
+                    }
+                    div(class = "row mb-3") {
+                        div(class = "col") {
+                            div(class = "form-check") {
+                                label(class = "form-check-label") {
+                                    let speed_limit_down_enabled_input = input(
+                                        class = "form-check-input",
+                                        type = "checkbox",
+                                    ){}
+                                    "Limit download speed (KB/s)"
+                                }
+                            }
+                            let speed_limit_down_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                            ){}
+                        }
+                        div(class = "col") {
+                            div(class = "form-check") {
+                                label(class = "form-check-label") {
+                                    let speed_limit_up_enabled_input = input(
+                                        class = "form-check-input",
+                                        type = "checkbox",
+                                    ){}
+                                    "Limit upload speed (KB/s)"
+                                }
+                            }
+                            let speed_limit_up_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                            ){}
+                        }
+                    }
+                    div(class = "mb-3") {
+                        div(class = "form-check") {
+                            label(class = "form-check-label") {
+                                let seed_ratio_limited_input = input(
+                                    class = "form-check-input",
+                                    type = "checkbox",
+                                ){}
+                                "Stop seeding at ratio"
+                            }
+                        }
+                        let seed_ratio_limit_input = input(
+                            class = "form-control",
+                            type = "number",
+                            min = "0",
+                            step = "0.1",
+                        ){}
+                    }
+                    div(class = "d-flex align-items-center gap-2") {
+                        div(on:click = on_click_save) {
+                            {&save_button}
+                        }
+                        span(class = "small") { let result_text = "" }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            on_click_toggle,
+            toggle_text,
+            body,
+            download_dir_input,
+            incomplete_dir_enabled_input,
+            incomplete_dir_input,
+            speed_limit_down_enabled_input,
+            speed_limit_down_input,
+            speed_limit_up_enabled_input,
+            speed_limit_up_input,
+            seed_ratio_limited_input,
+            seed_ratio_limit_input,
+            save_button,
+            on_click_save,
+            result_text,
+            is_open: false,
+            loaded: None,
+        }
+    }
+}
+
+impl<V: View> DaemonSection<V> {
+    /// Fill in a text or number field, or make it read-only if the daemon
+    /// doesn't have it.
+    fn set_value(el: &V::Element, value: Option<String>) {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| {
+            input.set_disabled(value.is_none());
+            input.set_placeholder(if value.is_some() { "" } else { UNSUPPORTED });
+            input.set_value(value.as_deref().unwrap_or_default());
+        });
+    }
+
+    fn set_checked(el: &V::Element, checked: Option<bool>) {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| {
+            input.set_disabled(checked.is_none());
+            input.set_title(if checked.is_some() { "" } else { UNSUPPORTED });
+            input.set_checked(checked.unwrap_or_default());
+        });
+    }
+
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn is_checked(el: &V::Element) -> bool {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or_default()
+    }
+
+    fn show(&self, session: &SessionConfig) {
+        Self::set_value(&self.download_dir_input, session.download_dir.clone());
+        Self::set_checked(
+            &self.incomplete_dir_enabled_input,
+            session.incomplete_dir_enabled,
+        );
+        Self::set_value(&self.incomplete_dir_input, session.incomplete_dir.clone());
+        Self::set_checked(
+            &self.speed_limit_down_enabled_input,
+            session.speed_limit_down_enabled,
+        );
+        Self::set_value(
+            &self.speed_limit_down_input,
+            session.speed_limit_down.map(|limit| limit.to_string()),
+        );
+        Self::set_checked(
+            &self.speed_limit_up_enabled_input,
+            session.speed_limit_up_enabled,
+        );
+        Self::set_value(
+            &self.speed_limit_up_input,
+            session.speed_limit_up.map(|limit| limit.to_string()),
+        );
+        Self::set_checked(&self.seed_ratio_limited_input, session.seed_ratio_limited);
+        Self::set_value(
+            &self.seed_ratio_limit_input,
+            session.seed_ratio_limit.map(|ratio| ratio.to_string()),
+        );
+    }
+
+    /// The preferences in the form, leaving out the fields the daemon
+    /// doesn't have, or the first problem with them.
+    fn read(&self, loaded: &SessionConfig) -> Result<SessionConfig, SettingsProblem> {
+        let text = |el: &V::Element, supported: bool| {
+            supported.then(|| Self::value(el).trim().to_string())
+        };
+        let flag = |el: &V::Element, supported: bool| supported.then(|| Self::is_checked(el));
+        let session = SessionConfig {
+            download_dir: text(&self.download_dir_input, loaded.download_dir.is_some()),
+            incomplete_dir_enabled: flag(
+                &self.incomplete_dir_enabled_input,
+                loaded.incomplete_dir_enabled.is_some(),
+            ),
+            incomplete_dir: text(&self.incomplete_dir_input, loaded.incomplete_dir.is_some()),
+            speed_limit_down_enabled: flag(
+                &self.speed_limit_down_enabled_input,
+                loaded.speed_limit_down_enabled.is_some(),
+            ),
+            speed_limit_down: text(
+                &self.speed_limit_down_input,
+                loaded.speed_limit_down.is_some(),
+            )
+            .map(|limit| parse_speed_limit(&limit))
+            .transpose()?,
+            speed_limit_up_enabled: flag(
+                &self.speed_limit_up_enabled_input,
+                loaded.speed_limit_up_enabled.is_some(),
+            ),
+            speed_limit_up: text(&self.speed_limit_up_input, loaded.speed_limit_up.is_some())
+                .map(|limit| parse_speed_limit(&limit))
+                .transpose()?,
+            seed_ratio_limited: flag(
+                &self.seed_ratio_limited_input,
+                loaded.seed_ratio_limited.is_some(),
+            ),
+            seed_ratio_limit: text(
+                &self.seed_ratio_limit_input,
+                loaded.seed_ratio_limit.is_some(),
+            )
+            .map(|ratio| parse_seed_ratio(&ratio))
+            .transpose()?,
+        };
+        session.validate()?;
+        Ok(session)
+    }
+
+    async fn load(&mut self) {
+        self.result_text.set_text("Loading\u{2026}");
+        match get_session_config().await {
+            Ok(session) => {
+                self.show(&session);
+                self.loaded = Some(session);
+                self.result_text.set_text("");
+            }
+            Err(e) => self
+                .result_text
+                .set_text(format!("Could not load the daemon's preferences: {e}")),
+        }
+    }
+
+    async fn save(&mut self) {
+        let Some(loaded) = &self.loaded else {
+            return;
+        };
+        let session = match self.read(loaded) {
+            Ok(session) => session,
+            Err(problem) => {
+                self.result_text.set_text(problem.message());
+                return;
+            }
+        };
+        self.save_button.start_spinner();
+        self.save_button.disable();
+        match set_session_config(&session).await {
+            Ok(()) => {
+                self.loaded = Some(session);
+                self.result_text.set_text("Daemon preferences saved.");
+            }
+            // Transmission's own wording, e.g. for a directory it can't use
+            Err(e) => self.result_text.set_text(e.message),
+        }
+        self.save_button.stop_spinner();
+        self.save_button.enable();
+    }
+
+    pub async fn step(&mut self) {
+        let action = self
+            .on_click_toggle
+            .next()
+            .map(|_| DaemonAction::Toggle)
+            .or(self.on_click_save.next().map(|_| DaemonAction::Save))
+            .await;
+
+        match action {
+            DaemonAction::Toggle => {
+                self.is_open = !self.is_open;
+                if self.is_open {
+                    self.toggle_text
+                        .set_text("\u{25BE} Hide daemon preferences");
+                    self.body.remove_style("display");
+                    // Reload each time, as the web UI may have changed them
+                    self.load().await;
+                } else {
+                    self.toggle_text
+                        .set_text("\u{25B8} Show daemon preferences");
+                    self.body.set_style("display", "none");
+                }
+            }
+            DaemonAction::Save => self.save().await,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Settings section showing the tail of the backend log file.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{AppError, LogLevel, LogLine};
+use wasm_bindgen::prelude::*;
+
+use crate::app::invoke;
+
+/// How many lines of the log file to show.
+const RECENT_LINES: usize = 300;
+
+async fn get_recent_logs(lines: usize) -> Result<Vec<LogLine>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args {
+        lines: usize,
+    }
+    invoke::cmd("get_recent_logs", &Args { lines }).await
+}
+
+async fn get_log_path() -> Result<String, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_log_path", &Empty {}).await
+}
+
+mod reveal {
+    use super::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "opener"])]
+        async fn revealItemInDir(path: &str);
+    }
+
+    pub async fn path(path: &str) {
+        log::info!("revealing path: {path}");
+        revealItemInDir(path).await
+    }
+}
+
+enum LogsAction {
+    Level,
+    Refresh,
+    Reveal,
+}
+
+/// The last few hundred log lines, filtered by level.
+#[derive(ViewChild)]
+pub struct LogsSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    level_select: V::Element,
+    log_pre: V::Element,
+    log_text: V::Text,
+    on_change_level: V::EventListener,
+    on_click_refresh: V::EventListener,
+    on_click_reveal: V::EventListener,
+    lines: Vec<LogLine>,
+}
+
+impl<V: View> Default for LogsSection<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div() {
+                div(class = "d-flex gap-2 mb-2") {
+                    let level_select = select(
+                        class = "form-select form-select-sm w-auto",
+                        on:change = on_change_level,
+                    ) {
+                        option(value = "ERROR") { "Errors" }
+                        option(value = "WARN") { "Warnings and above" }
+                        option(value = "INFO", selected = "") { "Info and above" }
+                        option(value = "DEBUG") { "Debug and above" }
+                        option(value = "TRACE") { "Everything" }
+                    }
+                    button(
+                        class = "btn btn-sm btn-outline-secondary",
+                        type = "button",
+                        on:click = on_click_refresh,
+                    ) {
+                        "Refresh"
+                    }
+                    button(
+                        class = "btn btn-sm btn-outline-secondary",
+                        type = "button",
+                        on:click = on_click_reveal,
+                    ) {
+                        "Reveal log file"
+                    }
+                }
+                let log_pre = pre(
+                    class = "border rounded p-2 small",
+                    style:max_height = "24rem",
+                    style:overflow_y = "auto",
+                ) {
+                    let log_text = "Loading\u{2026}"
+                }
+            }
+        }
+        Self {
+            wrapper,
+            level_select,
+            log_pre,
+            log_text,
+            on_change_level,
+            on_click_refresh,
+            on_click_reveal,
+            lines: vec![],
+        }
+    }
+}
+
+impl<V: View> LogsSection<V> {
+    fn selected_level(&self) -> LogLevel {
+        self.level_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| LogLevel::from_label(&value))
+            .unwrap_or(LogLevel::Info)
+    }
+
+    /// Show the lines at or above the selected level, scrolled to the end.
+    fn show_lines(&self) {
+        let level = self.selected_level();
+        let text = self
+            .lines
+            .iter()
+            .filter(|line| line.level <= level)
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.is_empty() {
+            self.log_text.set_text("No log lines at this level.");
+        } else {
+            self.log_text.set_text(text);
+        }
+        self.log_pre.dyn_el(|pre: &web_sys::Element| {
+            pre.set_scroll_top(pre.scroll_height());
+        });
+    }
+
+    /// Fetch the latest lines from the backend.
+    pub async fn load(&mut self) {
+        match get_recent_logs(RECENT_LINES).await {
+            Ok(lines) => {
+                self.lines = lines;
+                self.show_lines();
+            }
+            Err(e) => {
+                self.lines.clear();
+                self.log_text
+                    .set_text(format!("Could not read the log: {e}"));
+            }
+        }
+    }
+
+    pub async fn step(&mut self) {
+        let action = self
+            .on_change_level
+            .next()
+            .map(|_| LogsAction::Level)
+            .or(self.on_click_refresh.next().map(|_| LogsAction::Refresh))
+            .or(self.on_click_reveal.next().map(|_| LogsAction::Reveal))
+            .await;
+
+        match action {
+            LogsAction::Level => self.show_lines(),
+            LogsAction::Refresh => self.load().await,
+            LogsAction::Reveal => match get_log_path().await {
+                Ok(path) => reveal::path(&path).await,
+                Err(e) => log::error!("Failed to get log path: {e}"),
+            },
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Settings section for the media server scanned after each copy.
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, MediaServerConfig, MediaServerKind};
+
+use crate::app::invoke;
+
+async fn test_media_server() -> Result<String, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("test_media_server", &Empty {}).await
+}
+
+/// Plex/Jellyfin connection fields and a "Test media server" button.
+#[derive(ViewChild)]
+pub struct MediaServerSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    kind_select: V::Element,
+    base_url_input: V::Element,
+    token_input: V::Element,
+    movies_section_input: V::Element,
+    shows_section_input: V::Element,
+    test_button: Button<V>,
+    on_click_test: V::EventListener,
+    result_text: V::Text,
+}
+
+impl<V: View> Default for MediaServerSection<V> {
+    fn default() -> Self {
+        let mut test_button = Button::new("Test media server", Some(Flavor::Secondary));
+        test_button.get_icon_mut().set_glyph(IconGlyph::Globe);
+
+        rsx! {
+            let wrapper = div() {
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Server" }
+                    let kind_select = select(class = "form-select") {
+                        option(value = "", selected = "") { "None" }
+                        option(value = "Plex") { "Plex" }
+                        option(value = "Jellyfin") { "Jellyfin" }
+                    }
+                    div(class = "form-text") {
+                        "Asked to scan each copy so it shows up without waiting for a scheduled scan."
+                    }
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Server URL" }
+                    let base_url_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "http://192.168.1.10:32400",
+                    ){}
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Token" }
+                    let token_input = input(
+                        class = "form-control",
+                        type = "password",
+                        placeholder = "Plex token or Jellyfin API key",
+                    ){}
+                }
+                div(class = "row mb-3") {
+                    div(class = "col") {
+                        label(class = "form-label") { "Movies library section" }
+                        let movies_section_input = input(
+                            class = "form-control",
+                            type = "number",
+                            min = "1",
+                        ){}
+                    }
+                    div(class = "col") {
+                        label(class = "form-label") { "Shows library section" }
+                        let shows_section_input = input(
+                            class = "form-control",
+                            type = "number",
+                            min = "1",
+                        ){}
+                    }
+                    div(class = "form-text") {
+                        "Plex only: the section ids of the libraries holding each destination."
+                    }
+                }
+                div(class = "d-flex align-items-center gap-2") {
+                    div(on:click = on_click_test) {
+                        {&test_button}
+                    }
+                    span(class = "small") { let result_text = "" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            kind_select,
+            base_url_input,
+            token_input,
+            movies_section_input,
+            shows_section_input,
+            test_button,
+            on_click_test,
+            result_text,
+        }
+    }
+}
+
+impl<V: View> MediaServerSection<V> {
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn set_value(el: &V::Element, value: &str) {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(value));
+    }
+
+    /// The configured server, `None` if "None" is selected.
+    pub fn read(&self) -> Option<MediaServerConfig> {
+        let kind = self
+            .kind_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| MediaServerKind::from_label(&value))?;
+        Some(MediaServerConfig {
+            kind,
+            base_url: Self::value(&self.base_url_input).trim().to_string(),
+            token: Self::value(&self.token_input).trim().to_string(),
+            movies_section: Self::value(&self.movies_section_input).parse().ok(),
+            shows_section: Self::value(&self.shows_section_input).parse().ok(),
+        })
+    }
+
+    pub fn set(&self, config: Option<&MediaServerConfig>) {
+        let kind = config.map(|c| c.kind.label()).unwrap_or_default();
+        self.kind_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.set_value(kind));
+        let section = |s: Option<u32>| s.map(|s| s.to_string()).unwrap_or_default();
+        Self::set_value(
+            &self.base_url_input,
+            config.map(|c| c.base_url.as_str()).unwrap_or_default(),
+        );
+        Self::set_value(
+            &self.token_input,
+            config.map(|c| c.token.as_str()).unwrap_or_default(),
+        );
+        Self::set_value(
+            &self.movies_section_input,
+            &section(config.and_then(|c| c.movies_section)),
+        );
+        Self::set_value(
+            &self.shows_section_input,
+            &section(config.and_then(|c| c.shows_section)),
+        );
+    }
+
+    /// Wait for a click on "Test media server".
+    pub async fn test_clicked(&self) {
+        self.on_click_test.next().await;
+    }
+
+    /// Test the saved media server settings and show the outcome.
+    pub async fn test(&mut self) {
+        self.test_button.start_spinner();
+        self.test_button.disable();
+        self.result_text.set_text("");
+        match test_media_server().await {
+            Ok(message) => self.result_text.set_text(message),
+            Err(e) => self.result_text.set_text(e.message),
+        }
+        self.test_button.stop_spinner();
+        self.test_button.enable();
+    }
+}
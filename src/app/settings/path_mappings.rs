@@ -0,0 +1,165 @@
+//! Settings section mapping Transmission's paths to local mount points.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+
+/// One editable `remote → local` row.
+struct MappingRow<V: View> {
+    wrapper: V::Element,
+    remote_input: V::Element,
+    local_input: V::Element,
+    on_click_remove: V::EventListener,
+}
+
+impl<V: View> MappingRow<V> {
+    fn new(remote: &str, local: &str) -> Self {
+        rsx! {
+            let wrapper = div(class = "input-group input-group-sm mb-2") {
+                let remote_input = input(
+                    class = "form-control",
+                    type = "text",
+                    placeholder = "/home/seed/downloads",
+                    value = remote,
+                ){}
+                span(class = "input-group-text") { "\u{2192}" }
+                let local_input = input(
+                    class = "form-control",
+                    type = "text",
+                    placeholder = "/Volumes/seedbox",
+                    value = local,
+                ){}
+                button(
+                    class = "btn btn-outline-danger",
+                    type = "button",
+                    title = "Remove mapping",
+                    on:click = on_click_remove,
+                ) {
+                    "\u{2715}"
+                }
+            }
+        }
+        Self {
+            wrapper,
+            remote_input,
+            local_input,
+            on_click_remove,
+        }
+    }
+
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+}
+
+enum MappingsAction {
+    Add,
+    Remove(usize),
+}
+
+/// Remote/local prefix pairs, with buttons to add and remove rows.
+#[derive(ViewChild)]
+pub struct PathMappingsSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    list: V::Element,
+    rows: Vec<MappingRow<V>>,
+    on_click_add: V::EventListener,
+}
+
+impl<V: View> Default for PathMappingsSection<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div(class = "mb-3") {
+                let list = div() {}
+                button(
+                    class = "btn btn-sm btn-outline-secondary",
+                    type = "button",
+                    on:click = on_click_add,
+                ) {
+                    "+ Add mapping"
+                }
+                div(class = "form-text") {
+                    "For a Transmission on another machine: its download path on the \
+                     left, where that folder is mounted on this computer on the right. \
+                     Local paths must exist when saving."
+                }
+            }
+        }
+        Self {
+            wrapper,
+            list,
+            rows: Vec::new(),
+            on_click_add,
+        }
+    }
+}
+
+impl<V: View> PathMappingsSection<V> {
+    fn push_row(&mut self, remote: &str, local: &str) {
+        let row = MappingRow::new(remote, local);
+        self.list.append_child(&row.wrapper);
+        self.rows.push(row);
+    }
+
+    /// The mappings entered so far, skipping rows left completely blank.
+    pub fn read(&self) -> Vec<(String, String)> {
+        self.rows
+            .iter()
+            .map(|row| {
+                (
+                    MappingRow::<V>::value(&row.remote_input),
+                    MappingRow::<V>::value(&row.local_input),
+                )
+            })
+            .filter(|(remote, local)| !remote.is_empty() || !local.is_empty())
+            .collect()
+    }
+
+    pub fn set(&mut self, mappings: &[(String, String)]) {
+        for row in self.rows.drain(..) {
+            self.list.remove_child(&row.wrapper);
+        }
+        for (remote, local) in mappings {
+            self.push_row(remote, local);
+        }
+    }
+
+    /// Add or remove a row.
+    pub async fn step(&mut self) {
+        let rows = &self.rows;
+        let remove = async {
+            if rows.is_empty() {
+                std::future::pending::<MappingsAction>().await
+            } else {
+                let futures: Vec<_> = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| {
+                        async move {
+                            row.on_click_remove.next().await;
+                            MappingsAction::Remove(i)
+                        }
+                        .boxed_local()
+                    })
+                    .collect();
+                mogwai::future::race_all(futures).await
+            }
+        };
+        let action = self
+            .on_click_add
+            .next()
+            .map(|_| MappingsAction::Add)
+            .or(remove)
+            .await;
+
+        match action {
+            MappingsAction::Add => self.push_row("", ""),
+            MappingsAction::Remove(i) => {
+                let row = self.rows.remove(i);
+                self.list.remove_child(&row.wrapper);
+            }
+        }
+    }
+}
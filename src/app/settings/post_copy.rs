@@ -0,0 +1,105 @@
+//! Settings section for the command run after each copy.
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::web::prelude::*;
+use privateer_wire_types::AppError;
+
+use crate::app::invoke;
+
+async fn test_post_copy_hook() -> Result<String, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("test_post_copy_hook", &Empty {}).await
+}
+
+/// The post-copy command, with a warning and a "Test hook" button.
+#[derive(ViewChild)]
+pub struct PostCopySection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    command_input: V::Element,
+    test_button: Button<V>,
+    on_click_test: V::EventListener,
+    result_text: V::Text,
+}
+
+impl<V: View> Default for PostCopySection<V> {
+    fn default() -> Self {
+        let mut test_button = Button::new("Test hook", Some(Flavor::Secondary));
+        test_button.get_icon_mut().set_glyph(IconGlyph::Play);
+
+        rsx! {
+            let wrapper = div() {
+                div(class = "alert alert-danger small", role = "alert") {
+                    strong() { "This runs arbitrary commands" }
+                    " on this computer, as you, after every copy. Only enter a \
+                     command you trust."
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Command (optional)" }
+                    let command_input = input(
+                        class = "form-control font-monospace",
+                        type = "text",
+                        placeholder = "/home/me/bin/after-copy.sh",
+                    ){}
+                    div(class = "form-text") {
+                        "Run by the shell with PRIVATEER_NAME, PRIVATEER_DEST, \
+                         PRIVATEER_PATH and PRIVATEER_HASH set to the copy's \
+                         details. Its output goes to the log; a failure shows a \
+                         warning but the download stays copied."
+                    }
+                }
+                div(class = "d-flex align-items-center gap-2") {
+                    div(on:click = on_click_test) {
+                        {&test_button}
+                    }
+                    span(class = "small") { let result_text = "" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            command_input,
+            test_button,
+            on_click_test,
+            result_text,
+        }
+    }
+}
+
+impl<V: View> PostCopySection<V> {
+    /// The configured command, `None` if it is blank.
+    pub fn read(&self) -> Option<String> {
+        let command = self
+            .command_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default();
+        let command = command.trim();
+        (!command.is_empty()).then(|| command.to_string())
+    }
+
+    pub fn set(&self, command: Option<&str>) {
+        let command = command.unwrap_or_default();
+        self.command_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(command));
+    }
+
+    /// Wait for a click on "Test hook".
+    pub async fn test_clicked(&self) {
+        self.on_click_test.next().await;
+    }
+
+    /// Run the saved command for a made-up download and show how it went.
+    pub async fn test(&mut self) {
+        self.test_button.start_spinner();
+        self.test_button.disable();
+        self.result_text.set_text("Running\u{2026}");
+        match test_post_copy_hook().await {
+            Ok(message) => self.result_text.set_text(message),
+            Err(e) => self.result_text.set_text(e.message),
+        }
+        self.test_button.stop_spinner();
+        self.test_button.enable();
+    }
+}
@@ -0,0 +1,145 @@
+//! Settings section for when copied downloads stop seeding, per
+//! destination.
+use mogwai::web::prelude::*;
+use privateer_wire_types::{parse_seed_ratio, SeedLimits, SettingsProblem};
+
+/// The ratio and time limits of one destination.
+struct LimitsRow<V: View> {
+    ratio_input: V::Element,
+    minutes_input: V::Element,
+}
+
+impl<V: View> LimitsRow<V> {
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn set_value(el: &V::Element, value: Option<String>) {
+        let value = value.unwrap_or_default();
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(&value));
+    }
+
+    /// The limits in the row; an empty field is no limit.
+    fn read(&self) -> Result<SeedLimits, SettingsProblem> {
+        let ratio = Self::value(&self.ratio_input);
+        let seed_ratio_limit = if ratio.trim().is_empty() {
+            None
+        } else {
+            Some(parse_seed_ratio(&ratio)?)
+        };
+        Ok(SeedLimits {
+            seed_ratio_limit,
+            seed_time_limit: Self::value(&self.minutes_input).trim().parse().ok(),
+        })
+    }
+
+    fn set(&self, limits: SeedLimits) {
+        Self::set_value(
+            &self.ratio_input,
+            limits.seed_ratio_limit.map(|ratio| ratio.to_string()),
+        );
+        Self::set_value(
+            &self.minutes_input,
+            limits.seed_time_limit.map(|minutes| minutes.to_string()),
+        );
+    }
+}
+
+/// Seed ratio and time limits for Movies and Shows.
+#[derive(ViewChild)]
+pub struct SeedingSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    movies: LimitsRow<V>,
+    shows: LimitsRow<V>,
+}
+
+impl<V: View> Default for SeedingSection<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div(class = "mb-3") {
+                div(class = "row g-2 mb-2 align-items-center") {
+                    div(class = "col-2") { "Movies" }
+                    div(class = "col-5") {
+                        div(class = "input-group input-group-sm") {
+                            span(class = "input-group-text") { "Ratio" }
+                            let movies_ratio_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                                step = "0.1",
+                                placeholder = "No limit",
+                            ){}
+                        }
+                    }
+                    div(class = "col-5") {
+                        div(class = "input-group input-group-sm") {
+                            let movies_minutes_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                                placeholder = "No limit",
+                            ){}
+                            span(class = "input-group-text") { "minutes" }
+                        }
+                    }
+                }
+                div(class = "row g-2 mb-2 align-items-center") {
+                    div(class = "col-2") { "Shows" }
+                    div(class = "col-5") {
+                        div(class = "input-group input-group-sm") {
+                            span(class = "input-group-text") { "Ratio" }
+                            let shows_ratio_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                                step = "0.1",
+                                placeholder = "No limit",
+                            ){}
+                        }
+                    }
+                    div(class = "col-5") {
+                        div(class = "input-group input-group-sm") {
+                            let shows_minutes_input = input(
+                                class = "form-control",
+                                type = "number",
+                                min = "0",
+                                placeholder = "No limit",
+                            ){}
+                            span(class = "input-group-text") { "minutes" }
+                        }
+                    }
+                }
+                div(class = "form-text") {
+                    "Once a download is copied and reaches either limit, it is \
+                     stopped in Transmission. Torrents added outside Privateer \
+                     are never stopped."
+                }
+            }
+        }
+        Self {
+            wrapper,
+            movies: LimitsRow {
+                ratio_input: movies_ratio_input,
+                minutes_input: movies_minutes_input,
+            },
+            shows: LimitsRow {
+                ratio_input: shows_ratio_input,
+                minutes_input: shows_minutes_input,
+            },
+        }
+    }
+}
+
+impl<V: View> SeedingSection<V> {
+    /// The Movies and Shows limits, or the first problem with them.
+    pub fn read(&self) -> Result<(SeedLimits, SeedLimits), SettingsProblem> {
+        Ok((self.movies.read()?, self.shows.read()?))
+    }
+
+    pub fn set(&self, movies: SeedLimits, shows: SeedLimits) {
+        self.movies.set(movies);
+        self.shows.set(shows);
+    }
+}
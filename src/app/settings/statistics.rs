@@ -0,0 +1,181 @@
+//! Settings card showing cumulative copy statistics.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{format_duration, AppError, ConfirmArgs, CopyMetrics};
+
+use crate::app::pending::{self, PendingAction};
+use crate::app::{format, invoke};
+
+async fn get_copy_metrics() -> Result<CopyMetrics, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("get_copy_metrics", &Empty {}).await
+}
+
+/// Unconfirmed resets are refused; see [`pending`].
+async fn reset_copy_metrics(confirm: bool) -> Result<CopyMetrics, AppError> {
+    invoke::cmd("reset_copy_metrics", &ConfirmArgs { confirm }).await
+}
+
+enum StatisticsAction {
+    Reset,
+    /// A reset went through, after its countdown.
+    WasReset,
+}
+
+/// Totals of everything the copy task has copied, with a reset button that
+/// can be undone for a few seconds.
+#[derive(ViewChild)]
+pub struct StatisticsCard<V: View> {
+    #[child]
+    wrapper: V::Element,
+    copies_text: V::Text,
+    files_text: V::Text,
+    bytes_text: V::Text,
+    failed_text: V::Text,
+    time_text: V::Text,
+    destinations_text: V::Text,
+    since_text: V::Text,
+    on_click_reset: V::EventListener,
+    resets: (async_channel::Sender<()>, async_channel::Receiver<()>),
+}
+
+impl<V: View> Default for StatisticsCard<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div(class = "card") {
+                div(class = "card-body") {
+                    div(class = "row row-cols-2 row-cols-md-5 g-2 mb-2") {
+                        div(class = "col") {
+                            div(class = "small text-muted") { "Copies" }
+                            div(class = "fs-5") { let copies_text = "\u{2014}" }
+                        }
+                        div(class = "col") {
+                            div(class = "small text-muted") { "Files" }
+                            div(class = "fs-5") { let files_text = "\u{2014}" }
+                        }
+                        div(class = "col") {
+                            div(class = "small text-muted") { "Data" }
+                            div(class = "fs-5") { let bytes_text = "\u{2014}" }
+                        }
+                        div(class = "col") {
+                            div(class = "small text-muted") { "Failed" }
+                            div(class = "fs-5") { let failed_text = "\u{2014}" }
+                        }
+                        div(class = "col") {
+                            div(class = "small text-muted") { "Time copying" }
+                            div(class = "fs-5") { let time_text = "\u{2014}" }
+                        }
+                    }
+                    div(class = "small mb-1") { let destinations_text = "" }
+                    div(class = "d-flex align-items-center gap-2") {
+                        span(class = "small text-muted me-auto") { let since_text = "" }
+                        button(
+                            class = "btn btn-sm btn-outline-danger",
+                            type = "button",
+                            on:click = on_click_reset,
+                        ) {
+                            "Reset"
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            copies_text,
+            files_text,
+            bytes_text,
+            failed_text,
+            time_text,
+            destinations_text,
+            since_text,
+            on_click_reset,
+            resets: async_channel::unbounded(),
+        }
+    }
+}
+
+impl<V: View> StatisticsCard<V> {
+    fn show_metrics(&self, metrics: &CopyMetrics) {
+        self.copies_text
+            .set_text(metrics.copies_completed.to_string());
+        self.files_text.set_text(metrics.files_copied.to_string());
+        self.bytes_text.set_text(format::size(metrics.bytes_copied));
+        self.failed_text.set_text(metrics.copies_failed.to_string());
+        if metrics.copy_seconds > 0.0 {
+            let speed = metrics.bytes_copied as f64 / metrics.copy_seconds;
+            self.time_text.set_text(format!(
+                "{} ({})",
+                format_duration(metrics.copy_seconds as u64),
+                format::rate(speed as i64)
+            ));
+        } else {
+            self.time_text.set_text("\u{2014}");
+        }
+
+        let destinations = metrics
+            .destinations
+            .iter()
+            .map(|d| {
+                let mut text = format!(
+                    "{}: {} copies, {}",
+                    d.destination.label(),
+                    d.copies_completed,
+                    format::size(d.bytes_copied)
+                );
+                if d.copy_seconds > 0.0 {
+                    let speed = d.bytes_copied as f64 / d.copy_seconds;
+                    text.push_str(&format!(" at {}", format::rate(speed as i64)));
+                }
+                if d.copies_failed > 0 {
+                    text.push_str(&format!(", {} failed", d.copies_failed));
+                }
+                text
+            })
+            .collect::<Vec<_>>()
+            .join(" \u{00b7} ");
+        self.destinations_text.set_text(destinations);
+
+        match metrics.since {
+            Some(since) => self
+                .since_text
+                .set_text(format!("Since {}", format::timestamp(since))),
+            None => self.since_text.set_text("No copies yet."),
+        }
+    }
+
+    /// Fetch the totals from the backend.
+    pub async fn load(&mut self) {
+        match get_copy_metrics().await {
+            Ok(metrics) => self.show_metrics(&metrics),
+            Err(e) => self
+                .since_text
+                .set_text(format!("Could not load statistics: {e}")),
+        }
+    }
+
+    pub async fn step(&mut self) {
+        let resets = &self.resets.1;
+        let action = self
+            .on_click_reset
+            .next()
+            .map(|_| StatisticsAction::Reset)
+            .or(async {
+                let _ = resets.recv().await;
+                StatisticsAction::WasReset
+            })
+            .await;
+
+        match action {
+            StatisticsAction::Reset => {
+                let action =
+                    PendingAction::new("Resetting the copy statistics", |confirm| async move {
+                        reset_copy_metrics(confirm).await.map(|_| ())
+                    });
+                pending::enqueue(action.notify(&self.resets.0));
+            }
+            StatisticsAction::WasReset => self.load().await,
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Settings section for RSS feeds whose new items are added automatically.
+use futures_lite::FutureExt;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{AppError, Destination, FeedPreviewItem, FeedSubscription};
+
+use crate::app::invoke;
+
+async fn preview_subscription(
+    subscription: &FeedSubscription,
+) -> Result<Vec<FeedPreviewItem>, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        subscription: &'a FeedSubscription,
+    }
+    invoke::cmd("preview_subscription", &Args { subscription }).await
+}
+
+/// One editable subscription, with the result of its last preview.
+struct SubscriptionRow<V: View> {
+    wrapper: V::Element,
+    url_input: V::Element,
+    include_input: V::Element,
+    exclude_input: V::Element,
+    destination_select: V::Element,
+    on_click_preview: V::EventListener,
+    on_click_remove: V::EventListener,
+    preview_text: V::Text,
+    preview_list: V::Element,
+    preview_items: Vec<V::Element>,
+}
+
+impl<V: View> SubscriptionRow<V> {
+    fn new(subscription: &FeedSubscription) -> Self {
+        let exclude = subscription.exclude.as_deref().unwrap_or_default();
+        let preview_text = V::Text::new("");
+        rsx! {
+            let wrapper = div(class = "border rounded p-2 mb-2") {
+                div(class = "input-group input-group-sm mb-2") {
+                    let url_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "https://tracker.example/rss",
+                        value = subscription.url.as_str(),
+                    ){}
+                    button(
+                        class = "btn btn-outline-danger",
+                        type = "button",
+                        title = "Remove subscription",
+                        on:click = on_click_remove,
+                    ) {
+                        "\u{2715}"
+                    }
+                }
+                div(class = "input-group input-group-sm mb-2") {
+                    span(class = "input-group-text") { "Include" }
+                    let include_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "show name s\\d+e\\d+ .*1080p",
+                        value = subscription.include.as_str(),
+                    ){}
+                    span(class = "input-group-text") { "Exclude" }
+                    let exclude_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "optional, e.g. 2160p|hdr",
+                        value = exclude,
+                    ){}
+                }
+                div(class = "d-flex gap-2 align-items-center") {
+                    let destination_select = select(
+                        class = "form-select form-select-sm",
+                        style:width = "auto",
+                    ) {
+                        option(value = "Movies") { "Movies" }
+                        option(value = "Shows") { "Shows" }
+                    }
+                    button(
+                        class = "btn btn-sm btn-outline-secondary",
+                        type = "button",
+                        on:click = on_click_preview,
+                    ) {
+                        "Preview matches"
+                    }
+                    span(class = "small text-muted") { {&preview_text} }
+                }
+                let preview_list = ul(class = "small mb-0 mt-2") {}
+            }
+        }
+        destination_select.dyn_el(|select: &web_sys::HtmlSelectElement| {
+            select.set_value(subscription.destination.label())
+        });
+        Self {
+            wrapper,
+            url_input,
+            include_input,
+            exclude_input,
+            destination_select,
+            on_click_preview,
+            on_click_remove,
+            preview_text,
+            preview_list,
+            preview_items: Vec::new(),
+        }
+    }
+
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+
+    fn read(&self) -> FeedSubscription {
+        let exclude = Self::value(&self.exclude_input);
+        let destination = self
+            .destination_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| {
+                Destination::ALL
+                    .into_iter()
+                    .find(|dest| dest.label() == value)
+            })
+            .unwrap_or_default();
+        FeedSubscription {
+            url: Self::value(&self.url_input),
+            include: Self::value(&self.include_input),
+            exclude: (!exclude.is_empty()).then_some(exclude),
+            destination,
+        }
+    }
+
+    fn show_preview(&mut self, preview: Result<Vec<FeedPreviewItem>, AppError>) {
+        for item in self.preview_items.drain(..) {
+            self.preview_list.remove_child(&item);
+        }
+        let items = match preview {
+            Ok(items) => items,
+            Err(e) => {
+                self.preview_text.set_text(format!("Preview failed: {e}"));
+                return;
+            }
+        };
+        let matching = items.iter().filter(|item| item.matches).count();
+        self.preview_text.set_text(format!(
+            "{matching} of {} items in the feed match",
+            items.len()
+        ));
+        for item in items {
+            let class = if item.matches {
+                "text-success"
+            } else {
+                "text-muted"
+            };
+            rsx! {
+                let li = li(class = class) { {&item.title} }
+            }
+            self.preview_list.append_child(&li);
+            self.preview_items.push(li);
+        }
+    }
+}
+
+enum SubscriptionsAction {
+    Add,
+    Preview(usize),
+    Remove(usize),
+}
+
+/// Feed subscriptions, with buttons to add, remove and preview them.
+#[derive(ViewChild)]
+pub struct SubscriptionsSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    list: V::Element,
+    rows: Vec<SubscriptionRow<V>>,
+    on_click_add: V::EventListener,
+    poll_minutes_input: V::Element,
+}
+
+impl<V: View> Default for SubscriptionsSection<V> {
+    fn default() -> Self {
+        rsx! {
+            let wrapper = div(class = "mb-3") {
+                let list = div() {}
+                button(
+                    class = "btn btn-sm btn-outline-secondary",
+                    type = "button",
+                    on:click = on_click_add,
+                ) {
+                    "+ Add subscription"
+                }
+                div(class = "form-text mb-3") {
+                    "New items whose titles match a subscription's include pattern, \
+                     and not its exclude pattern, are added to Transmission. Patterns \
+                     are regular expressions and ignore case. Items already in a feed \
+                     when you subscribe are skipped."
+                }
+                label(class = "form-label") { "Check Feeds Every (minutes)" }
+                let poll_minutes_input = input(
+                    class = "form-control",
+                    type = "number",
+                    min = "1",
+                    value = "15",
+                    placeholder = "15",
+                ){}
+            }
+        }
+        Self {
+            wrapper,
+            list,
+            rows: Vec::new(),
+            on_click_add,
+            poll_minutes_input,
+        }
+    }
+}
+
+impl<V: View> SubscriptionsSection<V> {
+    fn push_row(&mut self, subscription: &FeedSubscription) {
+        let row = SubscriptionRow::new(subscription);
+        self.list.append_child(&row.wrapper);
+        self.rows.push(row);
+    }
+
+    /// The subscriptions entered so far, skipping rows without a URL.
+    pub fn read(&self) -> Vec<FeedSubscription> {
+        self.rows
+            .iter()
+            .map(SubscriptionRow::read)
+            .filter(|subscription| !subscription.url.is_empty())
+            .collect()
+    }
+
+    pub fn poll_minutes(&self) -> Option<u32> {
+        self.poll_minutes_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .and_then(|value| value.parse().ok())
+    }
+
+    pub fn set(&mut self, subscriptions: &[FeedSubscription], poll_minutes: u32) {
+        for row in self.rows.drain(..) {
+            self.list.remove_child(&row.wrapper);
+        }
+        for subscription in subscriptions {
+            self.push_row(subscription);
+        }
+        self.poll_minutes_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| {
+                input.set_value(&poll_minutes.to_string());
+            });
+    }
+
+    /// Add, remove or preview a subscription.
+    pub async fn step(&mut self) {
+        let rows = &self.rows;
+        let row_action = async {
+            if rows.is_empty() {
+                std::future::pending::<SubscriptionsAction>().await
+            } else {
+                let futures: Vec<_> = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| {
+                        async move {
+                            row.on_click_preview
+                                .next()
+                                .map(|_| SubscriptionsAction::Preview(i))
+                                .or(row
+                                    .on_click_remove
+                                    .next()
+                                    .map(|_| SubscriptionsAction::Remove(i)))
+                                .await
+                        }
+                        .boxed_local()
+                    })
+                    .collect();
+                mogwai::future::race_all(futures).await
+            }
+        };
+        let action = self
+            .on_click_add
+            .next()
+            .map(|_| SubscriptionsAction::Add)
+            .or(row_action)
+            .await;
+
+        match action {
+            SubscriptionsAction::Add => self.push_row(&FeedSubscription {
+                destination: Destination::Shows,
+                ..Default::default()
+            }),
+            SubscriptionsAction::Preview(i) => {
+                let row = &mut self.rows[i];
+                row.preview_text.set_text("Fetching the feed...");
+                let preview = preview_subscription(&row.read()).await;
+                row.show_preview(preview);
+            }
+            SubscriptionsAction::Remove(i) => {
+                let row = self.rows.remove(i);
+                self.list.remove_child(&row.wrapper);
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Settings section for the webhook that download and copy events are
+//! posted to.
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::web::prelude::*;
+use privateer_wire_types::{AppError, WebhookConfig, WebhookEvents};
+
+use crate::app::invoke;
+
+async fn send_test_webhook() -> Result<String, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("send_test_webhook", &Empty {}).await
+}
+
+/// Webhook URL, secret and event toggles, with a "Send test webhook" button.
+#[derive(ViewChild)]
+pub struct WebhookSection<V: View> {
+    #[child]
+    wrapper: V::Element,
+    url_input: V::Element,
+    secret_input: V::Element,
+    download_complete_input: V::Element,
+    copy_started_input: V::Element,
+    copy_completed_input: V::Element,
+    copy_failed_input: V::Element,
+    test_button: Button<V>,
+    on_click_test: V::EventListener,
+    result_text: V::Text,
+}
+
+impl<V: View> Default for WebhookSection<V> {
+    fn default() -> Self {
+        let mut test_button = Button::new("Send test webhook", Some(Flavor::Secondary));
+        test_button.get_icon_mut().set_glyph(IconGlyph::Globe);
+
+        rsx! {
+            let wrapper = div() {
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Webhook URL (optional)" }
+                    let url_input = input(
+                        class = "form-control",
+                        type = "text",
+                        placeholder = "http://homeassistant.local:8123/api/webhook/privateer",
+                    ){}
+                }
+                div(class = "mb-3") {
+                    label(class = "form-label") { "Shared secret (optional)" }
+                    let secret_input = input(
+                        class = "form-control",
+                        type = "password",
+                        placeholder = "Leave blank to send unsigned",
+                    ){}
+                    div(class = "form-text") {
+                        "Requests are signed with HMAC-SHA256 in the X-Privateer-Signature header."
+                    }
+                }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let download_complete_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Download complete"
+                    }
+                }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let copy_started_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Copy started"
+                    }
+                }
+                div(class = "form-check") {
+                    label(class = "form-check-label") {
+                        let copy_completed_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Copied to destination"
+                    }
+                }
+                div(class = "form-check mb-3") {
+                    label(class = "form-check-label") {
+                        let copy_failed_input = input(
+                            class = "form-check-input",
+                            type = "checkbox",
+                            checked = "",
+                        ){}
+                        "Copy failed"
+                    }
+                }
+                div(class = "d-flex align-items-center gap-2") {
+                    div(on:click = on_click_test) {
+                        {&test_button}
+                    }
+                    span(class = "small") { let result_text = "" }
+                }
+            }
+        }
+        Self {
+            wrapper,
+            url_input,
+            secret_input,
+            download_complete_input,
+            copy_started_input,
+            copy_completed_input,
+            copy_failed_input,
+            test_button,
+            on_click_test,
+            result_text,
+        }
+    }
+}
+
+impl<V: View> WebhookSection<V> {
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn is_checked(el: &V::Element) -> bool {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or(true)
+    }
+
+    /// The configured webhook, `None` if the URL is blank.
+    pub fn read(&self) -> Option<WebhookConfig> {
+        let url = Self::value(&self.url_input).trim().to_string();
+        if url.is_empty() {
+            return None;
+        }
+        let secret = Self::value(&self.secret_input);
+        Some(WebhookConfig {
+            url,
+            events: WebhookEvents {
+                download_complete: Self::is_checked(&self.download_complete_input),
+                copy_started: Self::is_checked(&self.copy_started_input),
+                copy_completed: Self::is_checked(&self.copy_completed_input),
+                copy_failed: Self::is_checked(&self.copy_failed_input),
+            },
+            secret: if secret.is_empty() {
+                None
+            } else {
+                Some(secret)
+            },
+        })
+    }
+
+    fn set_checked(el: &V::Element, checked: bool) {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(checked));
+    }
+
+    pub fn set(&self, config: Option<&WebhookConfig>) {
+        let url = config.map(|c| c.url.as_str()).unwrap_or_default();
+        self.url_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(url));
+        let secret = config.and_then(|c| c.secret.as_deref()).unwrap_or_default();
+        self.secret_input
+            .dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(secret));
+        let events = config.map(|c| c.events.clone()).unwrap_or_default();
+        Self::set_checked(&self.download_complete_input, events.download_complete);
+        Self::set_checked(&self.copy_started_input, events.copy_started);
+        Self::set_checked(&self.copy_completed_input, events.copy_completed);
+        Self::set_checked(&self.copy_failed_input, events.copy_failed);
+    }
+
+    /// Wait for a click on "Send test webhook".
+    pub async fn test_clicked(&self) {
+        self.on_click_test.next().await;
+    }
+
+    /// Send a test event to the saved webhook and show the HTTP status.
+    pub async fn test(&mut self) {
+        self.test_button.start_spinner();
+        self.test_button.disable();
+        self.result_text.set_text("");
+        match send_test_webhook().await {
+            Ok(message) => self.result_text.set_text(message),
+            Err(e) => self.result_text.set_text(e.message),
+        }
+        self.test_button.stop_spinner();
+        self.test_button.enable();
+    }
+}
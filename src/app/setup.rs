@@ -0,0 +1,630 @@
+//! First-run setup, shown instead of the tabs until the first config is
+//! saved: the Transmission connection, the copy destinations, then a few
+//! defaults.
+use futures_lite::FutureExt;
+use iti::components::alert::Alert;
+use iti::components::button::Button;
+use iti::components::icon::IconGlyph;
+use iti::components::Flavor;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{
+    check_credentials, check_destination_dirs, check_dir, check_host, normalize_dir, parse_port,
+    AppError, Destination, DestinationHealth, SettingsProblem, TransmissionConfig,
+};
+
+use super::settings::{
+    connection_failure, connection_success, get_transmission_config, pick_directory,
+    set_transmission_config, test_transmission_connection,
+};
+use super::{format, invoke};
+
+/// Whether there is no saved config yet.
+pub async fn needs_setup() -> Result<bool, AppError> {
+    #[derive(serde::Serialize)]
+    struct Empty {}
+    invoke::cmd("needs_setup", &Empty {}).await
+}
+
+async fn check_destination(
+    destination: Destination,
+    path: &str,
+) -> Result<DestinationHealth, AppError> {
+    #[derive(serde::Serialize)]
+    struct Args<'a> {
+        destination: Destination,
+        path: &'a str,
+    }
+    invoke::cmd("check_destination", &Args { destination, path }).await
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SetupStep {
+    Connection,
+    Destinations,
+    Defaults,
+}
+
+impl SetupStep {
+    const ALL: [SetupStep; 3] = [
+        SetupStep::Connection,
+        SetupStep::Destinations,
+        SetupStep::Defaults,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|step| *step == self).unwrap_or(0)
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SetupStep::Connection => "Connect to Transmission",
+            SetupStep::Destinations => "Choose where downloads are copied",
+            SetupStep::Defaults => "A few defaults",
+        }
+    }
+}
+
+enum SetupAction {
+    Skip,
+    Back,
+    Next,
+    Test,
+    Browse(Destination),
+    DirChanged(Destination),
+}
+
+/// What a chosen directory looks like, under its input.
+struct DirStatus<V: View> {
+    wrapper: V::Element,
+    text: V::Text,
+}
+
+impl<V: View> DirStatus<V> {
+    fn show(&self, health: &DestinationHealth) {
+        let text = match &health.path {
+            None => {
+                self.hide();
+                return;
+            }
+            Some(_) if !health.reachable => "This directory doesn't exist.".to_string(),
+            Some(_) if !health.writable => "Privateer can't write to this directory.".to_string(),
+            Some(_) => match health.free_bytes {
+                Some(free) => format!("Writable, {} free.", format::size(free)),
+                None => "Writable.".to_string(),
+            },
+        };
+        self.text.set_text(text);
+        self.wrapper.set_property(
+            "class",
+            if health.is_ok() {
+                "form-text text-success"
+            } else {
+                "form-text text-warning"
+            },
+        );
+        self.wrapper.remove_style("display");
+    }
+
+    fn hide(&self) {
+        self.wrapper.set_style("display", "none");
+    }
+}
+
+/// A guided setup for a new install, in three steps.  It can be skipped,
+/// which saves the default config so it isn't shown again.
+#[derive(ViewChild)]
+pub struct SetupWizard<V: View> {
+    #[child]
+    wrapper: V::Element,
+    step: SetupStep,
+    step_text: V::Text,
+    title_text: V::Text,
+    pages: [V::Element; 3],
+    host_input: V::Element,
+    port_input: V::Element,
+    username_input: V::Element,
+    password_input: V::Element,
+    test_button: Button<V>,
+    on_click_test: V::EventListener,
+    movies_dir_input: V::Element,
+    shows_dir_input: V::Element,
+    on_change_movies: V::EventListener,
+    on_change_shows: V::EventListener,
+    on_click_browse_movies: V::EventListener,
+    on_click_browse_shows: V::EventListener,
+    movies_status: DirStatus<V>,
+    shows_status: DirStatus<V>,
+    magnet_destination_select: V::Element,
+    close_to_tray_input: V::Element,
+    check_updates_input: V::Element,
+    status_alert: Alert<V>,
+    back_button: V::Element,
+    next_text: V::Text,
+    on_click_skip: V::EventListener,
+    on_click_back: V::EventListener,
+    on_click_next: V::EventListener,
+    /// The config the wizard's answers are written into.
+    config: TransmissionConfig,
+}
+
+impl<V: View> Default for SetupWizard<V> {
+    fn default() -> Self {
+        let status_alert = Alert::new("", Flavor::Info);
+        status_alert.set_is_visible(false);
+        let mut test_button = Button::new("Test Connection", Some(Flavor::Secondary));
+        test_button.get_icon_mut().set_glyph(IconGlyph::Globe);
+
+        rsx! {
+            let wrapper = div(
+                class = "container py-4",
+                style:max_width = "640px",
+                style:display = "none",
+            ) {
+                p(class = "text-muted small mb-1") { let step_text = "Step 1 of 3" }
+                h4(class = "mb-3") { let title_text = "Connect to Transmission" }
+                let connection_page = div() {
+                    p(class = "form-text mt-0") {
+                        "Privateer adds downloads to a Transmission daemon over its \
+                         remote access, which is turned on in Transmission's \
+                         Preferences \u{203a} Remote."
+                    }
+                    div(class = "row mb-3") {
+                        div(class = "col-8") {
+                            label(class = "form-label") { "Host" }
+                            let host_input = input(
+                                class = "form-control",
+                                type = "text",
+                                placeholder = "localhost",
+                            ){}
+                        }
+                        div(class = "col-4") {
+                            label(class = "form-label") { "Port" }
+                            let port_input = input(
+                                class = "form-control",
+                                type = "number",
+                                placeholder = "9091",
+                            ){}
+                        }
+                    }
+                    div(class = "row mb-3") {
+                        div(class = "col") {
+                            label(class = "form-label") { "Username" }
+                            let username_input = input(
+                                class = "form-control",
+                                type = "text",
+                                placeholder = "Leave blank if no auth",
+                            ){}
+                        }
+                        div(class = "col") {
+                            label(class = "form-label") { "Password" }
+                            let password_input = input(
+                                class = "form-control",
+                                type = "password",
+                                placeholder = "Leave blank if no auth",
+                            ){}
+                        }
+                    }
+                    div(on:click = on_click_test) { {&test_button} }
+                }
+                let destinations_page = div(style:display = "none") {
+                    p(class = "form-text mt-0") {
+                        "Completed downloads are copied to these directories, where a \
+                         media server can find them. Leave one blank to not copy \
+                         there."
+                    }
+                    div(class = "mb-3") {
+                        label(class = "form-label") { "Movies Directory" }
+                        div(class = "input-group") {
+                            let movies_dir_input = input(
+                                class = "form-control",
+                                type = "text",
+                                placeholder = "/Volumes/Media/Movies",
+                                on:change = on_change_movies,
+                            ){}
+                            button(
+                                class = "btn btn-outline-secondary",
+                                type = "button",
+                                on:click = on_click_browse_movies,
+                            ) { "Browse\u{2026}" }
+                        }
+                        let movies_status_wrapper = div(style:display = "none") {
+                            let movies_status_text = ""
+                        }
+                    }
+                    div(class = "mb-3") {
+                        label(class = "form-label") { "Shows Directory" }
+                        div(class = "input-group") {
+                            let shows_dir_input = input(
+                                class = "form-control",
+                                type = "text",
+                                placeholder = "/Volumes/Media/TV Shows",
+                                on:change = on_change_shows,
+                            ){}
+                            button(
+                                class = "btn btn-outline-secondary",
+                                type = "button",
+                                on:click = on_click_browse_shows,
+                            ) { "Browse\u{2026}" }
+                        }
+                        let shows_status_wrapper = div(style:display = "none") {
+                            let shows_status_text = ""
+                        }
+                    }
+                }
+                let defaults_page = div(style:display = "none") {
+                    div(class = "mb-3") {
+                        label(class = "form-label") { "Magnet Links Opened in Privateer" }
+                        let magnet_destination_select = select(class = "form-select") {
+                            option(value = "", selected = "") { "Ask for a destination" }
+                            option(value = "Movies") { "Add to Movies" }
+                            option(value = "Shows") { "Add to Shows" }
+                        }
+                    }
+                    div(class = "form-check") {
+                        label(class = "form-check-label") {
+                            let close_to_tray_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                                checked = "",
+                            ){}
+                            "Keep running in the tray when the window is closed"
+                        }
+                    }
+                    div(class = "form-check mb-3") {
+                        label(class = "form-check-label") {
+                            let check_updates_input = input(
+                                class = "form-check-input",
+                                type = "checkbox",
+                            ){}
+                            "Look for a newer release when Settings first opens"
+                        }
+                    }
+                    p(class = "form-text") {
+                        "Everything here, and more, can be changed later in Settings."
+                    }
+                }
+                div(class = "mt-3") { {&status_alert} }
+                div(class = "d-flex gap-2 mt-4") {
+                    button(
+                        class = "btn btn-link text-muted me-auto ps-0",
+                        type = "button",
+                        on:click = on_click_skip,
+                    ) { "Skip setup" }
+                    let back_button = button(
+                        class = "btn btn-outline-secondary",
+                        type = "button",
+                        style:display = "none",
+                        on:click = on_click_back,
+                    ) { "Back" }
+                    button(
+                        class = "btn btn-primary",
+                        type = "button",
+                        on:click = on_click_next,
+                    ) { let next_text = "Next" }
+                }
+            }
+        }
+
+        Self {
+            wrapper,
+            step: SetupStep::Connection,
+            step_text,
+            title_text,
+            pages: [connection_page, destinations_page, defaults_page],
+            host_input,
+            port_input,
+            username_input,
+            password_input,
+            test_button,
+            on_click_test,
+            movies_dir_input,
+            shows_dir_input,
+            on_change_movies,
+            on_change_shows,
+            on_click_browse_movies,
+            on_click_browse_shows,
+            movies_status: DirStatus {
+                wrapper: movies_status_wrapper,
+                text: movies_status_text,
+            },
+            shows_status: DirStatus {
+                wrapper: shows_status_wrapper,
+                text: shows_status_text,
+            },
+            magnet_destination_select,
+            close_to_tray_input,
+            check_updates_input,
+            status_alert,
+            back_button,
+            next_text,
+            on_click_skip,
+            on_click_back,
+            on_click_next,
+            config: TransmissionConfig::default(),
+        }
+    }
+}
+
+impl<V: View> SetupWizard<V> {
+    fn value(el: &V::Element) -> String {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.value())
+            .unwrap_or_default()
+    }
+
+    fn is_checked(el: &V::Element) -> bool {
+        el.dyn_el(|input: &web_sys::HtmlInputElement| input.checked())
+            .unwrap_or_default()
+    }
+
+    fn dir_input(&self, destination: Destination) -> &V::Element {
+        match destination {
+            Destination::Movies => &self.movies_dir_input,
+            Destination::Shows => &self.shows_dir_input,
+        }
+    }
+
+    fn dir_status(&self, destination: Destination) -> &DirStatus<V> {
+        match destination {
+            Destination::Movies => &self.movies_status,
+            Destination::Shows => &self.shows_status,
+        }
+    }
+
+    fn show_step(&mut self, step: SetupStep) {
+        self.step = step;
+        let index = step.index();
+        for (i, page) in self.pages.iter().enumerate() {
+            if i == index {
+                page.remove_style("display");
+            } else {
+                page.set_style("display", "none");
+            }
+        }
+        self.step_text
+            .set_text(format!("Step {} of {}", index + 1, SetupStep::ALL.len()));
+        self.title_text.set_text(step.title());
+        if step == SetupStep::Connection {
+            self.back_button.set_style("display", "none");
+        } else {
+            self.back_button.remove_style("display");
+        }
+        self.next_text.set_text(if step == SetupStep::Defaults {
+            "Finish"
+        } else {
+            "Next"
+        });
+        self.status_alert.set_is_visible(false);
+    }
+
+    fn show_problem(&self, message: impl Into<String>) {
+        self.status_alert.set_text(message.into());
+        self.status_alert.set_flavor(Flavor::Danger);
+        self.status_alert.set_is_visible(true);
+    }
+
+    /// Fill the form from the config as loaded.
+    fn set_config_values(&self) {
+        let profile = self.config.active();
+        let set_value = |el: &V::Element, value: &str| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(value));
+        };
+        set_value(&self.host_input, &profile.host);
+        set_value(&self.port_input, &profile.port.to_string());
+        set_value(
+            &self.username_input,
+            profile.username.as_deref().unwrap_or(""),
+        );
+        set_value(
+            &self.movies_dir_input,
+            self.config.movies_dir.as_deref().unwrap_or(""),
+        );
+        set_value(
+            &self.shows_dir_input,
+            self.config.shows_dir.as_deref().unwrap_or(""),
+        );
+        let set_checked = |el: &V::Element, checked: bool| {
+            el.dyn_el(|input: &web_sys::HtmlInputElement| input.set_checked(checked));
+        };
+        set_checked(&self.close_to_tray_input, self.config.close_to_tray);
+        set_checked(&self.check_updates_input, self.config.check_for_updates);
+    }
+
+    /// The config with the connection from the first step.
+    fn with_connection(&self) -> Result<TransmissionConfig, SettingsProblem> {
+        let host = Self::value(&self.host_input).trim().to_string();
+        check_host(&host)?;
+        let port = parse_port(&Self::value(&self.port_input))?;
+        let username = Self::value(&self.username_input);
+        let password = Self::value(&self.password_input);
+        check_credentials(&username, &password)?;
+        let mut config = self.config.clone();
+        let profile = config
+            .profiles
+            .get_mut(config.active_profile)
+            .ok_or(SettingsProblem::NoSuchProfile)?;
+        let non_empty = |value: String| (!value.is_empty()).then_some(value);
+        profile.host = host;
+        profile.port = port;
+        profile.username = non_empty(username);
+        profile.password = non_empty(password);
+        Ok(config)
+    }
+
+    /// A destination's directory, `None` if it was left blank.
+    fn dir(&self, destination: Destination) -> Result<Option<String>, SettingsProblem> {
+        let dir = normalize_dir(&Self::value(self.dir_input(destination)));
+        check_dir(&dir)?;
+        Ok(Some(dir).filter(|dir| !dir.is_empty()))
+    }
+
+    /// Check the directories of the second step.  Relative paths and the
+    /// same directory for both are refused; one inside the other is not.
+    fn check_dirs(&self) -> Result<(), SettingsProblem> {
+        let movies = self.dir(Destination::Movies)?.unwrap_or_default();
+        let shows = self.dir(Destination::Shows)?.unwrap_or_default();
+        match check_destination_dirs(&movies, &shows) {
+            Err(problem) if problem.is_error() => Err(problem),
+            _ => Ok(()),
+        }
+    }
+
+    /// The config with every step's answers.
+    fn read_config(&self) -> Result<TransmissionConfig, SettingsProblem> {
+        let mut config = self.with_connection()?;
+        config.movies_dir = self.dir(Destination::Movies)?;
+        config.shows_dir = self.dir(Destination::Shows)?;
+        config.magnet_destination = self
+            .magnet_destination_select
+            .dyn_el(|select: &web_sys::HtmlSelectElement| select.value())
+            .and_then(|value| {
+                Destination::ALL
+                    .into_iter()
+                    .find(|dest| dest.label() == value)
+            });
+        config.close_to_tray = Self::is_checked(&self.close_to_tray_input);
+        config.check_for_updates = Self::is_checked(&self.check_updates_input);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check whether the chosen directory can be copied to.
+    async fn check_dir(&self, destination: Destination) {
+        let status = self.dir_status(destination);
+        let path = Self::value(self.dir_input(destination));
+        if path.trim().is_empty() {
+            status.hide();
+            return;
+        }
+        match check_destination(destination, &path).await {
+            Ok(health) => status.show(&health),
+            Err(e) => {
+                log::error!("Could not check the {destination} directory: {e}");
+                status.hide();
+            }
+        }
+    }
+
+    async fn save(&self, config: &TransmissionConfig) -> bool {
+        match set_transmission_config(config).await {
+            Ok(()) => true,
+            Err(e) => {
+                self.show_problem(format!("Failed to save: {e}"));
+                false
+            }
+        }
+    }
+
+    async fn next_action(&self) -> SetupAction {
+        self.on_click_skip
+            .next()
+            .map(|_| SetupAction::Skip)
+            .or(self.on_click_back.next().map(|_| SetupAction::Back))
+            .or(self.on_click_next.next().map(|_| SetupAction::Next))
+            .or(self.on_click_test.next().map(|_| SetupAction::Test))
+            .or(self
+                .on_click_browse_movies
+                .next()
+                .map(|_| SetupAction::Browse(Destination::Movies)))
+            .or(self
+                .on_click_browse_shows
+                .next()
+                .map(|_| SetupAction::Browse(Destination::Shows)))
+            .or(self
+                .on_change_movies
+                .next()
+                .map(|_| SetupAction::DirChanged(Destination::Movies)))
+            .or(self
+                .on_change_shows
+                .next()
+                .map(|_| SetupAction::DirChanged(Destination::Shows)))
+            .await
+    }
+
+    /// Show the wizard and walk through it, returning once a config has
+    /// been saved, either finished or skipped.
+    pub async fn run(&mut self) {
+        match get_transmission_config().await {
+            Ok(config) => self.config = config,
+            Err(e) => log::error!("Could not load the default config: {e}"),
+        }
+        self.set_config_values();
+        self.show_step(SetupStep::Connection);
+        self.wrapper.remove_style("display");
+
+        loop {
+            let action = self.next_action().await;
+            match action {
+                SetupAction::Skip => {
+                    if self.save(&self.config).await {
+                        break;
+                    }
+                }
+                SetupAction::Back => {
+                    let previous = SetupStep::ALL[self.step.index().saturating_sub(1)];
+                    self.show_step(previous);
+                }
+                SetupAction::Next => match self.step {
+                    SetupStep::Connection => match self.with_connection() {
+                        Ok(_) => self.show_step(SetupStep::Destinations),
+                        Err(problem) => self.show_problem(problem.message()),
+                    },
+                    SetupStep::Destinations => match self.check_dirs() {
+                        Ok(()) => self.show_step(SetupStep::Defaults),
+                        Err(problem) => self.show_problem(problem.message()),
+                    },
+                    SetupStep::Defaults => match self.read_config() {
+                        Ok(config) => {
+                            if self.save(&config).await {
+                                break;
+                            }
+                        }
+                        Err(problem) => self.show_problem(problem.message()),
+                    },
+                },
+                SetupAction::Test => {
+                    let config = match self.with_connection() {
+                        Ok(config) => config,
+                        Err(problem) => {
+                            self.show_problem(problem.message());
+                            continue;
+                        }
+                    };
+                    self.test_button.start_spinner();
+                    self.test_button.disable();
+                    // The backend tests the saved connection, so this saves
+                    // a config, and setup won't be shown again
+                    if self.save(&config).await {
+                        // Skipping now keeps the connection
+                        self.config = config;
+                        match test_transmission_connection().await {
+                            Ok(daemon) => {
+                                let (msg, flavor) = connection_success(&daemon);
+                                self.status_alert.set_text(msg);
+                                self.status_alert.set_flavor(flavor);
+                                self.status_alert.set_is_visible(true);
+                            }
+                            Err(e) => self.show_problem(connection_failure(&e)),
+                        }
+                    }
+                    self.test_button.stop_spinner();
+                    self.test_button.enable();
+                }
+                SetupAction::Browse(destination) => {
+                    let input = self.dir_input(destination);
+                    match pick_directory(&Self::value(input)).await {
+                        Ok(Some(dir)) => {
+                            input.dyn_el(|input: &web_sys::HtmlInputElement| input.set_value(&dir));
+                            self.check_dir(destination).await;
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            self.show_problem(format!("Could not open the folder picker: {e}"))
+                        }
+                    }
+                }
+                SetupAction::DirChanged(destination) => self.check_dir(destination).await,
+            }
+        }
+        self.wrapper.set_style("display", "none");
+    }
+}
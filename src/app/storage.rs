@@ -0,0 +1,36 @@
+//! localStorage, where the frontend keeps what it remembers across
+//! restarts, e.g. the theme and the layout of the Downloads view.
+//!
+//! Only the browser has it: outside of it nothing is ever found and nothing
+//! is kept.  Values are stored as JSON.
+use mogwai::web::prelude::*;
+
+/// localStorage, `None` outside the browser or if it can't be had.
+pub fn local<V: View>() -> Option<web_sys::Storage> {
+    if !V::is_view::<Web>() {
+        return None;
+    }
+    mogwai::web::window().local_storage().ok().flatten()
+}
+
+/// The value stored under `key`, `None` if there is none or it isn't a `T`.
+pub fn get<V: View, T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+    let s = local::<V>()?.get_item(key).ok().flatten()?;
+    serde_json::from_str(&s).ok()
+}
+
+/// Keep `value` under `key`.
+pub fn set<V: View, T: serde::Serialize + ?Sized>(key: &str, value: &T) {
+    if let Some(storage) = local::<V>() {
+        if let Ok(s) = serde_json::to_string(value) {
+            let _ = storage.set_item(key, &s);
+        }
+    }
+}
+
+/// Forget whatever is stored under `key`.
+pub fn remove<V: View>(key: &str) {
+    if let Some(storage) = local::<V>() {
+        let _ = storage.remove_item(key);
+    }
+}
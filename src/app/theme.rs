@@ -0,0 +1,84 @@
+//! Light and dark themes, applied with Bootstrap's `data-bs-theme` attribute
+//! on the document.
+//!
+//! The choice is kept in localStorage so it applies before anything is
+//! fetched from the backend.
+use mogwai::web::prelude::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use super::storage;
+
+/// localStorage key for the chosen theme.
+const STORAGE_KEY: &str = "theme";
+
+/// Media query matching an OS in dark mode.
+const PREFERS_DARK: &str = "(prefers-color-scheme: dark)";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Theme {
+    /// Follow the OS, including when it changes.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+
+    /// The chosen theme, or [`Theme::System`] if none was.
+    pub fn load() -> Self {
+        storage::get::<Web, _>(STORAGE_KEY).unwrap_or_default()
+    }
+
+    /// Remember the theme and apply it.
+    pub fn store(self) {
+        storage::set::<Web, _>(STORAGE_KEY, &self);
+        self.apply();
+    }
+
+    /// Set `data-bs-theme` to "light" or "dark".
+    fn apply(self) {
+        let is_dark = match self {
+            Theme::System => prefers_dark(),
+            Theme::Light => false,
+            Theme::Dark => true,
+        };
+        if let Some(root) = mogwai::web::document().document_element() {
+            let _ = root.set_attribute("data-bs-theme", if is_dark { "dark" } else { "light" });
+        }
+    }
+}
+
+fn prefers_dark() -> bool {
+    mogwai::web::window()
+        .match_media(PREFERS_DARK)
+        .ok()
+        .flatten()
+        .is_some_and(|query| query.matches())
+}
+
+/// Apply the chosen theme, and apply it again whenever the OS switches
+/// between light and dark, which matters while following the OS.
+pub fn init() {
+    Theme::load().apply();
+    let Some(query) = mogwai::web::window()
+        .match_media(PREFERS_DARK)
+        .ok()
+        .flatten()
+    else {
+        return;
+    };
+    let on_change = Closure::<dyn Fn()>::new(|| Theme::load().apply());
+    let _ = query.add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref());
+    // Listens for the lifetime of the page
+    on_change.forget();
+}
@@ -0,0 +1,106 @@
+//! Messages from the backend, popped up in the corner of the window.
+use futures_lite::FutureExt;
+use iti::components::alert::Alert;
+use iti::components::Flavor;
+use mogwai::{future::MogwaiFutureExt, web::prelude::*};
+use privateer_wire_types::{LogLevel, Toast};
+
+use super::events;
+
+/// Backend event carrying a [`Toast`].
+const TOAST: &str = "toast";
+
+/// How long a toast stays up unless clicked away.
+const SHOW_MILLIS: f64 = 8000.0;
+
+/// The most recent toast, hidden after a few seconds or when clicked.
+pub struct Toasts<V: View> {
+    pub wrapper: V::Element,
+    alert: Alert<V>,
+    on_click_dismiss: V::EventListener,
+    messages: async_channel::Receiver<Toast>,
+    /// When the showing toast hides, in ms since the epoch.
+    hide_at: Option<f64>,
+}
+
+enum ToastEvent {
+    Show(Toast),
+    Hide,
+}
+
+impl<V: View> Toasts<V> {
+    pub fn new() -> Self {
+        let alert = Alert::new("", Flavor::Warning);
+        alert.set_is_visible(false);
+        let messages = if V::is_view::<Web>() {
+            events::subscribe(TOAST)
+        } else {
+            async_channel::bounded(1).1
+        };
+        rsx! {
+            let wrapper = div(
+                class = "position-fixed bottom-0 end-0 p-3",
+                style:z_index = "1100",
+                style:max_width = "28rem",
+                style:cursor = "pointer",
+                title = "Click to dismiss",
+                on:click = on_click_dismiss,
+            ) {
+                {&alert}
+            }
+        }
+        Self {
+            wrapper,
+            alert,
+            on_click_dismiss,
+            messages,
+            hide_at: None,
+        }
+    }
+
+    /// Show the next toast, or hide the current one when it's clicked or
+    /// has been up long enough.
+    pub async fn step(&mut self) {
+        let hide_at = self.hide_at;
+        let timeout = async {
+            match hide_at {
+                Some(at) => {
+                    let remaining = (at - web_sys::js_sys::Date::now()).max(0.0);
+                    mogwai::time::wait_millis(remaining as _).await;
+                    ToastEvent::Hide
+                }
+                None => std::future::pending().await,
+            }
+        };
+        let messages = &self.messages;
+        let next = async {
+            match messages.recv().await {
+                Ok(toast) => ToastEvent::Show(toast),
+                // Never closes on the web; elsewhere there are no events
+                Err(_) => std::future::pending().await,
+            }
+        };
+        let event = next
+            .or(self.on_click_dismiss.next().map(|_| ToastEvent::Hide))
+            .or(timeout)
+            .await;
+
+        match event {
+            ToastEvent::Show(toast) => {
+                let flavor = match toast.level {
+                    LogLevel::Error => Flavor::Danger,
+                    LogLevel::Warn => Flavor::Warning,
+                    LogLevel::Info | LogLevel::Debug | LogLevel::Trace => Flavor::Info,
+                };
+                self.alert.set_flavor(flavor);
+                self.alert.set_text(toast.message);
+                self.alert.set_is_visible(true);
+                self.hide_at = Some(web_sys::js_sys::Date::now() + SHOW_MILLIS);
+            }
+            ToastEvent::Hide => {
+                self.alert.set_is_visible(false);
+                self.hide_at = None;
+            }
+        }
+    }
+}
@@ -16,7 +16,9 @@ use iti::components::badge::Badge;
 use iti::components::card::Card;
 use iti::components::Flavor;
 use mogwai::web::prelude::*;
-use privateer_wire_types::{Destination, Torrent, WatchlistEntry};
+use privateer_wire_types::{Destination, LedgerQuery, Torrent, WatchlistEntry};
+
+use super::pending::{self, PendingAction};
 
 // ---------------------------------------------------------------------------
 // Episode parsing
@@ -359,6 +361,8 @@ pub struct WatchingView<V: View> {
     entries: Vec<WatchlistEntry>,
     selected_destination: Destination,
     loaded: bool,
+    /// Told when a removal has gone through, after its countdown.
+    removals: (async_channel::Sender<()>, async_channel::Receiver<()>),
 }
 
 impl<V: View> Default for WatchingView<V> {
@@ -449,6 +453,7 @@ impl<V: View> Default for WatchingView<V> {
             entries: Vec::new(),
             selected_destination: Destination::Movies,
             loaded: false,
+            removals: async_channel::unbounded(),
         }
     }
 }
@@ -549,8 +554,8 @@ impl<V: View> WatchingView<V> {
 
     /// Auto-remove movies that appear in the downloads ledger.
     async fn auto_remove_movies(&mut self) {
-        let ledger = match super::get_downloads_ledger().await {
-            Ok(l) => l,
+        let ledger = match super::get_downloads_ledger(&LedgerQuery::default()).await {
+            Ok(page) => page.entries,
             Err(_) => return,
         };
 
@@ -564,7 +569,9 @@ impl<V: View> WatchingView<V> {
                 .iter()
                 .any(|d| d.name.to_lowercase().contains(&title_lower))
             {
-                let _ = super::remove_from_watchlist(entry.id).await;
+                // Tidying up after a download, not the user's doing, so
+                // there is nothing to undo
+                let _ = super::remove_from_watchlist(entry.id, true).await;
                 removed = true;
             }
         }
@@ -603,6 +610,8 @@ impl<V: View> WatchingView<V> {
             DestMovies,
             DestShows,
             Remove(u64),
+            /// A removal went through.
+            Removed,
             Search(String),
         }
 
@@ -702,7 +711,13 @@ impl<V: View> WatchingView<V> {
             }
         };
 
+        let removed = async {
+            let _ = self.removals.1.recv().await;
+            Event::Removed
+        };
+
         let event = timeout
+            .or(removed)
             .or(add_click)
             .or(add_submit)
             .or(cancel)
@@ -751,14 +766,21 @@ impl<V: View> WatchingView<V> {
                 None
             }
             Event::Remove(id) => {
-                match super::remove_from_watchlist(id).await {
-                    Ok(()) => {
-                        self.reload().await;
-                    }
-                    Err(e) => {
-                        log::error!("Failed to remove from watchlist: {e}");
-                    }
-                }
+                let title = self
+                    .entries
+                    .iter()
+                    .find(|e| e.id == id)
+                    .map(|e| e.title.clone())
+                    .unwrap_or_default();
+                let action = PendingAction::new(
+                    format!("Removing '{title}' from the watchlist"),
+                    move |confirm| super::remove_from_watchlist(id, confirm),
+                );
+                pending::enqueue(action.notify(&self.removals.0));
+                None
+            }
+            Event::Removed => {
+                self.reload().await;
                 None
             }
             Event::Search(query) => Some(query),
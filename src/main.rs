@@ -30,6 +30,9 @@ fn main() {
         }
     }
 
+    theme::init();
+    layout::init();
+
     let mut app = App::<Web>::default();
     let body = mogwai::web::body();
     body.set_attribute("class", "system-9")